@@ -105,7 +105,10 @@ impl ZellijPlugin for State {
             } else if t.active {
                 active_tab_index = t.position;
                 is_swap_layout_dirty = t.is_swap_layout_dirty;
-                active_swap_layout_name = t.active_swap_layout_name.clone();
+                active_swap_layout_name = t
+                    .active_swap_layout_label
+                    .clone()
+                    .or_else(|| t.active_swap_layout_name.clone());
             }
             let tab = tab_style(
                 tabname,
@@ -238,7 +238,10 @@ fn swap_layout_keycode(mode_info: &ModeInfo, palette: &Palette) -> LinePart {
     let mode_keybinds = mode_info.get_mode_keybinds();
     let prev_next_keys = action_key_group(
         &mode_keybinds,
-        &[&[Action::PreviousSwapLayout], &[Action::NextSwapLayout]],
+        &[
+            &[Action::PreviousSwapLayout(None)],
+            &[Action::NextSwapLayout(None)],
+        ],
     );
     let prev_next_keys_indicator =
         style_key_with_modifier(&prev_next_keys, palette, Some(palette.black));
@@ -542,7 +545,10 @@ pub fn first_line(
             let mut remaining_space = max_len - key_indicators.len;
             if let Some(swap_layout_status) = swap_layout_status(
                 remaining_space,
-                &tab_info.active_swap_layout_name,
+                &tab_info
+                    .active_swap_layout_label
+                    .clone()
+                    .or_else(|| tab_info.active_swap_layout_name.clone()),
                 tab_info.is_swap_layout_dirty,
                 help,
                 colored_elements,
@@ -15,9 +15,9 @@ use zellij_tile_utils::{palette_match, style};
 
 use first_line::first_line;
 use second_line::{
-    floating_panes_are_visible, fullscreen_panes_to_hide, keybinds,
-    locked_floating_panes_are_visible, locked_fullscreen_panes_to_hide, system_clipboard_error,
-    text_copied_hint,
+    deep_stack_panes_hidden, fixed_panes_block_resize, floating_panes_are_visible,
+    fullscreen_panes_to_hide, input_to_read_only_pane, keybinds, locked_floating_panes_are_visible,
+    locked_fullscreen_panes_to_hide, system_clipboard_error, text_copied_hint,
 };
 use tip::utils::get_cached_tip_name;
 
@@ -34,6 +34,8 @@ struct State {
     mode_info: ModeInfo,
     text_copy_destination: Option<CopyDestination>,
     display_system_clipboard_failure: bool,
+    display_cant_resize_fixed_panes: bool,
+    display_input_to_read_only_pane: bool,
 }
 
 register_plugin!(State);
@@ -186,6 +188,8 @@ impl ZellijPlugin for State {
             EventType::CopyToClipboard,
             EventType::InputReceived,
             EventType::SystemClipboardFailure,
+            EventType::CantResizeFixedPanes,
+            EventType::InputToReadOnlyPane,
         ]);
     }
 
@@ -221,14 +225,26 @@ impl ZellijPlugin for State {
                 should_render = true;
                 self.display_system_clipboard_failure = true;
             },
+            Event::CantResizeFixedPanes => {
+                should_render = true;
+                self.display_cant_resize_fixed_panes = true;
+            },
+            Event::InputToReadOnlyPane => {
+                should_render = !self.display_input_to_read_only_pane;
+                self.display_input_to_read_only_pane = true;
+            },
             Event::InputReceived => {
                 if self.text_copy_destination.is_some()
                     || self.display_system_clipboard_failure == true
+                    || self.display_cant_resize_fixed_panes
+                    || self.display_input_to_read_only_pane
                 {
                     should_render = true;
                 }
                 self.text_copy_destination = None;
                 self.display_system_clipboard_failure = false;
+                self.display_cant_resize_fixed_panes = false;
+                self.display_input_to_read_only_pane = false;
             },
             _ => {},
         };
@@ -293,6 +309,10 @@ impl State {
             text_copied_hint(&self.mode_info.style.colors, copy_destination)
         } else if self.display_system_clipboard_failure {
             system_clipboard_error(&self.mode_info.style.colors)
+        } else if self.display_cant_resize_fixed_panes {
+            fixed_panes_block_resize(&self.mode_info.style.colors)
+        } else if self.display_input_to_read_only_pane {
+            input_to_read_only_pane(&self.mode_info.style.colors)
         } else if let Some(active_tab) = active_tab {
             if active_tab.is_fullscreen_active {
                 match self.mode_info.mode {
@@ -314,6 +334,14 @@ impl State {
                     },
                     _ => keybinds(&self.mode_info, &self.tip_name, cols),
                 }
+            } else if active_tab.panes_hidden_in_deep_stacks > 0 {
+                match self.mode_info.mode {
+                    InputMode::Normal | InputMode::Locked => deep_stack_panes_hidden(
+                        &self.mode_info.style.colors,
+                        active_tab.panes_hidden_in_deep_stacks,
+                    ),
+                    _ => keybinds(&self.mode_info, &self.tip_name, cols),
+                }
             } else {
                 keybinds(&self.mode_info, &self.tip_name, cols)
             }
@@ -179,7 +179,7 @@ fn get_keys_and_hints(mi: &ModeInfo) -> Vec<(String, String, Vec<Key>)> {
 
         vec![
         (s("Move focus"), s("Move"), focus_keys),
-        (s("New"), s("New"), action_key(&km, &[A::NewTab(None, vec![], None, None, None), TO_NORMAL])),
+        (s("New"), s("New"), action_key(&km, &[A::NewTab(None, vec![], None, None, None, None), TO_NORMAL])),
         (s("Close"), s("Close"), action_key(&km, &[A::CloseTab, TO_NORMAL])),
         (s("Rename"), s("Rename"),
             action_key(&km, &[A::SwitchToMode(IM::RenameTab), A::TabNameInput(vec![0])])),
@@ -254,7 +254,7 @@ fn get_keys_and_hints(mi: &ModeInfo) -> Vec<(String, String, Vec<Key>)> {
         (s("Split down"), s("Down"), action_key(&km, &[A::NewPane(Some(Dir::Down), None), TO_NORMAL])),
         (s("Split right"), s("Right"), action_key(&km, &[A::NewPane(Some(Dir::Right), None), TO_NORMAL])),
         (s("Fullscreen"), s("Fullscreen"), action_key(&km, &[A::ToggleFocusFullscreen, TO_NORMAL])),
-        (s("New tab"), s("New"), action_key(&km, &[A::NewTab(None, vec![], None, None, None), TO_NORMAL])),
+        (s("New tab"), s("New"), action_key(&km, &[A::NewTab(None, vec![], None, None, None, None), TO_NORMAL])),
         (s("Rename tab"), s("Rename"),
             action_key(&km, &[A::SwitchToMode(IM::RenameTab), A::TabNameInput(vec![0])])),
         (s("Previous Tab"), s("Previous"), action_key(&km, &[A::GoToPreviousTab, TO_NORMAL])),
@@ -374,6 +374,24 @@ pub fn system_clipboard_error(palette: &Palette) -> LinePart {
     }
 }
 
+pub fn fixed_panes_block_resize(palette: &Palette) -> LinePart {
+    let hint = " Can't resize: all panes in this direction have a fixed size.";
+    let orange_color = palette_match!(palette.orange);
+    LinePart {
+        part: Style::new().fg(orange_color).bold().paint(hint).to_string(),
+        len: hint.len(),
+    }
+}
+
+pub fn input_to_read_only_pane(palette: &Palette) -> LinePart {
+    let hint = " This pane is read-only, input to it was dropped.";
+    let orange_color = palette_match!(palette.orange);
+    LinePart {
+        part: Style::new().fg(orange_color).bold().paint(hint).to_string(),
+        len: hint.len(),
+    }
+}
+
 pub fn fullscreen_panes_to_hide(palette: &Palette, panes_to_hide: usize) -> LinePart {
     let text_color = palette_match!(match palette.theme_hue {
         ThemeHue::Dark => palette.white,
@@ -406,6 +424,32 @@ pub fn fullscreen_panes_to_hide(palette: &Palette, panes_to_hide: usize) -> Line
     }
 }
 
+pub fn deep_stack_panes_hidden(palette: &Palette, hidden_panes: usize) -> LinePart {
+    let text_color = palette_match!(match palette.theme_hue {
+        ThemeHue::Dark => palette.white,
+        ThemeHue::Light => palette.black,
+    });
+    let green_color = palette_match!(palette.green);
+    let orange_color = palette_match!(palette.orange);
+    let shortcut_left_separator = Style::new().fg(text_color).bold().paint(" (");
+    let shortcut_right_separator = Style::new().fg(text_color).bold().paint("): ");
+    let stack_too_tall = "STACK TOO TALL";
+    let panes = hidden_panes.to_string();
+    let hidden = " panes hidden, move focus up or down the stack to reach them";
+    let len = stack_too_tall.chars().count() + panes.chars().count() + hidden.chars().count() + 5; // 3 for ():'s around shortcut, 2 for the space
+    LinePart {
+        part: format!(
+            "{}{}{}{}{}",
+            shortcut_left_separator,
+            Style::new().fg(orange_color).bold().paint(stack_too_tall),
+            shortcut_right_separator,
+            Style::new().fg(green_color).bold().paint(panes),
+            Style::new().fg(text_color).bold().paint(hidden)
+        ),
+        len,
+    }
+}
+
 pub fn floating_panes_are_visible(mode_info: &ModeInfo) -> LinePart {
     let palette = mode_info.style.colors;
     let km = &mode_info.get_mode_keybinds();
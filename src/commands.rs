@@ -1,11 +1,12 @@
-use dialoguer::Confirm;
+use dialoguer::{Confirm, Select};
 use miette::{Report, Result};
 use std::{fs::File, io::prelude::*, path::PathBuf, process};
 
 use crate::sessions::{
     assert_session, assert_session_ne, get_active_session, get_sessions,
-    get_sessions_sorted_by_mtime, kill_session as kill_session_impl, match_session_name,
-    print_sessions, print_sessions_with_index, session_exists, ActiveSession, SessionNameMatch,
+    get_sessions_sorted_by_mtime, get_sessions_with_layouts, kill_session as kill_session_impl,
+    match_session_name, print_sessions, print_sessions_with_index, session_exists, ActiveSession,
+    SessionNameMatch,
 };
 use zellij_client::{
     old_config_converter::{
@@ -18,9 +19,15 @@ use zellij_server::{os_input_output::get_server_os_input, start_server as start_
 use zellij_utils::{
     cli::{CliArgs, Command, SessionCommand, Sessions},
     envs,
-    input::{actions::Action, config::ConfigError, options::Options},
+    input::{
+        actions::Action,
+        config::{Config, ConfigError},
+        layout::{Layout, LayoutFile, LayoutSessionStart, LayoutSources, ValidationOptions},
+        layout_trust::TrustedLayouts,
+        options::{ConfirmLayoutCommands, Options},
+    },
     nix,
-    setup::Setup,
+    setup::{find_default_config_dir, get_default_data_dir, get_layout_dir, Setup},
 };
 
 pub(crate) use crate::sessions::list_sessions;
@@ -261,6 +268,55 @@ fn attach_with_session_index(config_options: Options, index: usize, create: bool
     }
 }
 
+fn attach_with_layout(
+    layout_name: &str,
+    session_name: Option<String>,
+    config_options: Options,
+    find_or_create: bool,
+) -> ClientInfo {
+    let sessions = match get_sessions() {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("Error occurred: {:?}", e);
+            process::exit(1);
+        },
+    };
+    let mut sessions_with_layout: Vec<String> = get_sessions_with_layouts(sessions)
+        .into_iter()
+        .filter(|(_session, session_info)| {
+            session_info.as_ref().map(|s| s.layout.as_str()) == Some(layout_name)
+        })
+        .map(|(session, _session_info)| session)
+        .collect();
+    if let Some(session_name) = &session_name {
+        sessions_with_layout.retain(|session| session == session_name);
+    }
+    match sessions_with_layout.len() {
+        0 if find_or_create => {
+            let session_name =
+                session_name.unwrap_or_else(|| names::Generator::default().next().unwrap());
+            assert_session_ne(&session_name);
+            ClientInfo::New(session_name)
+        },
+        0 => {
+            eprintln!(
+                "No session started with the layout '{}' found!",
+                layout_name
+            );
+            process::exit(1);
+        },
+        1 => ClientInfo::Attach(sessions_with_layout.remove(0), config_options),
+        _ => {
+            println!(
+                "Ambiguous selection: multiple sessions were started with the layout '{}', please specify the session name:",
+                layout_name
+            );
+            print_sessions(sessions_with_layout);
+            process::exit(1);
+        },
+    }
+}
+
 fn attach_with_session_name(
     session_name: Option<String>,
     config_options: Options,
@@ -307,21 +363,177 @@ fn attach_with_session_name(
     }
 }
 
-pub(crate) fn start_client(opts: CliArgs) {
+fn exit_with_config_error(e: ConfigError) -> ! {
+    if let ConfigError::KdlError(error) = e {
+        let report: Report = error.into();
+        eprintln!("{:?}", report);
+    } else {
+        eprintln!("{}", e);
+    }
+    process::exit(1);
+}
+
+/// The default layout of a new session can depend on its name (see the `default_layouts`
+/// option), so it's only settled once we know what the session will be called
+fn layout_for_new_session(
+    opts: &CliArgs,
+    session_name: &str,
+    config: Config,
+    config_options: Options,
+    layout: (Layout, LayoutSources),
+) -> (Config, Options, (Layout, LayoutSources)) {
+    match Setup::from_cli_args_for_session(opts, &config_options, session_name) {
+        Some(Ok((config, layout, config_options, layout_sources))) => {
+            (config, config_options, (layout, layout_sources))
+        },
+        Some(Err(e)) => exit_with_config_error(e),
+        None => (config, config_options, layout),
+    }
+}
+
+/// Settles the layout of a new session and, if it is not trusted, asks the user before anything
+/// it runs is started
+fn setup_for_new_session(
+    opts: &CliArgs,
+    session_name: &str,
+    config: Config,
+    config_options: Options,
+    layout: (Layout, LayoutSources),
+) -> (Config, Options, (Layout, LayoutSources)) {
+    let (config, config_options, layout) =
+        layout_for_new_session(opts, session_name, config, config_options, layout);
+    let layout = confirm_layout_commands(opts, &config, &config_options, layout);
+    (config, config_options, layout)
+}
+
+/// Lists the commands of a layout that is not trusted (see the `confirm_layout_commands` option)
+/// and asks whether to run them, a layout they are denied to is opened with its command panes
+/// suspended
+fn confirm_layout_commands(
+    opts: &CliArgs,
+    config: &Config,
+    config_options: &Options,
+    (mut layout, layout_sources): (Layout, LayoutSources),
+) -> (Layout, LayoutSources) {
+    let confirm_layout_commands = config_options.confirm_layout_commands.unwrap_or_default();
+    let manifest = layout.manifest_for_review(&config.plugins, &config.env);
+    if opts.trust_layout
+        || confirm_layout_commands == ConfirmLayoutCommands::Never
+        || !manifest.runs_commands()
+    {
+        return (layout, layout_sources);
+    }
+    let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
+    let layout_dir = config_options
+        .layout_dir
+        .clone()
+        .or_else(|| get_layout_dir(opts.config_dir.clone().or_else(find_default_config_dir)));
+    let mut trusted_layouts = TrustedLayouts::load(&data_dir);
+    let untrusted_files: Vec<LayoutFile> = match confirm_layout_commands {
+        ConfirmLayoutCommands::Untrusted => layout_sources
+            .untrusted_files(layout_dir.as_deref(), &trusted_layouts)
+            .into_iter()
+            .cloned()
+            .collect(),
+        _ => layout_sources.files.clone(),
+    };
+    if confirm_layout_commands == ConfirmLayoutCommands::Untrusted && untrusted_files.is_empty() {
+        return (layout, layout_sources);
+    }
+    println!("The layout {} will run:", layout_sources.name);
+    print!("{}", manifest);
+    const RUN: &str = "Run these commands";
+    const RUN_AND_TRUST: &str = "Run these commands and trust this layout from now on";
+    const SUSPEND: &str = "Open the layout with its commands suspended";
+    let mut choices = vec![RUN];
+    if confirm_layout_commands == ConfirmLayoutCommands::Untrusted {
+        choices.push(RUN_AND_TRUST);
+    }
+    choices.push(SUSPEND);
+    let choice = Select::new()
+        .with_prompt("Do you want to run the commands of this layout?")
+        .items(&choices)
+        .default(0)
+        .interact_opt();
+    match choice.map(|choice| choice.map(|choice| choices[choice])) {
+        Ok(Some(RUN)) => {},
+        Ok(Some(RUN_AND_TRUST)) => {
+            for layout_file in &untrusted_files {
+                if let Err(e) = trusted_layouts.remember(&data_dir, &layout_file.content_hash) {
+                    eprintln!("Failed to trust the layout {}: {}", layout_file.path, e);
+                }
+            }
+        },
+        Ok(_) => layout.suspend_commands(),
+        Err(_) => {
+            // eg. not started from a terminal, there is no one to ask
+            eprintln!(
+                "The layout {} is not trusted, its commands are suspended. Use --trust-layout to run them.",
+                layout_sources.name
+            );
+            layout.suspend_commands();
+        },
+    }
+    (layout, layout_sources)
+}
+
+/// Prints what the layout would run and the problems found in it, then exits with an error if
+/// any of them would prevent the session from starting
+fn print_layout_manifest(config: &Config, layout: &Layout, layout_sources: &LayoutSources) -> ! {
+    if !layout_sources.name.is_empty() {
+        println!("layout: {}", layout_sources.name);
+    }
+    println!("loaded in: {}", layout_sources.load_stats);
+    print!("{}", layout.manifest(&config.plugins, &config.env));
+    let diagnostics = layout.validate(ValidationOptions::strict());
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+    if diagnostics.iter().any(|diagnostic| diagnostic.is_error()) {
+        process::exit(1);
+    }
+    process::exit(0);
+}
+
+pub(crate) fn start_client(mut opts: CliArgs) {
     // look for old YAML config/layout/theme files and convert them to KDL
     convert_old_yaml_files(&opts);
-    let (config, layout, config_options) = match Setup::from_cli_args(&opts) {
+    if let Some(Command::Sessions(Sessions::Attach {
+        with_layout: Some(with_layout),
+        ..
+    })) = &opts.command
+    {
+        // this is the layout we look for in the existing sessions, and the one a new session
+        // would be created with
+        opts.layout = vec![with_layout.clone()];
+    }
+    let (config, layout, config_options, layout_sources) = match Setup::from_cli_args(&opts) {
         Ok(results) => results,
-        Err(e) => {
-            if let ConfigError::KdlError(error) = e {
-                let report: Report = error.into();
-                eprintln!("{:?}", report);
-            } else {
-                eprintln!("{}", e);
-            }
-            process::exit(1);
+        // printed rather than opened, so that the user can save it and stop converting it
+        Err(ConfigError::YamlLayout(yaml_layout)) if opts.auto_convert => {
+            convert_old_layout_file(yaml_layout);
+            process::exit(0);
         },
+        Err(e) => exit_with_config_error(e),
     };
+    if opts.dry_run {
+        // a new session can still pick another layout by its name, like it would when started
+        let session_name = opts
+            .session
+            .clone()
+            .or_else(|| config_options.session_name.clone());
+        let (config, _config_options, (layout, layout_sources)) = match session_name {
+            Some(session_name) => layout_for_new_session(
+                &opts,
+                &session_name,
+                config,
+                config_options,
+                (layout, layout_sources),
+            ),
+            None => (config, config_options, (layout, layout_sources)),
+        };
+        print_layout_manifest(&config, &layout, &layout_sources);
+    }
     let os_input = get_os_input(get_client_os_input);
 
     let start_client_plan = |session_name: std::string::String| {
@@ -332,6 +544,8 @@ pub(crate) fn start_client(opts: CliArgs) {
         session_name,
         create,
         index,
+        with_layout,
+        find_or_create,
         options,
     })) = opts.command.clone()
     {
@@ -340,7 +554,14 @@ pub(crate) fn start_client(opts: CliArgs) {
             None => config_options,
         };
 
-        let client = if let Some(idx) = index {
+        let client = if with_layout.is_some() {
+            attach_with_layout(
+                &layout_sources.name,
+                session_name,
+                config_options.clone(),
+                find_or_create,
+            )
+        } else if let Some(idx) = index {
             attach_with_session_index(config_options.clone(), idx, create)
         } else {
             if create {
@@ -356,9 +577,25 @@ pub(crate) fn start_client(opts: CliArgs) {
             }
         }
 
-        let attach_layout = match client {
-            ClientInfo::Attach(_, _) => None,
-            ClientInfo::New(_) => Some(layout),
+        let (config, config_options, attach_layout) = match &client {
+            ClientInfo::Attach(_, _) => (config, config_options, None),
+            ClientInfo::New(session_name) => {
+                let (config, config_options, layout) = setup_for_new_session(
+                    &opts,
+                    session_name,
+                    config,
+                    config_options,
+                    (layout, layout_sources),
+                );
+                // options given to the attach command take precedence over the layout ones
+                let config_options = match options.as_deref() {
+                    Some(SessionCommand::Options(o)) => {
+                        config_options.merge_from_cli(o.to_owned().into())
+                    },
+                    None => config_options,
+                };
+                (config, config_options, Some(layout))
+            },
         };
 
         start_client_impl(
@@ -372,6 +609,13 @@ pub(crate) fn start_client(opts: CliArgs) {
     } else {
         if let Some(session_name) = opts.session.clone() {
             start_client_plan(session_name.clone());
+            let (config, config_options, layout) = setup_for_new_session(
+                &opts,
+                &session_name,
+                config,
+                config_options,
+                (layout, layout_sources),
+            );
             start_client_impl(
                 Box::new(os_input),
                 opts,
@@ -381,6 +625,28 @@ pub(crate) fn start_client(opts: CliArgs) {
                 Some(layout),
             );
         } else {
+            // a layout naming its session (`session_name`) decides which session is started over
+            // the session name of the configuration
+            let config_options = match layout
+                .session_start(|name| matches!(session_exists(name), Ok(true)))
+            {
+                Some(LayoutSessionStart::AlreadyRunning(session_name)) => {
+                    eprintln!(
+                        "The session \"{}\" of the layout {} is already running. Add `attach_to_existing true` to the layout to attach to it, or use --session to open the layout in another session.",
+                        session_name, layout_sources.name
+                    );
+                    process::exit(1);
+                },
+                Some(
+                    LayoutSessionStart::Create(session_name)
+                    | LayoutSessionStart::Attach(session_name),
+                ) => Options {
+                    session_name: Some(session_name),
+                    attach_to_session: layout.attach_to_existing,
+                    ..config_options
+                },
+                None => config_options,
+            };
             if let Some(session_name) = config_options.session_name.as_ref() {
                 if let Ok(val) = envs::get_session_name() {
                     // This prevents the same type of recursion as above, only that here we
@@ -402,9 +668,18 @@ pub(crate) fn start_client(opts: CliArgs) {
                             config_options.clone(),
                             true,
                         );
-                        let attach_layout = match client {
-                            ClientInfo::Attach(_, _) => None,
-                            ClientInfo::New(_) => Some(layout),
+                        let (config, config_options, attach_layout) = match &client {
+                            ClientInfo::Attach(_, _) => (config, config_options, None),
+                            ClientInfo::New(session_name) => {
+                                let (config, config_options, layout) = setup_for_new_session(
+                                    &opts,
+                                    session_name,
+                                    config,
+                                    config_options,
+                                    (layout, layout_sources),
+                                );
+                                (config, config_options, Some(layout))
+                            },
                         };
                         start_client_impl(
                             Box::new(os_input),
@@ -416,13 +691,21 @@ pub(crate) fn start_client(opts: CliArgs) {
                         );
                     },
                     _ => {
+                        let session_name = session_name.clone();
                         start_client_plan(session_name.clone());
+                        let (config, config_options, layout) = setup_for_new_session(
+                            &opts,
+                            &session_name,
+                            config,
+                            config_options,
+                            (layout, layout_sources),
+                        );
                         start_client_impl(
                             Box::new(os_input),
                             opts,
                             config,
-                            config_options.clone(),
-                            ClientInfo::New(session_name.clone()),
+                            config_options,
+                            ClientInfo::New(session_name),
                             Some(layout),
                         );
                     },
@@ -435,6 +718,13 @@ pub(crate) fn start_client(opts: CliArgs) {
 
             let session_name = names::Generator::default().next().unwrap();
             start_client_plan(session_name.clone());
+            let (config, config_options, layout) = setup_for_new_session(
+                &opts,
+                &session_name,
+                config,
+                config_options,
+                (layout, layout_sources),
+            );
             start_client_impl(
                 Box::new(os_input),
                 opts,
@@ -26,6 +26,9 @@ fn main() {
             name,
             close_on_exit,
             start_suspended,
+            target_pane,
+            any_tab,
+            force,
         })) = opts.command
         {
             let command_cli_action = CliAction::NewPane {
@@ -36,6 +39,9 @@ fn main() {
                 name,
                 close_on_exit,
                 start_suspended,
+                target_pane,
+                any_tab,
+                force,
             };
             commands::send_action_to_session(command_cli_action, opts.session);
             std::process::exit(0);
@@ -79,8 +85,8 @@ fn main() {
         }
     }
 
-    if let Some(Command::Sessions(Sessions::ListSessions)) = opts.command {
-        commands::list_sessions();
+    if let Some(Command::Sessions(Sessions::ListSessions { format })) = opts.command {
+        commands::list_sessions(format);
     } else if let Some(Command::Sessions(Sessions::KillAllSessions { yes })) = opts.command {
         commands::kill_all_sessions(yes);
     } else if let Some(Command::Sessions(Sessions::KillSession { ref target_session })) =
@@ -3,10 +3,15 @@ use std::time::SystemTime;
 use std::{fs, io, process};
 use suggest::Suggest;
 use zellij_utils::{
+    cli::SessionListFormat,
     consts::ZELLIJ_SOCK_DIR,
     envs,
     interprocess::local_socket::LocalSocketStream,
-    ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
+    ipc::{
+        ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg,
+        SessionInfo,
+    },
+    serde_json,
 };
 
 pub(crate) fn get_sessions() -> Result<Vec<String>, io::ErrorKind> {
@@ -69,6 +74,31 @@ fn assert_socket(name: &str) -> bool {
     }
 }
 
+pub(crate) fn get_session_info(name: &str) -> Option<SessionInfo> {
+    let path = &*ZELLIJ_SOCK_DIR.join(name);
+    let stream = LocalSocketStream::connect(path).ok()?;
+    let mut sender = IpcSenderWithContext::new(stream);
+    let _ = sender.send(ClientToServerMsg::SessionInfo);
+    let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = sender.get_receiver();
+    match receiver.recv() {
+        Some((ServerToClientMsg::SessionInfo(session_info), _)) => Some(session_info),
+        None | Some((_, _)) => None,
+    }
+}
+
+/// The sessions along with the layout each was started with
+pub(crate) fn get_sessions_with_layouts(
+    sessions: Vec<String>,
+) -> Vec<(String, Option<SessionInfo>)> {
+    sessions
+        .into_iter()
+        .map(|session| {
+            let session_info = get_session_info(&session);
+            (session, session_info)
+        })
+        .collect()
+}
+
 pub(crate) fn print_sessions(sessions: Vec<String>) {
     let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
     sessions.iter().for_each(|session| {
@@ -124,10 +154,43 @@ pub(crate) fn kill_session(name: &str) {
     };
 }
 
-pub(crate) fn list_sessions() {
+fn print_sessions_with_layouts(sessions: Vec<String>, format: SessionListFormat) {
+    let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
+    let sessions = get_sessions_with_layouts(sessions);
+    match format {
+        SessionListFormat::Plain => {
+            for (session, session_info) in sessions {
+                let layout = session_info
+                    .map(|s| s.layout)
+                    .unwrap_or_else(|| "unknown".into());
+                let suffix = if curr_session == session {
+                    " (current)"
+                } else {
+                    ""
+                };
+                println!("{} [layout: {}]{}", session, layout, suffix);
+            }
+        },
+        SessionListFormat::Json => {
+            let sessions: Vec<serde_json::Value> = sessions
+                .into_iter()
+                .map(|(session, session_info)| {
+                    serde_json::json!({
+                        "name": session,
+                        "layout": session_info.map(|s| s.layout),
+                        "current": curr_session == session,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::Value::Array(sessions));
+        },
+    }
+}
+
+pub(crate) fn list_sessions(format: SessionListFormat) {
     let exit_code = match get_sessions() {
         Ok(sessions) if !sessions.is_empty() => {
-            print_sessions(sessions);
+            print_sessions_with_layouts(sessions, format);
             0
         },
         Ok(_) => {
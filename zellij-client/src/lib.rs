@@ -28,10 +28,13 @@ use zellij_utils::{
     envs,
     errors::{ClientContext, ContextType, ErrorInstruction},
     input::{config::Config, options::Options},
-    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg, SessionInfo},
     termwiz::input::InputEvent,
 };
-use zellij_utils::{cli::CliArgs, input::layout::Layout};
+use zellij_utils::{
+    cli::CliArgs,
+    input::layout::{Layout, LayoutSources},
+};
 
 /// Instructions related to the client-side application
 #[derive(Debug, Clone)]
@@ -43,6 +46,9 @@ pub(crate) enum ClientInstruction {
     SwitchToMode(InputMode),
     Connected,
     ActiveClients(Vec<ClientId>),
+    Log(Vec<String>),
+    LogError(Vec<String>),
+    SessionInfo(SessionInfo),
     StartedParsingStdinQuery,
     DoneParsingStdinQuery,
 }
@@ -58,6 +64,11 @@ impl From<ServerToClientMsg> for ClientInstruction {
             },
             ServerToClientMsg::Connected => ClientInstruction::Connected,
             ServerToClientMsg::ActiveClients(clients) => ClientInstruction::ActiveClients(clients),
+            ServerToClientMsg::Log(log_lines) => ClientInstruction::Log(log_lines),
+            ServerToClientMsg::LogError(log_lines) => ClientInstruction::LogError(log_lines),
+            ServerToClientMsg::SessionInfo(session_info) => {
+                ClientInstruction::SessionInfo(session_info)
+            },
         }
     }
 }
@@ -72,6 +83,9 @@ impl From<&ClientInstruction> for ClientContext {
             ClientInstruction::SwitchToMode(_) => ClientContext::SwitchToMode,
             ClientInstruction::Connected => ClientContext::Connected,
             ClientInstruction::ActiveClients(_) => ClientContext::ActiveClients,
+            ClientInstruction::Log(_) => ClientContext::Log,
+            ClientInstruction::LogError(_) => ClientContext::LogError,
+            ClientInstruction::SessionInfo(_) => ClientContext::SessionInfo,
             ClientInstruction::StartedParsingStdinQuery => ClientContext::StartedParsingStdinQuery,
             ClientInstruction::DoneParsingStdinQuery => ClientContext::DoneParsingStdinQuery,
         }
@@ -135,9 +149,14 @@ pub fn start_client(
     config: Config,
     config_options: Options,
     info: ClientInfo,
-    layout: Option<Layout>,
+    layout: Option<(Layout, LayoutSources)>,
 ) {
     info!("Starting Zellij client!");
+    if opts.debug {
+        if let Some((_layout, layout_sources)) = &layout {
+            info!("Layout: {}", layout_sources);
+        }
+    }
     let clear_client_terminal_attributes = "\u{1b}[?1l\u{1b}=\u{1b}[r\u{1b}[?1000l\u{1b}[?1002l\u{1b}[?1003l\u{1b}[?1005l\u{1b}[?1006l\u{1b}[?12l";
     let take_snapshot = "\u{1b}[?1049h";
     let bracketed_paste = "\u{1b}[?2004h";
@@ -179,11 +198,13 @@ pub fn start_client(
 
             spawn_server(&*ZELLIJ_IPC_PIPE, opts.debug).unwrap();
 
+            let (layout, layout_sources) = layout.unwrap();
             ClientToServerMsg::NewClient(
                 client_attributes,
                 Box::new(opts),
                 Box::new(config_options.clone()),
-                Box::new(layout.unwrap()),
+                Box::new(layout),
+                Box::new(layout_sources),
                 Some(config.plugins.clone()),
             )
         },
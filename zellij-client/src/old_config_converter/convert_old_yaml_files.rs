@@ -15,7 +15,7 @@ pub fn convert_old_yaml_files(opts: &CliArgs) {
 
     let mut layout_files_to_convert = vec![];
     let mut theme_files_to_convert = vec![];
-    if let Some(layout) = opts.layout.as_ref() {
+    for layout in &opts.layout {
         if layout.extension().map(|s| s.to_string_lossy().to_string()) == Some("yaml".into()) {
             if layout.exists() {
                 layout_files_to_convert.push((layout.clone(), true));
@@ -2,6 +2,7 @@ use crate::old_config_converter::layout_yaml_to_layout_kdl;
 use insta::assert_snapshot;
 use std::path::PathBuf;
 use std::{fs::File, io::prelude::*};
+use zellij_utils::input::{config::ConfigError, layout::Layout};
 
 #[test]
 fn properly_convert_default_layout() -> Result<(), String> {
@@ -156,3 +157,43 @@ fn properly_convert_layout_with_command_quoted_args() -> Result<(), String> {
     assert_snapshot!(format!("{}", kdl_config));
     Ok(())
 }
+
+#[test]
+fn yaml_layouts_are_refused_and_parse_once_converted() -> Result<(), String> {
+    for fixture in [
+        "old_default_yaml_layout.yaml",
+        "old_yaml_layout_with_quoted_args.yaml",
+    ] {
+        let fixture = PathBuf::from(format!(
+            "{}/src/old_config_converter/unit/fixtures/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            fixture
+        ));
+        let mut handle = File::open(&fixture).map_err(|e| format!("{}", e))?;
+        let mut raw_layout_file = String::new();
+        handle
+            .read_to_string(&mut raw_layout_file)
+            .map_err(|e| format!("{}", e))?;
+        // pasted into a layout without the yaml extension
+        match Layout::from_str(
+            &raw_layout_file,
+            "pasted-layout".into(),
+            None,
+            None,
+            &Default::default(),
+        ) {
+            Err(ConfigError::YamlLayout(_)) => {},
+            other => panic!("expected a YAML layout error, got {:?}", other),
+        }
+        let kdl_layout = layout_yaml_to_layout_kdl(&raw_layout_file)?;
+        Layout::from_str(
+            &kdl_layout,
+            "converted-layout".into(),
+            None,
+            None,
+            &Default::default(),
+        )
+        .map_err(|e| format!("{:?}\n{}", e, kdl_layout))?;
+    }
+    Ok(())
+}
@@ -2,15 +2,25 @@ use zellij_utils::async_std::task;
 use zellij_utils::errors::{prelude::*, BackgroundJobContext, ContextType};
 
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::fs;
+use std::os::unix::io::RawFd;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::panes::PaneId;
+use crate::pty::PtyInstruction;
 use crate::screen::ScreenInstruction;
 use crate::thread_bus::Bus;
+use crate::ServerInstruction;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum BackgroundJob {
     DisplayPaneError(Vec<PaneId>, String),
+    PaneDependencyTimeout(PaneId, Duration), // the pane waiting for its dependency
+    SpawnQueuedCommand(usize, Duration),     // usize is the tab index
+    RelayoutAfterResize(Duration),
+    ForceKillCommandInPane(PaneId, RawFd, Duration), // RawFd is the child asked to terminate
+    WatchLayoutFiles(Vec<PathBuf>, Duration),        // checked this often for changes
     Exit,
 }
 
@@ -18,6 +28,13 @@ impl From<&BackgroundJob> for BackgroundJobContext {
     fn from(background_job: &BackgroundJob) -> Self {
         match *background_job {
             BackgroundJob::DisplayPaneError(..) => BackgroundJobContext::DisplayPaneError,
+            BackgroundJob::PaneDependencyTimeout(..) => BackgroundJobContext::PaneDependencyTimeout,
+            BackgroundJob::SpawnQueuedCommand(..) => BackgroundJobContext::SpawnQueuedCommand,
+            BackgroundJob::RelayoutAfterResize(..) => BackgroundJobContext::RelayoutAfterResize,
+            BackgroundJob::ForceKillCommandInPane(..) => {
+                BackgroundJobContext::ForceKillCommandInPane
+            },
+            BackgroundJob::WatchLayoutFiles(..) => BackgroundJobContext::WatchLayoutFiles,
             BackgroundJob::Exit => BackgroundJobContext::Exit,
         }
     }
@@ -54,6 +71,68 @@ pub(crate) fn background_jobs_main(bus: Bus<BackgroundJob>) -> Result<()> {
                     }
                 });
             },
+            BackgroundJob::PaneDependencyTimeout(pane_id, timeout) => {
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        task::sleep(timeout).await;
+                        let _ = senders
+                            .send_to_screen(ScreenInstruction::PaneDependencyTimedOut(pane_id));
+                    }
+                });
+            },
+            BackgroundJob::SpawnQueuedCommand(tab_index, interval) => {
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        task::sleep(interval).await;
+                        let _ = senders
+                            .send_to_screen(ScreenInstruction::SpawnQueuedCommand(tab_index));
+                    }
+                });
+            },
+            BackgroundJob::RelayoutAfterResize(delay) => {
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        task::sleep(delay).await;
+                        let _ = senders.send_to_screen(ScreenInstruction::RelayoutAfterResize);
+                    }
+                });
+            },
+            BackgroundJob::ForceKillCommandInPane(pane_id, child_fd, timeout) => {
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        task::sleep(timeout).await;
+                        let _ = senders
+                            .send_to_pty(PtyInstruction::ForceKillCommandInPane(pane_id, child_fd));
+                    }
+                });
+            },
+            BackgroundJob::WatchLayoutFiles(layout_files, interval) => {
+                task::spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        let mut last_modified = modification_times(&layout_files);
+                        loop {
+                            task::sleep(interval).await;
+                            let modified = modification_times(&layout_files);
+                            if modified == last_modified {
+                                continue;
+                            }
+                            last_modified = modified;
+                            if senders
+                                .send_to_server(ServerInstruction::ReloadLayout)
+                                .is_err()
+                            {
+                                // the session is gone
+                                break;
+                            }
+                        }
+                    }
+                });
+            },
             BackgroundJob::Exit => {
                 return Ok(());
             },
@@ -61,6 +140,19 @@ pub(crate) fn background_jobs_main(bus: Bus<BackgroundJob>) -> Result<()> {
     }
 }
 
+// None for the files that cannot be read (eg. a swap layout file that was removed), so that them
+// coming back is a change as well
+fn modification_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| {
+            fs::metadata(file)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
 fn job_already_running(
     job: BackgroundJob,
     running_jobs: &mut HashMap<BackgroundJob, Instant>,
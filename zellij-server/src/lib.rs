@@ -19,9 +19,10 @@ use log::info;
 use pty_writer::{pty_writer_main, PtyWriteInstruction};
 use std::collections::{HashMap, HashSet};
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex, RwLock},
     thread,
+    time::Duration,
 };
 use zellij_utils::envs;
 use zellij_utils::nix::sys::stat::{umask, Mode};
@@ -44,13 +45,17 @@ use zellij_utils::{
     data::{Event, PluginCapabilities},
     errors::{prelude::*, ContextType, ErrorInstruction, FatalError, ServerContext},
     input::{
-        command::{RunCommand, TerminalAction},
+        command::{command_exists, RunCommand, TerminalAction},
         get_mode_info,
-        layout::Layout,
+        layout::{
+            check_max_layout_commands, Layout, LayoutSources, SwapFloatingLayout, SwapTiledLayout,
+            ValidationOptions,
+        },
+        layout_variables::LayoutVariables,
         options::Options,
         plugins::PluginsConfig,
     },
-    ipc::{ClientAttributes, ExitReason, ServerToClientMsg},
+    ipc::{ClientAttributes, ExitReason, ServerToClientMsg, SessionInfo},
     setup::get_default_data_dir,
 };
 
@@ -64,6 +69,7 @@ pub enum ServerInstruction {
         Box<CliArgs>,
         Box<Options>,
         Box<Layout>,
+        Box<LayoutSources>,
         ClientId,
         Option<PluginsConfig>,
     ),
@@ -76,7 +82,11 @@ pub enum ServerInstruction {
     DetachSession(Vec<ClientId>),
     AttachClient(ClientAttributes, Options, ClientId),
     ConnStatus(ClientId),
+    SessionInfo(ClientId),
     ActiveClients(ClientId),
+    Log(Vec<String>, ClientId),
+    LogError(Vec<String>, ClientId),
+    ReloadLayout, // the files of the session's layout changed, see the `layout_hot_reload` option
 }
 
 impl From<&ServerInstruction> for ServerContext {
@@ -92,7 +102,11 @@ impl From<&ServerInstruction> for ServerContext {
             ServerInstruction::DetachSession(..) => ServerContext::DetachSession,
             ServerInstruction::AttachClient(..) => ServerContext::AttachClient,
             ServerInstruction::ConnStatus(..) => ServerContext::ConnStatus,
+            ServerInstruction::SessionInfo(..) => ServerContext::SessionInfo,
             ServerInstruction::ActiveClients(_) => ServerContext::ActiveClients,
+            ServerInstruction::Log(..) => ServerContext::Log,
+            ServerInstruction::LogError(..) => ServerContext::LogError,
+            ServerInstruction::ReloadLayout => ServerContext::ReloadLayout,
         }
     }
 }
@@ -109,6 +123,9 @@ pub(crate) struct SessionMetaData {
     pub client_attributes: ClientAttributes,
     pub default_shell: Option<TerminalAction>,
     pub layout: Box<Layout>,
+    pub layout_sources: Box<LayoutSources>,
+    pub max_layout_commands: Option<usize>, // see check_max_layout_commands
+    layout_hot_reload: Option<LayoutHotReload>,
     screen_thread: Option<thread::JoinHandle<()>>,
     pty_thread: Option<thread::JoinHandle<()>>,
     plugin_thread: Option<thread::JoinHandle<()>>,
@@ -141,6 +158,92 @@ impl Drop for SessionMetaData {
     }
 }
 
+/// How often the files of the session's layout are checked for changes when it is reloaded as
+/// they change
+const LAYOUT_WATCH_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Where the session's layout is read again from when its files change, see the
+/// `layout_hot_reload` option
+pub(crate) struct LayoutHotReload {
+    layout_file: PathBuf,
+    layout_dir: Option<PathBuf>,
+    validation_options: ValidationOptions,
+    variables: LayoutVariables,
+}
+
+impl SessionMetaData {
+    /// Reads the session's layout again, for the tabs opened from now on and for the swap layouts
+    /// of the open tabs. The layout is left as it is when it cannot be read or parsed, eg. while
+    /// it is being edited
+    fn reload_layout(&mut self) {
+        let layout_hot_reload = match &self.layout_hot_reload {
+            Some(layout_hot_reload) => layout_hot_reload,
+            None => return,
+        };
+        let layout_file = layout_hot_reload.layout_file.display();
+        let mut layout = match Layout::reload(
+            &layout_hot_reload.layout_file,
+            layout_hot_reload.layout_dir.as_deref(),
+            layout_hot_reload.validation_options,
+            &layout_hot_reload.variables,
+        ) {
+            Ok(layout) => layout,
+            Err(e) => {
+                log::error!(
+                    "Failed to reload the layout {}, keeping the previous one: {}",
+                    layout_file,
+                    e
+                );
+                return;
+            },
+        };
+        // whether to run the commands of the layout was settled as the session started, this is
+        // not asked again for those the layout has now
+        layout.suspend_commands();
+        let condition_outcomes =
+            layout.apply_command_conditions(|command| command_exists(Path::new(command), None));
+        for outcome in condition_outcomes {
+            info!("{}", outcome.to_diagnostic());
+        }
+        let _ = self
+            .senders
+            .send_to_screen(ScreenInstruction::ReloadSwapLayouts(reloaded_swap_layouts(
+                &self.layout,
+                &layout,
+            )));
+        info!("Reloaded the layout {}", layout_file);
+        self.layout = Box::new(layout);
+    }
+}
+
+// the swap layouts the tabs of `previous_layout` are opened with, paired with those the same tabs
+// of `layout` are opened with
+fn reloaded_swap_layouts(
+    previous_layout: &Layout,
+    layout: &Layout,
+) -> Vec<(
+    (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+    (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+)> {
+    let mut reloaded_swap_layouts = vec![(
+        (
+            previous_layout.swap_tiled_layouts.clone(),
+            previous_layout.swap_floating_layouts.clone(),
+        ),
+        (
+            layout.swap_tiled_layouts.clone(),
+            layout.swap_floating_layouts.clone(),
+        ),
+    )];
+    for tab_index in 0..previous_layout.tabs.len().min(layout.tabs.len()) {
+        reloaded_swap_layouts.push((
+            previous_layout.swap_layouts_of_tab(tab_index),
+            layout.swap_layouts_of_tab(tab_index),
+        ));
+    }
+    reloaded_swap_layouts
+}
+
 macro_rules! remove_client {
     ($client_id:expr, $os_input:expr, $session_state:expr) => {
         $os_input.remove_client($client_id).unwrap();
@@ -319,10 +422,47 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 client_attributes,
                 opts,
                 config_options,
-                layout,
+                mut layout,
+                layout_sources,
                 client_id,
                 plugins,
             ) => {
+                // the commands are looked for here rather than when the layout is loaded, since
+                // this is where they run
+                let condition_outcomes = layout
+                    .apply_command_conditions(|command| command_exists(Path::new(command), None));
+                for outcome in condition_outcomes {
+                    info!("{}", outcome.to_diagnostic());
+                }
+                // the tabs are checked before any of them is created, so that a layout that
+                // cannot be created does not leave the session with only some of its tabs
+                let preflight_errors = layout.preflight(
+                    Some(client_attributes.size),
+                    &plugins.clone().unwrap_or_default(),
+                );
+                if !preflight_errors.is_empty() {
+                    let mut message = String::from("The layout cannot be created:");
+                    for preflight_error in preflight_errors {
+                        message.push_str(&format!("\n  {}", preflight_error));
+                    }
+                    let _ = os_input.send_to_client(
+                        client_id,
+                        ServerToClientMsg::Exit(ExitReason::Error(message)),
+                    );
+                    remove_client!(client_id, os_input, session_state);
+                    break;
+                }
+                if let Err(e) = check_max_layout_commands(
+                    layout.command_count(),
+                    config_options.max_layout_commands,
+                ) {
+                    let _ = os_input.send_to_client(
+                        client_id,
+                        ServerToClientMsg::Exit(ExitReason::Error(e.to_string())),
+                    );
+                    remove_client!(client_id, os_input, session_state);
+                    break;
+                }
                 let session = init_session(
                     os_input.clone(),
                     to_server.clone(),
@@ -330,6 +470,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     SessionOptions {
                         opts,
                         layout: layout.clone(),
+                        layout_sources,
                         plugins,
                         config_options: config_options.clone(),
                     },
@@ -347,7 +488,23 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     })
                 });
 
-                let spawn_tabs = |tab_layout, floating_panes_layout, tab_name, swap_layouts| {
+                if let Some(side_panel_layout) = layout.side_panel.clone() {
+                    session_data
+                        .read()
+                        .unwrap()
+                        .as_ref()
+                        .unwrap()
+                        .senders
+                        .send_to_pty(PtyInstruction::SpawnSidePanel(side_panel_layout, client_id))
+                        .unwrap();
+                }
+
+                let spawn_tabs = |tab_layout,
+                                  floating_panes_layout,
+                                  tab_name,
+                                  pin,
+                                  max_content_width,
+                                  swap_layouts| {
                     session_data
                         .read()
                         .unwrap()
@@ -359,6 +516,8 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                             tab_layout,
                             floating_panes_layout,
                             tab_name,
+                            pin,
+                            max_content_width,
                             swap_layouts,
                             client_id,
                         ))
@@ -366,19 +525,26 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 };
 
                 if layout.has_tabs() {
-                    for (tab_name, tab_layout, floating_panes_layout) in layout.tabs() {
+                    for (
+                        tab_index,
+                        (tab_name, tab_layout, floating_panes_layout, pin, max_content_width, _),
+                    ) in layout.tabs().into_iter().enumerate()
+                    {
+                        // tabs cycle through their own swap layouts, falling back to the ones of
+                        // the layout
                         spawn_tabs(
                             Some(tab_layout.clone()),
                             floating_panes_layout.clone(),
                             tab_name,
-                            (
-                                layout.swap_tiled_layouts.clone(),
-                                layout.swap_floating_layouts.clone(),
-                            ),
+                            pin,
+                            max_content_width,
+                            layout.swap_layouts_of_tab(tab_index),
                         );
                     }
 
                     if let Some(focused_tab_index) = layout.focused_tab_index() {
+                        // the tabs are sorted by their pins as they are created
+                        let focused_tab_position = layout.pinned_tab_position(focused_tab_index);
                         session_data
                             .read()
                             .unwrap()
@@ -386,7 +552,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                             .unwrap()
                             .senders
                             .send_to_pty(PtyInstruction::GoToTab(
-                                (focused_tab_index + 1) as u32,
+                                (focused_tab_position + 1) as u32,
                                 client_id,
                             ))
                             .unwrap();
@@ -396,6 +562,8 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         None,
                         layout.template.map(|t| t.1).clone().unwrap_or_default(),
                         None,
+                        None,
+                        None,
                         (
                             layout.swap_tiled_layouts.clone(),
                             layout.swap_floating_layouts.clone(),
@@ -457,6 +625,11 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     session_state
                 );
             },
+            ServerInstruction::ReloadLayout => {
+                if let Some(session_data) = session_data.write().unwrap().as_mut() {
+                    session_data.reload_layout();
+                }
+            },
             ServerInstruction::UnblockInputThread => {
                 for client_id in session_state.read().unwrap().clients.keys() {
                     send_to_client!(
@@ -614,6 +787,19 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 let _ = os_input.send_to_client(client_id, ServerToClientMsg::Connected);
                 remove_client!(client_id, os_input, session_state);
             },
+            ServerInstruction::SessionInfo(client_id) => {
+                let layout = session_data
+                    .read()
+                    .unwrap()
+                    .as_ref()
+                    .map(|session_data| session_data.layout_sources.name.clone())
+                    .unwrap_or_else(|| String::from("default"));
+                let _ = os_input.send_to_client(
+                    client_id,
+                    ServerToClientMsg::SessionInfo(SessionInfo { layout }),
+                );
+                remove_client!(client_id, os_input, session_state);
+            },
             ServerInstruction::ActiveClients(client_id) => {
                 let client_ids = session_state.read().unwrap().client_ids();
                 send_to_client!(
@@ -623,6 +809,22 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     session_state
                 );
             },
+            ServerInstruction::Log(log_lines, client_id) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::Log(log_lines),
+                    session_state
+                );
+            },
+            ServerInstruction::LogError(log_lines, client_id) => {
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::LogError(log_lines),
+                    session_state
+                );
+            },
         }
     }
 
@@ -641,6 +843,7 @@ pub struct SessionOptions {
     pub opts: Box<CliArgs>,
     pub config_options: Box<Options>,
     pub layout: Box<Layout>,
+    pub layout_sources: Box<LayoutSources>,
     pub plugins: Option<PluginsConfig>,
 }
 
@@ -652,10 +855,17 @@ fn init_session(
 ) -> SessionMetaData {
     let SessionOptions {
         opts,
-        config_options,
+        mut config_options,
         layout,
+        layout_sources,
         plugins,
     } = options;
+    // the interval and focus policy set by the session's layout take precedence over the
+    // configured ones
+    config_options.command_spawn_interval_ms = layout
+        .command_spawn_interval_ms
+        .or(config_options.command_spawn_interval_ms);
+    config_options.default_focus = layout.default_focus.or(config_options.default_focus);
 
     SCROLL_BUFFER_SIZE
         .set(
@@ -691,6 +901,22 @@ fn init_session(
     let capabilities = PluginCapabilities {
         arrow_fonts: config_options.simplified_ui.unwrap_or_default(),
     };
+    let max_layout_commands = config_options.max_layout_commands;
+    let layout_watched_files = if config_options.layout_hot_reload.unwrap_or(false) {
+        layout_sources.watched_files()
+    } else {
+        None
+    };
+    let layout_hot_reload = layout_watched_files
+        .as_ref()
+        .and_then(|watched_files| watched_files.first())
+        .map(|layout_file| LayoutHotReload {
+            layout_file: layout_file.clone(),
+            layout_dir: config_options.layout_dir.clone(),
+            validation_options: ValidationOptions::strict()
+                .with_limits(config_options.layout_limits()),
+            variables: layout_sources.variables.clone(),
+        });
 
     let default_shell = config_options.default_shell.clone().map(|command| {
         TerminalAction::RunCommand(RunCommand {
@@ -716,6 +942,15 @@ fn init_session(
                 ),
                 opts.debug,
                 config_options.scrollback_editor.clone(),
+                config_options
+                    .pane_title_formats
+                    .clone()
+                    .unwrap_or_default(),
+                config_options
+                    .command_spawn_interval_ms
+                    .filter(|interval_ms| *interval_ms > 0)
+                    .map(Duration::from_millis),
+                layout.command_wrapper.clone(),
             );
 
             move || pty_thread_main(pty, layout).fatal()
@@ -738,12 +973,17 @@ fn init_session(
             let max_panes = opts.max_panes;
 
             let client_attributes_clone = client_attributes.clone();
+            let layout_sources = layout_sources.clone();
+            // a layout without tabs opens a single one
+            let session_layout_tab_count = layout.tabs().len().max(1);
             move || {
                 screen_thread_main(
                     screen_bus,
                     max_panes,
                     client_attributes_clone,
                     config_options,
+                    layout_sources,
+                    session_layout_tab_count,
                 )
                 .fatal();
             }
@@ -813,7 +1053,7 @@ fn init_session(
         })
         .unwrap();
 
-    SessionMetaData {
+    let session = SessionMetaData {
         senders: ThreadSenders {
             to_screen: Some(to_screen),
             to_pty: Some(to_pty),
@@ -827,12 +1067,24 @@ fn init_session(
         default_shell,
         client_attributes,
         layout,
+        layout_sources,
+        max_layout_commands,
+        layout_hot_reload,
         screen_thread: Some(screen_thread),
         pty_thread: Some(pty_thread),
         plugin_thread: Some(plugin_thread),
         pty_writer_thread: Some(pty_writer_thread),
         background_jobs_thread: Some(background_jobs_thread),
+    };
+    if let Some(layout_watched_files) = layout_watched_files {
+        let _ = session
+            .senders
+            .send_to_background_jobs(BackgroundJob::WatchLayoutFiles(
+                layout_watched_files,
+                LAYOUT_WATCH_INTERVAL,
+            ));
     }
+    session
 }
 
 #[cfg(not(feature = "singlepass"))]
@@ -17,7 +17,10 @@ use zellij_utils::{
     channels::TrySendError,
     data::Palette,
     errors::prelude::*,
-    input::command::{RunCommand, TerminalAction},
+    input::{
+        command::{command_exists, RunCommand, TerminalAction},
+        layout::SplitDirection,
+    },
     interprocess,
     ipc::{
         ClientToServerMsg, ExitReason, IpcReceiverWithContext, IpcSenderWithContext,
@@ -114,33 +117,6 @@ fn handle_command_exit(mut child: Child) -> Result<Option<i32>> {
     }
 }
 
-fn command_exists(cmd: &RunCommand) -> bool {
-    let command = &cmd.command;
-    match cmd.cwd.as_ref() {
-        Some(cwd) => {
-            let full_command = cwd.join(&command);
-            if full_command.exists() && full_command.is_file() {
-                return true;
-            }
-        },
-        None => {
-            if command.exists() && command.is_file() {
-                return true;
-            }
-        },
-    }
-
-    if let Some(paths) = env::var_os("PATH") {
-        for path in env::split_paths(&paths) {
-            let full_command = path.join(command);
-            if full_command.exists() && full_command.is_file() {
-                return true;
-            }
-        }
-    }
-    false
-}
-
 fn handle_openpty(
     open_pty_res: OpenptyResult,
     cmd: RunCommand,
@@ -158,7 +134,7 @@ fn handle_openpty(
     let pid_primary = open_pty_res.master;
     let pid_secondary = open_pty_res.slave;
 
-    if command_exists(&cmd) {
+    if command_exists(&cmd.command, cmd.cwd.as_deref()) {
         let mut child = unsafe {
             let cmd = cmd.clone();
             let command = &mut Command::new(cmd.command);
@@ -176,6 +152,7 @@ fn handle_openpty(
             }
             command
                 .args(&cmd.args)
+                .envs(&cmd.env)
                 .pre_exec(move || -> std::io::Result<()> {
                     if libc::login_tty(pid_secondary) != 0 {
                         panic!("failed to set controlling terminal");
@@ -236,6 +213,93 @@ fn handle_terminal(
     }
 }
 
+/// The command opening `files_to_open` in the default editor (or the one set in the `EDITOR` or
+/// `VISUAL` environment variables), along with the args to retry with if the editor does not
+/// understand the line number. The line number is the one of the first file. vim and nvim open the
+/// other files as splits in the `editor_splits` direction, the other editors are given all the
+/// files to open them the way they do from the command line
+pub(crate) fn open_file_command(
+    files_to_open: Vec<PathBuf>,
+    line_number: Option<usize>,
+    editor_splits: Option<SplitDirection>,
+    default_editor: Option<PathBuf>,
+) -> (RunCommand, Option<Vec<String>>) {
+    let mut failover_cmd_args = None;
+    let mut command = default_editor.unwrap_or_else(|| {
+        PathBuf::from(
+            env::var("EDITOR")
+                .unwrap_or_else(|_| env::var("VISUAL").unwrap_or_else(|_| "vi".into())),
+        )
+    });
+
+    let mut args = vec![];
+
+    if !command.is_dir() {
+        separate_command_arguments(&mut command, &mut args);
+    }
+    let files_to_open: Vec<String> = files_to_open
+        .into_iter()
+        .map(|file_to_open| {
+            file_to_open
+                .into_os_string()
+                .into_string()
+                .expect("Not valid Utf8 Encoding")
+        })
+        .collect();
+    let is_vim = command.ends_with("vim") || command.ends_with("nvim");
+    let mut editor_split_args = vec![];
+    if is_vim && files_to_open.len() > 1 {
+        match editor_splits {
+            Some(SplitDirection::Vertical) => editor_split_args.push(String::from("-O")),
+            Some(SplitDirection::Horizontal) => editor_split_args.push(String::from("-o")),
+            Some(SplitDirection::Auto) | None => {},
+        }
+    }
+    args.extend(editor_split_args.iter().cloned());
+    if let Some(line_number) = line_number {
+        if is_vim
+            || command.ends_with("emacs")
+            || command.ends_with("nano")
+            || command.ends_with("kak")
+        {
+            failover_cmd_args = Some(
+                editor_split_args
+                    .into_iter()
+                    .chain(files_to_open.iter().cloned())
+                    .collect(),
+            );
+            // these apply the line number to the file following it
+            args.push(format!("+{}", line_number));
+            args.extend(files_to_open);
+        } else if command.ends_with("hx") || command.ends_with("helix") {
+            // at the time of writing, helix only supports this syntax
+            // and it might be a good idea to leave this here anyway
+            // to keep supporting old versions
+            let mut files_to_open = files_to_open.into_iter();
+            if let Some(first_file_to_open) = files_to_open.next() {
+                args.push(format!("{}:{}", first_file_to_open, line_number));
+            }
+            args.extend(files_to_open);
+        } else {
+            args.extend(files_to_open);
+        }
+    } else {
+        args.extend(files_to_open);
+    }
+    let open_file = RunCommand {
+        command,
+        args,
+        cwd: None,
+        hold_on_close: false,
+        hold_on_start: false,
+        strict_placeholders: false,
+        depends_on: None,
+        encoding: None,
+        env: BTreeMap::new(),
+    };
+    (open_file, failover_cmd_args)
+}
+
 // this is a utility method to separate the arguments from a pathbuf before we turn it into a
 // Command. eg. "/usr/bin/vim -e" ==> "/usr/bin/vim" + "-e" (the latter will be pushed to args)
 fn separate_command_arguments(command: &mut PathBuf, args: &mut Vec<String>) {
@@ -275,55 +339,11 @@ fn spawn_terminal(
 ) -> Result<(RawFd, RawFd)> {
     // returns the terminal_id, the primary fd and the
     // secondary fd
-    let mut failover_cmd_args = None;
-    let cmd = match terminal_action {
-        TerminalAction::OpenFile(file_to_open, line_number) => {
-            let mut command = default_editor.unwrap_or_else(|| {
-                PathBuf::from(
-                    env::var("EDITOR")
-                        .unwrap_or_else(|_| env::var("VISUAL").unwrap_or_else(|_| "vi".into())),
-                )
-            });
-
-            let mut args = vec![];
-
-            if !command.is_dir() {
-                separate_command_arguments(&mut command, &mut args);
-            }
-            let file_to_open = file_to_open
-                .into_os_string()
-                .into_string()
-                .expect("Not valid Utf8 Encoding");
-            if let Some(line_number) = line_number {
-                if command.ends_with("vim")
-                    || command.ends_with("nvim")
-                    || command.ends_with("emacs")
-                    || command.ends_with("nano")
-                    || command.ends_with("kak")
-                {
-                    failover_cmd_args = Some(vec![file_to_open.clone()]);
-                    args.push(format!("+{}", line_number));
-                    args.push(file_to_open);
-                } else if command.ends_with("hx") || command.ends_with("helix") {
-                    // at the time of writing, helix only supports this syntax
-                    // and it might be a good idea to leave this here anyway
-                    // to keep supporting old versions
-                    args.push(format!("{}:{}", file_to_open, line_number));
-                } else {
-                    args.push(file_to_open);
-                }
-            } else {
-                args.push(file_to_open);
-            }
-            RunCommand {
-                command,
-                args,
-                cwd: None,
-                hold_on_close: false,
-                hold_on_start: false,
-            }
+    let (cmd, failover_cmd_args) = match terminal_action {
+        TerminalAction::OpenFile(files_to_open, line_number, editor_splits) => {
+            open_file_command(files_to_open, line_number, editor_splits, default_editor)
         },
-        TerminalAction::RunCommand(command) => command,
+        TerminalAction::RunCommand(command) => (command, None),
     };
     let failover_cmd = if let Some(failover_cmd_args) = failover_cmd_args {
         let mut cmd = cmd.clone();
@@ -469,6 +489,8 @@ pub trait ServerOsApi: Send + Sync {
     fn kill(&self, pid: Pid) -> Result<()>;
     /// Terminate the process with process ID `pid`. (SIGKILL)
     fn force_kill(&self, pid: Pid) -> Result<()>;
+    /// Ask the process with process ID `pid` to exit, letting it clean up first. (SIGTERM)
+    fn terminate(&self, pid: Pid) -> Result<()>;
     /// Returns a [`Box`] pointer to this [`ServerOsApi`] struct.
     fn box_clone(&self) -> Box<dyn ServerOsApi>;
     fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> Result<()>;
@@ -663,6 +685,10 @@ impl ServerOsApi for ServerOsInputOutput {
         let _ = kill(pid, Some(Signal::SIGKILL));
         Ok(())
     }
+    fn terminate(&self, pid: Pid) -> Result<()> {
+        let _ = kill(pid, Some(Signal::SIGTERM));
+        Ok(())
+    }
     fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> Result<()> {
         let err_context = || format!("failed to send message to client {client_id}");
 
@@ -229,7 +229,8 @@ impl Output {
         self.link_handler = Some(link_handler);
         self.floating_panes_stack = floating_panes_stack;
         for client_id in client_ids {
-            self.client_character_chunks.insert(*client_id, vec![]);
+            // a client might already have chunks from the side panel, which is rendered first
+            self.client_character_chunks.entry(*client_id).or_default();
         }
     }
     pub fn add_character_chunks_to_client(
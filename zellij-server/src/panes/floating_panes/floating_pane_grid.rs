@@ -93,7 +93,8 @@ impl<'a> FloatingPaneGrid<'a> {
         Ok(())
     }
 
-    pub fn resize(&mut self, space: Size) -> Result<()> {
+    /// Returns the viewport the panes were resized into
+    pub fn resize(&mut self, space: Size) -> Result<Viewport> {
         let err_context = || {
             format!(
                 "failed to resize from {:?} to {:?}",
@@ -265,7 +266,7 @@ impl<'a> FloatingPaneGrid<'a> {
                 pane.set_geom(new_pane_geom);
             }
         }
-        Ok(())
+        Ok(new_viewport)
     }
 
     pub fn move_pane_left(&mut self, pane_id: &PaneId) -> Result<()> {
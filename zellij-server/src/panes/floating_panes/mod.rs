@@ -25,13 +25,22 @@ use zellij_utils::{
     data::{ModeInfo, Style},
     errors::prelude::*,
     input::command::RunCommand,
-    input::layout::FloatingPaneLayout,
+    input::layout::{FloatingPaneAnchor, FloatingPaneLayout, PercentOrFixed},
     pane_size::{Dimension, Offset, PaneGeom, Size, Viewport},
 };
 
 const RESIZE_INCREMENT_WIDTH: usize = 5;
 const RESIZE_INCREMENT_HEIGHT: usize = 2;
 
+// a pane placed from the anchor of its layout, put back there when the screen is resized for as
+// long as it stays where it was placed (`geom`)
+struct AnchoredPane {
+    anchor: FloatingPaneAnchor,
+    x: Option<PercentOrFixed>,
+    y: Option<PercentOrFixed>,
+    geom: PaneGeom,
+}
+
 pub struct FloatingPanes {
     panes: BTreeMap<PaneId, Box<dyn Pane>>,
     display_area: Rc<RefCell<Size>>,
@@ -43,6 +52,7 @@ pub struct FloatingPanes {
     style: Style,
     session_is_mirrored: bool,
     desired_pane_positions: HashMap<PaneId, PaneGeom>, // this represents the positions of panes the user moved with intention, rather than by resizing the terminal window
+    anchored_panes: HashMap<PaneId, AnchoredPane>,
     z_indices: Vec<PaneId>,
     active_panes: ActivePanes,
     show_panes: bool,
@@ -76,6 +86,7 @@ impl FloatingPanes {
             default_mode_info,
             style,
             desired_pane_positions: HashMap::new(),
+            anchored_panes: HashMap::new(),
             z_indices: vec![],
             show_panes: false,
             active_panes: ActivePanes::new(&os_input),
@@ -84,11 +95,14 @@ impl FloatingPanes {
         }
     }
     pub fn stack(&self) -> Option<FloatingPanesStack> {
-        if self.panes_are_visible() {
+        if self.panes_are_visible() || self.has_pinned_panes() {
             let layers = self
                 .z_indices
                 .iter()
-                .map(|pane_id| self.panes.get(pane_id).unwrap().position_and_size())
+                .map(|pane_id| self.panes.get(pane_id).unwrap())
+                // pinned panes are shown even when the floating panes are hidden
+                .filter(|pane| self.show_panes || pane.pinned())
+                .map(|pane| pane.position_and_size())
                 .collect();
             Some(FloatingPanesStack { layers })
         } else {
@@ -104,6 +118,27 @@ impl FloatingPanes {
         self.panes.insert(pane_id, pane);
         self.z_indices.push(pane_id);
     }
+    /// Keeps the pane where the anchor of its layout placed it as the screen is resized, until it
+    /// is moved elsewhere
+    pub fn anchor_pane(&mut self, pane_id: PaneId, floating_pane_layout: &FloatingPaneLayout) {
+        match (
+            floating_pane_layout.anchor,
+            self.desired_pane_positions.get(&pane_id),
+        ) {
+            (Some(anchor), Some(geom)) => {
+                let anchored_pane = AnchoredPane {
+                    anchor,
+                    x: floating_pane_layout.x.clone(),
+                    y: floating_pane_layout.y.clone(),
+                    geom: *geom,
+                };
+                self.anchored_panes.insert(pane_id, anchored_pane);
+            },
+            _ => {
+                self.anchored_panes.remove(&pane_id);
+            },
+        }
+    }
     pub fn replace_active_pane(
         &mut self,
         pane: Box<dyn Pane>,
@@ -151,6 +186,9 @@ impl FloatingPanes {
             self.desired_pane_positions
                 .insert(with_pane_id, desired_pane_position);
         }
+        if let Some(anchored_pane) = self.anchored_panes.remove(&pane_id) {
+            self.anchored_panes.insert(with_pane_id, anchored_pane);
+        }
 
         // move clients from the previously active pane to the new pane we just inserted
         self.move_clients_between_panes(pane_id, with_pane_id);
@@ -159,6 +197,7 @@ impl FloatingPanes {
     pub fn remove_pane(&mut self, pane_id: PaneId) -> Option<Box<dyn Pane>> {
         self.z_indices.retain(|p_id| *p_id != pane_id);
         self.desired_pane_positions.remove(&pane_id);
+        self.anchored_panes.remove(&pane_id);
         self.panes.remove(&pane_id)
     }
     pub fn hold_pane(
@@ -172,6 +211,59 @@ impl FloatingPanes {
             .get_mut(&pane_id)
             .map(|p| p.hold(exit_status, is_first_run, run_command));
     }
+    /// Resizes a pane with a "fit" height or width to the output of its command (eg. once the
+    /// command exited), keeping it centered if it was
+    pub fn fit_pane_to_content(
+        &mut self,
+        pane_id: PaneId,
+        os_api: &mut Box<dyn ServerOsApi>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to fit pane {pane_id:?} to its content");
+        let viewport = *self.viewport.borrow();
+        let pane = match self.panes.get_mut(&pane_id) {
+            Some(pane) => pane,
+            None => return Ok(()),
+        };
+        let (height_fit, width_fit) = pane.fit_to_content();
+        let (content_rows, content_cols) = match pane.content_size() {
+            Some(content_size) if height_fit.is_some() || width_fit.is_some() => content_size,
+            _ => return Ok(()),
+        };
+        let mut geom = pane.position_and_size();
+        // the frame is part of the size of the pane
+        let frame_rows = geom.rows.as_usize().saturating_sub(pane.get_content_rows());
+        let frame_cols = geom
+            .cols
+            .as_usize()
+            .saturating_sub(pane.get_content_columns());
+        if let Some(height_fit) = height_fit {
+            let rows = height_fit.bound(content_rows.max(1) + frame_rows, viewport.rows);
+            geom.y = fitted_position(
+                geom.y,
+                geom.rows.as_usize(),
+                rows,
+                viewport.y,
+                viewport.rows,
+            );
+            geom.rows = Dimension::fixed(rows);
+        }
+        if let Some(width_fit) = width_fit {
+            let cols = width_fit.bound(content_cols.max(1) + frame_cols, viewport.cols);
+            geom.x = fitted_position(
+                geom.x,
+                geom.cols.as_usize(),
+                cols,
+                viewport.x,
+                viewport.cols,
+            );
+            geom.cols = Dimension::fixed(cols);
+        }
+        pane.set_geom(geom);
+        resize_pty!(pane, os_api, self.senders).with_context(err_context)?;
+        self.desired_pane_positions.insert(pane_id, geom);
+        self.set_force_render();
+        Ok(())
+    }
     pub fn get(&self, pane_id: &PaneId) -> Option<&Box<dyn Pane>> {
         self.panes.get(pane_id)
     }
@@ -197,6 +289,9 @@ impl FloatingPanes {
     pub fn has_panes(&self) -> bool {
         !self.panes.is_empty()
     }
+    pub fn has_pinned_panes(&self) -> bool {
+        self.panes.values().any(|pane| pane.pinned())
+    }
     pub fn active_pane_id(&self, client_id: ClientId) -> Option<PaneId> {
         self.active_panes.get(&client_id).copied()
     }
@@ -246,17 +341,25 @@ impl FloatingPanes {
             viewport,
         );
         let mut position = floating_pane_grid.find_room_for_new_pane().unwrap(); // TODO: no unwrap
+        if let Some(anchor) = floating_pane_layout.anchor {
+            return anchored_floating_pane_position(
+                anchor,
+                floating_pane_layout,
+                position,
+                viewport,
+            );
+        }
+        let (cols, rows) = floating_pane_layout.resolve_size(
+            (viewport.cols, viewport.rows),
+            (position.cols.as_usize(), position.rows.as_usize()),
+        );
+        position.cols = Dimension::fixed(cols);
+        position.rows = Dimension::fixed(rows);
         if let Some(x) = &floating_pane_layout.x {
-            position.x = x.to_position(viewport.cols);
+            position.x = viewport.x + x.to_coordinate(viewport.cols, position.cols.as_usize());
         }
         if let Some(y) = &floating_pane_layout.y {
-            position.y = y.to_position(viewport.rows);
-        }
-        if let Some(width) = &floating_pane_layout.width {
-            position.cols = Dimension::fixed(width.to_position(viewport.cols));
-        }
-        if let Some(height) = &floating_pane_layout.height {
-            position.rows = Dimension::fixed(height.to_position(viewport.rows));
+            position.y = y.to_coordinate(viewport.rows, position.rows.as_usize());
         }
         if position.cols.as_usize() > viewport.cols {
             position.cols = Dimension::fixed(viewport.cols);
@@ -264,10 +367,10 @@ impl FloatingPanes {
         if position.rows.as_usize() > viewport.rows {
             position.rows = Dimension::fixed(viewport.rows);
         }
-        if position.x + position.cols.as_usize() > viewport.cols {
-            position.x = position
-                .x
-                .saturating_sub((position.x + position.cols.as_usize()) - viewport.cols);
+        if position.x + position.cols.as_usize() > viewport.x + viewport.cols {
+            position.x = position.x.saturating_sub(
+                (position.x + position.cols.as_usize()) - (viewport.x + viewport.cols),
+            );
         }
         if position.y + position.rows.as_usize() > viewport.rows {
             position.y = position
@@ -312,7 +415,12 @@ impl FloatingPanes {
         let err_context = || "failed to render output";
         let connected_clients: Vec<ClientId> =
             { self.connected_clients.borrow().iter().copied().collect() };
-        let mut floating_panes: Vec<_> = self.panes.iter_mut().collect();
+        let show_panes = self.show_panes;
+        let mut floating_panes: Vec<_> = self
+            .panes
+            .iter_mut()
+            .filter(|(_pane_id, pane)| show_panes || pane.pinned())
+            .collect();
         floating_panes.sort_by(|(a_id, _a_pane), (b_id, _b_pane)| {
             self.z_indices
                 .iter()
@@ -375,6 +483,21 @@ impl FloatingPanes {
         Ok(())
     }
 
+    /// Moves all panes (and the positions they were moved to) `columns` to the right (or to the
+    /// left when negative)
+    pub fn move_panes_horizontally(&mut self, columns: isize) {
+        let moved = |x: usize| (x as isize + columns).max(0) as usize;
+        for pane in self.panes.values_mut() {
+            let mut geom = pane.current_geom();
+            geom.x = moved(geom.x);
+            pane.set_geom(geom);
+        }
+        for geom in self.desired_pane_positions.values_mut() {
+            geom.x = moved(geom.x);
+        }
+        self.set_force_render();
+    }
+
     pub fn resize(&mut self, new_screen_size: Size) {
         let display_area = *self.display_area.borrow();
         let viewport = *self.viewport.borrow();
@@ -384,9 +507,35 @@ impl FloatingPanes {
             display_area,
             viewport,
         );
-        floating_pane_grid.resize(new_screen_size).unwrap();
+        let viewport = floating_pane_grid.resize(new_screen_size).unwrap();
+        self.move_panes_back_to_their_anchors(viewport);
         self.set_force_render();
     }
+    fn move_panes_back_to_their_anchors(&mut self, viewport: Viewport) {
+        // a pane moved since it was placed is no longer held to its anchor
+        let desired_pane_positions = &self.desired_pane_positions;
+        self.anchored_panes.retain(|pane_id, anchored_pane| {
+            desired_pane_positions.get(pane_id) == Some(&anchored_pane.geom)
+        });
+        for (pane_id, anchored_pane) in &self.anchored_panes {
+            if let Some(pane) = self.panes.get_mut(pane_id) {
+                let mut geom = pane.current_geom();
+                let cols = anchored_pane.geom.cols.as_usize().min(viewport.cols);
+                let rows = anchored_pane.geom.rows.as_usize().min(viewport.rows);
+                let (x, y) = anchored_pane.anchor.position(
+                    anchored_pane.x.as_ref(),
+                    anchored_pane.y.as_ref(),
+                    (viewport.cols, viewport.rows),
+                    (cols, rows),
+                );
+                geom.x = viewport.x + x;
+                geom.y = viewport.y + y;
+                geom.cols = Dimension::fixed(cols);
+                geom.rows = Dimension::fixed(rows);
+                pane.set_geom(geom);
+            }
+        }
+    }
 
     pub fn resize_pty_all_panes(&mut self, os_api: &mut Box<dyn ServerOsApi>) -> Result<()> {
         for pane in self.panes.values_mut() {
@@ -796,6 +945,7 @@ impl FloatingPanes {
     pub fn drain(&mut self) -> BTreeMap<PaneId, Box<dyn Pane>> {
         self.z_indices.clear();
         self.desired_pane_positions.clear();
+        self.anchored_panes.clear();
         match self.panes.iter().next().map(|(pid, _p)| *pid) {
             Some(first_pid) => self.panes.split_off(&first_pid),
             None => BTreeMap::new(),
@@ -847,3 +997,49 @@ impl FloatingPanes {
         }
     }
 }
+
+// where a pane starting at `position` ends up along one dimension of the viewport once resized from
+// `size` to `new_size`: centered again if it was centered, otherwise where it was but still inside
+// the viewport
+fn fitted_position(
+    position: usize,
+    size: usize,
+    new_size: usize,
+    viewport_start: usize,
+    viewport_size: usize,
+) -> usize {
+    let margin_before = position.saturating_sub(viewport_start);
+    let margin_after = (viewport_start + viewport_size).saturating_sub(position + size);
+    let was_centered = margin_before.abs_diff(margin_after) <= 1;
+    let room = viewport_size.saturating_sub(new_size);
+    if was_centered {
+        viewport_start + room / 2
+    } else {
+        viewport_start + margin_before.min(room)
+    }
+}
+
+// `position` (where there is room for the pane) sized as the layout asks and moved to its anchor,
+// the pane being kept inside the viewport
+fn anchored_floating_pane_position(
+    anchor: FloatingPaneAnchor,
+    floating_pane_layout: &FloatingPaneLayout,
+    mut position: PaneGeom,
+    viewport: Viewport,
+) -> PaneGeom {
+    let (cols, rows) = floating_pane_layout.resolve_size(
+        (viewport.cols, viewport.rows),
+        (position.cols.as_usize(), position.rows.as_usize()),
+    );
+    let (x, y) = anchor.position(
+        floating_pane_layout.x.as_ref(),
+        floating_pane_layout.y.as_ref(),
+        (viewport.cols, viewport.rows),
+        (cols, rows),
+    );
+    position.x = viewport.x + x;
+    position.y = viewport.y + y;
+    position.cols = Dimension::fixed(cols);
+    position.rows = Dimension::fixed(rows);
+    position
+}
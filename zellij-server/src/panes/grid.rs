@@ -1066,6 +1066,31 @@ impl Grid {
         scrollback.push_str(&viewport);
         scrollback
     }
+    /// The rows and columns the output takes up: its lines (those scrolled off included) up to the
+    /// last one with anything on it, and the width of the widest of them without trailing spaces
+    pub fn content_size(&self) -> (usize, usize) {
+        let mut rows = 0;
+        let mut cols = 0;
+        for (index, row) in self
+            .lines_above
+            .iter()
+            .chain(self.viewport.iter())
+            .enumerate()
+        {
+            let row_width: usize = row
+                .columns
+                .iter()
+                .rev()
+                .skip_while(|terminal_character| terminal_character.character == ' ')
+                .map(|terminal_character| terminal_character.width)
+                .sum();
+            if row_width > 0 {
+                rows = index + 1;
+                cols = std::cmp::max(cols, row_width);
+            }
+        }
+        (rows, cols)
+    }
     pub fn move_viewport_up(&mut self, count: usize) {
         for _ in 0..count {
             self.scroll_up_one_line();
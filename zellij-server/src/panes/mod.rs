@@ -9,6 +9,7 @@ mod active_panes;
 mod floating_panes;
 mod plugin_pane;
 mod search;
+mod side_panel;
 mod terminal_pane;
 mod tiled_panes;
 
@@ -18,6 +19,7 @@ pub use floating_panes::*;
 pub use grid::*;
 pub use link_handler::*;
 pub(crate) use plugin_pane::*;
+pub(crate) use side_panel::*;
 pub use sixel::*;
 pub(crate) use terminal_character::*;
 pub use terminal_pane::*;
@@ -65,6 +65,10 @@ pub(crate) struct PluginPane {
     prev_pane_name: String,
     frame: HashMap<ClientId, PaneFrame>,
     borderless: bool,
+    hide_title: bool,
+    read_only: bool,
+    exclude_from_dump: bool,
+    pinned: bool,
     pane_frame_color_override: Option<(PaletteColor, Option<String>)>,
     invoked_with: Option<Run>,
 }
@@ -96,6 +100,10 @@ impl PluginPane {
             content_offset: Offset::default(),
             pane_title: title,
             borderless: false,
+            hide_title: false,
+            read_only: false,
+            exclude_from_dump: false,
+            pinned: false,
             pane_name: pane_name.clone(),
             prev_pane_name: pane_name,
             terminal_emulator_colors,
@@ -266,6 +274,8 @@ impl Pane for PluginPane {
                 && frame_params.is_main_client
             {
                 String::from("Enter name...")
+            } else if self.hide_title {
+                String::new()
             } else if self.pane_name.is_empty() {
                 grid.title
                     .clone()
@@ -290,6 +300,9 @@ impl Pane for PluginPane {
             if let Some((frame_color_override, _text)) = self.pane_frame_color_override.as_ref() {
                 frame.override_color(*frame_color_override);
             }
+            if self.read_only {
+                frame.indicate_read_only();
+            }
 
             let res = match self.frame.get(&client_id) {
                 // TODO: use and_then or something?
@@ -487,6 +500,30 @@ impl Pane for PluginPane {
     fn borderless(&self) -> bool {
         self.borderless
     }
+    fn set_hide_title(&mut self, hide_title: bool) {
+        self.hide_title = hide_title;
+    }
+    fn hide_title(&self) -> bool {
+        self.hide_title
+    }
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+    fn set_exclude_from_dump(&mut self, exclude_from_dump: bool) {
+        self.exclude_from_dump = exclude_from_dump;
+    }
+    fn exclude_from_dump(&self) -> bool {
+        self.exclude_from_dump
+    }
+    fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+    fn pinned(&self) -> bool {
+        self.pinned
+    }
     fn handle_right_click(&mut self, to: &Position, client_id: ClientId) {
         self.send_plugin_instructions
             .send(PluginInstruction::Update(vec![(
@@ -513,6 +550,9 @@ impl Pane for PluginPane {
     fn set_title(&mut self, title: String) {
         self.pane_title = title;
     }
+    fn pane_name(&self) -> &str {
+        &self.pane_name
+    }
 }
 
 impl PluginPane {
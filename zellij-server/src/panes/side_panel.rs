@@ -0,0 +1,223 @@
+use crate::output::Output;
+use crate::panes::{LinkHandler, PaneId};
+use crate::pty::{PtyInstruction, VteBytes};
+use crate::pty_writer::PtyWriteInstruction;
+use crate::tab::{AdjustedInput, Pane};
+use crate::thread_bus::ThreadSenders;
+use crate::ui::pane_contents_and_ui::PaneContentsAndUi;
+use crate::{os_input_output::ServerOsApi, ClientId};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use zellij_utils::{
+    data::{InputMode, Style},
+    errors::prelude::*,
+    input::{command::RunCommand, layout::SidePanelLayout},
+    pane_size::{Offset, Size},
+    position::Position,
+};
+
+/// A terminal pane docked to the side of the screen, it is shared between all the tabs which are
+/// laid out in the space left next to it
+pub(crate) struct SidePanel {
+    layout: SidePanelLayout,
+    pane: Box<dyn Pane>,
+    link_handler: Rc<RefCell<LinkHandler>>,
+    is_visible: bool, // false when the screen is too small to hold both the panel and the tabs
+    focused_clients: HashSet<ClientId>,
+    waiting_resize: bool,
+}
+
+impl SidePanel {
+    pub fn new(
+        layout: SidePanelLayout,
+        mut pane: Box<dyn Pane>,
+        link_handler: Rc<RefCell<LinkHandler>>,
+    ) -> Self {
+        // the panel always has a frame, it is what separates it from the tabs
+        pane.set_frame(true);
+        pane.set_content_offset(Offset::frame(1));
+        SidePanel {
+            layout,
+            pane,
+            link_handler,
+            is_visible: false,
+            focused_clients: HashSet::new(),
+            waiting_resize: false,
+        }
+    }
+    pub fn pid(&self) -> PaneId {
+        self.pane.pid()
+    }
+    /// Positions the panel in a screen of `screen_size`
+    pub fn resize(&mut self, screen_size: Size, os_api: &dyn ServerOsApi) -> Result<()> {
+        let err_context = || format!("failed to resize side panel to {screen_size:?}");
+        match self.layout.split_space(screen_size) {
+            Ok((panel_geom, _tab_area)) => {
+                self.is_visible = true;
+                self.pane.set_geom(panel_geom);
+                self.pane.render_full_viewport();
+                self.resize_pty(os_api).with_context(err_context)
+            },
+            Err(e) => {
+                log::error!("{}, hiding it", e);
+                self.is_visible = false;
+                Ok(())
+            },
+        }
+    }
+    /// The space left for the tabs in a screen of `screen_size`
+    pub fn tab_area(&self, screen_size: Size) -> Size {
+        self.layout
+            .split_space(screen_size)
+            .map(|(_panel_geom, tab_area)| tab_area)
+            .unwrap_or(screen_size)
+    }
+    pub fn contains(&self, position: &Position) -> bool {
+        self.is_visible && self.pane.contains(position)
+    }
+    pub fn is_focused(&self, client_id: ClientId) -> bool {
+        self.is_visible && self.focused_clients.contains(&client_id)
+    }
+    pub fn focus(&mut self, client_id: ClientId) {
+        self.focused_clients.insert(client_id);
+        self.pane.set_should_render(true);
+    }
+    pub fn unfocus(&mut self, client_id: ClientId) -> bool {
+        // returns true if the panel was focused for this client
+        let was_focused = self.focused_clients.remove(&client_id);
+        if was_focused {
+            self.pane.set_should_render(true);
+        }
+        was_focused
+    }
+    pub fn hold(&mut self, exit_status: Option<i32>, is_first_run: bool, run_command: RunCommand) {
+        self.pane.hold(exit_status, is_first_run, run_command);
+    }
+    pub fn handle_pty_bytes(
+        &mut self,
+        bytes: VteBytes,
+        os_api: &dyn ServerOsApi,
+        senders: &ThreadSenders,
+    ) -> Result<()> {
+        let err_context = || format!("failed to handle pty bytes in side panel");
+        if self.waiting_resize {
+            self.waiting_resize = false;
+            self.resize_pty(os_api).with_context(err_context)?;
+        }
+        self.pane.handle_pty_bytes(bytes);
+        for message in self.pane.drain_messages_to_pty() {
+            self.write(message, senders).with_context(err_context)?;
+        }
+        Ok(())
+    }
+    pub fn write(&mut self, input_bytes: Vec<u8>, senders: &ThreadSenders) -> Result<bool> {
+        // returns true if the panel should be closed (eg. its command pane was closed with ctrl-c)
+        let err_context = || format!("failed to write to side panel");
+        let terminal_id = match self.pane.pid() {
+            PaneId::Terminal(terminal_id) => terminal_id,
+            PaneId::Plugin(_) => return Ok(false),
+        };
+        match self.pane.adjust_input_to_terminal(input_bytes) {
+            Some(AdjustedInput::WriteBytesToTerminal(adjusted_input)) => {
+                senders
+                    .send_to_pty_writer(PtyWriteInstruction::Write(adjusted_input, terminal_id))
+                    .with_context(err_context)?;
+            },
+            Some(AdjustedInput::ReRunCommandInThisPane(command)) => {
+                self.waiting_resize = true;
+                senders
+                    .send_to_pty(PtyInstruction::ReRunCommandInPane(
+                        PaneId::Terminal(terminal_id),
+                        command,
+                    ))
+                    .with_context(err_context)?;
+            },
+            Some(AdjustedInput::CloseThisPane) => return Ok(true),
+            None => {},
+        }
+        Ok(false)
+    }
+    pub fn render_full_viewport(&mut self) {
+        self.pane.render_full_viewport();
+    }
+    pub fn render(
+        &mut self,
+        output: &mut Output,
+        style: Style,
+        client_modes: &[(ClientId, InputMode)],
+        session_is_mirrored: bool,
+    ) -> Result<()> {
+        let err_context = || "failed to render side panel";
+        if !self.is_visible {
+            return Ok(());
+        }
+        let clients: HashSet<ClientId> = client_modes
+            .iter()
+            .map(|(client_id, _client_mode)| *client_id)
+            .collect();
+        output.add_clients(&clients, self.link_handler.clone(), None);
+        let pane_id = self.pane.pid();
+        let active_panes: HashMap<ClientId, PaneId> = self
+            .focused_clients
+            .iter()
+            .map(|client_id| (*client_id, pane_id))
+            .collect();
+        let mut pane_contents_and_ui = PaneContentsAndUi::new(
+            &mut self.pane,
+            output,
+            style,
+            &active_panes,
+            client_modes.len() > 1,
+            None,
+            false,
+            false,
+            true,
+        );
+        for (client_id, client_mode) in client_modes {
+            pane_contents_and_ui
+                .render_pane_frame(*client_id, *client_mode, session_is_mirrored)
+                .with_context(err_context)?;
+        }
+        pane_contents_and_ui
+            .render_pane_contents_to_multiple_clients(clients.iter().copied())
+            .with_context(err_context)?;
+        Ok(())
+    }
+    pub fn render_cursor(&self, output: &mut Output) {
+        // this should happen after the tabs were rendered, so that it overrides their cursor
+        // position for the clients focused on the panel
+        if !self.is_visible || !output.is_dirty() {
+            return;
+        }
+        for client_id in &self.focused_clients {
+            match self.pane.cursor_coordinates() {
+                Some((x_in_terminal, y_in_terminal)) => {
+                    let show_cursor = "\u{1b}[?25h";
+                    let goto_cursor_position = &format!(
+                        "\u{1b}[{};{}H\u{1b}[m{}",
+                        self.pane.y() + y_in_terminal + 1,
+                        self.pane.x() + x_in_terminal + 1,
+                        self.pane.cursor_shape_csi()
+                    ); // goto row/col
+                    output.add_post_vte_instruction_to_client(*client_id, show_cursor);
+                    output.add_post_vte_instruction_to_client(*client_id, goto_cursor_position);
+                },
+                None => {
+                    let hide_cursor = "\u{1b}[?25l";
+                    output.add_post_vte_instruction_to_client(*client_id, hide_cursor);
+                },
+            }
+        }
+    }
+    fn resize_pty(&self, os_api: &dyn ServerOsApi) -> Result<()> {
+        match self.pane.pid() {
+            PaneId::Terminal(terminal_id) => os_api.set_terminal_size_using_terminal_id(
+                terminal_id,
+                self.pane.get_content_columns() as u16,
+                self.pane.get_content_rows() as u16,
+            ),
+            PaneId::Plugin(_) => Ok(()),
+        }
+    }
+}
@@ -18,7 +18,7 @@ use zellij_utils::pane_size::Offset;
 use zellij_utils::{
     data::{InputMode, Palette, PaletteColor, Style},
     errors::prelude::*,
-    input::layout::Run,
+    input::layout::{FitToContent, Run},
     pane_size::PaneGeom,
     pane_size::SizeInPixels,
     position::Position,
@@ -105,10 +105,17 @@ pub struct TerminalPane {
     prev_pane_name: String,
     frame: HashMap<ClientId, PaneFrame>,
     borderless: bool,
+    hide_title: bool, // draw the frame without the title, eg. if it contains secrets
+    read_only: bool,  // drop the input sent to this pane, eg. if it's part of a dashboard
+    exclude_from_dump: bool, // what the pane runs is left out of what plugins are told about it
+    pinned: bool,     // shown even when the floating panes are hidden
+    height_fit: Option<FitToContent>, // resize to the output once the command exits
+    width_fit: Option<FitToContent>,
     fake_cursor_locations: HashSet<(usize, usize)>, // (x, y) - these hold a record of previous fake cursors which we need to clear on render
     search_term: String,
     is_held: Option<(Option<i32>, IsFirstRun, RunCommand)>, // a "held" pane means that its command has either exited and the pane is waiting for a
     // possible user instruction to be re-run, or that the command has not yet been run
+    queued: bool, // the command of this held pane waits for its turn to be run by its tab
     banner: Option<String>, // a banner to be rendered inside this TerminalPane, used for panes
     // held on startup and can possibly be used to display some errors
     pane_frame_color_override: Option<(PaletteColor, Option<String>)>,
@@ -349,6 +356,8 @@ impl Pane for TerminalPane {
                 modifier_text.push(']');
             }
             format!("SEARCHING: {}{}", self.search_term, modifier_text)
+        } else if self.hide_title {
+            String::new()
         } else if self.pane_name.is_empty() {
             self.grid
                 .title
@@ -374,6 +383,9 @@ impl Pane for TerminalPane {
         if let Some((exit_status, is_first_run, _run_command)) = &self.is_held {
             if *is_first_run {
                 frame.indicate_first_run();
+                if self.queued {
+                    frame.indicate_queued();
+                }
             } else {
                 frame.add_exit_status(exit_status.as_ref().copied());
             }
@@ -381,6 +393,9 @@ impl Pane for TerminalPane {
         if let Some((frame_color_override, _text)) = self.pane_frame_color_override.as_ref() {
             frame.override_color(*frame_color_override);
         }
+        if self.read_only {
+            frame.indicate_read_only();
+        }
 
         let res = match self.frame.get(&client_id) {
             // TODO: use and_then or something?
@@ -604,6 +619,44 @@ impl Pane for TerminalPane {
     fn borderless(&self) -> bool {
         self.borderless
     }
+    fn set_hide_title(&mut self, hide_title: bool) {
+        self.hide_title = hide_title;
+    }
+    fn hide_title(&self) -> bool {
+        self.hide_title
+    }
+    fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+    fn set_exclude_from_dump(&mut self, exclude_from_dump: bool) {
+        self.exclude_from_dump = exclude_from_dump;
+    }
+    fn exclude_from_dump(&self) -> bool {
+        self.exclude_from_dump
+    }
+    fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+    fn pinned(&self) -> bool {
+        self.pinned
+    }
+    fn set_fit_to_content(
+        &mut self,
+        height_fit: Option<FitToContent>,
+        width_fit: Option<FitToContent>,
+    ) {
+        self.height_fit = height_fit;
+        self.width_fit = width_fit;
+    }
+    fn fit_to_content(&self) -> (Option<FitToContent>, Option<FitToContent>) {
+        (self.height_fit, self.width_fit)
+    }
+    fn content_size(&self) -> Option<(usize, usize)> {
+        Some(self.grid.content_size())
+    }
 
     fn mouse_left_click(&self, position: &Position, is_held: bool) -> Option<String> {
         self.grid.mouse_left_click_signal(position, is_held)
@@ -697,6 +750,17 @@ impl Pane for TerminalPane {
         }
         self.set_should_render(true);
     }
+    fn is_held(&self) -> bool {
+        self.is_held.is_some()
+    }
+    fn set_queued(&mut self, queued: bool) {
+        self.queued = queued;
+        self.set_should_render(true);
+    }
+    fn is_queued(&self) -> bool {
+        let is_first_run = matches!(self.is_held, Some((_, true, _)));
+        self.queued && is_first_run
+    }
     fn add_red_pane_frame_color_override(&mut self, error_text: Option<String>) {
         self.pane_frame_color_override = Some((self.style.colors.red, error_text));
     }
@@ -714,6 +778,9 @@ impl Pane for TerminalPane {
     fn set_title(&mut self, title: String) {
         self.pane_title = title;
     }
+    fn pane_name(&self) -> &str {
+        &self.pane_name
+    }
 }
 
 impl TerminalPane {
@@ -759,9 +826,16 @@ impl TerminalPane {
             pane_name: pane_name.clone(),
             prev_pane_name: pane_name,
             borderless: false,
+            hide_title: false,
+            read_only: false,
+            exclude_from_dump: false,
+            pinned: false,
+            height_fit: None,
+            width_fit: None,
             fake_cursor_locations: HashSet::new(),
             search_term: String::new(),
             is_held: None,
+            queued: false,
             banner: None,
             pane_frame_color_override: None,
             invoked_with,
@@ -21,7 +21,7 @@ use zellij_utils::{
     data::{ModeInfo, ResizeStrategy, Style},
     errors::prelude::*,
     input::{command::RunCommand, layout::SplitDirection},
-    pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
+    pane_size::{Dimension, Offset, PaneGeom, Size, SizeInPixels, Viewport},
 };
 
 use std::{
@@ -50,6 +50,16 @@ fn pane_content_offset(position_and_size: &PaneGeom, viewport: &Viewport) -> (us
     (columns_offset, rows_offset)
 }
 
+/// A stack with more panes than rows (see [`TiledPaneLayout::deep_stacks`]): only two of its
+/// panes are laid out, the expanded one and a one-liner standing for the others, which are kept
+/// here until stack navigation reaches them
+///
+/// [`TiledPaneLayout::deep_stacks`]: zellij_utils::input::layout::TiledPaneLayout::deep_stacks
+struct DeepStack {
+    members: Vec<PaneId>, // in stack order, including the two panes that are laid out
+    hidden: HashMap<PaneId, Box<dyn Pane>>,
+}
+
 pub struct TiledPanes {
     pub panes: BTreeMap<PaneId, Box<dyn Pane>>,
     display_area: Rc<RefCell<Size>>,
@@ -69,6 +79,7 @@ pub struct TiledPanes {
     senders: ThreadSenders,
     window_title: Option<String>,
     client_id_to_boundaries: HashMap<ClientId, Boundaries>,
+    deep_stacks: Vec<DeepStack>,
 }
 
 impl TiledPanes {
@@ -106,6 +117,7 @@ impl TiledPanes {
             senders,
             window_title: None,
             client_id_to_boundaries: HashMap::new(),
+            deep_stacks: vec![],
         }
     }
     pub fn add_pane_with_existing_geom(&mut self, pane_id: PaneId, mut pane: Box<dyn Pane>) {
@@ -207,6 +219,15 @@ impl TiledPanes {
             })
             .collect()
     }
+    /// Moves all panes `columns` to the right (or to the left when negative), eg. to recenter the
+    /// content of a width constrained tab
+    pub fn move_panes_horizontally(&mut self, columns: isize) {
+        for pane in self.panes.values_mut() {
+            let mut geom = pane.current_geom();
+            geom.x = (geom.x as isize + columns).max(0) as usize;
+            pane.set_geom(geom);
+        }
+    }
     pub fn borderless_pane_geoms(&self) -> Vec<Viewport> {
         self.panes
             .values()
@@ -238,7 +259,7 @@ impl TiledPanes {
             *self.viewport.borrow(),
         );
         match direction {
-            SplitDirection::Horizontal => {
+            SplitDirection::Horizontal | SplitDirection::Auto => {
                 pane_grid.layout(direction, (*self.display_area.borrow()).cols)
             },
             SplitDirection::Vertical => {
@@ -411,6 +432,9 @@ impl TiledPanes {
         for client_id in connected_clients {
             match &self.active_panes.get(&client_id).copied() {
                 Some(pane_id) => {
+                    if self.is_hidden_in_deep_stack(pane_id) {
+                        self.show_in_deep_stack(*pane_id);
+                    }
                     if self
                         .panes
                         .get(&pane_id)
@@ -451,6 +475,9 @@ impl TiledPanes {
         self.reapply_pane_frames();
     }
     pub fn focus_pane(&mut self, pane_id: PaneId, client_id: ClientId) {
+        if self.is_hidden_in_deep_stack(&pane_id) {
+            self.show_in_deep_stack(pane_id);
+        }
         if self
             .panes
             .get(&pane_id)
@@ -526,16 +553,26 @@ impl TiledPanes {
     // than me should clean this up someday...
     #[allow(clippy::borrowed_box)]
     pub fn get_pane(&self, pane_id: PaneId) -> Option<&Box<dyn Pane>> {
-        self.panes.get(&pane_id)
+        self.panes.get(&pane_id).or_else(|| {
+            self.deep_stacks
+                .iter()
+                .find_map(|deep_stack| deep_stack.hidden.get(&pane_id))
+        })
     }
     pub fn get_pane_mut(&mut self, pane_id: PaneId) -> Option<&mut Box<dyn Pane>> {
-        self.panes.get_mut(&pane_id)
+        match self.panes.get_mut(&pane_id) {
+            Some(pane) => Some(pane),
+            None => self
+                .deep_stacks
+                .iter_mut()
+                .find_map(|deep_stack| deep_stack.hidden.get_mut(&pane_id)),
+        }
     }
     pub fn get_active_pane_id(&self, client_id: ClientId) -> Option<PaneId> {
         self.active_panes.get(&client_id).copied()
     }
     pub fn panes_contain(&self, pane_id: &PaneId) -> bool {
-        self.panes.contains_key(pane_id)
+        self.panes.contains_key(pane_id) || self.is_hidden_in_deep_stack(pane_id)
     }
     pub fn set_force_render(&mut self) {
         for pane in self.panes.values_mut() {
@@ -573,6 +610,19 @@ impl TiledPanes {
                 .stacked_pane_ids_under_and_over_flexible_panes()
                 .unwrap() // TODO: no unwrap
         };
+        let more_panes_in_deep_stacks: HashMap<PaneId, usize> = self
+            .deep_stacks
+            .iter()
+            .filter_map(|deep_stack| {
+                let one_liner = deep_stack.members.iter().find(|pane_id| {
+                    self.panes
+                        .get(*pane_id)
+                        .map(|pane| !pane.current_geom().rows.is_percent())
+                        .unwrap_or(false)
+                })?;
+                Some((*one_liner, deep_stack.hidden.len()))
+            })
+            .collect();
         for (kind, pane) in self.panes.iter_mut() {
             if !self.panes_to_hide.contains(&pane.pid()) {
                 let pane_is_stacked_under =
@@ -581,6 +631,7 @@ impl TiledPanes {
                     stacked_pane_ids_over_flexible_pane.contains(&pane.pid());
                 let should_draw_pane_frames = self.draw_pane_frames;
                 let pane_is_stacked = pane.current_geom().is_stacked;
+                let more_panes_in_stack = more_panes_in_deep_stacks.get(&pane.pid()).copied();
                 let mut pane_contents_and_ui = PaneContentsAndUi::new(
                     pane,
                     output,
@@ -592,6 +643,9 @@ impl TiledPanes {
                     pane_is_stacked_over,
                     should_draw_pane_frames,
                 );
+                if let Some(count) = more_panes_in_stack {
+                    pane_contents_and_ui.indicate_more_panes_in_stack(count);
+                }
                 for client_id in &connected_clients {
                     let client_mode = self
                         .mode_info
@@ -894,6 +948,13 @@ impl TiledPanes {
         }
     }
     pub fn move_focus_down(&mut self, client_id: ClientId) -> bool {
+        if let Some(next_pane_id) = self
+            .get_active_pane_id(client_id)
+            .and_then(|active_pane_id| self.next_in_deep_stack(active_pane_id, false))
+        {
+            self.focus_pane(next_pane_id, client_id);
+            return true;
+        }
         match self.get_active_pane_id(client_id) {
             Some(active_pane_id) => {
                 let mut pane_grid = TiledPaneGrid::new(
@@ -948,6 +1009,13 @@ impl TiledPanes {
         }
     }
     pub fn move_focus_up(&mut self, client_id: ClientId) -> bool {
+        if let Some(next_pane_id) = self
+            .get_active_pane_id(client_id)
+            .and_then(|active_pane_id| self.next_in_deep_stack(active_pane_id, true))
+        {
+            self.focus_pane(next_pane_id, client_id);
+            return true;
+        }
         match self.get_active_pane_id(client_id) {
             Some(active_pane_id) => {
                 let mut pane_grid = TiledPaneGrid::new(
@@ -1043,6 +1111,9 @@ impl TiledPanes {
         }
     }
     pub fn switch_active_pane_with(&mut self, pane_id: PaneId) {
+        if self.is_hidden_in_deep_stack(&pane_id) {
+            self.show_in_deep_stack(pane_id);
+        }
         if let Some(active_pane_id) = self.first_active_pane_id() {
             if let PaneId::Plugin(_) = active_pane_id {
                 // we do not implicitly change the location of plugin panes
@@ -1316,10 +1387,16 @@ impl TiledPanes {
         }
     }
     pub fn extract_pane(&mut self, pane_id: PaneId) -> Option<Box<dyn Pane>> {
+        if let Some(pane) = self.remove_from_deep_stack(pane_id) {
+            return Some(pane);
+        }
         self.reset_boundaries();
         self.panes.remove(&pane_id)
     }
     pub fn remove_pane(&mut self, pane_id: PaneId) -> Option<Box<dyn Pane>> {
+        if let Some(pane) = self.remove_from_deep_stack(pane_id) {
+            return Some(pane);
+        }
         let mut pane_grid = TiledPaneGrid::new(
             &mut self.panes,
             &self.panes_to_hide,
@@ -1468,7 +1545,22 @@ impl TiledPanes {
         self.panes_to_hide.len()
     }
     pub fn visible_panes_count(&self) -> usize {
-        self.panes.len().saturating_sub(self.panes_to_hide.len())
+        // the panes of deep stacks that are not laid out still have their place in the layout
+        (self.panes.len() + self.panes_hidden_in_deep_stacks_count())
+            .saturating_sub(self.panes_to_hide.len())
+    }
+    /// Like [`TiledPanes::visible_panes_count`], but with each stack of panes counting as one
+    pub fn visible_groups_count(&self) -> usize {
+        let mut stacks = HashSet::new();
+        self.panes
+            .iter()
+            .filter(|(pane_id, _pane)| !self.panes_to_hide.contains(pane_id))
+            .filter(|(_pane_id, pane)| {
+                let geom = pane.position_and_size();
+                // stacked panes are told apart by the column span of their stack
+                !geom.is_stacked || stacks.insert((geom.x, geom.cols.as_usize()))
+            })
+            .count()
     }
     pub fn add_to_hidden_panels(&mut self, pid: PaneId) {
         self.panes_to_hide.insert(pid);
@@ -1483,10 +1575,194 @@ impl TiledPanes {
         self.active_panes.focus_all_panes(&mut self.panes);
     }
     pub fn drain(&mut self) -> BTreeMap<PaneId, Box<dyn Pane>> {
-        match self.panes.iter().next().map(|(pid, _p)| *pid) {
+        let mut panes = match self.panes.iter().next().map(|(pid, _p)| *pid) {
             Some(first_pid) => self.panes.split_off(&first_pid),
             None => BTreeMap::new(),
+        };
+        for deep_stack in self.deep_stacks.drain(..) {
+            panes.extend(deep_stack.hidden);
+        }
+        panes
+    }
+    /// Makes a deep stack of `members` (in stack order), which were all just laid out by a layout
+    /// (see [`TiledPaneLayout::deep_stacks`]). Only its last pane, which is expanded, and the
+    /// one-liner above it stay laid out.
+    ///
+    /// [`TiledPaneLayout::deep_stacks`]: zellij_utils::input::layout::TiledPaneLayout::deep_stacks
+    pub fn add_deep_stack(&mut self, members: Vec<PaneId>) {
+        let hidden_count = members.len().saturating_sub(2);
+        let hidden = members[..hidden_count]
+            .iter()
+            .filter_map(|pane_id| self.panes.remove(pane_id).map(|pane| (*pane_id, pane)))
+            .collect();
+        self.deep_stacks.push(DeepStack { members, hidden });
+        self.reset_boundaries();
+    }
+    pub fn panes_hidden_in_deep_stacks_count(&self) -> usize {
+        self.deep_stacks
+            .iter()
+            .map(|deep_stack| deep_stack.hidden.len())
+            .sum()
+    }
+    fn is_hidden_in_deep_stack(&self, pane_id: &PaneId) -> bool {
+        self.deep_stacks
+            .iter()
+            .any(|deep_stack| deep_stack.hidden.contains_key(pane_id))
+    }
+    /// The pane above (or below) `pane_id` in its deep stack, if `pane_id` is the expanded pane
+    /// of a deep stack
+    fn next_in_deep_stack(&self, pane_id: PaneId, search_up: bool) -> Option<PaneId> {
+        let pane_is_expanded = self
+            .panes
+            .get(&pane_id)
+            .map(|pane| pane.current_geom().is_stacked && pane.current_geom().rows.is_percent())
+            .unwrap_or(false);
+        if !pane_is_expanded {
+            return None;
         }
+        let deep_stack = self
+            .deep_stacks
+            .iter()
+            .find(|deep_stack| deep_stack.members.contains(&pane_id))?;
+        let position = deep_stack.members.iter().position(|p| *p == pane_id)?;
+        if search_up {
+            position
+                .checked_sub(1)
+                .and_then(|position| deep_stack.members.get(position))
+                .copied()
+        } else {
+            deep_stack.members.get(position + 1).copied()
+        }
+    }
+    /// Lays out `pane_id` as the expanded pane of its deep stack, along with the one-liner of
+    /// the pane above it (or below it, for the first pane of the stack). The other panes of the
+    /// stack are taken out of the layout.
+    fn show_in_deep_stack(&mut self, pane_id: PaneId) {
+        let deep_stack = match self
+            .deep_stacks
+            .iter_mut()
+            .find(|deep_stack| deep_stack.members.contains(&pane_id))
+        {
+            Some(deep_stack) => deep_stack,
+            None => return,
+        };
+        let laid_out: Vec<PaneId> = deep_stack
+            .members
+            .iter()
+            .filter(|member| self.panes.contains_key(*member))
+            .copied()
+            .collect();
+        let laid_out_geoms: Vec<PaneGeom> = laid_out
+            .iter()
+            .filter_map(|member| self.panes.get(member))
+            .map(|pane| pane.position_and_size())
+            .collect();
+        let expanded_geom = match laid_out_geoms.iter().find(|geom| geom.rows.is_percent()) {
+            Some(expanded_geom) => *expanded_geom,
+            None => return,
+        };
+        let top = laid_out_geoms.iter().map(|geom| geom.y).min().unwrap_or(0);
+        let rows: usize = laid_out_geoms.iter().map(|geom| geom.rows.as_usize()).sum();
+        let position = match deep_stack.members.iter().position(|p| *p == pane_id) {
+            Some(position) => position,
+            None => return,
+        };
+        // the one-liner is the pane we would move to when leaving the expanded pane upwards,
+        // unless it's the first pane and there's nothing left above it in the stack
+        let (one_liner_id, expanded_geom, one_liner_geom) = match position.checked_sub(1) {
+            Some(above) => {
+                let mut expanded = PaneGeom {
+                    y: top + 1,
+                    ..expanded_geom
+                };
+                expanded.rows.set_inner(rows.saturating_sub(1));
+                let one_liner = PaneGeom {
+                    y: top,
+                    rows: Dimension::fixed(1),
+                    ..expanded_geom
+                };
+                (deep_stack.members[above], expanded, one_liner)
+            },
+            None => {
+                let mut expanded = PaneGeom {
+                    y: top,
+                    ..expanded_geom
+                };
+                expanded.rows.set_inner(rows.saturating_sub(1));
+                let one_liner = PaneGeom {
+                    y: top + rows.saturating_sub(1),
+                    rows: Dimension::fixed(1),
+                    ..expanded_geom
+                };
+                (deep_stack.members[1], expanded, one_liner)
+            },
+        };
+        let mut taken_out = vec![];
+        for member in laid_out {
+            if member != pane_id && member != one_liner_id {
+                if let Some(pane) = self.panes.remove(&member) {
+                    deep_stack.hidden.insert(member, pane);
+                    taken_out.push(member);
+                }
+            }
+        }
+        for (member, geom) in [(pane_id, expanded_geom), (one_liner_id, one_liner_geom)] {
+            if let Some(mut pane) = self
+                .panes
+                .remove(&member)
+                .or_else(|| deep_stack.hidden.remove(&member))
+            {
+                pane.set_geom(geom);
+                pane.set_should_render(true);
+                pane.render_full_viewport();
+                self.panes.insert(member, pane);
+            }
+        }
+        for member in taken_out {
+            self.move_clients_between_panes(member, pane_id);
+        }
+        // resizes the ptys of the panes we just laid out
+        self.set_pane_frames(self.draw_pane_frames);
+    }
+    /// Removes a pane of a deep stack, replacing it with a pane of the stack that was not laid
+    /// out if it was. Returns None for panes that are not part of a deep stack.
+    fn remove_from_deep_stack(&mut self, pane_id: PaneId) -> Option<Box<dyn Pane>> {
+        let stack_index = self
+            .deep_stacks
+            .iter()
+            .position(|deep_stack| deep_stack.members.contains(&pane_id))?;
+        let deep_stack = &mut self.deep_stacks[stack_index];
+        let position = deep_stack.members.iter().position(|p| *p == pane_id)?;
+        deep_stack.members.remove(position);
+        let removed_pane = match deep_stack.hidden.remove(&pane_id) {
+            Some(pane) => Some(pane),
+            None => {
+                // the hidden pane closest to the removed one in the stack takes its place
+                let replacement = deep_stack
+                    .members
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, member)| deep_stack.hidden.contains_key(*member))
+                    .min_by_key(|(i, _)| (*i as isize - position as isize).abs())
+                    .map(|(_, member)| *member);
+                let mut removed_pane = self.panes.remove(&pane_id);
+                if let (Some(replacement), Some(removed_pane)) = (replacement, &mut removed_pane) {
+                    if let Some(mut pane) = deep_stack.hidden.remove(&replacement) {
+                        pane.set_geom(removed_pane.position_and_size());
+                        self.panes.insert(replacement, pane);
+                    }
+                    self.move_clients_between_panes(pane_id, replacement);
+                }
+                removed_pane
+            },
+        };
+        if self.deep_stacks[stack_index].hidden.is_empty() {
+            // all of its panes are laid out, this is a regular stack now
+            self.deep_stacks.remove(stack_index);
+        }
+        self.set_pane_frames(self.draw_pane_frames);
+        self.set_force_render();
+        removed_pane
     }
     pub fn active_panes(&self) -> ActivePanes {
         self.active_panes.clone()
@@ -113,7 +113,9 @@ impl<'a> PaneResizer<'a> {
 
         // Update span positions based on their rounded sizes
         for spans in &mut grid {
-            let mut offset = 0;
+            // the panes keep starting where they did, which is not the edge of the screen in tabs
+            // with a constrained width
+            let mut offset = spans.first().map(|s| s.pos).unwrap_or(0);
             for span in spans {
                 span.pos = offset;
                 let sz = rounded_sizes[&span.size_var];
@@ -144,7 +146,7 @@ impl<'a> PaneResizer<'a> {
                     .position_and_size_of_stack(&span.pid)
                     .unwrap();
                 let new_geom = match span.direction {
-                    SplitDirection::Horizontal => PaneGeom {
+                    SplitDirection::Horizontal | SplitDirection::Auto => PaneGeom {
                         x: span.pos,
                         cols: span.size,
                         ..current_geom
@@ -167,7 +169,7 @@ impl<'a> PaneResizer<'a> {
                 let pane = panes.get_mut(&span.pid).unwrap();
                 let current_geom = pane.position_and_size();
                 let new_geom = match span.direction {
-                    SplitDirection::Horizontal => PaneGeom {
+                    SplitDirection::Horizontal | SplitDirection::Auto => PaneGeom {
                         x: span.pos,
                         cols: span.size,
                         ..pane.current_geom()
@@ -262,7 +264,7 @@ impl<'a> PaneResizer<'a> {
         }?;
         let size_var = *self.vars.get(&pane.pid()).unwrap();
         match direction {
-            SplitDirection::Horizontal => Some(Span {
+            SplitDirection::Horizontal | SplitDirection::Auto => Some(Span {
                 pid: pane.pid(),
                 direction,
                 pos: position_and_size.x,
@@ -25,6 +25,17 @@ fn no_pane_id(pane_id: &PaneId) -> String {
     format!("no floating pane with ID {:?} found", pane_id)
 }
 
+fn cant_resize_fixed_panes(fixed_panes: &[PaneId]) -> ZellijError {
+    let pane_ids = fixed_panes
+        .iter()
+        .map(|pane_id| match pane_id {
+            PaneId::Terminal(id) => (*id, true),
+            PaneId::Plugin(id) => (*id, false),
+        })
+        .collect();
+    ZellijError::CantResizeFixedPanes { pane_ids }
+}
+
 pub struct TiledPaneGrid<'a> {
     panes: Rc<RefCell<HashMap<PaneId, &'a mut Box<dyn Pane>>>>,
     display_area: Size, // includes all panes (including eg. the status bar and tab bar in the default layout)
@@ -75,7 +86,7 @@ impl<'a> TiledPaneGrid<'a> {
             .with_context(err_context)?;
         Ok(!match direction {
             SplitDirection::Vertical => pane_geom.rows,
-            SplitDirection::Horizontal => pane_geom.cols,
+            SplitDirection::Horizontal | SplitDirection::Auto => pane_geom.cols,
         }
         .is_fixed())
     }
@@ -141,37 +152,22 @@ impl<'a> TiledPaneGrid<'a> {
         let err_context = || format!("failed to determine if pane {pane_id:?} can {strategy}");
 
         if let Some(direction) = strategy.direction {
-            if !self
-                .pane_is_flexible(direction.into(), pane_id)
-                .unwrap_or(false)
+            if !strategy.include_fixed
+                && !self
+                    .pane_is_flexible(direction.into(), pane_id)
+                    .unwrap_or(false)
             {
-                let pane_ids = match pane_id {
-                    PaneId::Terminal(id) => vec![(*id, true)],
-                    PaneId::Plugin(id) => vec![(*id, false)],
-                };
-                return Err(ZellijError::CantResizeFixedPanes { pane_ids })
-                    .with_context(err_context);
+                return Err(cant_resize_fixed_panes(&[*pane_id])).with_context(err_context);
             }
             let pane_ids = self
                 .neighbor_pane_ids(pane_id, direction)
                 .with_context(err_context)?;
-
-            let fixed_panes: Vec<PaneId> = pane_ids
-                .iter()
-                .filter(|p| !self.pane_is_flexible(direction.into(), p).unwrap_or(false))
-                .copied()
-                .collect();
-            if !fixed_panes.is_empty() {
-                let mut pane_ids = vec![];
-                for fixed_pane in fixed_panes {
-                    match fixed_pane {
-                        PaneId::Terminal(id) => pane_ids.push((id, true)),
-                        PaneId::Plugin(id) => pane_ids.push((id, false)),
-                    };
-                }
-                return Err(ZellijError::CantResizeFixedPanes { pane_ids })
-                    .with_context(err_context);
-            }
+            let pane_ids = if strategy.include_fixed {
+                pane_ids
+            } else {
+                self.nearest_flexible_pane_ids(pane_ids, direction)
+                    .with_context(err_context)?
+            };
             if pane_ids.is_empty() {
                 // TODO: proper error
                 return Ok(false);
@@ -182,7 +178,11 @@ impl<'a> TiledPaneGrid<'a> {
                     Resize::Increase => {
                         for id in pane_ids {
                             if !self
-                                .can_reduce_pane_width(&id, change_by.0 as f64)
+                                .can_reduce_pane_width(
+                                    &id,
+                                    change_by.0 as f64,
+                                    strategy.include_fixed,
+                                )
                                 .with_context(err_context)?
                             {
                                 return Ok(false);
@@ -191,7 +191,7 @@ impl<'a> TiledPaneGrid<'a> {
                         Ok(true)
                     },
                     Resize::Decrease => self
-                        .can_reduce_pane_width(pane_id, change_by.0 as f64)
+                        .can_reduce_pane_width(pane_id, change_by.0 as f64, strategy.include_fixed)
                         .with_context(err_context),
                 }
             } else {
@@ -199,7 +199,11 @@ impl<'a> TiledPaneGrid<'a> {
                     Resize::Increase => {
                         for id in pane_ids {
                             if !self
-                                .can_reduce_pane_height(&id, change_by.1 as f64)
+                                .can_reduce_pane_height(
+                                    &id,
+                                    change_by.1 as f64,
+                                    strategy.include_fixed,
+                                )
                                 .with_context(err_context)?
                             {
                                 return Ok(false);
@@ -208,7 +212,7 @@ impl<'a> TiledPaneGrid<'a> {
                         Ok(true)
                     },
                     Resize::Decrease => self
-                        .can_reduce_pane_height(pane_id, change_by.1 as f64)
+                        .can_reduce_pane_height(pane_id, change_by.1 as f64, strategy.include_fixed)
                         .with_context(err_context),
                 }
             }
@@ -231,6 +235,7 @@ impl<'a> TiledPaneGrid<'a> {
         // Shorthand
         use Direction as Dir;
         let mut fixed_panes_blocking_resize = vec![];
+        let include_fixed = strategy.include_fixed;
 
         // Default behavior is to only increase pane size, unless the direction being resized to is
         // a boundary. In this case, decrease size from the other side (invert strategy)!
@@ -328,6 +333,12 @@ impl<'a> TiledPaneGrid<'a> {
                     self.pane_is_between_vertical_borders(t, some_borders, other_borders)
                 }
             });
+            let neighbor_terminals = if strategy.include_fixed {
+                neighbor_terminals
+            } else {
+                self.nearest_flexible_pane_ids(neighbor_terminals, direction)
+                    .with_context(err_context)?
+            };
 
             // Perform the resize
             let change_by = match direction {
@@ -340,37 +351,37 @@ impl<'a> TiledPaneGrid<'a> {
                     .iter()
                     .chain(&some_terminals)
                     .chain(&other_terminals)
-                    .for_each(|pane| self.increase_pane_width(pane, change_by));
+                    .for_each(|pane| self.increase_pane_width(pane, change_by, include_fixed));
                 neighbor_terminals
                     .iter()
-                    .for_each(|pane| self.reduce_pane_width(pane, change_by));
+                    .for_each(|pane| self.reduce_pane_width(pane, change_by, include_fixed));
             } else if strategy.resize_increase() && direction.is_vertical() {
                 [*pane_id]
                     .iter()
                     .chain(&some_terminals)
                     .chain(&other_terminals)
-                    .for_each(|pane| self.increase_pane_height(pane, change_by));
+                    .for_each(|pane| self.increase_pane_height(pane, change_by, include_fixed));
                 neighbor_terminals
                     .iter()
-                    .for_each(|pane| self.reduce_pane_height(pane, change_by));
+                    .for_each(|pane| self.reduce_pane_height(pane, change_by, include_fixed));
             } else if strategy.resize_decrease() && direction.is_horizontal() {
                 [*pane_id]
                     .iter()
                     .chain(&some_terminals)
                     .chain(&other_terminals)
-                    .for_each(|pane| self.reduce_pane_width(pane, change_by));
+                    .for_each(|pane| self.reduce_pane_width(pane, change_by, include_fixed));
                 neighbor_terminals
                     .iter()
-                    .for_each(|pane| self.increase_pane_width(pane, change_by));
+                    .for_each(|pane| self.increase_pane_width(pane, change_by, include_fixed));
             } else if strategy.resize_decrease() && direction.is_vertical() {
                 [*pane_id]
                     .iter()
                     .chain(&some_terminals)
                     .chain(&other_terminals)
-                    .for_each(|pane| self.reduce_pane_height(pane, change_by));
+                    .for_each(|pane| self.reduce_pane_height(pane, change_by, include_fixed));
                 neighbor_terminals
                     .iter()
-                    .for_each(|pane| self.increase_pane_height(pane, change_by));
+                    .for_each(|pane| self.increase_pane_height(pane, change_by, include_fixed));
             } else {
                 return Err(anyhow!(
                     "Don't know how to perform resize operation: '{strategy}'"
@@ -513,7 +524,12 @@ impl<'a> TiledPaneGrid<'a> {
         Ok(true)
     }
 
-    fn can_reduce_pane_width(&self, pane_id: &PaneId, reduce_by: f64) -> Result<bool> {
+    fn can_reduce_pane_width(
+        &self,
+        pane_id: &PaneId,
+        reduce_by: f64,
+        include_fixed: bool,
+    ) -> Result<bool> {
         let err_context =
             || format!("failed to determine if pane {pane_id:?} can reduce width by {reduce_by} %");
 
@@ -528,10 +544,15 @@ impl<'a> TiledPaneGrid<'a> {
         } else if let Some(cols) = pane.cols.as_percent() {
             Ok(cols - reduce_by >= RESIZE_PERCENT)
         } else {
-            Ok(false)
+            Ok(include_fixed)
         }
     }
-    fn can_reduce_pane_height(&self, pane_id: &PaneId, reduce_by: f64) -> Result<bool> {
+    fn can_reduce_pane_height(
+        &self,
+        pane_id: &PaneId,
+        reduce_by: f64,
+        include_fixed: bool,
+    ) -> Result<bool> {
         let err_context = || {
             format!("failed to determine if pane {pane_id:?} can reduce height by {reduce_by} %")
         };
@@ -552,21 +573,20 @@ impl<'a> TiledPaneGrid<'a> {
         } else if let Some(rows) = pane.rows.as_percent() {
             Ok(rows - reduce_by >= RESIZE_PERCENT)
         } else {
-            Ok(false)
+            Ok(include_fixed)
         }
     }
 
-    fn reduce_pane_height(&mut self, id: &PaneId, percent: f64) {
-        if self.can_reduce_pane_height(id, percent).unwrap() {
-            let current_pane_is_stacked = self
-                .panes
-                .borrow()
-                .get(id)
-                .unwrap()
-                .current_geom()
-                .is_stacked;
-            if current_pane_is_stacked {
+    fn reduce_pane_height(&mut self, id: &PaneId, percent: f64, include_fixed: bool) {
+        if self
+            .can_reduce_pane_height(id, percent, include_fixed)
+            .unwrap()
+        {
+            let current_geom = self.panes.borrow().get(id).unwrap().current_geom();
+            if current_geom.is_stacked {
                 let _ = StackedPanes::new(self.panes.clone()).reduce_stack_height(&id, percent);
+            } else if current_geom.rows.is_fixed() {
+                self.change_fixed_pane_size(id, SplitDirection::Vertical, -percent);
             } else {
                 let mut panes = self.panes.borrow_mut();
                 let terminal = panes.get_mut(id).unwrap();
@@ -574,49 +594,40 @@ impl<'a> TiledPaneGrid<'a> {
             }
         }
     }
-    fn increase_pane_height(&mut self, id: &PaneId, percent: f64) {
-        let current_pane_is_stacked = self
-            .panes
-            .borrow()
-            .get(id)
-            .unwrap()
-            .current_geom()
-            .is_stacked;
-        if current_pane_is_stacked {
+    fn increase_pane_height(&mut self, id: &PaneId, percent: f64, include_fixed: bool) {
+        let current_geom = self.panes.borrow().get(id).unwrap().current_geom();
+        if current_geom.is_stacked {
             let _ = StackedPanes::new(self.panes.clone()).increase_stack_height(&id, percent);
+        } else if include_fixed && current_geom.rows.is_fixed() {
+            self.change_fixed_pane_size(id, SplitDirection::Vertical, percent);
         } else {
             let mut panes = self.panes.borrow_mut();
             let terminal = panes.get_mut(id).unwrap();
             terminal.increase_height(percent);
         }
     }
-    fn increase_pane_width(&mut self, id: &PaneId, percent: f64) {
-        let current_pane_is_stacked = self
-            .panes
-            .borrow()
-            .get(id)
-            .unwrap()
-            .current_geom()
-            .is_stacked;
-        if current_pane_is_stacked {
+    fn increase_pane_width(&mut self, id: &PaneId, percent: f64, include_fixed: bool) {
+        let current_geom = self.panes.borrow().get(id).unwrap().current_geom();
+        if current_geom.is_stacked {
             let _ = StackedPanes::new(self.panes.clone()).increase_stack_width(&id, percent);
+        } else if include_fixed && current_geom.cols.is_fixed() {
+            self.change_fixed_pane_size(id, SplitDirection::Horizontal, percent);
         } else {
             let mut panes = self.panes.borrow_mut();
             let pane = panes.get_mut(id).unwrap();
             pane.increase_width(percent);
         }
     }
-    fn reduce_pane_width(&mut self, id: &PaneId, percent: f64) {
-        if self.can_reduce_pane_width(id, percent).unwrap() {
-            let current_pane_is_stacked = self
-                .panes
-                .borrow()
-                .get(id)
-                .unwrap()
-                .current_geom()
-                .is_stacked;
-            if current_pane_is_stacked {
+    fn reduce_pane_width(&mut self, id: &PaneId, percent: f64, include_fixed: bool) {
+        if self
+            .can_reduce_pane_width(id, percent, include_fixed)
+            .unwrap()
+        {
+            let current_geom = self.panes.borrow().get(id).unwrap().current_geom();
+            if current_geom.is_stacked {
                 let _ = StackedPanes::new(self.panes.clone()).reduce_stack_width(&id, percent);
+            } else if current_geom.cols.is_fixed() {
+                self.change_fixed_pane_size(id, SplitDirection::Horizontal, -percent);
             } else {
                 let mut panes = self.panes.borrow_mut();
                 let terminal = panes.get_mut(id).unwrap();
@@ -624,6 +635,65 @@ impl<'a> TiledPaneGrid<'a> {
             }
         }
     }
+    /// Changes the size of a pane with a fixed size by as many columns (or rows) as `percent` of
+    /// the display area takes up. The pane stays fixed at its new size.
+    fn change_fixed_pane_size(&mut self, id: &PaneId, direction: SplitDirection, percent: f64) {
+        let mut panes = self.panes.borrow_mut();
+        let pane = panes.get_mut(id).unwrap();
+        let mut geom = pane.current_geom();
+        let (dimension, space) = match direction {
+            SplitDirection::Horizontal | SplitDirection::Auto => {
+                (&mut geom.cols, self.display_area.cols)
+            },
+            SplitDirection::Vertical => (&mut geom.rows, self.display_area.rows),
+        };
+        let change_by = ((space as f64 / 100.0) * percent) as isize;
+        let new_size = (dimension.as_usize() as isize + change_by).max(1) as usize;
+        *dimension = Dimension::fixed(new_size);
+        pane.set_geom(geom);
+    }
+    /// Walks past the panes in `pane_ids` that have a fixed size in `direction` until reaching the
+    /// nearest flexible panes, those are the ones giving up (or taking) the space of a resize.
+    ///
+    /// Fails with the fixed panes in the way if there are no flexible panes to reach.
+    fn nearest_flexible_pane_ids(
+        &self,
+        mut pane_ids: Vec<PaneId>,
+        direction: Direction,
+    ) -> Result<Vec<PaneId>> {
+        let err_context = || format!("failed to find flexible panes {direction}");
+
+        let mut fixed_panes = vec![];
+        loop {
+            let (flexible, fixed): (Vec<PaneId>, Vec<PaneId>) = pane_ids
+                .into_iter()
+                .partition(|p| self.pane_is_flexible(direction.into(), p).unwrap_or(false));
+            if fixed.is_empty() {
+                return Ok(flexible);
+            }
+            fixed_panes.extend(fixed.iter().copied());
+            if !flexible.is_empty() {
+                // the border we'd move is shared by fixed and flexible panes
+                break;
+            }
+            let mut panes_past_fixed = vec![];
+            for fixed_pane in &fixed {
+                for pane_id in self
+                    .neighbor_pane_ids(fixed_pane, direction)
+                    .with_context(err_context)?
+                {
+                    if !panes_past_fixed.contains(&pane_id) {
+                        panes_past_fixed.push(pane_id);
+                    }
+                }
+            }
+            if panes_past_fixed.is_empty() {
+                break;
+            }
+            pane_ids = panes_past_fixed;
+        }
+        Err(cant_resize_fixed_panes(&fixed_panes)).with_context(err_context)
+    }
 
     /// Return a vector of [`PaneId`]s directly adjacent to the given [`PaneId`], if any.
     ///
@@ -1231,14 +1301,14 @@ impl<'a> TiledPaneGrid<'a> {
         (width, height): (f64, f64),
     ) {
         match direction {
-            SplitDirection::Horizontal => {
+            SplitDirection::Horizontal | SplitDirection::Auto => {
                 for pane_id in panes {
-                    self.increase_pane_width(pane_id, width);
+                    self.increase_pane_width(pane_id, width, false);
                 }
             },
             SplitDirection::Vertical => {
                 for pane_id in panes {
-                    self.increase_pane_height(pane_id, height);
+                    self.increase_pane_height(pane_id, height, false);
                 }
             },
         };
@@ -1268,7 +1338,7 @@ impl<'a> TiledPaneGrid<'a> {
                 self.grow_panes(&panes_to_grow, direction, (freed_width, freed_height));
                 let side_length = match direction {
                     SplitDirection::Vertical => self.display_area.rows,
-                    SplitDirection::Horizontal => self.display_area.cols,
+                    SplitDirection::Horizontal | SplitDirection::Auto => self.display_area.cols,
                 };
                 self.panes.borrow_mut().remove(&id);
                 let mut pane_resizer = PaneResizer::new(self.panes.clone());
@@ -1325,9 +1395,10 @@ impl<'a> TiledPaneGrid<'a> {
 }
 
 pub fn split(direction: SplitDirection, rect: &PaneGeom) -> Option<(PaneGeom, PaneGeom)> {
+    let direction = direction.resolve(rect);
     let space = match direction {
         SplitDirection::Vertical => rect.cols,
-        SplitDirection::Horizontal => rect.rows,
+        SplitDirection::Horizontal | SplitDirection::Auto => rect.rows,
     };
     if let Some(p) = space.as_percent() {
         let first_rect = match direction {
@@ -1335,7 +1406,7 @@ pub fn split(direction: SplitDirection, rect: &PaneGeom) -> Option<(PaneGeom, Pa
                 cols: Dimension::percent(p / 2.0),
                 ..*rect
             },
-            SplitDirection::Horizontal => PaneGeom {
+            SplitDirection::Horizontal | SplitDirection::Auto => PaneGeom {
                 rows: Dimension::percent(p / 2.0),
                 ..*rect
             },
@@ -1346,7 +1417,7 @@ pub fn split(direction: SplitDirection, rect: &PaneGeom) -> Option<(PaneGeom, Pa
                 cols: first_rect.cols,
                 ..*rect
             },
-            SplitDirection::Horizontal => PaneGeom {
+            SplitDirection::Horizontal | SplitDirection::Auto => PaneGeom {
                 y: first_rect.y + 1,
                 rows: first_rect.rows,
                 ..*rect
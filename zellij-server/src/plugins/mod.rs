@@ -3,7 +3,7 @@ use log::info;
 use std::{collections::HashMap, fs, path::PathBuf};
 use wasmer::Store;
 
-use crate::{pty::PtyInstruction, thread_bus::Bus, ClientId};
+use crate::{pty::PtyInstruction, screen::ScreenInstruction, thread_bus::Bus, ClientId};
 
 use wasm_bridge::WasmBridge;
 
@@ -112,13 +112,34 @@ pub(crate) fn plugin_thread_main(
                     .map(|f| f.run.clone())
                     .collect();
                 extracted_run_instructions.append(&mut extracted_floating_plugins);
+                let mut load_error = None;
                 for run_instruction in extracted_run_instructions {
                     if let Some(Run::Plugin(run)) = run_instruction {
-                        let plugin_id =
-                            wasm_bridge.load_plugin(&run, tab_index, size, client_id)?;
-                        plugin_ids.entry(run.location).or_default().push(plugin_id);
+                        match wasm_bridge.load_plugin(&run, tab_index, size, client_id) {
+                            Ok(plugin_id) => {
+                                plugin_ids.entry(run.location).or_default().push(plugin_id)
+                            },
+                            Err(e) => {
+                                load_error = Some(e);
+                                break;
+                            },
+                        }
                     }
                 }
+                if let Some(e) = load_error {
+                    // the tab is not spawned, the screen decides what becomes of it
+                    for plugin_id in plugin_ids.values().flatten() {
+                        wasm_bridge.unload_plugin(*plugin_id)?;
+                    }
+                    drop(
+                        bus.senders
+                            .send_to_screen(ScreenInstruction::LayoutTabFailed(
+                                tab_index,
+                                format!("{:#}", e),
+                            )),
+                    );
+                    continue;
+                }
                 drop(bus.senders.send_to_pty(PtyInstruction::NewTab(
                     terminal_action,
                     tab_layout,
@@ -1,5 +1,7 @@
 use crate::terminal_bytes::TerminalBytes;
 use crate::{
+    background_jobs::BackgroundJob,
+    os_input_output::open_file_command,
     panes::PaneId,
     plugins::PluginInstruction,
     screen::ScreenInstruction,
@@ -7,21 +9,33 @@ use crate::{
     ClientId, ServerInstruction,
 };
 use async_std::task::{self, JoinHandle};
-use std::{collections::HashMap, env, os::unix::io::RawFd, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    os::unix::io::RawFd,
+    path::PathBuf,
+    time::Duration,
+};
 use zellij_utils::nix::unistd::Pid;
 use zellij_utils::{
-    async_std,
+    async_std, envs,
     errors::prelude::*,
     errors::{ContextType, PtyContext},
     input::{
-        command::{RunCommand, TerminalAction},
-        layout::{FloatingPaneLayout, Layout, Run, RunPluginLocation, TiledPaneLayout},
+        command::{CommandWrapper, PlaceholderValues, RunCommand, TerminalAction},
+        layout::{
+            FloatingPaneLayout, Layout, Run, RunPluginLocation, SidePanelLayout, TiledPaneLayout,
+        },
+        options::PaneTitleFormats,
     },
 };
 
 pub type VteBytes = Vec<u8>;
 pub type TabIndex = u32;
 
+// how long a command stopped to be re-run has to exit before it is killed
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(3);
+
 #[derive(Clone, Copy, Debug)]
 pub enum ClientOrTabIndex {
     ClientId(ClientId),
@@ -59,6 +73,11 @@ pub enum PtyInstruction {
     ClosePane(PaneId),
     CloseTab(Vec<PaneId>),
     ReRunCommandInPane(PaneId, RunCommand),
+    KillCommandInPane(PaneId),
+    TerminateCommandInPane(PaneId), // force killed if it did not exit after a timeout
+    ForceKillCommandInPane(PaneId, RawFd), // RawFd is the child that was asked to terminate
+    RespawnShellInPane(PaneId),
+    SpawnSidePanel(SidePanelLayout, ClientId),
     Exit,
 }
 
@@ -75,6 +94,11 @@ impl From<&PtyInstruction> for PtyContext {
             PtyInstruction::CloseTab(_) => PtyContext::CloseTab,
             PtyInstruction::NewTab(..) => PtyContext::NewTab,
             PtyInstruction::ReRunCommandInPane(..) => PtyContext::ReRunCommandInPane,
+            PtyInstruction::KillCommandInPane(..) => PtyContext::KillCommandInPane,
+            PtyInstruction::TerminateCommandInPane(..) => PtyContext::TerminateCommandInPane,
+            PtyInstruction::ForceKillCommandInPane(..) => PtyContext::ForceKillCommandInPane,
+            PtyInstruction::RespawnShellInPane(..) => PtyContext::RespawnShellInPane,
+            PtyInstruction::SpawnSidePanel(..) => PtyContext::SpawnSidePanel,
             PtyInstruction::Exit => PtyContext::Exit,
         }
     }
@@ -87,6 +111,34 @@ pub(crate) struct Pty {
     debug_to_file: bool,
     task_handles: HashMap<u32, JoinHandle<()>>, // terminal_id to join-handle
     default_editor: Option<PathBuf>,
+    pane_title_formats: PaneTitleFormats,
+    command_spawn_interval: Option<Duration>, // layout command panes are queued when set
+    command_wrapper: Option<CommandWrapper>,  // the session layout's wrapper
+}
+
+/// Expands the placeholders of a layout pane's command now that all their values are known, a
+/// command with unknown placeholders in strict mode is held rather than run
+fn expand_placeholders(
+    run_instruction: Option<Run>,
+    pane_name: Option<String>,
+    tab_name: Option<String>,
+) -> Option<Run> {
+    match run_instruction {
+        Some(Run::Command(mut run_command)) => {
+            let placeholder_values = PlaceholderValues {
+                pane_name,
+                cwd: run_command.cwd.clone().or_else(|| env::current_dir().ok()),
+                tab_name,
+                session_name: envs::get_session_name().ok(),
+            };
+            if let Err(e) = run_command.expand_placeholders(&placeholder_values) {
+                log::error!("{}", e);
+                run_command.hold_on_start = true;
+            }
+            Some(Run::Command(run_command))
+        },
+        run_instruction => run_instruction,
+    }
 }
 
 pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
@@ -103,14 +155,13 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                 let err_context =
                     || format!("failed to spawn terminal for {:?}", client_or_tab_index);
 
-                let (hold_on_close, run_command, pane_title) = match &terminal_action {
-                    Some(TerminalAction::RunCommand(run_command)) => (
-                        run_command.hold_on_close,
-                        Some(run_command.clone()),
-                        Some(name.unwrap_or_else(|| run_command.to_string())),
-                    ),
-                    _ => (false, None, name),
+                let (hold_on_close, run_command) = match &terminal_action {
+                    Some(TerminalAction::RunCommand(run_command)) => {
+                        (run_command.hold_on_close, Some(run_command.clone()))
+                    },
+                    _ => (false, None),
                 };
+                let pane_title = name.or_else(|| pty.pane_title(terminal_action.as_ref()));
                 match pty
                     .spawn_terminal(terminal_action, client_or_tab_index)
                     .with_context(err_context)
@@ -165,7 +216,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     || format!("failed to open in-place editor for client {}", client_id);
 
                 match pty.spawn_terminal(
-                    Some(TerminalAction::OpenFile(temp_file, line_number)),
+                    Some(TerminalAction::OpenFile(vec![temp_file], line_number, None)),
                     ClientOrTabIndex::ClientId(client_id),
                 ) {
                     Ok((pid, _starts_held)) => {
@@ -186,14 +237,13 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                 let err_context =
                     || format!("failed to spawn terminal vertically for client {client_id}");
 
-                let (hold_on_close, run_command, pane_title) = match &terminal_action {
-                    Some(TerminalAction::RunCommand(run_command)) => (
-                        run_command.hold_on_close,
-                        Some(run_command.clone()),
-                        Some(name.unwrap_or_else(|| run_command.to_string())),
-                    ),
-                    _ => (false, None, name),
+                let (hold_on_close, run_command) = match &terminal_action {
+                    Some(TerminalAction::RunCommand(run_command)) => {
+                        (run_command.hold_on_close, Some(run_command.clone()))
+                    },
+                    _ => (false, None),
                 };
+                let pane_title = name.or_else(|| pty.pane_title(terminal_action.as_ref()));
                 match pty
                     .spawn_terminal(terminal_action, ClientOrTabIndex::ClientId(client_id))
                     .with_context(err_context)
@@ -256,14 +306,13 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                 let err_context =
                     || format!("failed to spawn terminal horizontally for client {client_id}");
 
-                let (hold_on_close, run_command, pane_title) = match &terminal_action {
-                    Some(TerminalAction::RunCommand(run_command)) => (
-                        run_command.hold_on_close,
-                        Some(run_command.clone()),
-                        Some(name.unwrap_or_else(|| run_command.to_string())),
-                    ),
-                    _ => (false, None, name),
+                let (hold_on_close, run_command) = match &terminal_action {
+                    Some(TerminalAction::RunCommand(run_command)) => {
+                        (run_command.hold_on_close, Some(run_command.clone()))
+                    },
+                    _ => (false, None),
                 };
+                let pane_title = name.or_else(|| pty.pane_title(terminal_action.as_ref()));
                 match pty
                     .spawn_terminal(terminal_action, ClientOrTabIndex::ClientId(client_id))
                     .with_context(err_context)
@@ -354,6 +403,7 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     floating_panes_layout,
                     terminal_action.clone(),
                     plugin_ids,
+                    tab_name.clone(),
                     tab_index,
                     client_id,
                 )
@@ -392,9 +442,97 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
                     })
                     .context("failed to close tabs")?;
             },
+            PtyInstruction::KillCommandInPane(pane_id) => {
+                pty.kill_command_in_pane(pane_id)
+                    .with_context(|| format!("failed to kill command in pane {:?}", pane_id))?;
+            },
+            PtyInstruction::TerminateCommandInPane(pane_id) => {
+                pty.terminate_command_in_pane(pane_id).with_context(|| {
+                    format!("failed to terminate command in pane {:?}", pane_id)
+                })?;
+            },
+            PtyInstruction::ForceKillCommandInPane(pane_id, child_fd) => {
+                pty.force_kill_command_in_pane(pane_id, child_fd)
+                    .with_context(|| format!("failed to kill command in pane {:?}", pane_id))?;
+            },
+            PtyInstruction::RespawnShellInPane(pane_id) => {
+                let err_context = || format!("failed to respawn shell in pane {:?}", pane_id);
+                if let TerminalAction::RunCommand(run_command) = pty.get_default_terminal(None) {
+                    pty.rerun_command_in_pane(pane_id, run_command)
+                        .with_context(err_context)?;
+                }
+            },
+            PtyInstruction::SpawnSidePanel(side_panel_layout, client_id) => {
+                let err_context = || format!("failed to spawn side panel for client {client_id}");
+                let run = pty.wrap_run_instruction(
+                    side_panel_layout.pane.run.clone(),
+                    side_panel_layout.pane.no_wrapper,
+                );
+                let terminal_action = match &run {
+                    Some(Run::Command(run_command)) => {
+                        Some(TerminalAction::RunCommand(run_command.clone()))
+                    },
+                    Some(Run::EditFile(paths_to_files, line_number, editor_splits)) => {
+                        Some(TerminalAction::OpenFile(
+                            paths_to_files.clone(),
+                            *line_number,
+                            *editor_splits,
+                        ))
+                    },
+                    Some(Run::Cwd(cwd)) => Some(pty.get_default_terminal(Some(cwd.clone()))),
+                    Some(Run::Plugin(_)) | None => None,
+                };
+                let run_command = match &terminal_action {
+                    Some(TerminalAction::RunCommand(run_command)) => Some(run_command.clone()),
+                    _ => None,
+                };
+                match pty
+                    .spawn_terminal(terminal_action, ClientOrTabIndex::ClientId(client_id))
+                    .with_context(err_context)
+                {
+                    Ok((pid, starts_held)) => {
+                        let hold_for_command = if starts_held { run_command } else { None };
+                        pty.bus
+                            .senders
+                            .send_to_screen(ScreenInstruction::NewSidePanel(
+                                side_panel_layout,
+                                pid,
+                                hold_for_command,
+                            ))
+                            .with_context(err_context)?;
+                    },
+                    Err(err) => Err::<(), _>(err).non_fatal(),
+                }
+            },
             PtyInstruction::ReRunCommandInPane(pane_id, run_command) => {
                 let err_context = || format!("failed to rerun command in pane {:?}", pane_id);
 
+                if let (PaneId::Terminal(terminal_id), Err(e)) = (
+                    pane_id,
+                    // the placeholders of a command are expanded when it is first spawned, if
+                    // this failed the command is held and should not run with them
+                    run_command
+                        .clone()
+                        .expand_placeholders(&PlaceholderValues::default()),
+                ) {
+                    pty.bus
+                        .senders
+                        .send_to_screen(ScreenInstruction::PtyBytes(
+                            terminal_id,
+                            e.as_bytes().to_vec(),
+                        ))
+                        .with_context(err_context)?;
+                    pty.bus
+                        .senders
+                        .send_to_screen(ScreenInstruction::HoldPane(
+                            pane_id,
+                            Some(2), // exit status
+                            run_command,
+                            None,
+                        ))
+                        .with_context(err_context)?;
+                    continue;
+                }
                 match pty
                     .rerun_command_in_pane(pane_id, run_command.clone())
                     .with_context(err_context)
@@ -441,6 +579,9 @@ impl Pty {
         bus: Bus<PtyInstruction>,
         debug_to_file: bool,
         default_editor: Option<PathBuf>,
+        pane_title_formats: PaneTitleFormats,
+        command_spawn_interval: Option<Duration>,
+        command_wrapper: Option<CommandWrapper>,
     ) -> Self {
         Pty {
             active_panes: HashMap::new(),
@@ -449,8 +590,22 @@ impl Pty {
             debug_to_file,
             task_handles: HashMap::new(),
             default_editor,
+            pane_title_formats,
+            command_spawn_interval,
+            command_wrapper,
         }
     }
+    /// The title of a pane spawned to run `terminal_action`, without one the pane runs the default
+    /// shell which sets its own title
+    fn pane_title(&self, terminal_action: Option<&TerminalAction>) -> Option<String> {
+        let run = match terminal_action? {
+            TerminalAction::RunCommand(run_command) => Run::Command(run_command.clone()),
+            TerminalAction::OpenFile(paths_to_files, line_number, editor_splits) => {
+                Run::EditFile(paths_to_files.clone(), *line_number, *editor_splits)
+            },
+        };
+        Some(run.pane_title(&self.pane_title_formats))
+    }
     pub fn get_default_terminal(&self, cwd: Option<PathBuf>) -> TerminalAction {
         let shell = PathBuf::from(env::var("SHELL").unwrap_or_else(|_| {
             log::warn!("Cannot read SHELL env, falling back to use /bin/sh");
@@ -462,6 +617,10 @@ impl Pty {
             cwd, // note: this might also be filled by the calling function, eg. spawn_terminal
             hold_on_close: false,
             hold_on_start: false,
+            strict_placeholders: false,
+            depends_on: None,
+            encoding: None,
+            env: BTreeMap::new(),
         })
     }
     fn fill_cwd(&self, terminal_action: &mut TerminalAction, client_id: ClientId) {
@@ -503,11 +662,13 @@ impl Pty {
                 terminal_action.unwrap_or_else(|| self.get_default_terminal(None))
             },
         };
-        let (hold_on_start, hold_on_close) = match &terminal_action {
-            TerminalAction::RunCommand(run_command) => {
-                (run_command.hold_on_start, run_command.hold_on_close)
-            },
-            _ => (false, false),
+        let (hold_on_start, hold_on_close, encoding) = match &terminal_action {
+            TerminalAction::RunCommand(run_command) => (
+                run_command.hold_on_start,
+                run_command.hold_on_close,
+                run_command.encoding,
+            ),
+            _ => (false, false, None),
         };
 
         if hold_on_start {
@@ -560,11 +721,18 @@ impl Pty {
                 .clone();
             let debug_to_file = self.debug_to_file;
             async move {
-                TerminalBytes::new(pid_primary, senders, os_input, debug_to_file, terminal_id)
-                    .listen()
-                    .await
-                    .with_context(|| err_context(terminal_id))
-                    .fatal();
+                TerminalBytes::new(
+                    pid_primary,
+                    senders,
+                    os_input,
+                    debug_to_file,
+                    terminal_id,
+                    encoding,
+                )
+                .listen()
+                .await
+                .with_context(|| err_context(terminal_id))
+                .fatal();
             }
         });
 
@@ -579,6 +747,7 @@ impl Pty {
         floating_panes_layout: Vec<FloatingPaneLayout>,
         default_shell: Option<TerminalAction>,
         plugin_ids: HashMap<RunPluginLocation, Vec<u32>>,
+        tab_name: Option<String>,
         tab_index: usize,
         client_id: ClientId,
     ) -> Result<()> {
@@ -586,9 +755,26 @@ impl Pty {
 
         let mut default_shell = default_shell.unwrap_or_else(|| self.get_default_terminal(None));
         self.fill_cwd(&mut default_shell, client_id);
-        let extracted_run_instructions = layout.extract_run_instructions();
-        let extracted_floating_run_instructions =
-            floating_panes_layout.iter().map(|f| f.run.clone());
+        let extracted_run_instructions: Vec<Option<Run>> = layout
+            .extract_run_instructions_indexed()
+            .into_iter()
+            .zip(layout.extract_no_wrapper())
+            .map(|(run_instruction, no_wrapper)| {
+                let run = expand_placeholders(
+                    run_instruction.run,
+                    run_instruction.pane_name,
+                    tab_name.clone(),
+                );
+                self.wrap_run_instruction(run, no_wrapper)
+            })
+            .collect();
+        let extracted_floating_run_instructions: Vec<Option<Run>> = floating_panes_layout
+            .iter()
+            .map(|f| {
+                let run = expand_placeholders(f.run.clone(), f.name.clone(), tab_name.clone());
+                self.wrap_run_instruction(run, f.no_wrapper)
+            })
+            .collect();
         let mut new_pane_pids: Vec<(u32, bool, Option<RunCommand>, Result<RawFd>)> = vec![]; // (terminal_id,
                                                                                              // starts_held,
                                                                                              // run_command,
@@ -597,16 +783,19 @@ impl Pty {
             vec![]; // same
                     // as
                     // new_pane_pids
+        let mut first_command_spawned = false;
         for run_instruction in extracted_run_instructions {
+            let starts_queued = self.starts_queued(&run_instruction, &mut first_command_spawned);
             if let Some(new_pane_data) =
-                self.apply_run_instruction(run_instruction, default_shell.clone())?
+                self.apply_run_instruction(run_instruction, default_shell.clone(), starts_queued)?
             {
                 new_pane_pids.push(new_pane_data);
             }
         }
         for run_instruction in extracted_floating_run_instructions {
+            let starts_queued = self.starts_queued(&run_instruction, &mut first_command_spawned);
             if let Some(new_pane_data) =
-                self.apply_run_instruction(run_instruction, default_shell.clone())?
+                self.apply_run_instruction(run_instruction, default_shell.clone(), starts_queued)?
             {
                 new_floating_panes_pids.push(new_pane_data);
             }
@@ -663,6 +852,7 @@ impl Pty {
                             .with_context(err_context)?
                             .clone();
                         let debug_to_file = self.debug_to_file;
+                        let encoding = run_command.as_ref().and_then(|r| r.encoding);
                         async move {
                             TerminalBytes::new(
                                 pid_primary,
@@ -670,6 +860,7 @@ impl Pty {
                                 os_input,
                                 debug_to_file,
                                 terminal_id,
+                                encoding,
                             )
                             .listen()
                             .await
@@ -702,10 +893,50 @@ impl Pty {
         }
         Ok(())
     }
+    /// Runs the command or the editor of a layout pane under the layout's command wrapper, the
+    /// wrapper is spawned in the pane's cwd and plugins and shells are left alone
+    fn wrap_run_instruction(&self, run_instruction: Option<Run>, no_wrapper: bool) -> Option<Run> {
+        let command_wrapper = match &self.command_wrapper {
+            Some(command_wrapper) if !no_wrapper => command_wrapper,
+            _ => return run_instruction,
+        };
+        match run_instruction {
+            Some(Run::Command(run_command)) => {
+                Some(Run::Command(command_wrapper.wrap(run_command)))
+            },
+            Some(Run::EditFile(paths_to_files, line_number, editor_splits)) => {
+                let (open_file, _failover_args) = open_file_command(
+                    paths_to_files,
+                    line_number,
+                    editor_splits,
+                    self.default_editor.clone(),
+                );
+                Some(Run::Command(command_wrapper.wrap(open_file)))
+            },
+            run_instruction => run_instruction,
+        }
+    }
+    // with a command spawn interval, only the first command pane of a layout is spawned right away,
+    // its tab spawns the others one at a time
+    fn starts_queued(
+        &self,
+        run_instruction: &Option<Run>,
+        first_command_spawned: &mut bool,
+    ) -> bool {
+        match run_instruction {
+            Some(Run::Command(command))
+                if self.command_spawn_interval.is_some() && command.starts_without_waiting() =>
+            {
+                std::mem::replace(first_command_spawned, true)
+            },
+            _ => false,
+        }
+    }
     fn apply_run_instruction(
         &mut self,
         run_instruction: Option<Run>,
         default_shell: TerminalAction,
+        starts_queued: bool,
     ) -> Result<Option<(u32, bool, Option<RunCommand>, Result<i32>)>> {
         // terminal_id,
         // starts_held,
@@ -720,7 +951,10 @@ impl Pty {
         });
         match run_instruction {
             Some(Run::Command(command)) => {
-                let starts_held = command.hold_on_start;
+                // panes depending on another pane are started by their tab once it is ready, queued
+                // ones once it is their turn
+                let starts_held =
+                    command.hold_on_start || command.depends_on.is_some() || starts_queued;
                 let hold_on_close = command.hold_on_close;
                 let quit_cb = Box::new({
                     let senders = self.bus.senders.clone();
@@ -814,7 +1048,7 @@ impl Pty {
                     },
                 }
             },
-            Some(Run::EditFile(path_to_file, line_number)) => {
+            Some(Run::EditFile(paths_to_files, line_number, editor_splits)) => {
                 let starts_held = false; // we do not hold edit panes (for now?)
                 match self
                     .bus
@@ -823,7 +1057,7 @@ impl Pty {
                     .context("no OS I/O interface found")
                     .with_context(err_context)?
                     .spawn_terminal(
-                        TerminalAction::OpenFile(path_to_file, line_number),
+                        TerminalAction::OpenFile(paths_to_files, line_number, editor_splits),
                         quit_cb,
                         self.default_editor.clone(),
                     )
@@ -901,6 +1135,54 @@ impl Pty {
         }
         Ok(())
     }
+    pub fn kill_command_in_pane(&mut self, id: PaneId) -> Result<()> {
+        // unlike close_pane, the terminal is kept around so that another command can be run in it
+        // once this one exits
+        if let PaneId::Terminal(id) = id {
+            if let Some(child_fd) = self.id_to_child_pid.get(&id) {
+                self.bus
+                    .os_input
+                    .as_ref()
+                    .context("no OS I/O interface found")?
+                    .kill(Pid::from_raw(*child_fd))?;
+            }
+        }
+        Ok(())
+    }
+    /// Asks the command of a pane to exit, killing it if it is still running after
+    /// `TERMINATE_TIMEOUT`. The terminal is kept for another command to run in it
+    pub fn terminate_command_in_pane(&mut self, id: PaneId) -> Result<()> {
+        if let PaneId::Terminal(terminal_id) = id {
+            if let Some(child_fd) = self.id_to_child_pid.get(&terminal_id).copied() {
+                self.bus
+                    .os_input
+                    .as_ref()
+                    .context("no OS I/O interface found")?
+                    .terminate(Pid::from_raw(child_fd))?;
+                self.bus
+                    .senders
+                    .send_to_background_jobs(BackgroundJob::ForceKillCommandInPane(
+                        id,
+                        child_fd,
+                        TERMINATE_TIMEOUT,
+                    ))?;
+            }
+        }
+        Ok(())
+    }
+    pub fn force_kill_command_in_pane(&mut self, id: PaneId, child_fd: RawFd) -> Result<()> {
+        if let PaneId::Terminal(terminal_id) = id {
+            // the child exited in time if another one runs in the pane by now
+            if self.id_to_child_pid.get(&terminal_id) == Some(&child_fd) {
+                self.bus
+                    .os_input
+                    .as_ref()
+                    .context("no OS I/O interface found")?
+                    .force_kill(Pid::from_raw(child_fd))?;
+            }
+        }
+        Ok(())
+    }
     pub fn close_tab(&mut self, ids: Vec<PaneId>) -> Result<()> {
         for id in ids {
             self.close_pane(id)
@@ -926,6 +1208,7 @@ impl Pty {
                 let _ = self.id_to_child_pid.remove(&id); // if all is wlel, this shouldn't be here
 
                 let hold_on_close = run_command.hold_on_close;
+                let encoding = run_command.encoding;
                 let quit_cb = Box::new({
                     let senders = self.bus.senders.clone();
                     move |pane_id, exit_status, command| {
@@ -964,11 +1247,18 @@ impl Pty {
                         .clone();
                     let debug_to_file = self.debug_to_file;
                     async move {
-                        TerminalBytes::new(pid_primary, senders, os_input, debug_to_file, id)
-                            .listen()
-                            .await
-                            .with_context(|| err_context(pane_id))
-                            .fatal();
+                        TerminalBytes::new(
+                            pid_primary,
+                            senders,
+                            os_input,
+                            debug_to_file,
+                            id,
+                            encoding,
+                        )
+                        .listen()
+                        .await
+                        .with_context(|| err_context(pane_id))
+                        .fatal();
                     }
                 });
 
@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::path::Path;
 use std::sync::{Arc, RwLock};
 
 use crate::{
@@ -14,8 +15,9 @@ use zellij_utils::{
     errors::prelude::*,
     input::{
         actions::{Action, SearchDirection, SearchOption},
-        command::TerminalAction,
+        command::{command_exists, TerminalAction},
         get_mode_info,
+        layout::{apply_tab_command_conditions, check_max_layout_commands, tab_command_count},
     },
     ipc::{ClientToServerMsg, ExitReason, IpcReceiverWithContext, ServerToClientMsg},
 };
@@ -111,6 +113,16 @@ pub(crate) fn route_action(
                 .send_to_screen(screen_instr)
                 .with_context(err_context)?;
         },
+        Action::ResizeIncludingFixed(resize, direction) => {
+            let screen_instr = ScreenInstruction::Resize(
+                client_id,
+                ResizeStrategy::new(resize, direction).including_fixed_panes(true),
+            );
+            session
+                .senders
+                .send_to_screen(screen_instr)
+                .with_context(err_context)?;
+        },
         Action::SwitchFocus => {
             session
                 .senders
@@ -250,6 +262,12 @@ pub(crate) fn route_action(
                 .send_to_screen(ScreenInstruction::ToggleActiveTerminalFullscreen(client_id))
                 .with_context(err_context)?;
         },
+        Action::TogglePaneReadOnly => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::ToggleActivePaneReadOnly(client_id))
+                .with_context(err_context)?;
+        },
         Action::TogglePaneFrames => {
             session
                 .senders
@@ -286,7 +304,7 @@ pub(crate) fn route_action(
         },
         Action::EditFile(path_to_file, line_number, split_direction, should_float) => {
             let title = format!("Editing: {}", path_to_file.display());
-            let open_file = TerminalAction::OpenFile(path_to_file, line_number);
+            let open_file = TerminalAction::OpenFile(vec![path_to_file], line_number, None);
             let pty_instr = match (split_direction, should_float) {
                 (Some(Direction::Left), false) => {
                     PtyInstruction::SpawnTerminalVertically(Some(open_file), Some(title), client_id)
@@ -437,6 +455,18 @@ pub(crate) fn route_action(
                 .send_to_pty(pty_instr)
                 .with_context(err_context)?;
         },
+        Action::RunInPane(target_pane, command, any_tab, force) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::RunInPane(
+                    target_pane,
+                    command.into(),
+                    any_tab,
+                    force,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
         Action::CloseFocus => {
             session
                 .senders
@@ -444,12 +474,62 @@ pub(crate) fn route_action(
                 .with_context(err_context)?;
         },
         Action::NewTab(
-            tab_layout,
-            floating_panes_layout,
+            mut tab_layout,
+            mut floating_panes_layout,
             swap_tiled_layouts,
             swap_floating_layouts,
             tab_name,
+            template_name,
         ) => {
+            if let Some(template_name) = template_name {
+                match session.layout.new_tab_with_template(Some(&template_name)) {
+                    Ok((tiled_panes, floating_panes)) => {
+                        tab_layout = Some(tiled_panes);
+                        floating_panes_layout = floating_panes;
+                    },
+                    Err(e) => {
+                        log::error!("Refusing to open a new tab: {}", e);
+                        to_server
+                            .send(ServerInstruction::LogError(vec![e.to_string()], client_id))
+                            .with_context(err_context)?;
+                        // the client waits for the new tab before handling more input
+                        to_server
+                            .send(ServerInstruction::UnblockInputThread)
+                            .with_context(err_context)?;
+                        return Ok(should_break);
+                    },
+                }
+            }
+            if let Some(tab_layout) = tab_layout.as_mut() {
+                let condition_outcomes = apply_tab_command_conditions(
+                    tab_layout,
+                    &mut floating_panes_layout,
+                    |command| command_exists(Path::new(command), None),
+                );
+                for outcome in condition_outcomes {
+                    log::info!("{}", outcome.to_diagnostic());
+                }
+            }
+            // each new tab is its own opening of a layout, the commands of the tabs opened
+            // before it do not count towards the cap
+            let command_count = match &tab_layout {
+                Some(tab_layout) => tab_command_count(tab_layout, &floating_panes_layout),
+                None => {
+                    let (tiled_panes, floating_panes) = session.layout.new_tab();
+                    tab_command_count(&tiled_panes, &floating_panes)
+                },
+            };
+            if let Err(e) = check_max_layout_commands(command_count, session.max_layout_commands) {
+                log::error!("Refusing to open a new tab: {}", e);
+                to_server
+                    .send(ServerInstruction::LogError(vec![e.to_string()], client_id))
+                    .with_context(err_context)?;
+                // the client waits for the new tab before handling more input
+                to_server
+                    .send(ServerInstruction::UnblockInputThread)
+                    .with_context(err_context)?;
+                return Ok(should_break);
+            }
             let shell = session.default_shell.clone();
             let swap_tiled_layouts =
                 swap_tiled_layouts.unwrap_or_else(|| session.layout.swap_tiled_layouts.clone());
@@ -462,6 +542,8 @@ pub(crate) fn route_action(
                     tab_layout,
                     floating_panes_layout,
                     tab_name,
+                    None, // pin
+                    None, // max_content_width
                     (swap_tiled_layouts, swap_floating_layouts),
                     client_id,
                 ))
@@ -647,16 +729,64 @@ pub(crate) fn route_action(
                 .with_context(err_context)?;
         },
         Action::ToggleMouseMode => {}, // Handled client side
-        Action::PreviousSwapLayout => {
+        Action::PreviousSwapLayout(tag) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::PreviousSwapLayout(tag, client_id))
+                .with_context(err_context)?;
+        },
+        Action::NextSwapLayout(tag) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::NextSwapLayout(tag, client_id))
+                .with_context(err_context)?;
+        },
+        Action::RotateTabLayout { clockwise } => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::RotateTabLayout(clockwise, client_id))
+                .with_context(err_context)?;
+        },
+        Action::FlushCommandQueue => {
             session
                 .senders
-                .send_to_screen(ScreenInstruction::PreviousSwapLayout(client_id))
+                .send_to_screen(ScreenInstruction::FlushCommandQueue)
                 .with_context(err_context)?;
         },
-        Action::NextSwapLayout => {
+        Action::WriteToPane(bytes, target_pane, target_tab, all_matching) => {
             session
                 .senders
-                .send_to_screen(ScreenInstruction::NextSwapLayout(client_id))
+                .send_to_screen(ScreenInstruction::WriteToPane(
+                    bytes,
+                    target_pane,
+                    target_tab,
+                    all_matching,
+                    client_id,
+                ))
+                .with_context(err_context)?;
+        },
+        Action::RerunPane(target_pane, force) => {
+            session
+                .senders
+                .send_to_screen(ScreenInstruction::RerunPane(target_pane, force, client_id))
+                .with_context(err_context)?;
+        },
+        Action::DumpLayoutDebug => {
+            let layout_sources = &session.layout_sources;
+            let log_lines = vec![
+                format!("Layout: {}", layout_sources),
+                format!("Main layout: {}", layout_sources.main_layout),
+                format!(
+                    "Swap layouts: {}",
+                    layout_sources.swap_layouts.as_deref().unwrap_or("none")
+                ),
+                format!(
+                    "From default assets: {}",
+                    layout_sources.from_default_assets
+                ),
+            ];
+            to_server
+                .send(ServerInstruction::Log(log_lines, client_id))
                 .with_context(err_context)?;
         },
     }
@@ -794,6 +924,7 @@ pub(crate) fn route_thread_main(
                             cli_args,
                             opts,
                             layout,
+                            layout_sources,
                             plugin_config,
                         ) => {
                             let new_client_instruction = ServerInstruction::NewClient(
@@ -801,6 +932,7 @@ pub(crate) fn route_thread_main(
                                 cli_args,
                                 opts,
                                 layout,
+                                layout_sources,
                                 client_id,
                                 plugin_config,
                             );
@@ -830,6 +962,10 @@ pub(crate) fn route_thread_main(
                             let _ = to_server.send(ServerInstruction::ConnStatus(client_id));
                             should_break = true;
                         },
+                        ClientToServerMsg::SessionInfo => {
+                            let _ = to_server.send(ServerInstruction::SessionInfo(client_id));
+                            should_break = true;
+                        },
                         ClientToServerMsg::DetachSession(client_id) => {
                             let _ = to_server.send(ServerInstruction::DetachSession(client_id));
                             should_break = true;
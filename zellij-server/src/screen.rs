@@ -4,17 +4,20 @@ use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
 use std::str;
+use std::time::{Duration, Instant};
 
 use zellij_utils::data::{Direction, Resize, ResizeStrategy};
 use zellij_utils::errors::prelude::*;
 use zellij_utils::input::command::RunCommand;
-use zellij_utils::input::options::Clipboard;
+use zellij_utils::input::options::{Clipboard, OnLayoutFailure, PaneTitleFormats};
 use zellij_utils::pane_size::{Size, SizeInPixels};
 use zellij_utils::{
     input::command::TerminalAction,
     input::layout::{
-        FloatingPaneLayout, RunPluginLocation, SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
+        FloatingPaneLayout, FocusPolicy, LayoutSources, RunPluginLocation, SessionContext,
+        SidePanelLayout, SwapFloatingLayout, SwapTiledLayout, TabPin, TiledPaneLayout,
     },
+    input::layout_preflight::LayoutTabError,
     position::Position,
 };
 
@@ -22,9 +25,10 @@ use crate::panes::alacritty_functions::xparse_color;
 use crate::panes::terminal_character::AnsiCode;
 
 use crate::{
+    background_jobs::BackgroundJob,
     output::Output,
     panes::sixel::SixelImageStore,
-    panes::PaneId,
+    panes::{LinkHandler, PaneId, SidePanel, TerminalPane},
     plugins::PluginInstruction,
     pty::{ClientOrTabIndex, PtyInstruction, VteBytes},
     tab::Tab,
@@ -33,12 +37,19 @@ use crate::{
     ClientId, ServerInstruction,
 };
 use zellij_utils::{
-    data::{Event, InputMode, ModeInfo, Palette, PaletteColor, PluginCapabilities, Style, TabInfo},
+    data::{
+        Event, InputMode, ModeInfo, Palette, PaletteColor, PaneInfo, PluginCapabilities, Style,
+        TabInfo,
+    },
     errors::{ContextType, ScreenContext},
     input::{get_mode_info, options::Options},
     ipc::{ClientAttributes, PixelDimensions, ServerToClientMsg},
 };
 
+/// How often (in milliseconds) the swap layouts of the tabs are re-applied while the terminal is
+/// being resized, unless configured otherwise with `resize_relayout_interval_ms`
+const DEFAULT_RESIZE_RELAYOUT_INTERVAL_MS: u64 = 50;
+
 /// Get the active tab and call a closure on it
 ///
 /// If no active tab can be found, an error is logged instead.
@@ -172,6 +183,7 @@ pub enum ScreenInstruction {
     ClearScroll(ClientId),
     CloseFocusedPane(ClientId),
     ToggleActiveTerminalFullscreen(ClientId),
+    ToggleActivePaneReadOnly(ClientId),
     TogglePaneFrames,
     SetSelectable(PaneId, bool, usize),
     ClosePane(PaneId, Option<ClientId>),
@@ -183,6 +195,8 @@ pub enum ScreenInstruction {
         Option<TiledPaneLayout>,
         Vec<FloatingPaneLayout>,
         Option<String>,
+        Option<TabPin>,
+        Option<usize>,                                   // max_content_width
         (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>), // swap layouts
         ClientId,
     ),
@@ -195,6 +209,7 @@ pub enum ScreenInstruction {
         usize, // tab_index
         ClientId,
     ),
+    LayoutTabFailed(usize, String), // tab_index, the reason its layout could not be spawned
     SwitchTabNext(ClientId),
     SwitchTabPrev(ClientId),
     ToggleActiveSyncTab(ClientId),
@@ -240,8 +255,25 @@ pub enum ScreenInstruction {
     SearchToggleWrap(ClientId),
     AddRedPaneFrameColorOverride(Vec<PaneId>, Option<String>), // Option<String> => optional error text
     ClearPaneFrameColorOverride(Vec<PaneId>),
-    PreviousSwapLayout(ClientId),
-    NextSwapLayout(ClientId),
+    PreviousSwapLayout(Option<String>, ClientId), // optional tag to cycle among
+    NextSwapLayout(Option<String>, ClientId),     // optional tag to cycle among
+    RotateTabLayout(bool, ClientId),              // bool is clockwise
+    RunInPane(String, RunCommand, bool, bool, ClientId), // String is the target pane name or id,
+    // bools are: search all tabs, force
+    NewSidePanel(SidePanelLayout, u32, HoldForCommand), // u32 is the terminal id
+    PaneDependencyTimedOut(PaneId),                     // the pane waiting for its dependency
+    SpawnQueuedCommand(usize),                          // usize is the tab index
+    FlushCommandQueue,
+    RelayoutAfterResize,
+    ReloadSwapLayouts(
+        Vec<(
+            (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>), // the swap layouts before the reload
+            (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>), // and after it
+        )>,
+    ),
+    RerunPane(Option<String>, bool, ClientId), // the target pane name or id, force
+    WriteToPane(Vec<u8>, String, Option<String>, bool, ClientId), // the target pane name or id,
+                                               // the name of the tab to look in, write to all the matching panes
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
@@ -318,6 +350,9 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::ToggleActiveTerminalFullscreen(..) => {
                 ScreenContext::ToggleActiveTerminalFullscreen
             },
+            ScreenInstruction::ToggleActivePaneReadOnly(..) => {
+                ScreenContext::ToggleActivePaneReadOnly
+            },
             ScreenInstruction::TogglePaneFrames => ScreenContext::TogglePaneFrames,
             ScreenInstruction::SetSelectable(..) => ScreenContext::SetSelectable,
             ScreenInstruction::ClosePane(..) => ScreenContext::ClosePane,
@@ -326,6 +361,7 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::UndoRenamePane(..) => ScreenContext::UndoRenamePane,
             ScreenInstruction::NewTab(..) => ScreenContext::NewTab,
             ScreenInstruction::ApplyLayout(..) => ScreenContext::ApplyLayout,
+            ScreenInstruction::LayoutTabFailed(..) => ScreenContext::LayoutTabFailed,
             ScreenInstruction::SwitchTabNext(..) => ScreenContext::SwitchTabNext,
             ScreenInstruction::SwitchTabPrev(..) => ScreenContext::SwitchTabPrev,
             ScreenInstruction::CloseTab(..) => ScreenContext::CloseTab,
@@ -384,6 +420,16 @@ impl From<&ScreenInstruction> for ScreenContext {
             },
             ScreenInstruction::PreviousSwapLayout(..) => ScreenContext::PreviousSwapLayout,
             ScreenInstruction::NextSwapLayout(..) => ScreenContext::NextSwapLayout,
+            ScreenInstruction::RotateTabLayout(..) => ScreenContext::RotateTabLayout,
+            ScreenInstruction::RunInPane(..) => ScreenContext::RunInPane,
+            ScreenInstruction::NewSidePanel(..) => ScreenContext::NewSidePanel,
+            ScreenInstruction::PaneDependencyTimedOut(..) => ScreenContext::PaneDependencyTimedOut,
+            ScreenInstruction::SpawnQueuedCommand(..) => ScreenContext::SpawnQueuedCommand,
+            ScreenInstruction::FlushCommandQueue => ScreenContext::FlushCommandQueue,
+            ScreenInstruction::RelayoutAfterResize => ScreenContext::RelayoutAfterResize,
+            ScreenInstruction::RerunPane(..) => ScreenContext::RerunPane,
+            ScreenInstruction::WriteToPane(..) => ScreenContext::WriteToPane,
+            ScreenInstruction::ReloadSwapLayouts(..) => ScreenContext::ReloadSwapLayouts,
         }
     }
 }
@@ -445,8 +491,32 @@ pub(crate) struct Screen {
     style: Style,
     draw_pane_frames: bool,
     auto_layout: bool,
+    /// Whether interactive resizes leave the panes with a fixed size alone
+    respect_fixed_sizes: bool,
+    pane_title_formats: PaneTitleFormats,
+    /// How long the tabs wait between spawning the command panes of their layout
+    command_spawn_interval: Option<Duration>,
+    /// Which pane of a new tab is focused when its layout does not focus one
+    default_focus: Option<FocusPolicy>,
+    /// How often the swap layouts of the tabs are re-applied while the terminal is being resized,
+    /// `None` re-applies them on every resize
+    resize_relayout_interval: Option<Duration>,
+    last_resize_relayout: Option<Instant>,
+    resize_relayout_is_scheduled: bool,
     session_is_mirrored: bool,
     copy_options: CopyOptions,
+    /// Where this session's layout and swap layouts were loaded from, used when reporting errors.
+    layout_sources: LayoutSources,
+    /// A pane shared by all tabs, docked to the side of the screen.
+    side_panel: Option<SidePanel>,
+    /// Whether the tabs of the session's layout are kept when one of them fails to be created
+    on_layout_failure: OnLayoutFailure,
+    /// The number of tabs of the session's layout, and how many of them were opened so far
+    session_layout_tab_count: usize,
+    session_layout_tabs_opened: usize,
+    /// The tabs of the session's layout waiting for their layout to be applied, by tab index,
+    /// with their position in the layout and their name
+    session_layout_tabs_to_apply: HashMap<usize, (usize, Option<String>)>,
 }
 
 impl Screen {
@@ -458,8 +528,16 @@ impl Screen {
         mode_info: ModeInfo,
         draw_pane_frames: bool,
         auto_layout: bool,
+        respect_fixed_sizes: bool,
+        pane_title_formats: PaneTitleFormats,
+        command_spawn_interval: Option<Duration>,
+        default_focus: Option<FocusPolicy>,
+        resize_relayout_interval: Option<Duration>,
         session_is_mirrored: bool,
         copy_options: CopyOptions,
+        layout_sources: LayoutSources,
+        on_layout_failure: OnLayoutFailure,
+        session_layout_tab_count: usize,
     ) -> Self {
         Screen {
             bus,
@@ -480,8 +558,21 @@ impl Screen {
             default_mode_info: mode_info,
             draw_pane_frames,
             auto_layout,
+            respect_fixed_sizes,
+            pane_title_formats,
+            command_spawn_interval,
+            default_focus,
+            resize_relayout_interval,
+            last_resize_relayout: None,
+            resize_relayout_is_scheduled: false,
             session_is_mirrored,
             copy_options,
+            layout_sources,
+            side_panel: None,
+            on_layout_failure,
+            session_layout_tab_count,
+            session_layout_tabs_opened: 0,
+            session_layout_tabs_to_apply: HashMap::new(),
         }
     }
 
@@ -633,6 +724,7 @@ impl Screen {
                             .non_fatal();
                     }
 
+                    self.update_session_context();
                     self.update_tabs().with_context(err_context)?;
                     return self.render().with_context(err_context);
                 },
@@ -746,11 +838,113 @@ impl Screen {
                     t.position -= 1;
                 }
             }
+            self.sort_pinned_tabs();
+            self.update_session_context();
             self.update_tabs().with_context(err_context)?;
             self.render().with_context(err_context)
         }
     }
 
+    /// Remembers which tab of the session's layout a newly opened tab is, so that a failure to
+    /// apply its layout can be reported with the tab it was for
+    fn track_session_layout_tab(&mut self, tab_index: usize, tab_name: Option<String>) {
+        if self.session_layout_tabs_opened < self.session_layout_tab_count {
+            self.session_layout_tabs_to_apply
+                .insert(tab_index, (self.session_layout_tabs_opened, tab_name));
+            self.session_layout_tabs_opened += 1;
+        }
+    }
+
+    /// Either ends the session, tearing down the tabs its layout already created, or closes only
+    /// the tab that failed and keeps the others, according to `on_layout_failure`
+    fn fail_session_layout_tab(
+        &mut self,
+        tab_index: usize,
+        error: LayoutTabError,
+        new_pane_ids: Vec<PaneId>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to handle the failure of layout {}", error);
+        let message = format!("Failed to create the layout's {}", error);
+
+        match self.on_layout_failure {
+            OnLayoutFailure::Abort => self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::Error(message))
+                .with_context(err_context),
+            OnLayoutFailure::KeepPartial => {
+                log::error!("{}, keeping its other tabs", message);
+                self.remove_failed_tab(tab_index, new_pane_ids)
+                    .with_context(err_context)?;
+                let session_layout_is_done = self.session_layout_tabs_opened
+                    == self.session_layout_tab_count
+                    && self.session_layout_tabs_to_apply.is_empty();
+                if self.tabs.is_empty() && session_layout_is_done {
+                    // none of the tabs could be created, there is nothing to keep
+                    self.bus
+                        .senders
+                        .send_to_server(ServerInstruction::Error(message))
+                        .with_context(err_context)?;
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Removes a tab whose layout failed to be applied along with the panes spawned for it,
+    /// the clients that were moving to it are attached to another tab instead
+    fn remove_failed_tab(&mut self, tab_index: usize, new_pane_ids: Vec<PaneId>) -> Result<()> {
+        let err_context = || format!("failed to remove tab at index {tab_index:?}");
+
+        let mut pane_ids = new_pane_ids;
+        if let Some(failed_tab) = self.tabs.remove(&tab_index) {
+            for pane_id in failed_tab.get_all_pane_ids() {
+                if !pane_ids.contains(&pane_id) {
+                    pane_ids.push(pane_id);
+                }
+            }
+            for tab in self.tabs.values_mut() {
+                if tab.position > failed_tab.position {
+                    tab.position -= 1;
+                }
+            }
+        }
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::CloseTab(pane_ids))
+            .with_context(err_context)?;
+        let moving_client_ids: Vec<ClientId> = self
+            .active_tab_indices
+            .iter()
+            .filter(|(_client_id, active_tab_index)| **active_tab_index == tab_index)
+            .map(|(client_id, _active_tab_index)| *client_id)
+            .collect();
+        for client_id in &moving_client_ids {
+            self.active_tab_indices.remove(client_id);
+        }
+        for tab_history in self.tab_history.values_mut() {
+            tab_history.retain(|index| *index != tab_index);
+        }
+        if self.tabs.is_empty() {
+            // the clients are attached to the next tab of the layout once it is created
+            return Ok(());
+        }
+        for client_id in moving_client_ids {
+            self.add_client(client_id).with_context(err_context)?;
+        }
+        let visible_tab_indices: HashSet<usize> =
+            self.active_tab_indices.values().copied().collect();
+        for tab in self.tabs.values_mut() {
+            if visible_tab_indices.contains(&tab.index) {
+                tab.set_force_render();
+                tab.visible(true).with_context(err_context)?;
+            }
+        }
+        self.sort_pinned_tabs();
+        self.update_session_context();
+        self.update_tabs().with_context(err_context)
+    }
+
     // Closes the client_id's focused tab
     pub fn close_tab(&mut self, client_id: ClientId) -> Result<()> {
         let err_context = || format!("failed to close tab for client {client_id:?}");
@@ -778,14 +972,138 @@ impl Screen {
         let err_context = || format!("failed to resize to screen size: {new_screen_size:#?}");
 
         self.size = new_screen_size;
-        for tab in self.tabs.values_mut() {
-            tab.resize_whole_tab(new_screen_size)
+        if let Some(side_panel) = self.side_panel.as_mut() {
+            let os_input = self.bus.os_input.as_ref().with_context(err_context)?;
+            side_panel
+                .resize(new_screen_size, os_input.as_ref())
                 .with_context(err_context)?;
+        }
+        let tab_area = self.tab_area();
+        // re-applying the swap layouts is what makes resizing slow, so while the terminal is
+        // being resized it is done at most once per interval and only for the tabs clients are
+        // looking at, the others are re-applied once they are focused
+        let relayout_is_due = self.resize_relayout_is_due();
+        for tab in self.tabs.values_mut() {
+            if relayout_is_due && !tab.has_no_connected_clients() {
+                tab.resize_whole_tab(tab_area).with_context(err_context)?;
+            } else {
+                tab.resize_whole_tab_without_relayout(tab_area)
+                    .with_context(err_context)?;
+            }
             tab.set_force_render();
         }
+        if relayout_is_due {
+            self.last_resize_relayout = Some(Instant::now());
+        } else {
+            self.schedule_resize_relayout().with_context(err_context)?;
+        }
         self.render().with_context(err_context)
     }
 
+    fn resize_relayout_is_due(&self) -> bool {
+        match (self.resize_relayout_interval, self.last_resize_relayout) {
+            (Some(interval), Some(last_resize_relayout)) => {
+                last_resize_relayout.elapsed() >= interval
+            },
+            _ => true,
+        }
+    }
+
+    // makes sure the last size of a resize burst is re-applied once the interval is over
+    fn schedule_resize_relayout(&mut self) -> Result<()> {
+        if self.resize_relayout_is_scheduled {
+            return Ok(());
+        }
+        let (interval, last_resize_relayout) =
+            match (self.resize_relayout_interval, self.last_resize_relayout) {
+                (Some(interval), Some(last_resize_relayout)) => (interval, last_resize_relayout),
+                _ => return Ok(()),
+            };
+        self.resize_relayout_is_scheduled = true;
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::RelayoutAfterResize(
+                interval.saturating_sub(last_resize_relayout.elapsed()),
+            ))
+            .context("failed to schedule re-applying the swap layouts after a resize")
+    }
+
+    /// Re-applies the swap layouts of the tabs clients are looking at to the size the screen was
+    /// last resized to, see [`Screen::resize_to_screen`]
+    pub fn relayout_after_resize(&mut self) -> Result<()> {
+        self.resize_relayout_is_scheduled = false;
+        self.last_resize_relayout = Some(Instant::now());
+        for tab in self.tabs.values_mut() {
+            if !tab.has_no_connected_clients() {
+                tab.relayout_after_resize();
+            }
+        }
+        self.render()
+            .context("failed to re-apply the swap layouts after a resize")
+    }
+
+    /// The part of the screen the tabs are laid out in, this is all of it unless a side panel
+    /// takes up some of the space.
+    fn tab_area(&self) -> Size {
+        match &self.side_panel {
+            Some(side_panel) => side_panel.tab_area(self.size),
+            None => self.size,
+        }
+    }
+
+    pub fn new_side_panel(
+        &mut self,
+        side_panel_layout: SidePanelLayout,
+        pid: u32,
+        hold_for_command: HoldForCommand,
+    ) -> Result<()> {
+        let err_context = || format!("failed to create side panel with terminal id {pid}");
+        let invoked_with = side_panel_layout.pane.run.clone();
+        let pane_title = side_panel_layout.pane.name.clone().or_else(|| {
+            invoked_with
+                .as_ref()
+                .map(|run| run.pane_title(&self.pane_title_formats))
+        });
+        let link_handler = Rc::new(RefCell::new(LinkHandler::new()));
+        let pane = TerminalPane::new(
+            pid,
+            Default::default(), // the size is set when the panel is resized below
+            self.style,
+            0,
+            String::new(),
+            link_handler.clone(),
+            self.character_cell_size.clone(),
+            self.sixel_image_store.clone(),
+            self.terminal_emulator_colors.clone(),
+            self.terminal_emulator_color_codes.clone(),
+            pane_title,
+            invoked_with,
+        );
+        let mut side_panel = SidePanel::new(side_panel_layout, Box::new(pane), link_handler);
+        if let Some(run_command) = hold_for_command {
+            let is_first_run = true;
+            side_panel.hold(None, is_first_run, run_command);
+        }
+        self.side_panel = Some(side_panel);
+        self.resize_to_screen(self.size).with_context(err_context)
+    }
+
+    pub fn close_side_panel(&mut self) -> Result<()> {
+        let err_context = || "failed to close side panel";
+        if let Some(side_panel) = self.side_panel.take() {
+            self.bus
+                .senders
+                .send_to_pty(PtyInstruction::ClosePane(side_panel.pid()))
+                .with_context(err_context)?;
+            // we force render the active tabs so that their cursors are moved out of the panel
+            for tab in self.tabs.values_mut() {
+                tab.set_force_render();
+            }
+            self.resize_to_screen(self.size).with_context(err_context)?;
+        }
+        Ok(())
+    }
+
     pub fn update_pixel_dimensions(&mut self, pixel_dimensions: PixelDimensions) {
         self.pixel_dimensions.merge(pixel_dimensions);
         if let Some(character_cell_size) = self.pixel_dimensions.character_cell_size {
@@ -837,6 +1155,34 @@ impl Screen {
         let mut tabs_to_close = vec![];
         let size = self.size;
         let overlay = self.overlay.clone();
+        if let Some(side_panel) = self.side_panel.as_mut() {
+            // the panel is rendered before the tabs so that they place the cursor back in their
+            // active pane if the panel changed
+            if self.tabs.values().any(|tab| tab.will_clear_display()) {
+                side_panel.render_full_viewport();
+            }
+            let client_modes: Vec<(ClientId, InputMode)> = self
+                .connected_clients
+                .borrow()
+                .iter()
+                .map(|client_id| {
+                    let mode = self
+                        .mode_info
+                        .get(client_id)
+                        .unwrap_or(&self.default_mode_info)
+                        .mode;
+                    (*client_id, mode)
+                })
+                .collect();
+            side_panel
+                .render(
+                    &mut output,
+                    self.style,
+                    &client_modes,
+                    self.session_is_mirrored,
+                )
+                .context(err_context)?;
+        }
         for (tab_index, tab) in &mut self.tabs {
             if tab.has_selectable_tiled_panes() {
                 let vte_overlay = overlay.generate_overlay(size).context(err_context)?;
@@ -846,6 +1192,9 @@ impl Screen {
                 tabs_to_close.push(*tab_index);
             }
         }
+        if let Some(side_panel) = self.side_panel.as_ref() {
+            side_panel.render_cursor(&mut output);
+        }
         for tab_index in tabs_to_close {
             self.close_tab_at_index(tab_index).context(err_context)?;
         }
@@ -924,6 +1273,8 @@ impl Screen {
         &mut self,
         tab_index: usize,
         swap_layouts: (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+        pin: Option<TabPin>,
+        max_content_width: Option<usize>,
         client_id: ClientId,
     ) -> Result<()> {
         let err_context = || format!("failed to create new tab for client {client_id:?}",);
@@ -937,11 +1288,11 @@ impl Screen {
         };
 
         let position = self.tabs.len();
-        let tab = Tab::new(
+        let mut tab = Tab::new(
             tab_index,
             position,
             String::new(),
-            self.size,
+            self.tab_area(),
             self.character_cell_size.clone(),
             self.sixel_image_store.clone(),
             self.bus
@@ -963,9 +1314,24 @@ impl Screen {
             self.terminal_emulator_color_codes.clone(),
             swap_layouts,
         );
+        tab.pin = pin;
+        tab.max_content_width = max_content_width;
+        tab.pane_title_formats = self.pane_title_formats.clone();
+        tab.command_spawn_interval = self.command_spawn_interval;
+        tab.default_focus = self.default_focus;
         self.tabs.insert(tab_index, tab);
+        self.sort_pinned_tabs();
         Ok(())
     }
+    /// Moves the tabs pinned to the first or last position back to their edge, the relative order
+    /// of all other tabs is kept
+    fn sort_pinned_tabs(&mut self) {
+        let mut tabs: Vec<&mut Tab> = self.tabs.values_mut().collect();
+        tabs.sort_by_key(|tab| (TabPin::rank(tab.pin), tab.position));
+        for (position, tab) in tabs.into_iter().enumerate() {
+            tab.position = position;
+        }
+    }
     pub fn apply_layout(
         &mut self,
         layout: TiledPaneLayout,
@@ -983,7 +1349,10 @@ impl Screen {
         } else {
             client_id
         };
-        let err_context = || format!("failed to apply layout for tab {tab_index:?}",);
+        let layout_source = self.layout_sources.source(false).to_owned();
+        let err_context = || {
+            format!("failed to apply layout for tab {tab_index:?} (layout from {layout_source})")
+        };
 
         // move the relevant clients out of the current tab and place them in the new one
         let drained_clients = if self.session_is_mirrored {
@@ -1015,6 +1384,18 @@ impl Screen {
             None
         };
 
+        let layout_pane_ids: Vec<PaneId> = new_terminal_ids
+            .iter()
+            .chain(new_floating_terminal_ids.iter())
+            .map(|(terminal_id, _)| PaneId::Terminal(*terminal_id))
+            .chain(
+                new_plugin_ids
+                    .values()
+                    .flatten()
+                    .map(|id| PaneId::Plugin(*id)),
+            )
+            .collect();
+
         // apply the layout to the new tab
         self.tabs
             .get_mut(&tab_index)
@@ -1028,6 +1409,7 @@ impl Screen {
                     new_plugin_ids,
                     client_id,
                 )?;
+                tab.record_layout_origins(&layout_source, &layout_pane_ids);
                 tab.update_input_modes()?;
                 tab.visible(true)?;
                 if let Some(drained_clients) = drained_clients {
@@ -1042,6 +1424,7 @@ impl Screen {
             self.add_client(client_id).with_context(err_context)?;
         }
 
+        self.update_session_context();
         self.update_tabs()
             .and_then(|_| self.render())
             .with_context(err_context)
@@ -1098,6 +1481,24 @@ impl Screen {
         self.update_tabs().with_context(err_context)
     }
 
+    /// Tells the tabs how many tabs the session has and which one is focused, so that the swap
+    /// layouts depending on them are re-applied when tabs are opened or closed
+    fn update_session_context(&mut self) {
+        let focused_tab_index = self
+            .active_tab_indices
+            .values()
+            .next()
+            .and_then(|tab_index| self.tabs.get(tab_index))
+            .map(|tab| tab.position);
+        let session_context = SessionContext {
+            tab_count: self.tabs.len(),
+            focused_tab_index,
+        };
+        for tab in self.tabs.values_mut() {
+            tab.update_session_context(session_context);
+        }
+    }
+
     pub fn update_tabs(&self) -> Result<()> {
         let mut plugin_updates = vec![];
         for (client_id, active_tab_index) in self.active_tab_indices.iter() {
@@ -1115,7 +1516,8 @@ impl Screen {
                         .copied()
                         .collect()
                 };
-                let (active_swap_layout_name, is_swap_layout_dirty) = tab.swap_layout_info();
+                let (active_swap_layout_name, active_swap_layout_label, is_swap_layout_dirty) =
+                    tab.swap_layout_info();
                 tab_data.push(TabInfo {
                     position: tab.position,
                     name: tab.name.clone(),
@@ -1126,11 +1528,19 @@ impl Screen {
                     are_floating_panes_visible: tab.are_floating_panes_visible(),
                     other_focused_clients,
                     active_swap_layout_name,
+                    active_swap_layout_label,
                     is_swap_layout_dirty,
+                    panes_hidden_in_deep_stacks: tab.panes_hidden_in_deep_stacks_count(),
                 });
             }
+            tab_data.sort_by_key(|tab_info| tab_info.position);
             plugin_updates.push((None, Some(*client_id), Event::TabUpdate(tab_data)));
         }
+        let mut pane_infos: Vec<PaneInfo> = vec![];
+        for tab in self.tabs.values() {
+            pane_infos.append(&mut tab.pane_infos());
+        }
+        plugin_updates.push((None, None, Event::PaneUpdate(pane_infos)));
         self.bus
             .senders
             .send_to_plugin(PluginInstruction::Update(plugin_updates))
@@ -1363,6 +1773,234 @@ impl Screen {
         self.render()
     }
 
+    pub fn run_command_in_pane(
+        &mut self,
+        target_pane: &str,
+        run_command: RunCommand,
+        any_tab: bool,
+        force: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to run command in pane {target_pane:?}");
+
+        // cli clients are not attached to a tab, so we look in the tab of the first connected
+        // client instead
+        let active_tab_index = self
+            .active_tab_indices
+            .get(&client_id)
+            .or_else(|| {
+                self.get_first_client_id()
+                    .and_then(|client_id| self.active_tab_indices.get(&client_id))
+            })
+            .copied();
+        let tab_indices: Vec<usize> = active_tab_index
+            .into_iter()
+            .chain(
+                self.tabs
+                    .keys()
+                    .copied()
+                    .filter(|tab_index| any_tab && Some(*tab_index) != active_tab_index),
+            )
+            .collect();
+        let target = tab_indices.iter().find_map(|tab_index| {
+            self.tabs
+                .get(tab_index)
+                .and_then(|tab| tab.find_pane_id_by_name_or_id(target_pane))
+                .map(|pane_id| (*tab_index, pane_id))
+        });
+        let error_lines = match target {
+            Some((_, PaneId::Plugin(_))) => {
+                vec![format!(
+                    "Pane \"{}\" is a plugin pane and cannot run commands.",
+                    target_pane
+                )]
+            },
+            Some((tab_index, pane_id)) => {
+                let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+                if tab.pane_is_busy(pane_id) && !force {
+                    vec![format!(
+                        "Pane \"{}\" is busy running a command, use --force to stop it and run this one instead.",
+                        target_pane
+                    )]
+                } else {
+                    tab.run_command_in_pane(pane_id, run_command)
+                        .with_context(err_context)?;
+                    vec![]
+                }
+            },
+            None => {
+                let mut log_lines = vec![format!(
+                    "Could not find a pane named \"{}\"{}. Available panes:",
+                    target_pane,
+                    if any_tab { "" } else { " in the current tab" }
+                )];
+                for tab_index in &tab_indices {
+                    if let Some(tab) = self.tabs.get(tab_index) {
+                        for pane_name in tab.pane_names() {
+                            log_lines.push(format!("    {}", pane_name));
+                        }
+                    }
+                }
+                log_lines
+            },
+        };
+        if !error_lines.is_empty() {
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::LogError(error_lines, client_id))
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+
+    /// Writes to the panes `target_pane` names in any tab (or in the tab named `target_tab`),
+    /// without focusing them. Several panes are only written to with `all_matching`
+    pub fn write_to_target_pane(
+        &mut self,
+        bytes: Vec<u8>,
+        target_pane: &str,
+        target_tab: Option<String>,
+        all_matching: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to write to pane {target_pane:?}");
+
+        let mut tabs: Vec<&Tab> = self
+            .tabs
+            .values()
+            .filter(|tab| target_tab.as_ref().map(|t| &tab.name == t).unwrap_or(true))
+            .collect();
+        tabs.sort_by_key(|tab| tab.position);
+        let matches: Vec<(usize, PaneId)> = tabs
+            .iter()
+            .flat_map(|tab| {
+                tab.find_pane_ids_by_layout_name_or_id(target_pane)
+                    .into_iter()
+                    .map(move |pane_id| (tab.index, pane_id))
+            })
+            .collect();
+        let in_tab = target_tab
+            .as_ref()
+            .map(|target_tab| format!(" in tab \"{}\"", target_tab))
+            .unwrap_or_default();
+        let error_lines = if tabs.is_empty() {
+            vec![format!(
+                "Could not find a tab named \"{}\".",
+                target_tab.as_deref().unwrap_or_default()
+            )]
+        } else if matches.is_empty() {
+            let mut log_lines = vec![format!(
+                "Could not find a pane named \"{}\"{}. Available panes:",
+                target_pane, in_tab
+            )];
+            for tab in &tabs {
+                for pane_name in tab.pane_names() {
+                    log_lines.push(format!("    {} (tab \"{}\")", pane_name, tab.name));
+                }
+            }
+            log_lines
+        } else if matches.len() > 1 && !all_matching {
+            let mut log_lines = vec![format!(
+                "Several panes are named \"{}\"{}, use --all-matching to write to all of them:",
+                target_pane, in_tab
+            )];
+            for (tab_index, pane_id) in &matches {
+                if let Some(tab) = self.tabs.get(tab_index) {
+                    log_lines.push(format!(
+                        "    {} (tab \"{}\")",
+                        tab.pane_name(*pane_id).unwrap_or_default(),
+                        tab.name
+                    ));
+                }
+            }
+            log_lines
+        } else {
+            vec![]
+        };
+        if !error_lines.is_empty() {
+            return self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::LogError(error_lines, client_id))
+                .with_context(err_context);
+        }
+        let mut should_update_tabs = false;
+        for (tab_index, pane_id) in matches {
+            let tab = self.tabs.get_mut(&tab_index).with_context(err_context)?;
+            if tab
+                .write_to_pane_id(bytes.clone(), pane_id)
+                .with_context(err_context)?
+            {
+                should_update_tabs = true;
+            }
+        }
+        if should_update_tabs {
+            self.update_tabs().with_context(err_context)?;
+        }
+        Ok(())
+    }
+
+    /// Re-runs the command of a pane of the active tab, the focused pane if no target is given
+    pub fn rerun_pane(
+        &mut self,
+        target_pane: Option<String>,
+        force: bool,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || format!("failed to re-run pane {target_pane:?}");
+
+        // cli clients are not attached to a tab, so we use the first connected client instead
+        let tab_client_id = if self.active_tab_indices.contains_key(&client_id) {
+            Some(client_id)
+        } else {
+            self.get_first_client_id()
+        };
+        let tab_index = tab_client_id
+            .and_then(|client_id| self.active_tab_indices.get(&client_id))
+            .copied();
+        let tab = match tab_index.and_then(|tab_index| self.tabs.get_mut(&tab_index)) {
+            Some(tab) => tab,
+            None => return Ok(()),
+        };
+        let pane_id = match &target_pane {
+            Some(target_pane) => tab.find_pane_id_by_name_or_id(target_pane),
+            None => tab_client_id.and_then(|client_id| tab.get_active_pane_id(client_id)),
+        };
+        let error_lines = match (pane_id, &target_pane) {
+            (Some(pane_id), _) => {
+                match tab.rerun_pane(pane_id, force).with_context(err_context)? {
+                    Some(refusal) => vec![format!(
+                        "Pane \"{}\" {}",
+                        target_pane
+                            .clone()
+                            .or_else(|| tab.pane_name(pane_id))
+                            .unwrap_or_default(),
+                        refusal
+                    )],
+                    None => vec![],
+                }
+            },
+            (None, Some(target_pane)) => {
+                let mut log_lines = vec![format!(
+                    "Could not find a pane named \"{}\" in the current tab. Available panes:",
+                    target_pane
+                )];
+                for pane_name in tab.pane_names() {
+                    log_lines.push(format!("    {}", pane_name));
+                }
+                log_lines
+            },
+            (None, None) => vec![],
+        };
+        if !error_lines.is_empty() {
+            self.bus
+                .senders
+                .send_to_server(ServerInstruction::LogError(error_lines, client_id))
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+
     fn unblock_input(&self) -> Result<()> {
         self.bus
             .senders
@@ -1379,10 +2017,30 @@ pub(crate) fn screen_thread_main(
     max_panes: Option<usize>,
     client_attributes: ClientAttributes,
     config_options: Box<Options>,
+    layout_sources: Box<LayoutSources>,
+    session_layout_tab_count: usize,
 ) -> Result<()> {
     let capabilities = config_options.simplified_ui;
     let draw_pane_frames = config_options.pane_frames.unwrap_or(true);
     let auto_layout = config_options.auto_layout.unwrap_or(true);
+    let respect_fixed_sizes = config_options.respect_fixed_sizes.unwrap_or(true);
+    let pane_title_formats = config_options
+        .pane_title_formats
+        .clone()
+        .unwrap_or_default();
+    let command_spawn_interval = config_options
+        .command_spawn_interval_ms
+        .filter(|interval_ms| *interval_ms > 0)
+        .map(Duration::from_millis);
+    let default_focus = config_options.default_focus;
+    let resize_relayout_interval = Some(
+        config_options
+            .resize_relayout_interval_ms
+            .unwrap_or(DEFAULT_RESIZE_RELAYOUT_INTERVAL_MS),
+    )
+    .filter(|interval_ms| *interval_ms > 0)
+    .map(Duration::from_millis);
+    let on_layout_failure = config_options.on_layout_failure.unwrap_or_default();
     let session_is_mirrored = config_options.mirror_session.unwrap_or(false);
     let copy_options = CopyOptions::new(
         config_options.copy_command,
@@ -1403,8 +2061,16 @@ pub(crate) fn screen_thread_main(
         ),
         draw_pane_frames,
         auto_layout,
+        respect_fixed_sizes,
+        pane_title_formats,
+        command_spawn_interval,
+        default_focus,
+        resize_relayout_interval,
         session_is_mirrored,
         copy_options,
+        *layout_sources,
+        on_layout_failure,
+        session_layout_tab_count,
     );
 
     loop {
@@ -1416,6 +2082,19 @@ pub(crate) fn screen_thread_main(
 
         match event {
             ScreenInstruction::PtyBytes(pid, vte_bytes) => {
+                if let Some(side_panel) = screen.side_panel.as_mut() {
+                    if side_panel.pid() == PaneId::Terminal(pid) {
+                        let os_input = screen
+                            .bus
+                            .os_input
+                            .as_ref()
+                            .context("failed to process pty bytes")?;
+                        side_panel
+                            .handle_pty_bytes(vte_bytes, os_input.as_ref(), &screen.bus.senders)
+                            .context("failed to process pty bytes")?;
+                        continue;
+                    }
+                }
                 let all_tabs = screen.get_tabs_mut();
                 for tab in all_tabs.values_mut() {
                     if tab.has_terminal_pid(pid) {
@@ -1570,6 +2249,14 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
             },
             ScreenInstruction::WriteCharacter(bytes, client_id) => {
+                if let Some(side_panel) = screen.side_panel.as_mut() {
+                    if side_panel.is_focused(client_id) {
+                        if side_panel.write(bytes, &screen.bus.senders)? {
+                            screen.close_side_panel()?;
+                        }
+                        continue;
+                    }
+                }
                 let mut should_update_tabs = false;
                 active_tab_and_connected_client_id!(
                     screen,
@@ -1591,6 +2278,8 @@ pub(crate) fn screen_thread_main(
                 }
             },
             ScreenInstruction::Resize(client_id, strategy) => {
+                let strategy = strategy
+                    .including_fixed_panes(strategy.include_fixed || !screen.respect_fixed_sizes);
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
@@ -1902,10 +2591,18 @@ pub(crate) fn screen_thread_main(
                             Some(client_id)
                         ));
                     },
+                    None if screen.side_panel.as_ref().map(|s| s.pid()) == Some(id) => {
+                        screen.close_side_panel()?;
+                    },
                     None => {
                         for tab in screen.tabs.values_mut() {
                             if tab.get_all_pane_ids().contains(&id) {
-                                tab.close_pane(id, false, None);
+                                // the pane's command was stopped to run another one in its place
+                                if !tab.respawn_pane_if_pending(id, None)? {
+                                    // the pane closes because its command exited
+                                    tab.report_command_exit(id, None, None).non_fatal();
+                                    tab.close_pane(id, false, None);
+                                }
                                 break;
                             }
                         }
@@ -1925,6 +2622,11 @@ pub(crate) fn screen_thread_main(
                             run_command
                         ));
                     },
+                    None if screen.side_panel.as_ref().map(|s| s.pid()) == Some(id) => {
+                        if let Some(side_panel) = screen.side_panel.as_mut() {
+                            side_panel.hold(exit_status, is_first_run, run_command);
+                        }
+                    },
                     None => {
                         for tab in screen.tabs.values_mut() {
                             if tab.get_all_pane_ids().contains(&id) {
@@ -1966,6 +2668,16 @@ pub(crate) fn screen_thread_main(
                 screen.render()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::ToggleActivePaneReadOnly(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .toggle_active_pane_read_only(client_id)
+                );
+                screen.render()?;
+                screen.unblock_input()?;
+            },
             ScreenInstruction::TogglePaneFrames => {
                 screen.draw_pane_frames = !screen.draw_pane_frames;
                 for tab in screen.tabs.values_mut() {
@@ -1994,11 +2706,14 @@ pub(crate) fn screen_thread_main(
                 layout,
                 floating_panes_layout,
                 tab_name,
+                pin,
+                max_content_width,
                 swap_layouts,
                 client_id,
             ) => {
                 let tab_index = screen.get_new_tab_index();
-                screen.new_tab(tab_index, swap_layouts, client_id)?;
+                screen.new_tab(tab_index, swap_layouts, pin, max_content_width, client_id)?;
+                screen.track_session_layout_tab(tab_index, tab_name.clone());
                 screen
                     .bus
                     .senders
@@ -2020,15 +2735,64 @@ pub(crate) fn screen_thread_main(
                 tab_index,
                 client_id,
             ) => {
-                screen.apply_layout(
-                    layout,
-                    floating_panes_layout,
-                    new_pane_pids,
-                    new_floating_pane_pids,
-                    new_plugin_ids,
-                    tab_index,
-                    client_id,
-                )?;
+                match screen.session_layout_tabs_to_apply.remove(&tab_index) {
+                    Some((position_in_layout, tab_name)) => {
+                        let new_pane_ids: Vec<PaneId> = new_pane_pids
+                            .iter()
+                            .chain(new_floating_pane_pids.iter())
+                            .map(|(terminal_id, _)| PaneId::Terminal(*terminal_id))
+                            .chain(
+                                new_plugin_ids
+                                    .values()
+                                    .flatten()
+                                    .map(|id| PaneId::Plugin(*id)),
+                            )
+                            .collect();
+                        if let Err(e) = screen.apply_layout(
+                            layout,
+                            floating_panes_layout,
+                            new_pane_pids,
+                            new_floating_pane_pids,
+                            new_plugin_ids,
+                            tab_index,
+                            client_id,
+                        ) {
+                            let error = LayoutTabError {
+                                tab_index: position_in_layout,
+                                tab_name,
+                                reason: format!("{:#}", e),
+                            };
+                            screen.fail_session_layout_tab(tab_index, error, new_pane_ids)?;
+                        }
+                    },
+                    None => screen.apply_layout(
+                        layout,
+                        floating_panes_layout,
+                        new_pane_pids,
+                        new_floating_pane_pids,
+                        new_plugin_ids,
+                        tab_index,
+                        client_id,
+                    )?,
+                }
+                screen.unblock_input()?;
+                screen.render()?;
+            },
+            ScreenInstruction::LayoutTabFailed(tab_index, reason) => {
+                match screen.session_layout_tabs_to_apply.remove(&tab_index) {
+                    Some((position_in_layout, tab_name)) => {
+                        let error = LayoutTabError {
+                            tab_index: position_in_layout,
+                            tab_name,
+                            reason,
+                        };
+                        screen.fail_session_layout_tab(tab_index, error, vec![])?;
+                    },
+                    None => {
+                        log::error!("Failed to create tab: {}", reason);
+                        screen.remove_failed_tab(tab_index, vec![])?;
+                    },
+                }
                 screen.unblock_input()?;
                 screen.render()?;
             },
@@ -2066,7 +2830,7 @@ pub(crate) fn screen_thread_main(
                         screen.render()?;
                         if create && !tab_exists {
                             let tab_index = screen.get_new_tab_index();
-                            screen.new_tab(tab_index, swap_layouts, client_id)?;
+                            screen.new_tab(tab_index, swap_layouts, None, client_id)?;
                             screen
                                 .bus
                                 .senders
@@ -2130,6 +2894,17 @@ pub(crate) fn screen_thread_main(
                 screen.unblock_input()?;
             },
             ScreenInstruction::LeftClick(point, client_id) => {
+                if let Some(side_panel) = screen.side_panel.as_mut() {
+                    if side_panel.contains(&point) {
+                        side_panel.focus(client_id);
+                        screen.render()?;
+                        screen.unblock_input()?;
+                        continue;
+                    } else if side_panel.unfocus(client_id) {
+                        // so that the tab places the cursor back in its active pane
+                        active_tab!(screen, client_id, |tab: &mut Tab| tab.set_force_render());
+                    }
+                }
                 active_tab!(screen, client_id, |tab: &mut Tab| tab
                     .handle_left_click(&point, client_id), ?);
                 screen.update_tabs()?;
@@ -2307,28 +3082,120 @@ pub(crate) fn screen_thread_main(
                 }
                 screen.render()?;
             },
-            ScreenInstruction::PreviousSwapLayout(client_id) => {
+            ScreenInstruction::PreviousSwapLayout(tag, client_id) => {
+                let swap_layout_source = screen.layout_sources.source(true).to_owned();
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.previous_swap_layout(Some(client_id)),
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .cycle_swap_layouts(Some(client_id), true, true, tag.as_deref())
+                        .with_context(|| format!(
+                            "failed to apply previous swap layout from {swap_layout_source}"
+                        )),
                     ?
                 );
                 screen.render()?;
                 screen.update_tabs()?;
                 screen.unblock_input()?;
             },
-            ScreenInstruction::NextSwapLayout(client_id) => {
+            ScreenInstruction::NextSwapLayout(tag, client_id) => {
+                let swap_layout_source = screen.layout_sources.source(true).to_owned();
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.next_swap_layout(Some(client_id), true),
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .cycle_swap_layouts(Some(client_id), false, true, tag.as_deref())
+                        .with_context(|| format!(
+                            "failed to apply next swap layout from {swap_layout_source}"
+                        )),
                     ?
                 );
                 screen.render()?;
                 screen.update_tabs()?;
                 screen.unblock_input()?;
             },
+            ScreenInstruction::RotateTabLayout(clockwise, client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .rotate_tiled_panes(clockwise, Some(client_id)),
+                    ?
+                );
+                screen.render()?;
+                screen.update_tabs()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::RunInPane(target_pane, run_command, any_tab, force, client_id) => {
+                screen.run_command_in_pane(&target_pane, run_command, any_tab, force, client_id)?;
+                screen.render()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::NewSidePanel(side_panel_layout, pid, hold_for_command) => {
+                screen.new_side_panel(side_panel_layout, pid, hold_for_command)?;
+                screen.update_tabs()?;
+            },
+            ScreenInstruction::PaneDependencyTimedOut(pane_id) => {
+                for tab in screen.get_tabs_mut().values_mut() {
+                    if tab.has_pane_with_pid(&pane_id) {
+                        tab.pane_dependency_timed_out(pane_id)?;
+                        break;
+                    }
+                }
+                screen.render()?;
+            },
+            ScreenInstruction::SpawnQueuedCommand(tab_index) => {
+                if let Some(tab) = screen.get_tabs_mut().get_mut(&tab_index) {
+                    tab.spawn_next_queued_command()?;
+                }
+                screen.render()?;
+            },
+            ScreenInstruction::FlushCommandQueue => {
+                for tab in screen.get_tabs_mut().values_mut() {
+                    tab.flush_command_queue()?;
+                }
+                screen.render()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::RelayoutAfterResize => {
+                screen.relayout_after_resize()?;
+            },
+            ScreenInstruction::ReloadSwapLayouts(reloaded_swap_layouts) => {
+                let mut reloaded_tab_count = 0;
+                for tab in screen.tabs.values_mut() {
+                    if tab.reload_swap_layouts(&reloaded_swap_layouts) {
+                        reloaded_tab_count += 1;
+                    }
+                }
+                log::info!(
+                    "Reloaded the swap layouts of {} tab(s) from {}",
+                    reloaded_tab_count,
+                    screen.layout_sources.source(true)
+                );
+                screen.update_tabs()?;
+            },
+            ScreenInstruction::RerunPane(target_pane, force, client_id) => {
+                screen.rerun_pane(target_pane, force, client_id)?;
+                screen.render()?;
+                screen.unblock_input()?;
+            },
+            ScreenInstruction::WriteToPane(
+                bytes,
+                target_pane,
+                target_tab,
+                all_matching,
+                client_id,
+            ) => {
+                screen.write_to_target_pane(
+                    bytes,
+                    &target_pane,
+                    target_tab,
+                    all_matching,
+                    client_id,
+                )?;
+                screen.render()?;
+                screen.unblock_input()?;
+            },
         }
     }
     Ok(())
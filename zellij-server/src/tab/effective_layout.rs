@@ -0,0 +1,152 @@
+use crate::panes::PaneId;
+use crate::tab::pane_id_string;
+use zellij_utils::{
+    input::layout::{SplitDirection, SplitSize, TiledPaneLayout},
+    pane_size::{Dimension, PaneGeom},
+};
+
+/// The layout the tiled panes of a tab are laid out in, found by cutting the space they take
+/// across wherever no pane straddles the cut. Each pane of the layout is named after the id of
+/// the pane it was found from (eg. "terminal_1"). The panes keep the size they were given along
+/// their split when it is a fixed one, the others get their share of their container as it is
+/// now. None if the panes cannot be laid out this way (eg. some of them overlap).
+pub fn effective_tiled_layout(panes: &[(PaneId, PaneGeom)]) -> Option<TiledPaneLayout> {
+    if panes.is_empty() {
+        return None;
+    }
+    let mut layout = TiledPaneLayout::default();
+    fill_layout(&mut layout, panes.to_vec())?;
+    Some(layout)
+}
+
+fn fill_layout(layout: &mut TiledPaneLayout, mut panes: Vec<(PaneId, PaneGeom)>) -> Option<()> {
+    if panes.len() == 1 {
+        layout.name = Some(pane_id_string(&panes[0].0));
+        return Some(());
+    }
+    if is_one_stack(&panes) {
+        panes.sort_by_key(|(_pane_id, geom)| geom.y);
+        layout.children_split_direction = SplitDirection::Horizontal;
+        layout.children_are_stacked = true;
+        // the last pane of a stack is the expanded one unless another is marked as expanded
+        let last_index = panes.len() - 1;
+        layout.children = panes
+            .iter()
+            .enumerate()
+            .map(|(i, (pane_id, geom))| TiledPaneLayout {
+                name: Some(pane_id_string(pane_id)),
+                is_expanded_in_stack: i != last_index && geom.rows.as_usize() > 1,
+                ..Default::default()
+            })
+            .collect();
+        return Some(());
+    }
+    let (direction, groups) = split_into_groups(&panes, SplitDirection::Vertical)
+        .or_else(|| split_into_groups(&panes, SplitDirection::Horizontal))?;
+    let fixed_size = |group: &[(PaneId, PaneGeom)]| match group {
+        [(_pane_id, geom)] => Some(*split_dimension(geom, direction))
+            .filter(|dimension| dimension.is_fixed())
+            .map(|dimension| dimension.as_usize()),
+        _ => None,
+    };
+    let flexible_space: usize = groups
+        .iter()
+        .filter(|group| fixed_size(group).is_none())
+        .map(|group| group_size(group, direction))
+        .sum();
+    layout.children_split_direction = direction;
+    for group in groups {
+        // shares given as weights always add up to the whole container, whatever the rounding
+        let split_size = match fixed_size(&group) {
+            Some(fixed_size) => SplitSize::Fixed(fixed_size),
+            None => {
+                let share = group_size(&group, direction) * 100 + flexible_space / 2;
+                SplitSize::Weight((share / flexible_space.max(1)).max(1))
+            },
+        };
+        let mut child = TiledPaneLayout {
+            split_size: Some(split_size),
+            ..Default::default()
+        };
+        fill_layout(&mut child, group)?;
+        layout.children.push(child);
+    }
+    Some(())
+}
+
+fn is_one_stack(panes: &[(PaneId, PaneGeom)]) -> bool {
+    let first_geom = panes[0].1;
+    panes.iter().all(|(_pane_id, geom)| {
+        geom.is_stacked
+            && geom.x == first_geom.x
+            && geom.cols.as_usize() == first_geom.cols.as_usize()
+    })
+}
+
+pub fn is_in_same_stack(a: &PaneGeom, b: &PaneGeom) -> bool {
+    a.is_stacked && b.is_stacked && a.x == b.x && a.cols.as_usize() == b.cols.as_usize()
+}
+
+// the panes split into the groups that are side by side (vertical) or one above the other
+// (horizontal), from left to right or from top to bottom, None if there is nowhere to cut them
+fn split_into_groups(
+    panes: &[(PaneId, PaneGeom)],
+    direction: SplitDirection,
+) -> Option<(SplitDirection, Vec<Vec<(PaneId, PaneGeom)>>)> {
+    let mut panes = panes.to_vec();
+    panes.sort_by_key(|(_pane_id, geom)| split_start(geom, direction));
+    let mut groups: Vec<Vec<(PaneId, PaneGeom)>> = vec![];
+    let mut group_end = 0;
+    for (pane_id, geom) in panes {
+        // the panes of a stack are never cut apart
+        let is_stacked_below_group = groups.last().map_or(false, |group| {
+            group
+                .iter()
+                .any(|(_pane_id, group_geom)| is_in_same_stack(group_geom, &geom))
+        });
+        match groups.last_mut() {
+            Some(group) if split_start(&geom, direction) < group_end || is_stacked_below_group => {
+                group.push((pane_id, geom))
+            },
+            _ => groups.push(vec![(pane_id, geom)]),
+        }
+        group_end = group_end.max(split_end(&geom, direction));
+    }
+    if groups.len() > 1 {
+        Some((direction, groups))
+    } else {
+        None
+    }
+}
+
+fn split_start(geom: &PaneGeom, direction: SplitDirection) -> usize {
+    match direction {
+        SplitDirection::Vertical => geom.x,
+        SplitDirection::Horizontal | SplitDirection::Auto => geom.y,
+    }
+}
+
+fn split_end(geom: &PaneGeom, direction: SplitDirection) -> usize {
+    split_start(geom, direction) + split_dimension(geom, direction).as_usize()
+}
+
+fn split_dimension(geom: &PaneGeom, direction: SplitDirection) -> &Dimension {
+    match direction {
+        SplitDirection::Vertical => &geom.cols,
+        SplitDirection::Horizontal | SplitDirection::Auto => &geom.rows,
+    }
+}
+
+fn group_size(group: &[(PaneId, PaneGeom)], direction: SplitDirection) -> usize {
+    let group_start = group
+        .iter()
+        .map(|(_pane_id, geom)| split_start(geom, direction))
+        .min()
+        .unwrap_or(0);
+    let group_end = group
+        .iter()
+        .map(|(_pane_id, geom)| split_end(geom, direction))
+        .max()
+        .unwrap_or(0);
+    group_end - group_start
+}
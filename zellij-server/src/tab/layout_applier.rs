@@ -1,7 +1,10 @@
 use zellij_utils::errors::prelude::*;
 
 use crate::resize_pty;
-use crate::tab::{get_next_terminal_position, HoldForCommand, Pane};
+use crate::tab::{
+    effective_layout::is_in_same_stack, get_next_terminal_position, pane_id_matches,
+    HoldForCommand, Pane,
+};
 
 use crate::{
     os_input_output::ServerOsApi,
@@ -18,8 +21,14 @@ use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 use zellij_utils::{
     data::{Palette, Style},
-    input::layout::{FloatingPaneLayout, Run, RunPluginLocation, TiledPaneLayout},
-    pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
+    input::{
+        layout::{
+            floating_stack_geom, run_match, FloatingPaneLayout, FocusPolicy, MissingSlotsPolicy,
+            PanePositionsCache, Run, RunMatch, RunPluginLocation, TiledPaneLayout,
+        },
+        options::PaneTitleFormats,
+    },
+    pane_size::{Dimension, Offset, PaneGeom, Size, SizeInPixels, Viewport},
 };
 
 pub struct LayoutApplier<'a> {
@@ -32,11 +41,14 @@ pub struct LayoutApplier<'a> {
     character_cell_size: Rc<RefCell<Option<SizeInPixels>>>,
     style: Style,
     display_area: Rc<RefCell<Size>>, // includes all panes (including eg. the status bar and tab bar in the default layout)
+    content_x: usize, // the column the display area starts at, non-zero in width constrained tabs
     tiled_panes: &'a mut TiledPanes,
     floating_panes: &'a mut FloatingPanes,
     draw_pane_frames: bool,
     focus_pane_id: &'a mut Option<PaneId>,
     os_api: Box<dyn ServerOsApi>,
+    pane_title_formats: PaneTitleFormats,
+    default_focus: Option<FocusPolicy>,
 }
 
 impl<'a> LayoutApplier<'a> {
@@ -50,11 +62,14 @@ impl<'a> LayoutApplier<'a> {
         character_cell_size: &Rc<RefCell<Option<SizeInPixels>>>,
         style: &Style,
         display_area: &Rc<RefCell<Size>>, // includes all panes (including eg. the status bar and tab bar in the default layout)
+        content_x: usize,
         tiled_panes: &'a mut TiledPanes,
         floating_panes: &'a mut FloatingPanes,
         draw_pane_frames: bool,
         focus_pane_id: &'a mut Option<PaneId>,
         os_api: &Box<dyn ServerOsApi>,
+        pane_title_formats: &PaneTitleFormats,
+        default_focus: Option<FocusPolicy>,
     ) -> Self {
         let viewport = viewport.clone();
         let senders = senders.clone();
@@ -66,6 +81,7 @@ impl<'a> LayoutApplier<'a> {
         let style = style.clone();
         let display_area = display_area.clone();
         let os_api = os_api.clone();
+        let pane_title_formats = pane_title_formats.clone();
         LayoutApplier {
             viewport,
             senders,
@@ -76,11 +92,14 @@ impl<'a> LayoutApplier<'a> {
             character_cell_size,
             style,
             display_area,
+            content_x,
             tiled_panes,
             floating_panes,
             draw_pane_frames,
             focus_pane_id,
             os_api,
+            pane_title_formats,
+            default_focus,
         }
     }
     pub fn apply_layout(
@@ -108,20 +127,55 @@ impl<'a> LayoutApplier<'a> {
         layout: &TiledPaneLayout,
         refocus_pane: bool,
         client_id: Option<ClientId>,
-    ) -> Result<()> {
+        pane_positions: &mut PanePositionsCache,
+    ) -> Result<usize> {
+        // returns the number of empty slots in the layout that should be filled with new panes
         let err_context = || format!("failed to apply tiled panes layout");
         let free_space = self.total_space_for_tiled_panes();
         let tiled_panes_count = self.tiled_panes.visible_panes_count();
-        match layout.position_panes_in_space(&free_space, Some(tiled_panes_count)) {
+        let mut empty_slot_count = 0;
+        let focus_policy = self.default_focus.unwrap_or_default();
+        // a layout marking the pane to focus moves the focus to that pane, otherwise the focused
+        // pane keeps it and is moved to where the focus policy would put it
+        let layout_marks_focus = layout.has_focused_node();
+        let positions_in_layout = pane_positions
+            .position_panes_in_space_with_focus(
+                layout,
+                &free_space,
+                Some(tiled_panes_count),
+                focus_policy,
+            )
+            .and_then(|positions_in_layout| {
+                if positions_in_layout.len() > tiled_panes_count {
+                    // the layout keeps its empty slots, until they are filled we collapse them
+                    empty_slot_count = positions_in_layout.len() - tiled_panes_count;
+                    let mut collapsed_layout = layout.clone();
+                    collapsed_layout.missing_slots = MissingSlotsPolicy::Collapse;
+                    pane_positions.position_panes_in_space_with_focus(
+                        &collapsed_layout,
+                        &free_space,
+                        Some(tiled_panes_count),
+                        focus_policy,
+                    )
+                } else {
+                    Ok(positions_in_layout)
+                }
+            });
+        match positions_in_layout {
             Ok(positions_in_layout) => {
                 let currently_focused_pane_id =
                     client_id.and_then(|client_id| self.tiled_panes.focused_pane_id(client_id));
                 let mut existing_tab_state =
                     ExistingTabState::new(self.tiled_panes.drain(), currently_focused_pane_id);
                 let mut pane_focuser = PaneFocuser::new(refocus_pane);
+                pane_focuser.set_focus_moves_to_location(layout_marks_focus);
+                let deep_stacks = TiledPaneLayout::deep_stacks(&positions_in_layout);
+                let mut pane_ids_in_layout = vec![];
                 for (layout, position_and_size) in positions_in_layout {
+                    pane_ids_in_layout.push(None);
                     if let Some(mut pane) = existing_tab_state.find_and_extract_pane(
                         &layout.run,
+                        layout.name.as_deref(),
                         &position_and_size,
                         layout.focus.unwrap_or(false),
                         true,
@@ -133,6 +187,8 @@ impl<'a> LayoutApplier<'a> {
                         );
                         pane_focuser.set_pane_id_in_focused_location(layout.focus, &pane);
                         resize_pty!(pane, self.os_api, self.senders)?;
+                        pane_ids_in_layout.pop();
+                        pane_ids_in_layout.push(Some(pane.pid()));
                         self.tiled_panes
                             .add_pane_with_existing_geom(pane.pid(), pane);
                     }
@@ -144,15 +200,94 @@ impl<'a> LayoutApplier<'a> {
                         self.tiled_panes.insert_pane(pane.pid(), pane);
                     }
                 }
-                pane_focuser.focus_tiled_pane(&mut self.tiled_panes);
+                self.add_deep_stacks(&deep_stacks, &pane_ids_in_layout);
+                pane_focuser.focus_tiled_pane(&mut self.tiled_panes, client_id);
             },
             Err(e) => {
-                Err::<(), _>(anyError::msg(e))
+                empty_slot_count = 0;
+                Err::<(), _>(anyError::new(e))
                     .with_context(err_context)
                     .non_fatal(); // TODO: propagate this to the user
             },
         };
-        Ok(())
+        Ok(empty_slot_count)
+    }
+    /// Moves the existing tiled panes to the panes of `layout` named after their ids (see
+    /// `effective_tiled_layout`), each stack keeping the pane it has expanded. Returns false,
+    /// leaving the panes where they are, if the layout does not have a place for each of them.
+    pub fn apply_tiled_panes_layout_to_panes_by_id(
+        &mut self,
+        layout: &TiledPaneLayout,
+    ) -> Result<bool> {
+        let err_context = || format!("failed to apply tiled panes layout to panes by id");
+        let free_space = self.total_space_for_tiled_panes();
+        let positions_in_layout = match layout.position_panes_in_space(&free_space, None) {
+            Ok(positions_in_layout) => positions_in_layout,
+            Err(e) => {
+                log::error!("Failed to position the panes: {}", e);
+                return Ok(false);
+            },
+        };
+        let mut pane_positions = vec![];
+        for (pane_layout, position_and_size) in positions_in_layout {
+            let pane_id = pane_layout.name.as_deref().and_then(|name| {
+                self.tiled_panes
+                    .pane_ids()
+                    .find(|pane_id| pane_id_matches(pane_id, name))
+                    .copied()
+            });
+            match pane_id {
+                Some(pane_id) => pane_positions.push((pane_id, position_and_size)),
+                None => return Ok(false),
+            }
+        }
+        if pane_positions.len() != self.tiled_panes.pane_ids().count() {
+            return Ok(false);
+        }
+        self.keep_expanded_panes_of_stacks(&mut pane_positions);
+        for (pane_id, position_and_size) in pane_positions {
+            if let Some(pane) = self.tiled_panes.get_pane_mut(pane_id) {
+                pane.set_geom(position_and_size);
+                resize_pty!(pane, self.os_api, self.senders).with_context(err_context)?;
+            }
+        }
+        Ok(true)
+    }
+    // a stack is positioned with its last pane expanded, this expands the pane that was expanded
+    // before in its place
+    fn keep_expanded_panes_of_stacks(&self, pane_positions: &mut [(PaneId, PaneGeom)]) {
+        let mut i = 0;
+        while i < pane_positions.len() {
+            let first_geom = pane_positions[i].1;
+            let stack_len = pane_positions[i..]
+                .iter()
+                .take_while(|(_pane_id, geom)| is_in_same_stack(geom, &first_geom))
+                .count()
+                .max(1);
+            let stack = &mut pane_positions[i..i + stack_len];
+            let expanded_index = stack.iter().position(|(pane_id, _geom)| {
+                self.tiled_panes
+                    .get_pane(*pane_id)
+                    .map_or(false, |pane| pane.position_and_size().rows.as_usize() > 1)
+            });
+            let expanded_index = expanded_index.filter(|_| stack_len > 1);
+            if let (Some(expanded_index), Some((_pane_id, last_geom))) =
+                (expanded_index, stack.last())
+            {
+                let expanded_rows = last_geom.rows;
+                let mut y = first_geom.y;
+                for (index, (_pane_id, geom)) in stack.iter_mut().enumerate() {
+                    geom.y = y;
+                    geom.rows = if index == expanded_index {
+                        expanded_rows
+                    } else {
+                        Dimension::fixed(1)
+                    };
+                    y += geom.rows.as_usize();
+                }
+            }
+            i += stack_len;
+        }
     }
     fn apply_tiled_panes_layout(
         &mut self,
@@ -163,10 +298,21 @@ impl<'a> LayoutApplier<'a> {
     ) -> Result<()> {
         let err_context = || format!("failed to apply tiled panes layout");
         let free_space = self.total_space_for_tiled_panes();
+        let layout = match self.default_focus {
+            // without a policy the first selectable pane is focused, as it always was
+            Some(focus_policy) if !layout.has_focused_node() => {
+                let mut layout = layout;
+                layout.apply_focus_policy(focus_policy, &free_space);
+                layout
+            },
+            _ => layout,
+        };
         match layout.position_panes_in_space(&free_space, None) {
             Ok(positions_in_layout) => {
                 let positions_and_size = positions_in_layout.iter();
                 let mut new_terminal_ids = new_terminal_ids.iter();
+                let deep_stacks = TiledPaneLayout::deep_stacks(&positions_in_layout);
+                let mut pane_ids_in_layout = vec![];
 
                 let mut focus_pane_id: Option<PaneId> = None;
                 let mut set_focus_pane_id = |layout: &TiledPaneLayout, pane_id: PaneId| {
@@ -178,7 +324,8 @@ impl<'a> LayoutApplier<'a> {
                 for (layout, position_and_size) in positions_and_size {
                     // A plugin pane
                     if let Some(Run::Plugin(run)) = layout.run.clone() {
-                        let pane_title = run.location.to_string();
+                        let pane_title =
+                            Run::Plugin(run.clone()).pane_title(&self.pane_title_formats);
                         let pid = new_plugin_ids
                             .get_mut(&run.location)
                             .and_then(|ids| ids.pop())
@@ -202,18 +349,22 @@ impl<'a> LayoutApplier<'a> {
                             layout.run.clone(),
                         );
                         new_plugin.set_borderless(layout.borderless);
+                        new_plugin.set_hide_title(layout.hide_title);
+                        new_plugin.set_read_only(layout.read_only);
+                        new_plugin.set_exclude_from_dump(layout.exclude_from_dump);
                         self.tiled_panes
                             .add_pane_with_existing_geom(PaneId::Plugin(pid), Box::new(new_plugin));
                         set_focus_pane_id(layout, PaneId::Plugin(pid));
+                        pane_ids_in_layout.push(Some(PaneId::Plugin(pid)));
                     } else {
                         // there are still panes left to fill, use the pids we received in this method
                         if let Some((pid, hold_for_command)) = new_terminal_ids.next() {
                             let next_terminal_position =
                                 get_next_terminal_position(&self.tiled_panes, &self.floating_panes);
-                            let initial_title = match &layout.run {
-                                Some(Run::Command(run_command)) => Some(run_command.to_string()),
-                                _ => None,
-                            };
+                            let initial_title = layout
+                                .run
+                                .as_ref()
+                                .map(|run| run.pane_title(&self.pane_title_formats));
                             let mut new_pane = TerminalPane::new(
                                 *pid,
                                 *position_and_size,
@@ -229,6 +380,9 @@ impl<'a> LayoutApplier<'a> {
                                 layout.run.clone(),
                             );
                             new_pane.set_borderless(layout.borderless);
+                            new_pane.set_hide_title(layout.hide_title);
+                            new_pane.set_read_only(layout.read_only);
+                            new_pane.set_exclude_from_dump(layout.exclude_from_dump);
                             if let Some(held_command) = hold_for_command {
                                 new_pane.hold(None, true, held_command.clone());
                             }
@@ -237,6 +391,9 @@ impl<'a> LayoutApplier<'a> {
                                 Box::new(new_pane),
                             );
                             set_focus_pane_id(layout, PaneId::Terminal(*pid));
+                            pane_ids_in_layout.push(Some(PaneId::Terminal(*pid)));
+                        } else {
+                            pane_ids_in_layout.push(None);
                         }
                     }
                 }
@@ -245,6 +402,7 @@ impl<'a> LayoutApplier<'a> {
                         .send_to_pty(PtyInstruction::ClosePane(PaneId::Terminal(*unused_pid)))
                         .with_context(err_context)?;
                 }
+                self.add_deep_stacks(&deep_stacks, &pane_ids_in_layout);
                 self.adjust_viewport().with_context(err_context)?;
                 self.set_focused_tiled_pane(focus_pane_id, client_id);
             },
@@ -254,7 +412,7 @@ impl<'a> LayoutApplier<'a> {
                         .send_to_pty(PtyInstruction::ClosePane(PaneId::Terminal(unused_pid)))
                         .with_context(err_context)?;
                 }
-                Err::<(), _>(anyError::msg(e))
+                Err::<(), _>(anyError::new(e))
                     .with_context(err_context)
                     .non_fatal(); // TODO: propagate this to the user
             },
@@ -271,16 +429,18 @@ impl<'a> LayoutApplier<'a> {
         // true => has floating panes
         let err_context = || format!("Failed to apply_floating_panes_layout");
         let mut layout_has_floating_panes = false;
-        let floating_panes_layout = floating_panes_layout.iter();
         let mut focused_floating_pane = None;
         let mut new_floating_terminal_ids = new_floating_terminal_ids.iter();
-        for floating_pane_layout in floating_panes_layout {
+        let mut stack_geoms = HashMap::new();
+        for (i, floating_pane_layout) in floating_panes_layout.iter().enumerate() {
             layout_has_floating_panes = true;
             if let Some(Run::Plugin(run)) = floating_pane_layout.run.clone() {
-                let position_and_size = self
-                    .floating_panes
-                    .position_floating_pane_layout(&floating_pane_layout);
-                let pane_title = run.location.to_string();
+                let position_and_size = self.position_floating_pane_in_layout(
+                    &floating_panes_layout,
+                    i,
+                    &mut stack_geoms,
+                );
+                let pane_title = Run::Plugin(run.clone()).pane_title(&self.pane_title_formats);
                 let pid = new_plugin_ids
                     .get_mut(&run.location)
                     .and_then(|ids| ids.pop())
@@ -304,23 +464,31 @@ impl<'a> LayoutApplier<'a> {
                     floating_pane_layout.run.clone(),
                 );
                 new_pane.set_borderless(false);
+                new_pane.set_hide_title(floating_pane_layout.hide_title);
+                new_pane.set_read_only(floating_pane_layout.read_only);
+                new_pane.set_exclude_from_dump(floating_pane_layout.exclude_from_dump);
+                new_pane.set_pinned(floating_pane_layout.pinned.unwrap_or(false));
                 new_pane.set_content_offset(Offset::frame(1));
                 resize_pty!(new_pane, self.os_api, self.senders)?;
                 self.floating_panes
                     .add_pane(PaneId::Plugin(pid), Box::new(new_pane));
+                self.floating_panes
+                    .anchor_pane(PaneId::Plugin(pid), &floating_pane_layout);
                 if floating_pane_layout.focus.unwrap_or(false) {
                     focused_floating_pane = Some(PaneId::Plugin(pid));
                 }
             } else if let Some((pid, hold_for_command)) = new_floating_terminal_ids.next() {
-                let position_and_size = self
-                    .floating_panes
-                    .position_floating_pane_layout(&floating_pane_layout);
+                let position_and_size = self.position_floating_pane_in_layout(
+                    &floating_panes_layout,
+                    i,
+                    &mut stack_geoms,
+                );
                 let next_terminal_position =
                     get_next_terminal_position(&self.tiled_panes, &self.floating_panes);
-                let initial_title = match &floating_pane_layout.run {
-                    Some(Run::Command(run_command)) => Some(run_command.to_string()),
-                    _ => None,
-                };
+                let initial_title = floating_pane_layout
+                    .run
+                    .as_ref()
+                    .map(|run| run.pane_title(&self.pane_title_formats));
                 let mut new_pane = TerminalPane::new(
                     *pid,
                     position_and_size,
@@ -336,6 +504,14 @@ impl<'a> LayoutApplier<'a> {
                     floating_pane_layout.run.clone(),
                 );
                 new_pane.set_borderless(false);
+                new_pane.set_hide_title(floating_pane_layout.hide_title);
+                new_pane.set_read_only(floating_pane_layout.read_only);
+                new_pane.set_exclude_from_dump(floating_pane_layout.exclude_from_dump);
+                new_pane.set_pinned(floating_pane_layout.pinned.unwrap_or(false));
+                new_pane.set_fit_to_content(
+                    floating_pane_layout.height_fit,
+                    floating_pane_layout.width_fit,
+                );
                 new_pane.set_content_offset(Offset::frame(1));
                 if let Some(held_command) = hold_for_command {
                     new_pane.hold(None, true, held_command.clone());
@@ -343,6 +519,8 @@ impl<'a> LayoutApplier<'a> {
                 resize_pty!(new_pane, self.os_api, self.senders)?;
                 self.floating_panes
                     .add_pane(PaneId::Terminal(*pid), Box::new(new_pane));
+                self.floating_panes
+                    .anchor_pane(PaneId::Terminal(*pid), &floating_pane_layout);
                 if floating_pane_layout.focus.unwrap_or(false) {
                     focused_floating_pane = Some(PaneId::Terminal(*pid));
                 }
@@ -370,20 +548,20 @@ impl<'a> LayoutApplier<'a> {
             .iter()
             .find(|f| f.focus.map(|f| f).unwrap_or(false))
             .is_some();
-        let floating_panes_layout = floating_panes_layout.iter();
         let currently_focused_pane_id = self
             .floating_panes
             .active_pane_id_or_focused_pane_id(client_id);
         let mut existing_tab_state =
             ExistingTabState::new(self.floating_panes.drain(), currently_focused_pane_id);
         let mut pane_focuser = PaneFocuser::new(refocus_pane);
-        for floating_pane_layout in floating_panes_layout {
-            let position_and_size = self
-                .floating_panes
-                .position_floating_pane_layout(&floating_pane_layout);
+        let mut stack_geoms = HashMap::new();
+        for (i, floating_pane_layout) in floating_panes_layout.iter().enumerate() {
+            let position_and_size =
+                self.position_floating_pane_in_layout(floating_panes_layout, i, &mut stack_geoms);
             let is_focused = floating_pane_layout.focus.unwrap_or(false);
             if let Some(mut pane) = existing_tab_state.find_and_extract_pane(
                 &floating_pane_layout.run,
+                floating_pane_layout.name.as_deref(),
                 &position_and_size,
                 is_focused,
                 false,
@@ -399,7 +577,10 @@ impl<'a> LayoutApplier<'a> {
                     .or(Some(!layout_has_focused_pane));
                 pane_focuser.set_pane_id_in_focused_location(pane_is_focused, &pane);
                 resize_pty!(pane, self.os_api, self.senders)?;
-                self.floating_panes.add_pane(pane.pid(), pane);
+                let pane_id = pane.pid();
+                self.floating_panes.add_pane(pane_id, pane);
+                self.floating_panes
+                    .anchor_pane(pane_id, &floating_pane_layout);
             }
         }
         let remaining_pane_ids: Vec<PaneId> = existing_tab_state.pane_ids();
@@ -432,6 +613,26 @@ impl<'a> LayoutApplier<'a> {
             Ok(false)
         }
     }
+    // where the floating pane at `index` of the layout goes, the panes of a stack sharing the
+    // space found for the first of them (kept in `stack_geoms`)
+    fn position_floating_pane_in_layout(
+        &mut self,
+        floating_panes_layout: &[FloatingPaneLayout],
+        index: usize,
+        stack_geoms: &mut HashMap<usize, PaneGeom>,
+    ) -> PaneGeom {
+        let floating_pane_layout = &floating_panes_layout[index];
+        let stack_geom = match floating_pane_layout.stack {
+            Some(stack) => *stack_geoms.entry(stack).or_insert_with(|| {
+                self.floating_panes
+                    .position_floating_pane_layout(floating_pane_layout)
+            }),
+            None => self
+                .floating_panes
+                .position_floating_pane_layout(floating_pane_layout),
+        };
+        floating_stack_geom(floating_panes_layout, index, stack_geom)
+    }
     fn resize_whole_tab(&mut self, new_screen_size: Size) -> Result<()> {
         let err_context = || {
             format!(
@@ -491,6 +692,25 @@ impl<'a> LayoutApplier<'a> {
         self.tiled_panes.set_pane_frames(self.draw_pane_frames);
         Ok(())
     }
+    fn add_deep_stacks(
+        &mut self,
+        deep_stacks: &[Vec<usize>],
+        pane_ids_in_layout: &[Option<PaneId>],
+    ) {
+        for deep_stack in deep_stacks {
+            let members: Vec<PaneId> = deep_stack
+                .iter()
+                .filter_map(|i| pane_ids_in_layout.get(*i).copied().flatten())
+                .collect();
+            if members.len() > 2 {
+                log::warn!(
+                    "Not enough room for a stack of {} panes, only showing 2 of them",
+                    members.len()
+                );
+                self.tiled_panes.add_deep_stack(members);
+            }
+        }
+    }
     fn set_focused_tiled_pane(&mut self, focus_pane_id: Option<PaneId>, client_id: ClientId) {
         if let Some(pane_id) = focus_pane_id {
             *self.focus_pane_id = Some(pane_id);
@@ -532,6 +752,9 @@ impl<'a> LayoutApplier<'a> {
         if let Some(pane_title) = floating_pane_layout.and_then(|f| f.name.clone()) {
             pane.set_title(pane_title);
         }
+        if let Some(pinned) = floating_pane_layout.and_then(|f| f.pinned) {
+            pane.set_pinned(pinned);
+        }
         pane.set_content_offset(Offset::frame(1));
     }
     fn total_space_for_tiled_panes(&self) -> PaneGeom {
@@ -543,6 +766,7 @@ impl<'a> LayoutApplier<'a> {
         };
 
         let mut free_space = PaneGeom::default();
+        free_space.x = self.content_x;
         free_space.cols.set_inner(display_area_cols);
         free_space.rows.set_inner(display_area_rows);
         free_space
@@ -567,11 +791,27 @@ impl ExistingTabState {
     pub fn find_and_extract_pane(
         &mut self,
         run: &Option<Run>,
+        name: Option<&str>,
         position_and_size: &PaneGeom,
         is_focused: bool,
         default_to_closest_position: bool,
     ) -> Option<Box<dyn Pane>> {
-        let candidates = self.pane_candidates(run, position_and_size, default_to_closest_position);
+        let candidates =
+            self.pane_candidates(run, name, position_and_size, default_to_closest_position);
+        // the focused pane and the pane already in place only keep the slot if no pane fits it
+        // better
+        let best_candidates: Vec<(&PaneId, &Box<dyn Pane>)> = match candidates.first() {
+            Some((_, best_match)) => candidates
+                .iter()
+                .filter(|(_, pane_match)| pane_match == best_match)
+                .map(|(candidate, _)| *candidate)
+                .collect(),
+            None => vec![],
+        };
+        let candidates: Vec<(&PaneId, &Box<dyn Pane>)> = candidates
+            .into_iter()
+            .map(|(candidate, _)| candidate)
+            .collect();
         if let Some(current_pane_id_with_same_contents) =
             self.find_pane_id_with_same_contents(&candidates, run)
         {
@@ -579,10 +819,10 @@ impl ExistingTabState {
                 .existing_panes
                 .remove(&current_pane_id_with_same_contents);
         } else if let Some(currently_focused_pane_id) =
-            self.find_focused_pane_id(is_focused, &candidates)
+            self.find_focused_pane_id(is_focused, &best_candidates)
         {
             return self.existing_panes.remove(&currently_focused_pane_id);
-        } else if let Some(same_position_candidate_id) = candidates
+        } else if let Some(same_position_candidate_id) = best_candidates
             .iter()
             .find(|(_, p)| p.position_and_size() == *position_and_size)
             .map(|(pid, _p)| *pid)
@@ -602,32 +842,26 @@ impl ExistingTabState {
     pub fn remove_pane(&mut self, pane_id: &PaneId) -> Option<Box<dyn Pane>> {
         self.existing_panes.remove(pane_id)
     }
+    // the panes sorted from the one fitting the slot best, see `run_match`, and then from the
+    // closest one
     fn pane_candidates(
         &self,
         run: &Option<Run>,
+        name: Option<&str>,
         position_and_size: &PaneGeom,
         default_to_closest_position: bool,
-    ) -> Vec<(&PaneId, &Box<dyn Pane>)> {
-        let mut candidates: Vec<_> = self.existing_panes.iter().collect();
-        candidates.sort_by(|(_a_id, a), (_b_id, b)| {
-            let a_invoked_with = a.invoked_with();
-            let b_invoked_with = b.invoked_with();
-            if Run::is_same_category(run, a_invoked_with)
-                && !Run::is_same_category(run, b_invoked_with)
-            {
-                std::cmp::Ordering::Less
-            } else if Run::is_same_category(run, b_invoked_with)
-                && !Run::is_same_category(run, a_invoked_with)
-            {
-                std::cmp::Ordering::Greater
-            } else if Run::is_terminal(a_invoked_with) && !Run::is_terminal(b_invoked_with) {
-                // we place terminals before everything else because when we can't find
-                // an exact match, we need to prefer terminals are more often than not
-                // we'd be doing the right thing here
-                std::cmp::Ordering::Less
-            } else if Run::is_terminal(b_invoked_with) && !Run::is_terminal(a_invoked_with) {
-                std::cmp::Ordering::Greater
-            } else {
+    ) -> Vec<((&PaneId, &Box<dyn Pane>), RunMatch)> {
+        let mut candidates: Vec<_> = self
+            .existing_panes
+            .iter()
+            .map(|(pane_id, pane)| {
+                let pane_name = Some(pane.pane_name()).filter(|pane_name| !pane_name.is_empty());
+                let pane_match = run_match(run, name, pane.invoked_with(), pane_name);
+                ((pane_id, pane), pane_match)
+            })
+            .collect();
+        candidates.sort_by(|((_a_id, a), a_match), ((_b_id, b), b_match)| {
+            a_match.cmp(b_match).then_with(|| {
                 // try to find the closest pane
                 if default_to_closest_position {
                     let abs = |a, b| (a as isize - b as isize).abs();
@@ -639,7 +873,7 @@ impl ExistingTabState {
                 } else {
                     std::cmp::Ordering::Equal
                 }
-            }
+            })
         });
         candidates
     }
@@ -675,6 +909,7 @@ impl ExistingTabState {
 struct PaneFocuser {
     refocus_pane: bool,
     pane_id_in_focused_location: Option<PaneId>,
+    focus_moves_to_location: bool, // rather than the focused pane moving to the location
 }
 
 impl PaneFocuser {
@@ -693,10 +928,21 @@ impl PaneFocuser {
             self.pane_id_in_focused_location = Some(pane.pid());
         }
     }
-    pub fn focus_tiled_pane(&self, tiled_panes: &mut TiledPanes) {
+    pub fn set_focus_moves_to_location(&mut self, focus_moves_to_location: bool) {
+        self.focus_moves_to_location = focus_moves_to_location;
+    }
+    pub fn focus_tiled_pane(&self, tiled_panes: &mut TiledPanes, client_id: Option<ClientId>) {
         match self.pane_id_in_focused_location {
             Some(pane_id_in_focused_location) => {
-                if self.refocus_pane {
+                if self.refocus_pane && self.focus_moves_to_location {
+                    tiled_panes.reapply_pane_focus();
+                    match client_id {
+                        Some(client_id) => {
+                            tiled_panes.focus_pane(pane_id_in_focused_location, client_id)
+                        },
+                        None => tiled_panes.focus_pane_for_all_clients(pane_id_in_focused_location),
+                    }
+                } else if self.refocus_pane {
                     tiled_panes.reapply_pane_focus();
                     tiled_panes.switch_active_pane_with(pane_id_in_focused_location);
                 } else {
@@ -3,7 +3,9 @@
 
 mod clipboard;
 mod copy_command;
+mod effective_layout;
 mod layout_applier;
+mod pane_dependencies;
 mod swap_layouts;
 
 use copy_command::CopyCommand;
@@ -19,7 +21,9 @@ use crate::background_jobs::BackgroundJob;
 use crate::pty_writer::PtyWriteInstruction;
 use crate::screen::CopyOptions;
 use crate::ui::pane_boundaries_frame::FrameParams;
+use effective_layout::effective_tiled_layout;
 use layout_applier::LayoutApplier;
+use pane_dependencies::PaneDependencies;
 use swap_layouts::SwapLayouts;
 
 use self::clipboard::ClipboardProvider;
@@ -37,19 +41,24 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     str,
 };
 use zellij_utils::{
-    data::{Event, InputMode, ModeInfo, Palette, PaletteColor, Style},
+    data::{
+        CommandPaneExit, Event, InputMode, ModeInfo, Palette, PaletteColor, PaneInfo,
+        PaneLayoutOrigin, Style,
+    },
     input::{
         command::TerminalAction,
         layout::{
-            FloatingPaneLayout, Run, RunPluginLocation, SwapFloatingLayout, SwapTiledLayout,
+            FitToContent, FloatingPaneLayout, FocusPolicy, PaneNameValues, PanePositionsCache, Run,
+            RunPluginLocation, SessionContext, SwapFloatingLayout, SwapTiledLayout, TabPin,
             TiledPaneLayout,
         },
+        options::PaneTitleFormats,
         parse_keys,
     },
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
@@ -96,6 +105,13 @@ pub(crate) struct Tab {
     pub position: usize,
     pub name: String,
     pub prev_name: String,
+    pub pin: Option<TabPin>, // pinned tabs are kept first or last when the tabs are reordered
+    pub pane_title_formats: PaneTitleFormats,
+    pub command_spawn_interval: Option<Duration>, // layout command panes are spawned this long
+    // apart
+    pub default_focus: Option<FocusPolicy>, // focuses a pane when the layout focuses none
+    pub max_content_width: Option<usize>,   // the panes are centered in at most this many columns
+    content_x: usize, // the column the display area starts at, non-zero when centered
     tiled_panes: TiledPanes,
     floating_panes: FloatingPanes,
     suppressed_panes: HashMap<PaneId, Box<dyn Pane>>,
@@ -133,8 +149,42 @@ pub(crate) struct Tab {
     pending_instructions: Vec<BufferedTabInstruction>, // instructions that came while the tab was
     // pending and need to be re-applied
     swap_layouts: SwapLayouts,
+    respawning_panes: HashMap<PaneId, Respawn>, // panes whose command was stopped to run another
+    // one in its place once it exits
+    pane_dependencies: PaneDependencies,
+    queued_commands: VecDeque<(PaneId, RunCommand)>, // layout command panes waiting for their
+    // turn to be spawned
+    layout_panes: HashMap<PaneId, LayoutPane>,
+    failure_commands: HashMap<PaneId, FailureCommand>, // the layout command panes with an
+    // on_failure pane
+    pane_positions: PanePositionsCache, // where the swap layouts put the panes, by size
+    pending_resize_relayout: bool, // resized without re-applying the swap layouts to the new size
+}
+
+/// A pane created by a layout, plugins are told where it was declared
+struct LayoutPane {
+    origin: PaneLayoutOrigin,
+    command_started_at: Option<Instant>, // while the pane's command runs
+    run_command: Option<RunCommand>,     // as it last ran, to re-run it
 }
 
+/// The command of the `on_failure` pane of a layout command pane, run in its place when the
+/// pane's command exits with a non-zero status
+struct FailureCommand {
+    run_command: RunCommand,
+    is_running: bool, // in place of the pane's own command, which is the one re-run
+}
+
+/// What runs in a pane once the command that was stopped in it exits
+enum Respawn {
+    Command(RunCommand),     // another command
+    SameCommand(RunCommand), // the same command again, this is the one it last ran as
+    Shell,
+}
+
+// written to a re-run pane, to tell its runs apart in the scrollback
+const RERUN_SEPARATOR: &str = "\r\n\u{1b}[2m──── re-run ────\u{1b}[m\r\n";
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(crate = "self::serde")]
 pub(crate) struct TabData {
@@ -333,6 +383,31 @@ pub trait Pane {
     fn load_pane_name(&mut self);
     fn set_borderless(&mut self, borderless: bool);
     fn borderless(&self) -> bool;
+    fn set_hide_title(&mut self, hide_title: bool);
+    fn hide_title(&self) -> bool;
+    fn set_read_only(&mut self, read_only: bool);
+    fn read_only(&self) -> bool;
+    fn set_exclude_from_dump(&mut self, exclude_from_dump: bool);
+    fn exclude_from_dump(&self) -> bool;
+    /// A pinned floating pane is shown above the tiled panes even when the floating panes are
+    /// hidden
+    fn set_pinned(&mut self, pinned: bool);
+    fn pinned(&self) -> bool;
+    /// The "fit" height and width of the floating pane, it is resized to its output with them
+    /// once its command exits
+    fn set_fit_to_content(
+        &mut self,
+        _height_fit: Option<FitToContent>,
+        _width_fit: Option<FitToContent>,
+    ) {
+    }
+    fn fit_to_content(&self) -> (Option<FitToContent>, Option<FitToContent>) {
+        (None, None)
+    }
+    /// The rows and columns the output of the pane takes up, `None` if it is not known
+    fn content_size(&self) -> Option<(usize, usize)> {
+        None
+    }
     // TODO: this should probably be merged with the mouse_right_click
     fn handle_right_click(&mut self, _to: &Position, _client_id: ClientId) {}
     fn mouse_left_click(&self, _position: &Position, _is_held: bool) -> Option<String> {
@@ -396,11 +471,23 @@ pub trait Pane {
     fn hold(&mut self, _exit_status: Option<i32>, _is_first_run: bool, _run_command: RunCommand) {
         // No-op by default, only terminal panes support holding
     }
+    fn is_held(&self) -> bool {
+        // False by default, only terminal panes support holding
+        false
+    }
+    fn set_queued(&mut self, _queued: bool) {
+        // No-op by default, only terminal panes run layout commands
+    }
+    /// Whether this pane waits for its turn to run its layout command
+    fn is_queued(&self) -> bool {
+        false
+    }
     fn add_red_pane_frame_color_override(&mut self, _error_text: Option<String>);
     fn clear_pane_frame_color_override(&mut self);
     fn frame_color_override(&self) -> Option<PaletteColor>;
     fn invoked_with(&self) -> &Option<Run>;
     fn set_title(&mut self, title: String);
+    fn pane_name(&self) -> &str;
 }
 
 #[derive(Clone, Debug)]
@@ -430,6 +517,66 @@ pub fn get_next_terminal_position(
     tiled_panes_count + floating_panes_count + 1
 }
 
+fn pane_id_matches(pane_id: &PaneId, id: &str) -> bool {
+    // logical ids can be given as eg. "terminal_1" or "plugin_2", a bare number refers to a
+    // terminal pane
+    match pane_id {
+        PaneId::Terminal(terminal_id) => {
+            id == format!("terminal_{}", terminal_id) || id == terminal_id.to_string()
+        },
+        PaneId::Plugin(plugin_id) => id == format!("plugin_{}", plugin_id),
+    }
+}
+
+fn pane_id_string(pane_id: &PaneId) -> String {
+    match pane_id {
+        PaneId::Terminal(terminal_id) => format!("terminal_{}", terminal_id),
+        PaneId::Plugin(plugin_id) => format!("plugin_{}", plugin_id),
+    }
+}
+
+fn pane_name_or_id(pane_id: &PaneId, pane: &dyn Pane) -> String {
+    if pane.pane_name().is_empty() {
+        pane_id_string(pane_id)
+    } else {
+        pane.pane_name().to_owned()
+    }
+}
+
+fn run_summary(run: &Run) -> String {
+    match run {
+        Run::Command(run_command) => run_command.to_string(),
+        Run::EditFile(paths, _line_number, _editor_splits) => {
+            let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+            format!("edit {}", paths.join(" "))
+        },
+        Run::Plugin(run_plugin) => format!("plugin {}", run_plugin.location),
+        Run::Cwd(cwd) => format!("cwd {}", cwd.display()),
+    }
+}
+
+fn shell_command_line(run_command: &RunCommand) -> String {
+    // the command is typed into an interactive shell, so arguments that the shell would split
+    // or expand are single-quoted
+    let quote = |word: &str| {
+        let is_safe = !word.is_empty()
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+        if is_safe {
+            word.to_owned()
+        } else {
+            format!("'{}'", word.replace('\'', "'\\''"))
+        }
+    };
+    let mut command_line = quote(&run_command.command.to_string_lossy());
+    for arg in &run_command.args {
+        command_line.push(' ');
+        command_line.push_str(&quote(arg));
+    }
+    command_line
+}
+
 impl Tab {
     // FIXME: Still too many arguments for clippy to be happy...
     #[allow(clippy::too_many_arguments)]
@@ -510,6 +657,12 @@ impl Tab {
             suppressed_panes: HashMap::new(),
             name: name.clone(),
             prev_name: name,
+            pin: None,
+            pane_title_formats: PaneTitleFormats::default(),
+            command_spawn_interval: None,
+            default_focus: None,
+            max_content_width: None,
+            content_x: 0,
             max_panes,
             viewport,
             display_area,
@@ -539,6 +692,13 @@ impl Tab {
             is_pending: true, // will be switched to false once the layout is applied
             pending_instructions: vec![],
             swap_layouts,
+            respawning_panes: HashMap::new(),
+            pane_dependencies: PaneDependencies::default(),
+            queued_commands: VecDeque::new(),
+            layout_panes: HashMap::new(),
+            failure_commands: HashMap::new(),
+            pane_positions: PanePositionsCache::default(),
+            pending_resize_relayout: false,
         }
     }
 
@@ -553,6 +713,65 @@ impl Tab {
     ) -> Result<()> {
         self.swap_layouts
             .set_base_layout((layout.clone(), floating_panes_layout.clone()));
+        self.constrain_content_width(&layout);
+        // the pty spawned a terminal for each of the tiled run instructions in order, except for
+        // the plugins
+        let tiled_failure_commands = layout
+            .extract_run_instructions()
+            .into_iter()
+            .zip(layout.extract_failure_commands())
+            .filter(|(run, _failure_command)| !matches!(run, Some(Run::Plugin(_))))
+            .zip(new_terminal_ids.iter());
+        for ((_run, failure_command), (terminal_id, _hold_for_command)) in tiled_failure_commands {
+            if let Some(run_command) = failure_command {
+                self.failure_commands.insert(
+                    PaneId::Terminal(*terminal_id),
+                    FailureCommand {
+                        run_command,
+                        is_running: false,
+                    },
+                );
+            }
+        }
+        // panes held only because of their dependency, the ones the user asked to start suspended
+        // are left for them to start
+        let panes_with_dependencies: Vec<(PaneId, RunCommand)> = new_terminal_ids
+            .iter()
+            .chain(new_floating_terminal_ids.iter())
+            .filter_map(|(terminal_id, hold_for_command)| match hold_for_command {
+                Some(run_command)
+                    if run_command.depends_on.is_some() && !run_command.hold_on_start =>
+                {
+                    Some((PaneId::Terminal(*terminal_id), run_command.clone()))
+                },
+                _ => None,
+            })
+            .collect();
+        // panes held only to be spawned one at a time, the pty spawned the first of them already
+        let queued_commands: VecDeque<(PaneId, RunCommand)> = new_terminal_ids
+            .iter()
+            .chain(new_floating_terminal_ids.iter())
+            .filter_map(|(terminal_id, hold_for_command)| match hold_for_command {
+                Some(run_command)
+                    if self.command_spawn_interval.is_some()
+                        && run_command.starts_without_waiting() =>
+                {
+                    Some((PaneId::Terminal(*terminal_id), run_command.clone()))
+                },
+                _ => None,
+            })
+            .collect();
+        // the base layout keeps the names of its panes as they are written, for swapping back to
+        // it and dumping it
+        let pane_name_values = PaneNameValues::new(&self.name);
+        let layout = layout.with_resolved_pane_names(&pane_name_values);
+        let floating_panes_layout = floating_panes_layout
+            .iter()
+            .enumerate()
+            .map(|(index, floating_pane_layout)| {
+                floating_pane_layout.with_resolved_name(index + 1, &pane_name_values)
+            })
+            .collect();
         let layout_has_floating_panes = LayoutApplier::new(
             &self.viewport,
             &self.senders,
@@ -563,11 +782,14 @@ impl Tab {
             &self.character_cell_size,
             &self.style,
             &self.display_area,
+            self.content_x,
             &mut self.tiled_panes,
             &mut self.floating_panes,
             self.draw_pane_frames,
             &mut self.focus_pane_id,
             &self.os_api,
+            &self.pane_title_formats,
+            self.default_focus,
         )
         .apply_layout(
             layout,
@@ -583,11 +805,304 @@ impl Tab {
             }
         }
         self.tiled_panes.reapply_pane_frames();
+        self.wait_for_pane_dependencies(panes_with_dependencies)?;
+        self.queue_commands(queued_commands)?;
         self.is_pending = false;
         self.apply_buffered_instructions()?;
         Ok(())
     }
-    pub fn swap_layout_info(&self) -> (Option<String>, bool) {
+    /// Narrows the display area of the (still empty) tab to its `max_content_width`, unless the
+    /// layout does not fit in it, in which case the tab keeps its full width
+    fn constrain_content_width(&mut self, layout: &TiledPaneLayout) {
+        let tab_area = *self.display_area.borrow();
+        let (content_x, content_size) = self.content_area(tab_area);
+        if content_size == tab_area {
+            return;
+        }
+        let mut space = PaneGeom::from(&content_size);
+        space.x = content_x;
+        if let Err(e) = layout.position_panes_in_space(&space, None) {
+            log::warn!(
+                "Layout of tab \"{}\" does not fit in its max_content_width of {} columns ({}), using the full width",
+                self.name,
+                content_size.cols,
+                e
+            );
+            self.max_content_width = None;
+            return;
+        }
+        self.display_area.borrow_mut().cols = content_size.cols;
+        let mut viewport = self.viewport.borrow_mut();
+        viewport.x = content_x;
+        viewport.cols = content_size.cols;
+        self.content_x = content_x;
+    }
+    /// The column the panes start at and the space they are laid out in when the tab is given
+    /// `tab_area`: all of it, or `max_content_width` columns centered in it
+    fn content_area(&self, tab_area: Size) -> (usize, Size) {
+        match self.max_content_width {
+            Some(max_content_width) if max_content_width < tab_area.cols => (
+                (tab_area.cols - max_content_width) / 2,
+                Size {
+                    rows: tab_area.rows,
+                    cols: max_content_width,
+                },
+            ),
+            _ => (0, tab_area),
+        }
+    }
+    /// Recenters the panes in `tab_area`, returning the size they should be resized to
+    fn recenter_content(&mut self, tab_area: Size) -> Size {
+        let (content_x, content_size) = self.content_area(tab_area);
+        if content_x != self.content_x {
+            let columns = content_x as isize - self.content_x as isize;
+            self.tiled_panes.move_panes_horizontally(columns);
+            self.floating_panes.move_panes_horizontally(columns);
+            let mut viewport = self.viewport.borrow_mut();
+            viewport.x = (viewport.x as isize + columns).max(0) as usize;
+            self.content_x = content_x;
+        }
+        content_size
+    }
+    fn wait_for_pane_dependencies(&mut self, panes: Vec<(PaneId, RunCommand)>) -> Result<()> {
+        let err_context = || format!("failed to wait for pane dependencies");
+        for (pane_id, run_command) in panes {
+            let dependency = match run_command.depends_on.clone() {
+                Some(dependency) => dependency,
+                None => continue,
+            };
+            match self.find_pane_id_by_name_or_id(&dependency.pane_name) {
+                Some(dependency_id @ PaneId::Terminal(_)) if dependency_id != pane_id => {
+                    self.pane_dependencies.wait_for(
+                        pane_id,
+                        dependency_id,
+                        dependency.pane_name,
+                        dependency.ready_when.as_ref(),
+                        run_command,
+                    );
+                    if let Some(timeout_secs) = dependency.timeout_secs {
+                        self.senders
+                            .send_to_background_jobs(BackgroundJob::PaneDependencyTimeout(
+                                pane_id,
+                                Duration::from_secs(timeout_secs),
+                            ))
+                            .with_context(err_context)?;
+                    }
+                },
+                _ => {
+                    log::warn!(
+                        "Pane \"{}\" not found in tab, starting the pane depending on it",
+                        dependency.pane_name
+                    );
+                    self.rerun_held_pane(pane_id, run_command)
+                        .with_context(err_context)?;
+                },
+            }
+        }
+        Ok(())
+    }
+    fn queue_commands(&mut self, queued_commands: VecDeque<(PaneId, RunCommand)>) -> Result<()> {
+        for (pane_id, _run_command) in &queued_commands {
+            if let Some(pane) = self
+                .floating_panes
+                .get_pane_mut(*pane_id)
+                .or_else(|| self.tiled_panes.get_pane_mut(*pane_id))
+            {
+                pane.set_queued(true);
+            }
+        }
+        let was_empty = self.queued_commands.is_empty();
+        self.queued_commands.extend(queued_commands);
+        if was_empty {
+            self.schedule_next_queued_command()?;
+        }
+        Ok(())
+    }
+    fn schedule_next_queued_command(&mut self) -> Result<()> {
+        match self.command_spawn_interval {
+            Some(interval) if !self.queued_commands.is_empty() => self
+                .senders
+                .send_to_background_jobs(BackgroundJob::SpawnQueuedCommand(self.index, interval))
+                .with_context(|| format!("failed to schedule the next queued command")),
+            _ => Ok(()),
+        }
+    }
+    /// Spawns the next layout command pane waiting for its turn, skipping the ones the user
+    /// already started
+    pub fn spawn_next_queued_command(&mut self) -> Result<()> {
+        while let Some((pane_id, run_command)) = self.queued_commands.pop_front() {
+            if self.pane_is_queued(pane_id) {
+                self.rerun_held_pane(pane_id, run_command)
+                    .with_context(|| format!("failed to spawn queued pane {pane_id:?}"))?;
+                break;
+            }
+        }
+        self.schedule_next_queued_command()
+    }
+    /// Spawns all the layout command panes still waiting for their turn
+    pub fn flush_command_queue(&mut self) -> Result<()> {
+        while let Some((pane_id, run_command)) = self.queued_commands.pop_front() {
+            if self.pane_is_queued(pane_id) {
+                self.rerun_held_pane(pane_id, run_command)
+                    .with_context(|| format!("failed to spawn queued pane {pane_id:?}"))?;
+            }
+        }
+        Ok(())
+    }
+    fn pane_is_queued(&self, pane_id: PaneId) -> bool {
+        self.floating_panes
+            .get_pane(pane_id)
+            .or_else(|| self.tiled_panes.get_pane(pane_id))
+            .map(|pane| pane.is_queued())
+            .unwrap_or(false)
+    }
+    fn release_waiting_panes(&mut self, panes: Vec<(PaneId, RunCommand)>) -> Result<()> {
+        for (pane_id, run_command) in panes {
+            let is_held = self
+                .floating_panes
+                .get_pane(pane_id)
+                .or_else(|| self.tiled_panes.get_pane(pane_id))
+                .map(|pane| pane.is_held())
+                .unwrap_or(false);
+            if is_held {
+                self.rerun_held_pane(pane_id, run_command)?;
+            }
+        }
+        Ok(())
+    }
+    fn start_waiting_pane_with_warning(
+        &mut self,
+        pane_id: PaneId,
+        run_command: RunCommand,
+        warning: String,
+    ) -> Result<()> {
+        self.release_waiting_panes(vec![(pane_id, run_command)])?;
+        self.add_red_pane_frame_color_override(pane_id, Some(warning));
+        Ok(())
+    }
+    /// Starts a pane whose dependency was not ready in time
+    pub fn pane_dependency_timed_out(&mut self, pane_id: PaneId) -> Result<()> {
+        if let Some((dependency_name, run_command)) = self.pane_dependencies.timed_out(pane_id) {
+            self.start_waiting_pane_with_warning(
+                pane_id,
+                run_command,
+                format!("started before '{}' was ready", dependency_name),
+            )
+            .with_context(|| format!("failed to start pane {pane_id:?} after its timeout"))?;
+        }
+        Ok(())
+    }
+    /// Remembers where the panes created by a layout were declared, to tell plugins about it
+    pub fn record_layout_origins(&mut self, layout_source: &str, pane_ids: &[PaneId]) {
+        for pane_id in pane_ids {
+            let pane = match self
+                .floating_panes
+                .get_pane(*pane_id)
+                .or_else(|| self.tiled_panes.get_pane(*pane_id))
+            {
+                Some(pane) => pane,
+                None => continue,
+            };
+            let command_started_at = match pane.invoked_with() {
+                Some(Run::Command(_)) if !pane.is_held() => Some(Instant::now()),
+                _ => None,
+            };
+            let run_command = match pane.invoked_with() {
+                Some(Run::Command(run_command)) => Some(run_command.clone()),
+                _ => None,
+            };
+            let redacted = pane.exclude_from_dump();
+            let origin = PaneLayoutOrigin {
+                layout_source: layout_source.to_owned(),
+                pane_name: Some(pane.pane_name().to_owned()).filter(|name| !name.is_empty()),
+                run: pane
+                    .invoked_with()
+                    .as_ref()
+                    .filter(|_| !redacted)
+                    .map(run_summary),
+                redacted,
+            };
+            self.layout_panes.insert(
+                *pane_id,
+                LayoutPane {
+                    origin,
+                    command_started_at,
+                    run_command,
+                },
+            );
+        }
+    }
+    /// Tells plugins that the command of a pane created by a layout exited, `command` is the one
+    /// the layout declared if not given
+    pub fn report_command_exit(
+        &mut self,
+        pane_id: PaneId,
+        exit_status: Option<i32>,
+        command: Option<String>,
+    ) -> Result<()> {
+        let terminal_id = match pane_id {
+            PaneId::Terminal(terminal_id) => terminal_id,
+            PaneId::Plugin(_) => return Ok(()),
+        };
+        let layout_pane = match self.layout_panes.get_mut(&pane_id) {
+            Some(layout_pane) => layout_pane,
+            None => return Ok(()),
+        };
+        // held panes whose command never ran have nothing to report
+        let started_at = match layout_pane.command_started_at.take() {
+            Some(started_at) => started_at,
+            None => return Ok(()),
+        };
+        let command_pane_exit = CommandPaneExit {
+            pane_id: terminal_id,
+            command: command
+                .filter(|_| !layout_pane.origin.redacted)
+                .or_else(|| layout_pane.origin.run.clone())
+                .unwrap_or_default(),
+            exit_status,
+            runtime_secs: started_at.elapsed().as_secs_f64(),
+            layout_origin: Some(layout_pane.origin.clone()),
+        };
+        self.senders
+            .send_to_plugin(PluginInstruction::Update(vec![(
+                None,
+                None,
+                Event::CommandPaneExited(command_pane_exit),
+            )]))
+            .with_context(|| format!("failed to report the exit of pane {pane_id:?}"))
+    }
+    /// The panes of the tab as published to plugins
+    pub fn pane_infos(&self) -> Vec<PaneInfo> {
+        let tiled_panes = self
+            .get_tiled_panes()
+            .map(|(pane_id, pane)| (pane_id, pane, false));
+        let floating_panes = self
+            .floating_panes
+            .get_panes()
+            .map(|(pane_id, pane)| (pane_id, pane, true));
+        tiled_panes
+            .chain(floating_panes)
+            .map(|(pane_id, pane, is_floating)| {
+                let (id, is_plugin) = match pane_id {
+                    PaneId::Terminal(terminal_id) => (*terminal_id, false),
+                    PaneId::Plugin(plugin_id) => (*plugin_id, true),
+                };
+                PaneInfo {
+                    id,
+                    is_plugin,
+                    is_floating,
+                    name: pane.pane_name().to_owned(),
+                    tab_position: self.position,
+                    layout_origin: self
+                        .layout_panes
+                        .get(pane_id)
+                        .map(|layout_pane| layout_pane.origin.clone()),
+                }
+            })
+            .collect()
+    }
+    pub fn swap_layout_info(&self) -> (Option<String>, Option<String>, bool) {
+        // (swap_layout_name, swap_layout_label, is_swap_layout_dirty)
         if self.floating_panes.panes_are_visible() {
             self.swap_layouts.floating_layout_info()
         } else {
@@ -597,7 +1112,7 @@ impl Tab {
                 self.swap_layouts.tiled_layout_info()
             } else {
                 // no layout for single pane
-                (None, false)
+                (None, None, false)
             }
         }
     }
@@ -606,10 +1121,11 @@ impl Tab {
         client_id: Option<ClientId>,
         search_backwards: bool,
         refocus_pane: bool,
+        tag: Option<&str>,
     ) -> Result<()> {
-        if let Some(layout_candidate) = self
-            .swap_layouts
-            .swap_floating_panes(&self.floating_panes, search_backwards)
+        if let Some(layout_candidate) =
+            self.swap_layouts
+                .swap_floating_panes(&self.floating_panes, search_backwards, tag)
         {
             LayoutApplier::new(
                 &self.viewport,
@@ -621,11 +1137,14 @@ impl Tab {
                 &self.character_cell_size,
                 &self.style,
                 &self.display_area,
+                self.content_x,
                 &mut self.tiled_panes,
                 &mut self.floating_panes,
                 self.draw_pane_frames,
                 &mut self.focus_pane_id,
                 &self.os_api,
+                &self.pane_title_formats,
+                self.default_focus,
             )
             .apply_floating_panes_layout_to_existing_panes(
                 &layout_candidate,
@@ -644,6 +1163,7 @@ impl Tab {
         search_backwards: bool,
         refocus_pane: bool,
         best_effort: bool,
+        tag: Option<&str>,
     ) -> Result<()> {
         if self.tiled_panes.fullscreen_is_active() {
             self.tiled_panes.unset_fullscreen();
@@ -655,7 +1175,7 @@ impl Tab {
         };
         if let Some(layout_candidate) = self
             .swap_layouts
-            .swap_tiled_panes(&self.tiled_panes, search_backwards)
+            .swap_tiled_panes(&self.tiled_panes, search_backwards, tag)
             .or_else(|| {
                 if best_effort {
                     self.swap_layouts
@@ -665,7 +1185,7 @@ impl Tab {
                 }
             })
         {
-            LayoutApplier::new(
+            let empty_slot_count = LayoutApplier::new(
                 &self.viewport,
                 &self.senders,
                 &self.sixel_image_store,
@@ -675,17 +1195,22 @@ impl Tab {
                 &self.character_cell_size,
                 &self.style,
                 &self.display_area,
+                self.content_x,
                 &mut self.tiled_panes,
                 &mut self.floating_panes,
                 self.draw_pane_frames,
                 &mut self.focus_pane_id,
                 &self.os_api,
+                &self.pane_title_formats,
+                self.default_focus,
             )
             .apply_tiled_panes_layout_to_existing_panes(
                 &layout_candidate,
                 refocus_pane,
                 client_id,
+                &mut self.pane_positions,
             )?;
+            self.fill_empty_layout_slots(empty_slot_count)?;
         }
         self.tiled_panes.reapply_pane_frames();
         self.is_pending = false;
@@ -696,36 +1221,196 @@ impl Tab {
         self.should_clear_display_before_rendering = true;
         Ok(())
     }
+    fn fill_empty_layout_slots(&mut self, empty_slot_count: usize) -> Result<()> {
+        // the new panes will be placed in the empty slots once they arrive, when the swap layout
+        // is re-applied in new_pane
+        let panes_to_spawn = self.swap_layouts.request_empty_slot_panes(empty_slot_count);
+        for _ in 0..panes_to_spawn {
+            let should_float = false;
+            let name = None;
+            let instruction = PtyInstruction::SpawnTerminal(
+                None,
+                Some(should_float),
+                name,
+                ClientOrTabIndex::TabIndex(self.index),
+            );
+            self.senders
+                .send_to_pty(instruction)
+                .with_context(|| format!("failed to fill empty swap layout slot"))?;
+        }
+        Ok(())
+    }
     pub fn previous_swap_layout(&mut self, client_id: Option<ClientId>) -> Result<()> {
-        // warning, here we cache resizes rather than sending them to the pty, we do that in
-        // apply_cached_resizes below - beware when bailing on this function early!
-        self.os_api.cache_resizes();
         let search_backwards = true;
-        if self.floating_panes.panes_are_visible() {
-            self.relayout_floating_panes(client_id, search_backwards, true)?;
+        self.cycle_swap_layouts(client_id, search_backwards, true, None)
+    }
+    pub fn next_swap_layout(
+        &mut self,
+        client_id: Option<ClientId>,
+        refocus_pane: bool,
+    ) -> Result<()> {
+        let search_backwards = false;
+        self.cycle_swap_layouts(client_id, search_backwards, refocus_pane, None)
+    }
+    /// Turns the tiled panes a quarter turn by rotating the layout they are in now (see
+    /// [`TiledPaneLayout::rotate`]) and moving them to their place in it, like swap layouts do.
+    /// Stacks are not rotated, they keep their panes one above the other and only move along
+    /// with the rest of the layout, which is pointed out to the user.
+    pub fn rotate_tiled_panes(
+        &mut self,
+        clockwise: bool,
+        client_id: Option<ClientId>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to rotate the tiled panes of tab {}", self.index);
+        if self.tiled_panes.fullscreen_is_active() {
+            self.tiled_panes.unset_fullscreen();
+        }
+        let panes: Vec<(PaneId, PaneGeom)> = self
+            .tiled_panes
+            .get_panes()
+            .map(|(pane_id, pane)| (*pane_id, pane.position_and_size()))
+            .collect();
+        let has_stacks = panes.iter().any(|(_pane_id, geom)| geom.is_stacked);
+        // panes hidden in a deep stack or in a hidden panel have no place of their own to turn
+        let rotated_layout = if self.tiled_panes.panes_hidden_in_deep_stacks_count() == 0
+            && self.tiled_panes.panes_to_hide_count() == 0
+        {
+            effective_tiled_layout(&panes).map(|mut layout| {
+                layout.rotate(clockwise);
+                layout
+            })
         } else {
-            self.relayout_tiled_panes(client_id, search_backwards, true, false)?;
+            None
+        };
+        let is_rotated = match rotated_layout {
+            Some(rotated_layout) => LayoutApplier::new(
+                &self.viewport,
+                &self.senders,
+                &self.sixel_image_store,
+                &self.link_handler,
+                &self.terminal_emulator_colors,
+                &self.terminal_emulator_color_codes,
+                &self.character_cell_size,
+                &self.style,
+                &self.display_area,
+                self.content_x,
+                &mut self.tiled_panes,
+                &mut self.floating_panes,
+                self.draw_pane_frames,
+                &mut self.focus_pane_id,
+                &self.os_api,
+                &self.pane_title_formats,
+                self.default_focus,
+            )
+            .apply_tiled_panes_layout_to_panes_by_id(&rotated_layout)
+            .with_context(err_context)?,
+            None => false,
+        };
+        let message = if !is_rotated {
+            Some("CAN'T ROTATE!")
+        } else if has_stacks {
+            Some("STACKS ARE NOT ROTATED")
+        } else {
+            None
+        };
+        if let (Some(message), Some(active_pane_id)) = (
+            message,
+            client_id.and_then(|client_id| self.get_active_pane_id(client_id)),
+        ) {
+            self.senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    vec![active_pane_id],
+                    message.into(),
+                ))
+                .with_context(err_context)?;
+        }
+        if is_rotated {
+            // the panes are no longer laid out the way any of the swap layouts lays them out
+            self.swap_layouts.set_is_tiled_damaged();
+            self.tiled_panes.reapply_pane_frames();
+            let display_area = *self.display_area.borrow();
+            self.tiled_panes.resize(display_area);
+            self.should_clear_display_before_rendering = true;
+            self.set_force_render();
         }
-        self.os_api.apply_cached_resizes();
         Ok(())
     }
-    pub fn next_swap_layout(
+    /// Replaces the swap layouts of the tab with the reloaded ones of the first of
+    /// `reloaded_swap_layouts` (pairs of the swap layouts before and after they were reloaded) it
+    /// was opened with, they are used from the next time the tab moves to another swap layout.
+    /// Returns whether they were replaced
+    pub fn reload_swap_layouts(
+        &mut self,
+        reloaded_swap_layouts: &[(
+            (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+            (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+        )],
+    ) -> bool {
+        reloaded_swap_layouts
+            .iter()
+            .any(|(previous_swap_layouts, swap_layouts)| {
+                self.swap_layouts
+                    .reload(previous_swap_layouts, swap_layouts)
+            })
+    }
+    /// Moves to the next (or previous) swap layout, skipping those without `tag` if one is given
+    pub fn cycle_swap_layouts(
         &mut self,
         client_id: Option<ClientId>,
+        search_backwards: bool,
         refocus_pane: bool,
+        tag: Option<&str>,
     ) -> Result<()> {
+        let floating_panes_are_visible = self.floating_panes.panes_are_visible();
+        if let Some(tag) = tag {
+            let has_tagged_layouts = if floating_panes_are_visible {
+                self.swap_layouts.has_floating_layouts_tagged(tag)
+            } else {
+                self.swap_layouts.has_tiled_layouts_tagged(tag)
+            };
+            if !has_tagged_layouts {
+                return self.show_missing_swap_layout_tag(client_id, tag);
+            }
+        }
         // warning, here we cache resizes rather than sending them to the pty, we do that in
         // apply_cached_resizes below - beware when bailing on this function early!
         self.os_api.cache_resizes();
-        let search_backwards = false;
-        if self.floating_panes.panes_are_visible() {
-            self.relayout_floating_panes(client_id, search_backwards, refocus_pane)?;
+        if floating_panes_are_visible {
+            self.relayout_floating_panes(client_id, search_backwards, refocus_pane, tag)?;
         } else {
-            self.relayout_tiled_panes(client_id, search_backwards, refocus_pane, false)?;
+            self.relayout_tiled_panes(client_id, search_backwards, refocus_pane, false, tag)?;
         }
         self.os_api.apply_cached_resizes();
         Ok(())
     }
+    fn show_missing_swap_layout_tag(&self, client_id: Option<ClientId>, tag: &str) -> Result<()> {
+        let available_tags = if self.floating_panes.panes_are_visible() {
+            self.swap_layouts.floating_layout_tags()
+        } else {
+            self.swap_layouts.tiled_layout_tags()
+        };
+        let message = if available_tags.is_empty() {
+            format!("NO SWAP LAYOUT TAGGED {}, THERE ARE NO TAGS", tag)
+        } else {
+            format!(
+                "NO SWAP LAYOUT TAGGED {}, TAGS: {}",
+                tag,
+                available_tags.join(", ")
+            )
+        };
+        log::info!("{}", message);
+        if let Some(active_pane_id) =
+            client_id.and_then(|client_id| self.get_active_pane_id(client_id))
+        {
+            self.senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    vec![active_pane_id],
+                    message,
+                ))
+                .with_context(|| format!("failed to show the swap layout tags"))?;
+        }
+        Ok(())
+    }
     pub fn apply_buffered_instructions(&mut self) -> Result<()> {
         let buffered_instructions: Vec<BufferedTabInstruction> =
             self.pending_instructions.drain(..).collect();
@@ -795,6 +1480,8 @@ impl Tab {
                 mode_info.unwrap_or_else(|| self.default_mode_info.clone()),
             );
         }
+        // a tab no client was looking at while the terminal was resized
+        self.relayout_after_resize();
         self.set_force_render();
         Ok(())
     }
@@ -1026,6 +1713,7 @@ impl Tab {
                         None,
                     );
                     new_terminal.set_active_at(Instant::now());
+                    self.swap_layouts.empty_slot_pane_arrived();
                     self.tiled_panes.insert_pane(pid, Box::new(new_terminal));
                     self.should_clear_display_before_rendering = true;
                     if let Some(client_id) = client_id {
@@ -1281,6 +1969,205 @@ impl Tab {
             || self.floating_panes.panes_contain(pid)
             || self.suppressed_panes.values().any(|s_p| s_p.pid() == *pid)
     }
+    pub fn find_pane_id_by_name_or_id(&self, name_or_id: &str) -> Option<PaneId> {
+        // pane names take precedence over logical ids
+        self.get_tiled_panes()
+            .chain(self.floating_panes.get_panes())
+            .find(|(_, pane)| pane.pane_name() == name_or_id)
+            .or_else(|| {
+                self.get_tiled_panes()
+                    .chain(self.floating_panes.get_panes())
+                    .find(|(pane_id, _)| pane_id_matches(pane_id, name_or_id))
+            })
+            .map(|(pane_id, _)| *pane_id)
+    }
+    /// The panes the layout declared with this name, or else the panes currently named so, or
+    /// else the pane with this logical id
+    pub fn find_pane_ids_by_layout_name_or_id(&self, name_or_id: &str) -> Vec<PaneId> {
+        let pane_ids: Vec<PaneId> = self
+            .get_tiled_panes()
+            .chain(self.floating_panes.get_panes())
+            .map(|(pane_id, _)| *pane_id)
+            .collect();
+        let with_layout_name: Vec<PaneId> = pane_ids
+            .iter()
+            .copied()
+            .filter(|pane_id| {
+                self.layout_panes
+                    .get(pane_id)
+                    .and_then(|layout_pane| layout_pane.origin.pane_name.as_deref())
+                    == Some(name_or_id)
+            })
+            .collect();
+        if !with_layout_name.is_empty() {
+            return with_layout_name;
+        }
+        let with_name: Vec<PaneId> = self
+            .get_tiled_panes()
+            .chain(self.floating_panes.get_panes())
+            .filter(|(_, pane)| pane.pane_name() == name_or_id)
+            .map(|(pane_id, _)| *pane_id)
+            .collect();
+        if !with_name.is_empty() {
+            return with_name;
+        }
+        pane_ids
+            .into_iter()
+            .filter(|pane_id| pane_id_matches(pane_id, name_or_id))
+            .collect()
+    }
+    pub fn pane_names(&self) -> Vec<String> {
+        self.get_tiled_panes()
+            .chain(self.floating_panes.get_panes())
+            .map(|(pane_id, pane)| pane_name_or_id(pane_id, pane.as_ref()))
+            .collect()
+    }
+    /// The name of a pane, or its id (eg. "terminal_1") if it has none
+    pub fn pane_name(&self, pane_id: PaneId) -> Option<String> {
+        self.floating_panes
+            .get_pane(pane_id)
+            .or_else(|| self.tiled_panes.get_pane(pane_id))
+            .map(|pane| pane_name_or_id(&pane_id, pane.as_ref()))
+    }
+    pub fn pane_is_busy(&self, pane_id: PaneId) -> bool {
+        // we have no way of knowing whether an interactive shell is running something, so only
+        // command panes whose command has not yet exited are considered busy
+        self.floating_panes
+            .get(&pane_id)
+            .or_else(|| self.tiled_panes.get_pane(pane_id))
+            .map(|pane| {
+                matches!(
+                    pane.invoked_with(),
+                    Some(Run::Command(..)) | Some(Run::EditFile(..))
+                ) && !pane.is_held()
+            })
+            .unwrap_or(false)
+    }
+    pub fn run_command_in_pane(&mut self, pane_id: PaneId, run_command: RunCommand) -> Result<()> {
+        let err_context = || format!("failed to run command in pane {pane_id:?}");
+
+        if self.pane_is_busy(pane_id) {
+            // typing into a busy pane would send the command to the program running there, so we
+            // stop that program instead and run the command once it exits (see
+            // respawn_pane_if_pending)
+            self.respawning_panes
+                .insert(pane_id, Respawn::Command(run_command));
+            return self
+                .senders
+                .send_to_pty(PtyInstruction::KillCommandInPane(pane_id))
+                .with_context(err_context);
+        }
+        let pane = self
+            .floating_panes
+            .get_mut(&pane_id)
+            .or_else(|| self.tiled_panes.get_pane_mut(pane_id))
+            .ok_or_else(|| anyhow!("failed to find pane with id {pane_id:?}"))
+            .with_context(err_context)?;
+        if pane.is_held() {
+            return self
+                .rerun_held_pane(pane_id, run_command)
+                .with_context(err_context);
+        }
+        let mut command_line = shell_command_line(&run_command);
+        command_line.push('\r');
+        self.write_to_pane_id(command_line.into_bytes(), pane_id)
+            .with_context(err_context)?;
+        Ok(())
+    }
+    /// Stops the command of a pane and runs it again in its place, keeping the scrollback.
+    /// Returns why the pane cannot be re-run instead, if it cannot
+    pub fn rerun_pane(&mut self, pane_id: PaneId, force: bool) -> Result<Option<&'static str>> {
+        let err_context = || format!("failed to re-run pane {pane_id:?}");
+
+        let terminal_id = match pane_id {
+            PaneId::Terminal(terminal_id) => terminal_id,
+            PaneId::Plugin(_) => return Ok(Some("is a plugin pane and cannot be re-run.")),
+        };
+        let pane = self
+            .floating_panes
+            .get_pane(pane_id)
+            .or_else(|| self.tiled_panes.get_pane(pane_id))
+            .ok_or_else(|| anyhow!("failed to find pane with id {pane_id:?}"))
+            .with_context(err_context)?;
+        let respawn = match pane.invoked_with() {
+            Some(Run::Command(run_command)) => Respawn::SameCommand(
+                self.layout_panes
+                    .get(&pane_id)
+                    .and_then(|layout_pane| layout_pane.run_command.clone())
+                    .unwrap_or_else(|| run_command.clone()),
+            ),
+            Some(Run::EditFile(..)) => {
+                return Ok(Some("is editing a file and cannot be re-run."));
+            },
+            Some(Run::Plugin(_)) => return Ok(Some("is a plugin pane and cannot be re-run.")),
+            Some(Run::Cwd(_)) | None if force => Respawn::Shell,
+            Some(Run::Cwd(_)) | None => {
+                return Ok(Some(
+                    "is running a shell, use --force to start a new shell in its place.",
+                ));
+            },
+        };
+        let is_held = pane.is_held();
+        if self.failure_commands.contains_key(&pane_id) {
+            // the pane's own command replaces its failure command
+            self.clear_pane_frame_color_override(pane_id);
+        }
+        self.handle_pty_bytes(terminal_id, RERUN_SEPARATOR.as_bytes().to_vec())
+            .with_context(err_context)?;
+        match respawn {
+            Respawn::SameCommand(run_command) if is_held => {
+                self.rerun_held_pane(pane_id, run_command)
+            },
+            respawn => {
+                // the command gets a chance to clean up, it is respawned once it exits (see
+                // respawn_pane_if_pending)
+                self.respawning_panes.insert(pane_id, respawn);
+                self.senders
+                    .send_to_pty(PtyInstruction::TerminateCommandInPane(pane_id))
+            },
+        }
+        .with_context(err_context)?;
+        Ok(None)
+    }
+    /// Runs what was waiting for the previous command of this pane to exit, returns false if
+    /// nothing was. `exited_command` is the command that exited, as it ran
+    pub fn respawn_pane_if_pending(
+        &mut self,
+        pane_id: PaneId,
+        exited_command: Option<RunCommand>,
+    ) -> Result<bool> {
+        let err_context = || format!("failed to respawn pane {pane_id:?}");
+        match self.respawning_panes.remove(&pane_id) {
+            Some(Respawn::Command(run_command)) => {
+                self.rerun_held_pane(pane_id, run_command)
+                    .with_context(err_context)?;
+            },
+            Some(Respawn::SameCommand(run_command)) => {
+                self.rerun_held_pane(pane_id, exited_command.unwrap_or(run_command))
+                    .with_context(err_context)?;
+            },
+            Some(Respawn::Shell) => {
+                self.senders
+                    .send_to_pty(PtyInstruction::RespawnShellInPane(pane_id))
+                    .with_context(err_context)?;
+            },
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+    fn rerun_held_pane(&mut self, pane_id: PaneId, run_command: RunCommand) -> Result<()> {
+        let pane = self
+            .floating_panes
+            .get_mut(&pane_id)
+            .or_else(|| self.tiled_panes.get_pane_mut(pane_id))
+            .ok_or_else(|| anyhow!("failed to find pane with id {pane_id:?}"))?;
+        // replace the held command and re-run it as if the user pressed ENTER
+        pane.set_title(Run::Command(run_command.clone()).pane_title(&self.pane_title_formats));
+        pane.set_queued(false);
+        pane.hold(None, false, run_command);
+        self.write_to_pane_id(vec![13], pane_id)?;
+        Ok(())
+    }
     pub fn handle_pty_bytes(&mut self, pid: u32, bytes: VteBytes) -> Result<()> {
         if self.is_pending {
             self.pending_instructions
@@ -1288,6 +2175,11 @@ impl Tab {
             return Ok(());
         }
         let err_context = || format!("failed to handle pty bytes from fd {pid}");
+        let ready_panes = self
+            .pane_dependencies
+            .output_received(PaneId::Terminal(pid), &bytes);
+        self.release_waiting_panes(ready_panes)
+            .with_context(err_context)?;
         if let Some(terminal_output) = self
             .tiled_panes
             .get_pane_mut(PaneId::Terminal(pid))
@@ -1461,6 +2353,24 @@ impl Tab {
         let err_context = || format!("failed to write to pane with id {pane_id:?}");
 
         let mut should_update_ui = false;
+        let pane_is_read_only = self
+            .floating_panes
+            .get(&pane_id)
+            .or_else(|| self.tiled_panes.get_pane(pane_id))
+            .or_else(|| self.suppressed_panes.get(&pane_id))
+            .map(|pane| pane.read_only())
+            .unwrap_or(false);
+        if pane_is_read_only {
+            self.senders
+                .send_to_plugin(PluginInstruction::Update(vec![(
+                    None,
+                    None,
+                    Event::InputToReadOnlyPane,
+                )]))
+                .with_context(err_context)
+                .non_fatal();
+            return Ok(should_update_ui);
+        }
         match pane_id {
             PaneId::Terminal(active_terminal_id) => {
                 let active_terminal = self
@@ -1481,6 +2391,12 @@ impl Tab {
                     },
                     Some(AdjustedInput::ReRunCommandInThisPane(command)) => {
                         self.pids_waiting_resize.insert(active_terminal_id);
+                        if let Some(layout_pane) = self
+                            .layout_panes
+                            .get_mut(&PaneId::Terminal(active_terminal_id))
+                        {
+                            layout_pane.command_started_at = Some(Instant::now());
+                        }
                         self.senders
                             .send_to_pty(PtyInstruction::ReRunCommandInPane(
                                 PaneId::Terminal(active_terminal_id),
@@ -1537,6 +2453,12 @@ impl Tab {
         }
         self.tiled_panes.toggle_active_pane_fullscreen(client_id);
     }
+    pub fn toggle_active_pane_read_only(&mut self, client_id: ClientId) {
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            active_pane.set_read_only(!active_pane.read_only());
+            active_pane.set_should_render(true);
+        }
+    }
     pub fn is_fullscreen_active(&self) -> bool {
         self.tiled_panes.fullscreen_is_active()
     }
@@ -1607,7 +2529,9 @@ impl Tab {
         self.tiled_panes
             .render(output, self.floating_panes.panes_are_visible())
             .with_context(err_context)?;
-        if self.floating_panes.panes_are_visible() && self.floating_panes.has_active_panes() {
+        if (self.floating_panes.panes_are_visible() && self.floating_panes.has_active_panes())
+            || self.floating_panes.has_pinned_panes()
+        {
             self.floating_panes
                 .render(output)
                 .with_context(err_context)?;
@@ -1628,6 +2552,9 @@ impl Tab {
         Ok(())
     }
 
+    pub fn will_clear_display(&self) -> bool {
+        self.should_clear_display_before_rendering
+    }
     fn hide_cursor_and_clear_display_as_needed(&mut self, output: &mut Output) {
         let hide_cursor = "\u{1b}[?25l";
         let connected_clients: Vec<ClientId> =
@@ -1726,30 +2653,70 @@ impl Tab {
         selectable_tiled_panes.count() > 0
     }
     pub fn resize_whole_tab(&mut self, new_screen_size: Size) -> Result<()> {
+        self.resize_panes_of_whole_tab(new_screen_size, true)
+    }
+    /// Resizes the panes of the tab without re-applying its swap layouts to the new size, which
+    /// is left to [`Tab::relayout_after_resize`] (eg. once the terminal stops being resized)
+    pub fn resize_whole_tab_without_relayout(&mut self, new_screen_size: Size) -> Result<()> {
+        self.resize_panes_of_whole_tab(new_screen_size, false)
+    }
+    /// Re-applies the swap layouts of the tab to the size it was last resized to, if that was
+    /// not done when it was resized
+    pub fn relayout_after_resize(&mut self) {
+        if !self.pending_resize_relayout {
+            return;
+        }
+        self.os_api.cache_resizes();
+        self.relayout_to_new_size();
+        self.should_clear_display_before_rendering = true;
+        self.set_force_render();
+        let _ = self.os_api.apply_cached_resizes();
+    }
+    /// Re-applies the swap layouts of the tab if some of their entries depend on the number of
+    /// tabs in the session and it changed
+    pub fn update_session_context(&mut self, session_context: SessionContext) {
+        if !self.swap_layouts.set_session_context(session_context) {
+            return;
+        }
+        self.os_api.cache_resizes();
+        self.relayout_to_new_size();
+        self.should_clear_display_before_rendering = true;
+        self.set_force_render();
+        let _ = self.os_api.apply_cached_resizes();
+    }
+    fn resize_panes_of_whole_tab(&mut self, new_screen_size: Size, relayout: bool) -> Result<()> {
         // warning, here we cache resizes rather than sending them to the pty, we do that in
         // apply_cached_resizes below - beware when bailing on this function early!
         self.os_api.cache_resizes();
         let err_context = || format!("failed to resize whole tab (index {})", self.index);
+        let new_screen_size = self.recenter_content(new_screen_size);
         self.floating_panes.resize(new_screen_size);
         // we need to do this explicitly because floating_panes.resize does not do this
         self.floating_panes
             .resize_pty_all_panes(&mut self.os_api)
             .with_context(err_context)?;
         self.tiled_panes.resize(new_screen_size);
+        self.pending_resize_relayout = true;
+        if relayout {
+            self.relayout_to_new_size();
+        }
+        self.should_clear_display_before_rendering = true;
+        let _ = self.os_api.apply_cached_resizes();
+        Ok(())
+    }
+    fn relayout_to_new_size(&mut self) {
+        self.pending_resize_relayout = false;
         if self.auto_layout && !self.swap_layouts.is_floating_damaged() {
             // we do this only for floating panes, because the constraint system takes care of the
             // tiled panes
             self.swap_layouts.set_is_floating_damaged();
-            let _ = self.relayout_floating_panes(None, false, false);
+            let _ = self.relayout_floating_panes(None, false, false, None);
         }
         if self.auto_layout && !self.swap_layouts.is_tiled_damaged() && !self.is_fullscreen_active()
         {
             self.swap_layouts.set_is_tiled_damaged();
-            let _ = self.relayout_tiled_panes(None, false, false, true);
+            let _ = self.relayout_tiled_panes(None, false, false, true, None);
         }
-        self.should_clear_display_before_rendering = true;
-        let _ = self.os_api.apply_cached_resizes();
-        Ok(())
     }
     pub fn resize(&mut self, client_id: ClientId, strategy: ResizeStrategy) -> Result<()> {
         let err_context = || format!("unable to resize pane");
@@ -1782,6 +2749,14 @@ impl Tab {
                                 "FIXED!".into(),
                             ))
                             .with_context(err_context)?;
+                        self.senders
+                            .send_to_plugin(PluginInstruction::Update(vec![(
+                                None,
+                                None,
+                                Event::CantResizeFixedPanes,
+                            )]))
+                            .with_context(err_context)
+                            .non_fatal();
                     },
                     _ => Err::<(), _>(err).fatal(),
                 },
@@ -2058,6 +3033,19 @@ impl Tab {
         //
         // TODO: separate the "close_pane" logic and the "move_pane_somewhere_else" logic, they're
         // overloaded here and that's not great
+        if !ignore_suppressed_panes {
+            self.queued_commands.retain(|(pane_id, _)| *pane_id != id);
+            self.layout_panes.remove(&id);
+            self.failure_commands.remove(&id);
+            for (pane_id, dependency_name, run_command) in self.pane_dependencies.pane_closed(id) {
+                self.start_waiting_pane_with_warning(
+                    pane_id,
+                    run_command,
+                    format!("started after '{}' was closed", dependency_name),
+                )
+                .non_fatal();
+            }
+        }
         if !ignore_suppressed_panes && self.suppressed_panes.contains_key(&id) {
             return match self.replace_pane_with_suppressed_pane(id) {
                 Ok(pane) => pane,
@@ -2115,13 +3103,60 @@ impl Tab {
         is_first_run: bool,
         run_command: RunCommand,
     ) {
+        // the pane's own command is the one that ran last, not its failure command
+        let failure_command_exited = self
+            .failure_commands
+            .get_mut(&id)
+            .map(|failure_command| std::mem::replace(&mut failure_command.is_running, false))
+            .unwrap_or(false);
+        if let Some(layout_pane) = self.layout_panes.get_mut(&id) {
+            if !failure_command_exited {
+                layout_pane.run_command = Some(run_command.clone());
+            }
+        }
+        if self.respawning_panes.contains_key(&id) {
+            // the pane's command was stopped to run another one in its place
+            let exited_command = Some(run_command).filter(|_| !failure_command_exited);
+            self.respawn_pane_if_pending(id, exited_command).non_fatal();
+            return;
+        }
+        if !is_first_run {
+            self.report_command_exit(id, exit_status, Some(run_command.to_string()))
+                .non_fatal();
+        }
         if self.floating_panes.panes_contain(&id) {
             self.floating_panes
                 .hold_pane(id, exit_status, is_first_run, run_command);
+            if !is_first_run {
+                self.floating_panes
+                    .fit_pane_to_content(id, &mut self.os_api)
+                    .non_fatal();
+            }
         } else {
             self.tiled_panes
                 .hold_pane(id, exit_status, is_first_run, run_command);
         }
+        let ready_panes = self.pane_dependencies.pane_exited(id, exit_status);
+        self.release_waiting_panes(ready_panes).non_fatal();
+        let failed = matches!(exit_status, Some(exit_status) if exit_status != 0);
+        if failed && !is_first_run && !failure_command_exited {
+            self.run_failure_command(id).non_fatal();
+        }
+    }
+    /// Runs the command of the `on_failure` pane of this pane in its place, until the pane is
+    /// re-run
+    fn run_failure_command(&mut self, pane_id: PaneId) -> Result<()> {
+        let run_command = match self.failure_commands.get_mut(&pane_id) {
+            Some(failure_command) => {
+                failure_command.is_running = true;
+                failure_command.run_command.clone()
+            },
+            None => return Ok(()),
+        };
+        self.rerun_held_pane(pane_id, run_command)
+            .with_context(|| format!("failed to run the on_failure command of pane {pane_id:?}"))?;
+        self.add_red_pane_frame_color_override(pane_id, Some("command failed".to_owned()));
+        Ok(())
     }
     pub fn replace_pane_with_suppressed_pane(
         &mut self,
@@ -3013,6 +4048,9 @@ impl Tab {
     pub fn panes_to_hide_count(&self) -> usize {
         self.tiled_panes.panes_to_hide_count()
     }
+    pub fn panes_hidden_in_deep_stacks_count(&self) -> usize {
+        self.tiled_panes.panes_hidden_in_deep_stacks_count()
+    }
 
     pub fn update_search_term(&mut self, buf: Vec<u8>, client_id: ClientId) -> Result<()> {
         if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
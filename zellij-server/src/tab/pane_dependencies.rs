@@ -0,0 +1,154 @@
+use crate::panes::PaneId;
+use std::collections::HashMap;
+use zellij_utils::{
+    input::command::{ReadyCondition, RunCommand},
+    regex::Regex,
+    shared::strip_ansi,
+};
+
+// how much of a dependency's latest output is kept to match ready conditions spanning several
+// reads
+const OUTPUT_TAIL_LEN: usize = 4096;
+
+enum ReadyMatcher {
+    Contains(String),
+    Matches(Regex),
+}
+
+impl ReadyMatcher {
+    fn new(ready_condition: &ReadyCondition) -> Option<Self> {
+        match ready_condition {
+            ReadyCondition::Contains(text) => Some(ReadyMatcher::Contains(text.clone())),
+            ReadyCondition::Matches(regex) => match Regex::new(regex) {
+                Ok(regex) => Some(ReadyMatcher::Matches(regex)),
+                Err(e) => {
+                    log::error!("Invalid ready_regex: {}", e);
+                    None
+                },
+            },
+        }
+    }
+    fn is_match(&self, output: &str) -> bool {
+        match self {
+            ReadyMatcher::Contains(text) => output.contains(text.as_str()),
+            ReadyMatcher::Matches(regex) => regex.is_match(output),
+        }
+    }
+}
+
+struct WaitingPane {
+    pane_id: PaneId,
+    dependency_id: PaneId,
+    dependency_name: String,
+    ready_when: Option<ReadyMatcher>, // when None, only waits for the dependency to exit
+    run_command: RunCommand,
+}
+
+/// Layout command panes that start suspended until another pane of their tab is ready, see
+/// [`zellij_utils::input::command::PaneDependency`]
+#[derive(Default)]
+pub struct PaneDependencies {
+    waiting_panes: Vec<WaitingPane>,
+    dependency_output: HashMap<PaneId, String>,
+}
+
+impl PaneDependencies {
+    pub fn wait_for(
+        &mut self,
+        pane_id: PaneId,
+        dependency_id: PaneId,
+        dependency_name: String,
+        ready_when: Option<&ReadyCondition>,
+        run_command: RunCommand,
+    ) {
+        self.waiting_panes.push(WaitingPane {
+            pane_id,
+            dependency_id,
+            dependency_name,
+            ready_when: ready_when.and_then(ReadyMatcher::new),
+            run_command,
+        });
+    }
+    /// Returns the panes whose dependency became ready with this output
+    pub fn output_received(&mut self, pane_id: PaneId, bytes: &[u8]) -> Vec<(PaneId, RunCommand)> {
+        // a waiting pane producing output was started by the user, it no longer waits
+        self.waiting_panes.retain(|w| w.pane_id != pane_id);
+        if !self
+            .waiting_panes
+            .iter()
+            .any(|w| w.dependency_id == pane_id && w.ready_when.is_some())
+        {
+            return vec![];
+        }
+        let output = self.dependency_output.entry(pane_id).or_default();
+        output.push_str(&strip_ansi(bytes));
+        if output.len() > OUTPUT_TAIL_LEN {
+            let mut tail_start = output.len() - OUTPUT_TAIL_LEN;
+            while !output.is_char_boundary(tail_start) {
+                tail_start += 1;
+            }
+            output.drain(..tail_start);
+        }
+        let output = output.clone();
+        self.release(|w| {
+            w.dependency_id == pane_id
+                && w.ready_when
+                    .as_ref()
+                    .map(|ready_when| ready_when.is_match(&output))
+                    .unwrap_or(false)
+        })
+    }
+    /// Returns the panes released by the dependency exiting successfully
+    pub fn pane_exited(
+        &mut self,
+        pane_id: PaneId,
+        exit_status: Option<i32>,
+    ) -> Vec<(PaneId, RunCommand)> {
+        self.waiting_panes.retain(|w| w.pane_id != pane_id);
+        if exit_status == Some(0) {
+            self.release(|w| w.dependency_id == pane_id)
+        } else {
+            vec![]
+        }
+    }
+    /// Returns the panes that were waiting for the closed pane, they will never see it ready
+    pub fn pane_closed(&mut self, pane_id: PaneId) -> Vec<(PaneId, String, RunCommand)> {
+        self.waiting_panes.retain(|w| w.pane_id != pane_id);
+        self.dependency_output.remove(&pane_id);
+        let (orphaned, waiting): (Vec<_>, Vec<_>) = self
+            .waiting_panes
+            .drain(..)
+            .partition(|w| w.dependency_id == pane_id);
+        self.waiting_panes = waiting;
+        orphaned
+            .into_iter()
+            .map(|w| (w.pane_id, w.dependency_name, w.run_command))
+            .collect()
+    }
+    /// Stops waiting for this pane's dependency, returns its name and the command to run
+    pub fn timed_out(&mut self, pane_id: PaneId) -> Option<(String, RunCommand)> {
+        let position = self
+            .waiting_panes
+            .iter()
+            .position(|w| w.pane_id == pane_id)?;
+        let waiting_pane = self.waiting_panes.remove(position);
+        Some((waiting_pane.dependency_name, waiting_pane.run_command))
+    }
+    fn release(&mut self, is_ready: impl Fn(&WaitingPane) -> bool) -> Vec<(PaneId, RunCommand)> {
+        let (released, waiting): (Vec<_>, Vec<_>) =
+            self.waiting_panes.drain(..).partition(|w| is_ready(w));
+        self.waiting_panes = waiting;
+        let dependency_ids: Vec<PaneId> =
+            self.waiting_panes.iter().map(|w| w.dependency_id).collect();
+        self.dependency_output
+            .retain(|pane_id, _| dependency_ids.contains(pane_id));
+        released
+            .into_iter()
+            .map(|w| (w.pane_id, w.run_command))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/pane_dependencies_tests.rs"]
+mod pane_dependencies_tests;
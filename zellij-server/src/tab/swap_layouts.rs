@@ -4,7 +4,8 @@ use std::collections::BTreeMap;
 use std::rc::Rc;
 use zellij_utils::{
     input::layout::{
-        FloatingPaneLayout, LayoutConstraint, SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
+        select_swap_layout_entry, swap_layout_label, FloatingPaneLayout, LayoutConstraint,
+        PaneCounts, SessionContext, SwapFloatingLayout, SwapTiledLayout, TiledPaneLayout,
     },
     pane_size::{PaneGeom, Size},
 };
@@ -17,6 +18,9 @@ pub struct SwapLayouts {
     current_tiled_layout_position: usize,
     is_floating_damaged: bool,
     is_tiled_damaged: bool,
+    has_base_layout: bool, // the layout the tab was opened with, first among the swap layouts
+    pending_empty_slot_panes: usize, // panes requested to fill empty slots that have not yet arrived
+    session_context: SessionContext,
     display_area: Rc<RefCell<Size>>, // includes all panes (including eg. the status bar and tab bar in the default layout)
 }
 
@@ -47,11 +51,57 @@ impl SwapLayouts {
         base_swap_floating_layout
             .insert(LayoutConstraint::MaxPanes(floating_panes_count), layout.1);
         self.swap_tiled_layouts
-            .insert(0, (base_swap_tiled_layout, Some("BASE".into())));
+            .insert(0, (base_swap_tiled_layout, Some("BASE".into()), vec![]));
         self.swap_floating_layouts
-            .insert(0, (base_swap_floating_layout, Some("BASE".into())));
+            .insert(0, (base_swap_floating_layout, Some("BASE".into()), vec![]));
         self.current_tiled_layout_position = 0;
         self.current_floating_layout_position = 0;
+        self.has_base_layout = true;
+    }
+    /// Replaces the swap layouts the tab was opened with by `swap_layouts` if they are
+    /// `previous_swap_layouts` (eg. once the layout file they were read from changed), returns
+    /// whether they were replaced. The base layout of the tab is kept, and so is the swap layout
+    /// it is on while there is one in its place
+    pub fn reload(
+        &mut self,
+        previous_swap_layouts: &(Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+        swap_layouts: &(Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+    ) -> bool {
+        let base_layout_count = if self.has_base_layout { 1 } else { 0 };
+        let swap_tiled_layouts = self.swap_tiled_layouts.get(base_layout_count..);
+        let swap_floating_layouts = self.swap_floating_layouts.get(base_layout_count..);
+        if swap_tiled_layouts != Some(previous_swap_layouts.0.as_slice())
+            || swap_floating_layouts != Some(previous_swap_layouts.1.as_slice())
+        {
+            return false;
+        }
+        self.swap_tiled_layouts.truncate(base_layout_count);
+        self.swap_tiled_layouts
+            .extend(swap_layouts.0.iter().cloned());
+        self.swap_floating_layouts.truncate(base_layout_count);
+        self.swap_floating_layouts
+            .extend(swap_layouts.1.iter().cloned());
+        self.current_tiled_layout_position = self
+            .current_tiled_layout_position
+            .min(self.swap_tiled_layouts.len().saturating_sub(1));
+        self.current_floating_layout_position = self
+            .current_floating_layout_position
+            .min(self.swap_floating_layouts.len().saturating_sub(1));
+        true
+    }
+    /// Returns whether the swap layouts should be re-applied, because some of their entries
+    /// depend on the number of tabs in the session and it changed
+    pub fn set_session_context(&mut self, session_context: SessionContext) -> bool {
+        let tab_count_changed = self.session_context.tab_count != session_context.tab_count;
+        self.session_context = session_context;
+        tab_count_changed && self.has_session_constraints()
+    }
+    fn has_session_constraints(&self) -> bool {
+        let tiled_constraints = self.swap_tiled_layouts.iter().flat_map(|l| l.0.keys());
+        let floating_constraints = self.swap_floating_layouts.iter().flat_map(|l| l.0.keys());
+        tiled_constraints
+            .chain(floating_constraints)
+            .any(|constraint| constraint.session_constraint().is_some())
     }
     pub fn set_is_floating_damaged(&mut self) {
         self.is_floating_damaged = true;
@@ -65,48 +115,79 @@ impl SwapLayouts {
     pub fn is_tiled_damaged(&self) -> bool {
         self.is_tiled_damaged
     }
-    pub fn tiled_layout_info(&self) -> (Option<String>, bool) {
-        // (swap_layout_name, is_swap_layout_dirty)
+    pub fn request_empty_slot_panes(&mut self, empty_slot_count: usize) -> usize {
+        // returns the number of new panes that should be spawned to fill these slots, we only
+        // request them once so that re-applying the layout before they arrive won't spawn more
+        let panes_to_request = empty_slot_count.saturating_sub(self.pending_empty_slot_panes);
+        self.pending_empty_slot_panes += panes_to_request;
+        panes_to_request
+    }
+    pub fn empty_slot_pane_arrived(&mut self) {
+        self.pending_empty_slot_panes = self.pending_empty_slot_panes.saturating_sub(1);
+    }
+    pub fn has_tiled_layouts_tagged(&self, tag: &str) -> bool {
+        self.swap_tiled_layouts
+            .iter()
+            .any(|(_layouts, _name, tags)| tags.iter().any(|t| t == tag))
+    }
+    pub fn has_floating_layouts_tagged(&self, tag: &str) -> bool {
+        self.swap_floating_layouts
+            .iter()
+            .any(|(_layouts, _name, tags)| tags.iter().any(|t| t == tag))
+    }
+    pub fn tiled_layout_tags(&self) -> Vec<String> {
+        sorted_tags(self.swap_tiled_layouts.iter().map(|(_, _, tags)| tags))
+    }
+    pub fn floating_layout_tags(&self) -> Vec<String> {
+        sorted_tags(self.swap_floating_layouts.iter().map(|(_, _, tags)| tags))
+    }
+    pub fn tiled_layout_info(&self) -> (Option<String>, Option<String>, bool) {
+        // (swap_layout_name, swap_layout_label, is_swap_layout_dirty)
         match self
             .swap_tiled_layouts
             .iter()
             .nth(self.current_tiled_layout_position)
         {
-            Some(current_tiled_layout) => (
-                current_tiled_layout.1.clone().or_else(|| {
-                    Some(format!(
-                        "Layout #{}",
-                        self.current_tiled_layout_position + 1
-                    ))
-                }),
-                self.is_tiled_damaged,
-            ),
-            None => (None, self.is_tiled_damaged),
+            Some(current_tiled_layout) => {
+                let name = current_tiled_layout.1.clone().unwrap_or_else(|| {
+                    format!("Layout #{}", self.current_tiled_layout_position + 1)
+                });
+                let label = swap_layout_label(
+                    self.current_tiled_layout_position,
+                    self.swap_tiled_layouts.len(),
+                    &name,
+                );
+                (Some(name), Some(label), self.is_tiled_damaged)
+            },
+            None => (None, None, self.is_tiled_damaged),
         }
     }
-    pub fn floating_layout_info(&self) -> (Option<String>, bool) {
-        // (swap_layout_name, is_swap_layout_dirty)
+    pub fn floating_layout_info(&self) -> (Option<String>, Option<String>, bool) {
+        // (swap_layout_name, swap_layout_label, is_swap_layout_dirty)
         match self
             .swap_floating_layouts
             .iter()
             .nth(self.current_floating_layout_position)
         {
-            Some(current_floating_layout) => (
-                current_floating_layout.1.clone().or_else(|| {
-                    Some(format!(
-                        "Layout #{}",
-                        self.current_floating_layout_position + 1
-                    ))
-                }),
-                self.is_floating_damaged,
-            ),
-            None => (None, self.is_floating_damaged),
+            Some(current_floating_layout) => {
+                let name = current_floating_layout.1.clone().unwrap_or_else(|| {
+                    format!("Layout #{}", self.current_floating_layout_position + 1)
+                });
+                let label = swap_layout_label(
+                    self.current_floating_layout_position,
+                    self.swap_floating_layouts.len(),
+                    &name,
+                );
+                (Some(name), Some(label), self.is_floating_damaged)
+            },
+            None => (None, None, self.is_floating_damaged),
         }
     }
     pub fn swap_floating_panes(
         &mut self,
         floating_panes: &FloatingPanes,
         search_backwards: bool,
+        tag: Option<&str>,
     ) -> Option<Vec<FloatingPaneLayout>> {
         if self.swap_floating_layouts.is_empty() {
             return None;
@@ -144,11 +225,22 @@ impl SwapLayouts {
                 .iter()
                 .nth(self.current_floating_layout_position)
             {
+                Some(swap_layout) if !is_tagged(&swap_layout.2, tag) => {
+                    progress_layout!();
+                },
                 Some(swap_layout) => {
-                    for (constraint, layout) in swap_layout.0.iter() {
-                        if self.state_fits_floating_panes_constraint(constraint, floating_panes) {
-                            return Some(layout.clone());
-                        };
+                    let pane_count = floating_panes.visible_panes_count();
+                    let counts = PaneCounts {
+                        panes: pane_count,
+                        groups: pane_count,
+                    };
+                    if let Some(layout) = select_swap_layout_entry(
+                        &swap_layout.0,
+                        counts,
+                        self.session_context,
+                        |_| true,
+                    ) {
+                        return Some(layout.clone());
                     }
                     progress_layout!();
                 },
@@ -162,40 +254,11 @@ impl SwapLayouts {
         }
         None
     }
-    fn state_fits_tiled_panes_constraint(
-        &self,
-        constraint: &LayoutConstraint,
-        tiled_panes: &TiledPanes,
-    ) -> bool {
-        match constraint {
-            LayoutConstraint::MaxPanes(max_panes) => {
-                tiled_panes.visible_panes_count() <= *max_panes
-            },
-            LayoutConstraint::MinPanes(min_panes) => {
-                tiled_panes.visible_panes_count() >= *min_panes
-            },
-            LayoutConstraint::NoConstraint => true,
-        }
-    }
-    fn state_fits_floating_panes_constraint(
-        &self,
-        constraint: &LayoutConstraint,
-        floating_panes: &FloatingPanes,
-    ) -> bool {
-        match constraint {
-            LayoutConstraint::MaxPanes(max_panes) => {
-                floating_panes.visible_panes_count() <= *max_panes
-            },
-            LayoutConstraint::MinPanes(min_panes) => {
-                floating_panes.visible_panes_count() >= *min_panes
-            },
-            LayoutConstraint::NoConstraint => true,
-        }
-    }
     pub fn swap_tiled_panes(
         &mut self,
         tiled_panes: &TiledPanes,
         search_backwards: bool,
+        tag: Option<&str>,
     ) -> Option<TiledPaneLayout> {
         if self.swap_tiled_layouts.is_empty() {
             return None;
@@ -233,20 +296,28 @@ impl SwapLayouts {
                 .iter()
                 .nth(self.current_tiled_layout_position)
             {
+                Some(swap_layout) if !is_tagged(&swap_layout.2, tag) => {
+                    progress_layout!();
+                },
                 Some(swap_layout) => {
-                    for (constraint, layout) in swap_layout.0.iter() {
-                        if self.state_fits_tiled_panes_constraint(constraint, tiled_panes) {
-                            let display_area = self.display_area.borrow();
-                            // TODO: reuse the assets from position_panes_in_space here?
-                            let pane_count = tiled_panes.visible_panes_count();
-                            let display_area = PaneGeom::from(&*display_area);
-                            if layout
-                                .position_panes_in_space(&display_area, Some(pane_count))
-                                .is_ok()
-                            {
-                                return Some(layout.clone());
-                            }
-                        };
+                    let counts = PaneCounts {
+                        panes: tiled_panes.visible_panes_count(),
+                        groups: tiled_panes.visible_groups_count(),
+                    };
+                    let display_area = PaneGeom::from(&*self.display_area.borrow());
+                    // TODO: reuse the assets from position_panes_in_space here?
+                    let fits_display_area = |layout: &TiledPaneLayout| {
+                        layout
+                            .position_panes_in_space(&display_area, Some(counts.panes))
+                            .is_ok()
+                    };
+                    if let Some(layout) = select_swap_layout_entry(
+                        &swap_layout.0,
+                        counts,
+                        self.session_context,
+                        fits_display_area,
+                    ) {
+                        return Some(layout.clone());
                     }
                     progress_layout!();
                 },
@@ -282,3 +353,17 @@ impl SwapLayouts {
         None
     }
 }
+
+fn is_tagged(tags: &[String], tag: Option<&str>) -> bool {
+    match tag {
+        Some(tag) => tags.iter().any(|t| t == tag),
+        None => true,
+    }
+}
+
+fn sorted_tags<'a>(tags: impl Iterator<Item = &'a Vec<String>>) -> Vec<String> {
+    let mut tags: Vec<String> = tags.flatten().cloned().collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
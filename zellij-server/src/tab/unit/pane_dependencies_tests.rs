@@ -0,0 +1,115 @@
+use super::*;
+use std::path::PathBuf;
+
+fn run_command(command: &str) -> RunCommand {
+    RunCommand {
+        command: PathBuf::from(command),
+        ..Default::default()
+    }
+}
+
+fn released_pane_ids(released: Vec<(PaneId, RunCommand)>) -> Vec<PaneId> {
+    released.into_iter().map(|(pane_id, _)| pane_id).collect()
+}
+
+#[test]
+fn pane_is_released_when_its_dependency_prints_the_ready_text() {
+    let mut pane_dependencies = PaneDependencies::default();
+    let db = PaneId::Terminal(1);
+    let api = PaneId::Terminal(2);
+    pane_dependencies.wait_for(
+        api,
+        db,
+        "db".into(),
+        Some(&ReadyCondition::Contains(
+            "ready to accept connections".into(),
+        )),
+        run_command("cargo"),
+    );
+    assert!(pane_dependencies
+        .output_received(db, b"starting up\r\n")
+        .is_empty());
+    assert!(pane_dependencies
+        .output_received(db, b"\x1b[32mready to accept")
+        .is_empty());
+    assert_eq!(
+        released_pane_ids(pane_dependencies.output_received(db, b" connections\x1b[m\r\n")),
+        vec![api],
+        "ready text is matched across reads and without escape sequences"
+    );
+    assert!(pane_dependencies
+        .output_received(db, b"ready to accept connections")
+        .is_empty());
+}
+
+#[test]
+fn pane_is_released_when_its_dependency_output_matches_the_ready_regex() {
+    let mut pane_dependencies = PaneDependencies::default();
+    let api = PaneId::Terminal(1);
+    let web = PaneId::Terminal(2);
+    pane_dependencies.wait_for(
+        web,
+        api,
+        "api".into(),
+        Some(&ReadyCondition::Matches(r"listening on :\d+".into())),
+        run_command("npm"),
+    );
+    assert!(pane_dependencies
+        .output_received(api, b"listening on :port")
+        .is_empty());
+    assert_eq!(
+        released_pane_ids(pane_dependencies.output_received(api, b"listening on :8080")),
+        vec![web]
+    );
+}
+
+#[test]
+fn pane_is_released_when_its_dependency_exits_successfully() {
+    let mut pane_dependencies = PaneDependencies::default();
+    let build = PaneId::Terminal(1);
+    let tests = PaneId::Terminal(2);
+    let lint = PaneId::Terminal(3);
+    pane_dependencies.wait_for(tests, build, "build".into(), None, run_command("test"));
+    pane_dependencies.wait_for(
+        lint,
+        build,
+        "build".into(),
+        Some(&ReadyCondition::Contains("compiled".into())),
+        run_command("lint"),
+    );
+    assert!(pane_dependencies.pane_exited(build, Some(1)).is_empty());
+    assert_eq!(
+        released_pane_ids(pane_dependencies.pane_exited(build, Some(0))),
+        vec![tests, lint]
+    );
+}
+
+#[test]
+fn timed_out_pane_no_longer_waits() {
+    let mut pane_dependencies = PaneDependencies::default();
+    let db = PaneId::Terminal(1);
+    let api = PaneId::Terminal(2);
+    pane_dependencies.wait_for(api, db, "db".into(), None, run_command("cargo"));
+    let (dependency_name, run_command) = pane_dependencies.timed_out(api).unwrap();
+    assert_eq!(dependency_name, "db");
+    assert_eq!(run_command.command, PathBuf::from("cargo"));
+    assert!(pane_dependencies.timed_out(api).is_none());
+    assert!(pane_dependencies.pane_exited(db, Some(0)).is_empty());
+}
+
+#[test]
+fn panes_waiting_for_a_closed_pane_are_returned() {
+    let mut pane_dependencies = PaneDependencies::default();
+    let db = PaneId::Terminal(1);
+    let api = PaneId::Terminal(2);
+    let web = PaneId::Terminal(3);
+    pane_dependencies.wait_for(api, db, "db".into(), None, run_command("cargo"));
+    pane_dependencies.wait_for(web, api, "api".into(), None, run_command("npm"));
+    let orphaned: Vec<PaneId> = pane_dependencies
+        .pane_closed(api)
+        .into_iter()
+        .map(|(pane_id, _dependency_name, _run_command)| pane_id)
+        .collect();
+    assert_eq!(orphaned, vec![web], "the closed pane itself stops waiting");
+    assert!(pane_dependencies.pane_exited(db, Some(0)).is_empty());
+}
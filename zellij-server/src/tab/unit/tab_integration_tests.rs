@@ -1,4 +1,4 @@
-use super::{Output, Tab};
+use super::{Output, Pane, Tab};
 use crate::panes::sixel::SixelImageStore;
 use crate::screen::CopyOptions;
 use crate::Arc;
@@ -25,6 +25,7 @@ use zellij_utils::input::plugins::PluginTag;
 use zellij_utils::ipc::IpcReceiverWithContext;
 use zellij_utils::pane_size::{Size, SizeInPixels};
 use zellij_utils::position::Position;
+use zellij_utils::test_support;
 
 use crate::pty_writer::PtyWriteInstruction;
 use zellij_utils::channels::{self, ChannelWithContext, SenderWithContext};
@@ -84,6 +85,9 @@ impl ServerOsApi for FakeInputOutput {
     fn force_kill(&self, _pid: Pid) -> Result<()> {
         unimplemented!()
     }
+    fn terminate(&self, _pid: Pid) -> Result<()> {
+        unimplemented!()
+    }
     fn box_clone(&self) -> Box<dyn ServerOsApi> {
         Box::new((*self).clone())
     }
@@ -411,7 +415,164 @@ fn create_new_tab_with_layout(size: Size, default_mode: ModeInfo, layout: &str)
     let copy_options = CopyOptions::default();
     let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
     let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
-    let layout = Layout::from_str(layout, "layout_file_name".into(), None, None).unwrap();
+    let layout = Layout::from_str(
+        layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    let (tab_layout, floating_panes_layout) = layout.new_tab();
+    let mut tab = Tab::new(
+        index,
+        position,
+        name,
+        size,
+        character_cell_info,
+        sixel_image_store,
+        os_api,
+        senders,
+        max_panes,
+        style,
+        mode_info,
+        draw_pane_frames,
+        auto_layout,
+        connected_clients,
+        session_is_mirrored,
+        client_id,
+        copy_options,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        (vec![], vec![]), // swap layouts
+    );
+    let pane_ids = tab_layout
+        .extract_run_instructions()
+        .iter()
+        .enumerate()
+        .map(|(i, _)| (i as u32, None))
+        .collect();
+    let floating_pane_ids = floating_panes_layout
+        .iter()
+        .enumerate()
+        .map(|(i, _)| (i as u32, None))
+        .collect();
+    tab.apply_layout(
+        tab_layout,
+        floating_panes_layout,
+        pane_ids,
+        floating_pane_ids,
+        HashMap::new(),
+        client_id,
+    )
+    .unwrap();
+    tab
+}
+
+fn create_new_tab_with_command_spawn_interval(
+    size: Size,
+    layout: &str,
+    new_terminal_ids: Vec<(u32, Option<RunCommand>)>,
+) -> Tab {
+    set_session_name("test".into());
+    let index = 0;
+    let position = 0;
+    let name = String::new();
+    let os_api = Box::new(FakeInputOutput::default());
+    let senders = ThreadSenders::default().silently_fail_on_send();
+    let max_panes = None;
+    let mode_info = ModeInfo::default();
+    let style = Style::default();
+    let draw_pane_frames = true;
+    let auto_layout = true;
+    let client_id = 1;
+    let session_is_mirrored = true;
+    let mut connected_clients = HashSet::new();
+    connected_clients.insert(client_id);
+    let connected_clients = Rc::new(RefCell::new(connected_clients));
+    let character_cell_info = Rc::new(RefCell::new(None));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let copy_options = CopyOptions::default();
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let layout = Layout::from_str(
+        layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    let (tab_layout, floating_panes_layout) = layout.new_tab();
+    let mut tab = Tab::new(
+        index,
+        position,
+        name,
+        size,
+        character_cell_info,
+        sixel_image_store,
+        os_api,
+        senders,
+        max_panes,
+        style,
+        mode_info,
+        draw_pane_frames,
+        auto_layout,
+        connected_clients,
+        session_is_mirrored,
+        client_id,
+        copy_options,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        (vec![], vec![]), // swap layouts
+    );
+    tab.command_spawn_interval = Some(std::time::Duration::from_millis(500));
+    tab.apply_layout(
+        tab_layout,
+        floating_panes_layout,
+        new_terminal_ids,
+        vec![],
+        HashMap::new(),
+        client_id,
+    )
+    .unwrap();
+    tab
+}
+
+fn create_new_tab_with_max_content_width(
+    size: Size,
+    layout: &str,
+    max_content_width: usize,
+) -> Tab {
+    set_session_name("test".into());
+    let index = 0;
+    let position = 0;
+    let name = String::new();
+    let os_api = Box::new(FakeInputOutput::default());
+    let senders = ThreadSenders::default().silently_fail_on_send();
+    let max_panes = None;
+    let mode_info = ModeInfo::default();
+    let style = Style::default();
+    let draw_pane_frames = true;
+    let auto_layout = true;
+    let client_id = 1;
+    let session_is_mirrored = true;
+    let mut connected_clients = HashSet::new();
+    connected_clients.insert(client_id);
+    let connected_clients = Rc::new(RefCell::new(connected_clients));
+    let character_cell_info = Rc::new(RefCell::new(None));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let copy_options = CopyOptions::default();
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let layout = Layout::from_str(
+        layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
     let (tab_layout, floating_panes_layout) = layout.new_tab();
     let mut tab = Tab::new(
         index,
@@ -435,6 +596,7 @@ fn create_new_tab_with_layout(size: Size, default_mode: ModeInfo, layout: &str)
         terminal_emulator_color_codes,
         (vec![], vec![]), // swap layouts
     );
+    tab.max_content_width = Some(max_content_width);
     let pane_ids = tab_layout
         .extract_run_instructions()
         .iter()
@@ -458,6 +620,68 @@ fn create_new_tab_with_layout(size: Size, default_mode: ModeInfo, layout: &str)
     tab
 }
 
+fn create_new_tab_with_tiled_layout(size: Size, tab_layout: TiledPaneLayout) -> Tab {
+    set_session_name("test".into());
+    let index = 0;
+    let position = 0;
+    let name = String::new();
+    let os_api = Box::new(FakeInputOutput::default());
+    let senders = ThreadSenders::default().silently_fail_on_send();
+    let max_panes = None;
+    let mode_info = ModeInfo::default();
+    let style = Style::default();
+    let draw_pane_frames = true;
+    let auto_layout = true;
+    let client_id = 1;
+    let session_is_mirrored = true;
+    let mut connected_clients = HashSet::new();
+    connected_clients.insert(client_id);
+    let connected_clients = Rc::new(RefCell::new(connected_clients));
+    let character_cell_info = Rc::new(RefCell::new(None));
+    let terminal_emulator_colors = Rc::new(RefCell::new(Palette::default()));
+    let copy_options = CopyOptions::default();
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let mut tab = Tab::new(
+        index,
+        position,
+        name,
+        size,
+        character_cell_info,
+        sixel_image_store,
+        os_api,
+        senders,
+        max_panes,
+        style,
+        mode_info,
+        draw_pane_frames,
+        auto_layout,
+        connected_clients,
+        session_is_mirrored,
+        client_id,
+        copy_options,
+        terminal_emulator_colors,
+        terminal_emulator_color_codes,
+        (vec![], vec![]), // swap layouts
+    );
+    let pane_ids = tab_layout
+        .extract_run_instructions()
+        .iter()
+        .enumerate()
+        .map(|(i, _)| (i as u32, None))
+        .collect();
+    tab.apply_layout(
+        tab_layout,
+        vec![],
+        pane_ids,
+        vec![],
+        HashMap::new(),
+        client_id,
+    )
+    .unwrap();
+    tab
+}
+
 fn create_new_tab_with_mock_pty_writer(
     size: Size,
     default_mode: ModeInfo,
@@ -3038,65 +3262,188 @@ fn can_swap_tiled_layout_at_runtime() {
     assert_snapshot!(snapshot);
 }
 
-#[test]
-fn can_swap_floating_layout_at_runtime() {
-    let size = Size {
-        cols: 121,
-        rows: 20,
-    };
-    let client_id = 1;
-    let mut output = Output::default();
+fn create_new_tab_with_tagged_swap_layouts(size: Size) -> Tab {
     let swap_layouts = r#"
         layout {
-            swap_floating_layout {
-                floating_panes max_panes=2 {
+            swap_tiled_layout name="wide" {
+                tags "review"
+                tab split_direction="vertical" {
                     pane
                     pane
                 }
             }
-            swap_floating_layout {
-                floating_panes max_panes=2 {
-                    pane {
-                        x "0%"
-                    }
-                    pane {
-                        x "100%"
-                    }
+            swap_tiled_layout name="tall" {
+                tab {
+                    pane
+                    pane
+                }
+            }
+            swap_tiled_layout name="narrow" {
+                tags "review" "small"
+                tab split_direction="vertical" {
+                    pane size="30%"
+                    pane
                 }
             }
         }
     "#;
     let layout = Layout::from_kdl(swap_layouts, "file_name.kdl".into(), None, None).unwrap();
-    let swap_floating_layouts = layout.swap_floating_layouts.clone();
     let swap_tiled_layouts = layout.swap_tiled_layouts.clone();
-    let mut tab = create_new_tab_with_swap_layouts(
+    let swap_floating_layouts = layout.swap_floating_layouts.clone();
+    create_new_tab_with_swap_layouts(
         size,
         ModeInfo::default(),
         (swap_tiled_layouts, swap_floating_layouts),
         None,
         true,
-    );
-    let new_pane_id_1 = PaneId::Terminal(2);
-    let new_pane_id_2 = PaneId::Terminal(3);
+    )
+}
 
-    tab.toggle_floating_panes(Some(client_id), None).unwrap();
-    tab.new_pane(new_pane_id_1, None, None, Some(client_id))
-        .unwrap();
-    tab.new_pane(new_pane_id_2, None, None, Some(client_id))
+#[test]
+fn cycling_swap_layouts_without_a_tag_goes_through_all_of_them() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab_with_tagged_swap_layouts(size);
+    tab.new_pane(PaneId::Terminal(2), None, None, Some(client_id))
         .unwrap();
-    tab.next_swap_layout(Some(client_id), false).unwrap();
-    tab.render(&mut output, None).unwrap();
-    let snapshot = take_snapshot(
-        output.serialize().unwrap().get(&client_id).unwrap(),
-        size.rows,
-        size.cols,
-        Palette::default(),
-    );
-    assert_snapshot!(snapshot);
+    assert_eq!(tab.swap_layout_info().0.as_deref(), Some("wide"));
+    tab.next_swap_layout(Some(client_id), true).unwrap();
+    assert_eq!(tab.swap_layout_info().0.as_deref(), Some("tall"));
+    tab.next_swap_layout(Some(client_id), true).unwrap();
+    assert_eq!(tab.swap_layout_info().0.as_deref(), Some("narrow"));
+    tab.previous_swap_layout(Some(client_id)).unwrap();
+    assert_eq!(tab.swap_layout_info().0.as_deref(), Some("tall"));
 }
 
 #[test]
-fn swapping_layouts_after_resize_snaps_to_current_layout() {
+fn swap_layout_labels_count_the_base_layout() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab_with_tagged_swap_layouts(size);
+    tab.new_pane(PaneId::Terminal(2), None, None, Some(client_id))
+        .unwrap();
+    assert_eq!(tab.swap_layout_info().1.as_deref(), Some("2/4: wide"));
+    tab.next_swap_layout(Some(client_id), true).unwrap();
+    assert_eq!(tab.swap_layout_info().1.as_deref(), Some("3/4: tall"));
+}
+
+#[test]
+fn cycling_swap_layouts_with_a_tag_skips_the_ones_without_it() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab_with_tagged_swap_layouts(size);
+    tab.new_pane(PaneId::Terminal(2), None, None, Some(client_id))
+        .unwrap();
+    assert_eq!(tab.swap_layout_info().0.as_deref(), Some("wide"));
+    tab.cycle_swap_layouts(Some(client_id), false, true, Some("review"))
+        .unwrap();
+    assert_eq!(tab.swap_layout_info().0.as_deref(), Some("narrow"));
+    tab.cycle_swap_layouts(Some(client_id), false, true, Some("review"))
+        .unwrap();
+    assert_eq!(
+        tab.swap_layout_info().0.as_deref(),
+        Some("wide"),
+        "wrapped around to the first tagged layout"
+    );
+    tab.cycle_swap_layouts(Some(client_id), true, true, Some("review"))
+        .unwrap();
+    assert_eq!(
+        tab.swap_layout_info().0.as_deref(),
+        Some("narrow"),
+        "wrapped around backwards to the last tagged layout"
+    );
+    tab.cycle_swap_layouts(Some(client_id), false, true, Some("small"))
+        .unwrap();
+    assert_eq!(
+        tab.swap_layout_info().0.as_deref(),
+        Some("narrow"),
+        "stayed on the only layout with the tag"
+    );
+}
+
+#[test]
+fn cycling_swap_layouts_with_a_tag_none_of_them_have_keeps_the_current_layout() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab_with_tagged_swap_layouts(size);
+    tab.new_pane(PaneId::Terminal(2), None, None, Some(client_id))
+        .unwrap();
+    tab.cycle_swap_layouts(Some(client_id), false, true, Some("missing"))
+        .unwrap();
+    assert_eq!(tab.swap_layout_info().0.as_deref(), Some("wide"));
+}
+
+#[test]
+fn can_swap_floating_layout_at_runtime() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut output = Output::default();
+    let swap_layouts = r#"
+        layout {
+            swap_floating_layout {
+                floating_panes max_panes=2 {
+                    pane
+                    pane
+                }
+            }
+            swap_floating_layout {
+                floating_panes max_panes=2 {
+                    pane {
+                        x "0%"
+                    }
+                    pane {
+                        x "100%"
+                    }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(swap_layouts, "file_name.kdl".into(), None, None).unwrap();
+    let swap_floating_layouts = layout.swap_floating_layouts.clone();
+    let swap_tiled_layouts = layout.swap_tiled_layouts.clone();
+    let mut tab = create_new_tab_with_swap_layouts(
+        size,
+        ModeInfo::default(),
+        (swap_tiled_layouts, swap_floating_layouts),
+        None,
+        true,
+    );
+    let new_pane_id_1 = PaneId::Terminal(2);
+    let new_pane_id_2 = PaneId::Terminal(3);
+
+    tab.toggle_floating_panes(Some(client_id), None).unwrap();
+    tab.new_pane(new_pane_id_1, None, None, Some(client_id))
+        .unwrap();
+    tab.new_pane(new_pane_id_2, None, None, Some(client_id))
+        .unwrap();
+    tab.next_swap_layout(Some(client_id), false).unwrap();
+    tab.render(&mut output, None).unwrap();
+    let snapshot = take_snapshot(
+        output.serialize().unwrap().get(&client_id).unwrap(),
+        size.rows,
+        size.cols,
+        Palette::default(),
+    );
+    assert_snapshot!(snapshot);
+}
+
+#[test]
+fn swapping_layouts_after_resize_snaps_to_current_layout() {
     let size = Size {
         cols: 121,
         rows: 20,
@@ -3199,6 +3546,52 @@ fn swap_tiled_layout_with_stacked_children() {
     assert_snapshot!(snapshot);
 }
 
+#[test]
+fn stacked_panes_count_as_one_group() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let swap_layouts = r#"
+        layout {
+            swap_tiled_layout {
+                tab {
+                    pane split_direction="vertical" {
+                        pane focus=true
+                        pane { children stacked=true; }
+                    }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(swap_layouts, "file_name.kdl".into(), None, None).unwrap();
+    let swap_tiled_layouts = layout.swap_tiled_layouts.clone();
+    let swap_floating_layouts = layout.swap_floating_layouts.clone();
+    let mut tab = create_new_tab_with_swap_layouts(
+        size,
+        ModeInfo::default(),
+        (swap_tiled_layouts, swap_floating_layouts),
+        None,
+        true,
+    );
+    assert_eq!(tab.tiled_panes.visible_groups_count(), 1);
+    let mut groups_counts = vec![];
+    for pane_id in 2..=5 {
+        tab.new_pane(PaneId::Terminal(pane_id), None, None, Some(client_id))
+            .unwrap();
+        groups_counts.push((
+            tab.tiled_panes.visible_panes_count(),
+            tab.tiled_panes.visible_groups_count(),
+        ));
+    }
+    assert_eq!(
+        groups_counts,
+        vec![(2, 2), (3, 2), (4, 2), (5, 2)],
+        "the panes beyond the first one are added to the stack"
+    );
+}
+
 #[test]
 fn swap_tiled_layout_with_stacked_children_and_no_pane_frames() {
     let size = Size {
@@ -5800,6 +6193,114 @@ fn when_swapping_tiled_layouts_in_an_undamaged_state_with_no_focus_node_pane_foc
     assert_snapshot!(snapshot);
 }
 
+#[test]
+fn when_cycling_tiled_layouts_the_focus_moves_to_the_pane_each_swap_layout_focuses() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let base_layout = r#"
+        layout {
+            pane command="htop"
+            pane command="tail"
+            pane command="vim"
+        }
+    "#;
+    // the focused slots are given to the panes running their command, so the focus has to move to
+    // these panes rather than the focused pane moving to the focused slot
+    let swap_layouts = r#"
+        layout {
+            swap_tiled_layout name="logs" {
+                tab {
+                    pane split_direction="vertical" {
+                        pane command="tail" focus=true
+                        pane command="htop"
+                        pane command="vim"
+                    }
+                }
+            }
+            swap_tiled_layout name="presentation" {
+                tab {
+                    pane split_direction="vertical" {
+                        pane command="htop" size="20%"
+                        pane command="vim" size="60%" focus=true
+                        pane command="tail" size="20%"
+                    }
+                }
+            }
+            swap_tiled_layout name="monitoring" {
+                tab {
+                    pane split_direction="vertical" {
+                        pane command="vim"
+                        pane focus=true {
+                            pane command="htop"
+                            pane command="tail"
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+    let (base_layout, base_floating_layout) =
+        Layout::from_kdl(base_layout, "file_name.kdl".into(), None, None)
+            .unwrap()
+            .template
+            .unwrap();
+
+    let mut htop = RunCommand::default();
+    htop.command = PathBuf::from("htop");
+    let mut tail = RunCommand::default();
+    tail.command = PathBuf::from("tail");
+    let mut vim = RunCommand::default();
+    vim.command = PathBuf::from("vim");
+    let new_terminal_ids = vec![(1, Some(htop)), (2, Some(tail)), (3, Some(vim))];
+    let new_floating_terminal_ids = vec![];
+    let new_plugin_ids = HashMap::new();
+
+    let swap_layout = Layout::from_kdl(swap_layouts, "file_name.kdl".into(), None, None).unwrap();
+    let swap_tiled_layouts = swap_layout.swap_tiled_layouts.clone();
+    let swap_floating_layouts = swap_layout.swap_floating_layouts.clone();
+    let mut tab = create_new_tab_with_swap_layouts(
+        size,
+        ModeInfo::default(),
+        (swap_tiled_layouts, swap_floating_layouts),
+        Some((
+            base_layout,
+            base_floating_layout,
+            new_terminal_ids,
+            new_floating_terminal_ids,
+            new_plugin_ids,
+        )),
+        true,
+    );
+
+    tab.next_swap_layout(Some(client_id), true).unwrap();
+    assert_eq!(
+        tab.get_active_pane_id(client_id),
+        Some(PaneId::Terminal(2)),
+        "focus moved to the pane running tail"
+    );
+    tab.next_swap_layout(Some(client_id), true).unwrap();
+    assert_eq!(
+        tab.get_active_pane_id(client_id),
+        Some(PaneId::Terminal(3)),
+        "focus moved to the pane running vim"
+    );
+    tab.next_swap_layout(Some(client_id), true).unwrap();
+    assert_eq!(
+        tab.get_active_pane_id(client_id),
+        Some(PaneId::Terminal(1)),
+        "focus moved to the first pane of the focused container"
+    );
+    tab.next_swap_layout(Some(client_id), true).unwrap();
+    assert_eq!(
+        tab.get_active_pane_id(client_id),
+        Some(PaneId::Terminal(2)),
+        "the same swap layout focuses the same pane every time"
+    );
+}
+
 #[test]
 fn when_closing_a_pane_in_auto_layout_the_focus_goes_to_last_focused_pane() {
     let size = Size {
@@ -6849,3 +7350,475 @@ fn when_resizing_whole_tab_with_auto_layout_and_floating_panes_the_layout_is_mai
     );
     assert_snapshot!(snapshot);
 }
+
+#[test]
+fn layout_command_panes_are_spawned_one_at_a_time() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let layout = r#"
+        layout {
+            pane command="db"
+            pane
+            pane command="api"
+            pane command="web" start_suspended=true
+            pane command="tail"
+        }
+    "#;
+    let held_command = |command: &str, hold_on_start: bool| {
+        Some(RunCommand {
+            command: PathBuf::from(command),
+            hold_on_start,
+            ..Default::default()
+        })
+    };
+    // the pty spawned "db" right away and held the other command panes
+    let new_terminal_ids = vec![
+        (1, None),
+        (2, None),
+        (3, held_command("api", false)),
+        (4, held_command("web", true)),
+        (5, held_command("tail", false)),
+    ];
+    let mut tab = create_new_tab_with_command_spawn_interval(size, layout, new_terminal_ids);
+    let is_queued = |tab: &Tab, terminal_id: u32| {
+        tab.tiled_panes
+            .get_pane(PaneId::Terminal(terminal_id))
+            .unwrap()
+            .is_queued()
+    };
+    assert!(is_queued(&tab, 3));
+    assert!(
+        !is_queued(&tab, 4),
+        "panes started suspended wait for the user rather than their turn"
+    );
+    assert!(is_queued(&tab, 5));
+
+    let mut output = Output::default();
+    tab.render(&mut output, None).unwrap();
+    let snapshot = take_snapshot(
+        output.serialize().unwrap().get(&1).unwrap(),
+        size.rows,
+        size.cols,
+        Palette::default(),
+    );
+    assert_eq!(
+        snapshot.matches("QUEUED").count(),
+        2,
+        "queued panes show it in their frame"
+    );
+
+    tab.spawn_next_queued_command().unwrap();
+    assert!(!is_queued(&tab, 3));
+    assert!(is_queued(&tab, 5));
+    tab.flush_command_queue().unwrap();
+    assert!(!is_queued(&tab, 5));
+    assert!(
+        tab.tiled_panes
+            .get_pane(PaneId::Terminal(4))
+            .unwrap()
+            .is_held(),
+        "flushing the queue does not start suspended panes"
+    );
+}
+
+#[test]
+fn closed_queued_panes_are_removed_from_the_command_queue() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let layout = r#"
+        layout {
+            pane command="db"
+            pane command="api"
+            pane command="web"
+        }
+    "#;
+    let held_command = |command: &str| {
+        Some(RunCommand {
+            command: PathBuf::from(command),
+            ..Default::default()
+        })
+    };
+    let new_terminal_ids = vec![
+        (1, None),
+        (2, held_command("api")),
+        (3, held_command("web")),
+    ];
+    let mut tab = create_new_tab_with_command_spawn_interval(size, layout, new_terminal_ids);
+    tab.close_pane(PaneId::Terminal(2), false, None);
+    assert_eq!(tab.queued_commands.len(), 1);
+    tab.spawn_next_queued_command().unwrap();
+    assert!(
+        !tab.tiled_panes
+            .get_pane(PaneId::Terminal(3))
+            .unwrap()
+            .is_queued(),
+        "the pane after the closed one is spawned in its place"
+    );
+    assert!(tab.queued_commands.is_empty());
+}
+
+#[test]
+fn tab_with_max_content_width_is_centered() {
+    let size = Size {
+        cols: 200,
+        rows: 20,
+    };
+    let layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                pane
+            }
+        }
+    "#;
+    let tab = create_new_tab_with_max_content_width(size, layout, 100);
+    let geom = |terminal_id| {
+        tab.tiled_panes
+            .get_pane(PaneId::Terminal(terminal_id))
+            .unwrap()
+            .position_and_size()
+    };
+    assert_eq!(
+        geom(0).x,
+        50,
+        "the content starts half of the free columns in"
+    );
+    assert_eq!(geom(0).cols.as_usize(), 50);
+    assert_eq!(geom(1).x, 100);
+    assert_eq!(geom(1).cols.as_usize(), 50);
+}
+
+#[test]
+fn resizing_tab_with_max_content_width_recenters_it() {
+    let size = Size {
+        cols: 200,
+        rows: 20,
+    };
+    let layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                pane
+            }
+        }
+    "#;
+    let mut tab = create_new_tab_with_max_content_width(size, layout, 100);
+    let geom = |tab: &Tab, terminal_id| {
+        tab.tiled_panes
+            .get_pane(PaneId::Terminal(terminal_id))
+            .unwrap()
+            .position_and_size()
+    };
+    tab.resize_whole_tab(Size {
+        cols: 160,
+        rows: 20,
+    })
+    .unwrap();
+    assert_eq!(geom(&tab, 0).x, 30);
+    assert_eq!(geom(&tab, 1).x + geom(&tab, 1).cols.as_usize(), 130);
+    tab.resize_whole_tab(Size { cols: 80, rows: 20 }).unwrap();
+    assert_eq!(geom(&tab, 0).x, 0, "narrower screens are used in full");
+    assert_eq!(geom(&tab, 1).x + geom(&tab, 1).cols.as_usize(), 80);
+    tab.resize_whole_tab(size).unwrap();
+    assert_eq!(geom(&tab, 0).x, 50);
+    assert_eq!(geom(&tab, 1).x + geom(&tab, 1).cols.as_usize(), 150);
+}
+
+#[test]
+fn max_content_width_narrower_than_the_layout_uses_the_full_width() {
+    let size = Size {
+        cols: 200,
+        rows: 20,
+    };
+    let layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size=120
+                pane
+            }
+        }
+    "#;
+    let tab = create_new_tab_with_max_content_width(size, layout, 100);
+    assert_eq!(tab.max_content_width, None);
+    let first_pane = tab
+        .tiled_panes
+        .get_pane(PaneId::Terminal(0))
+        .unwrap()
+        .position_and_size();
+    assert_eq!(first_pane.x, 0);
+}
+
+#[test]
+fn stack_taller_than_the_tab_is_laid_out_as_a_deep_stack() {
+    let size = Size { cols: 100, rows: 2 };
+    let tab_layout = test_support::stacked().new_tab().0;
+    let tab = create_new_tab_with_tiled_layout(size, tab_layout);
+    assert_eq!(
+        tab.get_tiled_pane_ids().len(),
+        2,
+        "only the expanded pane and the one-liner above it are laid out"
+    );
+    assert_eq!(tab.panes_hidden_in_deep_stacks_count(), 1);
+    for terminal_id in 0..3 {
+        assert!(
+            tab.has_terminal_pid(terminal_id),
+            "terminal {} is still part of the tab",
+            terminal_id
+        );
+    }
+}
+
+#[test]
+fn stack_navigation_reaches_the_hidden_panes_of_a_deep_stack() {
+    let size = Size { cols: 100, rows: 2 };
+    let tab_layout = test_support::stacked().new_tab().0;
+    let mut tab = create_new_tab_with_tiled_layout(size, tab_layout);
+    let client_id = 1;
+    for _ in 0..3 {
+        tab.move_focus_up(client_id).unwrap();
+    }
+    assert_eq!(tab.get_active_pane_id(client_id), Some(PaneId::Terminal(0)));
+    for _ in 0..3 {
+        tab.move_focus_down(client_id).unwrap();
+    }
+    assert_eq!(tab.get_active_pane_id(client_id), Some(PaneId::Terminal(2)));
+    let expanded_pane = tab
+        .tiled_panes
+        .get_pane(PaneId::Terminal(2))
+        .unwrap()
+        .position_and_size();
+    assert_eq!((expanded_pane.y, expanded_pane.rows.as_usize()), (1, 1));
+    assert_eq!(tab.get_tiled_pane_ids().len(), 2);
+    assert_eq!(tab.panes_hidden_in_deep_stacks_count(), 1);
+}
+
+#[test]
+fn closing_a_laid_out_pane_of_a_deep_stack_lays_out_a_hidden_one() {
+    let size = Size { cols: 100, rows: 2 };
+    let tab_layout = test_support::stacked().new_tab().0;
+    let mut tab = create_new_tab_with_tiled_layout(size, tab_layout);
+    let closed_pane = tab.close_pane(PaneId::Terminal(2), false, None);
+    assert!(closed_pane.is_some());
+    assert_eq!(tab.panes_hidden_in_deep_stacks_count(), 0);
+    let mut laid_out_panes = tab.get_tiled_pane_ids();
+    laid_out_panes.sort();
+    assert_eq!(
+        laid_out_panes,
+        vec![PaneId::Terminal(0), PaneId::Terminal(1)]
+    );
+}
+
+#[test]
+fn floating_command_pane_fits_its_height_to_its_output_once_it_exits() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let kdl_layout = r#"
+        layout {
+            pane
+            floating_panes {
+                pane command="cal" height="fit" max_height=15 width=30 x=10 y=2
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "file_name.kdl".into(), None, None).unwrap();
+    let (tab_layout, floating_layout) = layout.template.unwrap();
+    let mut tab = create_new_tab_with_swap_layouts(
+        size,
+        ModeInfo::default(),
+        (vec![], vec![]),
+        Some((
+            tab_layout,
+            floating_layout,
+            vec![(1, None)],
+            vec![(2, None)],
+            HashMap::new(),
+        )),
+        true,
+    );
+    let pane_geom = |tab: &Tab| {
+        tab.floating_panes
+            .get(&PaneId::Terminal(2))
+            .unwrap()
+            .position_and_size()
+    };
+    assert_eq!(pane_geom(&tab).rows.as_usize(), 10, "the initial height");
+    tab.handle_pty_bytes(
+        2,
+        Vec::from("   October\n\rSu Mo Tu\n\r 1  2  3".as_bytes()),
+    )
+    .unwrap();
+    tab.hold_pane(
+        PaneId::Terminal(2),
+        Some(0),
+        false,
+        RunCommand {
+            command: PathBuf::from("cal"),
+            ..Default::default()
+        },
+    );
+    let fitted_geom = pane_geom(&tab);
+    assert_eq!(
+        fitted_geom.rows.as_usize(),
+        5,
+        "3 lines of output and the frame"
+    );
+    assert_eq!(fitted_geom.cols.as_usize(), 30, "the width is not fitted");
+    assert_eq!((fitted_geom.x, fitted_geom.y), (10, 2));
+}
+
+#[test]
+fn a_tab_resized_without_relayout_ends_up_as_if_it_was_relayouted_on_every_resize() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let swap_layouts = r#"
+        layout {
+            swap_tiled_layout {
+                tab {
+                    pane split_direction="vertical" {
+                        pane focus=true
+                        pane { children stacked=true; }
+                    }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(swap_layouts, "file_name.kdl".into(), None, None).unwrap();
+    let create_tab = || {
+        let mut tab = create_new_tab_with_swap_layouts(
+            size,
+            ModeInfo::default(),
+            (
+                layout.swap_tiled_layouts.clone(),
+                layout.swap_floating_layouts.clone(),
+            ),
+            None,
+            true,
+        );
+        for terminal_id in 2..5 {
+            tab.new_pane(PaneId::Terminal(terminal_id), None, None, Some(client_id))
+                .unwrap();
+        }
+        tab
+    };
+    let pane_geoms = |tab: &Tab| {
+        (1..5)
+            .map(|terminal_id| {
+                tab.tiled_panes
+                    .get_pane(PaneId::Terminal(terminal_id))
+                    .unwrap()
+                    .position_and_size()
+            })
+            .collect::<Vec<_>>()
+    };
+    let resizes = [
+        Size { cols: 90, rows: 15 },
+        Size { cols: 60, rows: 12 },
+        Size {
+            cols: 100,
+            rows: 18,
+        },
+    ];
+
+    let mut relayouted_tab = create_tab();
+    let mut deferred_tab = create_tab();
+    for resize in resizes {
+        relayouted_tab.resize_whole_tab(resize).unwrap();
+        deferred_tab
+            .resize_whole_tab_without_relayout(resize)
+            .unwrap();
+    }
+    assert!(deferred_tab.pending_resize_relayout);
+    deferred_tab.relayout_after_resize();
+    assert!(!deferred_tab.pending_resize_relayout);
+    assert_eq!(pane_geoms(&deferred_tab), pane_geoms(&relayouted_tab));
+}
+
+#[test]
+fn rotating_a_tab_turns_its_panes_a_quarter_turn() {
+    let size = Size {
+        cols: 100,
+        rows: 20,
+    };
+    let layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                pane
+            }
+            pane split_direction="vertical" {
+                pane
+                pane
+            }
+        }
+    "#;
+    let client_id = 1;
+    let mut tab = create_new_tab_with_layout(size, ModeInfo::default(), layout);
+    let position = |tab: &Tab, terminal_id| {
+        let geom = tab
+            .tiled_panes
+            .get_pane(PaneId::Terminal(terminal_id))
+            .unwrap()
+            .position_and_size();
+        (geom.x, geom.y, geom.cols.as_usize(), geom.rows.as_usize())
+    };
+    let positions_before_rotating: Vec<_> = (0..4).map(|i| position(&tab, i)).collect();
+    tab.rotate_tiled_panes(true, Some(client_id)).unwrap();
+    // 0 1 / 2 3 turns into 2 0 / 3 1, each pane keeping its process
+    assert_eq!(tab.get_tiled_pane_ids().len(), 4);
+    assert_eq!(position(&tab, 2), (0, 0, 50, 10));
+    assert_eq!(position(&tab, 0), (50, 0, 50, 10));
+    assert_eq!(position(&tab, 3), (0, 10, 50, 10));
+    assert_eq!(position(&tab, 1), (50, 10, 50, 10));
+    tab.rotate_tiled_panes(false, Some(client_id)).unwrap();
+    let positions_after_rotating_back: Vec<_> = (0..4).map(|i| position(&tab, i)).collect();
+    assert_eq!(positions_after_rotating_back, positions_before_rotating);
+}
+
+#[test]
+fn rotating_a_tab_moves_its_stacks_without_rotating_them() {
+    let size = Size {
+        cols: 100,
+        rows: 20,
+    };
+    let layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                pane stacked=true {
+                    pane
+                    pane
+                    pane
+                }
+            }
+        }
+    "#;
+    let client_id = 1;
+    let mut tab = create_new_tab_with_layout(size, ModeInfo::default(), layout);
+    let geom = |tab: &Tab, terminal_id| {
+        tab.tiled_panes
+            .get_pane(PaneId::Terminal(terminal_id))
+            .unwrap()
+            .position_and_size()
+    };
+    tab.rotate_tiled_panes(true, Some(client_id)).unwrap();
+    assert_eq!((geom(&tab, 0).y, geom(&tab, 0).cols.as_usize()), (0, 100));
+    let stacked_panes: Vec<_> = (1..4).map(|i| geom(&tab, i)).collect();
+    assert!(stacked_panes.iter().all(|geom| geom.is_stacked));
+    assert_eq!(
+        stacked_panes
+            .iter()
+            .map(|geom| (geom.y, geom.rows.as_usize()))
+            .collect::<Vec<_>>(),
+        vec![(10, 1), (11, 1), (12, 8)],
+        "the stack keeps its panes one above the other"
+    );
+}
@@ -4,13 +4,14 @@ use crate::screen::CopyOptions;
 use crate::{
     os_input_output::{AsyncReader, Pid, ServerOsApi},
     panes::PaneId,
+    pty::PtyInstruction,
     thread_bus::ThreadSenders,
     ClientId,
 };
 use std::path::PathBuf;
 use zellij_utils::data::{Direction, Resize, ResizeStrategy};
 use zellij_utils::errors::prelude::*;
-use zellij_utils::input::layout::{SplitDirection, SplitSize, TiledPaneLayout};
+use zellij_utils::input::layout::{Run, SplitDirection, SplitSize, TiledPaneLayout};
 use zellij_utils::ipc::IpcReceiverWithContext;
 use zellij_utils::pane_size::{Size, SizeInPixels};
 
@@ -20,6 +21,7 @@ use std::os::unix::io::RawFd;
 use std::rc::Rc;
 
 use zellij_utils::{
+    channels::{self, ChannelWithContext, SenderWithContext},
     data::{ModeInfo, Palette, Style},
     input::command::{RunCommand, TerminalAction},
     interprocess::local_socket::LocalSocketStream,
@@ -60,6 +62,9 @@ impl ServerOsApi for FakeInputOutput {
     fn force_kill(&self, _pid: Pid) -> Result<()> {
         unimplemented!()
     }
+    fn terminate(&self, _pid: Pid) -> Result<()> {
+        unimplemented!()
+    }
     fn box_clone(&self) -> Box<dyn ServerOsApi> {
         Box::new((*self).clone())
     }
@@ -843,6 +848,36 @@ pub fn toggle_focused_pane_fullscreen() {
     // function and we already test that in the e2e tests
 }
 
+#[test]
+pub fn toggle_focused_pane_read_only() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    let mut read_only_child = TiledPaneLayout::default();
+    read_only_child.read_only = true;
+    initial_layout.children = vec![read_only_child, TiledPaneLayout::default()];
+    let mut tab = create_new_tab_with_layout(size, initial_layout);
+    let pane_is_read_only = |tab: &Tab, id: u32| {
+        tab.tiled_panes
+            .panes
+            .get(&PaneId::Terminal(id))
+            .unwrap()
+            .read_only()
+    };
+    assert!(
+        pane_is_read_only(&tab, 0),
+        "pane is read-only from the layout"
+    );
+    assert!(!pane_is_read_only(&tab, 1), "other pane is not read-only");
+    tab.toggle_active_pane_read_only(1);
+    assert!(!pane_is_read_only(&tab, 0), "read-only was lifted");
+    tab.toggle_active_pane_read_only(1);
+    assert!(pane_is_read_only(&tab, 0), "read-only was restored");
+}
+
 #[test]
 fn switch_to_next_pane_fullscreen() {
     let size = Size {
@@ -6081,6 +6116,100 @@ pub fn cannot_resize_up_when_pane_below_has_fixed_rows() {
     );
 }
 
+#[test]
+pub fn resize_down_takes_space_from_pane_past_fixed_rows() {
+    // ┌───────────┐                  ┌───────────┐
+    // │███████████│                  │███████████│
+    // ├───────────┤                  │███████████│
+    // │   fixed   │ ==resize=down==> ├───────────┤
+    // ├───────────┤                  │   fixed   │
+    // │           │                  ├───────────┤
+    // └───────────┘                  └───────────┘
+    // █ == focused pane
+
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Horizontal;
+    let mut fixed_child = TiledPaneLayout::default();
+    fixed_child.split_size = Some(SplitSize::Fixed(4));
+    initial_layout.children = vec![
+        TiledPaneLayout::default(),
+        fixed_child,
+        TiledPaneLayout::default(),
+    ];
+    let mut tab = create_new_tab_with_layout(size, initial_layout);
+    tab_resize_down(&mut tab, 1);
+
+    let rows_of = |tab: &Tab, id: u32| {
+        tab.tiled_panes
+            .panes
+            .get(&PaneId::Terminal(id))
+            .unwrap()
+            .position_and_size()
+            .rows
+            .as_usize()
+    };
+    assert!(rows_of(&tab, 0) > 8, "pane 1 grew");
+    assert_eq!(rows_of(&tab, 1), 4, "fixed pane height stayed the same");
+    assert!(rows_of(&tab, 2) < 8, "pane 3 gave up the space");
+    assert_eq!(
+        rows_of(&tab, 0) + rows_of(&tab, 1) + rows_of(&tab, 2),
+        20,
+        "panes still fill the screen"
+    );
+}
+
+#[test]
+pub fn resize_including_fixed_panes_changes_fixed_rows() {
+    // ┌───────────┐                  ┌───────────┐
+    // │███████████│                  │███████████│
+    // ├───────────┤ ==resize=down==> │███████████│
+    // │   fixed   │                  ├───────────┤
+    // └───────────┘                  └───────────┘
+    // █ == focused pane
+
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Horizontal;
+    let mut fixed_child = TiledPaneLayout::default();
+    fixed_child.split_size = Some(SplitSize::Fixed(10));
+    initial_layout.children = vec![TiledPaneLayout::default(), fixed_child];
+    let mut tab = create_new_tab_with_layout(size, initial_layout);
+    tab.resize(
+        1,
+        ResizeStrategy::new(Resize::Increase, Some(Direction::Down)).including_fixed_panes(true),
+    )
+    .unwrap();
+
+    let fixed_pane_geom = tab
+        .tiled_panes
+        .panes
+        .get(&PaneId::Terminal(1))
+        .unwrap()
+        .position_and_size();
+    assert_eq!(
+        tab.tiled_panes
+            .panes
+            .get(&PaneId::Terminal(0))
+            .unwrap()
+            .position_and_size()
+            .rows
+            .as_usize(),
+        11,
+        "pane 1 grew"
+    );
+    assert_eq!(fixed_pane_geom.rows.as_usize(), 9, "fixed pane shrank");
+    assert!(fixed_pane_geom.rows.is_fixed(), "pane 2 is still fixed");
+}
+
 #[test]
 pub fn resize_left_with_pane_to_the_left() {
     // ┌─────┬─────┐                    ┌───┬───────┐
@@ -14393,3 +14522,244 @@ fn correctly_resize_frameless_panes_on_pane_close() {
     let content_size = (pane.get_content_columns(), pane.get_content_rows());
     assert_eq!(content_size, (cols, rows));
 }
+
+#[test]
+fn find_pane_by_name_or_logical_id() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut layout = TiledPaneLayout::default();
+    layout.children_split_direction = SplitDirection::Vertical;
+    layout.children = vec![
+        TiledPaneLayout {
+            name: Some("runner".into()),
+            ..Default::default()
+        },
+        TiledPaneLayout::default(),
+    ];
+    let tab = create_new_tab_with_layout(size, layout);
+    assert_eq!(
+        tab.find_pane_id_by_name_or_id("runner"),
+        Some(PaneId::Terminal(0))
+    );
+    assert_eq!(
+        tab.find_pane_id_by_name_or_id("terminal_1"),
+        Some(PaneId::Terminal(1))
+    );
+    assert_eq!(
+        tab.find_pane_id_by_name_or_id("1"),
+        Some(PaneId::Terminal(1))
+    );
+    assert_eq!(tab.find_pane_id_by_name_or_id("plugin_1"), None);
+    assert_eq!(tab.find_pane_id_by_name_or_id("no-such-pane"), None);
+    let mut pane_names = tab.pane_names();
+    pane_names.sort();
+    assert_eq!(pane_names, vec!["runner", "terminal_1"]);
+}
+
+#[test]
+fn only_running_command_panes_are_busy() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut layout = TiledPaneLayout::default();
+    layout.children_split_direction = SplitDirection::Vertical;
+    layout.children = vec![
+        TiledPaneLayout::default(),
+        TiledPaneLayout {
+            run: Some(Run::Command(RunCommand {
+                command: PathBuf::from("htop"),
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+    ];
+    let mut tab = create_new_tab_with_layout(size, layout);
+    assert!(!tab.pane_is_busy(PaneId::Terminal(0)), "shells are idle");
+    assert!(tab.pane_is_busy(PaneId::Terminal(1)), "command is running");
+    tab.hold_pane(
+        PaneId::Terminal(1),
+        Some(0),
+        false,
+        RunCommand {
+            command: PathBuf::from("htop"),
+            ..Default::default()
+        },
+    );
+    assert!(!tab.pane_is_busy(PaneId::Terminal(1)), "command has exited");
+}
+
+#[test]
+fn forcing_a_command_into_a_busy_pane_respawns_it() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut layout = TiledPaneLayout::default();
+    layout.children_split_direction = SplitDirection::Vertical;
+    layout.children = vec![
+        TiledPaneLayout::default(),
+        TiledPaneLayout {
+            run: Some(Run::Command(RunCommand {
+                command: PathBuf::from("htop"),
+                hold_on_close: true,
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+    ];
+    let mut tab = create_new_tab_with_layout(size, layout);
+    let (pty_sender, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    tab.senders
+        .replace_to_pty(SenderWithContext::new(pty_sender));
+    let cargo_test = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".into()],
+        hold_on_close: true,
+        ..Default::default()
+    };
+    tab.run_command_in_pane(PaneId::Terminal(1), cargo_test.clone())
+        .unwrap();
+    match pty_receiver.try_recv() {
+        Ok((PtyInstruction::KillCommandInPane(pane_id), _)) => {
+            assert_eq!(pane_id, PaneId::Terminal(1))
+        },
+        _ => panic!("the running command should have been stopped"),
+    }
+    assert!(
+        pty_receiver.try_recv().is_err(),
+        "nothing is run before the previous command exits"
+    );
+    // the stopped command exits
+    tab.hold_pane(
+        PaneId::Terminal(1),
+        Some(1),
+        false,
+        RunCommand {
+            command: PathBuf::from("htop"),
+            hold_on_close: true,
+            ..Default::default()
+        },
+    );
+    match pty_receiver.try_recv() {
+        Ok((PtyInstruction::ReRunCommandInPane(pane_id, run_command), _)) => {
+            assert_eq!(pane_id, PaneId::Terminal(1));
+            assert_eq!(run_command, cargo_test);
+        },
+        _ => panic!("the new command should have been run in the pane"),
+    }
+    assert!(tab.pane_is_busy(PaneId::Terminal(1)));
+}
+
+#[test]
+fn rerunning_a_command_pane_runs_its_command_again_once_it_exits() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut layout = TiledPaneLayout::default();
+    layout.children_split_direction = SplitDirection::Vertical;
+    layout.children = vec![
+        TiledPaneLayout::default(),
+        TiledPaneLayout {
+            run: Some(Run::Command(RunCommand {
+                command: PathBuf::from("npm"),
+                args: vec!["run".into(), "dev".into()],
+                hold_on_close: true,
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+    ];
+    let mut tab = create_new_tab_with_layout(size, layout);
+    let (pty_sender, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    tab.senders
+        .replace_to_pty(SenderWithContext::new(pty_sender));
+    assert_eq!(tab.rerun_pane(PaneId::Terminal(1), false).unwrap(), None);
+    match pty_receiver.try_recv() {
+        Ok((PtyInstruction::TerminateCommandInPane(pane_id), _)) => {
+            assert_eq!(pane_id, PaneId::Terminal(1))
+        },
+        _ => panic!("the running command should have been stopped"),
+    }
+    // the command exits, reporting the command line it ran with
+    let resolved_command = RunCommand {
+        command: PathBuf::from("npm"),
+        args: vec!["run".into(), "dev".into(), "--port=3000".into()],
+        hold_on_close: true,
+        ..Default::default()
+    };
+    tab.hold_pane(PaneId::Terminal(1), None, false, resolved_command.clone());
+    match pty_receiver.try_recv() {
+        Ok((PtyInstruction::ReRunCommandInPane(pane_id, run_command), _)) => {
+            assert_eq!(pane_id, PaneId::Terminal(1));
+            assert_eq!(run_command, resolved_command);
+        },
+        _ => panic!("the command should have been run again in the pane"),
+    }
+    assert!(tab.pane_is_busy(PaneId::Terminal(1)));
+}
+
+#[test]
+fn shells_are_only_rerun_when_forced() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut layout = TiledPaneLayout::default();
+    layout.children_split_direction = SplitDirection::Vertical;
+    layout.children = vec![
+        TiledPaneLayout::default(),
+        TiledPaneLayout {
+            run: Some(Run::EditFile(vec![PathBuf::from("notes.md")], None, None)),
+            ..Default::default()
+        },
+    ];
+    let mut tab = create_new_tab_with_layout(size, layout);
+    let (pty_sender, pty_receiver): ChannelWithContext<PtyInstruction> = channels::unbounded();
+    tab.senders
+        .replace_to_pty(SenderWithContext::new(pty_sender));
+    assert!(tab
+        .rerun_pane(PaneId::Terminal(0), false)
+        .unwrap()
+        .is_some());
+    assert!(
+        tab.rerun_pane(PaneId::Terminal(1), true).unwrap().is_some(),
+        "editors are never re-run"
+    );
+    assert!(tab.rerun_pane(PaneId::Plugin(1), true).unwrap().is_some());
+    assert!(pty_receiver.try_recv().is_err(), "nothing was stopped");
+
+    assert_eq!(tab.rerun_pane(PaneId::Terminal(0), true).unwrap(), None);
+    match pty_receiver.try_recv() {
+        Ok((PtyInstruction::TerminateCommandInPane(pane_id), _)) => {
+            assert_eq!(pane_id, PaneId::Terminal(0))
+        },
+        _ => panic!("the shell should have been stopped"),
+    }
+    // shells close their pane when they exit
+    assert!(tab
+        .respawn_pane_if_pending(PaneId::Terminal(0), None)
+        .unwrap());
+    match pty_receiver.try_recv() {
+        Ok((PtyInstruction::RespawnShellInPane(pane_id), _)) => {
+            assert_eq!(pane_id, PaneId::Terminal(0))
+        },
+        _ => panic!("a new shell should have been started in the pane"),
+    }
+}
+
+#[test]
+fn quote_command_line_typed_into_shell() {
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".into(), "--".into(), "it's a test".into(), "".into()],
+        ..Default::default()
+    };
+    assert_eq!(
+        super::shell_command_line(&run_command),
+        "cargo test -- 'it'\\''s a test' ''"
+    );
+}
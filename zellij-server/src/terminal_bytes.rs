@@ -4,6 +4,7 @@ use crate::{
     thread_bus::ThreadSenders,
 };
 use async_std::{future::timeout as async_timeout, task};
+use encoding_rs::{Decoder, SHIFT_JIS, WINDOWS_1252};
 use std::{
     os::unix::io::RawFd,
     time::{Duration, Instant},
@@ -11,6 +12,7 @@ use std::{
 use zellij_utils::{
     async_std,
     errors::{get_current_ctx, prelude::*, ContextType},
+    input::command::PaneEncoding,
     logging::debug_to_file,
 };
 
@@ -40,6 +42,7 @@ pub(crate) struct TerminalBytes {
     minimum_render_send_time: Option<Duration>,
     buffering_pause: Duration,
     last_render: Instant,
+    decoder: Option<Decoder>, // None if the bytes are already UTF-8
 }
 
 impl TerminalBytes {
@@ -49,6 +52,7 @@ impl TerminalBytes {
         os_input: Box<dyn ServerOsApi>,
         debug: bool,
         terminal_id: u32,
+        encoding: Option<PaneEncoding>,
     ) -> Self {
         TerminalBytes {
             pid,
@@ -61,6 +65,7 @@ impl TerminalBytes {
             minimum_render_send_time: None,
             buffering_pause: Duration::from_millis(30),
             last_render: Instant::now(),
+            decoder: encoding.and_then(decoder_for),
         }
     }
     pub async fn listen(&mut self) -> Result<()> {
@@ -98,12 +103,10 @@ impl TerminalBytes {
                     if self.debug {
                         let _ = debug_to_file(bytes, self.pid);
                     }
-                    self.async_send_to_screen(ScreenInstruction::PtyBytes(
-                        self.terminal_id,
-                        bytes.to_vec(),
-                    ))
-                    .await
-                    .with_context(err_context)?;
+                    let bytes = self.transcode(bytes);
+                    self.async_send_to_screen(ScreenInstruction::PtyBytes(self.terminal_id, bytes))
+                        .await
+                        .with_context(err_context)?;
                     if !self.backed_up {
                         // we're not backed up, let's send an immediate render instruction
                         let time_to_send_render = self
@@ -139,6 +142,22 @@ impl TerminalBytes {
 
         Ok(())
     }
+    fn transcode(&mut self, bytes: &[u8]) -> Vec<u8> {
+        // the decoder keeps the start of a character split between two reads for the next one,
+        // invalid sequences are replaced with U+FFFD
+        match self.decoder.as_mut() {
+            Some(decoder) => {
+                let mut transcoded = String::with_capacity(
+                    decoder
+                        .max_utf8_buffer_length(bytes.len())
+                        .unwrap_or(bytes.len() * 3),
+                );
+                let _ = decoder.decode_to_string(bytes, &mut transcoded, false);
+                transcoded.into_bytes()
+            },
+            None => bytes.to_vec(),
+        }
+    }
     async fn async_send_to_screen(
         &self,
         screen_instruction: ScreenInstruction,
@@ -192,3 +211,12 @@ impl TerminalBytes {
         }
     }
 }
+
+fn decoder_for(encoding: PaneEncoding) -> Option<Decoder> {
+    match encoding {
+        PaneEncoding::Utf8 => None,
+        // like browsers, latin1 is decoded as its windows-1252 superset
+        PaneEncoding::Latin1 => Some(WINDOWS_1252.new_decoder_without_bom_handling()),
+        PaneEncoding::ShiftJis => Some(SHIFT_JIS.new_decoder_without_bom_handling()),
+    }
+}
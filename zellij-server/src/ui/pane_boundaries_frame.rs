@@ -8,6 +8,8 @@ use zellij_utils::pane_size::Viewport;
 
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+static READ_ONLY_INDICATOR: &str = "🔒";
+
 fn foreground_color(characters: &str, color: Option<PaletteColor>) -> Vec<TerminalCharacter> {
     let mut colored_string = Vec::with_capacity(characters.chars().count());
     for character in characters.chars() {
@@ -78,6 +80,7 @@ pub struct FrameParams {
     pub pane_is_stacked_under: bool,
     pub pane_is_stacked_over: bool,
     pub should_draw_pane_frames: bool,
+    pub more_panes_in_stack: usize, // the panes of its deep stack that this one-liner stands for
 }
 
 #[derive(Default, PartialEq)]
@@ -93,9 +96,12 @@ pub struct PaneFrame {
     pub other_focused_clients: Vec<ClientId>,
     exit_status: Option<ExitStatus>,
     is_first_run: bool,
+    is_queued: bool,
+    is_read_only: bool,
     pane_is_stacked_over: bool,
     pane_is_stacked_under: bool,
     should_draw_pane_frames: bool,
+    more_panes_in_stack: usize,
 }
 
 impl PaneFrame {
@@ -117,9 +123,12 @@ impl PaneFrame {
             other_cursors_exist_in_session: frame_params.other_cursors_exist_in_session,
             exit_status: None,
             is_first_run: false,
+            is_queued: false,
+            is_read_only: false,
             pane_is_stacked_over: frame_params.pane_is_stacked_over,
             pane_is_stacked_under: frame_params.pane_is_stacked_under,
             should_draw_pane_frames: frame_params.should_draw_pane_frames,
+            more_panes_in_stack: frame_params.more_panes_in_stack,
         }
     }
     pub fn add_exit_status(&mut self, exit_status: Option<i32>) {
@@ -131,6 +140,12 @@ impl PaneFrame {
     pub fn indicate_first_run(&mut self) {
         self.is_first_run = true;
     }
+    pub fn indicate_queued(&mut self) {
+        self.is_queued = true;
+    }
+    pub fn indicate_read_only(&mut self) {
+        self.is_read_only = true;
+    }
     pub fn override_color(&mut self, color: PaletteColor) {
         self.color = Some(color);
     }
@@ -357,8 +372,17 @@ impl PaneFrame {
     fn render_title_left_side(&self, max_length: usize) -> Option<(Vec<TerminalCharacter>, usize)> {
         let middle_truncated_sign = "[..]";
         let middle_truncated_sign_long = "[...]";
-        let full_text = format!(" {} ", &self.title);
-        if max_length <= 6 || self.title.is_empty() {
+        let mut full_text = if self.is_read_only {
+            format!(" {} {} ", READ_ONLY_INDICATOR, &self.title)
+        } else {
+            format!(" {} ", &self.title)
+        };
+        if self.more_panes_in_stack > 0 {
+            full_text.push_str(&format!("[ {} MORE PANES ] ", self.more_panes_in_stack));
+        }
+        if max_length <= 6
+            || (self.title.is_empty() && !self.is_read_only && self.more_panes_in_stack == 0)
+        {
             None
         } else if full_text.width() <= max_length {
             Some((foreground_color(&full_text, self.color), full_text.width()))
@@ -825,6 +849,22 @@ impl PaneFrame {
                     left_bracket.len() + exited_text.len() + right_bracket.len(),
                 )
             },
+            None if self.is_queued => {
+                let mut first_part = vec![];
+                let left_bracket = " [ ";
+                let queued_text = "QUEUED";
+                let right_bracket = " ]";
+                first_part.append(&mut foreground_color(left_bracket, self.color));
+                first_part.append(&mut foreground_color(
+                    queued_text,
+                    Some(self.style.colors.orange),
+                ));
+                first_part.append(&mut foreground_color(right_bracket, self.color));
+                (
+                    first_part,
+                    left_bracket.len() + queued_text.len() + right_bracket.len(),
+                )
+            },
             None => (foreground_color(boundary_type::HORIZONTAL, self.color), 1),
         }
     }
@@ -19,6 +19,7 @@ pub struct PaneContentsAndUi<'a> {
     pane_is_stacked_under: bool,
     pane_is_stacked_over: bool,
     should_draw_pane_frames: bool,
+    more_panes_in_stack: usize,
 }
 
 impl<'a> PaneContentsAndUi<'a> {
@@ -49,8 +50,13 @@ impl<'a> PaneContentsAndUi<'a> {
             pane_is_stacked_under,
             pane_is_stacked_over,
             should_draw_pane_frames,
+            more_panes_in_stack: 0,
         }
     }
+    /// Shows in the frame of this pane that it stands for `count` more panes of its deep stack
+    pub fn indicate_more_panes_in_stack(&mut self, count: usize) {
+        self.more_panes_in_stack = count;
+    }
     pub fn render_pane_contents_to_multiple_clients(
         &mut self,
         clients: impl Iterator<Item = ClientId>,
@@ -206,6 +212,7 @@ impl<'a> PaneContentsAndUi<'a> {
                 pane_is_stacked_over: self.pane_is_stacked_over,
                 pane_is_stacked_under: self.pane_is_stacked_under,
                 should_draw_pane_frames: self.should_draw_pane_frames,
+                more_panes_in_stack: self.more_panes_in_stack,
             }
         } else {
             FrameParams {
@@ -218,6 +225,7 @@ impl<'a> PaneContentsAndUi<'a> {
                 pane_is_stacked_over: self.pane_is_stacked_over,
                 pane_is_stacked_under: self.pane_is_stacked_under,
                 should_draw_pane_frames: self.should_draw_pane_frames,
+                more_panes_in_stack: self.more_panes_in_stack,
             }
         };
 
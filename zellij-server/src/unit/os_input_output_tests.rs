@@ -49,3 +49,45 @@ fn get_cwd() {
         pid
     );
 }
+
+#[test]
+fn editors_open_several_files_as_splits() {
+    let files_to_open = vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")];
+    let (nvim, failover_args) = open_file_command(
+        files_to_open.clone(),
+        Some(42),
+        Some(SplitDirection::Vertical),
+        Some(PathBuf::from("nvim")),
+    );
+    assert_eq!(nvim.args, vec!["-O", "+42", "a.rs", "b.rs"]);
+    assert_eq!(
+        failover_args,
+        Some(vec!["-O".into(), "a.rs".into(), "b.rs".into()]),
+        "the files are still split without the line number"
+    );
+    let (vim, _failover_args) = open_file_command(
+        files_to_open.clone(),
+        None,
+        Some(SplitDirection::Horizontal),
+        Some(PathBuf::from("vim")),
+    );
+    assert_eq!(vim.args, vec!["-o", "a.rs", "b.rs"]);
+    let (hx, _failover_args) = open_file_command(
+        files_to_open.clone(),
+        Some(42),
+        Some(SplitDirection::Vertical),
+        Some(PathBuf::from("hx")),
+    );
+    assert_eq!(
+        hx.args,
+        vec!["a.rs:42", "b.rs"],
+        "the line number is the one of the first file"
+    );
+    let (other_editor, _failover_args) = open_file_command(
+        files_to_open,
+        None,
+        Some(SplitDirection::Vertical),
+        Some(PathBuf::from("code")),
+    );
+    assert_eq!(other_editor.args, vec!["a.rs", "b.rs"]);
+}
@@ -14,8 +14,11 @@ use zellij_utils::data::Resize;
 use zellij_utils::errors::{prelude::*, ErrorContext};
 use zellij_utils::input::actions::Action;
 use zellij_utils::input::command::{RunCommand, TerminalAction};
-use zellij_utils::input::layout::{Layout, SplitDirection, TiledPaneLayout};
-use zellij_utils::input::options::Options;
+use zellij_utils::input::layout::{
+    Layout, LayoutSources, PercentOrFixed, Run, SidePanelLayout, SplitDirection, TabPin,
+    TiledPaneLayout,
+};
+use zellij_utils::input::options::{OnLayoutFailure, Options, PaneTitleFormats};
 use zellij_utils::ipc::IpcReceiverWithContext;
 use zellij_utils::pane_size::{Size, SizeInPixels};
 
@@ -30,7 +33,7 @@ use zellij_utils::ipc::PixelDimensions;
 
 use zellij_utils::{
     channels::{self, ChannelWithContext, Receiver},
-    data::{Direction, InputMode, ModeInfo, Palette, PluginCapabilities},
+    data::{Direction, Event, InputMode, ModeInfo, Palette, PaneInfo, PluginCapabilities},
     interprocess::local_socket::LocalSocketStream,
     ipc::{ClientAttributes, ClientToServerMsg, ServerToClientMsg},
 };
@@ -39,7 +42,7 @@ use crate::panes::grid::Grid;
 use crate::panes::link_handler::LinkHandler;
 use crate::panes::sixel::SixelImageStore;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 use zellij_utils::vte;
 
@@ -158,6 +161,9 @@ impl ServerOsApi for FakeInputOutput {
     fn force_kill(&self, _pid: Pid) -> Result<()> {
         unimplemented!()
     }
+    fn terminate(&self, _pid: Pid) -> Result<()> {
+        unimplemented!()
+    }
     fn box_clone(&self) -> Box<dyn ServerOsApi> {
         Box::new((*self).clone())
     }
@@ -221,6 +227,8 @@ fn create_new_screen(size: Size) -> Screen {
     mode_info.session_name = Some("zellij-test".into());
     let draw_pane_frames = false;
     let auto_layout = true;
+    let respect_fixed_sizes = true;
+    let pane_title_formats = PaneTitleFormats::default();
     let session_is_mirrored = true;
     let copy_options = CopyOptions::default();
 
@@ -231,8 +239,16 @@ fn create_new_screen(size: Size) -> Screen {
         mode_info,
         draw_pane_frames,
         auto_layout,
+        respect_fixed_sizes,
+        pane_title_formats,
+        None,
+        None,
+        None,
         session_is_mirrored,
         copy_options,
+        LayoutSources::default(),
+        OnLayoutFailure::default(),
+        0,
     );
     screen
 }
@@ -282,6 +298,8 @@ impl MockScreen {
                     None,
                     client_attributes,
                     Box::new(config_options),
+                    Box::new(LayoutSources::default()),
+                    0,
                 )
                 .expect("TEST")
             })
@@ -301,6 +319,8 @@ impl MockScreen {
             Some(pane_layout.clone()),
             vec![], // floating_panes_layout
             tab_name,
+            None,             // pin
+            None,             // max_content_width
             (vec![], vec![]), // swap layouts
             self.main_client_id,
         ));
@@ -331,6 +351,8 @@ impl MockScreen {
             Some(tab_layout.clone()),
             vec![], // floating_panes_layout
             tab_name,
+            None,             // pin
+            None,             // max_content_width
             (vec![], vec![]), // swap layouts
             self.main_client_id,
         ));
@@ -369,6 +391,9 @@ impl MockScreen {
             pty_writer_thread: None,
             background_jobs_thread: None,
             layout,
+            layout_sources: Box::new(LayoutSources::default()),
+            max_layout_commands: self.session_metadata.max_layout_commands,
+            layout_hot_reload: None,
         }
     }
 }
@@ -425,6 +450,9 @@ impl MockScreen {
             pty_writer_thread: None,
             background_jobs_thread: None,
             layout,
+            layout_sources: Box::new(LayoutSources::default()),
+            max_layout_commands: None,
+            layout_hot_reload: None,
         };
 
         let os_input = FakeInputOutput::default();
@@ -478,12 +506,29 @@ macro_rules! log_actions_in_thread {
     };
 }
 
+// pane updates are left out of the snapshots of the plugin instructions, they are tested on their
+// own
+fn remove_pane_updates(plugin_instructions: &mut Vec<PluginInstruction>) {
+    for plugin_instruction in plugin_instructions.iter_mut() {
+        if let PluginInstruction::Update(updates) = plugin_instruction {
+            updates.retain(|(_plugin_id, _client_id, event)| match event {
+                Event::PaneUpdate(..) => false,
+                _ => true,
+            });
+        }
+    }
+}
+
 fn new_tab(screen: &mut Screen, pid: u32, tab_index: usize) {
+    new_pinned_tab(screen, pid, tab_index, None);
+}
+
+fn new_pinned_tab(screen: &mut Screen, pid: u32, tab_index: usize, pin: Option<TabPin>) {
     let client_id = 1;
     let new_terminal_ids = vec![(pid, None)];
     let new_plugin_ids = HashMap::new();
     screen
-        .new_tab(tab_index, (vec![], vec![]), client_id)
+        .new_tab(tab_index, (vec![], vec![]), pin, None, client_id)
         .expect("TEST");
     screen
         .apply_layout(
@@ -632,6 +677,58 @@ pub fn close_the_middle_tab() {
     );
 }
 
+fn tab_indices_by_position(screen: &Screen) -> Vec<usize> {
+    let mut tabs: Vec<_> = screen.tabs.values().collect();
+    tabs.sort_by_key(|tab| tab.position);
+    tabs.iter().map(|tab| tab.index).collect()
+}
+
+#[test]
+fn pinned_tabs_stay_at_their_edge_when_tabs_are_created() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_pinned_tab(&mut screen, 1, 0, Some(TabPin::First));
+    new_pinned_tab(&mut screen, 2, 1, None);
+    new_pinned_tab(&mut screen, 3, 2, Some(TabPin::Last));
+    new_pinned_tab(&mut screen, 4, 3, Some(TabPin::First));
+    new_pinned_tab(&mut screen, 5, 4, Some(TabPin::Last));
+    new_pinned_tab(&mut screen, 6, 5, None);
+
+    assert_eq!(
+        tab_indices_by_position(&screen),
+        vec![0, 3, 1, 5, 2, 4],
+        "pinned tabs are kept at their edge in the order they were created"
+    );
+}
+
+#[test]
+fn pinned_tabs_stay_at_their_edge_when_tabs_are_closed() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+
+    new_pinned_tab(&mut screen, 1, 0, Some(TabPin::Last));
+    new_pinned_tab(&mut screen, 2, 1, Some(TabPin::First));
+    new_pinned_tab(&mut screen, 3, 2, None);
+    new_pinned_tab(&mut screen, 4, 3, Some(TabPin::Last));
+    new_pinned_tab(&mut screen, 5, 4, None);
+    screen.close_tab_at_index(2).expect("TEST");
+    screen.close_tab_at_index(1).expect("TEST");
+    new_pinned_tab(&mut screen, 6, 5, None);
+
+    assert_eq!(
+        tab_indices_by_position(&screen),
+        vec![4, 5, 0, 3],
+        "pinned tabs are kept at their edge after tabs were closed"
+    );
+}
+
 #[test]
 fn move_focus_left_at_left_screen_edge_changes_tab() {
     let size = Size {
@@ -986,6 +1083,60 @@ fn attach_after_first_tab_closed() {
     screen.add_client(1).expect("TEST");
 }
 
+#[test]
+fn side_panel_is_carved_out_of_all_tabs() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size);
+    let active_pane_cols = |screen: &Screen, tab_index: usize| {
+        screen
+            .tabs
+            .get(&tab_index)
+            .unwrap()
+            .get_active_pane(1)
+            .unwrap()
+            .position_and_size()
+            .cols
+            .as_usize()
+    };
+
+    new_tab(&mut screen, 1, 0);
+    let side_panel_layout = SidePanelLayout {
+        width: PercentOrFixed::Fixed(21),
+        pane: TiledPaneLayout::default(),
+    };
+    screen
+        .new_side_panel(side_panel_layout, 10, None)
+        .expect("TEST");
+    new_tab(&mut screen, 2, 1);
+
+    assert_eq!(
+        active_pane_cols(&screen, 0),
+        100,
+        "existing tab was resized"
+    );
+    assert_eq!(
+        active_pane_cols(&screen, 1),
+        100,
+        "new tab was created next to the panel"
+    );
+
+    screen.close_side_panel().expect("TEST");
+
+    assert_eq!(
+        active_pane_cols(&screen, 0),
+        121,
+        "tab took back the panel space"
+    );
+    assert_eq!(
+        active_pane_cols(&screen, 1),
+        121,
+        "tab took back the panel space"
+    );
+}
+
 // Following are tests for sending CLI actions
 // these tests are only partially relevant to Screen
 // and are included here for two reasons:
@@ -1012,6 +1163,9 @@ pub fn send_cli_write_chars_action_to_screen() {
     );
     let cli_action = CliAction::WriteChars {
         chars: "input from the cli".into(),
+        target_pane: None,
+        target_tab: None,
+        all_matching: false,
     };
     send_cli_action_to_server(&session_metadata, cli_action, &mut mock_screen, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -1038,6 +1192,9 @@ pub fn send_cli_write_action_to_screen() {
     );
     let cli_action = CliAction::Write {
         bytes: vec![102, 111, 111],
+        target_pane: None,
+        target_tab: None,
+        all_matching: false,
     };
     send_cli_action_to_server(&session_metadata, cli_action, &mut mock_screen, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -1045,6 +1202,119 @@ pub fn send_cli_write_action_to_screen() {
     assert_snapshot!(format!("{:?}", *received_pty_instructions.lock().unwrap()));
 }
 
+fn write_chars_to(
+    target_pane: &str,
+    target_tab: Option<&str>,
+    all_matching: bool,
+) -> (Vec<(Vec<u8>, u32)>, Vec<Vec<String>>) {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let pty_writer_receiver = mock_screen.pty_writer_receiver.take().unwrap();
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let session_metadata = mock_screen.clone_session_metadata();
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = ["editor", "shell", "editor"]
+        .iter()
+        .map(|name| TiledPaneLayout {
+            name: Some(name.to_string()),
+            ..Default::default()
+        })
+        .collect();
+    let screen_thread = mock_screen.run(Some(initial_layout));
+    let received_pty_instructions = Arc::new(Mutex::new(vec![]));
+    let pty_writer_thread = log_actions_in_thread!(
+        received_pty_instructions,
+        PtyWriteInstruction::Exit,
+        pty_writer_receiver
+    );
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let cli_action = CliAction::WriteChars {
+        chars: ":wqa".into(),
+        target_pane: Some(target_pane.into()),
+        target_tab: target_tab.map(|target_tab| target_tab.into()),
+        all_matching,
+    };
+    send_cli_action_to_server(&session_metadata, cli_action, &mut mock_screen, client_id);
+    std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
+    mock_screen.teardown(vec![pty_writer_thread, server_thread, screen_thread]);
+    let writes = received_pty_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            PtyWriteInstruction::Write(bytes, terminal_id) => Some((bytes.clone(), *terminal_id)),
+            _ => None,
+        })
+        .collect();
+    let errors = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::LogError(error_lines, _client_id) => Some(error_lines.clone()),
+            _ => None,
+        })
+        .collect();
+    (writes, errors)
+}
+
+#[test]
+pub fn send_cli_write_chars_action_to_a_pane_by_name() {
+    // the focused pane is the first editor
+    let (writes, errors) = write_chars_to("shell", None, false);
+    assert_eq!(writes, vec![(b":wqa".to_vec(), 1)]);
+    assert!(errors.is_empty(), "{:?}", errors);
+    let (writes, errors) = write_chars_to("terminal_2", Some("Tab #1"), false);
+    assert_eq!(writes, vec![(b":wqa".to_vec(), 2)]);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+pub fn send_cli_write_chars_action_to_an_ambiguous_pane_name() {
+    let (writes, errors) = write_chars_to("editor", None, false);
+    assert!(writes.is_empty(), "{:?}", writes);
+    assert_eq!(
+        errors,
+        vec![vec![
+            String::from(
+                "Several panes are named \"editor\", use --all-matching to write to all of them:"
+            ),
+            String::from("    editor (tab \"Tab #1\")"),
+            String::from("    editor (tab \"Tab #1\")"),
+        ]]
+    );
+    let (mut writes, errors) = write_chars_to("editor", None, true);
+    writes.sort_by_key(|(_bytes, terminal_id)| *terminal_id);
+    assert_eq!(writes, vec![(b":wqa".to_vec(), 0), (b":wqa".to_vec(), 2)]);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+pub fn send_cli_write_chars_action_to_a_missing_pane_or_tab() {
+    let (writes, errors) = write_chars_to("logs", None, false);
+    assert!(writes.is_empty(), "{:?}", writes);
+    assert_eq!(
+        errors[0][0],
+        "Could not find a pane named \"logs\". Available panes:"
+    );
+    let (writes, errors) = write_chars_to("shell", Some("other"), false);
+    assert!(writes.is_empty(), "{:?}", writes);
+    assert_eq!(
+        errors,
+        vec![vec![String::from("Could not find a tab named \"other\".")]]
+    );
+}
+
 #[test]
 pub fn send_cli_resize_action_to_screen() {
     let size = Size { cols: 80, rows: 20 };
@@ -1065,6 +1335,7 @@ pub fn send_cli_resize_action_to_screen() {
     let resize_cli_action = CliAction::Resize {
         resize: Resize::Increase,
         direction: Some(Direction::Left),
+        include_fixed: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -1926,6 +2197,9 @@ pub fn send_cli_toggle_active_tab_sync_action() {
     let cli_toggle_active_tab_sync_action = CliAction::ToggleActiveSyncTab;
     let cli_write_action = CliAction::Write {
         bytes: vec![102, 111, 111],
+        target_pane: None,
+        target_tab: None,
+        all_matching: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -1972,6 +2246,9 @@ pub fn send_cli_new_pane_action_with_default_parameters() {
         name: None,
         close_on_exit: false,
         start_suspended: false,
+        target_pane: None,
+        any_tab: false,
+        force: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -2012,6 +2289,9 @@ pub fn send_cli_new_pane_action_with_split_direction() {
         name: None,
         close_on_exit: false,
         start_suspended: false,
+        target_pane: None,
+        any_tab: false,
+        force: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -2052,6 +2332,9 @@ pub fn send_cli_new_pane_action_with_command_and_cwd() {
         name: None,
         close_on_exit: false,
         start_suspended: false,
+        target_pane: None,
+        any_tab: false,
+        force: false,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -2371,6 +2654,7 @@ pub fn send_cli_new_tab_action_default_params() {
         name: None,
         layout: None,
         cwd: None,
+        template: None,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -2415,6 +2699,7 @@ pub fn send_cli_new_tab_action_with_name_and_layout() {
             env!("CARGO_MANIFEST_DIR")
         ))),
         cwd: None,
+        template: None,
     };
     send_cli_action_to_server(
         &session_metadata,
@@ -2441,6 +2726,212 @@ pub fn send_cli_new_tab_action_with_name_and_layout() {
     assert_snapshot!(format!("{:#?}", new_tab_instruction));
 }
 
+fn tab_layout_running(commands: &[&str]) -> TiledPaneLayout {
+    let mut tab_layout = TiledPaneLayout::default();
+    tab_layout.children = commands
+        .iter()
+        .map(|command| TiledPaneLayout {
+            run: Some(Run::Command(RunCommand {
+                command: PathBuf::from(command),
+                ..Default::default()
+            })),
+            ..Default::default()
+        })
+        .collect();
+    tab_layout
+}
+
+#[test]
+pub fn new_tab_layouts_starting_more_commands_than_the_maximum_are_refused() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let mut session_metadata = mock_screen.clone_session_metadata();
+    session_metadata.max_layout_commands = Some(2);
+    let screen_thread = mock_screen.run(Some(TiledPaneLayout::default()));
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let os_input = mock_screen.os_input.clone();
+    for commands in [&["htop", "tail"][..], &["htop", "tail", "top"][..]] {
+        route_action(
+            Action::NewTab(
+                Some(tab_layout_running(commands)),
+                vec![],
+                None,
+                None,
+                None,
+                None,
+            ),
+            &session_metadata,
+            &os_input,
+            &mock_screen.to_server.clone(),
+            client_id,
+        )
+        .unwrap();
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, server_thread, screen_thread]);
+    let new_tab_count = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|instruction| matches!(instruction, PluginInstruction::NewTab(..)))
+        .count();
+    assert_eq!(
+        new_tab_count, 2,
+        "the initial tab and the one starting 2 commands"
+    );
+    let errors: Vec<Vec<String>> = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::LogError(error_lines, _client_id) => Some(error_lines.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![vec![String::from(
+            "The layout starts 3 commands, more than the maximum of 2 (max_layout_commands)"
+        )]]
+    );
+}
+
+#[test]
+pub fn new_tabs_opened_from_the_template_do_not_add_up_towards_the_maximum_commands() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let mut session_metadata = mock_screen.clone_session_metadata();
+    session_metadata.max_layout_commands = Some(2);
+    session_metadata.layout = Box::new(Layout {
+        template: Some((tab_layout_running(&["htop", "tail"]), vec![])),
+        ..Default::default()
+    });
+    let screen_thread = mock_screen.run(Some(TiledPaneLayout::default()));
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    for _ in 0..3 {
+        let new_tab_action = CliAction::NewTab {
+            name: None,
+            layout: None,
+            cwd: None,
+            template: None,
+        };
+        send_cli_action_to_server(
+            &session_metadata,
+            new_tab_action,
+            &mut mock_screen,
+            client_id,
+        );
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    let new_tab_count = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|instruction| matches!(instruction, PluginInstruction::NewTab(..)))
+        .count();
+    assert_eq!(new_tab_count, 4, "the initial tab and the 3 new ones");
+}
+
+#[test]
+pub fn new_tabs_can_be_opened_from_the_tab_templates_of_the_session_layout() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let mut session_metadata = mock_screen.clone_session_metadata();
+    let mut tab_templates = BTreeMap::new();
+    tab_templates.insert(
+        "dev".to_string(),
+        (tab_layout_running(&["htop", "tail"]), vec![]),
+    );
+    session_metadata.layout = Box::new(Layout {
+        tab_templates,
+        ..Default::default()
+    });
+    let screen_thread = mock_screen.run(Some(TiledPaneLayout::default()));
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    for template in ["dev", "docs"] {
+        let new_tab_action = CliAction::NewTab {
+            name: None,
+            layout: None,
+            cwd: None,
+            template: Some(template.to_string()),
+        };
+        send_cli_action_to_server(
+            &session_metadata,
+            new_tab_action,
+            &mut mock_screen,
+            client_id,
+        );
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, server_thread, screen_thread]);
+    let new_tab_layouts: Vec<Option<TiledPaneLayout>> = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            PluginInstruction::NewTab(_shell, tab_layout, ..) => Some(tab_layout.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        new_tab_layouts.last(),
+        Some(&Some(tab_layout_running(&["htop", "tail"]))),
+        "the tab opened from the template runs its commands"
+    );
+    assert_eq!(new_tab_layouts.len(), 2, "the initial tab and the dev one");
+    let errors: Vec<Vec<String>> = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::LogError(error_lines, _client_id) => Some(error_lines.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![vec![String::from(
+            "There is no tab_template named \"docs\" in the layout"
+        )]]
+    );
+}
+
 #[test]
 pub fn send_cli_next_tab_action() {
     let size = Size { cols: 80, rows: 10 };
@@ -2622,6 +3113,7 @@ pub fn send_cli_rename_tab() {
     send_cli_action_to_server(&session_metadata, rename_tab, &mut mock_screen, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
     mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    remove_pane_updates(&mut received_plugin_instructions.lock().unwrap());
     assert_snapshot!(format!(
         "{:#?}",
         *received_plugin_instructions.lock().unwrap()
@@ -2665,8 +3157,132 @@ pub fn send_cli_undo_rename_tab() {
     );
     std::thread::sleep(std::time::Duration::from_millis(100));
     mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    remove_pane_updates(&mut received_plugin_instructions.lock().unwrap());
     assert_snapshot!(format!(
         "{:#?}",
         *received_plugin_instructions.lock().unwrap()
     ))
 }
+
+#[test]
+pub fn pane_updates_carry_the_layout_origin_of_layout_panes() {
+    let size = Size { cols: 80, rows: 10 };
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![
+        TiledPaneLayout {
+            name: Some("editor".into()),
+            ..Default::default()
+        },
+        TiledPaneLayout {
+            run: Some(Run::Command(RunCommand {
+                command: PathBuf::from("htop"),
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+    ];
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(Some(initial_layout));
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    let last_pane_update: Vec<PaneInfo> = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .find_map(|instruction| match instruction {
+            PluginInstruction::Update(updates) => {
+                updates.iter().find_map(|(_, _, event)| match event {
+                    Event::PaneUpdate(pane_infos) => Some(pane_infos.clone()),
+                    _ => None,
+                })
+            },
+            _ => None,
+        })
+        .expect("no pane update sent to plugins");
+    let mut origins: Vec<(u32, Option<String>, Option<String>)> = last_pane_update
+        .iter()
+        .map(|pane_info| {
+            let origin = pane_info
+                .layout_origin
+                .clone()
+                .expect("layout pane without an origin");
+            (pane_info.id, origin.pane_name, origin.run)
+        })
+        .collect();
+    origins.sort();
+    assert_eq!(
+        origins,
+        vec![
+            (0, Some("editor".to_owned()), None),
+            (1, None, Some("htop".to_owned())),
+        ]
+    );
+}
+
+#[test]
+pub fn pane_updates_leave_out_what_layout_panes_excluded_from_dump_run() {
+    let size = Size { cols: 80, rows: 10 };
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![
+        TiledPaneLayout {
+            name: Some("editor".into()),
+            ..Default::default()
+        },
+        TiledPaneLayout {
+            run: Some(Run::Command(RunCommand {
+                command: PathBuf::from("htop"),
+                ..Default::default()
+            })),
+            exclude_from_dump: true,
+            ..Default::default()
+        },
+    ];
+    let mut mock_screen = MockScreen::new(size);
+    let screen_thread = mock_screen.run(Some(initial_layout));
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+    let last_pane_update: Vec<PaneInfo> = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .find_map(|instruction| match instruction {
+            PluginInstruction::Update(updates) => {
+                updates.iter().find_map(|(_, _, event)| match event {
+                    Event::PaneUpdate(pane_infos) => Some(pane_infos.clone()),
+                    _ => None,
+                })
+            },
+            _ => None,
+        })
+        .expect("no pane update sent to plugins");
+    let mut origins: Vec<(u32, Option<String>, bool)> = last_pane_update
+        .iter()
+        .map(|pane_info| {
+            let origin = pane_info
+                .layout_origin
+                .clone()
+                .expect("layout pane without an origin");
+            (pane_info.id, origin.run, origin.redacted)
+        })
+        .collect();
+    origins.sort();
+    assert_eq!(origins, vec![(0, None, false), (1, None, true)]);
+}
@@ -0,0 +1,148 @@
+//! Positions the panes of the swap layouts of 10 tabs through a burst of 200 resize events (the
+//! terminal's corner dragged back and forth), the way the server did before and after resizes
+//! were coalesced:
+//!
+//! - before: every tab re-positions its panes on every resize event
+//! - after: only the focused tab re-positions its panes, at most once per relayout interval and
+//!   through a `PanePositionsCache`, the other tabs do so once when they are focused
+//!
+//! Run with `cargo bench -p zellij-utils --bench resize_relayout`
+use std::time::{Duration, Instant};
+use zellij_utils::{
+    input::layout::{FocusPolicy, Layout, PanePositionsCache, TiledPaneLayout},
+    pane_size::PaneGeom,
+};
+
+const TAB_COUNT: usize = 10;
+const RESIZE_EVENT_COUNT: usize = 200;
+const RESIZE_EVENT_SPACING: Duration = Duration::from_millis(5);
+const RELAYOUT_INTERVAL: Duration = Duration::from_millis(50);
+const RUNS: u32 = 20;
+
+const SWAP_LAYOUT: &str = r#"
+layout {
+    swap_tiled_layout {
+        tab {
+            pane size=1 borderless=true
+            pane split_direction="vertical" {
+                pane size="60%" {
+                    pane
+                    pane split_direction="vertical" {
+                        pane
+                        pane
+                        pane
+                    }
+                }
+                pane {
+                    pane size="30%"
+                    pane { children stacked=true; }
+                }
+            }
+            pane size=2 borderless=true
+        }
+    }
+}
+"#;
+
+fn swap_layout() -> TiledPaneLayout {
+    let layout = Layout::from_kdl(SWAP_LAYOUT, "bench.kdl".into(), None, None).unwrap();
+    let (swap_tiled_layout, _name, _tags) = &layout.swap_tiled_layouts[0];
+    swap_tiled_layout.values().next().unwrap().clone()
+}
+
+// the corner of the terminal dragged back and forth between 80x24 and 200x60
+fn resize_events() -> Vec<PaneGeom> {
+    (0..RESIZE_EVENT_COUNT)
+        .map(|event| {
+            let step = event % 60;
+            let step = if step < 30 { step } else { 60 - step };
+            let mut space = PaneGeom::default();
+            space.cols.set_inner(80 + step * 4);
+            space.rows.set_inner(24 + step);
+            space
+        })
+        .collect()
+}
+
+fn position(layout: &TiledPaneLayout, space: &PaneGeom) -> usize {
+    layout
+        .position_panes_in_space_with_focus(space, Some(12), FocusPolicy::default())
+        .map(|positions| positions.len())
+        .unwrap_or(0)
+}
+
+fn before(layouts: &[TiledPaneLayout], resize_events: &[PaneGeom]) -> usize {
+    let mut positioned = 0;
+    for space in resize_events {
+        for layout in layouts {
+            positioned += position(layout, space);
+        }
+    }
+    positioned
+}
+
+fn after(layouts: &[TiledPaneLayout], resize_events: &[PaneGeom]) -> usize {
+    let mut caches: Vec<PanePositionsCache> = layouts
+        .iter()
+        .map(|_| PanePositionsCache::default())
+        .collect();
+    let mut positioned = 0;
+    let mut position_cached = |tab: usize, space: &PaneGeom, positioned: &mut usize| {
+        *positioned += caches[tab]
+            .position_panes_in_space_with_focus(
+                &layouts[tab],
+                space,
+                Some(12),
+                FocusPolicy::default(),
+            )
+            .map(|positions| positions.len())
+            .unwrap_or(0);
+    };
+    // the resize events arrive RESIZE_EVENT_SPACING apart, tab 0 is the focused one
+    let mut last_relayout: Option<Duration> = None;
+    for (event, space) in resize_events.iter().enumerate() {
+        let now = RESIZE_EVENT_SPACING * event as u32;
+        let relayout_is_due = last_relayout
+            .map(|last_relayout| now - last_relayout >= RELAYOUT_INTERVAL)
+            .unwrap_or(true);
+        if relayout_is_due {
+            position_cached(0, space, &mut positioned);
+            last_relayout = Some(now);
+        }
+    }
+    // the final size is always applied, and the other tabs once they are focused
+    let final_space = resize_events.last().unwrap();
+    for tab in 0..layouts.len() {
+        position_cached(tab, final_space, &mut positioned);
+    }
+    positioned
+}
+
+fn measure(name: &str, run: impl Fn() -> usize) -> Duration {
+    let mut positioned = 0;
+    let started_at = Instant::now();
+    for _ in 0..RUNS {
+        positioned = run();
+    }
+    let per_run = started_at.elapsed() / RUNS;
+    println!(
+        "{:<7} {:>10.3?} per burst ({} panes positioned)",
+        name, per_run, positioned
+    );
+    per_run
+}
+
+fn main() {
+    let layouts: Vec<TiledPaneLayout> = (0..TAB_COUNT).map(|_| swap_layout()).collect();
+    let resize_events = resize_events();
+    println!(
+        "{} resize events across {} tabs, averaged over {} runs",
+        RESIZE_EVENT_COUNT, TAB_COUNT, RUNS
+    );
+    let before = measure("before", || before(&layouts, &resize_events));
+    let after = measure("after", || after(&layouts, &resize_events));
+    println!(
+        "speedup: {:.1}x",
+        before.as_secs_f64() / after.as_secs_f64().max(f64::EPSILON)
+    );
+}
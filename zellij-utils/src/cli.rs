@@ -2,9 +2,10 @@ use crate::data::{Direction, InputMode, Resize};
 use crate::setup::Setup;
 use crate::{
     consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV},
+    input::layout_variables::{parse_layout_variable, LayoutVariables},
     input::options::CliOptions,
 };
-use clap::{Parser, Subcommand};
+use clap::{ArgEnum, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -27,9 +28,16 @@ pub struct CliArgs {
     #[clap(long, short, overrides_with = "session", value_parser)]
     pub session: Option<String>,
 
-    /// Name of a predefined layout inside the layout directory or the path to a layout file
-    #[clap(short, long, value_parser, overrides_with = "layout")]
-    pub layout: Option<PathBuf>,
+    /// Name of a predefined layout inside the layout directory, the path to a layout file, an
+    /// http(s) URL to fetch it from or `-` to read it from stdin, repeat it to open the tabs of
+    /// several layouts in order
+    #[clap(short, long, value_parser)]
+    pub layout: Vec<PathBuf>,
+
+    /// Give a value to a variable of the layout (referred to as `{{NAME}}` in it), repeat it for
+    /// each variable
+    #[clap(long, value_name = "NAME=VALUE", value_parser = parse_layout_variable)]
+    pub layout_var: Vec<(String, String)>,
 
     /// Change where zellij looks for the configuration file
     #[clap(short, long, overrides_with = "config", env = ZELLIJ_CONFIG_FILE_ENV, value_parser)]
@@ -39,6 +47,20 @@ pub struct CliArgs {
     #[clap(long, overrides_with = "config_dir", env = ZELLIJ_CONFIG_DIR_ENV, value_parser)]
     pub config_dir: Option<PathBuf>,
 
+    /// Print what the layout would run and exit without starting a session
+    #[clap(long, value_parser)]
+    pub dry_run: bool,
+
+    /// Start the commands of the layout without asking first, even if it is not trusted (see the
+    /// confirm_layout_commands option)
+    #[clap(long, value_parser)]
+    pub trust_layout: bool,
+
+    /// Print the layout converted to KDL (to save it) when it is in the YAML format of zellij
+    /// versions before 0.32.0, instead of refusing it
+    #[clap(long, value_parser)]
+    pub auto_convert: bool,
+
     #[clap(subcommand)]
     pub command: Option<Command>,
 
@@ -47,6 +69,13 @@ pub struct CliArgs {
     pub debug: bool,
 }
 
+impl CliArgs {
+    /// The variables given to the layout with `--layout-var`, the last value of each one counts
+    pub fn layout_variables(&self) -> LayoutVariables {
+        self.layout_var.iter().cloned().collect()
+    }
+}
+
 #[derive(Debug, Subcommand, Clone, Serialize, Deserialize)]
 pub enum Command {
     /// Change the behaviour of zellij
@@ -69,11 +98,21 @@ pub enum SessionCommand {
     Options(CliOptions),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ArgEnum)]
+pub enum SessionListFormat {
+    Plain,
+    Json,
+}
+
 #[derive(Debug, Subcommand, Clone, Serialize, Deserialize)]
 pub enum Sessions {
     /// List active sessions
     #[clap(visible_alias = "ls")]
-    ListSessions,
+    ListSessions {
+        /// How to print the sessions
+        #[clap(long, arg_enum, value_parser, default_value = "plain")]
+        format: SessionListFormat,
+    },
 
     /// Attach to a session
     #[clap(visible_alias = "a")]
@@ -90,6 +129,14 @@ pub enum Sessions {
         #[clap(long, value_parser)]
         index: Option<usize>,
 
+        /// Attach to the session that was started with this layout (name or path)
+        #[clap(long, value_parser, conflicts_with("index"))]
+        with_layout: Option<PathBuf>,
+
+        /// Create a session with the layout given in --with-layout if none was started with it
+        #[clap(long, value_parser, requires("with_layout"))]
+        find_or_create: bool,
+
         /// Change the behaviour of zellij
         #[clap(subcommand, name = "options")]
         options: Option<Box<SessionCommand>>,
@@ -144,6 +191,19 @@ pub enum Sessions {
         /// Start the command suspended, only running after you first presses ENTER
         #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
         start_suspended: bool,
+
+        /// Run the command in an existing pane (by name or id, eg. "terminal_1") instead of
+        /// opening a new one
+        #[clap(long, value_parser, conflicts_with_all(&["direction", "floating"]))]
+        target_pane: Option<String>,
+
+        /// Look for the target pane in all tabs, not just the current one
+        #[clap(long, value_parser, takes_value(false), requires("target_pane"))]
+        any_tab: bool,
+
+        /// If the target pane is busy running another command, stop it and run this one instead
+        #[clap(long, value_parser, takes_value(false), requires("target_pane"))]
+        force: bool,
     },
     /// Edit file with default $EDITOR / $VISUAL
     #[clap(visible_alias = "e")]
@@ -182,15 +242,46 @@ pub enum CliAction {
     /// Write bytes to the terminal.
     Write {
         bytes: Vec<u8>,
+
+        /// Write to the pane with this name (as declared in the layout, or its current name) or
+        /// id (eg. "terminal_1") instead of the focused pane, without focusing it
+        #[clap(long, value_parser)]
+        target_pane: Option<String>,
+
+        /// Only look for the target pane in the tab with this name
+        #[clap(long, value_parser, requires("target_pane"))]
+        target_tab: Option<String>,
+
+        /// Write to all the panes matching the target pane, instead of failing when there are
+        /// several
+        #[clap(long, value_parser, takes_value(false), requires("target_pane"))]
+        all_matching: bool,
     },
     /// Write characters to the terminal.
     WriteChars {
         chars: String,
+
+        /// Write to the pane with this name (as declared in the layout, or its current name) or
+        /// id (eg. "terminal_1") instead of the focused pane, without focusing it
+        #[clap(long, value_parser)]
+        target_pane: Option<String>,
+
+        /// Only look for the target pane in the tab with this name
+        #[clap(long, value_parser, requires("target_pane"))]
+        target_tab: Option<String>,
+
+        /// Write to all the panes matching the target pane, instead of failing when there are
+        /// several
+        #[clap(long, value_parser, takes_value(false), requires("target_pane"))]
+        all_matching: bool,
     },
     /// [increase|decrease] the focused panes area at the [left|down|up|right] border.
     Resize {
         resize: Resize,
         direction: Option<Direction>,
+        /// Resize panes with a fixed size too, instead of taking the space from flexible panes
+        #[clap(long, value_parser)]
+        include_fixed: bool,
     },
     /// Change focus to the next pane
     FocusNextPane,
@@ -240,6 +331,8 @@ pub enum CliAction {
     HalfPageScrollDown,
     /// Toggle between fullscreen focus pane and normal layout.
     ToggleFullscreen,
+    /// Toggle whether the focused pane drops the input sent to it (it can still be scrolled)
+    TogglePaneReadOnly,
     /// Toggle frames around panes in the UI
     TogglePaneFrames,
     /// Toggle between sending text commands to all panes on the current tab and normal mode.
@@ -286,6 +379,21 @@ pub enum CliAction {
             requires("command")
         )]
         start_suspended: bool,
+        /// Run the command in an existing pane (by name or id, eg. "terminal_1") instead of
+        /// opening a new one
+        #[clap(
+            long,
+            value_parser,
+            conflicts_with_all(&["direction", "floating"]),
+            requires("command")
+        )]
+        target_pane: Option<String>,
+        /// Look for the target pane in all tabs, not just the current one
+        #[clap(long, value_parser, takes_value(false), requires("target_pane"))]
+        any_tab: bool,
+        /// If the target pane is busy running another command, stop it and run this one instead
+        #[clap(long, value_parser, takes_value(false), requires("target_pane"))]
+        force: bool,
     },
     /// Open the specified file in a new zellij pane with your default EDITOR
     Edit {
@@ -348,7 +456,7 @@ pub enum CliAction {
     UndoRenameTab,
     /// Create a new tab, optionally with a specified tab layout and name
     NewTab {
-        /// Layout to use for the new tab
+        /// Layout to use for the new tab (`-` to read it from stdin)
         #[clap(short, long, value_parser)]
         layout: Option<PathBuf>,
 
@@ -359,7 +467,43 @@ pub enum CliAction {
         /// Change the working directory of the new tab
         #[clap(short, long, value_parser, requires("layout"))]
         cwd: Option<PathBuf>,
+
+        /// Open the new tab from this tab_template of the session's layout
+        #[clap(short, long, value_parser, conflicts_with("layout"))]
+        template: Option<String>,
+    },
+    /// Go to the previous swap layout of the current tab
+    PreviousSwapLayout {
+        /// Only cycle among the swap layouts with this tag
+        #[clap(short, long, value_parser)]
+        tag: Option<String>,
+    },
+    /// Go to the next swap layout of the current tab
+    NextSwapLayout {
+        /// Only cycle among the swap layouts with this tag
+        #[clap(short, long, value_parser)]
+        tag: Option<String>,
+    },
+    /// Turn the tiled panes of the current tab a quarter turn (clockwise unless told otherwise),
+    /// side by side panes ending up one above the other and the other way around
+    RotateTabLayout {
+        /// Turn the panes counter-clockwise
+        #[clap(long, value_parser, takes_value(false))]
+        counter_clockwise: bool,
+    },
+    /// Print where the session's layout and swap layouts were loaded from
+    DumpLayoutDebug,
+    /// Spawn the layout command panes still waiting for their turn right away
+    FlushCommandQueue,
+    /// Stop the command running in a pane and run it again in its place, keeping the pane's
+    /// scrollback
+    RerunPane {
+        /// The pane to re-run (by name or id, eg. "terminal_1"), the focused pane if not given
+        #[clap(long, value_parser)]
+        target_pane: Option<String>,
+
+        /// Also restart panes running a shell, starting a new shell in their place
+        #[clap(long, value_parser, takes_value(false))]
+        force: bool,
     },
-    PreviousSwapLayout,
-    NextSwapLayout,
 }
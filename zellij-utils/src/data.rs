@@ -320,6 +320,10 @@ pub struct ResizeStrategy {
     /// +---+---+
     /// ```
     pub invert_on_boundaries: bool,
+    /// If set to true, panes with a fixed size are resized like any other pane. Otherwise (the
+    /// default) they keep their size and the space is taken from the nearest flexible pane.
+    #[serde(default)]
+    pub include_fixed: bool,
 }
 
 impl From<Direction> for ResizeStrategy {
@@ -340,6 +344,14 @@ impl ResizeStrategy {
             resize,
             direction,
             invert_on_boundaries: true,
+            include_fixed: false,
+        }
+    }
+
+    pub fn including_fixed_panes(self, include_fixed: bool) -> Self {
+        ResizeStrategy {
+            include_fixed,
+            ..self
         }
     }
 
@@ -353,7 +365,7 @@ impl ResizeStrategy {
             None => None,
         };
 
-        ResizeStrategy::new(resize, direction)
+        ResizeStrategy::new(resize, direction).including_fixed_panes(self.include_fixed)
     }
 
     pub fn resize_type(&self) -> Resize {
@@ -469,6 +481,13 @@ pub enum Event {
     SystemClipboardFailure,
     InputReceived,
     Visible(bool),
+    /// A resize was refused because every pane that could give up space has a fixed size
+    CantResizeFixedPanes,
+    /// Input was dropped because it was sent to a read-only pane
+    InputToReadOnlyPane,
+    PaneUpdate(Vec<PaneInfo>),
+    /// The command of a command pane created by a layout exited
+    CommandPaneExited(CommandPaneExit),
 }
 
 /// Describes the different input modes, which change the way that keystrokes will be interpreted.
@@ -687,7 +706,46 @@ pub struct TabInfo {
     pub are_floating_panes_visible: bool,
     pub other_focused_clients: Vec<ClientId>,
     pub active_swap_layout_name: Option<String>,
+    #[serde(default)]
+    pub active_swap_layout_label: Option<String>, // its position and name, eg. "3/5: wide-editor"
     pub is_swap_layout_dirty: bool,
+    #[serde(default)]
+    pub panes_hidden_in_deep_stacks: usize, // stack panes left out for lack of rows
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct PaneInfo {
+    /* subset of fields to publish to plugins */
+    pub id: u32, // a terminal id or a plugin id, depending on is_plugin
+    pub is_plugin: bool,
+    pub is_floating: bool,
+    pub name: String,
+    pub tab_position: usize,
+    #[serde(default)]
+    pub layout_origin: Option<PaneLayoutOrigin>, // None for panes not created by a layout
+}
+
+/// Where a pane created by a layout was declared
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct PaneLayoutOrigin {
+    pub layout_source: String, // the file the layout was loaded from
+    #[serde(default)]
+    pub pane_name: Option<String>, // as declared, the pane may have been renamed since
+    #[serde(default)]
+    pub run: Option<String>, // eg. the command and its args, or the plugin location
+    #[serde(default)]
+    pub redacted: bool, // the pane is marked with `exclude_from_dump`, `run` is left out
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CommandPaneExit {
+    pub pane_id: u32, // a terminal id
+    pub command: String,
+    #[serde(default)]
+    pub exit_status: Option<i32>, // None if the pane was closed when its command exited
+    pub runtime_secs: f64,
+    #[serde(default)]
+    pub layout_origin: Option<PaneLayoutOrigin>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
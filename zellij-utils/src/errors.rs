@@ -271,6 +271,7 @@ pub enum ScreenContext {
     CloseFocusedPane,
     ToggleActiveSyncTab,
     ToggleActiveTerminalFullscreen,
+    ToggleActivePaneReadOnly,
     TogglePaneFrames,
     SetSelectable,
     SetInvisibleBorders,
@@ -282,6 +283,7 @@ pub enum ScreenContext {
     UndoRenamePane,
     NewTab,
     ApplyLayout,
+    LayoutTabFailed,
     SwitchTabNext,
     SwitchTabPrev,
     CloseTab,
@@ -323,6 +325,16 @@ pub enum ScreenContext {
     ClearPaneFrameColorOverride,
     PreviousSwapLayout,
     NextSwapLayout,
+    RotateTabLayout,
+    RunInPane,
+    NewSidePanel,
+    PaneDependencyTimedOut,
+    SpawnQueuedCommand,
+    FlushCommandQueue,
+    RelayoutAfterResize,
+    RerunPane,
+    WriteToPane,
+    ReloadSwapLayouts,
 }
 
 /// Stack call representations corresponding to the different types of [`PtyInstruction`]s.
@@ -338,6 +350,11 @@ pub enum PtyContext {
     ClosePane,
     CloseTab,
     ReRunCommandInPane,
+    KillCommandInPane,
+    TerminateCommandInPane,
+    ForceKillCommandInPane,
+    RespawnShellInPane,
+    SpawnSidePanel,
     Exit,
 }
 
@@ -366,7 +383,10 @@ pub enum ClientContext {
     SwitchToMode,
     Connected,
     ActiveClients,
+    Log,
+    LogError,
     OwnClientId,
+    SessionInfo,
     StartedParsingStdinQuery,
     DoneParsingStdinQuery,
 }
@@ -385,6 +405,10 @@ pub enum ServerContext {
     AttachClient,
     ConnStatus,
     ActiveClients,
+    Log,
+    LogError,
+    SessionInfo,
+    ReloadLayout,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -396,6 +420,11 @@ pub enum PtyWriteContext {
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum BackgroundJobContext {
     DisplayPaneError,
+    PaneDependencyTimeout,
+    SpawnQueuedCommand,
+    RelayoutAfterResize,
+    ForceKillCommandInPane,
+    WatchLayoutFiles,
     Exit,
 }
 
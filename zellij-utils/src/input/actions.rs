@@ -8,6 +8,7 @@ use crate::cli::CliAction;
 use crate::data::InputMode;
 use crate::data::{Direction, Resize};
 use crate::input::config::{ConfigError, KdlError};
+use crate::input::layout_variables::LayoutVariables;
 use crate::input::options::OnForceClose;
 use miette::{NamedSource, Report};
 use serde::{Deserialize, Serialize};
@@ -101,12 +102,18 @@ pub enum Action {
     Write(Vec<u8>),
     /// Write Characters to the terminal.
     WriteChars(String),
+    /// Write to the panes with a name (as declared in the layout, or their current name) or id,
+    /// without focusing them
+    WriteToPane(Vec<u8>, String, Option<String>, bool), // the name of the tab to look in, bool
+    // is write to all the matching panes
     /// Switch to the specified input mode.
     SwitchToMode(InputMode),
     /// Switch all connected clients to the specified input mode.
     SwitchModeForAllClients(InputMode),
     /// Shrink/enlarge focused pane at specified border
     Resize(Resize, Option<Direction>),
+    /// Shrink/enlarge focused pane at specified border, resizing panes with a fixed size as well
+    ResizeIncludingFixed(Resize, Option<Direction>),
     /// Switch focus to next pane in specified direction.
     FocusNextPane,
     FocusPreviousPane,
@@ -143,6 +150,8 @@ pub enum Action {
     HalfPageScrollDown,
     /// Toggle between fullscreen focus pane and normal layout.
     ToggleFocusFullscreen,
+    /// Toggle dropping the input sent to the focused pane
+    TogglePaneReadOnly,
     /// Toggle frames around panes in the UI
     TogglePaneFrames,
     /// Toggle between sending text commands to all panes on the current tab and normal mode.
@@ -173,7 +182,8 @@ pub enum Action {
         Option<Vec<SwapTiledLayout>>,
         Option<Vec<SwapFloatingLayout>>,
         Option<String>,
-    ), // the String is the tab name
+        Option<String>,
+    ), // the tab name and the tab_template of the session layout to open the tab from
     /// Do nothing.
     NoOp,
     /// Go to the next tab.
@@ -189,6 +199,9 @@ pub enum Action {
     UndoRenameTab,
     /// Run specified command in new pane.
     Run(RunCommandAction),
+    /// Run specified command in an existing pane, identified by its name or id
+    RunInPane(String, RunCommandAction, bool, bool), // bools are: search all tabs, force even if
+    // the pane is busy
     /// Detach session and exit
     Detach,
     LeftClick(Position),
@@ -214,8 +227,20 @@ pub enum Action {
     /// Toggle case sensitivity of search
     SearchToggleOption(SearchOption),
     ToggleMouseMode,
-    PreviousSwapLayout,
-    NextSwapLayout,
+    /// Go to the previous swap layout, only among those with the tag if one is given
+    PreviousSwapLayout(Option<String>),
+    /// Go to the next swap layout, only among those with the tag if one is given
+    NextSwapLayout(Option<String>),
+    /// Turn the tiled panes of the current tab a quarter turn, keeping what runs in them
+    RotateTabLayout {
+        clockwise: bool,
+    },
+    /// Report the files the session's layout was loaded from
+    DumpLayoutDebug,
+    /// Spawn the queued layout command panes without waiting for their turn
+    FlushCommandQueue,
+    /// Stop the command of a pane (the focused one if no name or id is given) and run it again
+    RerunPane(Option<String>, bool), // bool is force, to also restart shells
 }
 
 impl Action {
@@ -232,9 +257,45 @@ impl Action {
         get_current_dir: Box<dyn Fn() -> PathBuf>,
     ) -> Result<Vec<Action>, String> {
         match cli_action {
-            CliAction::Write { bytes } => Ok(vec![Action::Write(bytes)]),
-            CliAction::WriteChars { chars } => Ok(vec![Action::WriteChars(chars)]),
-            CliAction::Resize { resize, direction } => Ok(vec![Action::Resize(resize, direction)]),
+            CliAction::Write {
+                bytes,
+                target_pane,
+                target_tab,
+                all_matching,
+            } => match target_pane {
+                Some(target_pane) => Ok(vec![Action::WriteToPane(
+                    bytes,
+                    target_pane,
+                    target_tab,
+                    all_matching,
+                )]),
+                None => Ok(vec![Action::Write(bytes)]),
+            },
+            CliAction::WriteChars {
+                chars,
+                target_pane,
+                target_tab,
+                all_matching,
+            } => match target_pane {
+                Some(target_pane) => Ok(vec![Action::WriteToPane(
+                    chars.into_bytes(),
+                    target_pane,
+                    target_tab,
+                    all_matching,
+                )]),
+                None => Ok(vec![Action::WriteChars(chars)]),
+            },
+            CliAction::Resize {
+                resize,
+                direction,
+                include_fixed,
+            } => {
+                if include_fixed {
+                    Ok(vec![Action::ResizeIncludingFixed(resize, direction)])
+                } else {
+                    Ok(vec![Action::Resize(resize, direction)])
+                }
+            },
             CliAction::FocusNextPane => Ok(vec![Action::FocusNextPane]),
             CliAction::FocusPreviousPane => Ok(vec![Action::FocusPreviousPane]),
             CliAction::MoveFocus { direction } => Ok(vec![Action::MoveFocus(direction)]),
@@ -255,6 +316,7 @@ impl Action {
             CliAction::HalfPageScrollUp => Ok(vec![Action::HalfPageScrollUp]),
             CliAction::HalfPageScrollDown => Ok(vec![Action::HalfPageScrollDown]),
             CliAction::ToggleFullscreen => Ok(vec![Action::ToggleFocusFullscreen]),
+            CliAction::TogglePaneReadOnly => Ok(vec![Action::TogglePaneReadOnly]),
             CliAction::TogglePaneFrames => Ok(vec![Action::TogglePaneFrames]),
             CliAction::ToggleActiveSyncTab => Ok(vec![Action::ToggleActiveSyncTab]),
             CliAction::NewPane {
@@ -265,6 +327,9 @@ impl Action {
                 name,
                 close_on_exit,
                 start_suspended,
+                target_pane,
+                any_tab,
+                force,
             } => {
                 if !command.is_empty() {
                     let mut command = command.clone();
@@ -283,7 +348,14 @@ impl Action {
                         hold_on_close,
                         hold_on_start,
                     };
-                    if floating {
+                    if let Some(target_pane) = target_pane {
+                        Ok(vec![Action::RunInPane(
+                            target_pane,
+                            run_command_action,
+                            any_tab,
+                            force,
+                        )])
+                    } else if floating {
                         Ok(vec![Action::NewFloatingPane(
                             Some(run_command_action),
                             name,
@@ -348,19 +420,25 @@ impl Action {
                 Action::TabNameInput(name.as_bytes().to_vec()),
             ]),
             CliAction::UndoRenameTab => Ok(vec![Action::UndoRenameTab]),
-            CliAction::NewTab { name, layout, cwd } => {
+            CliAction::NewTab {
+                name,
+                layout,
+                cwd,
+                template,
+            } => {
                 let current_dir = get_current_dir();
                 let cwd = cwd
                     .map(|cwd| current_dir.join(cwd))
                     .or_else(|| Some(current_dir));
                 if let Some(layout_path) = layout {
-                    let (path_to_raw_layout, raw_layout, swap_layouts) =
+                    let (path_to_raw_layout, raw_layout, swap_layouts, _layout_sources) =
                         Layout::stringified_from_path_or_default(Some(&layout_path), None)
                             .map_err(|e| format!("Failed to load layout: {}", e))?;
-                    let layout = Layout::from_str(&raw_layout, path_to_raw_layout, swap_layouts.as_ref().map(|(f, p)| (f.as_str(), p.as_str())), cwd).map_err(|e| {
+                    let layout_source_name = path_to_raw_layout.clone();
+                    let layout = Layout::from_str(&raw_layout, path_to_raw_layout, swap_layouts.as_ref().map(|(f, p)| (f.as_str(), p.as_str())), cwd, &LayoutVariables::new()).map_err(|e| {
                         let stringified_error = match e {
                             ConfigError::KdlError(kdl_error) => {
-                                let error = kdl_error.add_src(layout_path.as_path().as_os_str().to_string_lossy().to_string(), String::from(raw_layout));
+                                let error = kdl_error.add_src(layout_source_name.clone(), String::from(raw_layout));
                                 let report: Report = error.into();
                                 format!("{:?}", report)
                             }
@@ -377,10 +455,11 @@ impl Action {
                                 };
                                 let kdl_error = KdlError {
                                     error_message,
-                                    src: Some(NamedSource::new(layout_path.as_path().as_os_str().to_string_lossy().to_string(), String::from(raw_layout))),
+                                    src: Some(NamedSource::new(layout_source_name.clone(), String::from(raw_layout))),
                                     offset: Some(kdl_error.span.offset()),
                                     len: Some(kdl_error.span.len()),
                                     help_message: None,
+                                    secondary_labels: vec![],
                                 };
                                 let report: Report = kdl_error.into();
                                 format!("{:?}", report)
@@ -393,17 +472,21 @@ impl Action {
                     if tabs.len() > 1 {
                         return Err(format!("Tab layout cannot itself have tabs"));
                     } else if !tabs.is_empty() {
-                        let swap_tiled_layouts = if layout.swap_tiled_layouts.is_empty() {
+                        // the swap layouts of the tab itself come before the ones of the layout
+                        let (swap_tiled_layouts, swap_floating_layouts) =
+                            layout.swap_layouts_of_tab(0);
+                        let swap_tiled_layouts = if swap_tiled_layouts.is_empty() {
                             None
                         } else {
-                            Some(layout.swap_tiled_layouts.clone())
+                            Some(swap_tiled_layouts)
                         };
-                        let swap_floating_layouts = if layout.swap_floating_layouts.is_empty() {
+                        let swap_floating_layouts = if swap_floating_layouts.is_empty() {
                             None
                         } else {
-                            Some(layout.swap_floating_layouts.clone())
+                            Some(swap_floating_layouts)
                         };
-                        let (tab_name, layout, floating_panes_layout) =
+                        // pins only apply to the tabs of the session layout
+                        let (tab_name, layout, floating_panes_layout, ..) =
                             tabs.drain(..).next().unwrap();
                         let name = tab_name.or(name);
                         Ok(vec![Action::NewTab(
@@ -412,6 +495,7 @@ impl Action {
                             swap_tiled_layouts,
                             swap_floating_layouts,
                             name,
+                            None,
                         )])
                     } else {
                         let swap_tiled_layouts = if layout.swap_tiled_layouts.is_empty() {
@@ -431,14 +515,30 @@ impl Action {
                             swap_tiled_layouts,
                             swap_floating_layouts,
                             name,
+                            None,
                         )])
                     }
                 } else {
-                    Ok(vec![Action::NewTab(None, vec![], None, None, name)])
+                    Ok(vec![Action::NewTab(
+                        None,
+                        vec![],
+                        None,
+                        None,
+                        name,
+                        template,
+                    )])
                 }
             },
-            CliAction::PreviousSwapLayout => Ok(vec![Action::PreviousSwapLayout]),
-            CliAction::NextSwapLayout => Ok(vec![Action::NextSwapLayout]),
+            CliAction::PreviousSwapLayout { tag } => Ok(vec![Action::PreviousSwapLayout(tag)]),
+            CliAction::NextSwapLayout { tag } => Ok(vec![Action::NextSwapLayout(tag)]),
+            CliAction::RotateTabLayout { counter_clockwise } => Ok(vec![Action::RotateTabLayout {
+                clockwise: !counter_clockwise,
+            }]),
+            CliAction::DumpLayoutDebug => Ok(vec![Action::DumpLayoutDebug]),
+            CliAction::FlushCommandQueue => Ok(vec![Action::FlushCommandQueue]),
+            CliAction::RerunPane { target_pane, force } => {
+                Ok(vec![Action::RerunPane(target_pane, force)])
+            },
         }
     }
 }
@@ -1,11 +1,17 @@
 //! Trigger a command
 use crate::data::Direction;
+use crate::input::layout::SplitDirection;
+use directories_next::BaseDirs;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 #[derive(Debug, Clone)]
 pub enum TerminalAction {
-    OpenFile(PathBuf, Option<usize>), // path to file and optional line_number
+    // paths to files (opened as splits of the editor in the optional direction, when there are
+    // more than one) and optional line_number of the first one
+    OpenFile(Vec<PathBuf>, Option<usize>, Option<SplitDirection>),
     RunCommand(RunCommand),
 }
 
@@ -21,6 +27,101 @@ pub struct RunCommand {
     pub hold_on_close: bool,
     #[serde(default)]
     pub hold_on_start: bool,
+    #[serde(default)]
+    pub strict_placeholders: bool, // unknown placeholders are an error rather than left as is
+    #[serde(default)]
+    pub depends_on: Option<PaneDependency>,
+    #[serde(default)]
+    pub encoding: Option<PaneEncoding>, // None for UTF-8 output, which is rendered as is
+    #[serde(default)]
+    pub env: BTreeMap<String, String>, // added to the environment the command inherits
+}
+
+/// Another pane of the same tab a layout command pane waits for before running its command
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PaneDependency {
+    pub pane_name: String,
+    /// What to look for in the output of the pane, the command also runs once the pane's own
+    /// command exits successfully
+    pub ready_when: Option<ReadyCondition>,
+    pub timeout_secs: Option<u64>, // run the command anyway after this long
+}
+
+/// A program the command panes of a layout are run under (eg. `devcontainer exec --`), it is
+/// spawned in the pane's cwd and gets the pane's command and its args as its last args, what cwd
+/// the command then runs in is up to the wrapper
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CommandWrapper {
+    pub command: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl CommandWrapper {
+    pub fn wrap(&self, run_command: RunCommand) -> RunCommand {
+        let mut args = self.args.clone();
+        args.push(run_command.command.to_string_lossy().to_string());
+        args.extend(run_command.args.iter().cloned());
+        RunCommand {
+            command: self.command.clone(),
+            args,
+            ..run_command
+        }
+    }
+}
+
+/// The encoding a command writes its output in, the output is transcoded to UTF-8 before it is
+/// rendered while what is typed into the pane is sent to the command as is
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum PaneEncoding {
+    Utf8,
+    Latin1,
+    ShiftJis,
+}
+
+impl FromStr for PaneEncoding {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "utf8" => Ok(PaneEncoding::Utf8),
+            "latin1" => Ok(PaneEncoding::Latin1),
+            "shift-jis" => Ok(PaneEncoding::ShiftJis),
+            _ => Err("encoding must be one of \"utf8\", \"latin1\" or \"shift-jis\"".into()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ReadyCondition {
+    Contains(String),
+    Matches(String), // a regex
+}
+
+impl RunCommand {
+    /// Whether the command runs as soon as its pane is created, rather than once the user starts
+    /// it or once the pane it depends on is ready
+    pub fn starts_without_waiting(&self) -> bool {
+        !self.hold_on_start && self.depends_on.is_none()
+    }
+    /// Replaces the placeholders in the command and its args with their values, see
+    /// [`expand_placeholders`]
+    pub fn expand_placeholders(&mut self, values: &PlaceholderValues) -> Result<(), String> {
+        let command = expand_placeholders(
+            &self.command.to_string_lossy(),
+            values,
+            self.strict_placeholders,
+        )?;
+        let args = self
+            .args
+            .iter()
+            .map(|arg| expand_placeholders(arg, values, self.strict_placeholders))
+            .collect::<Result<Vec<String>, String>>()?;
+        self.command = PathBuf::from(command);
+        self.args = args;
+        // the placeholders are expanded, what is left (eg. escaped braces) is to be taken as is
+        self.strict_placeholders = false;
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for RunCommand {
@@ -64,6 +165,187 @@ impl From<RunCommandAction> for RunCommand {
             cwd: action.cwd,
             hold_on_close: action.hold_on_close,
             hold_on_start: action.hold_on_start,
+            strict_placeholders: false,
+            depends_on: None,
+            encoding: None,
+            env: BTreeMap::new(),
         }
     }
 }
+
+/// The values of the placeholders that can be used in the command and args of a [`RunCommand`],
+/// some of them (eg. the session name) are only known once the pane is spawned
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlaceholderValues {
+    pub pane_name: Option<String>,
+    pub cwd: Option<PathBuf>,
+    pub tab_name: Option<String>,
+    pub session_name: Option<String>,
+}
+
+impl PlaceholderValues {
+    fn get(&self, placeholder: &str) -> Option<String> {
+        // missing values of known placeholders are expanded to an empty string
+        let value = match placeholder {
+            "pane_name" => self.pane_name.clone(),
+            "cwd" => self.cwd.as_ref().map(|c| c.to_string_lossy().to_string()),
+            "tab_name" => self.tab_name.clone(),
+            "session_name" => self.session_name.clone(),
+            _ => return None,
+        };
+        Some(value.unwrap_or_default())
+    }
+}
+
+/// Replaces the `{pane_name}`, `{cwd}`, `{tab_name}` and `{session_name}` placeholders in `text`
+/// with their values, `{{` is a literal brace.
+///
+/// Commands legitimately contain braces, so anything else in braces is left as is unless `strict`
+/// is set, in which case unknown placeholders are an error.
+pub fn expand_placeholders(
+    text: &str,
+    values: &PlaceholderValues,
+    strict: bool,
+) -> Result<String, String> {
+    expand_placeholders_with(text, strict, |placeholder| values.get(placeholder))
+}
+
+/// Replaces the `{placeholder}`s in `text` for which `value_of` returns a value, see
+/// [`expand_placeholders`]
+pub fn expand_placeholders_with(
+    text: &str,
+    strict: bool,
+    value_of: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(brace_index) = rest.find('{') {
+        expanded.push_str(&rest[..brace_index]);
+        rest = &rest[brace_index..];
+        if rest.starts_with("{{") {
+            expanded.push('{');
+            rest = &rest[2..];
+            continue;
+        }
+        let placeholder = rest[1..]
+            .find('}')
+            .map(|end_index| &rest[1..end_index + 1])
+            .filter(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        match placeholder {
+            Some(placeholder) => {
+                match value_of(placeholder) {
+                    Some(value) => expanded.push_str(&value),
+                    None if strict => {
+                        return Err(format!(
+                            "Unknown placeholder {{{}}} in: {}",
+                            placeholder, text
+                        ))
+                    },
+                    None => expanded.push_str(&rest[..placeholder.len() + 2]),
+                }
+                rest = &rest[placeholder.len() + 2..];
+            },
+            None => {
+                expanded.push('{');
+                rest = &rest[1..];
+            },
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Replaces the `$VAR` and `${VAR}` references in `text` with the values of the variables in the
+/// environment of zellij, `$$` is a literal dollar. Fails with the name of the first variable
+/// that is not set.
+pub fn expand_env_vars(text: &str) -> Result<String, String> {
+    expand_env_vars_with(text, |variable| std::env::var(variable).ok())
+}
+
+/// Replaces the `$VAR` and `${VAR}` references in `text` with the values `value_of` returns, see
+/// [`expand_env_vars`]. A `$` that does not start a variable name is taken as is.
+pub fn expand_env_vars_with(
+    text: &str,
+    value_of: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(dollar_index) = rest.find('$') {
+        expanded.push_str(&rest[..dollar_index]);
+        rest = &rest[dollar_index + 1..];
+        if let Some(after_dollar) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = after_dollar;
+            continue;
+        }
+        let (variable, reference_len) = match rest.strip_prefix('{') {
+            Some(in_braces) => match in_braces.find('}') {
+                Some(end_index) => (&in_braces[..end_index], end_index + 2),
+                None => ("", 0),
+            },
+            None => {
+                let name_len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+                (&rest[..name_len], name_len)
+            },
+        };
+        let is_variable_name = variable.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && variable.chars().all(is_name_char);
+        if !is_variable_name {
+            expanded.push('$');
+            continue;
+        }
+        match value_of(variable) {
+            Some(value) => expanded.push_str(&value),
+            None => return Err(variable.to_owned()),
+        }
+        rest = &rest[reference_len..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Replaces a leading `~` in `path` with the home directory of the user
+pub fn expand_home(path: &str) -> PathBuf {
+    let home_dir = BaseDirs::new().map(|base_dirs| base_dirs.home_dir().to_path_buf());
+    expand_home_with(path, home_dir.as_deref())
+}
+
+/// Replaces a leading `~` in `path` with `home_dir`, see [`expand_home`]
+pub fn expand_home_with(path: &str, home_dir: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix('~'), home_dir) {
+        (Some(""), Some(home_dir)) => home_dir.to_path_buf(),
+        (Some(in_home_dir), Some(home_dir)) if in_home_dir.starts_with('/') => {
+            home_dir.join(&in_home_dir[1..])
+        },
+        _ => PathBuf::from(path),
+    }
+}
+
+/// `path` with `home_dir` at its start written as `~`, the reverse of [`expand_home_with`]
+pub fn shorten_home_with(path: &Path, home_dir: Option<&Path>) -> String {
+    match home_dir.and_then(|home_dir| path.strip_prefix(home_dir).ok()) {
+        Some(in_home_dir) if in_home_dir.as_os_str().is_empty() => String::from("~"),
+        Some(in_home_dir) => format!("~/{}", in_home_dir.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// Whether `command` can be run: found in `cwd` (or as it is without one), or in one of the
+/// directories of the PATH
+pub fn command_exists(command: &Path, cwd: Option<&Path>) -> bool {
+    let full_command = match cwd {
+        Some(cwd) => cwd.join(command),
+        None => command.to_path_buf(),
+    };
+    if full_command.is_file() {
+        return true;
+    }
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|path| path.join(command).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+#[path = "./unit/command_test.rs"]
+mod command_test;
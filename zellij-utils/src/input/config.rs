@@ -2,17 +2,19 @@ use crate::data::Palette;
 use miette::{Diagnostic, LabeledSpan, NamedSource, SourceCode};
 use std::fs::File;
 use std::io::{self, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 use std::convert::TryFrom;
 
 use super::keybinds::Keybinds;
+use super::layout_apply_error::LayoutApplyError;
 use super::options::Options;
 use super::plugins::{PluginsConfig, PluginsConfigError};
 use super::theme::{Themes, UiConfig};
 use crate::cli::{CliArgs, Command};
 use crate::envs::EnvironmentVariables;
+use crate::kdl::LayoutTemplates;
 use crate::setup;
 
 const DEFAULT_CONFIG_FILE_NAME: &str = "config.kdl";
@@ -28,6 +30,8 @@ pub struct Config {
     pub plugins: PluginsConfig,
     pub ui: UiConfig,
     pub env: EnvironmentVariables,
+    /// Pane and tab templates every layout can use as if it defined them
+    pub layout_templates: LayoutTemplates,
 }
 
 #[derive(Error, Debug)]
@@ -37,6 +41,7 @@ pub struct KdlError {
     pub offset: Option<usize>,
     pub len: Option<usize>,
     pub help_message: Option<String>,
+    pub secondary_labels: Vec<(String, usize, usize)>, // (label, offset, len)
 }
 
 impl KdlError {
@@ -69,7 +74,11 @@ impl Diagnostic for KdlError {
     fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
         if let (Some(offset), Some(len)) = (self.offset, self.len) {
             let label = LabeledSpan::new(Some(self.error_message.clone()), offset, len);
-            Some(Box::new(std::iter::once(label)))
+            let secondary_labels = self
+                .secondary_labels
+                .iter()
+                .map(|(label, offset, len)| LabeledSpan::new(Some(label.clone()), *offset, *len));
+            Some(Box::new(std::iter::once(label).chain(secondary_labels)))
         } else {
             None
         }
@@ -99,6 +108,21 @@ pub enum ConfigError {
     PluginsError(#[from] PluginsConfigError),
     #[error("{0}")]
     ConversionError(#[from] ConversionError),
+    // A layout exceeding one of the layout limits, which are named in the message
+    #[error("{0}")]
+    LayoutLimitExceeded(String),
+    // A layout in the YAML format of zellij versions before 0.32.0, which can be converted
+    #[error("{} is a layout in the YAML format of zellij versions before 0.32.0, which is no longer supported.\nConvert it to a KDL layout with: zellij convert-layout {}\n(or open it with --auto-convert to print it converted)", .0.display(), .0.display())]
+    YamlLayout(PathBuf),
+    // A layout whose panes cannot be positioned, naming the pane at fault
+    #[error("{0}")]
+    LayoutApply(#[from] LayoutApplyError),
+    // An error found in one of several layouts opened together, naming that layout
+    #[error("In layout {}: {1}", .0.display())]
+    InLayoutFile(PathBuf, Box<ConfigError>),
+    // A new tab asked to be opened from a tab_template the layout does not have
+    #[error("There is no tab_template named \"{0}\" in the layout")]
+    UnknownTabTemplate(String),
 }
 
 impl ConfigError {
@@ -109,6 +133,7 @@ impl ConfigError {
             offset: Some(offset),
             len: Some(len),
             help_message: None,
+            secondary_labels: vec![],
         })
     }
     pub fn new_layout_kdl_error(error_message: String, offset: usize, len: usize) -> Self {
@@ -118,8 +143,34 @@ impl ConfigError {
             offset: Some(offset),
             len: Some(len),
             help_message: Some(format!("For more information, please see our layout guide: https://zellij.dev/documentation/creating-a-layout.html")),
+            secondary_labels: vec![],
         })
     }
+    /// Names the layout file the error was found in, unless the error already shows it
+    pub fn in_layout_file(self, layout_path: &Path) -> Self {
+        match self {
+            ConfigError::KdlError(KdlError { src: Some(_), .. })
+            | ConfigError::IoPath(..)
+            | ConfigError::YamlLayout(..) => self,
+            // kept a KdlError so that it is still reported with its labels
+            ConfigError::KdlError(mut kdl_error) => {
+                kdl_error.error_message = format!(
+                    "In layout {}: {}",
+                    layout_path.display(),
+                    kdl_error.error_message
+                );
+                ConfigError::KdlError(kdl_error)
+            },
+            e => ConfigError::InLayoutFile(layout_path.to_path_buf(), Box::new(e)),
+        }
+    }
+    /// Points at another part of the source, eg. where a duplicate was first defined
+    pub fn with_secondary_label(mut self, label: String, offset: usize, len: usize) -> Self {
+        if let ConfigError::KdlError(kdl_error) = &mut self {
+            kdl_error.secondary_labels.push((label, offset, len));
+        }
+        self
+    }
 }
 
 #[derive(Debug, Error)]
@@ -210,6 +261,7 @@ impl Config {
                             offset: Some(kdl_error.span.offset()),
                             len: Some(kdl_error.span.len()),
                             help_message: None,
+                            secondary_labels: vec![],
                         };
                         Err(ConfigError::KdlError(kdl_error))
                     },
@@ -231,8 +283,8 @@ impl Config {
 mod config_test {
     use super::*;
     use crate::data::{InputMode, Palette, PaletteColor, PluginTag};
-    use crate::input::layout::RunPluginLocation;
-    use crate::input::options::{Clipboard, OnForceClose};
+    use crate::input::layout::{FocusPolicy, RunPluginLocation};
+    use crate::input::options::{Clipboard, ConfirmLayoutCommands, OnForceClose, OnLayoutFailure};
     use crate::input::plugins::{PluginConfig, PluginType, PluginsConfig};
     use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
     use std::collections::HashMap;
@@ -317,6 +369,13 @@ mod config_test {
             scrollback_editor "/path/to/my/scrollback-editor"
             session_name "my awesome session"
             attach_to_session true
+            strider_pane_width "25%"
+            strider_stack_below_width 100
+            command_spawn_interval_ms 500
+            default_focus "largest"
+            on_layout_failure "keep_partial"
+            confirm_layout_commands "always"
+            resize_relayout_interval_ms 100
         "#;
         let config = Config::from_kdl(config_contents, None).unwrap();
         assert_eq!(
@@ -409,6 +468,41 @@ mod config_test {
             Some(true),
             "Option set in config"
         );
+        assert_eq!(
+            config.options.strider_pane_width,
+            Some(crate::input::layout::SplitSize::Percent(25)),
+            "Option set in config"
+        );
+        assert_eq!(
+            config.options.strider_stack_below_width,
+            Some(100),
+            "Option set in config"
+        );
+        assert_eq!(
+            config.options.command_spawn_interval_ms,
+            Some(500),
+            "Option set in config"
+        );
+        assert_eq!(
+            config.options.default_focus,
+            Some(FocusPolicy::Largest),
+            "Option set in config"
+        );
+        assert_eq!(
+            config.options.on_layout_failure,
+            Some(OnLayoutFailure::KeepPartial),
+            "Option set in config"
+        );
+        assert_eq!(
+            config.options.confirm_layout_commands,
+            Some(ConfirmLayoutCommands::Always),
+            "Option set in config"
+        );
+        assert_eq!(
+            config.options.resize_relayout_interval_ms,
+            Some(100),
+            "Option set in config"
+        );
     }
 
     #[test]
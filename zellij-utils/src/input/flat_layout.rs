@@ -0,0 +1,202 @@
+//! Layouts flattened to the panes they end up with and the space each of them takes, for tools
+//! outside of zellij (eg. layout visualizers) that should not have to redo the layout math.
+use crate::{
+    input::{
+        layout::{floating_stack_geom, FloatingPaneLayout, Layout, Run, TiledPaneLayout},
+        layout_apply_error::LayoutApplyError,
+        options::PaneTitleFormats,
+    },
+    pane_size::{Dimension, PaneGeom, Size},
+};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// The size of the space a [`TiledPaneLayout`] is flattened in when converted without one, it
+/// matters for panes with a fixed size
+pub const DEFAULT_FLAT_LAYOUT_SIZE: Size = Size {
+    rows: 50,
+    cols: 200,
+};
+
+/// The panes of a layout tab, tiled panes first in the order they appear in the layout and then
+/// floating panes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlatLayout {
+    pub tab_name: Option<String>,
+    pub panes: Vec<FlatPane>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlatPane {
+    /// The child indices leading to a tiled pane, the index among the floating panes of a
+    /// floating one
+    pub path: Vec<usize>,
+    pub name: Option<String>,
+    /// What the pane runs, as it would be shown in the pane's title
+    pub run_summary: Option<String>,
+    /// x, y, width and height as fractions (0.0 to 1.0) of the viewport
+    pub geom_percent: (f32, f32, f32, f32),
+    pub stacked: bool,
+    pub borderless: bool,
+    pub floating: bool,
+}
+
+impl FlatLayout {
+    pub fn new(
+        tiled_panes: &TiledPaneLayout,
+        floating_panes: &[FloatingPaneLayout],
+        viewport: &PaneGeom,
+    ) -> Result<Self, LayoutApplyError> {
+        FlatLayout::in_tab_area(tiled_panes, floating_panes, viewport, viewport)
+    }
+    fn in_tab_area(
+        tiled_panes: &TiledPaneLayout,
+        floating_panes: &[FloatingPaneLayout],
+        tab_area: &PaneGeom,
+        viewport: &PaneGeom,
+    ) -> Result<Self, LayoutApplyError> {
+        let mut panes: Vec<FlatPane> = tiled_panes
+            .position_panes_in_space(tab_area, None)?
+            .into_iter()
+            .zip(tiled_panes.extract_pane_paths())
+            .map(|((pane, geom), path)| FlatPane {
+                path,
+                name: pane.name.clone(),
+                run_summary: run_summary(&pane.run),
+                geom_percent: geom_fractions(&geom, viewport),
+                stacked: geom.is_stacked,
+                borderless: pane.borderless,
+                floating: false,
+            })
+            .collect();
+        for (i, floating_pane) in floating_panes.iter().enumerate() {
+            // the panes of a stack share the space the first of them would have had
+            let first_index = floating_pane
+                .stack
+                .and_then(|stack| {
+                    floating_panes
+                        .iter()
+                        .position(|pane| pane.stack == Some(stack))
+                })
+                .unwrap_or(i);
+            let stack_geom =
+                floating_pane_geom(&floating_panes[first_index], tab_area, first_index);
+            let geom = floating_stack_geom(floating_panes, i, stack_geom);
+            panes.push(FlatPane {
+                path: vec![i],
+                name: floating_pane.name.clone(),
+                run_summary: run_summary(&floating_pane.run),
+                geom_percent: geom_fractions(&geom, viewport),
+                stacked: floating_pane.stack.is_some(),
+                borderless: false,
+                floating: true,
+            });
+        }
+        Ok(FlatLayout {
+            tab_name: None,
+            panes,
+        })
+    }
+}
+
+impl TryFrom<&TiledPaneLayout> for FlatLayout {
+    type Error = LayoutApplyError;
+    fn try_from(tiled_panes: &TiledPaneLayout) -> Result<Self, Self::Error> {
+        FlatLayout::new(tiled_panes, &[], &PaneGeom::from(&DEFAULT_FLAT_LAYOUT_SIZE))
+    }
+}
+
+impl Layout {
+    /// Flattens each tab of the layout (or the new tab template of a layout without tabs) in
+    /// `viewport`, the tabs are placed in the space left by the side panel if there is one
+    pub fn flatten(&self, viewport: &PaneGeom) -> Result<Vec<FlatLayout>, LayoutApplyError> {
+        let tab_area = match &self.side_panel {
+            Some(side_panel) => {
+                let (_panel_geom, tab_area) = side_panel.split_space(Size::from(viewport))?;
+                let mut tab_area = PaneGeom::from(&tab_area);
+                tab_area.x = viewport.x;
+                tab_area.y = viewport.y;
+                tab_area
+            },
+            None => *viewport,
+        };
+        if self.tabs.is_empty() {
+            let (tiled_panes, floating_panes) = self.new_tab();
+            return Ok(vec![FlatLayout::in_tab_area(
+                &tiled_panes,
+                &floating_panes,
+                &tab_area,
+                viewport,
+            )?]);
+        }
+        self.tabs
+            .iter()
+            .map(|(tab_name, tiled_panes, floating_panes, ..)| {
+                FlatLayout::in_tab_area(tiled_panes, floating_panes, &tab_area, viewport).map(
+                    |flat_layout| FlatLayout {
+                        tab_name: tab_name.clone(),
+                        ..flat_layout
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+fn run_summary(run: &Option<Run>) -> Option<String> {
+    run.as_ref()
+        .map(|run| run.pane_title(&PaneTitleFormats::default()))
+}
+
+fn geom_fractions(geom: &PaneGeom, viewport: &PaneGeom) -> (f32, f32, f32, f32) {
+    let cols = viewport.cols.as_usize().max(1) as f32;
+    let rows = viewport.rows.as_usize().max(1) as f32;
+    (
+        geom.x.saturating_sub(viewport.x) as f32 / cols,
+        geom.y.saturating_sub(viewport.y) as f32 / rows,
+        geom.cols.as_usize() as f32 / cols,
+        geom.rows.as_usize() as f32 / rows,
+    )
+}
+
+// like the server places floating panes when it has room for them: half the size of the space,
+// in its middle, each one a little lower and to the right of the previous one
+fn floating_pane_geom(
+    floating_pane: &FloatingPaneLayout,
+    space: &PaneGeom,
+    index: usize,
+) -> PaneGeom {
+    let space_cols = space.cols.as_usize();
+    let space_rows = space.rows.as_usize();
+    let offset = index * 2;
+    let mut x = (space_cols as f64 / 4.0).round() as usize + offset;
+    let mut y = (space_rows as f64 / 4.0).round() as usize + offset;
+    let (cols, rows) =
+        floating_pane.resolve_size((space_cols, space_rows), (space_cols / 2, space_rows / 2));
+    if let Some(floating_x) = &floating_pane.x {
+        x = floating_x.to_coordinate(space_cols, cols);
+    }
+    if let Some(floating_y) = &floating_pane.y {
+        y = floating_y.to_coordinate(space_rows, rows);
+    }
+    let (x, y) = match floating_pane.anchor {
+        Some(anchor) => anchor.position(
+            floating_pane.x.as_ref(),
+            floating_pane.y.as_ref(),
+            (space_cols, space_rows),
+            (cols, rows),
+        ),
+        None => (x.min(space_cols - cols), y.min(space_rows - rows)),
+    };
+    PaneGeom {
+        x: space.x + x,
+        y: space.y + y,
+        cols: Dimension::fixed(cols),
+        rows: Dimension::fixed(rows),
+        is_stacked: false,
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/flat_layout_test.rs"]
+mod flat_layout_test;
@@ -11,19 +11,37 @@
 use crate::{
     data::Direction,
     input::{
-        command::RunCommand,
+        command::{
+            expand_placeholders_with, shorten_home_with, CommandWrapper, PaneDependency,
+            PaneEncoding, RunCommand,
+        },
         config::{Config, ConfigError},
+        layout_apply_error::{LayoutApplyError, LayoutPanePath},
+        layout_stats::LayoutLoadStats,
+        layout_trust::layout_content_hash,
+        layout_variables::{
+            resolve_layout_variables, substitute_layout_variables, LayoutVariables,
+        },
+        options::PaneTitleFormats,
     },
-    pane_size::{Dimension, PaneGeom},
+    kdl::LayoutTemplates,
+    pane_size::{Dimension, PaneGeom, Size},
     setup,
 };
 
+use directories_next::BaseDirs;
+use lazy_static::lazy_static;
 use std::str::FromStr;
 
+#[cfg(not(target_family = "wasm"))]
+use super::layout_url::{layout_url, stringified_from_url};
 use super::plugins::{PluginTag, PluginsConfigError};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap};
 use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
 use std::vec::Vec;
 use std::{
     fmt,
@@ -34,9 +52,72 @@ use std::{fs::File, io::prelude::*};
 use url::Url;
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+pub enum TabPin {
+    First,
+    Last,
+}
+
+impl TabPin {
+    /// Where a tab with this pin is sorted relative to the others: pinned-first tabs come before
+    /// unpinned ones, which come before pinned-last tabs
+    pub fn rank(pin: Option<TabPin>) -> usize {
+        match pin {
+            Some(TabPin::First) => 0,
+            None => 1,
+            Some(TabPin::Last) => 2,
+        }
+    }
+}
+
+/// Which pane of a layout gets the focus when none of its panes is marked with `focus=true`
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy)]
+pub enum FocusPolicy {
+    First,
+    /// The last of the most nested panes, the default since this is what layouts always focused
+    Deepest,
+    Largest,
+    Last,
+}
+
+impl Default for FocusPolicy {
+    fn default() -> Self {
+        FocusPolicy::Deepest
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy)]
 pub enum SplitDirection {
     Horizontal,
     Vertical,
+    /// Whichever of the two the space being split has more room for, see
+    /// [`SplitDirection::resolve`]. Anything splitting panes without a space to resolve it in
+    /// splits them horizontally, as by default
+    Auto,
+}
+
+// the smallest pane an `Auto` split leaves room for along each dimension, as the server's
+// minimum terminal size
+const AUTO_SPLIT_MIN_PANE_COLS: usize = 5;
+const AUTO_SPLIT_MIN_PANE_ROWS: usize = 5;
+
+impl SplitDirection {
+    /// The direction to split `space` in: `Auto` splits along the dimension of `space` that fits
+    /// more panes of the minimum size (vertically if its columns do, horizontally otherwise, so
+    /// that the same space always resolves the same way). The other directions are as they are.
+    pub fn resolve(self, space: &PaneGeom) -> SplitDirection {
+        match self {
+            SplitDirection::Auto => {
+                let cols = space.cols.as_usize() * AUTO_SPLIT_MIN_PANE_ROWS;
+                let rows = space.rows.as_usize() * AUTO_SPLIT_MIN_PANE_COLS;
+                if cols > rows {
+                    SplitDirection::Vertical
+                } else {
+                    SplitDirection::Horizontal
+                }
+            },
+            direction => direction,
+        }
+    }
 }
 
 impl Not for SplitDirection {
@@ -46,6 +127,7 @@ impl Not for SplitDirection {
         match self {
             SplitDirection::Horizontal => SplitDirection::Vertical,
             SplitDirection::Vertical => SplitDirection::Horizontal,
+            SplitDirection::Auto => SplitDirection::Auto,
         }
     }
 }
@@ -59,12 +141,14 @@ impl From<Direction> for SplitDirection {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SplitSize {
     #[serde(alias = "percent")]
     Percent(usize), // 1 to 100
     #[serde(alias = "fixed")]
     Fixed(usize), // An absolute number of columns or rows
+    #[serde(alias = "weight")]
+    Weight(usize), // A share of the space the fixed and percent sized siblings leave
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -73,7 +157,9 @@ pub enum Run {
     Plugin(RunPlugin),
     #[serde(rename = "command")]
     Command(RunCommand),
-    EditFile(PathBuf, Option<usize>), // TODO: merge this with TerminalAction::OpenFile
+    // the files to edit (never empty), the line number the first one opens at and how the editor
+    // splits between them
+    EditFile(Vec<PathBuf>, Option<usize>, Option<SplitDirection>), // TODO: merge this with TerminalAction::OpenFile
     Cwd(PathBuf),
 }
 
@@ -82,7 +168,6 @@ impl Run {
         // This method is necessary to merge between pane_templates and their consumers
         // TODO: reconsider the way we parse command/edit/plugin pane_templates from layouts to prevent this
         // madness
-        // TODO: handle Plugin variants once there's a need
         match (base, other) {
             (Some(Run::Command(base_run_command)), Some(Run::Command(other_run_command))) => {
                 let mut merged = other_run_command.clone();
@@ -92,6 +177,7 @@ impl Run {
                 if merged.args.is_empty() && !base_run_command.args.is_empty() {
                     merged.args = base_run_command.args.clone();
                 }
+                merged.env = merge_env(&base_run_command.env, &other_run_command.env);
                 Some(Run::Command(merged))
             },
             (Some(Run::Command(base_run_command)), Some(Run::Cwd(other_cwd))) => {
@@ -106,15 +192,37 @@ impl Run {
                 }
                 Some(Run::Command(merged))
             },
-            (
-                Some(Run::Command(base_run_command)),
-                Some(Run::EditFile(file_to_edit, line_number)),
-            ) => match &base_run_command.cwd {
-                Some(cwd) => Some(Run::EditFile(cwd.join(&file_to_edit), *line_number)),
-                None => Some(Run::EditFile(file_to_edit.clone(), *line_number)),
+            // the files of the consumer replace the ones of the template
+            (Some(Run::Command(base_run_command)), Some(Run::EditFile(..))) => {
+                let mut merged = other.clone();
+                if let (Some(merged), Some(cwd)) = (merged.as_mut(), &base_run_command.cwd) {
+                    merged.add_cwd(cwd);
+                }
+                merged
             },
-            (Some(Run::Cwd(cwd)), Some(Run::EditFile(file_to_edit, line_number))) => {
-                Some(Run::EditFile(cwd.join(&file_to_edit), *line_number))
+            (Some(Run::Cwd(cwd)), Some(Run::EditFile(..))) => {
+                let mut merged = other.clone();
+                if let Some(merged) = merged.as_mut() {
+                    merged.add_cwd(cwd);
+                }
+                merged
+            },
+            // the location of the consumer replaces the one of the template, which fills in the
+            // rest
+            (Some(Run::Plugin(base_run_plugin)), Some(Run::Plugin(other_run_plugin))) => {
+                let mut merged = other_run_plugin.clone();
+                merged._allow_exec_host_cmd |= base_run_plugin._allow_exec_host_cmd;
+                Some(Run::Plugin(merged))
+            },
+            (Some(Run::Plugin(base_run_plugin)), Some(Run::Cwd(other_cwd))) => {
+                let mut merged = Run::Plugin(base_run_plugin.clone());
+                merged.add_cwd(other_cwd);
+                Some(merged)
+            },
+            (Some(Run::Cwd(base_cwd)), Some(Run::Plugin(other_run_plugin))) => {
+                let mut merged = Run::Plugin(other_run_plugin.clone());
+                merged.add_cwd(base_cwd);
+                Some(merged)
             },
             (Some(_base), Some(other)) => Some(other.clone()),
             (Some(base), _) => Some(base.clone()),
@@ -132,13 +240,41 @@ impl Run {
                     run_command.cwd = Some(cwd.clone());
                 },
             },
-            Run::EditFile(path_to_file, _line_number) => {
-                *path_to_file = cwd.join(&path_to_file);
+            Run::EditFile(paths_to_files, _line_number, _editor_splits) => {
+                for path_to_file in paths_to_files.iter_mut() {
+                    *path_to_file = cwd.join(&path_to_file);
+                }
             },
             Run::Cwd(path) => {
                 *path = cwd.join(&path);
             },
-            _ => {}, // plugins aren't yet supported
+            Run::Plugin(run_plugin) => match &mut run_plugin.location {
+                // absolute paths are left as they are by the join
+                RunPluginLocation::File(path) => {
+                    *path = cwd.join(&path);
+                },
+                RunPluginLocation::Zellij(_) => {},
+            },
+        }
+    }
+    /// Moves the paths this runs in or with from under `old_root` to under `new_root`, see
+    /// [`rebased_path`]
+    pub fn rebase_paths(&mut self, old_root: &Path, new_root: &Path) {
+        let paths: Vec<&mut PathBuf> = match self {
+            Run::Command(run_command) => run_command.cwd.as_mut().into_iter().collect(),
+            Run::EditFile(paths_to_files, _line_number, _editor_splits) => {
+                paths_to_files.iter_mut().collect()
+            },
+            Run::Cwd(path) => vec![path],
+            Run::Plugin(run_plugin) => match &mut run_plugin.location {
+                RunPluginLocation::File(path) => vec![path],
+                RunPluginLocation::Zellij(_) => vec![],
+            },
+        };
+        for path in paths {
+            if let Some(rebased) = rebased_path(path, old_root, new_root) {
+                *path = rebased;
+            }
         }
     }
     pub fn add_args(&mut self, args: Option<Vec<String>>) {
@@ -170,6 +306,38 @@ impl Run {
             }
         }
     }
+    pub fn add_strict_placeholders(&mut self, strict_placeholders: Option<bool>) {
+        // overrides the strict_placeholders of a Run::Command if it is Some
+        if let Some(strict_placeholders) = strict_placeholders {
+            if let Run::Command(run_command) = self {
+                run_command.strict_placeholders = strict_placeholders;
+            }
+        }
+    }
+    pub fn add_encoding(&mut self, encoding: Option<PaneEncoding>) {
+        // overrides the encoding of a Run::Command if it is Some
+        if let Some(encoding) = encoding {
+            if let Run::Command(run_command) = self {
+                run_command.encoding = Some(encoding);
+            }
+        }
+    }
+    pub fn add_env(&mut self, env: Option<BTreeMap<String, String>>) {
+        // the variables of a Run::Command are overriden one by one by those that are Some
+        if let Some(env) = env {
+            if let Run::Command(run_command) = self {
+                run_command.env = merge_env(&run_command.env, &env);
+            }
+        }
+    }
+    pub fn add_depends_on(&mut self, depends_on: Option<PaneDependency>) {
+        // overrides the depends_on of a Run::Command if it is Some
+        if let Some(depends_on) = depends_on {
+            if let Run::Command(run_command) = self {
+                run_command.depends_on = Some(depends_on);
+            }
+        }
+    }
     pub fn is_same_category(first: &Option<Run>, second: &Option<Run>) -> bool {
         match (first, second) {
             (Some(Run::Plugin(..)), Some(Run::Plugin(..))) => true,
@@ -179,12 +347,115 @@ impl Run {
             _ => false,
         }
     }
+    /// Whether panes running these can take each other's place, everything running in a terminal
+    /// can while plugins can only take the place of other plugins
+    pub fn is_compatible(first: &Option<Run>, second: &Option<Run>) -> bool {
+        Run::is_terminal(first) == Run::is_terminal(second)
+    }
     pub fn is_terminal(run: &Option<Run>) -> bool {
         match run {
             Some(Run::Command(..)) | Some(Run::EditFile(..)) | Some(Run::Cwd(..)) | None => true,
             _ => false,
         }
     }
+    /// The title of a pane running this, this is the single place pane titles are derived from
+    /// what the pane runs so that they read the same wherever they are shown
+    pub fn pane_title(&self, formats: &PaneTitleFormats) -> String {
+        // everything we put in the title is also available unabridged, eg. `{path}` next to
+        // `{file}`, for formats that prefer it
+        let last_component = |path: &Path| {
+            path.file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string())
+        };
+        let (format, values): (&str, Vec<(&str, String)>) = match self {
+            Run::Command(run_command) => (
+                formats.command(),
+                vec![("command", run_command.to_string())],
+            ),
+            // the title is about the first of the files, where the line number is
+            Run::EditFile(paths, line_number, _editor_splits) => {
+                let path = paths.first().map(|p| p.as_path()).unwrap_or(Path::new(""));
+                (
+                    formats.edit(),
+                    vec![
+                        ("file", last_component(path)),
+                        ("path", path.display().to_string()),
+                        (
+                            "line",
+                            line_number.map(|l| l.to_string()).unwrap_or_default(),
+                        ),
+                        (
+                            "position",
+                            line_number.map(|l| format!(":{}", l)).unwrap_or_default(),
+                        ),
+                    ],
+                )
+            },
+            Run::Plugin(run_plugin) => {
+                let plugin = match &run_plugin.location {
+                    RunPluginLocation::Zellij(tag) => tag.to_string(),
+                    RunPluginLocation::File(path) => path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string()),
+                };
+                (
+                    formats.plugin(),
+                    vec![
+                        ("plugin", plugin),
+                        ("location", run_plugin.location.to_string()),
+                    ],
+                )
+            },
+            Run::Cwd(cwd) => (
+                formats.cwd(),
+                vec![
+                    ("dir", last_component(cwd)),
+                    ("cwd", cwd.display().to_string()),
+                ],
+            ),
+        };
+        // unknown placeholders are left as they are rather than failing, so this can't error
+        expand_placeholders_with(format, false, |placeholder| {
+            values
+                .iter()
+                .find(|(name, _value)| *name == placeholder)
+                .map(|(_name, value)| value.clone())
+        })
+        .unwrap_or_else(|_| format.to_owned())
+    }
+}
+
+// the variables of `base` with those of `other` set over them
+fn merge_env(
+    base: &BTreeMap<String, String>,
+    other: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut merged = base.clone();
+    merged.extend(
+        other
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone())),
+    );
+    merged
+}
+
+/// `path` moved from under `old_root` to under `new_root`, `None` if it is not under `old_root`.
+///
+/// Paths are compared component by component as they are written, without touching the
+/// filesystem: `/home/al` is not a prefix of `/home/alice`, relative paths are never under an
+/// absolute `old_root`, and a path that only reaches `old_root` through a symlink (or `..`) is not
+/// under it. Components are split by the separators of the platform zellij runs on, so on unix a
+/// `\` is part of a file name while on windows both `\` and `/` separate components.
+pub fn rebased_path(path: &Path, old_root: &Path, new_root: &Path) -> Option<PathBuf> {
+    let relative_path = path.strip_prefix(old_root).ok()?;
+    if relative_path.as_os_str().is_empty() {
+        // joining an empty path would add a trailing separator
+        Some(new_root.to_path_buf())
+    } else {
+        Some(new_root.join(relative_path))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -227,33 +498,413 @@ impl fmt::Display for RunPluginLocation {
     }
 }
 
+// the order of the variants is the order swap layout entries are tried in
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum LayoutConstraint {
+    // also depends on the tabs of the session, tried before the entries that only depend on the
+    // panes of the tab since it is the more specific
+    InSession(SessionConstraint, Box<LayoutConstraint>),
+    // tried before the min/max constraints, so that an exact match wins over a range that also
+    // includes the pane count
+    ExactPanes(usize),
+    // both ends are included
+    PaneRange { min: usize, max: usize },
     MaxPanes(usize),
+    MaxGroups(usize), // groups are panes, except that a stack of panes counts as one
+    MinGroups(usize),
     MinPanes(usize),
     NoConstraint,
 }
 
-pub type SwapTiledLayout = (BTreeMap<LayoutConstraint, TiledPaneLayout>, Option<String>); // Option<String> is the swap layout name
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum SessionConstraint {
+    MaxTabs(usize),
+    MinTabs(usize),
+}
+
+/// What swap layout constraints are matched against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PaneCounts {
+    pub panes: usize,
+    pub groups: usize, // each stack of panes counts as one
+}
+
+/// The state of the session the tab is in, for the swap layout constraints that depend on it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionContext {
+    pub tab_count: usize,
+    pub focused_tab_index: Option<usize>, // the position of the tab focused by the first client
+}
+
+impl SessionConstraint {
+    pub fn is_satisfied_by(&self, session_context: SessionContext) -> bool {
+        match self {
+            SessionConstraint::MaxTabs(max_tabs) => session_context.tab_count <= *max_tabs,
+            SessionConstraint::MinTabs(min_tabs) => session_context.tab_count >= *min_tabs,
+        }
+    }
+}
+
+impl LayoutConstraint {
+    pub fn is_satisfied_by(&self, counts: PaneCounts, session_context: SessionContext) -> bool {
+        match self {
+            LayoutConstraint::InSession(session_constraint, pane_constraint) => {
+                session_constraint.is_satisfied_by(session_context)
+                    && pane_constraint.is_satisfied_by(counts, session_context)
+            },
+            LayoutConstraint::ExactPanes(exact_panes) => counts.panes == *exact_panes,
+            LayoutConstraint::PaneRange { min, max } => {
+                counts.panes >= *min && counts.panes <= *max
+            },
+            LayoutConstraint::MaxPanes(max_panes) => counts.panes <= *max_panes,
+            LayoutConstraint::MinPanes(min_panes) => counts.panes >= *min_panes,
+            LayoutConstraint::MaxGroups(max_groups) => counts.groups <= *max_groups,
+            LayoutConstraint::MinGroups(min_groups) => counts.groups >= *min_groups,
+            LayoutConstraint::NoConstraint => true,
+        }
+    }
+    /// The part of the constraint that depends on the tabs of the session, if any
+    pub fn session_constraint(&self) -> Option<SessionConstraint> {
+        match self {
+            LayoutConstraint::InSession(session_constraint, _pane_constraint) => {
+                Some(*session_constraint)
+            },
+            _ => None,
+        }
+    }
+    /// The part of the constraint that depends on the panes of the tab
+    pub fn pane_constraint(&self) -> &LayoutConstraint {
+        match self {
+            LayoutConstraint::InSession(_session_constraint, pane_constraint) => {
+                pane_constraint.pane_constraint()
+            },
+            pane_constraint => pane_constraint,
+        }
+    }
+}
+
+/// The first of the entries of a swap layout (in the order they are tried) whose constraint is
+/// satisfied by `counts` in `session_context` and for which `fits` is true
+pub fn select_swap_layout_entry<'a, T>(
+    entries: &'a BTreeMap<LayoutConstraint, T>,
+    counts: PaneCounts,
+    session_context: SessionContext,
+    mut fits: impl FnMut(&T) -> bool,
+) -> Option<&'a T> {
+    entries
+        .iter()
+        .find(|(constraint, layout)| {
+            constraint.is_satisfied_by(counts, session_context) && fits(layout)
+        })
+        .map(|(_constraint, layout)| layout)
+}
+
+/// The name given to a swap layout that was not named, from the constraints of its entries and
+/// its position among the swap layouts of its kind (eg. "max_panes=4 #2")
+pub fn synthesized_swap_layout_name<T>(
+    entries: &BTreeMap<LayoutConstraint, T>,
+    position: usize,
+) -> String {
+    let constraints: Vec<String> = entries
+        .keys()
+        .flat_map(|constraint| {
+            let session_constraint = match constraint.session_constraint() {
+                Some(SessionConstraint::MaxTabs(max_tabs)) => {
+                    Some(format!("session_tabs_max={}", max_tabs))
+                },
+                Some(SessionConstraint::MinTabs(min_tabs)) => {
+                    Some(format!("session_tabs_min={}", min_tabs))
+                },
+                None => None,
+            };
+            let pane_constraint = match constraint.pane_constraint() {
+                LayoutConstraint::ExactPanes(exact_panes) => {
+                    Some(format!("exact_panes={}", exact_panes))
+                },
+                LayoutConstraint::PaneRange { min, max } => {
+                    Some(format!("min_panes={},max_panes={}", min, max))
+                },
+                LayoutConstraint::MaxPanes(max_panes) => Some(format!("max_panes={}", max_panes)),
+                LayoutConstraint::MinPanes(min_panes) => Some(format!("min_panes={}", min_panes)),
+                LayoutConstraint::MaxGroups(max_groups) => {
+                    Some(format!("max_groups={}", max_groups))
+                },
+                LayoutConstraint::MinGroups(min_groups) => {
+                    Some(format!("min_groups={}", min_groups))
+                },
+                LayoutConstraint::InSession(..) | LayoutConstraint::NoConstraint => None,
+            };
+            session_constraint.into_iter().chain(pane_constraint)
+        })
+        .collect();
+    if constraints.is_empty() {
+        format!("#{}", position + 1)
+    } else {
+        format!("{} #{}", constraints.join(","), position + 1)
+    }
+}
+
+/// The active swap layout as shown when cycling through them, its position among the `total`
+/// swap layouts and its name (eg. "3/5: wide-editor")
+pub fn swap_layout_label(position: usize, total: usize, name: &str) -> String {
+    format!("{}/{}: {}", position + 1, total, name)
+}
+
+/// What to do with the tab's panes that have no slot in a swap layout entry
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtraPanesPolicy {
+    StackLast,          // stack them together with the last pane
+    AppendAfterFocused, // place them as siblings after the focused pane
+    NewStack,           // place them in a new stack after the last pane
+}
+
+impl FromStr for ExtraPanesPolicy {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stack_last" => Ok(ExtraPanesPolicy::StackLast),
+            "append_after_focused" => Ok(ExtraPanesPolicy::AppendAfterFocused),
+            "new_stack" => Ok(ExtraPanesPolicy::NewStack),
+            _ => Err(
+                "extra_panes must be one of \"stack_last\", \"append_after_focused\" or \"new_stack\""
+                    .into(),
+            ),
+        }
+    }
+}
+
+/// What to do with the slots of a swap layout entry that have no pane in the tab
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MissingSlotsPolicy {
+    Collapse,  // truncate the entry so that the existing panes take up its space
+    KeepEmpty, // keep the geometry of the entry, the empty slots are filled with new panes
+}
+
+impl Default for MissingSlotsPolicy {
+    fn default() -> Self {
+        MissingSlotsPolicy::Collapse
+    }
+}
+
+impl FromStr for MissingSlotsPolicy {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "collapse" => Ok(MissingSlotsPolicy::Collapse),
+            "keep_empty" => Ok(MissingSlotsPolicy::KeepEmpty),
+            _ => Err("missing_slots must be either \"collapse\" or \"keep_empty\"".into()),
+        }
+    }
+}
+
+/// A pane of a swap layout entry standing for a pane of the base layout (the one new tabs are
+/// created with), eg. `pane ref="editor" size="+10%"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaneRef {
+    pub target: String, // the name of the base pane, or its position path (eg. "1/0")
+    pub size: Option<SizeAdjustment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeAdjustment {
+    Set(SplitSize),
+    PercentDelta(isize), // eg. "+10%"
+    FixedDelta(isize),   // eg. "-2"
+}
+
+impl SizeAdjustment {
+    pub fn apply(&self, base_size: Option<SplitSize>) -> Result<SplitSize, String> {
+        match (self, base_size) {
+            (SizeAdjustment::Set(size), _) => Ok(*size),
+            (SizeAdjustment::PercentDelta(delta), Some(SplitSize::Percent(base_percent))) => {
+                let percent = base_percent as isize + delta;
+                if percent < 1 || percent > 100 {
+                    Err(format!(
+                        "{}% {:+}% is {}%, sizes must be between 1% and 100%",
+                        base_percent, delta, percent
+                    ))
+                } else {
+                    Ok(SplitSize::Percent(percent as usize))
+                }
+            },
+            (SizeAdjustment::FixedDelta(delta), Some(SplitSize::Fixed(base_fixed))) => {
+                let fixed = base_fixed as isize + delta;
+                if fixed < 1 {
+                    Err(format!(
+                        "{} {:+} is {}, sizes must be greater than 0",
+                        base_fixed, delta, fixed
+                    ))
+                } else {
+                    Ok(SplitSize::Fixed(fixed as usize))
+                }
+            },
+            (SizeAdjustment::PercentDelta(_), Some(SplitSize::Fixed(_))) => Err(
+                "cannot add a percent to a pane with a fixed size in the base layout".into(),
+            ),
+            (SizeAdjustment::FixedDelta(_), Some(SplitSize::Percent(_))) => Err(
+                "cannot add a fixed number to a pane with a percent size in the base layout"
+                    .into(),
+            ),
+            (_, Some(SplitSize::Weight(_))) => Err(
+                "cannot change the size of a pane that has a weight in the base layout by a relative amount"
+                    .into(),
+            ),
+            (_, None) => Err(
+                "cannot change the size of a pane that has no size in the base layout by a relative amount"
+                    .into(),
+            ),
+        }
+    }
+}
+
+impl FromStr for SizeAdjustment {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sign = match s.chars().next() {
+            Some('+') => 1,
+            Some('-') => -1,
+            _ => return Ok(SizeAdjustment::Set(SplitSize::from_str(s)?)),
+        };
+        let delta = &s[1..];
+        if delta.chars().last() == Some('%') {
+            let percent = isize::from_str_radix(&delta[..delta.len() - 1], 10)?;
+            Ok(SizeAdjustment::PercentDelta(sign * percent))
+        } else {
+            let fixed = isize::from_str_radix(delta, 10)?;
+            Ok(SizeAdjustment::FixedDelta(sign * fixed))
+        }
+    }
+}
+
+pub type SwapTiledLayout = (
+    BTreeMap<LayoutConstraint, TiledPaneLayout>,
+    Option<String>,
+    Vec<String>,
+); // (layouts, the swap layout name, its tags)
 pub type SwapFloatingLayout = (
     BTreeMap<LayoutConstraint, Vec<FloatingPaneLayout>>,
     Option<String>,
-); // Option<String> is the swap layout name
+    Vec<String>,
+); // (layouts, the swap layout name, its tags)
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
 pub struct Layout {
-    pub tabs: Vec<(Option<String>, TiledPaneLayout, Vec<FloatingPaneLayout>)>,
+    pub tabs: Vec<(
+        Option<String>,
+        TiledPaneLayout,
+        Vec<FloatingPaneLayout>,
+        Option<TabPin>,
+        Option<usize>,                                   // max_content_width
+        (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>), // the swap layouts of this tab only
+    )>,
     pub focused_tab_index: Option<usize>,
+    /// The name of the focused tab when the layout focuses it with `focused_tab`, kept to write
+    /// the layout back the same way
+    pub focused_tab_name: Option<String>,
     pub template: Option<(TiledPaneLayout, Vec<FloatingPaneLayout>)>,
+    /// The tab_templates of the layout by name, each as the tab it opens when it is given no
+    /// children (see [`Layout::new_tab_with_template`])
+    pub tab_templates: BTreeMap<String, (TiledPaneLayout, Vec<FloatingPaneLayout>)>,
     pub swap_layouts: Vec<(TiledPaneLayout, Vec<FloatingPaneLayout>)>,
     pub swap_tiled_layouts: Vec<SwapTiledLayout>,
     pub swap_floating_layouts: Vec<SwapFloatingLayout>,
+    pub side_panel: Option<SidePanelLayout>,
+    /// When set, the command panes of the layout are spawned one at a time this many
+    /// milliseconds apart
+    pub command_spawn_interval_ms: Option<u64>,
+    /// Which pane of each tab gets the focus when the layout does not focus one explicitly
+    pub default_focus: Option<FocusPolicy>,
+    /// The program the command and edit panes of the layout are run under, except for the ones
+    /// with `no_wrapper`
+    pub command_wrapper: Option<CommandWrapper>,
+    /// The name of the session the layout is opened in, unless one is given on the command line
+    pub session_name: Option<String>,
+    /// Whether to attach to the session named [`Layout::session_name`] when it is already running
+    /// rather than refusing to start
+    pub attach_to_existing: Option<bool>,
+}
+
+/// How a session is started with a layout naming its session, see [`Layout::session_start`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutSessionStart {
+    Create(String),
+    Attach(String),
+    /// The session is running and the layout does not attach to it
+    AlreadyRunning(String),
+}
+
+/// A pane docked to the right edge of the screen and shared by all tabs, the tabs are laid out in
+/// the space left of it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SidePanelLayout {
+    pub width: PercentOrFixed,
+    pub pane: TiledPaneLayout,
+}
+
+impl SidePanelLayout {
+    /// Returns the position of the panel in a screen of `space` and the space left for the tabs
+    pub fn split_space(&self, space: Size) -> Result<(PaneGeom, Size), LayoutApplyError> {
+        let panel_cols = self.width.to_position(space.cols);
+        if panel_cols == 0 || panel_cols >= space.cols {
+            return Err(LayoutApplyError::SidePanelOverflow {
+                available: space.cols,
+            });
+        }
+        let tab_cols = space.cols - panel_cols;
+        let panel_geom = PaneGeom {
+            x: tab_cols,
+            y: 0,
+            rows: Dimension::fixed(space.rows),
+            cols: Dimension::fixed(panel_cols),
+            is_stacked: false,
+        };
+        let tab_space = Size {
+            rows: space.rows,
+            cols: tab_cols,
+        };
+        Ok((panel_geom, tab_space))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum PercentOrFixed {
+    #[serde(alias = "percent")]
     Percent(usize), // 1 to 100
-    Fixed(usize),   // An absolute number of columns or rows
+    #[serde(alias = "fixed")]
+    Fixed(usize), // An absolute number of columns or rows
+    /// A floating pane coordinate measured from the far (right or bottom) edge, as how far the
+    /// far side of the pane is from it, eg. `x="-10"`
+    #[serde(alias = "fixed_from_far_edge")]
+    FixedFromFarEdge(usize),
+    /// Like [`PercentOrFixed::FixedFromFarEdge`], in percent, eg. `x="-10%"` or `x="10%-"`
+    #[serde(alias = "percent_from_far_edge")]
+    PercentFromFarEdge(usize),
+}
+
+impl TryFrom<PercentOrFixed> for SplitSize {
+    type Error = &'static str;
+    fn try_from(percent_or_fixed: PercentOrFixed) -> Result<Self, Self::Error> {
+        match percent_or_fixed {
+            PercentOrFixed::Percent(percent) => Ok(SplitSize::Percent(percent)),
+            PercentOrFixed::Fixed(fixed) => Ok(SplitSize::Fixed(fixed)),
+            PercentOrFixed::FixedFromFarEdge(_) | PercentOrFixed::PercentFromFarEdge(_) => {
+                Err("A distance from the far edge is a coordinate rather than a size")
+            },
+        }
+    }
+}
+
+impl TryFrom<SplitSize> for PercentOrFixed {
+    type Error = &'static str;
+    fn try_from(split_size: SplitSize) -> Result<Self, Self::Error> {
+        match split_size {
+            SplitSize::Percent(percent) => Ok(PercentOrFixed::Percent(percent)),
+            SplitSize::Fixed(fixed) => Ok(PercentOrFixed::Fixed(fixed)),
+            SplitSize::Weight(_) => Err(
+                "A weight is a share of the space left by its siblings and has no size of its own",
+            ),
+        }
+    }
 }
 
 impl PercentOrFixed {
@@ -269,6 +920,37 @@ impl PercentOrFixed {
                     *fixed
                 }
             },
+            PercentOrFixed::FixedFromFarEdge(fixed) => {
+                whole.saturating_sub(PercentOrFixed::Fixed(*fixed).to_position(whole))
+            },
+            PercentOrFixed::PercentFromFarEdge(percent) => {
+                whole.saturating_sub(PercentOrFixed::Percent(*percent).to_position(whole))
+            },
+        }
+    }
+    /// Where something `size` long starts in `whole` when placed at this coordinate, kept inside
+    /// `whole` however far from its start or its far edge the coordinate is
+    pub fn to_coordinate(&self, whole: usize, size: usize) -> usize {
+        self.to_anchored_position(whole, size, AnchorSide::Start)
+    }
+    /// Like [`PercentOrFixed::to_coordinate`], but as an offset from `side` of `whole` (from the
+    /// opposite side for the distances from the far edge)
+    pub fn to_anchored_position(&self, whole: usize, size: usize, side: AnchorSide) -> usize {
+        let room = whole.saturating_sub(size);
+        if self.is_from_far_edge() {
+            let offset = whole.saturating_sub(self.to_position(whole)).min(room);
+            match side {
+                AnchorSide::Start => room - offset,
+                AnchorSide::Middle => (room / 2).saturating_sub(offset),
+                AnchorSide::End => offset,
+            }
+        } else {
+            let offset = self.to_position(whole).min(room);
+            match side {
+                AnchorSide::Start => offset,
+                AnchorSide::Middle => (room / 2 + offset).min(room),
+                AnchorSide::End => room - offset,
+            }
         }
     }
 }
@@ -276,15 +958,31 @@ impl PercentOrFixed {
 impl PercentOrFixed {
     pub fn is_zero(&self) -> bool {
         match self {
-            PercentOrFixed::Percent(percent) => *percent == 0,
-            PercentOrFixed::Fixed(fixed) => *fixed == 0,
+            PercentOrFixed::Percent(percent) | PercentOrFixed::PercentFromFarEdge(percent) => {
+                *percent == 0
+            },
+            PercentOrFixed::Fixed(fixed) | PercentOrFixed::FixedFromFarEdge(fixed) => *fixed == 0,
         }
     }
+    pub fn is_from_far_edge(&self) -> bool {
+        matches!(
+            self,
+            PercentOrFixed::FixedFromFarEdge(_) | PercentOrFixed::PercentFromFarEdge(_)
+        )
+    }
 }
 
 impl FromStr for PercentOrFixed {
     type Err = Box<dyn std::error::Error>;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // "-10", "-10%" or "10%-"
+        if let Some(distance) = s.strip_prefix('-').or_else(|| s.strip_suffix('-')) {
+            return match PercentOrFixed::from_str(distance)? {
+                PercentOrFixed::Percent(percent) => Ok(PercentOrFixed::PercentFromFarEdge(percent)),
+                PercentOrFixed::Fixed(fixed) => Ok(PercentOrFixed::FixedFromFarEdge(fixed)),
+                _ => Err("The distance from the far edge can only be given once".into()),
+            };
+        }
         if s.chars().last() == Some('%') {
             let char_count = s.chars().count();
             let percent_size = usize::from_str_radix(&s[..char_count.saturating_sub(1)], 10)?;
@@ -305,13 +1003,146 @@ pub struct FloatingPaneLayout {
     pub name: Option<String>,
     pub height: Option<PercentOrFixed>,
     pub width: Option<PercentOrFixed>,
+    pub max_height: Option<PercentOrFixed>, // bounds the height unless it is "fit"
+    pub max_width: Option<PercentOrFixed>,  // bounds the width unless it is "fit"
     pub x: Option<PercentOrFixed>,
     pub y: Option<PercentOrFixed>,
     pub run: Option<Run>,
     pub focus: Option<bool>,
+    pub pinned: Option<bool>, // shown above the tiled panes even when the floating panes are hidden
+    pub anchor: Option<FloatingPaneAnchor>, // x and y are offsets from it rather than the top left
+    pub hide_title: bool,
+    pub read_only: bool, // input to the pane is dropped, it can still be scrolled and copied from
+    pub no_wrapper: bool, // the pane's command is not run under the layout's command wrapper
+    pub exclude_from_dump: bool, // the pane's command is redacted when the layout is dumped
+    pub height_fit: Option<FitToContent>, // the height was declared as "fit"
+    pub width_fit: Option<FitToContent>, // the width was declared as "fit"
+    pub conditions: PaneConditions,
+    /// The stack of floating panes the pane is in, told apart from the other stacks of the tab by
+    /// this index. The panes of a stack share its x, y, width and height (see
+    /// [`floating_stack_geom`])
+    pub stack: Option<usize>,
+    pub is_expanded_in_stack: bool,
+}
+
+/// A floating pane height or width declared as `"fit"`: the pane starts at a modest size, and
+/// once its command exits it is resized to fit the command's output within `min` and `max`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FitToContent {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl FitToContent {
+    /// The rows a pane with a "fit" height starts with, before the output of its command is known
+    pub const INITIAL_ROWS: usize = 10;
+    /// The columns a pane with a "fit" width starts with
+    pub const INITIAL_COLS: usize = 40;
+    /// `size` within `min` and `max`, and then within the `available` space
+    pub fn bound(&self, size: usize, available: usize) -> usize {
+        let size = self.max.map(|max| size.min(max)).unwrap_or(size);
+        let size = self.min.map(|min| size.max(min)).unwrap_or(size);
+        size.min(available)
+    }
+}
+
+/// The corner (or the center) of the screen a floating pane is placed from, its `x` and `y` being
+/// offsets from there towards the middle of the screen (eg. `anchor="bottom_right" x=2 y=1`). The
+/// pane stays there when the screen is resized, until it is moved
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FloatingPaneAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+/// Where along one axis something is placed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorSide {
+    Start,
+    Middle,
+    End,
+}
+
+impl FloatingPaneAnchor {
+    /// The sides the anchor is on horizontally and vertically
+    pub fn sides(&self) -> (AnchorSide, AnchorSide) {
+        match self {
+            FloatingPaneAnchor::TopLeft => (AnchorSide::Start, AnchorSide::Start),
+            FloatingPaneAnchor::TopRight => (AnchorSide::End, AnchorSide::Start),
+            FloatingPaneAnchor::BottomLeft => (AnchorSide::Start, AnchorSide::End),
+            FloatingPaneAnchor::BottomRight => (AnchorSide::End, AnchorSide::End),
+            FloatingPaneAnchor::Center => (AnchorSide::Middle, AnchorSide::Middle),
+        }
+    }
+    /// The x and y of a `cols` by `rows` pane `x` and `y` away from the anchor in a `space_cols`
+    /// by `space_rows` space, kept inside the space
+    pub fn position(
+        &self,
+        x: Option<&PercentOrFixed>,
+        y: Option<&PercentOrFixed>,
+        (space_cols, space_rows): (usize, usize),
+        (cols, rows): (usize, usize),
+    ) -> (usize, usize) {
+        let no_offset = PercentOrFixed::Fixed(0);
+        let (horizontal_side, vertical_side) = self.sides();
+        (
+            x.unwrap_or(&no_offset)
+                .to_anchored_position(space_cols, cols, horizontal_side),
+            y.unwrap_or(&no_offset)
+                .to_anchored_position(space_rows, rows, vertical_side),
+        )
+    }
+}
+
+impl FromStr for FloatingPaneAnchor {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top_left" => Ok(FloatingPaneAnchor::TopLeft),
+            "top_right" => Ok(FloatingPaneAnchor::TopRight),
+            "bottom_left" => Ok(FloatingPaneAnchor::BottomLeft),
+            "bottom_right" => Ok(FloatingPaneAnchor::BottomRight),
+            "center" => Ok(FloatingPaneAnchor::Center),
+            _ => Err(
+                "anchor must be one of \"top_left\", \"top_right\", \"bottom_left\", \"bottom_right\" or \"center\""
+                    .into(),
+            ),
+        }
+    }
 }
 
 impl FloatingPaneLayout {
+    /// The cells left free on each side of a floating pane whose fixed size does not fit the
+    /// screen
+    pub const MARGIN: usize = 1;
+    /// The columns and rows of the pane in a `space_cols` by `space_rows` space (`default_size`
+    /// for those it does not declare), no larger than its max_width and max_height. A fixed size
+    /// larger than the space is shrunk to leave a margin around the pane. The declared sizes are
+    /// left as they are, for the layout to be dumped as it was written
+    pub fn resolve_size(
+        &self,
+        (space_cols, space_rows): (usize, usize),
+        (default_cols, default_rows): (usize, usize),
+    ) -> (usize, usize) {
+        (
+            resolve_floating_pane_size(&self.width, &self.max_width, space_cols, default_cols),
+            resolve_floating_pane_size(&self.height, &self.max_height, space_rows, default_rows),
+        )
+    }
+    /// This pane with the placeholders in its name replaced, see [`resolve_pane_name`]. `index`
+    /// is its position among the floating panes of the layout, from 1
+    pub fn with_resolved_name(&self, index: usize, values: &PaneNameValues) -> FloatingPaneLayout {
+        FloatingPaneLayout {
+            name: self
+                .name
+                .as_ref()
+                .map(|name| resolve_pane_name(name, &self.run, index, values)),
+            ..self.clone()
+        }
+    }
     pub fn add_cwd_to_layout(&mut self, cwd: &PathBuf) {
         match self.run.as_mut() {
             Some(run) => run.add_cwd(cwd),
@@ -320,6 +1151,128 @@ impl FloatingPaneLayout {
             },
         }
     }
+    pub fn rebase_paths(&mut self, old_root: &Path, new_root: &Path) {
+        if let Some(run) = self.run.as_mut() {
+            run.rebase_paths(old_root, new_root);
+        }
+    }
+}
+
+/// What the placeholders in pane names are replaced with besides what the panes run, see
+/// [`resolve_pane_name`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaneNameValues {
+    pub tab_name: String,
+    pub home_dir: Option<PathBuf>, // written as `~` at the start of a `{cwd}`
+}
+
+impl PaneNameValues {
+    pub fn new(tab_name: &str) -> Self {
+        PaneNameValues {
+            tab_name: tab_name.to_owned(),
+            home_dir: BaseDirs::new().map(|base_dirs| base_dirs.home_dir().to_path_buf()),
+        }
+    }
+}
+
+/// `name` with its `{command}` (along with its args, as in pane titles), `{args}`, `{cwd}`,
+/// `{index}` (the position of the pane among its siblings, from 1) and `{tab}` placeholders
+/// replaced. The names are resolved when the panes are created, from what they run once they were
+/// merged with their pane templates, while the layout keeps them as they are written. Unknown
+/// placeholders are left as they are, those known without a value (eg. the `{command}` of a pane
+/// without one) are removed.
+pub fn resolve_pane_name(
+    name: &str,
+    run: &Option<Run>,
+    index: usize,
+    values: &PaneNameValues,
+) -> String {
+    let (command, args, cwd) = match run {
+        Some(Run::Command(run_command)) => (
+            run_command.to_string(),
+            run_command.args.join(" "),
+            run_command.cwd.as_deref(),
+        ),
+        Some(Run::Cwd(cwd)) => (String::new(), String::new(), Some(cwd.as_path())),
+        _ => (String::new(), String::new(), None),
+    };
+    // unknown placeholders are left as they are rather than failing, so this can't error
+    expand_placeholders_with(name, false, |placeholder| match placeholder {
+        "command" => Some(command.clone()),
+        "args" => Some(args.clone()),
+        "cwd" => Some(
+            cwd.map(|cwd| shorten_home_with(cwd, values.home_dir.as_deref()))
+                .unwrap_or_default(),
+        ),
+        "index" => Some(index.to_string()),
+        "tab" => Some(values.tab_name.clone()),
+        _ => None,
+    })
+    .unwrap_or_else(|_| name.to_owned())
+}
+
+/// Where the floating pane at `index` of `floating_panes` goes when its stack (if it is in one)
+/// takes `stack_geom`: the panes of a stack are one below the other, all but the expanded one (the
+/// one marked as expanded, or the last one) collapsed to their title bar
+pub fn floating_stack_geom(
+    floating_panes: &[FloatingPaneLayout],
+    index: usize,
+    stack_geom: PaneGeom,
+) -> PaneGeom {
+    let stack = match floating_panes.get(index).and_then(|pane| pane.stack) {
+        Some(stack) => stack,
+        None => return stack_geom,
+    };
+    let stacked_panes: Vec<(usize, &FloatingPaneLayout)> = floating_panes
+        .iter()
+        .enumerate()
+        .filter(|(_index, pane)| pane.stack == Some(stack))
+        .collect();
+    let expanded_index = stacked_panes
+        .iter()
+        .find(|(_index, pane)| pane.is_expanded_in_stack)
+        .or(stacked_panes.last())
+        .map(|(index, _pane)| *index);
+    let collapsed_count = stacked_panes.len().saturating_sub(1);
+    let expanded_rows = stack_geom
+        .rows
+        .as_usize()
+        .saturating_sub(collapsed_count)
+        .max(1);
+    let mut geom = stack_geom;
+    for (pane_index, _pane) in stacked_panes {
+        let rows = if Some(pane_index) == expanded_index {
+            expanded_rows
+        } else {
+            1
+        };
+        if pane_index == index {
+            geom.rows = Dimension::fixed(rows);
+            break;
+        }
+        geom.y += rows;
+    }
+    geom
+}
+
+fn resolve_floating_pane_size(
+    size: &Option<PercentOrFixed>,
+    max_size: &Option<PercentOrFixed>,
+    space: usize,
+    default_size: usize,
+) -> usize {
+    let size = match size {
+        Some(PercentOrFixed::Fixed(fixed)) if *fixed > space => {
+            space.saturating_sub(FloatingPaneLayout::MARGIN * 2).max(1)
+        },
+        Some(size) => size.to_position(space),
+        None => default_size,
+    };
+    let size = match max_size {
+        Some(max_size) => size.min(max_size.to_position(space)),
+        None => size,
+    };
+    size.min(space)
 }
 
 impl From<&TiledPaneLayout> for FloatingPaneLayout {
@@ -328,25 +1281,251 @@ impl From<&TiledPaneLayout> for FloatingPaneLayout {
             name: pane_layout.name.clone(),
             run: pane_layout.run.clone(),
             focus: pane_layout.focus,
+            hide_title: pane_layout.hide_title,
+            read_only: pane_layout.read_only,
+            no_wrapper: pane_layout.no_wrapper,
+            exclude_from_dump: pane_layout.exclude_from_dump,
+            conditions: pane_layout.conditions.clone(),
             ..Default::default()
         }
     }
 }
 
+/// What a pane of a layout (or a tab, through the root pane of its layout) is only included
+/// under. The panes failing their conditions are dropped before the layout is positioned, so that
+/// their siblings take their space
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
-pub struct TiledPaneLayout {
-    pub children_split_direction: SplitDirection,
-    pub name: Option<String>,
-    pub children: Vec<TiledPaneLayout>,
-    pub split_size: Option<SplitSize>,
-    pub run: Option<Run>,
-    pub borderless: bool,
+pub struct PaneConditions {
+    /// An environment variable that has to be set and not empty, checked when the layout is loaded
+    /// (see [`Layout::apply_env_conditions`])
+    pub if_env: Option<String>,
+    /// A command that has to be found, in the PATH unless it is a path, checked on the machine of
+    /// the session when the layout is opened (see [`Layout::apply_command_conditions`])
+    pub if_command_exists: Option<String>,
+}
+
+impl PaneConditions {
+    /// Replaces these conditions with those `other` has
+    pub fn merge(&mut self, other: PaneConditions) {
+        if other.if_env.is_some() {
+            self.if_env = other.if_env;
+        }
+        if other.if_command_exists.is_some() {
+            self.if_command_exists = other.if_command_exists;
+        }
+    }
+}
+
+/// Whether a pane or tab of a layout was kept under one of its [`PaneConditions`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionOutcome {
+    pub location: Option<LayoutLocation>, // None for the side panel and the swap layouts of tabs
+    pub pane: Option<String>,             // eg. `pane "docker"`, None for the tab itself
+    pub condition: String,                // eg. `if_env="USE_DOCKER"`
+    pub included: bool,
+}
+
+impl ConditionOutcome {
+    pub fn to_diagnostic(&self) -> LayoutDiagnostic {
+        LayoutDiagnostic::info("pane-condition", self.to_string(), self.location.clone())
+    }
+}
+
+impl fmt::Display for ConditionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = if self.included { "included" } else { "dropped" };
+        match &self.pane {
+            Some(pane) => write!(f, "{} is {} ({})", pane, outcome, self.condition),
+            None => write!(f, "the tab is {} ({})", outcome, self.condition),
+        }
+    }
+}
+
+// checks the condition of a kind it takes out of the conditions of a pane, returning how the
+// condition is written and whether the pane is kept, None if the pane has no such condition
+type ConditionCheck<'a> = dyn FnMut(&mut PaneConditions) -> Option<(String, bool)> + 'a;
+
+fn check_env_condition(conditions: &mut PaneConditions) -> Option<(String, bool)> {
+    let variable = conditions.if_env.take()?;
+    let is_set = std::env::var_os(&variable).map_or(false, |value| !value.is_empty());
+    Some((format!("if_env=\"{}\"", variable), is_set))
+}
+
+fn command_condition_check<'a>(
+    command_exists: &'a impl Fn(&str) -> bool,
+) -> impl FnMut(&mut PaneConditions) -> Option<(String, bool)> + 'a {
+    move |conditions: &mut PaneConditions| {
+        let command = conditions.if_command_exists.take()?;
+        let exists = command_exists(&command);
+        Some((format!("if_command_exists=\"{}\"", command), exists))
+    }
+}
+
+/// Drops the panes of a tab opened from a layout of its own (eg. with `zellij action new-tab
+/// --layout`) whose `if_command_exists` command is not found, see
+/// [`Layout::apply_command_conditions`]. The conditions of the tab itself are not checked, it was
+/// asked for
+pub fn apply_tab_command_conditions(
+    tiled_panes: &mut TiledPaneLayout,
+    floating_panes: &mut Vec<FloatingPaneLayout>,
+    command_exists: impl Fn(&str) -> bool,
+) -> Vec<ConditionOutcome> {
+    let mut outcomes = vec![];
+    let mut check = command_condition_check(&command_exists);
+    tiled_panes.apply_conditions(&None, &mut check, &mut outcomes);
+    apply_floating_pane_conditions(floating_panes, &None, &mut check, &mut outcomes);
+    outcomes
+}
+
+fn apply_floating_pane_conditions(
+    floating_panes: &mut Vec<FloatingPaneLayout>,
+    location: &Option<LayoutLocation>,
+    check: &mut ConditionCheck,
+    outcomes: &mut Vec<ConditionOutcome>,
+) {
+    let panes = std::mem::take(floating_panes);
+    for (index, mut floating_pane) in panes.into_iter().enumerate() {
+        if let Some((condition, included)) = check(&mut floating_pane.conditions) {
+            let pane = LayoutPanePath::new(floating_pane.name.clone(), vec![index]);
+            outcomes.push(ConditionOutcome {
+                location: location.clone(),
+                pane: Some(format!("floating {}", pane)),
+                condition,
+                included,
+            });
+            if !included {
+                continue;
+            }
+        }
+        floating_panes.push(floating_pane);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct TiledPaneLayout {
+    pub children_split_direction: SplitDirection,
+    pub name: Option<String>,
+    pub children: Vec<TiledPaneLayout>,
+    pub split_size: Option<SplitSize>,
+    pub min_size: Option<SplitSize>, // a fixed size or a percent of the space of the parent pane
+    pub max_size: Option<SplitSize>, // a fixed size or a percent of the space of the parent pane
+    pub run: Option<Run>,
+    pub borderless: bool,
+    pub hide_title: bool,
+    pub read_only: bool,
+    pub no_wrapper: bool,
+    pub exclude_from_dump: bool,
     pub focus: Option<bool>,
     pub external_children_index: Option<usize>,
     pub children_are_stacked: bool,
+    pub is_expanded_in_stack: bool, // rather than the last pane of the stack it is in
+    pub stack_below_width: Option<usize>, // when narrower, all panes below this one are stacked
+    pub extra_panes: Option<ExtraPanesPolicy>, // None means panes are added in the `children` block
+    pub missing_slots: MissingSlotsPolicy,
+    pub on_failure: Option<Box<TiledPaneLayout>>, // runs in place of the command when it fails
+    pub conditions: PaneConditions,
+}
+
+/// What a pane of a tiled layout runs, see [`TiledPaneLayout::extract_run_instructions_indexed`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunInstruction {
+    pub run: Option<Run>,
+    /// The child indices leading to the pane in the layout
+    pub leaf_path: Vec<usize>,
+    pub pane_name: Option<String>,
+}
+
+// only what decides where the panes are positioned is hashed, layouts differing in anything else
+// (eg. what their panes run) are told apart by comparing them
+impl Hash for TiledPaneLayout {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.children_split_direction.hash(state);
+        self.children.hash(state);
+        self.split_size.hash(state);
+        self.min_size.hash(state);
+        self.max_size.hash(state);
+        self.focus.hash(state);
+        self.external_children_index.hash(state);
+        self.children_are_stacked.hash(state);
+        self.is_expanded_in_stack.hash(state);
+        self.stack_below_width.hash(state);
+        self.extra_panes.hash(state);
+        self.missing_slots.hash(state);
+    }
+}
+
+/// The panes of a layout positioned in a space, see
+/// [`TiledPaneLayout::position_panes_in_space_with_focus`]
+pub type PanePositions = Result<Vec<(TiledPaneLayout, PaneGeom)>, LayoutApplyError>;
+
+/// How many positioned layouts a [`PanePositionsCache`] keeps before starting over
+const PANE_POSITIONS_CACHE_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PanePositionsKey {
+    layout_hash: u64,
+    space: PaneGeom,
+    max_panes: Option<usize>,
+    focus_policy: FocusPolicy,
+}
+
+/// Remembers where the panes of the layouts positioned with it were put, so that applying a swap
+/// layout again to a size it was already applied to (as happens many times while the terminal is
+/// resized back and forth) does not position its panes again
+#[derive(Debug, Default)]
+pub struct PanePositionsCache {
+    positions: HashMap<PanePositionsKey, (TiledPaneLayout, PanePositions)>,
+}
+
+impl PanePositionsCache {
+    /// [`TiledPaneLayout::position_panes_in_space_with_focus`], unless this layout was already
+    /// positioned in this space
+    pub fn position_panes_in_space_with_focus(
+        &mut self,
+        layout: &TiledPaneLayout,
+        space: &PaneGeom,
+        max_panes: Option<usize>,
+        focus_policy: FocusPolicy,
+    ) -> PanePositions {
+        let mut hasher = DefaultHasher::new();
+        layout.hash(&mut hasher);
+        let key = PanePositionsKey {
+            layout_hash: hasher.finish(),
+            space: *space,
+            max_panes,
+            focus_policy,
+        };
+        if let Some((positioned_layout, positions)) = self.positions.get(&key) {
+            if positioned_layout == layout {
+                return positions.clone();
+            }
+        }
+        let positions = layout.position_panes_in_space_with_focus(space, max_panes, focus_policy);
+        if self.positions.len() >= PANE_POSITIONS_CACHE_CAPACITY {
+            self.positions.clear();
+        }
+        self.positions
+            .insert(key, (layout.clone(), positions.clone()));
+        positions
+    }
 }
 
 impl TiledPaneLayout {
+    /// This layout with the placeholders in the names of its panes replaced, see
+    /// [`resolve_pane_name`]
+    pub fn with_resolved_pane_names(&self, values: &PaneNameValues) -> TiledPaneLayout {
+        let mut layout = self.clone();
+        layout.resolve_pane_names(1, values);
+        layout
+    }
+    fn resolve_pane_names(&mut self, index: usize, values: &PaneNameValues) {
+        if let Some(name) = &self.name {
+            self.name = Some(resolve_pane_name(name, &self.run, index, values));
+        }
+        for (child_index, child) in self.children.iter_mut().enumerate() {
+            child.resolve_pane_names(child_index + 1, values);
+        }
+    }
     pub fn insert_children_layout(
         &mut self,
         children_layout: &mut TiledPaneLayout,
@@ -375,6 +1554,23 @@ impl TiledPaneLayout {
     ) -> Result<bool, ConfigError> {
         // returns true if successfully inserted and false otherwise
         match self.external_children_index {
+            Some(external_children_index)
+                if !self.children.is_empty()
+                    && !self.children_are_stacked
+                    && children_nodes.len() > 1 =>
+            {
+                // next to other panes, the inserted ones share the space of the `children` block
+                // between them rather than that of its siblings
+                let children_container = TiledPaneLayout {
+                    children_split_direction: self.children_split_direction,
+                    children: children_nodes.drain(..).collect(),
+                    ..Default::default()
+                };
+                self.children
+                    .insert(external_children_index, children_container);
+                self.external_children_index = None;
+                Ok(true)
+            },
             Some(external_children_index) => {
                 children_nodes.reverse();
                 for child_node in children_nodes.drain(..) {
@@ -393,6 +1589,17 @@ impl TiledPaneLayout {
             },
         }
     }
+    // whether the first `children` block of this layout (the one panes are inserted in) has
+    // sibling panes, None if it has no `children` block
+    fn children_block_has_siblings(&self) -> Option<bool> {
+        match self.external_children_index {
+            Some(_) => Some(!self.children.is_empty()),
+            None => self
+                .children
+                .iter()
+                .find_map(|pane| pane.children_block_has_siblings()),
+        }
+    }
     pub fn children_block_count(&self) -> usize {
         let mut count = 0;
         if self.external_children_index.is_some() {
@@ -404,68 +1611,538 @@ impl TiledPaneLayout {
         count
     }
     pub fn pane_count(&self) -> usize {
-        if self.children.is_empty() {
-            1 // self
-        } else {
-            let mut pane_count = 0;
-            for child in &self.children {
-                pane_count += child.pane_count();
+        self.leaves().len()
+    }
+    pub fn position_panes_in_space(
+        &self,
+        space: &PaneGeom,
+        max_panes: Option<usize>,
+    ) -> Result<Vec<(TiledPaneLayout, PaneGeom)>, LayoutApplyError> {
+        self.position_panes_in_space_with_focus(space, max_panes, FocusPolicy::default())
+    }
+    /// Like [`TiledPaneLayout::position_panes_in_space`], focusing the pane chosen by
+    /// `focus_policy` when the layout has no focused pane once fitted to `max_panes`
+    pub fn position_panes_in_space_with_focus(
+        &self,
+        space: &PaneGeom,
+        max_panes: Option<usize>,
+        focus_policy: FocusPolicy,
+    ) -> Result<Vec<(TiledPaneLayout, PaneGeom)>, LayoutApplyError> {
+        let positions = self.position_panes_in_space_indexed(space, max_panes, focus_policy)?;
+        Ok(positions
+            .into_iter()
+            .map(|(pane_layout, pane_geom, _leaf_path)| (pane_layout, pane_geom))
+            .collect())
+    }
+    /// Like [`TiledPaneLayout::position_panes_in_space_with_focus`], along with the leaf path of
+    /// each pane in the layout fitted to `max_panes`. The positioned panes and their leaf paths
+    /// are those of [`TiledPaneLayout::extract_run_instructions_indexed`], in the same order (the
+    /// panes of a layout collapsed into a stack keep the paths they had before it was collapsed)
+    pub fn position_panes_in_space_indexed(
+        &self,
+        space: &PaneGeom,
+        max_panes: Option<usize>,
+        focus_policy: FocusPolicy,
+    ) -> Result<Vec<(TiledPaneLayout, PaneGeom, Vec<usize>)>, LayoutApplyError> {
+        let layout_to_split = self.fitted_to_pane_count(space, max_panes, focus_policy);
+        let layouts = split_space_indexed(space, &layout_to_split, space, vec![])?;
+        // the panes are matched with the run instructions of the layout by their position
+        debug_assert!(
+            layouts
+                .iter()
+                .map(|(_pane_layout, _pane_geom, leaf_path)| leaf_path)
+                .eq(layout_to_split.extract_pane_paths().iter()),
+            "positioned panes do not match the run instructions of the layout"
+        );
+        for (pane_layout, pane_geom, leaf_path) in layouts.iter() {
+            if !pane_geom.is_at_least_minimum_size() {
+                return Err(LayoutApplyError::BelowMinimumSize {
+                    pane: LayoutPanePath::new(pane_layout.name.clone(), leaf_path.clone()),
+                });
             }
-            pane_count
         }
+        Ok(layouts)
     }
-    pub fn position_panes_in_space(
+    /// The layout [`TiledPaneLayout::position_panes_in_space_with_focus`] positions: this one with
+    /// panes added or truncated to fit `max_panes`, and its empty containers turned into panes.
+    /// Its [`TiledPaneLayout::extract_run_instructions`] are in the order of the positioned panes
+    pub fn fitted_to_pane_count(
         &self,
         space: &PaneGeom,
         max_panes: Option<usize>,
-    ) -> Result<Vec<(TiledPaneLayout, PaneGeom)>, &'static str> {
-        let layouts = match max_panes {
-            Some(max_panes) => {
-                let mut layout_to_split = self.clone();
-                let pane_count_in_layout = layout_to_split.pane_count();
-                if max_panes > pane_count_in_layout {
-                    // the + 1 here is because this was previously an "actual" pane and will now
-                    // become just a container, so we need to account for it too
-                    // TODO: make sure this works when the `children` node has sibling nodes,
-                    // because we really should support that
-                    let children_count = (max_panes - pane_count_in_layout) + 1;
+        focus_policy: FocusPolicy,
+    ) -> Cow<TiledPaneLayout> {
+        let max_panes = match max_panes {
+            Some(max_panes) => max_panes,
+            None => return Cow::Borrowed(self),
+        };
+        let mut layout_to_split = self.clone();
+        let pane_count_in_layout = layout_to_split.pane_count();
+        if max_panes > pane_count_in_layout {
+            match self.extra_panes {
+                Some(extra_panes_policy) => {
+                    let extra_pane_count = max_panes - pane_count_in_layout;
+                    layout_to_split.add_extra_panes(extra_panes_policy, extra_pane_count);
+                },
+                None => {
+                    // a `children` block alone in its container leaves it an "actual" pane, which
+                    // will now become just a container, so we need to account for it too. Next to
+                    // sibling panes the container is not a pane of its own
+                    let children_count = match layout_to_split.children_block_has_siblings() {
+                        Some(true) => max_panes - pane_count_in_layout,
+                        _ => (max_panes - pane_count_in_layout) + 1,
+                    };
                     let mut extra_children = vec![TiledPaneLayout::default(); children_count];
-                    if !layout_to_split.has_focused_node() {
+                    if focus_policy == FocusPolicy::Deepest && !layout_to_split.has_focused_node() {
                         if let Some(last_child) = extra_children.last_mut() {
                             last_child.focus = Some(true);
                         }
                     }
                     let _ = layout_to_split.insert_children_nodes(&mut extra_children);
-                } else {
-                    layout_to_split.truncate(max_panes);
-                }
-                if !layout_to_split.has_focused_node() {
-                    layout_to_split.focus_deepest_pane();
+                },
+            }
+        } else if self.missing_slots == MissingSlotsPolicy::Collapse {
+            layout_to_split.truncate(max_panes);
+        }
+        // the children inserted in a focused container take its focus
+        layout_to_split.resolve_container_focus();
+        if !layout_to_split.has_focused_node() {
+            layout_to_split.apply_focus_policy(focus_policy, space);
+        }
+        // truncating empties the containers it cuts the children of
+        layout_to_split.normalize_empty_containers();
+        Cow::Owned(layout_to_split)
+    }
+    /// Turns the containers without children (authored empty, emptied by truncation or by a
+    /// `children` block that got no panes) into plain panes, so that nothing walking the layout
+    /// sees them as anything but the single panes they are laid out as. Containers still waiting
+    /// for their `children` are left alone.
+    pub fn normalize_empty_containers(&mut self) {
+        let mut to_visit = vec![self];
+        while let Some(pane) = to_visit.pop() {
+            if pane.children.is_empty() && pane.external_children_index.is_none() {
+                pane.children_split_direction = SplitDirection::default();
+                pane.children_are_stacked = false;
+                pane.stack_below_width = None;
+            }
+            to_visit.extend(pane.children.iter_mut());
+        }
+    }
+    /// Makes the commands of this layout wait for the user to start them
+    pub fn suspend_commands(&mut self) {
+        let mut to_visit = vec![self];
+        while let Some(pane) = to_visit.pop() {
+            if let Some(run) = pane.run.as_mut() {
+                run.add_start_suspended(Some(true));
+            }
+            to_visit.extend(pane.children.iter_mut());
+        }
+    }
+    /// Drops the panes of this layout failing `check`, recording the outcome of each condition
+    /// checked. The pane itself is not checked, its conditions are those of the tab it is the
+    /// root of
+    fn apply_conditions(
+        &mut self,
+        location: &Option<LayoutLocation>,
+        check: &mut ConditionCheck,
+        outcomes: &mut Vec<ConditionOutcome>,
+    ) {
+        let mut to_visit = vec![(self, vec![])];
+        while let Some((pane, path)) = to_visit.pop() {
+            let mut kept_paths = vec![];
+            let mut dropped_before_external_children = 0;
+            for (index, mut child) in std::mem::take(&mut pane.children).into_iter().enumerate() {
+                let mut child_path: Vec<usize> = path.clone();
+                child_path.push(index);
+                if let Some((condition, included)) = check(&mut child.conditions) {
+                    let child_pane = LayoutPanePath::new(child.name.clone(), child_path.clone());
+                    outcomes.push(ConditionOutcome {
+                        location: location.clone(),
+                        pane: Some(child_pane.to_string()),
+                        condition,
+                        included,
+                    });
+                    if !included {
+                        if pane.external_children_index.map_or(false, |i| index < i) {
+                            dropped_before_external_children += 1;
+                        }
+                        continue;
+                    }
                 }
-
-                split_space(space, &layout_to_split, space)?
-            },
-            None => split_space(space, self, space)?,
+                pane.children.push(child);
+                kept_paths.push(child_path);
+            }
+            if let Some(external_children_index) = pane.external_children_index.as_mut() {
+                *external_children_index -= dropped_before_external_children;
+            }
+            to_visit.extend(pane.children.iter_mut().zip(kept_paths));
+        }
+    }
+    /// Moves the paths of this layout from under `old_root` to under `new_root`, see
+    /// [`Layout::rebase_paths`]
+    pub fn rebase_paths(&mut self, old_root: &Path, new_root: &Path) {
+        let mut to_visit = vec![self];
+        while let Some(pane) = to_visit.pop() {
+            if let Some(run) = pane.run.as_mut() {
+                run.rebase_paths(old_root, new_root);
+            }
+            to_visit.extend(pane.children.iter_mut());
+        }
+    }
+    /// Changes the split sizes of this layout so that positioning it in `space` gives its panes
+    /// the sizes of `pane_geoms` (in the order of [`TiledPaneLayout::position_panes_in_space`]),
+    /// eg. to save it again after its panes were resized. Split sizes that already give these
+    /// sizes are kept as they were declared, so that re-saving an unchanged layout does not drift.
+    pub fn fit_split_sizes(
+        &mut self,
+        space: &PaneGeom,
+        pane_geoms: &[PaneGeom],
+    ) -> Result<(), LayoutApplyError> {
+        if pane_geoms.len() != self.pane_count() {
+            return Err(LayoutApplyError::PaneCountMismatch {
+                expected: self.pane_count(),
+                found: pane_geoms.len(),
+            });
+        }
+        self.fit_children_split_sizes(space, space, pane_geoms, vec![])
+    }
+    fn fit_children_split_sizes(
+        &mut self,
+        space: &PaneGeom,
+        total_space: &PaneGeom,
+        pane_geoms: &[PaneGeom],
+        path: Vec<usize>,
+    ) -> Result<(), LayoutApplyError> {
+        if self.children.is_empty() || self.is_collapsed_into_stack(space) {
+            // stacked panes get the same sizes whatever their split sizes are
+            return Ok(());
+        }
+        // the children's own children are left out, so that we get one geom per child
+        let children_geoms = |layout: &TiledPaneLayout| {
+            let shallow_layout = TiledPaneLayout {
+                name: layout.name.clone(),
+                children_split_direction: layout.children_split_direction,
+                children_are_stacked: layout.children_are_stacked,
+                children: layout
+                    .children
+                    .iter()
+                    .map(|child| TiledPaneLayout {
+                        name: child.name.clone(),
+                        split_size: child.split_size,
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            split_space_indexed(space, &shallow_layout, total_space, path.clone())
         };
-        for (_pane_layout, pane_geom) in layouts.iter() {
-            if !pane_geom.is_at_least_minimum_size() {
-                return Err("No room on screen for this layout!");
+        let mut child_pane_geoms = vec![];
+        let mut first_pane = 0;
+        for child in &self.children {
+            let pane_count = child.pane_count();
+            child_pane_geoms.push(&pane_geoms[first_pane..first_pane + pane_count]);
+            first_pane += pane_count;
+        }
+        let split_direction = self.children_split_direction.resolve(space);
+        let (start, len): (fn(&PaneGeom) -> usize, fn(&PaneGeom) -> usize) = match split_direction {
+            SplitDirection::Vertical => (|g| g.x, |g| g.cols.as_usize()),
+            SplitDirection::Horizontal | SplitDirection::Auto => (|g| g.y, |g| g.rows.as_usize()),
+        };
+        let cell_sizes: Vec<usize> = child_pane_geoms
+            .iter()
+            .map(|geoms| {
+                let first_cell = geoms.iter().map(start).min().unwrap_or(0);
+                let last_cell = geoms.iter().map(|g| start(g) + len(g)).max().unwrap_or(0);
+                last_cell - first_cell
+            })
+            .collect();
+        let resolved_cell_sizes =
+            |geoms: &[(TiledPaneLayout, PaneGeom, Vec<usize>)]| -> Vec<usize> {
+                geoms.iter().map(|(_, geom, _)| len(geom)).collect()
+            };
+        let mut geoms = children_geoms(self)?;
+        // stacked panes are always laid out the same way, no matter their split size
+        if !self.children_are_stacked && resolved_cell_sizes(&geoms) != cell_sizes {
+            let declared_sizes: Vec<Option<SplitSize>> =
+                self.children.iter().map(|child| child.split_size).collect();
+            let total_cells = match split_direction {
+                SplitDirection::Vertical => total_space.cols.as_usize(),
+                SplitDirection::Horizontal | SplitDirection::Auto => total_space.rows.as_usize(),
+            };
+            let split_sizes = split_sizes_for_cells(&cell_sizes, &declared_sizes, total_cells);
+            for (child, split_size) in self.children.iter_mut().zip(split_sizes) {
+                child.split_size = split_size;
+            }
+            geoms = children_geoms(self)?;
+            if resolved_cell_sizes(&geoms) != cell_sizes {
+                return Err(LayoutApplyError::NoFittingSplitSizes {
+                    pane: LayoutPanePath::new(self.name.clone(), path),
+                });
             }
         }
-        Ok(layouts)
+        for ((child, (_, child_space, child_path)), pane_geoms) in self
+            .children
+            .iter_mut()
+            .zip(geoms.iter())
+            .zip(child_pane_geoms)
+        {
+            child.fit_children_split_sizes(
+                child_space,
+                total_space,
+                pane_geoms,
+                child_path.clone(),
+            )?;
+        }
+        Ok(())
+    }
+    /// Whether all the panes below this one are put in a single stack because `space` is narrower
+    /// than its `stack_below_width`, so that none of them ends up too narrow to use
+    pub fn is_collapsed_into_stack(&self, space: &PaneGeom) -> bool {
+        match self.stack_below_width {
+            Some(stack_below_width) => {
+                !self.children.is_empty()
+                    && !self.children_are_stacked
+                    && self.children_split_direction.resolve(space) == SplitDirection::Vertical
+                    && space.cols.as_usize() < stack_below_width
+            },
+            None => false,
+        }
+    }
+    /// The index of the child of this stack that is expanded: the first one marked with
+    /// `expanded=true`, or the last one if none is. None if this pane has no children
+    pub fn expanded_index_in_stack(&self) -> Option<usize> {
+        self.children
+            .iter()
+            .position(|child| child.is_expanded_in_stack)
+            .or_else(|| self.children.len().checked_sub(1))
+    }
+    /// Whether this pane's children are stacked but `space` does not have a row for each of them,
+    /// in which case they are laid out as a deep stack: the last pane is expanded over all the
+    /// rows but one (even if another pane is marked as expanded), and the others share that row
+    /// (see [`TiledPaneLayout::deep_stacks`])
+    pub fn is_deep_stack(&self, space: &PaneGeom) -> bool {
+        let rows = space.rows.as_usize();
+        self.children_are_stacked && rows >= 2 && self.children.len() > rows
+    }
+    /// The deep stacks among panes positioned by [`TiledPaneLayout::position_panes_in_space`], as
+    /// the indices of their panes in stack order. All but the last (expanded) pane of a deep stack
+    /// have the same geometry, only one of them can be shown at a time.
+    pub fn deep_stacks(positions: &[(TiledPaneLayout, PaneGeom)]) -> Vec<Vec<usize>> {
+        let mut deep_stacks = vec![];
+        let mut i = 0;
+        while i < positions.len() {
+            let geom = positions[i].1;
+            let shared_row_len = positions[i..]
+                .iter()
+                .take_while(|(_, other_geom)| *other_geom == geom)
+                .count();
+            if geom.is_stacked && shared_row_len > 1 && i + shared_row_len < positions.len() {
+                deep_stacks.push((i..=i + shared_row_len).collect());
+                i += shared_row_len + 1;
+            } else {
+                i += 1;
+            }
+        }
+        deep_stacks
+    }
+    fn collapsed_into_stack(&self) -> TiledPaneLayout {
+        TiledPaneLayout {
+            children_split_direction: SplitDirection::Horizontal,
+            children: self.leaf_panes(),
+            children_are_stacked: true,
+            stack_below_width: None,
+            ..self.clone()
+        }
+    }
+    fn configure_strider_panes(
+        &mut self,
+        width: Option<SplitSize>,
+        stack_below_width: Option<usize>,
+    ) {
+        let strider = PluginTag::new("strider");
+        let mut has_strider_pane = false;
+        for child in self.children.iter_mut() {
+            match &child.run {
+                Some(Run::Plugin(RunPlugin {
+                    location: RunPluginLocation::Zellij(tag),
+                    ..
+                })) if tag == &strider => {
+                    has_strider_pane = true;
+                    if width.is_some() {
+                        child.split_size = width;
+                    }
+                },
+                _ => child.configure_strider_panes(width, stack_below_width),
+            }
+        }
+        if has_strider_pane && stack_below_width.is_some() {
+            self.stack_below_width = stack_below_width;
+        }
+    }
+    fn leaf_panes(&self) -> Vec<TiledPaneLayout> {
+        self.children
+            .iter()
+            .flat_map(|child| child.leaves())
+            .cloned()
+            .collect()
+    }
+    /// The panes of the layout (the nodes without children, or this one if it has none) in the
+    /// order they are positioned. Walked with an explicit stack rather than recursively, so that
+    /// deeply nested layouts cannot overflow the call stack
+    fn leaves(&self) -> Vec<&TiledPaneLayout> {
+        let mut leaves = vec![];
+        let mut to_visit = vec![self];
+        while let Some(pane) = to_visit.pop() {
+            if pane.children.is_empty() {
+                leaves.push(pane);
+            } else {
+                to_visit.extend(pane.children.iter().rev());
+            }
+        }
+        leaves
+    }
+    /// [`TiledPaneLayout::leaves`], along with the child indices leading to each of them
+    fn leaves_with_paths(&self) -> Vec<(&TiledPaneLayout, Vec<usize>)> {
+        let mut leaves = vec![];
+        let mut to_visit = vec![(self, vec![])];
+        while let Some((pane, path)) = to_visit.pop() {
+            if pane.children.is_empty() {
+                leaves.push((pane, path));
+            } else {
+                for (i, child) in pane.children.iter().enumerate().rev() {
+                    let mut child_path = path.clone();
+                    child_path.push(i);
+                    to_visit.push((child, child_path));
+                }
+            }
+        }
+        leaves
     }
     pub fn extract_run_instructions(&self) -> Vec<Option<Run>> {
-        // the order of these run instructions is significant and needs to be the same
-        // as the order of the "flattened" layout panes received from eg. position_panes_in_space
-        let mut run_instructions = vec![];
-        if self.children.is_empty() {
-            run_instructions.push(self.run.clone());
+        self.extract_run_instructions_indexed()
+            .into_iter()
+            .map(|run_instruction| run_instruction.run)
+            .collect()
+    }
+    /// The run instructions of the panes along with where they are in the layout. They are in the
+    /// same order as the panes positioned by
+    /// [`TiledPaneLayout::position_panes_in_space_indexed`] (once fitted with
+    /// [`TiledPaneLayout::fitted_to_pane_count`]), and have the same leaf paths
+    pub fn extract_run_instructions_indexed(&self) -> Vec<RunInstruction> {
+        self.leaves_with_paths()
+            .into_iter()
+            .map(|(pane, leaf_path)| RunInstruction {
+                run: pane.run.clone(),
+                leaf_path,
+                pane_name: pane.name.clone(),
+            })
+            .collect()
+    }
+    /// The names of the panes, in the same order as [`TiledPaneLayout::extract_run_instructions`]
+    pub fn extract_pane_names(&self) -> Vec<Option<String>> {
+        self.leaves().iter().map(|pane| pane.name.clone()).collect()
+    }
+    /// The command each pane runs in its place when its own command fails (see
+    /// [`TiledPaneLayout::on_failure`]), in the same order as
+    /// [`TiledPaneLayout::extract_run_instructions`]. Runs in the cwd of the failed command unless
+    /// it has its own
+    pub fn extract_failure_commands(&self) -> Vec<Option<RunCommand>> {
+        self.leaves()
+            .iter()
+            .map(|pane| {
+                let failure_command = match pane.on_failure.as_ref().map(|p| &p.run) {
+                    Some(Some(Run::Command(failure_command))) => failure_command,
+                    _ => return None,
+                };
+                let cwd = match &pane.run {
+                    Some(Run::Command(run_command)) => run_command.cwd.as_ref(),
+                    _ => None,
+                };
+                Some(RunCommand {
+                    cwd: failure_command.cwd.clone().or_else(|| cwd.cloned()),
+                    ..failure_command.clone()
+                })
+            })
+            .collect()
+    }
+    /// Whether each pane opted out of the layout's command wrapper, in the same order as
+    /// [`TiledPaneLayout::extract_run_instructions`]
+    pub fn extract_no_wrapper(&self) -> Vec<bool> {
+        self.leaves().iter().map(|pane| pane.no_wrapper).collect()
+    }
+    /// Whether each pane is redacted when the layout is dumped, in the same order as
+    /// [`TiledPaneLayout::extract_run_instructions`]
+    pub fn extract_exclude_from_dump(&self) -> Vec<bool> {
+        self.leaves()
+            .iter()
+            .map(|pane| pane.exclude_from_dump)
+            .collect()
+    }
+    /// The child indices leading to each pane, in the same order as
+    /// [`TiledPaneLayout::extract_run_instructions`]
+    pub fn extract_pane_paths(&self) -> Vec<Vec<usize>> {
+        self.leaves_with_paths()
+            .into_iter()
+            .map(|(_pane, path)| path)
+            .collect()
+    }
+    /// Applies a swap layout pane ref to this (base) layout: the referred pane is looked up by
+    /// name, or if no pane has this name by its position path (child indices separated by "/")
+    pub fn apply_pane_ref(&mut self, pane_ref: &PaneRef) -> Result<(), String> {
+        let path = self.path_of_pane_ref(&pane_ref.target)?;
+        let pane = self
+            .pane_at_path_mut(&path)
+            .ok_or_else(|| format!("Unknown pane ref '{}'", pane_ref.target))?;
+        if let Some(size) = &pane_ref.size {
+            pane.split_size = Some(
+                size.apply(pane.split_size)
+                    .map_err(|e| format!("Cannot resize pane '{}': {}", pane_ref.target, e))?,
+            );
         }
-        for child in &self.children {
-            let mut child_run_instructions = child.extract_run_instructions();
-            run_instructions.append(&mut child_run_instructions);
+        Ok(())
+    }
+    fn path_of_pane_ref(&self, target: &str) -> Result<Vec<usize>, String> {
+        let mut named_paths = vec![];
+        self.paths_of_panes_named(target, &mut vec![], &mut named_paths);
+        if named_paths.len() > 1 {
+            return Err(format!(
+                "Ambiguous pane ref '{}', more than one pane in the base layout has this name",
+                target
+            ));
+        }
+        if let Some(path) = named_paths.pop() {
+            return Ok(path);
+        }
+        let path: Option<Vec<usize>> = target.split('/').map(|i| i.parse().ok()).collect();
+        match path {
+            Some(path) if self.pane_at_path(&path).is_some() => Ok(path),
+            _ => Err(format!(
+                "Unknown pane ref '{}', the base layout has no pane with this name or position",
+                target
+            )),
+        }
+    }
+    fn paths_of_panes_named(&self, name: &str, path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+        for (i, child) in self.children.iter().enumerate() {
+            path.push(i);
+            if child.name.as_deref() == Some(name) {
+                paths.push(path.clone());
+            }
+            child.paths_of_panes_named(name, path, paths);
+            path.pop();
+        }
+    }
+    fn pane_at_path(&self, path: &[usize]) -> Option<&TiledPaneLayout> {
+        match path.split_first() {
+            Some((i, rest)) => self.children.get(*i)?.pane_at_path(rest),
+            None => Some(self),
+        }
+    }
+    fn pane_at_path_mut(&mut self, path: &[usize]) -> Option<&mut TiledPaneLayout> {
+        match path.split_first() {
+            Some((i, rest)) => self.children.get_mut(*i)?.pane_at_path_mut(rest),
+            None => Some(self),
         }
-        run_instructions
     }
     pub fn with_one_pane() -> Self {
         let mut default_layout = TiledPaneLayout::default();
@@ -484,14 +2161,13 @@ impl TiledPaneLayout {
         }
     }
     pub fn deepest_depth(&self) -> usize {
-        let mut deepest_child_depth = 0;
-        for child in self.children.iter() {
-            let child_deepest_depth = child.deepest_depth();
-            if child_deepest_depth > deepest_child_depth {
-                deepest_child_depth = child_deepest_depth;
-            }
+        let mut deepest_depth = 0;
+        let mut to_visit = vec![(self, 1)];
+        while let Some((pane, depth)) = to_visit.pop() {
+            deepest_depth = deepest_depth.max(depth);
+            to_visit.extend(pane.children.iter().map(|child| (child, depth + 1)));
         }
-        deepest_child_depth + 1
+        deepest_depth
     }
     pub fn focus_deepest_pane(&mut self) {
         let mut deepest_child_index = None;
@@ -514,6 +2190,74 @@ impl TiledPaneLayout {
             },
         }
     }
+    pub fn focus_first_pane(&mut self) {
+        match self.children.first_mut() {
+            Some(first_child) => first_child.focus_first_pane(),
+            None => self.focus = Some(true),
+        }
+    }
+    pub fn focus_last_pane(&mut self) {
+        match self.children.last_mut() {
+            Some(last_child) => last_child.focus_last_pane(),
+            None => self.focus = Some(true),
+        }
+    }
+    /// Focuses the pane taking the most room once this layout is positioned in `space` (the first
+    /// one if several are as large), or the deepest pane if the layout does not fit in it
+    pub fn focus_largest_pane(&mut self, space: &PaneGeom) {
+        let pane_paths = self.extract_pane_paths();
+        let largest_pane_path = split_space(space, self, space)
+            .ok()
+            .filter(|positions| positions.len() == pane_paths.len())
+            .and_then(|positions| {
+                positions
+                    .iter()
+                    .enumerate()
+                    .rev() // max_by_key keeps the last of the largest panes
+                    .max_by_key(|(_, (_, geom))| geom.rows.as_usize() * geom.cols.as_usize())
+                    .map(|(i, _)| i)
+            })
+            .and_then(|i| pane_paths.get(i).cloned());
+        match largest_pane_path.and_then(|path| self.pane_at_path_mut(&path)) {
+            Some(largest_pane) => largest_pane.focus = Some(true),
+            None => self.focus_deepest_pane(),
+        }
+    }
+    pub fn apply_focus_policy(&mut self, focus_policy: FocusPolicy, space: &PaneGeom) {
+        match focus_policy {
+            FocusPolicy::First => self.focus_first_pane(),
+            FocusPolicy::Deepest => self.focus_deepest_pane(),
+            FocusPolicy::Largest => self.focus_largest_pane(space),
+            FocusPolicy::Last => self.focus_last_pane(),
+        }
+    }
+    /// Turns this layout a quarter turn, clockwise or counter-clockwise: panes side by side end up
+    /// one above the other and the other way around, each keeping its size along the other
+    /// dimension. Stacked panes are left as they are since a stack always has its panes one above
+    /// the other, only where the stack is in the layout turns.
+    pub fn rotate(&mut self, clockwise: bool) {
+        if self.children.is_empty() || self.children_are_stacked {
+            return;
+        }
+        // turning clockwise takes the top of the layout to its right and its left to its top, so
+        // panes above one another end up from right to left
+        let reverse_children = match self.children_split_direction {
+            SplitDirection::Horizontal => clockwise,
+            SplitDirection::Vertical => !clockwise,
+            // the panes of an automatic split are turned with the space they are split in
+            SplitDirection::Auto => false,
+        };
+        self.children_split_direction = !self.children_split_direction;
+        if reverse_children {
+            self.children.reverse();
+            if let Some(external_children_index) = self.external_children_index.as_mut() {
+                *external_children_index = self.children.len() - *external_children_index;
+            }
+        }
+        for child in self.children.iter_mut() {
+            child.rotate(clockwise);
+        }
+    }
     pub fn truncate(&mut self, max_panes: usize) -> usize {
         // returns remaining children length
         // if max_panes is 1, it means there's only enough panes for this node,
@@ -521,8 +2265,30 @@ impl TiledPaneLayout {
         if max_panes <= 1 {
             self.children.clear();
         } else if max_panes <= self.children.len() {
-            self.children.truncate(max_panes);
-            self.children.iter_mut().for_each(|l| l.children.clear());
+            // the focused pane is kept first, then the panes running a command or a plugin, and
+            // only then the others in the order they are in
+            let mut ranked_children: Vec<usize> = (0..self.children.len()).collect();
+            ranked_children.sort_by_key(|i| std::cmp::Reverse(self.children[*i].truncation_rank()));
+            let kept_children: BTreeSet<usize> =
+                ranked_children.into_iter().take(max_panes).collect();
+            if let Some(external_children_index) = self.external_children_index.as_mut() {
+                *external_children_index = kept_children
+                    .iter()
+                    .filter(|i| **i < *external_children_index)
+                    .count();
+            }
+            let mut child_index = 0;
+            self.children.retain(|_child| {
+                child_index += 1;
+                kept_children.contains(&(child_index - 1))
+            });
+            for child in self.children.iter_mut() {
+                // a container cut down to a single pane stands in for the focused pane in it
+                if !child.children.is_empty() && child.has_focused_node() {
+                    child.focus = Some(true);
+                }
+                child.children.clear();
+            }
         } else {
             let mut remaining_panes = max_panes
                 - self
@@ -545,6 +2311,140 @@ impl TiledPaneLayout {
             1 // just me
         }
     }
+    // how much truncating should try to keep this pane: the focused pane first, then the panes
+    // running a command or a plugin before bare ones
+    fn truncation_rank(&self) -> (bool, bool) {
+        let runs_something = self.children.is_empty()
+            && matches!(self.run, Some(Run::Command(_)) | Some(Run::Plugin(_)));
+        (self.has_focused_node(), runs_something)
+    }
+    fn add_extra_panes(&mut self, extra_panes_policy: ExtraPanesPolicy, extra_pane_count: usize) {
+        if extra_pane_count == 0 {
+            return;
+        }
+        let mut extra_panes = vec![TiledPaneLayout::default(); extra_pane_count];
+        if !self.has_focused_node() {
+            if let Some(last_pane) = extra_panes.last_mut() {
+                last_pane.focus = Some(true);
+            }
+        }
+        let anchor_path = match extra_panes_policy {
+            ExtraPanesPolicy::AppendAfterFocused => self
+                .focused_pane_path()
+                .or_else(|| self.last_terminal_pane_path()),
+            ExtraPanesPolicy::StackLast | ExtraPanesPolicy::NewStack => {
+                self.last_terminal_pane_path()
+            },
+        };
+        let anchor_path = match anchor_path {
+            Some(anchor_path) if !anchor_path.is_empty() => anchor_path,
+            _ => {
+                // this layout is a single pane, so we add the panes next to it
+                let mut single_pane = self.clone();
+                single_pane.split_size = None;
+                self.children = vec![single_pane];
+                self.run = None;
+                self.name = None;
+                self.focus = None;
+                vec![0]
+            },
+        };
+        let (index_in_parent, parent_path) = match anchor_path.split_last() {
+            Some((index_in_parent, parent_path)) => (*index_in_parent, parent_path),
+            None => return,
+        };
+        let parent = match self.node_at_path_mut(parent_path) {
+            Some(parent) => parent,
+            None => return,
+        };
+        match extra_panes_policy {
+            ExtraPanesPolicy::StackLast | ExtraPanesPolicy::NewStack
+                if parent.children_are_stacked =>
+            {
+                // we're already in a stack, no need to create another one
+                for (i, extra_pane) in extra_panes.drain(..).enumerate() {
+                    parent.children.insert(index_in_parent + 1 + i, extra_pane);
+                }
+            },
+            ExtraPanesPolicy::StackLast => {
+                let mut anchor = parent.children.remove(index_in_parent);
+                let stack_size = anchor.split_size.take();
+                let mut stacked_panes = vec![anchor];
+                stacked_panes.append(&mut extra_panes);
+                let stack = TiledPaneLayout {
+                    children: stacked_panes,
+                    children_are_stacked: true,
+                    split_size: stack_size,
+                    ..Default::default()
+                };
+                parent.children.insert(index_in_parent, stack);
+            },
+            ExtraPanesPolicy::AppendAfterFocused => {
+                for (i, extra_pane) in extra_panes.drain(..).enumerate() {
+                    parent.children.insert(index_in_parent + 1 + i, extra_pane);
+                }
+            },
+            ExtraPanesPolicy::NewStack => {
+                let stack = if extra_panes.len() == 1 {
+                    extra_panes.remove(0)
+                } else {
+                    TiledPaneLayout {
+                        children: extra_panes,
+                        children_are_stacked: true,
+                        ..Default::default()
+                    }
+                };
+                parent.children.insert(index_in_parent + 1, stack);
+            },
+        }
+    }
+    fn focused_pane_path(&self) -> Option<Vec<usize>> {
+        // the path of child indices leading to the focused pane
+        if self.children.is_empty() {
+            return if self.focus == Some(true) {
+                Some(vec![])
+            } else {
+                None
+            };
+        }
+        for (i, child) in self.children.iter().enumerate() {
+            if let Some(mut path) = child.focused_pane_path() {
+                path.insert(0, i);
+                return Some(path);
+            }
+        }
+        None
+    }
+    fn last_terminal_pane_path(&self) -> Option<Vec<usize>> {
+        // the path of child indices leading to the last pane that is not a plugin (eg. a tab-bar),
+        // or to the last pane if all of them are plugins
+        fn last_pane_path(layout: &TiledPaneLayout, skip_plugins: bool) -> Option<Vec<usize>> {
+            if layout.children.is_empty() {
+                return if skip_plugins && !Run::is_terminal(&layout.run) {
+                    None
+                } else {
+                    Some(vec![])
+                };
+            }
+            for (i, child) in layout.children.iter().enumerate().rev() {
+                if let Some(mut path) = last_pane_path(child, skip_plugins) {
+                    path.insert(0, i);
+                    return Some(path);
+                }
+            }
+            None
+        }
+        last_pane_path(self, true).or_else(|| last_pane_path(self, false))
+    }
+    fn node_at_path_mut(&mut self, path: &[usize]) -> Option<&mut TiledPaneLayout> {
+        match path.split_first() {
+            Some((index, rest)) => self
+                .children
+                .get_mut(*index)
+                .and_then(|child| child.node_at_path_mut(rest)),
+            None => Some(self),
+        }
+    }
     pub fn has_focused_node(&self) -> bool {
         if self.focus.map(|f| f).unwrap_or(false) {
             return true;
@@ -556,7 +2456,64 @@ impl TiledPaneLayout {
         }
         false
     }
-}
+    /// How many of the panes and containers of this layout are marked `focus true`
+    pub fn focused_node_count(&self) -> usize {
+        let own_focus = if self.focus.unwrap_or(false) { 1 } else { 0 };
+        own_focus
+            + self
+                .children
+                .iter()
+                .map(|child| child.focused_node_count())
+                .sum::<usize>()
+    }
+    /// Moves the focus of the containers marked `focus true` to their first pane, since only the
+    /// panes a layout is laid out as can be focused
+    pub fn resolve_container_focus(&mut self) {
+        let mut to_visit = vec![self];
+        while let Some(pane) = to_visit.pop() {
+            if pane.focus == Some(true) && !pane.children.is_empty() {
+                pane.focus = None;
+                if !pane.has_focused_node() {
+                    pane.focus_first_pane();
+                }
+            }
+            to_visit.extend(pane.children.iter_mut());
+        }
+    }
+}
+
+/// How well an existing pane fits a slot of a layout when the panes of a tab are given the slots
+/// of a swap layout, from the best fit to the worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RunMatch {
+    SameCategory,
+    SameName,
+    Compatible,   // both run in a terminal (eg. an editor in a slot meant for a command)
+    Incompatible, // a plugin and a terminal, only used when no other pane is left for the slot
+}
+
+/// How well a pane running `pane_run` and named `pane_name` fits a layout slot running `slot_run`
+/// and named `slot_name`
+pub fn run_match(
+    slot_run: &Option<Run>,
+    slot_name: Option<&str>,
+    pane_run: &Option<Run>,
+    pane_name: Option<&str>,
+) -> RunMatch {
+    let names_match = match (slot_name, pane_name) {
+        (Some(slot_name), Some(pane_name)) => !slot_name.is_empty() && slot_name == pane_name,
+        _ => false,
+    };
+    if Run::is_same_category(slot_run, pane_run) {
+        RunMatch::SameCategory
+    } else if names_match && Run::is_compatible(slot_run, pane_run) {
+        RunMatch::SameName
+    } else if Run::is_compatible(slot_run, pane_run) {
+        RunMatch::Compatible
+    } else {
+        RunMatch::Incompatible
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum LayoutParts {
@@ -592,249 +2549,2319 @@ impl Default for LayoutParts {
     }
 }
 
+// the first of "name (2)", "name (3)"... that no other tab is called, or the name itself if it
+// is not taken
+fn disambiguated_tab_name(tab_name: String, taken_names: &[&str]) -> String {
+    if !taken_names.contains(&tab_name.as_str()) {
+        return tab_name;
+    }
+    let mut suffix = 2;
+    loop {
+        let candidate = format!("{} ({})", tab_name, suffix);
+        if !taken_names.contains(&candidate.as_str()) {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+/// Where the pieces of a [`Layout`] were loaded from, so that errors found at runtime can point
+/// the user at the right file, and how long loading them took
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct LayoutSources {
+    pub name: String, // the layout as the user asked for it: an alias (eg. "default") or a full path
+    pub main_layout: String, // a path, or the name of the default asset
+    pub swap_layouts: Option<String>,
+    pub from_default_assets: bool,
+    pub resolution_chain: Vec<String>, // eg. the layout name as it was requested by the user
+    pub files: Vec<LayoutFile>,        // more than one for layouts opened together
+    pub load_stats: LayoutLoadStats,
+    pub variables: LayoutVariables, // the ones given when opening the layout, see `vars`
+}
+
+impl LayoutSources {
+    /// The file the main layout was read from, next to which the files it includes are looked
+    /// for. `None` for the built-in layouts and the ones fetched from a URL or read from stdin,
+    /// whose includes can only come from the layout dir
+    pub fn main_layout_file(&self) -> Option<&Path> {
+        let main_layout = Path::new(&self.main_layout);
+        let read_from_file = !self.from_default_assets
+            && self.name != STDIN_LAYOUT_NAME
+            && !is_layout_url(main_layout);
+        Some(main_layout).filter(|_| read_from_file)
+    }
+    /// The files the layout is read again from when they change (see [`Layout::reload`]): the
+    /// main layout file, its swap layouts and the files it includes. `None` for the layouts not
+    /// read from a single file (eg. the built-in ones, or several layouts opened together)
+    pub fn watched_files(&self) -> Option<Vec<PathBuf>> {
+        let main_layout_file = self.main_layout_file().filter(|path| path.is_file())?;
+        let mut watched_files = vec![main_layout_file.to_path_buf()];
+        watched_files.extend(self.swap_layouts.iter().map(PathBuf::from));
+        // the first file is the main layout (along with its swap layouts), the others are those
+        // it includes
+        watched_files.extend(
+            self.files
+                .iter()
+                .skip(1)
+                .filter(|layout_file| !layout_file.from_default_assets)
+                .map(|layout_file| PathBuf::from(&layout_file.path)),
+        );
+        Some(watched_files)
+    }
+}
+
+/// A layout file (with its swap layouts) a [`Layout`] was read from, to tell whether the commands
+/// it runs can be trusted, see [`layout_trust`](crate::input::layout_trust)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct LayoutFile {
+    pub path: String, // a path, or the name of the default asset
+    pub from_default_assets: bool,
+    pub content_hash: String,
+}
+
+/// A layout that can be opened by name, see [`Layout::list_available`]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LayoutInfo {
+    pub name: String,
+    pub source: LayoutInfoSource,
+    pub has_swap_layouts: bool, // whether it has a `.swap.kdl` companion
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum LayoutInfoSource {
+    BuiltIn,
+    File(PathBuf),
+}
+
+/// A layout shipped with zellij, see [`BUILTIN_LAYOUTS`]
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinLayout {
+    pub description: &'static str,
+    pub layout: &'static [u8],
+    pub swap_layout: Option<(&'static str, &'static [u8])>, // (description, swap layout)
+}
+
+lazy_static! {
+    /// The layouts shipped with zellij, by the name they are opened with
+    pub static ref BUILTIN_LAYOUTS: BTreeMap<&'static str, BuiltinLayout> = BTreeMap::from([
+        (
+            "default",
+            BuiltinLayout {
+                description: "Default layout",
+                layout: setup::DEFAULT_LAYOUT,
+                swap_layout: Some(("Default swap layout", setup::DEFAULT_SWAP_LAYOUT)),
+            },
+        ),
+        (
+            "strider",
+            BuiltinLayout {
+                description: "Strider layout",
+                layout: setup::STRIDER_LAYOUT,
+                swap_layout: Some(("Strider swap layout", setup::STRIDER_SWAP_LAYOUT)),
+            },
+        ),
+        (
+            "disable-status-bar",
+            BuiltinLayout {
+                description: "Disable Status Bar layout",
+                layout: setup::NO_STATUS_LAYOUT,
+                swap_layout: None,
+            },
+        ),
+        (
+            "compact",
+            BuiltinLayout {
+                description: "Compact layout",
+                layout: setup::COMPACT_BAR_LAYOUT,
+                swap_layout: Some(("Compact layout swap", setup::COMPACT_BAR_SWAP_LAYOUT)),
+            },
+        ),
+    ]);
+}
+
+impl LayoutSources {
+    /// The source of the layout parts parsed from the main file or from the swap companion file
+    pub fn source(&self, in_swap_layout_file: bool) -> &str {
+        if in_swap_layout_file {
+            self.swap_layouts.as_deref().unwrap_or(&self.main_layout)
+        } else {
+            &self.main_layout
+        }
+    }
+    /// The sources of the layouts combined with [`Layout::concatenate`], in order
+    pub fn concatenate(self, other: LayoutSources) -> LayoutSources {
+        let join = |a: String, b: String| format!("{} + {}", a, b);
+        LayoutSources {
+            name: join(self.name, other.name),
+            main_layout: join(self.main_layout, other.main_layout),
+            swap_layouts: match (self.swap_layouts, other.swap_layouts) {
+                (Some(a), Some(b)) => Some(join(a, b)),
+                (a, b) => a.or(b),
+            },
+            from_default_assets: self.from_default_assets && other.from_default_assets,
+            resolution_chain: self
+                .resolution_chain
+                .into_iter()
+                .chain(other.resolution_chain)
+                .collect(),
+            files: self.files.into_iter().chain(other.files).collect(),
+            load_stats: self.load_stats.combined_with(other.load_stats),
+            variables: self.variables,
+        }
+    }
+}
+
+impl fmt::Display for LayoutSources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for link in &self.resolution_chain {
+            write!(f, "{} -> ", link)?;
+        }
+        write!(f, "{}", self.main_layout)?;
+        if let Some(swap_layouts) = &self.swap_layouts {
+            write!(f, " + {}", swap_layouts)?;
+        }
+        if self.from_default_assets {
+            write!(f, " (built-in)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The layout path meaning the layout is read from stdin, eg. `zellij --layout -`
+pub const LAYOUT_FROM_STDIN: &str = "-";
+/// What a layout read from stdin is called in errors
+pub const STDIN_LAYOUT_NAME: &str = "stdin";
+
+#[cfg(not(target_family = "wasm"))]
+fn is_layout_url(layout_path: &Path) -> bool {
+    layout_url(layout_path).is_some()
+}
+
+#[cfg(target_family = "wasm")]
+fn is_layout_url(_layout_path: &Path) -> bool {
+    false
+}
+
 impl Layout {
     pub fn stringified_from_path_or_default(
         layout_path: Option<&PathBuf>,
         layout_dir: Option<PathBuf>,
-    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
-        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
-        match layout_path {
+    ) -> Result<(String, String, Option<(String, String)>, LayoutSources), ConfigError> {
+        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>, LayoutSources)
+        let mut layout_sources = LayoutSources::default();
+        let (path_to_raw_layout, raw_layout, raw_swap_layouts) = match layout_path {
+            #[cfg(not(target_family = "wasm"))]
+            Some(layout_path) if layout_path == Path::new(LAYOUT_FROM_STDIN) => {
+                layout_sources.name = String::from(STDIN_LAYOUT_NAME);
+                Layout::stringified_from_stdin()?
+            },
+            #[cfg(not(target_family = "wasm"))]
+            Some(layout_path) if is_layout_url(layout_path) => {
+                let url = layout_url(layout_path).unwrap();
+                layout_sources.name = url.to_string();
+                stringified_from_url(&url)?
+            },
             Some(layout_path) => {
                 // The way we determine where to look for the layout is similar to
                 // how a path would look for an executable.
                 // See the gh issue for more: https://github.com/zellij-org/zellij/issues/1412#issuecomment-1131559720
                 if layout_path.extension().is_some() || layout_path.components().count() > 1 {
                     // We look localy!
-                    Layout::stringified_from_path(layout_path)
+                    layout_sources.name = Layout::canonical_layout_path(layout_path)
+                        .display()
+                        .to_string();
+                    Layout::stringified_from_path(layout_path)?
                 } else {
                     // We look in the default dir
-                    Layout::stringified_from_dir(layout_path, layout_dir.as_ref())
+                    layout_sources.name = layout_path.display().to_string();
+                    layout_sources
+                        .resolution_chain
+                        .push(format!("alias '{}'", layout_path.display()));
+                    layout_sources.from_default_assets =
+                        !Layout::layout_dir_contains(layout_path, layout_dir.as_ref());
+                    Layout::stringified_from_dir(layout_path, layout_dir.as_ref())?
                 }
             },
-            None => Layout::stringified_from_dir(
-                &std::path::PathBuf::from("default"),
-                layout_dir.as_ref(),
+            None => {
+                let default_layout = PathBuf::from("default");
+                layout_sources.name = default_layout.display().to_string();
+                layout_sources
+                    .resolution_chain
+                    .push(String::from("alias 'default'"));
+                layout_sources.from_default_assets =
+                    !Layout::layout_dir_contains(&default_layout, layout_dir.as_ref());
+                Layout::stringified_from_dir(&default_layout, layout_dir.as_ref())?
+            },
+        };
+        layout_sources.main_layout = path_to_raw_layout.clone();
+        layout_sources.swap_layouts = raw_swap_layouts
+            .as_ref()
+            .map(|(path_to_swap_layout, _)| path_to_swap_layout.clone());
+        layout_sources.files = vec![LayoutFile {
+            path: path_to_raw_layout.clone(),
+            from_default_assets: layout_sources.from_default_assets,
+            content_hash: layout_content_hash(
+                &raw_layout,
+                raw_swap_layouts
+                    .as_ref()
+                    .map(|(_, raw_swap_layouts)| raw_swap_layouts.as_str()),
             ),
-        }
+        }];
+        Ok((
+            path_to_raw_layout,
+            raw_layout,
+            raw_swap_layouts,
+            layout_sources,
+        ))
     }
+    /// Loads the layout with the values given to its variables (see
+    /// [`layout_variables`](crate::input::layout_variables))
     pub fn from_path_or_default(
         layout_path: Option<&PathBuf>,
         layout_dir: Option<PathBuf>,
         config: Config,
-    ) -> Result<(Layout, Config), ConfigError> {
+        layout_variables: &LayoutVariables,
+    ) -> Result<(Layout, Config, LayoutSources), ConfigError> {
+        let (path_to_raw_layout, raw_layout, raw_swap_layouts, mut layout_sources) =
+            Layout::stringified_from_path_or_default(layout_path, layout_dir.clone())?;
+        let (raw_layout, raw_swap_layouts) = Layout::with_variables(
+            &raw_layout,
+            &path_to_raw_layout,
+            raw_swap_layouts,
+            layout_variables,
+        )?;
+        layout_sources.variables = layout_variables.clone();
+        let validation_options =
+            ValidationOptions::strict().with_limits(config.options.layout_limits());
+        let (layout_templates, included_files) = Layout::resolve_includes(
+            &raw_layout,
+            &path_to_raw_layout,
+            layout_sources.main_layout_file(),
+            layout_dir.as_deref(),
+            validation_options,
+            &config.layout_templates,
+        )?;
+        layout_sources.files.extend(included_files);
+        let (mut layout, warnings, load_stats) = Layout::from_kdl_with_stats(
+            &raw_layout,
+            path_to_raw_layout,
+            raw_swap_layouts
+                .as_ref()
+                .map(|(r, f)| (r.as_str(), f.as_str())),
+            None,
+            validation_options,
+            &layout_templates,
+        )?;
+        for warning in warnings {
+            log::warn!("{}", warning);
+        }
+        load_stats.log_if_slow(&layout_sources.name);
+        layout_sources.load_stats = load_stats;
+        let (raw_layout, _had_byte_order_mark) = normalize_layout_source(&raw_layout);
+        // the cap is set by the user (or the admin) on the layouts they open, so the layouts
+        // themselves cannot raise it
+        let max_layout_commands = config.options.max_layout_commands;
+        // this merges the two configs, with the one in the layout taking precedence
+        let mut config = Config::from_kdl(&raw_layout, Some(config)).map_err(|e| match e {
+            ConfigError::KdlError(kdl_error) if kdl_error.src.is_none() => ConfigError::KdlError(
+                kdl_error.add_src(layout_sources.main_layout.clone(), raw_layout.to_string()),
+            ),
+            e => e,
+        })?;
+        config.options.max_layout_commands = max_layout_commands;
+        layout.configure_strider_panes(
+            config.options.strider_pane_width,
+            config.options.strider_stack_below_width,
+        );
+        Ok((layout, config, layout_sources))
+    }
+    /// Reads the layout at `layout_path` again (along with its swap layouts and the files it
+    /// includes), eg. once it was changed while a session opened with it is running. Unlike
+    /// [`Layout::from_path_or_default`], the configuration in the layout and the templates of the
+    /// config are left out
+    pub fn reload(
+        layout_path: &Path,
+        layout_dir: Option<&Path>,
+        validation_options: ValidationOptions,
+        layout_variables: &LayoutVariables,
+    ) -> Result<Layout, ConfigError> {
         let (path_to_raw_layout, raw_layout, raw_swap_layouts) =
-            Layout::stringified_from_path_or_default(layout_path, layout_dir)?;
-        let layout = Layout::from_kdl(
+            Layout::stringified_from_path(layout_path)?;
+        let (raw_layout, raw_swap_layouts) = Layout::with_variables(
+            &raw_layout,
+            &path_to_raw_layout,
+            raw_swap_layouts,
+            layout_variables,
+        )?;
+        let (layout_templates, _included_files) = Layout::resolve_includes(
+            &raw_layout,
+            &path_to_raw_layout,
+            Some(layout_path),
+            layout_dir,
+            validation_options,
+            &LayoutTemplates::default(),
+        )?;
+        let (layout, warnings, _load_stats) = Layout::from_kdl_with_stats(
             &raw_layout,
             path_to_raw_layout,
             raw_swap_layouts
                 .as_ref()
                 .map(|(r, f)| (r.as_str(), f.as_str())),
             None,
+            validation_options,
+            &layout_templates,
         )?;
-        let config = Config::from_kdl(&raw_layout, Some(config))?; // this merges the two config, with
-        Ok((layout, config))
+        for warning in warnings {
+            log::warn!("{}", warning);
+        }
+        Ok(layout)
+    }
+    /// Loads each of the layouts like [`Layout::from_path_or_default`] and combines them with
+    /// [`Layout::concatenate`]. The configuration in each layout file applies over the one in the
+    /// files before it. An error names the layout it was found in.
+    pub fn from_paths_or_default(
+        layout_paths: &[PathBuf],
+        layout_dir: Option<PathBuf>,
+        config: Config,
+        layout_variables: &LayoutVariables,
+    ) -> Result<(Layout, Config, LayoutSources), ConfigError> {
+        let (first_layout_path, other_layout_paths) = match layout_paths.split_first() {
+            Some((first_layout_path, other_layout_paths)) => {
+                (Some(first_layout_path), other_layout_paths)
+            },
+            None => (None, layout_paths),
+        };
+        // with a single layout, the error cannot be about another one
+        let in_layout = |layout_path: Option<&PathBuf>, e: ConfigError| match layout_path {
+            Some(layout_path) if layout_paths.len() > 1 => e.in_layout_file(layout_path),
+            _ => e,
+        };
+        let (mut layout, mut config, mut layout_sources) = Layout::from_path_or_default(
+            first_layout_path,
+            layout_dir.clone(),
+            config,
+            layout_variables,
+        )
+        .map_err(|e| in_layout(first_layout_path, e))?;
+        for layout_path in other_layout_paths {
+            let (other_layout, other_config, other_layout_sources) = Layout::from_path_or_default(
+                Some(layout_path),
+                layout_dir.clone(),
+                config,
+                layout_variables,
+            )
+            .map_err(|e| in_layout(Some(layout_path), e))?;
+            layout = layout.concatenate(other_layout);
+            config = other_config;
+            layout_sources = layout_sources.concatenate(other_layout_sources);
+        }
+        Ok((layout, config, layout_sources))
+    }
+    /// Parses the layout with the values given to its variables (see
+    /// [`layout_variables`](crate::input::layout_variables)), none for the defaults of its `vars`
+    /// block
+    pub fn from_str(
+        raw: &str,
+        path_to_raw_layout: String,
+        swap_layouts: Option<(&str, &str)>, // Option<path_to_swap_layout, stringified_swap_layout>
+        cwd: Option<PathBuf>,
+        layout_variables: &LayoutVariables,
+    ) -> Result<Layout, ConfigError> {
+        if is_yaml_layout(raw, Some(Path::new(&path_to_raw_layout))) {
+            return Err(ConfigError::YamlLayout(path_to_raw_layout.into()));
+        }
+        let (raw, swap_layouts) = Layout::with_variables(
+            raw,
+            &path_to_raw_layout,
+            swap_layouts
+                .map(|(path, raw_swap_layouts)| (path.to_owned(), raw_swap_layouts.to_owned())),
+            layout_variables,
+        )?;
+        Layout::from_kdl(
+            &raw,
+            path_to_raw_layout,
+            swap_layouts
+                .as_ref()
+                .map(|(path, raw_swap_layouts)| (path.as_str(), raw_swap_layouts.as_str())),
+            cwd,
+        )
+    }
+    /// The layout and its swap layouts with the variables of the layout substituted in them (see
+    /// [`layout_variables`](crate::input::layout_variables))
+    pub fn with_variables(
+        raw_layout: &str,
+        path_to_raw_layout: &str,
+        raw_swap_layouts: Option<(String, String)>, // Option<path_to_swap_layout, stringified_swap_layout>
+        layout_variables: &LayoutVariables,
+    ) -> Result<(String, Option<(String, String)>), ConfigError> {
+        let variables = resolve_layout_variables(raw_layout, path_to_raw_layout, layout_variables)?;
+        let raw_swap_layouts = raw_swap_layouts.map(|(path, raw_swap_layouts)| {
+            (
+                path,
+                substitute_layout_variables(&raw_swap_layouts, &variables),
+            )
+        });
+        Ok((
+            substitute_layout_variables(raw_layout, &variables),
+            raw_swap_layouts,
+        ))
+    }
+    /// A hash of what this layout opens, to tell whether it is the same layout as one seen
+    /// before. Two layouts have the same fingerprint when they are equal, so the comments and
+    /// the formatting of the text they were parsed from do not change it, nor does the order
+    /// properties are written in or where the layout was loaded from. Any change to what a pane,
+    /// tab or swap layout is declared with does. It is that of the layout as loaded, so it also
+    /// changes when a pane is dropped because its `if_env` condition fails.
+    pub fn fingerprint(&self) -> String {
+        // the fields of the layout in the order they are declared, the maps in the order of their
+        // keys. Serializing cannot fail: none of the types in a layout refuse to be serialized
+        let serialized_layout = rmp_serde::to_vec(self).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(&serialized_layout);
+        format!("{:x}", hasher.finalize())
+    }
+    /// A hash of the text of a layout file, before it is parsed: unlike
+    /// [`Layout::fingerprint`], any change to the text changes it, even to a comment. This is
+    /// what the layouts the user trusts are remembered by (see
+    /// [`layout_trust`](crate::input::layout_trust)), as a comment can hide what a layout runs
+    /// from a quick look at it just as well as any other change.
+    pub fn fingerprint_of_source(raw: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(raw.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+    pub fn stringified_from_dir(
+        layout: &PathBuf,
+        layout_dir: Option<&PathBuf>,
+    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
+        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
+        match layout_dir {
+            Some(dir) if Layout::layout_dir_contains(layout, Some(dir)) => {
+                Self::stringified_from_path(&dir.join(layout))
+            },
+            _ => Layout::stringified_from_default_assets(layout),
+        }
+    }
+    /// The absolute path of a layout file, so that the same layout can be recognized no matter
+    /// which directory it was loaded from
+    pub fn canonical_layout_path(layout_path: &Path) -> PathBuf {
+        std::fs::canonicalize(layout_path)
+            .or_else(|_| std::fs::canonicalize(layout_path.with_extension("kdl")))
+            .unwrap_or_else(|_| layout_path.to_path_buf())
+    }
+    fn layout_dir_contains(layout: &Path, layout_dir: Option<&PathBuf>) -> bool {
+        layout_dir
+            .map(|dir| dir.join(layout).with_extension("kdl").exists())
+            .unwrap_or(false)
+    }
+    pub fn stringified_from_path(
+        layout_path: &Path,
+    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
+        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
+        let (opened_path, mut layout_file) = match File::open(&layout_path) {
+            Ok(layout_file) => (layout_path.to_path_buf(), layout_file),
+            Err(e) => {
+                let with_kdl_extension = layout_path.with_extension("kdl");
+                match File::open(&with_kdl_extension) {
+                    Ok(layout_file) => (with_kdl_extension, layout_file),
+                    Err(_) => {
+                        let e = Layout::dangling_link_error(layout_path)
+                            .or_else(|| Layout::dangling_link_error(&with_kdl_extension))
+                            .unwrap_or(e);
+                        return Err(ConfigError::IoPath(e, layout_path.into()));
+                    },
+                }
+            },
+        };
+
+        let mut kdl_layout = String::new();
+        layout_file.read_to_string(&mut kdl_layout)?;
+        if is_yaml_layout(&kdl_layout, Some(&opened_path)) {
+            return Err(ConfigError::YamlLayout(opened_path));
+        }
+        let swap_layout_and_path = Layout::swap_layout_and_path(&opened_path);
+        Ok((
+            layout_path.as_os_str().to_string_lossy().into(),
+            kdl_layout,
+            swap_layout_and_path,
+        ))
+    }
+    /// Reads a layout from stdin to its end (see [`LAYOUT_FROM_STDIN`]), it has no swap layouts
+    pub fn stringified_from_stdin(
+    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
+        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
+        Layout::stringified_from_reader(std::io::stdin().lock(), STDIN_LAYOUT_NAME)
+    }
+    fn stringified_from_reader(
+        mut reader: impl Read,
+        name: &str,
+    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
+        let mut kdl_layout = String::new();
+        reader
+            .read_to_string(&mut kdl_layout)
+            .map_err(|e| ConfigError::IoPath(e, name.into()))?;
+        if kdl_layout.trim().is_empty() {
+            return Err(ConfigError::IoPath(
+                std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "The layout is empty, nothing was read",
+                ),
+                name.into(),
+            ));
+        }
+        Ok((name.to_owned(), kdl_layout, None))
+    }
+    pub fn stringified_from_default_assets(
+        path: &Path,
+    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
+        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
+        let builtin_layout = path
+            .to_str()
+            .and_then(|name| BUILTIN_LAYOUTS.get(name))
+            .ok_or_else(|| {
+                let builtin_layout_names: Vec<&str> = BUILTIN_LAYOUTS.keys().copied().collect();
+                let error_message = format!(
+                    "The layout was not found, the built-in layouts are: {}",
+                    builtin_layout_names.join(", ")
+                );
+                ConfigError::IoPath(
+                    std::io::Error::new(std::io::ErrorKind::Other, error_message),
+                    path.into(),
+                )
+            })?;
+        let swap_layout = match builtin_layout.swap_layout {
+            Some((swap_description, swap_layout)) => Some((
+                swap_description.to_owned(),
+                String::from_utf8(swap_layout.to_vec())?,
+            )),
+            None => None,
+        };
+        Ok((
+            builtin_layout.description.to_owned(),
+            String::from_utf8(builtin_layout.layout.to_vec())?,
+            swap_layout,
+        ))
+    }
+    /// The layouts that can be opened by name, sorted by name: the `.kdl` files of `layout_dir`
+    /// and the built-in layouts they do not shadow. The files are not read, so that this stays
+    /// cheap enough to build pickers and completions with.
+    pub fn list_available(layout_dir: Option<&PathBuf>) -> Vec<LayoutInfo> {
+        let mut available_layouts: BTreeMap<String, LayoutInfo> = BUILTIN_LAYOUTS
+            .iter()
+            .map(|(name, builtin_layout)| {
+                let layout_info = LayoutInfo {
+                    name: name.to_string(),
+                    source: LayoutInfoSource::BuiltIn,
+                    has_swap_layouts: builtin_layout.swap_layout.is_some(),
+                };
+                (name.to_string(), layout_info)
+            })
+            .collect();
+        let layout_dir_entries = layout_dir.and_then(|dir| std::fs::read_dir(dir).ok());
+        for entry in layout_dir_entries.into_iter().flatten().flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let name = match file_name.strip_suffix(".kdl") {
+                // swap layout files are companions of the layout with their name
+                Some(name) if !name.is_empty() && !name.ends_with(".swap") => name,
+                _ => continue,
+            };
+            if !path.is_file() {
+                continue;
+            }
+            let has_swap_layouts = Layout::swap_layout_path(&path).is_file();
+            let layout_info = LayoutInfo {
+                name: name.to_owned(),
+                source: LayoutInfoSource::File(path),
+                has_swap_layouts,
+            };
+            available_layouts.insert(name.to_owned(), layout_info);
+        }
+        available_layouts.into_values().collect()
+    }
+    /// Gives the panes running the strider plugin this width and stacks them with the panes next
+    /// to them below `stack_below_width` columns, in all tabs and swap layouts
+    pub fn configure_strider_panes(
+        &mut self,
+        width: Option<SplitSize>,
+        stack_below_width: Option<usize>,
+    ) {
+        let tiled_layouts = self
+            .tabs
+            .iter_mut()
+            .map(|(_tab_name, tiled_panes, ..)| tiled_panes)
+            .chain(self.template.iter_mut().map(|(tiled_panes, _)| tiled_panes))
+            .chain(
+                self.tab_templates
+                    .values_mut()
+                    .map(|(tiled_panes, _)| tiled_panes),
+            );
+        for tiled_panes in tiled_layouts {
+            tiled_panes.configure_strider_panes(width, stack_below_width);
+        }
+        for (swap_layouts, _name, _tags) in self.swap_tiled_layouts_mut() {
+            for tiled_panes in swap_layouts.values_mut() {
+                tiled_panes.configure_strider_panes(width, stack_below_width);
+            }
+        }
+    }
+    /// Turns the empty containers of all tabs and swap layouts into plain panes, see
+    /// [`TiledPaneLayout::normalize_empty_containers`], and moves the focus of the focused
+    /// containers of swap layouts to one of their panes
+    pub fn normalize_empty_containers(&mut self) {
+        let tiled_layouts = self
+            .tabs
+            .iter_mut()
+            .map(|(_tab_name, tiled_panes, ..)| tiled_panes)
+            .chain(self.template.iter_mut().map(|(tiled_panes, _)| tiled_panes))
+            .chain(
+                self.tab_templates
+                    .values_mut()
+                    .map(|(tiled_panes, _)| tiled_panes),
+            );
+        for tiled_panes in tiled_layouts {
+            tiled_panes.normalize_empty_containers();
+        }
+        for (swap_layouts, _name, _tags) in self.swap_tiled_layouts_mut() {
+            for tiled_panes in swap_layouts.values_mut() {
+                tiled_panes.normalize_empty_containers();
+                tiled_panes.resolve_container_focus();
+            }
+        }
+    }
+    /// Makes the commands of all tabs, the side panel and the swap layouts wait for the user to
+    /// start them, eg. because the user did not trust the layout to run them
+    pub fn suspend_commands(&mut self) {
+        let tabs = self
+            .tabs
+            .iter_mut()
+            .map(|(_tab_name, tiled_panes, floating_panes, ..)| (tiled_panes, floating_panes));
+        let templates = self
+            .template
+            .iter_mut()
+            .chain(self.tab_templates.values_mut())
+            .map(|(tiled_panes, floating_panes)| (tiled_panes, floating_panes));
+        for (tiled_panes, floating_panes) in tabs.chain(templates) {
+            tiled_panes.suspend_commands();
+            for floating_pane in floating_panes.iter_mut() {
+                if let Some(run) = floating_pane.run.as_mut() {
+                    run.add_start_suspended(Some(true));
+                }
+            }
+        }
+        if let Some(side_panel) = self.side_panel.as_mut() {
+            side_panel.pane.suspend_commands();
+        }
+        for (swap_layouts, _name, _tags) in self.swap_tiled_layouts_mut() {
+            for tiled_panes in swap_layouts.values_mut() {
+                tiled_panes.suspend_commands();
+            }
+        }
+        for (swap_layouts, _name, _tags) in self.swap_floating_layouts_mut() {
+            for floating_pane in swap_layouts.values_mut().flatten() {
+                if let Some(run) = floating_pane.run.as_mut() {
+                    run.add_start_suspended(Some(true));
+                }
+            }
+        }
+    }
+    /// Drops the tabs and panes whose `if_env` variable is not set (see [`PaneConditions`]),
+    /// returning whether each of those with such a condition was kept
+    pub fn apply_env_conditions(&mut self) -> Vec<ConditionOutcome> {
+        self.apply_conditions(&mut check_env_condition)
+    }
+    /// Drops the tabs and panes whose `if_command_exists` command is not found by
+    /// `command_exists` (see [`PaneConditions`]), returning whether each of those with such a
+    /// condition was kept
+    pub fn apply_command_conditions(
+        &mut self,
+        command_exists: impl Fn(&str) -> bool,
+    ) -> Vec<ConditionOutcome> {
+        self.apply_conditions(&mut command_condition_check(&command_exists))
+    }
+    // the conditions of all tabs, the side panel and the swap layouts. Dropping the focused tab
+    // leaves the layout without one, and dropping the focused pane of a tab leaves its focus to
+    // the focus policy, as for layouts focusing none
+    fn apply_conditions(&mut self, check: &mut ConditionCheck) -> Vec<ConditionOutcome> {
+        let mut outcomes = vec![];
+        let focused_tab_index = self.focused_tab_index.take();
+        for (tab_index, mut tab) in std::mem::take(&mut self.tabs).into_iter().enumerate() {
+            let location = Some(LayoutLocation::Tab(tab_index));
+            let (_tab_name, tiled_panes, floating_panes, _pin, _max_content_width, swap_layouts) =
+                &mut tab;
+            if let Some((condition, included)) = check(&mut tiled_panes.conditions) {
+                outcomes.push(ConditionOutcome {
+                    location: location.clone(),
+                    pane: None,
+                    condition,
+                    included,
+                });
+                if !included {
+                    continue;
+                }
+            }
+            tiled_panes.apply_conditions(&location, check, &mut outcomes);
+            apply_floating_pane_conditions(floating_panes, &location, check, &mut outcomes);
+            let (swap_tiled_layouts, swap_floating_layouts) = swap_layouts;
+            for (swap_layouts, _name, _tags) in swap_tiled_layouts.iter_mut() {
+                for tiled_panes in swap_layouts.values_mut() {
+                    tiled_panes.apply_conditions(&None, check, &mut outcomes);
+                }
+            }
+            for (swap_layouts, _name, _tags) in swap_floating_layouts.iter_mut() {
+                for floating_panes in swap_layouts.values_mut() {
+                    apply_floating_pane_conditions(floating_panes, &None, check, &mut outcomes);
+                }
+            }
+            if focused_tab_index == Some(tab_index) {
+                self.focused_tab_index = Some(self.tabs.len());
+            }
+            self.tabs.push(tab);
+        }
+        if let Some((tiled_panes, floating_panes)) = self.template.as_mut() {
+            let location = Some(LayoutLocation::NewTabTemplate);
+            tiled_panes.apply_conditions(&location, check, &mut outcomes);
+            apply_floating_pane_conditions(floating_panes, &location, check, &mut outcomes);
+        }
+        for (tiled_panes, floating_panes) in self.tab_templates.values_mut() {
+            tiled_panes.apply_conditions(&None, check, &mut outcomes);
+            apply_floating_pane_conditions(floating_panes, &None, check, &mut outcomes);
+        }
+        if let Some(side_panel) = self.side_panel.as_mut() {
+            side_panel
+                .pane
+                .apply_conditions(&None, check, &mut outcomes);
+        }
+        for (index, (swap_layouts, _name, _tags)) in self.swap_tiled_layouts.iter_mut().enumerate()
+        {
+            for (constraint, tiled_panes) in swap_layouts.iter_mut() {
+                let location = Some(LayoutLocation::SwapTiledLayout(index, constraint.clone()));
+                tiled_panes.apply_conditions(&location, check, &mut outcomes);
+            }
+        }
+        for (index, (swap_layouts, _name, _tags)) in
+            self.swap_floating_layouts.iter_mut().enumerate()
+        {
+            for (constraint, floating_panes) in swap_layouts.iter_mut() {
+                let location = Some(LayoutLocation::SwapFloatingLayout(
+                    index,
+                    constraint.clone(),
+                ));
+                apply_floating_pane_conditions(floating_panes, &location, check, &mut outcomes);
+            }
+        }
+        outcomes
+    }
+    /// Moves the paths of all tabs, the side panel and the swap layouts from under `old_root` to
+    /// under `new_root` (eg. to open a layout written on a machine with another home directory):
+    /// the cwds of panes and their commands, the files they edit and the plugins they load from
+    /// files. Paths that are not under `old_root` are left as they are, see [`rebased_path`] for
+    /// what counts as being under it.
+    pub fn rebase_paths(&mut self, old_root: &Path, new_root: &Path) {
+        let tabs = self
+            .tabs
+            .iter_mut()
+            .map(|(_tab_name, tiled_panes, floating_panes, ..)| (tiled_panes, floating_panes));
+        let templates = self
+            .template
+            .iter_mut()
+            .chain(self.tab_templates.values_mut())
+            .map(|(tiled_panes, floating_panes)| (tiled_panes, floating_panes));
+        for (tiled_panes, floating_panes) in tabs.chain(templates) {
+            tiled_panes.rebase_paths(old_root, new_root);
+            for floating_pane in floating_panes.iter_mut() {
+                floating_pane.rebase_paths(old_root, new_root);
+            }
+        }
+        if let Some(side_panel) = self.side_panel.as_mut() {
+            side_panel.pane.rebase_paths(old_root, new_root);
+        }
+        for (swap_layouts, _name, _tags) in self.swap_tiled_layouts_mut() {
+            for tiled_panes in swap_layouts.values_mut() {
+                tiled_panes.rebase_paths(old_root, new_root);
+            }
+        }
+        for (swap_layouts, _name, _tags) in self.swap_floating_layouts_mut() {
+            for floating_pane in swap_layouts.values_mut().flatten() {
+                floating_pane.rebase_paths(old_root, new_root);
+            }
+        }
+    }
+    /// How many commands (and files to edit) opening the layout starts: those of its tabs, or of
+    /// its new tab template when it has none, and of its side panel. Swap layouts only rearrange
+    /// the panes that are already open, so their commands are not counted
+    pub fn command_count(&self) -> usize {
+        let side_panel_commands = self
+            .side_panel
+            .as_ref()
+            .map(|side_panel| tab_command_count(&side_panel.pane, &[]))
+            .unwrap_or(0);
+        let tab_commands: usize = if self.tabs.is_empty() {
+            let (tiled_panes, floating_panes) = self.new_tab();
+            tab_command_count(&tiled_panes, &floating_panes)
+        } else {
+            self.tabs
+                .iter()
+                .map(|(_tab_name, tiled_panes, floating_panes, ..)| {
+                    tab_command_count(tiled_panes, floating_panes)
+                })
+                .sum()
+        };
+        tab_commands + side_panel_commands
+    }
+    pub fn new_tab(&self) -> (TiledPaneLayout, Vec<FloatingPaneLayout>) {
+        self.template.clone().unwrap_or_default()
+    }
+    /// The tab opened from the tab_template named `template_name`, or the one of
+    /// [`Layout::new_tab`] when there is no name
+    pub fn new_tab_with_template(
+        &self,
+        template_name: Option<&str>,
+    ) -> Result<(TiledPaneLayout, Vec<FloatingPaneLayout>), ConfigError> {
+        match template_name {
+            Some(template_name) => self
+                .tab_templates
+                .get(template_name)
+                .cloned()
+                .ok_or_else(|| ConfigError::UnknownTabTemplate(template_name.to_string())),
+            None => Ok(self.new_tab()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.tabs.is_empty()
+    }
+    // TODO: do we need both of these?
+    pub fn has_tabs(&self) -> bool {
+        !self.tabs.is_empty()
+    }
+
+    pub fn tabs(
+        &self,
+    ) -> Vec<(
+        Option<String>,
+        TiledPaneLayout,
+        Vec<FloatingPaneLayout>,
+        Option<TabPin>,
+        Option<usize>,
+        (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+    )> {
+        // String is the tab name, usize is the max_content_width, the last ones are the swap
+        // layouts of the tab
+        self.tabs.clone()
+    }
+
+    /// The swap layouts cycled through in the tab at `tab_index`: its own ones, or those of the
+    /// layout when it has none of that kind (tiled or floating)
+    pub fn swap_layouts_of_tab(
+        &self,
+        tab_index: usize,
+    ) -> (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>) {
+        let (tab_swap_tiled_layouts, tab_swap_floating_layouts) = self
+            .tabs
+            .get(tab_index)
+            .map(|(.., swap_layouts)| swap_layouts.clone())
+            .unwrap_or_default();
+        let swap_tiled_layouts = if tab_swap_tiled_layouts.is_empty() {
+            self.swap_tiled_layouts.clone()
+        } else {
+            tab_swap_tiled_layouts
+        };
+        let swap_floating_layouts = if tab_swap_floating_layouts.is_empty() {
+            self.swap_floating_layouts.clone()
+        } else {
+            tab_swap_floating_layouts
+        };
+        (swap_tiled_layouts, swap_floating_layouts)
+    }
+
+    pub fn focused_tab_index(&self) -> Option<usize> {
+        self.focused_tab_index
+    }
+
+    /// The position the tab at `tab_index` ends up in once the pinned tabs were moved to their
+    /// edge, the relative order of tabs with the same pin is kept
+    pub fn pinned_tab_position(&self, tab_index: usize) -> usize {
+        let mut tab_indices: Vec<usize> = (0..self.tabs.len()).collect();
+        tab_indices.sort_by_key(|i| TabPin::rank(self.tabs[*i].3));
+        tab_indices
+            .iter()
+            .position(|i| *i == tab_index)
+            .unwrap_or(tab_index)
+    }
+
+    /// Combines two layouts opened in the same session (eg. `zellij -l editor -l services`):
+    ///
+    /// - the tabs of `other` come after the tabs of this layout, a layout without tabs contributes
+    ///   the single tab it would otherwise have opened
+    /// - a tab of `other` named like another tab is renamed "name (2)", "name (3)" and so on
+    /// - the first layout focusing a tab decides which tab is focused
+    /// - the template for new tabs and the session-wide settings (side panel, spawn interval,
+    ///   default focus, command wrapper and session name) of `other` replace these of this layout
+    ///   when it has them
+    /// - the swap layouts of both are available, those of this layout first
+    /// - the tab_templates of both are available, those of `other` replacing the ones of this
+    ///   layout with the same name
+    pub fn concatenate(mut self, mut other: Layout) -> Layout {
+        self.tabs_from_template();
+        other.tabs_from_template();
+        let tab_offset = self.tabs.len();
+        let other_tab_names: Vec<Option<String>> = other
+            .tabs
+            .iter()
+            .map(|(tab_name, ..)| tab_name.clone())
+            .collect();
+        for (
+            index,
+            (tab_name, tiled_panes, floating_panes, pin, max_content_width, swap_layouts),
+        ) in other.tabs.into_iter().enumerate()
+        {
+            let tab_name = tab_name.map(|tab_name| {
+                // the tabs after this one keep their names if they can
+                let taken_names: Vec<&str> = self
+                    .tabs
+                    .iter()
+                    .map(|(tab_name, ..)| tab_name)
+                    .chain(other_tab_names.iter().skip(index + 1))
+                    .filter_map(|tab_name| tab_name.as_deref())
+                    .collect();
+                disambiguated_tab_name(tab_name, &taken_names)
+            });
+            self.tabs.push((
+                tab_name,
+                tiled_panes,
+                floating_panes,
+                pin,
+                max_content_width,
+                swap_layouts,
+            ));
+        }
+        if self.focused_tab_index.is_none() {
+            self.focused_tab_name = other.focused_tab_name;
+        }
+        self.focused_tab_index = self
+            .focused_tab_index
+            .or_else(|| other.focused_tab_index.map(|index| index + tab_offset));
+        self.template = other.template.or(self.template);
+        self.tab_templates.append(&mut other.tab_templates);
+        self.swap_layouts.append(&mut other.swap_layouts);
+        self.swap_tiled_layouts
+            .append(&mut other.swap_tiled_layouts);
+        self.swap_floating_layouts
+            .append(&mut other.swap_floating_layouts);
+        self.side_panel = other.side_panel.or(self.side_panel);
+        self.command_spawn_interval_ms = other
+            .command_spawn_interval_ms
+            .or(self.command_spawn_interval_ms);
+        self.default_focus = other.default_focus.or(self.default_focus);
+        self.command_wrapper = other.command_wrapper.or(self.command_wrapper);
+        if other.session_name.is_some() {
+            self.session_name = other.session_name;
+            self.attach_to_existing = other.attach_to_existing;
+        }
+        self
+    }
+    /// Whether the session named by this layout (`session_name`) is created or attached to, `None`
+    /// if the layout does not name its session
+    pub fn session_start(
+        &self,
+        session_exists: impl FnOnce(&str) -> bool,
+    ) -> Option<LayoutSessionStart> {
+        let session_name = self.session_name.clone()?;
+        Some(if !session_exists(&session_name) {
+            LayoutSessionStart::Create(session_name)
+        } else if self.attach_to_existing.unwrap_or(false) {
+            LayoutSessionStart::Attach(session_name)
+        } else {
+            LayoutSessionStart::AlreadyRunning(session_name)
+        })
+    }
+    // a layout without tabs opens a single tab with its template
+    fn tabs_from_template(&mut self) {
+        if self.tabs.is_empty() {
+            let (tiled_panes, floating_panes) = self.new_tab();
+            self.tabs.push((
+                None,
+                tiled_panes,
+                floating_panes,
+                None,
+                None,
+                (vec![], vec![]),
+            ));
+        }
+    }
+    // the swap layouts of the layout and those of each of its tabs
+    fn swap_tiled_layouts_mut(&mut self) -> impl Iterator<Item = &mut SwapTiledLayout> + '_ {
+        let tab_swap_tiled_layouts = self
+            .tabs
+            .iter_mut()
+            .flat_map(|(.., (swap_tiled_layouts, _))| swap_tiled_layouts.iter_mut());
+        self.swap_tiled_layouts
+            .iter_mut()
+            .chain(tab_swap_tiled_layouts)
+    }
+    fn swap_floating_layouts_mut(&mut self) -> impl Iterator<Item = &mut SwapFloatingLayout> + '_ {
+        let tab_swap_floating_layouts = self
+            .tabs
+            .iter_mut()
+            .flat_map(|(.., (_, swap_floating_layouts))| swap_floating_layouts.iter_mut());
+        self.swap_floating_layouts
+            .iter_mut()
+            .chain(tab_swap_floating_layouts)
+    }
+    /// The error for a layout path that is a symlink to a file that does not exist, which would
+    /// otherwise be reported as the link itself not being found
+    fn dangling_link_error(path: &Path) -> Option<std::io::Error> {
+        let is_link = std::fs::symlink_metadata(path)
+            .map(|metadata| metadata.file_type().is_symlink())
+            .unwrap_or(false);
+        if !is_link || path.exists() {
+            return None;
+        }
+        let target = std::fs::read_link(path).ok()?;
+        Some(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "{} is a symlink to {}, which does not exist",
+                path.display(),
+                target.display()
+            ),
+        ))
+    }
+    /// The swap layout companion of a layout file: `work.kdl` and `work` both have
+    /// `work.swap.kdl`
+    fn swap_layout_path(layout_path: &Path) -> PathBuf {
+        let file_name = layout_path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let stem = file_name.strip_suffix(".kdl").unwrap_or(&file_name);
+        layout_path.with_file_name(format!("{}.swap.kdl", stem))
+    }
+    fn swap_layout_and_path(path: &Path) -> Option<(String, String)> {
+        // Option<path, stringified_swap_layout>
+        // the companion is looked for next to the layout as it was given and, if it is a link,
+        // next to the file it links to
+        let mut swap_layout_paths = vec![Layout::swap_layout_path(path)];
+        if let Ok(resolved_path) = std::fs::canonicalize(path) {
+            let resolved_swap_layout_path = Layout::swap_layout_path(&resolved_path);
+            if resolved_swap_layout_path != swap_layout_paths[0] {
+                swap_layout_paths.push(resolved_swap_layout_path);
+            }
+        }
+        for swap_layout_path in swap_layout_paths {
+            if let Some(e) = Layout::dangling_link_error(&swap_layout_path) {
+                log::warn!("Failed to read swap layout file: {}", e);
+                continue;
+            }
+            let mut stringified_swap_layout_file = match File::open(&swap_layout_path) {
+                Ok(stringified_swap_layout_file) => stringified_swap_layout_file,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to read swap layout file: {}. Error: {:?}",
+                        swap_layout_path.as_os_str().to_string_lossy(),
+                        e
+                    );
+                    continue;
+                },
+            };
+            let mut swap_kdl_layout = String::new();
+            match stringified_swap_layout_file.read_to_string(&mut swap_kdl_layout) {
+                Ok(..) => {
+                    return Some((
+                        swap_layout_path.as_os_str().to_string_lossy().into(),
+                        swap_kdl_layout,
+                    ))
+                },
+                Err(e) => {
+                    log::warn!(
+                        "Failed to read swap layout file: {}. Error: {:?}",
+                        swap_layout_path.as_os_str().to_string_lossy(),
+                        e
+                    );
+                },
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticSeverity {
+    Info, // what loading the layout decided, eg. the panes it dropped, reported in lenient mode
+    Warning,
+    Error,
+}
+
+impl fmt::Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticSeverity::Info => write!(f, "info"),
+            DiagnosticSeverity::Warning => write!(f, "warning"),
+            DiagnosticSeverity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The part of a [`Layout`] a [`LayoutDiagnostic`] refers to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LayoutLocation {
+    Tab(usize), // index in Layout::tabs
+    NewTabTemplate,
+    SwapTiledLayout(usize, LayoutConstraint), // index in Layout::swap_tiled_layouts
+    SwapFloatingLayout(usize, LayoutConstraint), // index in Layout::swap_floating_layouts
+}
+
+impl fmt::Display for LayoutLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutLocation::Tab(index) => write!(f, "tab #{}", index + 1),
+            LayoutLocation::NewTabTemplate => write!(f, "new tab template"),
+            LayoutLocation::SwapTiledLayout(index, constraint) => {
+                write!(f, "swap tiled layout #{} ({})", index + 1, constraint)
+            },
+            LayoutLocation::SwapFloatingLayout(index, constraint) => {
+                write!(f, "swap floating layout #{} ({})", index + 1, constraint)
+            },
+        }
+    }
+}
+
+impl fmt::Display for SessionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionConstraint::MaxTabs(max_tabs) => write!(f, "session_tabs_max {}", max_tabs),
+            SessionConstraint::MinTabs(min_tabs) => write!(f, "session_tabs_min {}", min_tabs),
+        }
+    }
+}
+
+impl fmt::Display for LayoutConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutConstraint::InSession(session_constraint, pane_constraint) => {
+                match **pane_constraint {
+                    LayoutConstraint::NoConstraint => write!(f, "{}", session_constraint),
+                    _ => write!(f, "{}, {}", session_constraint, pane_constraint),
+                }
+            },
+            LayoutConstraint::ExactPanes(exact_panes) => write!(f, "exact_panes {}", exact_panes),
+            LayoutConstraint::PaneRange { min, max } => {
+                write!(f, "min_panes {}, max_panes {}", min, max)
+            },
+            LayoutConstraint::MaxPanes(max_panes) => write!(f, "max_panes {}", max_panes),
+            LayoutConstraint::MinPanes(min_panes) => write!(f, "min_panes {}", min_panes),
+            LayoutConstraint::MaxGroups(max_groups) => write!(f, "max_groups {}", max_groups),
+            LayoutConstraint::MinGroups(min_groups) => write!(f, "min_groups {}", min_groups),
+            LayoutConstraint::NoConstraint => write!(f, "no constraint"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutSpan {
+    pub offset: usize,
+    pub len: usize,
+    pub in_swap_layout_file: bool,
+}
+
+/// Spans of the KDL nodes the parts of a [`Layout`] were parsed from, so that diagnostics found
+/// after parsing can still point at the source
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayoutSpans {
+    spans: HashMap<LayoutLocation, LayoutSpan>,
+}
+
+impl LayoutSpans {
+    pub fn insert(&mut self, location: LayoutLocation, span: LayoutSpan) {
+        self.spans.insert(location, span);
+    }
+    pub fn get(&self, location: &LayoutLocation) -> Option<&LayoutSpan> {
+        self.spans.get(location)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub code: &'static str,
+    pub message: String,
+    pub location: Option<LayoutLocation>,
+    pub span: Option<LayoutSpan>,
+}
+
+impl LayoutDiagnostic {
+    pub fn error(code: &'static str, message: String, location: Option<LayoutLocation>) -> Self {
+        LayoutDiagnostic {
+            severity: DiagnosticSeverity::Error,
+            code,
+            message,
+            location,
+            span: None,
+        }
+    }
+    pub fn warning(code: &'static str, message: String, location: Option<LayoutLocation>) -> Self {
+        LayoutDiagnostic {
+            severity: DiagnosticSeverity::Warning,
+            code,
+            message,
+            location,
+            span: None,
+        }
+    }
+    pub fn info(code: &'static str, message: String, location: Option<LayoutLocation>) -> Self {
+        LayoutDiagnostic {
+            severity: DiagnosticSeverity::Info,
+            code,
+            message,
+            location,
+            span: None,
+        }
+    }
+    pub fn is_error(&self) -> bool {
+        self.severity == DiagnosticSeverity::Error
+    }
+    pub fn with_spans(mut self, spans: &LayoutSpans) -> Self {
+        if let Some(location) = &self.location {
+            self.span = spans.get(location).copied();
+        }
+        self
+    }
+}
+
+impl fmt::Display for LayoutDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(
+                f,
+                "{}[{}] ({}): {}",
+                self.severity, self.code, location, self.message
+            ),
+            None => write!(f, "{}[{}]: {}", self.severity, self.code, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// when false, all diagnostics are reported as warnings
+    pub strict: bool,
+    /// exceeding these is always an error
+    pub limits: LayoutLimits,
+}
+
+impl ValidationOptions {
+    pub fn strict() -> Self {
+        ValidationOptions {
+            strict: true,
+            limits: LayoutLimits::default(),
+        }
+    }
+    pub fn lenient() -> Self {
+        ValidationOptions {
+            strict: false,
+            limits: LayoutLimits::default(),
+        }
+    }
+    pub fn with_limits(mut self, limits: LayoutLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+/// How big a layout can be before it is refused, so that a runaway layout (eg. written by a buggy
+/// generator) fails to load with an error instead of exhausting the stack or the memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutLimits {
+    pub max_depth: usize, // how deeply the children blocks of the layout file can nest
+    pub max_nodes: usize,
+    pub max_file_size: usize, // in bytes
+}
+
+impl Default for LayoutLimits {
+    fn default() -> Self {
+        LayoutLimits {
+            max_depth: 200,
+            max_nodes: 50_000,
+            max_file_size: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl LayoutLimits {
+    /// Checks the size and nesting of a layout file before it is parsed, the KDL parser recurses
+    /// into children blocks so a file nesting too deeply has to be refused before reaching it
+    pub fn check_source(&self, raw_layout: &str) -> Result<(), ConfigError> {
+        if raw_layout.len() > self.max_file_size {
+            return Err(ConfigError::LayoutLimitExceeded(format!(
+                "The layout file is {} bytes, more than the maximum of {} (layout_max_file_size)",
+                raw_layout.len(),
+                self.max_file_size
+            )));
+        }
+        let depth = kdl_nesting_depth(raw_layout);
+        if depth > self.max_depth {
+            return Err(ConfigError::LayoutLimitExceeded(format!(
+                "The layout nests {} levels deep, more than the maximum of {} (layout_max_depth)",
+                depth, self.max_depth
+            )));
+        }
+        Ok(())
+    }
+    pub fn check_node_count(&self, node_count: usize) -> Result<(), ConfigError> {
+        if node_count > self.max_nodes {
+            return Err(ConfigError::LayoutLimitExceeded(format!(
+                "The layout has {} nodes, more than the maximum of {} (layout_max_nodes)",
+                node_count, self.max_nodes
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// How many panes of a tab run a command or edit a file
+pub fn tab_command_count(
+    tiled_panes: &TiledPaneLayout,
+    floating_panes: &[FloatingPaneLayout],
+) -> usize {
+    let tiled_runs = tiled_panes.extract_run_instructions();
+    tiled_runs
+        .iter()
+        .chain(
+            floating_panes
+                .iter()
+                .map(|floating_pane| &floating_pane.run),
+        )
+        .filter(|run| matches!(run, Some(Run::Command(_)) | Some(Run::EditFile(..))))
+        .count()
+}
+
+/// Refuses to open a layout starting `command_count` commands when that is more than the
+/// `max_layout_commands` of the config. The cap is on each opening of a layout by itself (the
+/// session layout, a tab opened from a layout file or from the new tab template) and not on the
+/// session: the commands of the tabs opened before are not added up, so a session can end up
+/// running more of them than the cap
+pub fn check_max_layout_commands(
+    command_count: usize,
+    max_layout_commands: Option<usize>,
+) -> Result<(), ConfigError> {
+    match max_layout_commands {
+        Some(max_layout_commands) if command_count > max_layout_commands => {
+            Err(ConfigError::LayoutLimitExceeded(format!(
+                "The layout starts {} commands, more than the maximum of {} (max_layout_commands)",
+                command_count, max_layout_commands
+            )))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// How deeply the children blocks of a KDL document nest, found by scanning for braces outside of
+/// strings and comments rather than by parsing it
+fn kdl_nesting_depth(raw_layout: &str) -> usize {
+    let (mut depth, mut deepest_depth) = (0, 0);
+    let mut chars = raw_layout.chars().peekable();
+    while let Some(character) = chars.next() {
+        match character {
+            '{' => {
+                depth += 1;
+                deepest_depth = deepest_depth.max(depth);
+            },
+            '}' => depth = depth.saturating_sub(1),
+            '"' => {
+                while let Some(character) = chars.next() {
+                    match character {
+                        '\\' => {
+                            chars.next();
+                        },
+                        '"' => break,
+                        _ => {},
+                    }
+                }
+            },
+            'r' if matches!(chars.peek(), Some('"') | Some('#')) => {
+                let mut hashes = 0;
+                while chars.next_if_eq(&'#').is_some() {
+                    hashes += 1;
+                }
+                if chars.next_if_eq(&'"').is_some() {
+                    // a raw string ends with a quote followed by as many hashes as it started with
+                    while let Some(character) = chars.next() {
+                        if character == '"' {
+                            let mut closing_hashes = 0;
+                            while closing_hashes < hashes && chars.next_if_eq(&'#').is_some() {
+                                closing_hashes += 1;
+                            }
+                            if closing_hashes == hashes {
+                                break;
+                            }
+                        }
+                    }
+                }
+            },
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            },
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                // block comments nest in KDL
+                let mut comment_depth = 1;
+                while comment_depth > 0 {
+                    match chars.next() {
+                        Some('/') if chars.next_if_eq(&'*').is_some() => comment_depth += 1,
+                        Some('*') if chars.next_if_eq(&'/').is_some() => comment_depth -= 1,
+                        Some(_) => {},
+                        None => break,
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    deepest_depth
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        ValidationOptions::strict()
+    }
+}
+
+impl Layout {
+    /// Runs all the semantic checks over this (already parsed) layout
+    pub fn validate(&self, options: ValidationOptions) -> Vec<LayoutDiagnostic> {
+        let mut diagnostics = vec![];
+        diagnostics.append(&mut check_focused_tab_index(self));
+        let tabs = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, (_name, tiled, floating, ..))| (LayoutLocation::Tab(i), tiled, floating));
+        let template = self
+            .template
+            .iter()
+            .map(|(tiled, floating)| (LayoutLocation::NewTabTemplate, tiled, floating));
+        for (location, tiled_panes, floating_panes) in tabs.chain(template) {
+            diagnostics.append(&mut check_zero_sizes(
+                tiled_panes,
+                floating_panes,
+                &location,
+            ));
+            diagnostics.append(&mut check_percent_sums(tiled_panes, &location));
+            diagnostics.append(&mut check_multiple_focus(
+                tiled_panes,
+                floating_panes,
+                &location,
+            ));
+        }
+        for (i, (swap_tiled_layout, swap_layout_name, _tags)) in
+            self.swap_tiled_layouts.iter().enumerate()
+        {
+            diagnostics.append(&mut check_empty_swap_layout(
+                swap_tiled_layout.is_empty(),
+                swap_layout_name,
+            ));
+            for constraint in unreachable_swap_constraints(swap_tiled_layout.keys()) {
+                diagnostics.push(unreachable_swap_constraint_diagnostic(
+                    LayoutLocation::SwapTiledLayout(i, constraint),
+                ));
+            }
+            for (constraint, tiled_panes) in swap_tiled_layout {
+                let location = LayoutLocation::SwapTiledLayout(i, constraint.clone());
+                diagnostics.append(&mut check_zero_sizes(tiled_panes, &[], &location));
+                diagnostics.append(&mut check_percent_sums(tiled_panes, &location));
+                diagnostics.append(&mut check_multiple_focus(tiled_panes, &[], &location));
+                diagnostics.append(&mut check_missing_children_slot(
+                    constraint,
+                    tiled_panes,
+                    &location,
+                ));
+            }
+        }
+        for (i, (swap_floating_layout, swap_layout_name, _tags)) in
+            self.swap_floating_layouts.iter().enumerate()
+        {
+            diagnostics.append(&mut check_empty_swap_layout(
+                swap_floating_layout.is_empty(),
+                swap_layout_name,
+            ));
+            for constraint in unreachable_swap_constraints(swap_floating_layout.keys()) {
+                diagnostics.push(unreachable_swap_constraint_diagnostic(
+                    LayoutLocation::SwapFloatingLayout(i, constraint),
+                ));
+            }
+            for (constraint, floating_panes) in swap_floating_layout {
+                let location = LayoutLocation::SwapFloatingLayout(i, constraint.clone());
+                diagnostics.append(&mut check_zero_sizes(
+                    &TiledPaneLayout::default(),
+                    floating_panes,
+                    &location,
+                ));
+                diagnostics.append(&mut check_multiple_focus(
+                    &TiledPaneLayout::default(),
+                    floating_panes,
+                    &location,
+                ));
+            }
+        }
+        if !options.strict {
+            for diagnostic in diagnostics.iter_mut() {
+                diagnostic.severity = DiagnosticSeverity::Warning;
+            }
+        }
+        // a dependency cycle would leave its panes suspended forever, so it is an error even when
+        // validation is not strict
+        let tabs = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, (_name, tiled, floating, ..))| (LayoutLocation::Tab(i), tiled, floating));
+        let template = self
+            .template
+            .iter()
+            .map(|(tiled, floating)| (LayoutLocation::NewTabTemplate, tiled, floating));
+        for (location, tiled_panes, floating_panes) in tabs.chain(template) {
+            diagnostics.append(&mut check_pane_dependencies(
+                &PaneDependencyGraph::new(tiled_panes, floating_panes),
+                &location,
+            ));
+        }
+        diagnostics
+    }
+}
+
+/// The panes of a tab that wait for other panes (see [`PaneDependency`]), by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaneDependencyGraph {
+    pane_names: BTreeSet<String>,
+    // the name of the waiting pane (if it has one) and the name of the pane it waits for
+    dependencies: Vec<(Option<String>, String)>,
+}
+
+impl PaneDependencyGraph {
+    pub fn new(tiled_panes: &TiledPaneLayout, floating_panes: &[FloatingPaneLayout]) -> Self {
+        let tiled_panes = tiled_panes
+            .extract_run_instructions_indexed()
+            .into_iter()
+            .map(|run_instruction| (run_instruction.pane_name, run_instruction.run));
+        let floating_panes = floating_panes
+            .iter()
+            .map(|floating_pane| (floating_pane.name.clone(), floating_pane.run.clone()));
+        let mut graph = PaneDependencyGraph::default();
+        for (name, run) in tiled_panes.chain(floating_panes) {
+            if let Some(name) = &name {
+                graph.pane_names.insert(name.clone());
+            }
+            if let Some(Run::Command(RunCommand {
+                depends_on: Some(PaneDependency { pane_name, .. }),
+                ..
+            })) = run
+            {
+                graph.dependencies.push((name, pane_name));
+            }
+        }
+        graph
     }
-    pub fn from_str(
-        raw: &str,
-        path_to_raw_layout: String,
-        swap_layouts: Option<(&str, &str)>, // Option<path_to_swap_layout, stringified_swap_layout>
-        cwd: Option<PathBuf>,
-    ) -> Result<Layout, ConfigError> {
-        Layout::from_kdl(raw, path_to_raw_layout, swap_layouts, cwd)
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
     }
-    pub fn stringified_from_dir(
-        layout: &PathBuf,
-        layout_dir: Option<&PathBuf>,
-    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
-        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
-        match layout_dir {
-            Some(dir) => {
-                let layout_path = &dir.join(layout);
-                if layout_path.with_extension("kdl").exists() {
-                    Self::stringified_from_path(layout_path)
-                } else {
-                    Layout::stringified_from_default_assets(layout)
+    /// Names panes depend on that no pane in the tab has, these panes start without waiting
+    pub fn unknown_dependencies(&self) -> Vec<&str> {
+        let mut unknown_dependencies: Vec<&str> = self
+            .dependencies
+            .iter()
+            .map(|(_name, dependency)| dependency.as_str())
+            .filter(|dependency| !self.pane_names.contains(*dependency))
+            .collect();
+        unknown_dependencies.sort_unstable();
+        unknown_dependencies.dedup();
+        unknown_dependencies
+    }
+    /// The first cycle found, as the names of the panes in it starting and ending with the same
+    /// pane (eg. `["api", "db", "api"]`)
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        let mut dependencies_of: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (name, dependency) in &self.dependencies {
+            if let Some(name) = name {
+                dependencies_of
+                    .entry(name.as_str())
+                    .or_default()
+                    .push(dependency.as_str());
+            }
+        }
+        fn visit<'a>(
+            pane: &'a str,
+            dependencies_of: &BTreeMap<&'a str, Vec<&'a str>>,
+            path: &mut Vec<&'a str>,
+            done: &mut BTreeSet<&'a str>,
+        ) -> Option<Vec<String>> {
+            if let Some(position) = path.iter().position(|p| *p == pane) {
+                let mut cycle: Vec<String> =
+                    path[position..].iter().map(|p| p.to_string()).collect();
+                cycle.push(pane.to_string());
+                return Some(cycle);
+            }
+            if done.contains(pane) {
+                return None;
+            }
+            path.push(pane);
+            for dependency in dependencies_of.get(pane).into_iter().flatten() {
+                if let Some(cycle) = visit(dependency, dependencies_of, path, done) {
+                    return Some(cycle);
                 }
-            },
-            None => Layout::stringified_from_default_assets(layout),
+            }
+            path.pop();
+            done.insert(pane);
+            None
         }
+        let mut done = BTreeSet::new();
+        for pane in dependencies_of.keys() {
+            if let Some(cycle) = visit(pane, &dependencies_of, &mut vec![], &mut done) {
+                return Some(cycle);
+            }
+        }
+        None
     }
-    pub fn stringified_from_path(
-        layout_path: &Path,
-    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
-        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
-        let mut layout_file = File::open(&layout_path)
-            .or_else(|_| File::open(&layout_path.with_extension("kdl")))
-            .map_err(|e| ConfigError::IoPath(e, layout_path.into()))?;
+}
 
-        let swap_layout_and_path = Layout::swap_layout_and_path(&layout_path);
+const BYTE_ORDER_MARK: char = '\u{feff}';
 
-        let mut kdl_layout = String::new();
-        layout_file.read_to_string(&mut kdl_layout)?;
-        Ok((
-            layout_path.as_os_str().to_string_lossy().into(),
-            kdl_layout,
-            swap_layout_and_path,
-        ))
-    }
-    pub fn stringified_from_default_assets(
-        path: &Path,
-    ) -> Result<(String, String, Option<(String, String)>), ConfigError> {
-        // (path_to_layout as String, stringified_layout, Option<path_to_swap_layout as String, stringified_swap_layout>)
-        // TODO: ideally these should not be hard-coded
-        // we should load layouts by name from the config
-        // and load them from a hashmap or some such
-        match path.to_str() {
-            Some("default") => Ok((
-                "Default layout".into(),
-                Self::stringified_default_from_assets()?,
-                Some((
-                    "Default swap layout".into(),
-                    Self::stringified_default_swap_from_assets()?,
-                )),
-            )),
-            Some("strider") => Ok((
-                "Strider layout".into(),
-                Self::stringified_strider_from_assets()?,
-                Some((
-                    "Strider swap layout".into(),
-                    Self::stringified_strider_swap_from_assets()?,
-                )),
-            )),
-            Some("disable-status-bar") => Ok((
-                "Disable Status Bar layout".into(),
-                Self::stringified_disable_status_from_assets()?,
+/// Strips a leading UTF-8 byte order mark and turns CRLF and lone CR line endings into LF, so that
+/// layouts saved by Windows editors parse like any other and the byte offsets of errors map to the
+/// right line and column of the returned source. Also returns whether a byte order mark was
+/// stripped.
+pub fn normalize_layout_source(raw_layout: &str) -> (String, bool) {
+    let (raw_layout, had_byte_order_mark) = match raw_layout.strip_prefix(BYTE_ORDER_MARK) {
+        Some(raw_layout) => (raw_layout, true),
+        None => (raw_layout, false),
+    };
+    let normalized = raw_layout.replace("\r\n", "\n").replace('\r', "\n");
+    (normalized, had_byte_order_mark)
+}
+
+// what the YAML layouts of zellij versions before 0.32.0 start with
+const YAML_LAYOUT_MARKERS: &[&str] = &["---", "template:", "tabs:", "parts:", "session:"];
+
+/// Whether this is a layout in the YAML format of zellij versions before 0.32.0 (by its extension
+/// or by how it starts), which is refused with a way to convert it rather than with a KDL syntax
+/// error at its first line
+pub fn is_yaml_layout(raw_layout: &str, layout_path: Option<&Path>) -> bool {
+    let has_yaml_extension = layout_path
+        .and_then(|layout_path| layout_path.extension())
+        .map(|extension| extension == "yaml" || extension == "yml")
+        .unwrap_or(false);
+    let first_line = raw_layout
+        .trim_start_matches(BYTE_ORDER_MARK)
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty() && !line.starts_with('#'));
+    has_yaml_extension
+        || first_line
+            .map(|line| {
+                YAML_LAYOUT_MARKERS
+                    .iter()
+                    .any(|marker| line.starts_with(marker))
+            })
+            .unwrap_or(false)
+}
+
+fn check_focused_tab_index(layout: &Layout) -> Vec<LayoutDiagnostic> {
+    match layout.focused_tab_index {
+        Some(focused_tab_index) if focused_tab_index >= layout.tabs.len() => {
+            vec![LayoutDiagnostic::error(
+                "focused-tab-out-of-range",
+                format!(
+                    "Focused tab index {} is out of range, layout has {} tabs",
+                    focused_tab_index,
+                    layout.tabs.len()
+                ),
                 None,
-            )),
-            Some("compact") => Ok((
-                "Compact layout".into(),
-                Self::stringified_compact_from_assets()?,
-                Some((
-                    "Compact layout swap".into(),
-                    Self::stringified_compact_swap_from_assets()?,
-                )),
-            )),
-            None | Some(_) => Err(ConfigError::IoPath(
-                std::io::Error::new(std::io::ErrorKind::Other, "The layout was not found"),
-                path.into(),
-            )),
-        }
+            )]
+        },
+        _ => vec![],
     }
-    pub fn stringified_default_from_assets() -> Result<String, ConfigError> {
-        Ok(String::from_utf8(setup::DEFAULT_LAYOUT.to_vec())?)
-    }
-    pub fn stringified_default_swap_from_assets() -> Result<String, ConfigError> {
-        Ok(String::from_utf8(setup::DEFAULT_SWAP_LAYOUT.to_vec())?)
+}
+
+fn check_zero_sizes(
+    tiled_panes: &TiledPaneLayout,
+    floating_panes: &[FloatingPaneLayout],
+    location: &LayoutLocation,
+) -> Vec<LayoutDiagnostic> {
+    fn has_zero_sized_pane(layout: &TiledPaneLayout) -> bool {
+        let is_zero = match layout.split_size {
+            Some(SplitSize::Percent(size))
+            | Some(SplitSize::Fixed(size))
+            | Some(SplitSize::Weight(size)) => size == 0,
+            None => false,
+        };
+        is_zero || layout.children.iter().any(has_zero_sized_pane)
     }
-    pub fn stringified_strider_from_assets() -> Result<String, ConfigError> {
-        Ok(String::from_utf8(setup::STRIDER_LAYOUT.to_vec())?)
+    let mut diagnostics = vec![];
+    if has_zero_sized_pane(tiled_panes) {
+        diagnostics.push(LayoutDiagnostic::error(
+            "zero-size",
+            "Pane size should be greater than 0".into(),
+            Some(location.clone()),
+        ));
     }
-    pub fn stringified_strider_swap_from_assets() -> Result<String, ConfigError> {
-        Ok(String::from_utf8(setup::STRIDER_SWAP_LAYOUT.to_vec())?)
+    let has_zero_sized_floating_pane = floating_panes.iter().any(|floating_pane| {
+        floating_pane
+            .width
+            .as_ref()
+            .map(|w| w.is_zero())
+            .unwrap_or(false)
+            || floating_pane
+                .height
+                .as_ref()
+                .map(|h| h.is_zero())
+                .unwrap_or(false)
+    });
+    if has_zero_sized_floating_pane {
+        diagnostics.push(LayoutDiagnostic::error(
+            "zero-size",
+            "Floating pane width and height should be greater than 0".into(),
+            Some(location.clone()),
+        ));
     }
+    diagnostics
+}
 
-    pub fn stringified_disable_status_from_assets() -> Result<String, ConfigError> {
-        Ok(String::from_utf8(setup::NO_STATUS_LAYOUT.to_vec())?)
+fn check_percent_sums(
+    tiled_panes: &TiledPaneLayout,
+    location: &LayoutLocation,
+) -> Vec<LayoutDiagnostic> {
+    let mut diagnostics = vec![];
+    let percent_sum: usize = tiled_panes
+        .children
+        .iter()
+        .filter_map(|child| match child.split_size {
+            Some(SplitSize::Percent(percent)) => Some(percent),
+            _ => None,
+        })
+        .sum();
+    if percent_sum > 100 {
+        diagnostics.push(LayoutDiagnostic::warning(
+            "percent-overflow",
+            format!(
+                "Sibling pane sizes add up to {}%, which is more than 100%",
+                percent_sum
+            ),
+            Some(location.clone()),
+        ));
     }
-
-    pub fn stringified_compact_from_assets() -> Result<String, ConfigError> {
-        Ok(String::from_utf8(setup::COMPACT_BAR_LAYOUT.to_vec())?)
+    for child in &tiled_panes.children {
+        diagnostics.append(&mut check_percent_sums(child, location));
     }
+    diagnostics
+}
 
-    pub fn stringified_compact_swap_from_assets() -> Result<String, ConfigError> {
-        Ok(String::from_utf8(setup::COMPACT_BAR_SWAP_LAYOUT.to_vec())?)
+fn check_multiple_focus(
+    tiled_panes: &TiledPaneLayout,
+    floating_panes: &[FloatingPaneLayout],
+    location: &LayoutLocation,
+) -> Vec<LayoutDiagnostic> {
+    fn focused_tiled_pane_count(layout: &TiledPaneLayout) -> usize {
+        let own_focus = if layout.focus == Some(true) { 1 } else { 0 };
+        own_focus
+            + layout
+                .children
+                .iter()
+                .map(focused_tiled_pane_count)
+                .sum::<usize>()
     }
-
-    pub fn new_tab(&self) -> (TiledPaneLayout, Vec<FloatingPaneLayout>) {
-        self.template.clone().unwrap_or_default()
+    let focused_floating_pane_count = floating_panes
+        .iter()
+        .filter(|floating_pane| floating_pane.focus == Some(true))
+        .count();
+    let focused_pane_count = focused_tiled_pane_count(tiled_panes) + focused_floating_pane_count;
+    if focused_pane_count > 1 {
+        vec![LayoutDiagnostic::warning(
+            "multiple-focus",
+            format!(
+                "Found {} panes with focus, only one of them will be focused",
+                focused_pane_count
+            ),
+            Some(location.clone()),
+        )]
+    } else {
+        vec![]
     }
+}
 
-    pub fn is_empty(&self) -> bool {
-        !self.tabs.is_empty()
+fn check_pane_dependencies(
+    graph: &PaneDependencyGraph,
+    location: &LayoutLocation,
+) -> Vec<LayoutDiagnostic> {
+    let mut diagnostics = vec![];
+    if graph.is_empty() {
+        return diagnostics;
     }
-    // TODO: do we need both of these?
-    pub fn has_tabs(&self) -> bool {
-        !self.tabs.is_empty()
+    if let Some(cycle) = graph.find_cycle() {
+        diagnostics.push(LayoutDiagnostic::error(
+            "dependency-cycle",
+            format!(
+                "Panes depend on each other in a cycle ({}), none of them would ever start",
+                cycle.join(" -> ")
+            ),
+            Some(location.clone()),
+        ));
+    }
+    for dependency in graph.unknown_dependencies() {
+        diagnostics.push(LayoutDiagnostic::warning(
+            "unknown-dependency",
+            format!(
+                "No pane is named \"{}\", the panes depending on it will start right away",
+                dependency
+            ),
+            Some(location.clone()),
+        ));
     }
+    diagnostics
+}
 
-    pub fn tabs(&self) -> Vec<(Option<String>, TiledPaneLayout, Vec<FloatingPaneLayout>)> {
-        // String is the tab name
-        self.tabs.clone()
+fn check_empty_swap_layout(
+    is_empty: bool,
+    swap_layout_name: &Option<String>,
+) -> Vec<LayoutDiagnostic> {
+    if is_empty {
+        vec![LayoutDiagnostic::warning(
+            "empty-swap-layout",
+            format!(
+                "Swap layout {}has no entries and will be skipped",
+                swap_layout_name
+                    .as_ref()
+                    .map(|n| format!("\"{}\" ", n))
+                    .unwrap_or_default()
+            ),
+            None,
+        )]
+    } else {
+        vec![]
     }
+}
 
-    pub fn focused_tab_index(&self) -> Option<usize> {
-        self.focused_tab_index
+fn check_missing_children_slot(
+    constraint: &LayoutConstraint,
+    tiled_panes: &TiledPaneLayout,
+    location: &LayoutLocation,
+) -> Vec<LayoutDiagnostic> {
+    // stacks hold any number of panes, so a group count does not limit the pane count
+    let accepts_any_pane_count = !matches!(
+        constraint.pane_constraint(),
+        LayoutConstraint::MaxPanes(_)
+            | LayoutConstraint::ExactPanes(_)
+            | LayoutConstraint::PaneRange { .. }
+    );
+    if accepts_any_pane_count && tiled_panes.children_block_count() == 0 {
+        vec![LayoutDiagnostic::warning(
+            "missing-children-slot",
+            format!(
+                "This swap layout has no `children` node, so panes beyond its {} will not be placed",
+                tiled_panes.pane_count()
+            ),
+            Some(location.clone()),
+        )]
+    } else {
+        vec![]
     }
+}
 
-    fn swap_layout_and_path(path: &Path) -> Option<(String, String)> {
-        // Option<path, stringified_swap_layout>
-        let mut swap_layout_path = PathBuf::from(path);
-        swap_layout_path.set_extension("swap.kdl");
-        match File::open(&swap_layout_path) {
-            Ok(mut stringified_swap_layout_file) => {
-                let mut swap_kdl_layout = String::new();
-                match stringified_swap_layout_file.read_to_string(&mut swap_kdl_layout) {
-                    Ok(..) => Some((
-                        swap_layout_path.as_os_str().to_string_lossy().into(),
-                        swap_kdl_layout,
-                    )),
-                    Err(e) => {
-                        log::warn!(
-                            "Failed to read swap layout file: {}. Error: {:?}",
-                            swap_layout_path.as_os_str().to_string_lossy(),
-                            e
-                        );
-                        None
-                    },
-                }
+fn unreachable_swap_constraint_diagnostic(location: LayoutLocation) -> LayoutDiagnostic {
+    LayoutDiagnostic::warning(
+        "unreachable-swap-layout",
+        "Every pane count this swap layout accepts is matched by a previous one, so it will never be used".into(),
+        Some(location),
+    )
+}
+
+/// Constraints (in the order they are tried when swapping) whose pane counts are all matched by a
+/// previous constraint
+fn unreachable_swap_constraints<'a>(
+    constraints: impl Iterator<Item = &'a LayoutConstraint>,
+) -> Vec<LayoutConstraint> {
+    // panes 1..=covered_up_to and covered_from.. are already matched
+    let mut covered_up_to = 0;
+    let mut covered_from: Option<usize> = None;
+    let mut unreachable = vec![];
+    for constraint in constraints {
+        let (first_uncovered, last_accepted) = match constraint {
+            // group counts depend on how the panes are stacked and session constraints on the
+            // other tabs, they neither cover pane counts nor are covered by them
+            LayoutConstraint::MaxGroups(_)
+            | LayoutConstraint::MinGroups(_)
+            | LayoutConstraint::InSession(..) => continue,
+            LayoutConstraint::ExactPanes(exact_panes) => (
+                std::cmp::max(*exact_panes, covered_up_to + 1),
+                Some(*exact_panes),
+            ),
+            LayoutConstraint::PaneRange { min, max } => {
+                (std::cmp::max(*min, covered_up_to + 1), Some(*max))
             },
-            Err(e) => {
-                log::warn!(
-                    "Failed to read swap layout file: {}. Error: {:?}",
-                    swap_layout_path.as_os_str().to_string_lossy(),
-                    e
+            LayoutConstraint::MaxPanes(max_panes) => (covered_up_to + 1, Some(*max_panes)),
+            LayoutConstraint::MinPanes(min_panes) => {
+                (std::cmp::max(*min_panes, covered_up_to + 1), None)
+            },
+            LayoutConstraint::NoConstraint => (covered_up_to + 1, None),
+        };
+        let is_reachable = last_accepted.map(|l| first_uncovered <= l).unwrap_or(true)
+            && covered_from.map(|c| first_uncovered < c).unwrap_or(true);
+        if !is_reachable {
+            unreachable.push(constraint.clone());
+        }
+        match constraint {
+            // an exact count only extends the counts covered from 1 when it is the next one
+            LayoutConstraint::ExactPanes(exact_panes) if *exact_panes == covered_up_to + 1 => {
+                covered_up_to = *exact_panes;
+            },
+            // a range only extends them when it starts at or before the next one
+            LayoutConstraint::PaneRange { min, max } if *min <= covered_up_to + 1 => {
+                covered_up_to = std::cmp::max(covered_up_to, *max);
+            },
+            LayoutConstraint::MaxPanes(max_panes) => {
+                covered_up_to = std::cmp::max(covered_up_to, *max_panes);
+            },
+            LayoutConstraint::MinPanes(min_panes) => {
+                let min_panes = std::cmp::max(*min_panes, 1);
+                covered_from = Some(
+                    covered_from
+                        .map(|c| std::cmp::min(c, min_panes))
+                        .unwrap_or(min_panes),
                 );
-                None
             },
+            LayoutConstraint::NoConstraint => {
+                covered_from = Some(1);
+            },
+            LayoutConstraint::ExactPanes(_)
+            | LayoutConstraint::PaneRange { .. }
+            | LayoutConstraint::MaxGroups(_)
+            | LayoutConstraint::MinGroups(_)
+            | LayoutConstraint::InSession(..) => {},
+        }
+    }
+    unreachable
+}
+
+// the sizes in cells of sibling panes of `exact_sizes` in `space`: rounded down, after which the
+// cells missing are given one at a time to the flexible panes (those not of a fixed size) that
+// lost the most in rounding, or the cells too many taken from those that lost the least, so that
+// panes of the same size end up within a cell of each other whatever the size of the space. Ties
+// go to the first pane. The last pane makes up for what the flexible panes cannot.
+fn round_to_space(exact_sizes: &[f64], is_flexible: &[bool], space: usize) -> Vec<usize> {
+    let mut sizes: Vec<usize> = exact_sizes
+        .iter()
+        .map(|size| size.floor() as usize)
+        .collect();
+    let remainder = |i: &usize| exact_sizes[*i] - exact_sizes[*i].floor();
+    let mut flexible: Vec<usize> = (0..sizes.len()).filter(|i| is_flexible[*i]).collect();
+    let total: usize = sizes.iter().sum();
+    if total < space {
+        flexible.sort_by(|a, b| {
+            remainder(b)
+                .partial_cmp(&remainder(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut missing = space - total;
+        if !flexible.is_empty() {
+            for i in flexible.iter().cycle().take(missing) {
+                sizes[*i] += 1;
+            }
+            missing = 0;
+        }
+        if let Some(last_size) = sizes.last_mut() {
+            *last_size += missing;
+        }
+    } else if total > space {
+        flexible.sort_by(|a, b| {
+            remainder(a)
+                .partial_cmp(&remainder(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut extra = total - space;
+        // flexible panes keep at least a cell
+        while extra > 0 && flexible.iter().any(|i| sizes[*i] > 1) {
+            for i in &flexible {
+                if extra > 0 && sizes[*i] > 1 {
+                    sizes[*i] -= 1;
+                    extra -= 1;
+                }
+            }
+        }
+        if let Some(last_size) = sizes.last_mut() {
+            *last_size = last_size.saturating_sub(extra);
+        }
+    }
+    sizes
+}
+
+// the size in cells a percent split size gets, the same way split_space resolves it
+fn cells_of_percent(percent: usize, space: usize) -> usize {
+    let mut dimension = Dimension::percent(percent as f64);
+    dimension.adjust_inner(space);
+    dimension.as_usize()
+}
+
+/// Split sizes for sibling panes of `cell_sizes` cells that give them exactly these sizes again
+/// when split from a layout space of `total_cells`. Panes declared with a fixed size keep a fixed
+/// size, the others get percentages picked with the largest remainder method, so that they add up
+/// to the share of the space they take. A pane whose size no whole percentage gives gets a fixed
+/// size instead. The cells the percentages leave in rounding go to the panes that lose the most in
+/// it when splitting (see [`round_to_space`]), which should be the last pane, so it does not need
+/// an exact percentage. When it cannot be, the last pane gets a fixed size too.
+fn split_sizes_for_cells(
+    cell_sizes: &[usize],
+    declared_sizes: &[Option<SplitSize>],
+    total_cells: usize,
+) -> Vec<Option<SplitSize>> {
+    let mut is_fixed: Vec<bool> = declared_sizes
+        .iter()
+        .map(|size| matches!(size, Some(SplitSize::Fixed(_))))
+        .collect();
+    loop {
+        let fixed_cells: usize = cell_sizes
+            .iter()
+            .zip(&is_fixed)
+            .filter(|(_, is_fixed)| **is_fixed)
+            .map(|(cells, _)| cells)
+            .sum();
+        let flexible_space = total_cells.saturating_sub(fixed_cells);
+        let flexible: Vec<usize> = (0..cell_sizes.len()).filter(|i| !is_fixed[*i]).collect();
+        if flexible_space == 0 || flexible.is_empty() {
+            return cell_sizes
+                .iter()
+                .map(|cells| Some(SplitSize::Fixed(*cells)))
+                .collect();
+        }
+        let exact_percents: Vec<f64> = flexible
+            .iter()
+            .map(|i| cell_sizes[*i] as f64 * 100.0 / flexible_space as f64)
+            .collect();
+        let percent_total = exact_percents.iter().sum::<f64>().round() as usize;
+        let mut percents = largest_remainder_round(&exact_percents, percent_total);
+        let last_pane = cell_sizes.len() - 1;
+        let mut found_fixed_pane = false;
+        for (percent, i) in percents.iter_mut().zip(&flexible) {
+            if *i == last_pane || cells_of_percent(*percent, flexible_space) == cell_sizes[*i] {
+                continue;
+            }
+            match (1..=100).find(|p| cells_of_percent(*p, flexible_space) == cell_sizes[*i]) {
+                Some(exact_percent) => *percent = exact_percent,
+                None => {
+                    is_fixed[*i] = true;
+                    found_fixed_pane = true;
+                },
+            }
+        }
+        if found_fixed_pane {
+            // the fixed size changes the space the percentages are of
+            continue;
+        }
+        let give_rest_to_last_pane = |percents: &mut Vec<usize>| {
+            if flexible.last() == Some(&last_pane) {
+                let other_percents: usize = percents[..percents.len() - 1].iter().sum();
+                if let Some(last_percent) = percents.last_mut() {
+                    *last_percent = percent_total.saturating_sub(other_percents).max(1);
+                }
+            }
+        };
+        let resolves_to_cell_sizes = |percents: &[usize]| {
+            let mut percents = percents.iter();
+            let exact_sizes: Vec<f64> = cell_sizes
+                .iter()
+                .zip(&is_fixed)
+                .map(|(cells, is_fixed)| {
+                    if *is_fixed {
+                        return *cells as f64;
+                    }
+                    percents.next().map_or(*cells as f64, |percent| {
+                        (*percent as f64 / 100.0) * flexible_space as f64
+                    })
+                })
+                .collect();
+            let is_flexible: Vec<bool> = is_fixed.iter().map(|is_fixed| !is_fixed).collect();
+            round_to_space(&exact_sizes, &is_flexible, cell_sizes.iter().sum()) == cell_sizes
+        };
+        give_rest_to_last_pane(&mut percents);
+        if !resolves_to_cell_sizes(&percents) {
+            // the smallest percentages that give the other panes their size lose the least in
+            // rounding, leaving the cells they do not take to the last pane
+            for (percent, i) in percents.iter_mut().zip(&flexible) {
+                if *i != last_pane {
+                    if let Some(smallest_percent) =
+                        (1..=100).find(|p| cells_of_percent(*p, flexible_space) == cell_sizes[*i])
+                    {
+                        *percent = smallest_percent;
+                    }
+                }
+            }
+            give_rest_to_last_pane(&mut percents);
+            if !resolves_to_cell_sizes(&percents) {
+                if let Some(last_flexible) = flexible.last() {
+                    is_fixed[*last_flexible] = true;
+                }
+                continue;
+            }
         }
+        let mut percents = percents.into_iter();
+        return cell_sizes
+            .iter()
+            .zip(&is_fixed)
+            .map(|(cells, is_fixed)| {
+                if *is_fixed {
+                    Some(SplitSize::Fixed(*cells))
+                } else {
+                    percents.next().map(SplitSize::Percent)
+                }
+            })
+            .collect();
+    }
+}
+
+// rounds the values down and gives what is left to reach `total` to the ones that lost the most
+// in rounding
+fn largest_remainder_round(exact_values: &[f64], total: usize) -> Vec<usize> {
+    let mut values: Vec<usize> = exact_values.iter().map(|v| v.floor() as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..exact_values.len()).collect();
+    by_remainder.sort_by(|a, b| {
+        let remainder = |i: &usize| exact_values[*i] - exact_values[*i].floor();
+        remainder(b)
+            .partial_cmp(&remainder(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let left = total.saturating_sub(values.iter().sum());
+    for i in by_remainder.into_iter().cycle().take(left) {
+        values[i] += 1;
     }
+    values
 }
 
 fn split_space(
     space_to_split: &PaneGeom,
     layout: &TiledPaneLayout,
     total_space_to_split: &PaneGeom,
-) -> Result<Vec<(TiledPaneLayout, PaneGeom)>, &'static str> {
+) -> Result<Vec<(TiledPaneLayout, PaneGeom)>, LayoutApplyError> {
+    let pane_positions = split_space_indexed(space_to_split, layout, total_space_to_split, vec![])?;
+    Ok(pane_positions
+        .into_iter()
+        .map(|(pane_layout, pane_geom, _leaf_path)| (pane_layout, pane_geom))
+        .collect())
+}
+
+/// [`split_space`], along with the leaf path of each pane in `layout`, which is itself at `path`
+/// (the leaf paths start with it)
+fn split_space_indexed(
+    space_to_split: &PaneGeom,
+    layout: &TiledPaneLayout,
+    total_space_to_split: &PaneGeom,
+    path: Vec<usize>,
+) -> Result<Vec<(TiledPaneLayout, PaneGeom, Vec<usize>)>, LayoutApplyError> {
+    // an explicit stack rather than recursion, so that deeply nested layouts cannot overflow the
+    // call stack
     let mut pane_positions = Vec::new();
+    let mut to_split = vec![(Cow::Borrowed(layout), *space_to_split, path)];
+    while let Some((layout, space_to_split, path)) = to_split.pop() {
+        if layout.children.is_empty() {
+            let layout = with_resolved_split_direction(layout, &space_to_split);
+            pane_positions.push((layout.into_owned(), space_to_split, path));
+            continue;
+        }
+        // the children of a layout collapsed into a stack are its panes, which keep their paths
+        let child_paths: Vec<Vec<usize>> = if layout.is_collapsed_into_stack(&space_to_split) {
+            layout.extract_pane_paths()
+        } else {
+            (0..layout.children.len()).map(|i| vec![i]).collect()
+        };
+        let (layout, split_geom) =
+            split_children(&space_to_split, layout, total_space_to_split, &path)?;
+        let children: Vec<Cow<TiledPaneLayout>> = match layout {
+            Cow::Borrowed(layout) => layout.children.iter().map(Cow::Borrowed).collect(),
+            Cow::Owned(layout) => layout.children.into_iter().map(Cow::Owned).collect(),
+        };
+        let child_paths = child_paths.into_iter().map(|child_path| {
+            let mut full_path = path.clone();
+            full_path.extend(child_path);
+            full_path
+        });
+        let children = children.into_iter().zip(split_geom).zip(child_paths);
+        to_split.extend(
+            children
+                .map(|((child, geom), child_path)| (child, geom, child_path))
+                .rev(),
+        );
+    }
+    Ok(pane_positions)
+}
+
+/// `layout` with its `Auto` split direction resolved in `space_to_split` (see
+/// [`SplitDirection::resolve`]), so that the panes it is positioned as show how they were split
+fn with_resolved_split_direction<'a>(
+    layout: Cow<'a, TiledPaneLayout>,
+    space_to_split: &PaneGeom,
+) -> Cow<'a, TiledPaneLayout> {
+    if layout.children_split_direction != SplitDirection::Auto {
+        return layout;
+    }
+    let mut resolved_layout = layout.into_owned();
+    resolved_layout.children_split_direction = SplitDirection::Auto.resolve(space_to_split);
+    Cow::Owned(resolved_layout)
+}
+
+/// The geometries of the children of `layout` in `space_to_split`, along with the layout they are
+/// the children of (which is not `layout` if it is collapsed into a stack)
+fn split_children<'a>(
+    space_to_split: &PaneGeom,
+    layout: Cow<'a, TiledPaneLayout>,
+    total_space_to_split: &PaneGeom,
+    path: &[usize],
+) -> Result<(Cow<'a, TiledPaneLayout>, Vec<PaneGeom>), LayoutApplyError> {
+    let layout = if layout.is_collapsed_into_stack(space_to_split) {
+        Cow::Owned(layout.collapsed_into_stack())
+    } else {
+        layout
+    };
+    let layout = with_resolved_split_direction(layout, space_to_split);
+    if layout.is_deep_stack(space_to_split) {
+        let split_geom = split_deep_stack(space_to_split, &layout, total_space_to_split, path)?;
+        return Ok((layout, split_geom));
+    }
     let sizes: Vec<Option<SplitSize>> = if layout.children_are_stacked {
-        let mut sizes: Vec<Option<SplitSize>> = layout
+        // the expanded pane takes the rows the others leave, one each
+        let expanded_index = layout.expanded_index_in_stack();
+        layout
             .children
             .iter()
-            .map(|_part| Some(SplitSize::Fixed(1)))
-            .collect();
-        if let Some(last_size) = sizes.last_mut() {
-            *last_size = None;
-        }
-        sizes
+            .enumerate()
+            .map(|(i, _part)| {
+                if Some(i) == expanded_index {
+                    None
+                } else {
+                    Some(SplitSize::Fixed(1))
+                }
+            })
+            .collect()
     } else {
         layout.children.iter().map(|part| part.split_size).collect()
     };
 
+    // stacked panes are always laid out one below the other
+    let children_split_direction = if layout.children_are_stacked {
+        SplitDirection::Horizontal
+    } else {
+        layout.children_split_direction
+    };
     let mut split_geom = Vec::new();
     let (
         mut current_position,
         split_dimension_space,
         inherited_dimension,
         total_split_dimension_space,
-    ) = match layout.children_split_direction {
+    ) = match children_split_direction {
         SplitDirection::Vertical => (
             space_to_split.x,
             space_to_split.cols,
             space_to_split.rows,
             total_space_to_split.cols,
         ),
-        SplitDirection::Horizontal => (
+        SplitDirection::Horizontal | SplitDirection::Auto => (
             space_to_split.y,
             space_to_split.rows,
             space_to_split.cols,
@@ -843,14 +4870,41 @@ fn split_space(
     };
 
     let min_size_for_panes = sizes.iter().fold(0, |acc, size| match size {
-        Some(SplitSize::Percent(_)) | None => acc + 1, // TODO: minimum height/width as relevant here
+        Some(SplitSize::Percent(_)) | Some(SplitSize::Weight(_)) | None => acc + 1, // TODO: minimum height/width as relevant here
+        Some(SplitSize::Fixed(fixed)) => acc + fixed,
+    });
+    let fixed_size_of_panes = sizes.iter().fold(0, |acc, size| match size {
         Some(SplitSize::Fixed(fixed)) => acc + fixed,
+        _ => acc,
     });
-    if min_size_for_panes > split_dimension_space.as_usize() {
-        return Err("Not enough room for panes"); // TODO: use error infra
+    let available = split_dimension_space.as_usize();
+    let pane = || LayoutPanePath::new(layout.name.clone(), path.to_vec());
+    if fixed_size_of_panes > available {
+        return Err(LayoutApplyError::FixedSizeOverflow {
+            pane: pane(),
+            needed: fixed_size_of_panes,
+            available,
+            direction: children_split_direction,
+        });
+    } else if min_size_for_panes > available {
+        return Err(LayoutApplyError::NotEnoughRoom {
+            pane: pane(),
+            needed: min_size_for_panes,
+            available,
+            direction: children_split_direction,
+        });
     }
 
-    let flex_parts = sizes.iter().filter(|s| s.is_none()).count();
+    // the space the percent sizes leave is shared by the weighted panes and the panes without a
+    // size, which have a weight of 1
+    let total_weight: usize = sizes
+        .iter()
+        .map(|s| match s {
+            Some(SplitSize::Weight(weight)) => *weight,
+            None => 1,
+            _ => 0,
+        })
+        .sum();
     let total_fixed_size = sizes.iter().fold(0, |acc, s| {
         if let Some(SplitSize::Fixed(fixed)) = s {
             acc + fixed
@@ -858,35 +4912,95 @@ fn split_space(
             acc
         }
     });
+    // a container of a fixed size never resizes, so the panes without a size in it share the
+    // cells the others leave as fixed sizes of their own, at least one cell each
+    let mut flexible_cells = if split_dimension_space.is_fixed() {
+        let percent_cells: usize = sizes
+            .iter()
+            .map(|s| match s {
+                Some(SplitSize::Percent(percent)) => {
+                    let mut dimension = Dimension::percent(*percent as f64);
+                    dimension.adjust_inner(
+                        total_split_dimension_space
+                            .as_usize()
+                            .saturating_sub(total_fixed_size),
+                    );
+                    dimension.as_usize()
+                },
+                _ => 0,
+            })
+            .sum();
+        let weights: Vec<usize> = sizes
+            .iter()
+            .filter_map(|s| match s {
+                Some(SplitSize::Weight(weight)) => Some(*weight),
+                None => Some(1),
+                _ => None,
+            })
+            .collect();
+        let remaining = available.saturating_sub(fixed_size_of_panes + percent_cells);
+        if remaining < weights.len() {
+            return Err(LayoutApplyError::NotEnoughRoom {
+                pane: pane(),
+                needed: fixed_size_of_panes + percent_cells + weights.len(),
+                available,
+                direction: children_split_direction,
+            });
+        }
+        let shared = remaining - weights.len();
+        let exact_cells: Vec<f64> = weights
+            .iter()
+            .map(|weight| 1.0 + (shared * weight) as f64 / total_weight.max(1) as f64)
+            .collect();
+        largest_remainder_round(&exact_cells, remaining).into_iter()
+    } else {
+        vec![].into_iter()
+    };
 
-    let mut total_pane_size = 0;
-    for (&size, _part) in sizes.iter().zip(&*layout.children) {
-        let mut split_dimension = match size {
+    let percent_space = total_split_dimension_space
+        .as_usize()
+        .saturating_sub(total_fixed_size);
+    let split_dimensions: Vec<Dimension> = sizes
+        .iter()
+        .map(|&size| match size {
             Some(SplitSize::Percent(percent)) => Dimension::percent(percent as f64),
             Some(SplitSize::Fixed(size)) => Dimension::fixed(size),
-            None => {
-                let free_percent = if let Some(p) = split_dimension_space.as_percent() {
-                    p - sizes
-                        .iter()
-                        .map(|&s| match s {
-                            Some(SplitSize::Percent(ip)) => ip as f64,
-                            _ => 0.0,
-                        })
-                        .sum::<f64>()
-                } else {
-                    panic!("Implicit sizing within fixed-size panes is not supported");
+            Some(SplitSize::Weight(_)) | None => {
+                let weight = match size {
+                    Some(SplitSize::Weight(weight)) => weight,
+                    _ => 1,
                 };
-                Dimension::percent(free_percent / flex_parts as f64)
+                match split_dimension_space.as_percent() {
+                    Some(p) => {
+                        let free_percent = p - sizes
+                            .iter()
+                            .map(|&s| match s {
+                                Some(SplitSize::Percent(ip)) => ip as f64,
+                                _ => 0.0,
+                            })
+                            .sum::<f64>();
+                        Dimension::percent(free_percent * weight as f64 / total_weight as f64)
+                    },
+                    None => Dimension::fixed(flexible_cells.next().unwrap_or(1)),
+                }
             },
-        };
-        split_dimension.adjust_inner(
-            total_split_dimension_space
-                .as_usize()
-                .saturating_sub(total_fixed_size),
-        );
-        total_pane_size += split_dimension.as_usize();
-
-        let geom = match layout.children_split_direction {
+        })
+        .collect();
+    let exact_sizes: Vec<f64> = split_dimensions
+        .iter()
+        .map(|dimension| match dimension.as_percent() {
+            Some(percent) => (percent / 100.0) * percent_space as f64,
+            None => dimension.as_usize() as f64,
+        })
+        .collect();
+    let is_flexible: Vec<bool> = split_dimensions
+        .iter()
+        .map(|dimension| dimension.is_percent())
+        .collect();
+    let cell_sizes = round_to_space(&exact_sizes, &is_flexible, available);
+    for (mut split_dimension, cells) in split_dimensions.into_iter().zip(cell_sizes) {
+        split_dimension.set_inner(cells);
+        let geom = match children_split_direction {
             SplitDirection::Vertical => PaneGeom {
                 x: current_position,
                 y: space_to_split.y,
@@ -894,7 +5008,7 @@ fn split_space(
                 rows: inherited_dimension,
                 is_stacked: layout.children_are_stacked,
             },
-            SplitDirection::Horizontal => PaneGeom {
+            SplitDirection::Horizontal | SplitDirection::Auto => PaneGeom {
                 x: space_to_split.x,
                 y: current_position,
                 cols: inherited_dimension,
@@ -903,44 +5017,169 @@ fn split_space(
             },
         };
         split_geom.push(geom);
-        current_position += split_dimension.as_usize();
+        current_position += cells;
     }
+    if !layout.children_are_stacked {
+        // the panes of a stack are one line each but for the expanded one, whatever their bounds
+        clamp_to_min_and_max_sizes(
+            &layout,
+            &mut split_geom,
+            children_split_direction,
+            split_dimension_space.as_usize(),
+            path,
+        )?;
+    }
+    Ok((layout, split_geom))
+}
 
-    if total_pane_size < split_dimension_space.as_usize() {
-        // add extra space from rounding errors to the last pane
-        let increase_by = split_dimension_space.as_usize() - total_pane_size;
-        if let Some(last_geom) = split_geom.last_mut() {
-            match layout.children_split_direction {
-                SplitDirection::Vertical => last_geom.cols.increase_inner(increase_by),
-                SplitDirection::Horizontal => last_geom.rows.increase_inner(increase_by),
-            }
+fn split_dimension_mut(geom: &mut PaneGeom, direction: SplitDirection) -> &mut Dimension {
+    match direction {
+        SplitDirection::Vertical => &mut geom.cols,
+        SplitDirection::Horizontal | SplitDirection::Auto => &mut geom.rows,
+    }
+}
+
+/// Keeps the children of `layout` that are not of a fixed size within their `min_size` and
+/// `max_size`, giving the space this takes or leaves to the other flexible children. Their sizes
+/// are scaled together until they fill `space` again, the ones at a bound staying at it.
+fn clamp_to_min_and_max_sizes(
+    layout: &TiledPaneLayout,
+    split_geom: &mut [PaneGeom],
+    direction: SplitDirection,
+    space: usize,
+    path: &[usize],
+) -> Result<(), LayoutApplyError> {
+    if layout
+        .children
+        .iter()
+        .all(|child| child.min_size.is_none() && child.max_size.is_none())
+    {
+        return Ok(());
+    }
+    let in_cells = |size: Option<SplitSize>| match size {
+        Some(SplitSize::Fixed(fixed)) => Some(fixed),
+        Some(SplitSize::Percent(percent)) => Some(space * percent / 100),
+        Some(SplitSize::Weight(_)) | None => None, // weights are not bounds, see the parser
+    };
+    let mut fixed_space = 0;
+    let mut flexible = vec![]; // (index, size as split, min, max)
+    for (i, (child, geom)) in layout
+        .children
+        .iter()
+        .zip(split_geom.iter_mut())
+        .enumerate()
+    {
+        let size = split_dimension_mut(geom, direction).as_usize();
+        if let Some(SplitSize::Fixed(_)) = child.split_size {
+            fixed_space += size;
+            continue;
         }
-    } else if total_pane_size > split_dimension_space.as_usize() {
-        // remove extra space from rounding errors to the last pane
-        let decrease_by = total_pane_size - split_dimension_space.as_usize();
-        if let Some(last_geom) = split_geom.last_mut() {
-            match layout.children_split_direction {
-                SplitDirection::Vertical => last_geom.cols.decrease_inner(decrease_by),
-                SplitDirection::Horizontal => last_geom.rows.decrease_inner(decrease_by),
-            }
+        let min = in_cells(child.min_size).unwrap_or(1).max(1);
+        let max = in_cells(child.max_size);
+        if max.map(|max| max < min).unwrap_or(false) {
+            let mut child_path = path.to_vec();
+            child_path.push(i);
+            return Err(LayoutApplyError::MinSizeAboveMaxSize {
+                pane: LayoutPanePath::new(child.name.clone(), child_path),
+            });
         }
+        flexible.push((i, size as f64, min, max));
+    }
+    if flexible.is_empty() {
+        return Ok(());
+    }
+    let flexible_space = space.saturating_sub(fixed_space);
+    let pane = || LayoutPanePath::new(layout.name.clone(), path.to_vec());
+    let min_space: usize = flexible.iter().map(|(_, _, min, _)| min).sum();
+    if min_space > flexible_space {
+        return Err(LayoutApplyError::MinSizeOverflow {
+            pane: pane(),
+            needed: min_space,
+            available: flexible_space,
+            direction,
+        });
+    }
+    let max_space: Option<usize> = flexible.iter().map(|(_, _, _, max)| *max).sum();
+    if max_space
+        .map(|max_space| max_space < flexible_space)
+        .unwrap_or(false)
+    {
+        return Err(LayoutApplyError::MaxSizeUnderflow {
+            pane: pane(),
+            available: flexible_space,
+            direction,
+        });
+    }
+
+    let scaled = |scale: f64| -> Vec<f64> {
+        flexible
+            .iter()
+            .map(|(_, size, min, max)| {
+                (size * scale)
+                    .max(*min as f64)
+                    .min(max.map(|max| max as f64).unwrap_or(f64::INFINITY))
+            })
+            .collect()
+    };
+    let fills_space = |scale: f64| scaled(scale).iter().sum::<f64>() >= flexible_space as f64;
+    let (mut low, mut high) = (0.0, 1.0);
+    while !fills_space(high) {
+        high *= 2.0;
     }
-    for (i, part) in layout.children.iter().enumerate() {
-        let part_position_and_size = split_geom.get(i).unwrap();
-        if !part.children.is_empty() {
-            let mut part_positions =
-                split_space(part_position_and_size, part, total_space_to_split)?;
-            pane_positions.append(&mut part_positions);
+    for _ in 0..64 {
+        let middle = (low + high) / 2.0;
+        if fills_space(middle) {
+            high = middle;
         } else {
-            let part = part.clone();
-            pane_positions.push((part, *part_position_and_size));
+            low = middle;
         }
     }
-    if pane_positions.is_empty() {
-        let layout = layout.clone();
-        pane_positions.push((layout, space_to_split.clone()));
+    let sizes = largest_remainder_round(&scaled(high), flexible_space);
+    for ((i, previous_size, _, _), size) in flexible.iter().zip(sizes) {
+        let dimension = split_dimension_mut(&mut split_geom[*i], direction);
+        if let (Some(percent), true) = (dimension.as_percent(), *previous_size > 0.0) {
+            // so that resizing the pane later keeps its proportions as clamped
+            dimension.set_percent(percent * size as f64 / previous_size);
+        }
+        dimension.set_inner(size);
     }
-    Ok(pane_positions)
+    let mut position = match direction {
+        SplitDirection::Vertical => split_geom.first().map(|geom| geom.x),
+        SplitDirection::Horizontal | SplitDirection::Auto => split_geom.first().map(|geom| geom.y),
+    }
+    .unwrap_or(0);
+    for geom in split_geom.iter_mut() {
+        match direction {
+            SplitDirection::Vertical => geom.x = position,
+            SplitDirection::Horizontal | SplitDirection::Auto => geom.y = position,
+        }
+        position += split_dimension_mut(geom, direction).as_usize();
+    }
+    Ok(())
+}
+
+fn split_deep_stack(
+    space_to_split: &PaneGeom,
+    layout: &TiledPaneLayout,
+    total_space_to_split: &PaneGeom,
+    path: &[usize],
+) -> Result<Vec<PaneGeom>, LayoutApplyError> {
+    // the stack is laid out as if it only had two panes, all but its last pane sharing the
+    // one-line row of the first
+    let two_pane_stack = TiledPaneLayout {
+        children: vec![TiledPaneLayout::default(), TiledPaneLayout::default()],
+        ..layout.clone()
+    };
+    let (_, two_pane_geoms) = split_children(
+        space_to_split,
+        Cow::Owned(two_pane_stack),
+        total_space_to_split,
+        path,
+    )?;
+    let (shared_row, expanded_pane) = (two_pane_geoms[0], two_pane_geoms[1]);
+    let mut split_geom = vec![shared_row; layout.children.len() - 1];
+    split_geom.push(expanded_pane);
+    Ok(split_geom)
 }
 
 impl TryFrom<Url> for RunPluginLocation {
@@ -964,13 +5203,41 @@ impl Default for SplitDirection {
     }
 }
 
+impl FromStr for TabPin {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first" | "First" => Ok(TabPin::First),
+            "last" | "Last" => Ok(TabPin::Last),
+            _ => Err("tab pin must be either first or last".into()),
+        }
+    }
+}
+
+impl FromStr for FocusPolicy {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first" => Ok(FocusPolicy::First),
+            "deepest" => Ok(FocusPolicy::Deepest),
+            "largest" => Ok(FocusPolicy::Largest),
+            "last" => Ok(FocusPolicy::Last),
+            _ => Err(
+                "default_focus must be one of \"first\", \"deepest\", \"largest\" or \"last\""
+                    .into(),
+            ),
+        }
+    }
+}
+
 impl FromStr for SplitDirection {
     type Err = Box<dyn std::error::Error>;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "vertical" | "Vertical" => Ok(SplitDirection::Vertical),
             "horizontal" | "Horizontal" => Ok(SplitDirection::Horizontal),
-            _ => Err("split direction must be either vertical or horizontal".into()),
+            "auto" | "Auto" => Ok(SplitDirection::Auto),
+            _ => Err("split direction must be either vertical, horizontal or auto".into()),
         }
     }
 }
@@ -978,7 +5245,14 @@ impl FromStr for SplitDirection {
 impl FromStr for SplitSize {
     type Err = Box<dyn std::error::Error>;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.chars().last() == Some('%') {
+        if let Some(weight) = s.strip_suffix('w') {
+            let weight = usize::from_str_radix(weight, 10)?;
+            if weight > 0 {
+                Ok(SplitSize::Weight(weight))
+            } else {
+                Err("Weight must be greater than 0".into())
+            }
+        } else if s.chars().last() == Some('%') {
             let char_count = s.chars().count();
             let percent_size = usize::from_str_radix(&s[..char_count.saturating_sub(1)], 10)?;
             if percent_size > 0 && percent_size <= 100 {
@@ -0,0 +1,200 @@
+//! Why the panes of a layout cannot be positioned in a space (see
+//! [`TiledPaneLayout::position_panes_in_space`]), naming the pane of the layout at fault so that
+//! the user knows what to shrink
+//!
+//! [`TiledPaneLayout::position_panes_in_space`]: crate::input::layout::TiledPaneLayout::position_panes_in_space
+use crate::input::layout::SplitDirection;
+use std::fmt;
+
+/// A pane of a layout, by its name if it has one and otherwise by where it is in the layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutPanePath {
+    pub name: Option<String>,
+    /// The child indices leading to the pane in the layout, empty for the layout itself
+    pub path: Vec<usize>,
+}
+
+impl LayoutPanePath {
+    pub fn new(name: Option<String>, path: Vec<usize>) -> Self {
+        LayoutPanePath { name, path }
+    }
+    /// The pane by where it is in the layout along with its name if it has one (eg. `pane 2.1
+    /// "logs"`), for the panes to be found in the layout even when their names repeat or were
+    /// changed by a pane template
+    pub fn located(&self) -> String {
+        match &self.name {
+            Some(name) if !self.path.is_empty() => {
+                let unnamed = LayoutPanePath::new(None, self.path.clone());
+                format!("{} \"{}\"", unnamed, name)
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LayoutPanePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "pane \"{}\"", name),
+            None if self.path.is_empty() => write!(f, "the layout"),
+            None => {
+                // the position of the pane among its siblings and of each pane it is in, from 1
+                let positions: Vec<String> = self
+                    .path
+                    .iter()
+                    .map(|index| (index + 1).to_string())
+                    .collect();
+                write!(f, "pane {}", positions.join("."))
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutApplyError {
+    /// The children of `pane` need more room than it has, even at a single cell each for those
+    /// without a fixed size
+    NotEnoughRoom {
+        pane: LayoutPanePath,
+        needed: usize,
+        available: usize,
+        direction: SplitDirection,
+    },
+    /// The children of `pane` with a fixed size alone take more room than it has
+    FixedSizeOverflow {
+        pane: LayoutPanePath,
+        needed: usize,
+        available: usize,
+        direction: SplitDirection,
+    },
+    /// The pane was positioned but is too small to be shown
+    BelowMinimumSize {
+        pane: LayoutPanePath,
+    },
+    MinSizeAboveMaxSize {
+        pane: LayoutPanePath,
+    },
+    /// The min_size of the children of `pane` add up to more room than it has
+    MinSizeOverflow {
+        pane: LayoutPanePath,
+        needed: usize,
+        available: usize,
+        direction: SplitDirection,
+    },
+    /// The max_size of the children of `pane` add up to less room than it has
+    MaxSizeUnderflow {
+        pane: LayoutPanePath,
+        available: usize,
+        direction: SplitDirection,
+    },
+    /// The side panel leaves no room for the tabs, or has none itself
+    SidePanelOverflow {
+        available: usize,
+    },
+    /// The geometries given to [`TiledPaneLayout::fit_split_sizes`] are not one per pane
+    ///
+    /// [`TiledPaneLayout::fit_split_sizes`]: crate::input::layout::TiledPaneLayout::fit_split_sizes
+    PaneCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// No split sizes of the children of `pane` give them the geometries they were asked to fit
+    NoFittingSplitSizes {
+        pane: LayoutPanePath,
+    },
+}
+
+fn cells(direction: &SplitDirection) -> &'static str {
+    match direction {
+        SplitDirection::Vertical => "columns",
+        SplitDirection::Horizontal | SplitDirection::Auto => "rows",
+    }
+}
+
+impl fmt::Display for LayoutApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutApplyError::NotEnoughRoom {
+                pane,
+                needed,
+                available,
+                direction,
+            } => write!(
+                f,
+                "Not enough room for the panes in {}: they need at least {} {} but have {}",
+                pane.located(),
+                needed,
+                cells(direction),
+                available
+            ),
+            LayoutApplyError::FixedSizeOverflow {
+                pane,
+                needed,
+                available,
+                direction,
+            } => write!(
+                f,
+                "The fixed size panes in {} take {} {} but only {} are available",
+                pane.located(),
+                needed,
+                cells(direction),
+                available
+            ),
+            LayoutApplyError::BelowMinimumSize { pane } => {
+                write!(f, "No room on screen for {}", pane.located())
+            },
+            LayoutApplyError::MinSizeAboveMaxSize { pane } => {
+                write!(
+                    f,
+                    "The min_size of {} is larger than its max_size",
+                    pane.located()
+                )
+            },
+            LayoutApplyError::MinSizeOverflow {
+                pane,
+                needed,
+                available,
+                direction,
+            } => write!(
+                f,
+                "Not enough room for the min_size of the panes in {}: they need {} {} but have {}",
+                pane.located(),
+                needed,
+                cells(direction),
+                available
+            ),
+            LayoutApplyError::MaxSizeUnderflow {
+                pane,
+                available,
+                direction,
+            } => write!(
+                f,
+                "The panes in {} cannot fill its {} {} without growing past their max_size",
+                pane.located(),
+                available,
+                cells(direction)
+            ),
+            LayoutApplyError::SidePanelOverflow { available } => write!(
+                f,
+                "Not enough room for the side panel and the tabs in {} columns",
+                available
+            ),
+            LayoutApplyError::PaneCountMismatch { expected, found } => write!(
+                f,
+                "{} pane geometries were given for the {} panes of the layout",
+                found, expected
+            ),
+            LayoutApplyError::NoFittingSplitSizes { pane } => write!(
+                f,
+                "Could not find split sizes for the pane geometries in {}",
+                pane.located()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutApplyError {}
+
+#[cfg(test)]
+#[path = "./unit/layout_apply_error_test.rs"]
+mod layout_apply_error_test;
@@ -0,0 +1,369 @@
+//! The layout format features this version of zellij understands, so that tools generating
+//! layouts can emit only supported syntax (`zellij setup --layout-capabilities`)
+use crate::consts::VERSION;
+use serde_json::{json, Map, Value};
+
+pub const STACKED_PANES: &str = "stacked_panes";
+pub const SIZE_OFFSETS: &str = "size_offsets";
+pub const SWAP_CONSTRAINTS: &str = "swap_constraints";
+pub const SWAP_EXTRA_PANES: &str = "swap_extra_panes";
+pub const SWAP_MISSING_SLOTS: &str = "swap_missing_slots";
+pub const SWAP_TAGS: &str = "swap_tags";
+pub const PANE_REFS: &str = "pane_refs";
+pub const TAB_PIN: &str = "tab_pin";
+pub const PANE_DEPENDENCIES: &str = "pane_dependencies";
+pub const SIDE_PANEL: &str = "side_panel";
+pub const STACK_BELOW_WIDTH: &str = "stack_below_width";
+pub const COMMAND_SPAWN_INTERVAL: &str = "command_spawn_interval";
+pub const READ_ONLY_PANES: &str = "read_only_panes";
+pub const HIDDEN_PANE_TITLES: &str = "hidden_pane_titles";
+pub const DEFAULT_FOCUS: &str = "default_focus";
+pub const COMMAND_WRAPPER: &str = "command_wrapper";
+pub const PANE_ENCODING: &str = "pane_encoding";
+pub const MAX_CONTENT_WIDTH: &str = "max_content_width";
+pub const EXCLUDE_FROM_DUMP: &str = "exclude_from_dump";
+pub const FIT_TO_CONTENT: &str = "fit_to_content";
+pub const SESSION_NAME: &str = "session_name";
+pub const WEIGHTED_SIZES: &str = "weighted_sizes";
+pub const SIZE_BOUNDS: &str = "size_bounds";
+pub const TAB_SWAP_LAYOUTS: &str = "tab_swap_layouts";
+pub const CONDITIONAL_PANES: &str = "conditional_panes";
+pub const PINNED_FLOATING_PANES: &str = "pinned_floating_panes";
+pub const FLOATING_PANE_ANCHORS: &str = "floating_pane_anchors";
+pub const FAR_EDGE_COORDINATES: &str = "far_edge_coordinates";
+pub const FLOATING_PANE_MAX_SIZE: &str = "floating_pane_max_size";
+pub const PANE_ENV: &str = "pane_env";
+pub const PANE_NAME_PLACEHOLDERS: &str = "pane_name_placeholders";
+pub const FOCUSED_TAB_NAME: &str = "focused_tab_name";
+pub const AUTO_SPLIT_DIRECTION: &str = "auto_split_direction";
+pub const EXPANDED_STACKED_PANE: &str = "expanded_stacked_pane";
+pub const FLOATING_STACKS: &str = "floating_stacks";
+pub const LAYOUT_VARIABLES: &str = "layout_variables";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilitySupport {
+    Supported,
+    Unsupported,
+    Values(&'static [&'static str]), // the values the layout format accepts for it
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutCapability {
+    pub id: &'static str,
+    pub support: CapabilitySupport,
+    /// A layout using the capability, the tests parse it so that nothing is advertised without
+    /// being parsed
+    pub example: Option<&'static str>,
+}
+
+pub const LAYOUT_CAPABILITIES: &[LayoutCapability] = &[
+    LayoutCapability {
+        id: STACKED_PANES,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane { pane; children stacked=true; }; }"),
+    },
+    LayoutCapability {
+        id: SIZE_OFFSETS,
+        support: CapabilitySupport::Unsupported,
+        example: None,
+    },
+    LayoutCapability {
+        id: SWAP_CONSTRAINTS,
+        support: CapabilitySupport::Values(&[
+            "exact_panes",
+            "min_panes",
+            "max_panes",
+            "min_groups",
+            "max_groups",
+            "session_tabs_max",
+            "session_tabs_min",
+        ]),
+        example: Some(
+            "layout { swap_tiled_layout { tab min_panes=2 { pane; pane; }; tab max_panes=2 { pane; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: SWAP_EXTRA_PANES,
+        support: CapabilitySupport::Values(&["stack_last", "append_after_focused", "new_stack"]),
+        example: Some(
+            "layout { swap_tiled_layout { tab extra_panes=\"new_stack\" { pane; pane; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: SWAP_MISSING_SLOTS,
+        support: CapabilitySupport::Values(&["collapse", "keep_empty"]),
+        example: Some(
+            "layout { swap_tiled_layout { tab missing_slots=\"keep_empty\" { pane; pane; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: SWAP_TAGS,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { swap_tiled_layout { tags \"review\" \"wide\"; tab { pane; pane; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: PANE_REFS,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { pane name=\"editor\"; pane; swap_tiled_layout { tab { pane ref=\"editor\" size=\"+10%\"; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: TAB_PIN,
+        support: CapabilitySupport::Values(&["first", "last"]),
+        example: Some("layout { tab pin=\"first\"; tab pin=\"last\"; }"),
+    },
+    LayoutCapability {
+        id: PANE_DEPENDENCIES,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { pane name=\"db\" command=\"postgres\"; pane command=\"cargo\" depends_on=\"db\" ready_when=\"ready\" ready_timeout=30; }",
+        ),
+    },
+    LayoutCapability {
+        id: SIDE_PANEL,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { side_panel width=\"25%\" { pane; }; pane; }"),
+    },
+    LayoutCapability {
+        id: STACK_BELOW_WIDTH,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane stack_below_width=80 { pane; pane; }; }"),
+    },
+    LayoutCapability {
+        id: COMMAND_SPAWN_INTERVAL,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { command_spawn_interval_ms 100; pane; }"),
+    },
+    LayoutCapability {
+        id: READ_ONLY_PANES,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane read_only=true; }"),
+    },
+    LayoutCapability {
+        id: HIDDEN_PANE_TITLES,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane command=\"htop\" hide_title=true; }"),
+    },
+    LayoutCapability {
+        id: DEFAULT_FOCUS,
+        support: CapabilitySupport::Values(&["first", "deepest", "largest", "last"]),
+        example: Some("layout { default_focus \"largest\"; pane size=\"30%\"; pane; }"),
+    },
+    LayoutCapability {
+        id: COMMAND_WRAPPER,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { command_wrapper \"devcontainer\" { args \"exec\" \"--\"; }; pane command=\"cargo\"; pane no_wrapper=true; }",
+        ),
+    },
+    LayoutCapability {
+        id: PANE_ENCODING,
+        support: CapabilitySupport::Values(&["utf8", "latin1", "shift-jis"]),
+        example: Some("layout { pane command=\"legacy-report\" encoding=\"latin1\"; }"),
+    },
+    LayoutCapability {
+        id: MAX_CONTENT_WIDTH,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { tab name=\"write\" max_content_width=100 { pane; }; }"),
+    },
+    LayoutCapability {
+        id: EXCLUDE_FROM_DUMP,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane command=\"deploy\" exclude_from_dump=true; }"),
+    },
+    LayoutCapability {
+        id: FIT_TO_CONTENT,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { floating_panes { pane command=\"cal\" height=\"fit\" max_height=20; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: SESSION_NAME,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { session_name \"acme-api\"; attach_to_existing true; pane; }"),
+    },
+    LayoutCapability {
+        id: WEIGHTED_SIZES,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane size=\"2w\"; pane size=\"1w\"; }"),
+    },
+    LayoutCapability {
+        id: SIZE_BOUNDS,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane min_size=30; pane max_size=\"50%\"; }"),
+    },
+    LayoutCapability {
+        id: TAB_SWAP_LAYOUTS,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { tab { pane; swap_tiled_layout { tab max_panes=2 { pane; pane; }; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: CONDITIONAL_PANES,
+        support: CapabilitySupport::Values(&["if_env", "if_command_exists"]),
+        example: Some(
+            "layout { tab if_command_exists=\"cargo\" { pane; pane command=\"docker\" if_env=\"USE_DOCKER\"; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: PINNED_FLOATING_PANES,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane; floating_panes { pane pinned=true x=\"70%\" y=1; }; }"),
+    },
+    LayoutCapability {
+        id: FLOATING_PANE_ANCHORS,
+        support: CapabilitySupport::Values(&[
+            "top_left",
+            "top_right",
+            "bottom_left",
+            "bottom_right",
+            "center",
+        ]),
+        example: Some(
+            "layout { pane; floating_panes { pane anchor=\"bottom_right\" x=2 y=1 width=40; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: FAR_EDGE_COORDINATES,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane; floating_panes { pane x=-2 y=\"-10%\" width=40; }; }"),
+    },
+    LayoutCapability {
+        id: FLOATING_PANE_MAX_SIZE,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { pane; floating_panes { pane width=120 max_width=\"90%\" max_height=20; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: PANE_ENV,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { pane command=\"cargo\" { args \"watch\"; env { RUST_LOG \"debug\"; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: PANE_NAME_PLACEHOLDERS,
+        support: CapabilitySupport::Values(&["command", "args", "cwd", "index", "tab"]),
+        example: Some("layout { pane name=\"{index}: {command} ({cwd})\" command=\"htop\"; }"),
+    },
+    LayoutCapability {
+        id: FOCUSED_TAB_NAME,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { focused_tab \"logs\"; tab name=\"code\"; tab name=\"logs\"; }"),
+    },
+    LayoutCapability {
+        id: AUTO_SPLIT_DIRECTION,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane split_direction=\"auto\" { pane; pane; }; }"),
+    },
+    LayoutCapability {
+        id: EXPANDED_STACKED_PANE,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { pane { pane expanded=true; pane; children stacked=true; }; }"),
+    },
+    LayoutCapability {
+        id: FLOATING_STACKS,
+        support: CapabilitySupport::Supported,
+        example: Some(
+            "layout { floating_panes { stack x=2 y=2 width=80 height=20 { pane; pane expanded=true; }; }; }",
+        ),
+    },
+    LayoutCapability {
+        id: LAYOUT_VARIABLES,
+        support: CapabilitySupport::Supported,
+        example: Some("layout { vars { branch \"main\"; }; pane name=\"{{branch}}\"; }"),
+    },
+];
+
+/// Where a deprecated name can appear in a layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeprecatedNameKind {
+    /// A property of a node or the child node it is also written as (eg. `pane direction="vertical"`
+    /// or `pane { direction "vertical"; }`), anywhere but in plugin configuration
+    Attribute,
+    /// A node directly under the `layout` node
+    LayoutNode,
+}
+
+/// An old spelling the layout format still understands, translated to `new` when parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeprecatedName {
+    pub old: &'static str,
+    pub new: &'static str,
+    pub kind: DeprecatedNameKind,
+}
+
+/// The deprecated spellings of the layout format, a lenient parse accepts them with a warning and
+/// a strict one refuses them, both naming the replacement
+pub const DEPRECATED_NAMES: &[DeprecatedName] = &[
+    // the names of the yaml layouts
+    DeprecatedName {
+        old: "direction",
+        new: "split_direction",
+        kind: DeprecatedNameKind::Attribute,
+    },
+    DeprecatedName {
+        old: "split_size",
+        new: "size",
+        kind: DeprecatedNameKind::Attribute,
+    },
+    DeprecatedName {
+        old: "template",
+        new: "default_tab_template",
+        kind: DeprecatedNameKind::LayoutNode,
+    },
+];
+
+/// The replacement of a deprecated name, if it is one
+pub fn deprecated_name(kind: DeprecatedNameKind, old: &str) -> Option<&'static DeprecatedName> {
+    DEPRECATED_NAMES
+        .iter()
+        .find(|deprecated| deprecated.kind == kind && deprecated.old == old)
+}
+
+/// The values the layout format accepts for a capability, empty if it is not a capability with
+/// values
+pub fn layout_capability_values(id: &str) -> &'static [&'static str] {
+    LAYOUT_CAPABILITIES
+        .iter()
+        .find(|capability| capability.id == id)
+        .and_then(|capability| match capability.support {
+            CapabilitySupport::Values(values) => Some(values),
+            _ => None,
+        })
+        .unwrap_or(&[])
+}
+
+/// The capabilities as printed by `zellij setup --layout-capabilities`
+pub fn layout_capabilities_json() -> Value {
+    let capabilities: Map<String, Value> = LAYOUT_CAPABILITIES
+        .iter()
+        .map(|capability| {
+            let support = match capability.support {
+                CapabilitySupport::Supported => json!(true),
+                CapabilitySupport::Unsupported => json!(false),
+                CapabilitySupport::Values(values) => json!(values),
+            };
+            (capability.id.to_owned(), support)
+        })
+        .collect();
+    let deprecated: Map<String, Value> = DEPRECATED_NAMES
+        .iter()
+        .map(|deprecated| (deprecated.old.to_owned(), json!(deprecated.new)))
+        .collect();
+    json!({
+        "version": VERSION,
+        "capabilities": capabilities,
+        "deprecated": deprecated,
+    })
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_capabilities_test.rs"]
+mod layout_capabilities_test;
@@ -0,0 +1,352 @@
+//! Everything a layout will run once loaded, so that it can be reviewed without starting a
+//! session (`zellij --layout <layout> --dry-run`)
+use crate::{
+    envs::EnvironmentVariables,
+    input::{
+        command::CommandWrapper,
+        layout::{FitToContent, FloatingPaneLayout, Layout, PercentOrFixed, Run, TiledPaneLayout},
+        plugins::PluginsConfig,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayoutManifest {
+    pub tabs: Vec<TabManifest>,
+    pub side_panel: Option<Vec<PaneManifest>>,
+    /// The environment variables set for everything the layout runs
+    pub env: BTreeMap<String, String>,
+    /// The commands of the panes are listed as written in the layout, not as run by the wrapper
+    pub command_wrapper: Option<CommandWrapper>,
+}
+
+/// The panes of a layout tab, tiled panes first in the order they appear in the layout and then
+/// floating panes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TabManifest {
+    pub name: Option<String>,
+    pub panes: Vec<PaneManifest>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaneManifest {
+    pub name: Option<String>,
+    pub runs: Option<PaneRunManifest>, // None for a shell
+    pub cwd: Option<PathBuf>,          // None when started in the session's cwd
+    pub floating: Option<FloatingGeometry>,
+    pub no_wrapper: bool,
+    pub redacted: bool, // marked with `exclude_from_dump`, what it runs is left out
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PaneRunManifest {
+    Command {
+        command: PathBuf,
+        args: Vec<String>,
+        waits_for: Option<String>, // the name of the pane it depends on
+    },
+    EditFile(Vec<PathBuf>, Option<usize>), // the line number is the one of the first file
+    Plugin {
+        location: String,
+        path: Option<PathBuf>, // None when the location is an alias missing from the config
+    },
+}
+
+/// The position and size of a floating pane as given in the layout, `None` for the ones left to
+/// zellij
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FloatingGeometry {
+    pub x: Option<String>,
+    pub y: Option<String>,
+    pub width: Option<String>,
+    pub height: Option<String>,
+}
+
+impl Layout {
+    /// Lists what each tab of the layout (or the new tab template of a layout without tabs)
+    /// runs, with plugin aliases resolved through `plugins`
+    pub fn manifest(&self, plugins: &PluginsConfig, env: &EnvironmentVariables) -> LayoutManifest {
+        self.manifest_with_redactions(plugins, env, true)
+    }
+    /// Like [`Layout::manifest`] but also listing what the panes marked with
+    /// `exclude_from_dump` run, for the user to review before the layout runs anything
+    pub fn manifest_for_review(
+        &self,
+        plugins: &PluginsConfig,
+        env: &EnvironmentVariables,
+    ) -> LayoutManifest {
+        self.manifest_with_redactions(plugins, env, false)
+    }
+    fn manifest_with_redactions(
+        &self,
+        plugins: &PluginsConfig,
+        env: &EnvironmentVariables,
+        redact: bool,
+    ) -> LayoutManifest {
+        let tabs = if self.tabs.is_empty() {
+            let (tiled_panes, floating_panes) = self.new_tab();
+            vec![TabManifest {
+                name: None,
+                panes: pane_manifests(&tiled_panes, &floating_panes, plugins, redact),
+            }]
+        } else {
+            self.tabs
+                .iter()
+                .map(|(tab_name, tiled_panes, floating_panes, ..)| TabManifest {
+                    name: tab_name.clone(),
+                    panes: pane_manifests(tiled_panes, floating_panes, plugins, redact),
+                })
+                .collect()
+        };
+        LayoutManifest {
+            tabs,
+            side_panel: self
+                .side_panel
+                .as_ref()
+                .map(|side_panel| pane_manifests(&side_panel.pane, &[], plugins, redact)),
+            env: env
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            command_wrapper: self.command_wrapper.clone(),
+        }
+    }
+}
+
+fn pane_manifests(
+    tiled_panes: &TiledPaneLayout,
+    floating_panes: &[FloatingPaneLayout],
+    plugins: &PluginsConfig,
+    redact: bool,
+) -> Vec<PaneManifest> {
+    let mut panes: Vec<PaneManifest> = tiled_panes
+        .extract_run_instructions_indexed()
+        .into_iter()
+        .zip(tiled_panes.extract_no_wrapper())
+        .zip(tiled_panes.extract_exclude_from_dump())
+        .map(|((run_instruction, no_wrapper), exclude_from_dump)| {
+            PaneManifest {
+                no_wrapper,
+                ..PaneManifest::new(
+                    run_instruction.pane_name,
+                    run_instruction.run.as_ref(),
+                    plugins,
+                )
+            }
+            .redacted_if(redact && exclude_from_dump)
+        })
+        .collect();
+    for floating_pane in floating_panes {
+        let pane = PaneManifest {
+            floating: Some(FloatingGeometry {
+                x: floating_pane.x.as_ref().map(describe_position),
+                y: floating_pane.y.as_ref().map(describe_position),
+                width: describe_size(&floating_pane.width, &floating_pane.width_fit),
+                height: describe_size(&floating_pane.height, &floating_pane.height_fit),
+            }),
+            no_wrapper: floating_pane.no_wrapper,
+            ..PaneManifest::new(
+                floating_pane.name.clone(),
+                floating_pane.run.as_ref(),
+                plugins,
+            )
+        };
+        panes.push(pane.redacted_if(redact && floating_pane.exclude_from_dump));
+    }
+    panes
+}
+
+impl PaneManifest {
+    fn new(name: Option<String>, run: Option<&Run>, plugins: &PluginsConfig) -> Self {
+        let (runs, cwd) = match run {
+            Some(Run::Command(run_command)) => (
+                Some(PaneRunManifest::Command {
+                    command: run_command.command.clone(),
+                    args: run_command.args.clone(),
+                    waits_for: run_command
+                        .depends_on
+                        .as_ref()
+                        .map(|depends_on| depends_on.pane_name.clone()),
+                }),
+                run_command.cwd.clone(),
+            ),
+            Some(Run::EditFile(paths, line_number, _editor_splits)) => (
+                Some(PaneRunManifest::EditFile(paths.clone(), *line_number)),
+                None,
+            ),
+            Some(Run::Plugin(run_plugin)) => (
+                Some(PaneRunManifest::Plugin {
+                    location: run_plugin.location.to_string(),
+                    path: plugins.get(run_plugin).map(|plugin| plugin.path),
+                }),
+                None,
+            ),
+            Some(Run::Cwd(cwd)) => (None, Some(cwd.clone())),
+            None => (None, None),
+        };
+        PaneManifest {
+            name,
+            runs,
+            cwd,
+            floating: None,
+            no_wrapper: false,
+            redacted: false,
+        }
+    }
+    fn redacted_if(self, exclude_from_dump: bool) -> Self {
+        if exclude_from_dump {
+            PaneManifest {
+                runs: None,
+                redacted: true,
+                ..self
+            }
+        } else {
+            self
+        }
+    }
+}
+
+fn describe_position(position: &PercentOrFixed) -> String {
+    match position {
+        PercentOrFixed::Percent(percent) => format!("{}%", percent),
+        PercentOrFixed::Fixed(fixed) => fixed.to_string(),
+        PercentOrFixed::FixedFromFarEdge(fixed) => format!("-{}", fixed),
+        PercentOrFixed::PercentFromFarEdge(percent) => format!("-{}%", percent),
+    }
+}
+
+// a size declared as "fit" is listed as such rather than as the size the pane starts with
+fn describe_size(size: &Option<PercentOrFixed>, fit: &Option<FitToContent>) -> Option<String> {
+    match fit {
+        Some(_) => Some("fit".to_owned()),
+        None => size.as_ref().map(describe_position),
+    }
+}
+
+impl LayoutManifest {
+    /// Whether the layout starts any program besides shells and plugins
+    pub fn runs_commands(&self) -> bool {
+        let is_wrapped = self.command_wrapper.is_some();
+        self.tabs
+            .iter()
+            .flat_map(|tab| tab.panes.iter())
+            .chain(self.side_panel.iter().flatten())
+            .any(|pane| match &pane.runs {
+                Some(PaneRunManifest::Command { .. }) => true,
+                // the editor is started under the wrapper
+                Some(PaneRunManifest::EditFile(..)) => is_wrapped && !pane.no_wrapper,
+                _ => pane.redacted,
+            })
+    }
+}
+
+impl fmt::Display for LayoutManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(command_wrapper) = &self.command_wrapper {
+            writeln!(f, "command wrapper: {}", command_wrapper.command.display())?;
+            if !command_wrapper.args.is_empty() {
+                writeln!(f, "  args: {:?}", command_wrapper.args)?;
+            }
+        }
+        let is_wrapped = self.command_wrapper.is_some();
+        for (i, tab) in self.tabs.iter().enumerate() {
+            match &tab.name {
+                Some(name) => writeln!(f, "tab \"{}\"", name)?,
+                None => writeln!(f, "tab #{}", i + 1)?,
+            }
+            write_panes(f, &tab.panes, is_wrapped)?;
+        }
+        if let Some(side_panel) = &self.side_panel {
+            writeln!(f, "side panel")?;
+            write_panes(f, side_panel, is_wrapped)?;
+        }
+        if !self.env.is_empty() {
+            writeln!(f, "env")?;
+            for (name, value) in &self.env {
+                writeln!(f, "  {}={:?}", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_panes(
+    f: &mut fmt::Formatter<'_>,
+    panes: &[PaneManifest],
+    is_wrapped: bool,
+) -> fmt::Result {
+    for (i, pane) in panes.iter().enumerate() {
+        let kind = if pane.floating.is_some() {
+            "floating pane"
+        } else {
+            "pane"
+        };
+        match &pane.name {
+            Some(name) => writeln!(f, "  {} \"{}\"", kind, name)?,
+            None => writeln!(f, "  {} #{}", kind, i + 1)?,
+        }
+        match &pane.runs {
+            Some(PaneRunManifest::Command {
+                command,
+                args,
+                waits_for,
+            }) => {
+                writeln!(f, "    command: {}", command.display())?;
+                if !args.is_empty() {
+                    writeln!(f, "    args: {:?}", args)?;
+                }
+                if let Some(waits_for) = waits_for {
+                    writeln!(f, "    waits for: \"{}\"", waits_for)?;
+                }
+            },
+            Some(PaneRunManifest::EditFile(paths, line_number)) => {
+                let files: Vec<String> = paths
+                    .iter()
+                    .enumerate()
+                    .map(|(i, path)| match line_number {
+                        Some(line_number) if i == 0 => {
+                            format!("{}:{}", path.display(), line_number)
+                        },
+                        _ => path.display().to_string(),
+                    })
+                    .collect();
+                writeln!(f, "    edit: {}", files.join(", "))?
+            },
+            Some(PaneRunManifest::Plugin { location, path }) => match path {
+                Some(path) => writeln!(f, "    plugin: {} ({})", location, path.display())?,
+                None => writeln!(f, "    plugin: {} (not found in the config)", location)?,
+            },
+            None if pane.redacted => writeln!(f, "    redacted")?,
+            None => writeln!(f, "    shell")?,
+        }
+        let runs_in_terminal = match &pane.runs {
+            Some(PaneRunManifest::Command { .. }) | Some(PaneRunManifest::EditFile(..)) => true,
+            _ => false,
+        };
+        if is_wrapped && runs_in_terminal && pane.no_wrapper {
+            writeln!(f, "    not wrapped")?;
+        }
+        if let Some(cwd) = &pane.cwd {
+            writeln!(f, "    cwd: {}", cwd.display())?;
+        }
+        if let Some(floating) = &pane.floating {
+            let describe = |position: &Option<String>| {
+                position.clone().unwrap_or_else(|| String::from("default"))
+            };
+            writeln!(
+                f,
+                "    x: {}, y: {}, width: {}, height: {}",
+                describe(&floating.x),
+                describe(&floating.y),
+                describe(&floating.width),
+                describe(&floating.height)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_manifest_test.rs"]
+mod layout_manifest_test;
@@ -0,0 +1,170 @@
+//! Upgrades the layouts sessions are saved with for resurrection to the layout format of this
+//! version of zellij, so that a session saved by an older version can still be resurrected.
+//!
+//! These layouts start with a `format_version` node. When the layout format changes in a way that
+//! older layouts would no longer parse (or would parse differently), bump
+//! [`RESURRECTION_FORMAT_VERSION`] and register a migration from the previous version in
+//! `MIGRATIONS`. Migrations only transform the KDL document, they run before the layout is
+//! parsed.
+use crate::{
+    input::{
+        config::ConfigError,
+        layout::{normalize_layout_source, Layout},
+    },
+    kdl::migrate_deprecated_names,
+};
+use kdl::KdlDocument;
+use std::path::PathBuf;
+
+/// The layout format sessions are saved with by this version of zellij
+pub const RESURRECTION_FORMAT_VERSION: u64 = 1;
+
+/// The format of layouts saved before they had a `format_version`
+const UNVERSIONED_FORMAT_VERSION: u64 = 0;
+
+struct LayoutMigration {
+    from_version: u64, // upgrades layouts of this format to the next one
+    description: &'static str,
+    migrate: fn(&mut KdlDocument),
+}
+
+const MIGRATIONS: &[LayoutMigration] = &[LayoutMigration {
+    from_version: 0,
+    description: "deprecated layout spellings were rewritten",
+    migrate: rewrite_deprecated_names,
+}];
+
+fn rewrite_deprecated_names(kdl_layout: &mut KdlDocument) {
+    migrate_deprecated_names(kdl_layout);
+}
+
+/// A resurrection layout upgraded to [`RESURRECTION_FORMAT_VERSION`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigratedLayout {
+    pub raw_layout: String, // without its `format_version` node
+    pub from_version: u64,
+    pub applied_migrations: Vec<&'static str>, // the descriptions of the migrations, in order
+}
+
+impl MigratedLayout {
+    /// One line telling the user why their resurrected session may differ slightly from the one
+    /// they saved, `None` if the layout did not need to be migrated
+    pub fn notice(&self) -> Option<String> {
+        if self.applied_migrations.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "This session was saved by an older version of zellij, its layout was upgraded from format {} to {} ({})",
+            self.from_version,
+            RESURRECTION_FORMAT_VERSION,
+            self.applied_migrations.join(", ")
+        ))
+    }
+}
+
+/// Adds the `format_version` node to the layout a session is saved with
+pub fn versioned_resurrection_layout(raw_layout: &str) -> String {
+    format!(
+        "format_version {}\n{}",
+        RESURRECTION_FORMAT_VERSION, raw_layout
+    )
+}
+
+/// Upgrades a resurrection layout to [`RESURRECTION_FORMAT_VERSION`], refusing layouts saved by a
+/// newer version of zellij
+pub fn migrate_resurrection_layout(raw_layout: &str) -> Result<MigratedLayout, ConfigError> {
+    let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+    let mut kdl_layout: KdlDocument = raw_layout.parse()?;
+    let from_version = take_format_version(&mut kdl_layout)?;
+    let mut applied_migrations = vec![];
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| migration.from_version >= from_version)
+    {
+        (migration.migrate)(&mut kdl_layout);
+        applied_migrations.push(migration.description);
+    }
+    Ok(MigratedLayout {
+        raw_layout: kdl_layout.to_string(),
+        from_version,
+        applied_migrations,
+    })
+}
+
+/// Removes the `format_version` node from the layout, returning the version it had
+fn take_format_version(kdl_layout: &mut KdlDocument) -> Result<u64, ConfigError> {
+    let version_node_indices: Vec<usize> = kdl_layout
+        .nodes()
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.name().value() == "format_version")
+        .map(|(i, _)| i)
+        .collect();
+    let version_node_index = match version_node_indices.as_slice() {
+        [] => return Ok(UNVERSIONED_FORMAT_VERSION),
+        [version_node_index] => *version_node_index,
+        [_, second_version_node_index, ..] => {
+            let node = &kdl_layout.nodes()[*second_version_node_index];
+            return Err(ConfigError::new_layout_kdl_error(
+                "Only one format_version node per file allowed".into(),
+                node.span().offset(),
+                node.span().len(),
+            ));
+        },
+    };
+    let node = &kdl_layout.nodes()[version_node_index];
+    let version = match node.entries() {
+        [entry] if entry.name().is_none() => entry.value().as_i64(),
+        _ => None,
+    };
+    let version = version
+        .and_then(|version| u64::try_from(version).ok())
+        .ok_or_else(|| {
+            ConfigError::new_layout_kdl_error(
+                "format_version must be a single whole number".into(),
+                node.span().offset(),
+                node.span().len(),
+            )
+        })?;
+    if version > RESURRECTION_FORMAT_VERSION {
+        return Err(ConfigError::new_layout_kdl_error(
+            format!(
+                "This session was saved by a newer version of zellij (layout format {}), this version can only resurrect sessions up to format {}. Please upgrade zellij to resurrect it.",
+                version, RESURRECTION_FORMAT_VERSION
+            ),
+            node.span().offset(),
+            node.span().len(),
+        ));
+    }
+    kdl_layout.nodes_mut().remove(version_node_index);
+    Ok(version)
+}
+
+impl Layout {
+    /// Parses the layout a session was saved with, upgrading it first if it was saved by an older
+    /// version of zellij. Returns the notice to show the user when it was upgraded.
+    pub fn from_resurrection_kdl(
+        raw_layout: &str,
+        file_name: String,
+        cwd: Option<PathBuf>,
+    ) -> Result<(Layout, Option<String>), ConfigError> {
+        // errors point at the normalized source, so that is also what they are reported with
+        let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+        let migrated_layout = migrate_resurrection_layout(&raw_layout).map_err(|e| match e {
+            ConfigError::KdlError(kdl_error) => {
+                ConfigError::KdlError(kdl_error.add_src(file_name.clone(), raw_layout.clone()))
+            },
+            e => e,
+        })?;
+        let notice = migrated_layout.notice();
+        if let Some(notice) = &notice {
+            log::info!("{}: {}", file_name, notice);
+        }
+        let layout = Layout::from_kdl(&migrated_layout.raw_layout, file_name, None, cwd)?;
+        Ok((layout, notice))
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_migrations_test.rs"]
+mod layout_migrations_test;
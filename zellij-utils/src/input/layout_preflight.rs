@@ -0,0 +1,168 @@
+//! Checks that every tab of a layout can be created before any of them is, so that a layout
+//! failing half way through does not leave a session with only some of its tabs
+use crate::{
+    input::{
+        layout::{FloatingPaneLayout, Layout, Run, RunPlugin, RunPluginLocation, TiledPaneLayout},
+        plugins::PluginsConfig,
+    },
+    pane_size::{PaneGeom, Size},
+};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutTabError {
+    pub tab_index: usize, // the position of the tab in the layout, starting from 0
+    pub tab_name: Option<String>, // None for unnamed tabs and the new tab template
+    pub reason: String,
+}
+
+impl fmt::Display for LayoutTabError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.tab_name {
+            Some(tab_name) => write!(
+                f,
+                "tab #{} \"{}\": {}",
+                self.tab_index + 1,
+                tab_name,
+                self.reason
+            ),
+            None => write!(f, "tab #{}: {}", self.tab_index + 1, self.reason),
+        }
+    }
+}
+
+impl Layout {
+    /// Returns the reasons the tabs of the layout (or its new tab template) cannot be created.
+    /// The panes are fitted into `screen_size` when one is given, less the side panel if the
+    /// layout has one, and plugin locations are resolved through `plugins`
+    pub fn preflight(
+        &self,
+        screen_size: Option<Size>,
+        plugins: &PluginsConfig,
+    ) -> Vec<LayoutTabError> {
+        let tab_area = screen_size.map(|screen_size| self.tab_area(screen_size));
+        let mut errors = vec![];
+        if self.tabs.is_empty() {
+            let (tiled_panes, floating_panes) = self.new_tab();
+            preflight_tab(
+                0,
+                None,
+                &tiled_panes,
+                &floating_panes,
+                tab_area,
+                plugins,
+                &mut errors,
+            );
+        } else {
+            for (tab_index, (tab_name, tiled_panes, floating_panes, ..)) in
+                self.tabs.iter().enumerate()
+            {
+                preflight_tab(
+                    tab_index,
+                    tab_name.clone(),
+                    tiled_panes,
+                    floating_panes,
+                    tab_area,
+                    plugins,
+                    &mut errors,
+                );
+            }
+        }
+        errors
+    }
+    /// The swap tiled layouts of the layout and of its tabs whose panes do not fit into
+    /// `screen_size`, less the side panel if the layout has one. These are skipped when swapping
+    /// layouts rather than failing, so they are no reason not to create the tabs
+    pub fn swap_layouts_not_fitting(&self, screen_size: Size) -> Vec<String> {
+        let space = PaneGeom::from(&self.tab_area(screen_size));
+        let tab_swap_tiled_layouts = self
+            .tabs
+            .iter()
+            .flat_map(|(.., (swap_tiled_layouts, _swap_floating_layouts))| swap_tiled_layouts);
+        let mut not_fitting = vec![];
+        for (swap_layouts, swap_layout_name, _tags) in
+            self.swap_tiled_layouts.iter().chain(tab_swap_tiled_layouts)
+        {
+            for (constraint, tiled_panes) in swap_layouts {
+                if let Err(e) = tiled_panes.position_panes_in_space(&space, None) {
+                    let swap_layout_name = swap_layout_name
+                        .as_ref()
+                        .map(|name| format!("\"{}\" ", name))
+                        .unwrap_or_default();
+                    not_fitting.push(format!(
+                        "swap layout {}({}) does not fit: {}",
+                        swap_layout_name, constraint, e
+                    ));
+                }
+            }
+        }
+        not_fitting
+    }
+    // the room the tabs have on a screen of this size, next to the side panel
+    fn tab_area(&self, screen_size: Size) -> Size {
+        match &self.side_panel {
+            Some(side_panel) => side_panel
+                .split_space(screen_size)
+                .map(|(_panel_geom, tab_area)| tab_area)
+                .unwrap_or(screen_size),
+            None => screen_size,
+        }
+    }
+}
+
+fn preflight_tab(
+    tab_index: usize,
+    tab_name: Option<String>,
+    tiled_panes: &TiledPaneLayout,
+    floating_panes: &[FloatingPaneLayout],
+    tab_area: Option<Size>,
+    plugins: &PluginsConfig,
+    errors: &mut Vec<LayoutTabError>,
+) {
+    let mut fail = |reason: String| {
+        errors.push(LayoutTabError {
+            tab_index,
+            tab_name: tab_name.clone(),
+            reason,
+        })
+    };
+    if let Some(tab_area) = tab_area {
+        let space = PaneGeom::from(&tab_area);
+        if let Err(e) = tiled_panes.position_panes_in_space(&space, None) {
+            fail(format!(
+                "{} ({} columns, {} rows)",
+                e, tab_area.cols, tab_area.rows
+            ));
+        }
+    }
+    let runs = tiled_panes
+        .extract_run_instructions()
+        .into_iter()
+        .chain(floating_panes.iter().map(|pane| pane.run.clone()));
+    for run in runs {
+        if let Some(Run::Plugin(run_plugin)) = run {
+            if let Some(reason) = unresolvable_plugin(&run_plugin, plugins) {
+                fail(reason);
+            }
+        }
+    }
+}
+
+fn unresolvable_plugin(run_plugin: &RunPlugin, plugins: &PluginsConfig) -> Option<String> {
+    match &run_plugin.location {
+        RunPluginLocation::Zellij(tag) if plugins.get(run_plugin).is_none() => {
+            Some(format!("plugin \"{}\" is not defined in the config", tag))
+        },
+        // relative paths are looked up in the plugin directory once the session runs
+        RunPluginLocation::File(path)
+            if path.is_absolute() && !path.exists() && !path.with_extension("wasm").exists() =>
+        {
+            Some(format!("plugin file {} does not exist", path.display()))
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_preflight_test.rs"]
+mod layout_preflight_test;
@@ -0,0 +1,99 @@
+//! How long loading a layout took and how big it is, so that a layout that is slow to open (eg. a
+//! very large generated one) can be narrowed down to the stage the time goes to
+//! (`zellij setup --check --timings`, `zellij --layout <layout> --dry-run`)
+use crate::{
+    input::{flat_layout::DEFAULT_FLAT_LAYOUT_SIZE, layout::Layout},
+    pane_size::PaneGeom,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, time::Instant};
+
+/// Loading stages taking longer than this (in milliseconds) are logged
+pub const SLOW_LAYOUT_STAGE_MS: u64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutLoadStats {
+    /// Reading the KDL documents of the layout and of its swap layouts
+    pub parse_ms: u64,
+    /// Turning these documents into the layout, inserting templates into their children blocks
+    pub expand_ms: u64,
+    /// Placing the panes of each tab in the screen the first time, see [`Layout::time_geometry`]
+    pub geometry_ms: u64,
+    /// The nodes of the KDL documents, at any depth
+    pub node_count: usize,
+    /// The tiled and floating panes of all tabs, or of the new tab template of a layout without
+    /// tabs
+    pub pane_count: usize,
+}
+
+impl LayoutLoadStats {
+    /// The stats of layouts loaded together, eg. with [`Layout::concatenate`]
+    pub fn combined_with(self, other: LayoutLoadStats) -> LayoutLoadStats {
+        LayoutLoadStats {
+            parse_ms: self.parse_ms + other.parse_ms,
+            expand_ms: self.expand_ms + other.expand_ms,
+            geometry_ms: self.geometry_ms + other.geometry_ms,
+            node_count: self.node_count + other.node_count,
+            pane_count: self.pane_count + other.pane_count,
+        }
+    }
+    pub fn has_slow_stage(&self) -> bool {
+        self.parse_ms > SLOW_LAYOUT_STAGE_MS
+            || self.expand_ms > SLOW_LAYOUT_STAGE_MS
+            || self.geometry_ms > SLOW_LAYOUT_STAGE_MS
+    }
+    pub fn log_if_slow(&self, layout_name: &str) {
+        if self.has_slow_stage() {
+            log::info!("Loading the {} layout took a while: {}", layout_name, self);
+        }
+    }
+}
+
+impl fmt::Display for LayoutLoadStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "parse {}ms, expand {}ms, geometry {}ms ({} nodes, {} panes)",
+            self.parse_ms, self.expand_ms, self.geometry_ms, self.node_count, self.pane_count
+        )
+    }
+}
+
+/// The milliseconds since `start`
+pub fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
+
+impl Layout {
+    /// Places the tiled panes of each tab (or of the new tab template of a layout without tabs)
+    /// like creating the tab does, in a screen of [`DEFAULT_FLAT_LAYOUT_SIZE`] since the real
+    /// one is only known once the session starts. Returns how long it took and how many panes
+    /// the tabs have.
+    pub fn time_geometry(&self) -> (u64, usize) {
+        let space = PaneGeom::from(&DEFAULT_FLAT_LAYOUT_SIZE);
+        let focus_policy = self.default_focus.unwrap_or_default();
+        let tabs = if self.tabs.is_empty() {
+            vec![self.new_tab()]
+        } else {
+            self.tabs
+                .iter()
+                .map(|(_tab_name, tiled_panes, floating_panes, ..)| {
+                    (tiled_panes.clone(), floating_panes.clone())
+                })
+                .collect()
+        };
+        let start = Instant::now();
+        let mut pane_count = 0;
+        for (tiled_panes, floating_panes) in &tabs {
+            // a layout too large for this screen fails to be placed, which still takes the time
+            // to find out
+            let _ = tiled_panes.position_panes_in_space_with_focus(&space, None, focus_policy);
+            pane_count += tiled_panes.pane_count() + floating_panes.len();
+        }
+        (elapsed_ms(start), pane_count)
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_stats_test.rs"]
+mod layout_stats_test;
@@ -0,0 +1,116 @@
+//! Whether the commands a layout runs can be started without the user confirming them first (see
+//! the `confirm_layout_commands` option). Layouts shipped with zellij and layouts in the layout
+//! dir are trusted, as are the layouts the user chose to trust when asked: these are remembered
+//! by the hash of their content in the data dir, so that changing a trusted layout makes it
+//! untrusted again.
+use crate::input::layout::{Layout, LayoutFile, LayoutSources};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The file in the data dir listing the hashes of the trusted layouts, one per line
+pub const TRUSTED_LAYOUTS_FILE_NAME: &str = "trusted_layouts";
+
+/// The hash a layout is trusted by, over the layout and its swap layouts so that changing either
+/// of them makes it untrusted again. That of a layout without swap layouts is its
+/// [`Layout::fingerprint_of_source`].
+pub fn layout_content_hash(raw_layout: &str, raw_swap_layouts: Option<&str>) -> String {
+    let raw_swap_layouts = match raw_swap_layouts {
+        Some(raw_swap_layouts) => raw_swap_layouts,
+        None => return Layout::fingerprint_of_source(raw_layout),
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(raw_layout.as_bytes());
+    // so that moving text between the two files changes the hash
+    hasher.update([0u8]);
+    hasher.update(raw_swap_layouts.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// The layouts the user chose to trust
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedLayouts {
+    content_hashes: BTreeSet<String>,
+}
+
+impl TrustedLayouts {
+    /// The layouts trusted in `data_dir`, none if it does not list any (yet)
+    pub fn load(data_dir: &Path) -> Self {
+        let content_hashes = fs::read_to_string(TrustedLayouts::file_path(data_dir))
+            .map(|trusted_layouts| {
+                trusted_layouts
+                    .lines()
+                    .map(|line| line.trim().to_owned())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        TrustedLayouts { content_hashes }
+    }
+    pub fn contains(&self, content_hash: &str) -> bool {
+        self.content_hashes.contains(content_hash)
+    }
+    /// Trusts the layout with this content hash from now on, remembering it in `data_dir`
+    pub fn remember(&mut self, data_dir: &Path, content_hash: &str) -> io::Result<()> {
+        if self.contains(content_hash) {
+            return Ok(());
+        }
+        fs::create_dir_all(data_dir)?;
+        let mut trusted_layouts_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(TrustedLayouts::file_path(data_dir))?;
+        writeln!(trusted_layouts_file, "{}", content_hash)?;
+        self.content_hashes.insert(content_hash.to_owned());
+        Ok(())
+    }
+    fn file_path(data_dir: &Path) -> PathBuf {
+        data_dir.join(TRUSTED_LAYOUTS_FILE_NAME)
+    }
+}
+
+impl LayoutFile {
+    /// Whether the commands of this layout can run without asking the user first
+    pub fn is_trusted(&self, layout_dir: Option<&Path>, trusted_layouts: &TrustedLayouts) -> bool {
+        self.from_default_assets
+            || layout_dir
+                .map(|layout_dir| is_inside(Path::new(&self.path), layout_dir))
+                .unwrap_or(false)
+            || trusted_layouts.contains(&self.content_hash)
+    }
+}
+
+impl LayoutSources {
+    /// The files of the layout the user has to confirm the commands of, see
+    /// [`LayoutFile::is_trusted`]
+    pub fn untrusted_files(
+        &self,
+        layout_dir: Option<&Path>,
+        trusted_layouts: &TrustedLayouts,
+    ) -> Vec<&LayoutFile> {
+        self.files
+            .iter()
+            .filter(|layout_file| !layout_file.is_trusted(layout_dir, trusted_layouts))
+            .collect()
+    }
+}
+
+// resolving `..` and links in the directories, so that neither can make a layout look like it is
+// in the layout dir. A link the user put in the layout dir counts as being in it.
+fn is_inside(path: &Path, dir: &Path) -> bool {
+    let parent = match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => return false,
+    };
+    match (fs::canonicalize(parent), fs::canonicalize(dir)) {
+        (Ok(parent), Ok(dir)) => path.file_name().is_some() && parent.starts_with(dir),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_trust_test.rs"]
+mod layout_trust_test;
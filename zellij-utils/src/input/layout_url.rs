@@ -0,0 +1,110 @@
+//! Layouts opened from an HTTP(S) URL, eg. `zellij --layout https://example.com/layouts/dev.kdl`
+use crate::input::{
+    config::ConfigError,
+    layout::{is_yaml_layout, LayoutLimits},
+};
+
+use isahc::config::{Configurable, RedirectPolicy};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use url::Url;
+
+/// How long fetching a layout can take before giving up on it
+pub const LAYOUT_URL_TIMEOUT: Duration = Duration::from_secs(10);
+/// How many redirects are followed when fetching a layout
+const LAYOUT_URL_MAX_REDIRECTS: u32 = 5;
+
+/// The URL of a layout given as a path, if it is an `http://` or `https://` URL
+pub fn layout_url(layout_path: &Path) -> Option<Url> {
+    let url = Url::parse(layout_path.to_str()?).ok()?;
+    match url.scheme() {
+        "http" | "https" => Some(url),
+        _ => None,
+    }
+}
+
+/// The URL of the swap layout companion of a layout URL: `https://example.com/dev.kdl` and
+/// `https://example.com/dev` both have `https://example.com/dev.swap.kdl` (keeping the query)
+pub fn swap_layout_url(url: &Url) -> Option<Url> {
+    let file_name = url.path_segments()?.last()?.to_owned();
+    if file_name.is_empty() {
+        return None;
+    }
+    let stem = file_name.strip_suffix(".kdl").unwrap_or(&file_name);
+    let mut swap_layout_url = url.clone();
+    swap_layout_url
+        .path_segments_mut()
+        .ok()?
+        .pop()
+        .push(&format!("{}.swap.kdl", stem));
+    Some(swap_layout_url)
+}
+
+/// Fetches the layout at `url` and its swap layout companion, like
+/// [`crate::input::layout::Layout::stringified_from_path`] reads a layout file. Not finding the
+/// swap layouts is not an error
+pub fn stringified_from_url(
+    url: &Url,
+) -> Result<(String, String, Option<(String, String)>), ConfigError> {
+    // (url of the layout, stringified_layout, Option<url of the swap layout, stringified_swap_layout>)
+    let kdl_layout = fetch(url).map_err(|e| ConfigError::IoPath(e, url_as_path(url)))?;
+    if is_yaml_layout(&kdl_layout, Some(Path::new(url.path()))) {
+        return Err(ConfigError::YamlLayout(url_as_path(url)));
+    }
+    let swap_layout_and_url =
+        swap_layout_url(url).and_then(|swap_layout_url| match fetch(&swap_layout_url) {
+            Ok(swap_kdl_layout) => Some((swap_layout_url.to_string(), swap_kdl_layout)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                log::warn!(
+                    "Failed to fetch swap layout: {}. Error: {:?}",
+                    swap_layout_url,
+                    e
+                );
+                None
+            },
+        });
+    Ok((url.to_string(), kdl_layout, swap_layout_and_url))
+}
+
+/// Where a layout fetched from `url` is reported to be in errors
+fn url_as_path(url: &Url) -> PathBuf {
+    PathBuf::from(url.as_str())
+}
+
+fn fetch(url: &Url) -> io::Result<String> {
+    let max_size = LayoutLimits::default().max_file_size;
+    let request = isahc::Request::get(url.as_str())
+        .timeout(LAYOUT_URL_TIMEOUT)
+        .redirect_policy(RedirectPolicy::Limit(LAYOUT_URL_MAX_REDIRECTS))
+        .body(())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut response = isahc::send(request).map_err(io::Error::from)?;
+    let status = response.status();
+    if !status.is_success() {
+        let kind = if status == isahc::http::StatusCode::NOT_FOUND {
+            io::ErrorKind::NotFound
+        } else {
+            io::ErrorKind::Other
+        };
+        return Err(io::Error::new(kind, format!("HTTP status {}", status)));
+    }
+    // one byte more than allowed is read to tell a layout at the limit from a larger one
+    let mut raw_layout = vec![];
+    response
+        .body_mut()
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut raw_layout)?;
+    if raw_layout.len() > max_size {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("The layout is more than {} bytes", max_size),
+        ));
+    }
+    String::from_utf8(raw_layout).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_url_test.rs"]
+mod layout_url_test;
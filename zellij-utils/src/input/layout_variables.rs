@@ -0,0 +1,194 @@
+//! The variables a layout file can be opened with (eg. `--layout-var project=/home/me/foo`), so
+//! that one layout can serve several projects. The layout refers to them as `{{project}}` in its
+//! strings (eg. the `cwd`, `args`, `name` or `edit` of a pane) and can give them defaults in a
+//! `vars` block:
+//!
+//! ```kdl
+//! layout {
+//!     vars {
+//!         project          // required
+//!         branch "main"    // "main" unless given
+//!     }
+//!     pane cwd="{{project}}" command="git" {
+//!         args "checkout" "{{branch}}"
+//!     }
+//! }
+//! ```
+//!
+//! The variables are substituted in the text of the layout (and of its swap layouts) before it
+//! is parsed. Only the variables declared in the `vars` block or given when opening the layout
+//! are, so that the `{{` other text starts with keeps meaning a literal brace (see
+//! [`expand_placeholders`](crate::input::command::expand_placeholders)).
+use crate::input::config::ConfigError;
+use crate::input::layout::normalize_layout_source;
+use kdl::{KdlDocument, KdlNode};
+use std::collections::BTreeMap;
+
+/// The values of the variables of a layout, by their name
+pub type LayoutVariables = BTreeMap<String, String>;
+
+/// A variable given as `NAME=VALUE` (eg. on the command line)
+pub fn parse_layout_variable(variable: &str) -> Result<(String, String), String> {
+    match variable.split_once('=') {
+        Some((name, value)) if is_a_variable_name(name) => Ok((name.to_owned(), value.to_owned())),
+        Some((name, _value)) => Err(format!(
+            "'{}' is not a valid layout variable name (only letters, digits, '_' and '-' are)",
+            name
+        )),
+        None => Err(format!("expected NAME=VALUE, found '{}'", variable)),
+    }
+}
+
+/// The values the variables of the layout take: those in `given`, over the defaults of its
+/// `vars` block. An error lists the variables the block declares without a default that are not
+/// given either. A layout that cannot be parsed is left for the layout parser to report.
+pub fn resolve_layout_variables(
+    raw_layout: &str,
+    file_name: &str,
+    given: &LayoutVariables,
+) -> Result<LayoutVariables, ConfigError> {
+    // errors point at the normalized source, like those of the layout parser
+    let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+    let kdl_layout: KdlDocument = match raw_layout.parse() {
+        Ok(kdl_layout) => kdl_layout,
+        Err(_) => return Ok(given.clone()),
+    };
+    let mut variables = declared_variables(&kdl_layout, given).map_err(|e| match e {
+        ConfigError::KdlError(kdl_error) => {
+            ConfigError::KdlError(kdl_error.add_src(file_name.to_owned(), raw_layout.clone()))
+        },
+        e => e,
+    })?;
+    // those given without being declared are substituted all the same
+    variables.extend(
+        given
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone())),
+    );
+    Ok(variables)
+}
+
+// the variables of the `vars` blocks of the layout, with their given value or else their default
+fn declared_variables(
+    kdl_layout: &KdlDocument,
+    given: &LayoutVariables,
+) -> Result<LayoutVariables, ConfigError> {
+    let mut variables = LayoutVariables::new();
+    let vars_nodes: Vec<&KdlNode> = kdl_layout
+        .nodes()
+        .iter()
+        .filter(|node| node.name().value() == "layout")
+        .filter_map(|layout_node| layout_node.children())
+        .flat_map(|layout_children| layout_children.nodes())
+        .filter(|node| node.name().value() == "vars")
+        .collect();
+    let mut missing_variables = vec![];
+    for vars_node in &vars_nodes {
+        let declared_variables = vars_node
+            .children()
+            .map(|children| children.nodes())
+            .unwrap_or(&[]);
+        for declared_variable in declared_variables {
+            let name = declared_variable.name().value();
+            let error = |error_message: String| {
+                ConfigError::new_layout_kdl_error(
+                    error_message,
+                    declared_variable.span().offset(),
+                    declared_variable.span().len(),
+                )
+            };
+            if !is_a_variable_name(name) {
+                return Err(error(format!(
+                    "'{}' is not a valid layout variable name (only letters, digits, '_' and '-' are)",
+                    name
+                )));
+            }
+            let default_value = declared_variable
+                .entries()
+                .iter()
+                .find(|entry| entry.name().is_none())
+                .map(|entry| entry.value().as_string());
+            match (given.get(name), default_value) {
+                (Some(value), _) | (None, Some(Some(value))) => {
+                    variables.insert(name.to_owned(), value.to_owned());
+                },
+                (None, Some(None)) => {
+                    return Err(error(format!(
+                        "The default of the layout variable '{}' must be a string",
+                        name
+                    )));
+                },
+                (None, None) => missing_variables.push(name),
+            }
+        }
+    }
+    match vars_nodes.first() {
+        Some(vars_node) if !missing_variables.is_empty() => Err(ConfigError::new_layout_kdl_error(
+            format!(
+                "No value for the layout variables: {} (give them with --layout-var NAME=VALUE)",
+                missing_variables.join(", ")
+            ),
+            vars_node.span().offset(),
+            vars_node.span().len(),
+        )),
+        _ => Ok(variables),
+    }
+}
+
+/// The layout with each `{{name}}` of the variables replaced by their value, escaped so that it
+/// stays a single KDL string. References to other names are left as they are.
+pub fn substitute_layout_variables(raw_layout: &str, variables: &LayoutVariables) -> String {
+    if variables.is_empty() || !raw_layout.contains("{{") {
+        return raw_layout.to_owned();
+    }
+    let mut substituted = String::with_capacity(raw_layout.len());
+    let mut rest = raw_layout;
+    while let Some(start) = rest.find("{{") {
+        substituted.push_str(&rest[..start]);
+        let after_braces = &rest[start + 2..];
+        let value = after_braces.find("}}").and_then(|end| {
+            variables
+                .get(&after_braces[..end])
+                .map(|value| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                substituted.push_str(&escape_kdl_string(value));
+                rest = &after_braces[end + 2..];
+            },
+            None => {
+                // the second brace can still start a reference, eg. in `{{{name}}`
+                substituted.push('{');
+                rest = &rest[start + 1..];
+            },
+        }
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+fn is_a_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn escape_kdl_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+#[path = "./unit/layout_variables_test.rs"]
+mod layout_variables_test;
@@ -1,8 +1,19 @@
 pub mod actions;
 pub mod command;
 pub mod config;
+pub mod flat_layout;
 pub mod keybinds;
 pub mod layout;
+pub mod layout_apply_error;
+pub mod layout_capabilities;
+pub mod layout_manifest;
+pub mod layout_migrations;
+pub mod layout_preflight;
+pub mod layout_stats;
+pub mod layout_trust;
+#[cfg(not(target_family = "wasm"))]
+pub mod layout_url;
+pub mod layout_variables;
 pub mod options;
 pub mod plugins;
 pub mod theme;
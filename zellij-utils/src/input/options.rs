@@ -1,6 +1,7 @@
 //! Handles cli and configuration options
 use crate::cli::Command;
 use crate::data::InputMode;
+use crate::input::layout::{FocusPolicy, LayoutLimits, SplitSize};
 use clap::{ArgEnum, Args};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -32,6 +33,64 @@ impl FromStr for OnForceClose {
     }
 }
 
+/// What becomes of the tabs a session layout already created when one of its later tabs fails
+/// to be created
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize, ArgEnum)]
+pub enum OnLayoutFailure {
+    #[serde(alias = "abort")]
+    Abort,
+    #[serde(alias = "keep_partial")]
+    KeepPartial,
+}
+
+impl Default for OnLayoutFailure {
+    fn default() -> Self {
+        Self::Abort
+    }
+}
+
+impl FromStr for OnLayoutFailure {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(Self::Abort),
+            "keep_partial" => Ok(Self::KeepPartial),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
+/// Which layouts need the user to confirm the commands they run before these are started
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize, ArgEnum)]
+pub enum ConfirmLayoutCommands {
+    #[serde(alias = "untrusted")]
+    Untrusted,
+    #[serde(alias = "always")]
+    Always,
+    #[serde(alias = "never")]
+    Never,
+}
+
+impl Default for ConfirmLayoutCommands {
+    fn default() -> Self {
+        Self::Untrusted
+    }
+}
+
+impl FromStr for ConfirmLayoutCommands {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "untrusted" => Ok(Self::Untrusted),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            e => Err(e.to_string().into()),
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, PartialEq, Deserialize, Serialize, Args)]
 /// Options that can be set either through the config file,
 /// or cli flags - cli flags should take precedence over the config file
@@ -55,6 +114,11 @@ pub struct Options {
     /// Set the default layout
     #[clap(long, value_parser)]
     pub default_layout: Option<PathBuf>,
+    /// Default layouts by session name pattern (eg. "work-*"), the first matching pattern wins
+    /// and sessions matching none of them fall back to `default_layout`
+    #[clap(skip)]
+    #[serde(default)]
+    pub default_layouts: Option<Vec<(String, PathBuf)>>,
     /// Set the layout_dir, defaults to
     /// subdirectory of config dir
     #[clap(long, value_parser)]
@@ -121,6 +185,102 @@ pub struct Options {
     #[clap(long, value_parser)]
     #[serde(default)]
     pub auto_layout: Option<bool>,
+
+    /// Whether panes with a fixed size keep it when resizing the panes next to them
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub respect_fixed_sizes: Option<bool>,
+
+    /// Whether the session's layout is read again when its file (or its swap layouts) changes,
+    /// for the tabs opened after that and the swap layouts of the open tabs
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub layout_hot_reload: Option<bool>,
+
+    /// The formats of the titles panes get from what they run
+    #[clap(skip)]
+    #[serde(default)]
+    pub pane_title_formats: Option<PaneTitleFormats>,
+
+    /// The width of the strider file tree pane in layouts (eg. 30 or "20%")
+    #[clap(skip)]
+    #[serde(default)]
+    pub strider_pane_width: Option<SplitSize>,
+
+    /// Stack the strider file tree pane with the panes next to it in tabs narrower than this
+    /// number of columns
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub strider_stack_below_width: Option<usize>,
+
+    /// Spawn the command panes of layouts one at a time, this many milliseconds apart (0 spawns
+    /// them all at once)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub command_spawn_interval_ms: Option<u64>,
+    /// Which pane of a tab gets the focus when its layout does not focus one explicitly, layouts
+    /// can set their own with a root level `default_focus` node
+    #[clap(skip)]
+    #[serde(default)]
+    pub default_focus: Option<FocusPolicy>,
+    /// Whether the tabs a session layout already created are closed (abort) or kept
+    /// (keep-partial) when one of its tabs fails to be created
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    pub on_layout_failure: Option<OnLayoutFailure>,
+    /// How deeply the children blocks of a layout file can nest before it is refused (default: 200)
+    #[clap(skip)]
+    #[serde(default)]
+    pub layout_max_depth: Option<usize>,
+    /// How many nodes a layout file can have before it is refused (default: 50000)
+    #[clap(skip)]
+    #[serde(default)]
+    pub layout_max_nodes: Option<usize>,
+    /// How big (in bytes) a layout file can be before it is refused (default: 10485760)
+    #[clap(skip)]
+    #[serde(default)]
+    pub layout_max_file_size: Option<usize>,
+    /// How many commands (and files to edit) opening a layout can start before it is refused,
+    /// counted each time a layout is opened rather than over the session (default: no maximum)
+    #[clap(skip)]
+    #[serde(default)]
+    pub max_layout_commands: Option<usize>,
+    /// Which layouts ask before starting the commands they run (untrusted, always or never),
+    /// untrusted ones are those outside the layout dir that were not trusted before
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    pub confirm_layout_commands: Option<ConfirmLayoutCommands>,
+    /// While the terminal is being resized, re-apply the swap layouts of the tabs at most this
+    /// often (in milliseconds), the final size is always applied (default: 50, 0 re-applies them on
+    /// every resize)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub resize_relayout_interval_ms: Option<u64>,
+}
+
+/// Formats of the titles panes get from what they run (see [`Run::pane_title`]), the placeholders
+/// available to each are listed in the default config
+///
+/// [`Run::pane_title`]: crate::input::layout::Run::pane_title
+#[derive(Clone, Default, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PaneTitleFormats {
+    pub command: Option<String>,
+    pub edit: Option<String>,
+    pub plugin: Option<String>,
+    pub cwd: Option<String>,
+}
+
+impl PaneTitleFormats {
+    pub fn command(&self) -> &str {
+        self.command.as_deref().unwrap_or("{command}")
+    }
+    pub fn edit(&self) -> &str {
+        self.edit.as_deref().unwrap_or("{file}{position}")
+    }
+    pub fn plugin(&self) -> &str {
+        self.plugin.as_deref().unwrap_or("{plugin}")
+    }
+    pub fn cwd(&self) -> &str {
+        self.cwd.as_deref().unwrap_or("{dir}")
+    }
 }
 
 #[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
@@ -163,11 +323,38 @@ impl Options {
         let mouse_mode = other.mouse_mode.or(self.mouse_mode);
         let pane_frames = other.pane_frames.or(self.pane_frames);
         let auto_layout = other.auto_layout.or(self.auto_layout);
+        let respect_fixed_sizes = other.respect_fixed_sizes.or(self.respect_fixed_sizes);
+        let layout_hot_reload = other.layout_hot_reload.or(self.layout_hot_reload);
+        let pane_title_formats = other
+            .pane_title_formats
+            .or_else(|| self.pane_title_formats.clone());
+        let strider_pane_width = other.strider_pane_width.or(self.strider_pane_width);
+        let strider_stack_below_width = other
+            .strider_stack_below_width
+            .or(self.strider_stack_below_width);
+        let command_spawn_interval_ms = other
+            .command_spawn_interval_ms
+            .or(self.command_spawn_interval_ms);
+        let default_focus = other.default_focus.or(self.default_focus);
+        let on_layout_failure = other.on_layout_failure.or(self.on_layout_failure);
+        let layout_max_depth = other.layout_max_depth.or(self.layout_max_depth);
+        let layout_max_nodes = other.layout_max_nodes.or(self.layout_max_nodes);
+        let layout_max_file_size = other.layout_max_file_size.or(self.layout_max_file_size);
+        let max_layout_commands = other.max_layout_commands.or(self.max_layout_commands);
+        let confirm_layout_commands = other
+            .confirm_layout_commands
+            .or(self.confirm_layout_commands);
+        let resize_relayout_interval_ms = other
+            .resize_relayout_interval_ms
+            .or(self.resize_relayout_interval_ms);
         let mirror_session = other.mirror_session.or(self.mirror_session);
         let simplified_ui = other.simplified_ui.or(self.simplified_ui);
         let default_mode = other.default_mode.or(self.default_mode);
         let default_shell = other.default_shell.or_else(|| self.default_shell.clone());
         let default_layout = other.default_layout.or_else(|| self.default_layout.clone());
+        let default_layouts = other
+            .default_layouts
+            .or_else(|| self.default_layouts.clone());
         let layout_dir = other.layout_dir.or_else(|| self.layout_dir.clone());
         let theme_dir = other.theme_dir.or_else(|| self.theme_dir.clone());
         let theme = other.theme.or_else(|| self.theme.clone());
@@ -190,6 +377,7 @@ impl Options {
             default_mode,
             default_shell,
             default_layout,
+            default_layouts,
             layout_dir,
             theme_dir,
             mouse_mode,
@@ -204,6 +392,20 @@ impl Options {
             session_name,
             attach_to_session,
             auto_layout,
+            respect_fixed_sizes,
+            layout_hot_reload,
+            pane_title_formats,
+            strider_pane_width,
+            strider_stack_below_width,
+            command_spawn_interval_ms,
+            default_focus,
+            on_layout_failure,
+            layout_max_depth,
+            layout_max_nodes,
+            layout_max_file_size,
+            max_layout_commands,
+            confirm_layout_commands,
+            resize_relayout_interval_ms,
         }
     }
 
@@ -226,11 +428,38 @@ impl Options {
         let mouse_mode = merge_bool(other.mouse_mode, self.mouse_mode);
         let pane_frames = merge_bool(other.pane_frames, self.pane_frames);
         let auto_layout = merge_bool(other.auto_layout, self.auto_layout);
+        let respect_fixed_sizes = merge_bool(other.respect_fixed_sizes, self.respect_fixed_sizes);
+        let layout_hot_reload = merge_bool(other.layout_hot_reload, self.layout_hot_reload);
+        let pane_title_formats = other
+            .pane_title_formats
+            .or_else(|| self.pane_title_formats.clone());
+        let strider_pane_width = other.strider_pane_width.or(self.strider_pane_width);
+        let strider_stack_below_width = other
+            .strider_stack_below_width
+            .or(self.strider_stack_below_width);
+        let command_spawn_interval_ms = other
+            .command_spawn_interval_ms
+            .or(self.command_spawn_interval_ms);
+        let default_focus = other.default_focus.or(self.default_focus);
+        let on_layout_failure = other.on_layout_failure.or(self.on_layout_failure);
+        let layout_max_depth = other.layout_max_depth.or(self.layout_max_depth);
+        let layout_max_nodes = other.layout_max_nodes.or(self.layout_max_nodes);
+        let layout_max_file_size = other.layout_max_file_size.or(self.layout_max_file_size);
+        let max_layout_commands = other.max_layout_commands.or(self.max_layout_commands);
+        let confirm_layout_commands = other
+            .confirm_layout_commands
+            .or(self.confirm_layout_commands);
+        let resize_relayout_interval_ms = other
+            .resize_relayout_interval_ms
+            .or(self.resize_relayout_interval_ms);
         let mirror_session = merge_bool(other.mirror_session, self.mirror_session);
 
         let default_mode = other.default_mode.or(self.default_mode);
         let default_shell = other.default_shell.or_else(|| self.default_shell.clone());
         let default_layout = other.default_layout.or_else(|| self.default_layout.clone());
+        let default_layouts = other
+            .default_layouts
+            .or_else(|| self.default_layouts.clone());
         let layout_dir = other.layout_dir.or_else(|| self.layout_dir.clone());
         let theme_dir = other.theme_dir.or_else(|| self.theme_dir.clone());
         let theme = other.theme.or_else(|| self.theme.clone());
@@ -253,6 +482,7 @@ impl Options {
             default_mode,
             default_shell,
             default_layout,
+            default_layouts,
             layout_dir,
             theme_dir,
             mouse_mode,
@@ -267,6 +497,41 @@ impl Options {
             session_name,
             attach_to_session,
             auto_layout,
+            respect_fixed_sizes,
+            layout_hot_reload,
+            pane_title_formats,
+            strider_pane_width,
+            strider_stack_below_width,
+            command_spawn_interval_ms,
+            default_focus,
+            on_layout_failure,
+            layout_max_depth,
+            layout_max_nodes,
+            layout_max_file_size,
+            max_layout_commands,
+            confirm_layout_commands,
+            resize_relayout_interval_ms,
+        }
+    }
+
+    /// The layout a new session called `session_name` should start with according to
+    /// `default_layouts`, if any of its patterns match
+    pub fn default_layout_for_session(&self, session_name: &str) -> Option<&PathBuf> {
+        self.default_layouts.as_ref().and_then(|default_layouts| {
+            default_layouts
+                .iter()
+                .find(|(pattern, _layout)| glob_matches(pattern, session_name))
+                .map(|(_pattern, layout)| layout)
+        })
+    }
+
+    /// The limits layouts are parsed with, the defaults for the ones not set
+    pub fn layout_limits(&self) -> LayoutLimits {
+        let defaults = LayoutLimits::default();
+        LayoutLimits {
+            max_depth: self.layout_max_depth.unwrap_or(defaults.max_depth),
+            max_nodes: self.layout_max_nodes.unwrap_or(defaults.max_nodes),
+            max_file_size: self.layout_max_file_size.unwrap_or(defaults.max_file_size),
         }
     }
 
@@ -279,6 +544,38 @@ impl Options {
     }
 }
 
+/// Matches `text` against a glob `pattern`, where `*` matches any number of characters and `?`
+/// matches exactly one
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pattern_index, mut text_index) = (0, 0);
+    // the position of the last `*` we saw and the text position it was matched up to, so that we
+    // can backtrack and have it swallow one more character when the rest fails to match
+    let mut last_star: Option<(usize, usize)> = None;
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                last_star = Some((pattern_index, text_index));
+                pattern_index += 1;
+            },
+            Some(c) if *c == '?' || *c == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            },
+            _ => match last_star {
+                Some((star_index, star_text_index)) => {
+                    last_star = Some((star_index, star_text_index + 1));
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[pattern_index..].iter().all(|c| *c == '*')
+}
+
 #[derive(Clone, Default, Debug, PartialEq, Args, Serialize, Deserialize)]
 /// Options that can be set through cli flags
 /// boolean flags end up toggling boolean options in `Options`
@@ -311,6 +608,7 @@ impl From<CliOptions> for Options {
             default_mode: opts.default_mode,
             default_shell: opts.default_shell,
             default_layout: opts.default_layout,
+            default_layouts: opts.default_layouts,
             layout_dir: opts.layout_dir,
             theme_dir: opts.theme_dir,
             mouse_mode: opts.mouse_mode,
@@ -324,7 +622,16 @@ impl From<CliOptions> for Options {
             scrollback_editor: opts.scrollback_editor,
             session_name: opts.session_name,
             attach_to_session: opts.attach_to_session,
+            strider_stack_below_width: opts.strider_stack_below_width,
+            command_spawn_interval_ms: opts.command_spawn_interval_ms,
+            on_layout_failure: opts.on_layout_failure,
+            confirm_layout_commands: opts.confirm_layout_commands,
+            resize_relayout_interval_ms: opts.resize_relayout_interval_ms,
             ..Default::default()
         }
     }
 }
+
+#[cfg(test)]
+#[path = "./unit/options_test.rs"]
+mod options_test;
@@ -0,0 +1,188 @@
+use super::*;
+
+fn placeholder_values() -> PlaceholderValues {
+    PlaceholderValues {
+        pane_name: Some("api".into()),
+        cwd: Some(PathBuf::from("/projects/services/api")),
+        tab_name: Some("backend".into()),
+        session_name: Some("work".into()),
+    }
+}
+
+#[test]
+fn known_placeholders_are_expanded() {
+    let expanded = expand_placeholders(
+        "{session_name}/{tab_name}/{pane_name} in {cwd}",
+        &placeholder_values(),
+        false,
+    );
+    assert_eq!(
+        expanded,
+        Ok("work/backend/api in /projects/services/api".to_owned())
+    );
+}
+
+#[test]
+fn missing_values_are_expanded_to_an_empty_string() {
+    let expanded = expand_placeholders("--name={pane_name}", &PlaceholderValues::default(), false);
+    assert_eq!(expanded, Ok("--name=".to_owned()));
+}
+
+#[test]
+fn unknown_placeholders_are_left_as_is() {
+    let expanded = expand_placeholders(
+        "find . -exec rm {} ; echo {user} { pane_name } {pane_name",
+        &placeholder_values(),
+        false,
+    );
+    assert_eq!(
+        expanded,
+        Ok("find . -exec rm {} ; echo {user} { pane_name } {pane_name".to_owned())
+    );
+}
+
+#[test]
+fn unknown_placeholders_are_an_error_when_strict() {
+    let expanded = expand_placeholders("echo {user}", &placeholder_values(), true);
+    assert!(expanded.is_err(), "unknown placeholder is an error");
+    let expanded = expand_placeholders("echo {pane_name} {}", &placeholder_values(), true);
+    assert_eq!(expanded, Ok("echo api {}".to_owned()));
+}
+
+#[test]
+fn double_braces_are_a_literal_brace() {
+    let expanded = expand_placeholders("{{pane_name} {{{pane_name}}", &placeholder_values(), true);
+    assert_eq!(expanded, Ok("{pane_name} {api}".to_owned()));
+}
+
+#[test]
+fn run_command_placeholders_are_expanded_in_command_and_args() {
+    let mut run_command = RunCommand {
+        command: PathBuf::from("{cwd}/bin/just"),
+        args: vec!["dev".into(), "--service".into(), "{pane_name}".into()],
+        ..Default::default()
+    };
+    run_command
+        .expand_placeholders(&placeholder_values())
+        .unwrap();
+    assert_eq!(
+        run_command.command,
+        PathBuf::from("/projects/services/api/bin/just")
+    );
+    assert_eq!(run_command.args, vec!["dev", "--service", "api"]);
+}
+
+#[test]
+fn run_command_is_left_untouched_on_strict_placeholder_error() {
+    let mut run_command = RunCommand {
+        command: PathBuf::from("echo"),
+        args: vec!["{pane_name}".into(), "{unknown}".into()],
+        strict_placeholders: true,
+        ..Default::default()
+    };
+    let original = run_command.clone();
+    assert!(run_command
+        .expand_placeholders(&placeholder_values())
+        .is_err());
+    assert_eq!(run_command, original);
+}
+
+#[test]
+fn wrapped_command_runs_under_the_wrapper_in_the_same_cwd() {
+    let wrapper = CommandWrapper {
+        command: PathBuf::from("devcontainer"),
+        args: vec!["exec".into(), "--".into()],
+    };
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["build".into()],
+        cwd: Some(PathBuf::from("/projects/zellij")),
+        hold_on_close: true,
+        ..Default::default()
+    };
+    let wrapped = wrapper.wrap(run_command);
+    assert_eq!(wrapped.command, PathBuf::from("devcontainer"));
+    assert_eq!(wrapped.args, vec!["exec", "--", "cargo", "build"]);
+    assert_eq!(wrapped.cwd, Some(PathBuf::from("/projects/zellij")));
+    assert!(
+        wrapped.hold_on_close,
+        "the pane behaves as it would unwrapped"
+    );
+}
+
+#[test]
+fn environment_variables_are_expanded_and_double_dollars_are_literal() {
+    let value_of = |variable: &str| match variable {
+        "HOME" => Some("/home/aram".to_owned()),
+        "PROJECT" => Some("zellij".to_owned()),
+        _ => None,
+    };
+    assert_eq!(
+        expand_env_vars_with("$HOME/projects/${PROJECT}-fork", value_of),
+        Ok("/home/aram/projects/zellij-fork".to_owned())
+    );
+    assert_eq!(
+        expand_env_vars_with("$$HOME costs $5 or ${", value_of),
+        Ok("$HOME costs $5 or ${".to_owned())
+    );
+    assert_eq!(
+        expand_env_vars_with("${PROJECT}/$MISSING", value_of),
+        Err("MISSING".to_owned())
+    );
+}
+
+#[test]
+fn leading_tilde_is_the_home_directory() {
+    let home_dir = Path::new("/home/aram");
+    assert_eq!(
+        expand_home_with("~", Some(home_dir)),
+        PathBuf::from("/home/aram")
+    );
+    assert_eq!(
+        expand_home_with("~/projects", Some(home_dir)),
+        PathBuf::from("/home/aram/projects")
+    );
+    assert_eq!(
+        expand_home_with("~aram/projects", Some(home_dir)),
+        PathBuf::from("~aram/projects")
+    );
+    assert_eq!(
+        expand_home_with("/tmp/~", Some(home_dir)),
+        PathBuf::from("/tmp/~")
+    );
+    assert_eq!(
+        expand_home_with("~/projects", None),
+        PathBuf::from("~/projects")
+    );
+}
+
+#[test]
+fn home_directory_is_shortened_to_a_tilde() {
+    let home_dir = Some(Path::new("/home/aram"));
+    assert_eq!(shorten_home_with(Path::new("/home/aram"), home_dir), "~");
+    assert_eq!(
+        shorten_home_with(Path::new("/home/aram/projects"), home_dir),
+        "~/projects"
+    );
+    assert_eq!(
+        shorten_home_with(Path::new("/home/aramis/projects"), home_dir),
+        "/home/aramis/projects"
+    );
+    assert_eq!(
+        shorten_home_with(Path::new("/home/aram/projects"), None),
+        "/home/aram/projects"
+    );
+}
+
+#[test]
+fn commands_are_found_in_the_cwd_or_the_path() {
+    let cwd = tempfile::tempdir().unwrap();
+    std::fs::write(cwd.path().join("run.sh"), "").unwrap();
+    assert!(command_exists(Path::new("run.sh"), Some(cwd.path())));
+    assert!(!command_exists(Path::new("run.sh"), None));
+    assert!(command_exists(Path::new("sh"), None));
+    assert!(!command_exists(
+        Path::new("zellij-no-such-command"),
+        Some(cwd.path())
+    ));
+}
@@ -0,0 +1,187 @@
+use super::*;
+use insta::assert_snapshot;
+
+fn viewport() -> PaneGeom {
+    PaneGeom::from(&Size {
+        rows: 40,
+        cols: 120,
+    })
+}
+
+fn flatten_built_in_layout(layout_name: &str) -> Vec<FlatLayout> {
+    let (path_to_layout, raw_layout, _swap_layouts) =
+        Layout::stringified_from_default_assets(std::path::Path::new(layout_name)).unwrap();
+    let layout = Layout::from_kdl(&raw_layout, path_to_layout, None, None).unwrap();
+    layout.flatten(&viewport()).unwrap()
+}
+
+#[test]
+fn flatten_default_layout() {
+    assert_snapshot!(format!("{:#?}", flatten_built_in_layout("default")));
+}
+
+#[test]
+fn flatten_strider_layout() {
+    assert_snapshot!(format!("{:#?}", flatten_built_in_layout("strider")));
+}
+
+#[test]
+fn flatten_compact_layout() {
+    assert_snapshot!(format!("{:#?}", flatten_built_in_layout("compact")));
+}
+
+#[test]
+fn flatten_disable_status_bar_layout() {
+    assert_snapshot!(format!(
+        "{:#?}",
+        flatten_built_in_layout("disable-status-bar")
+    ));
+}
+
+#[test]
+fn flatten_tabs_with_floating_panes() {
+    let kdl_layout = r#"
+        layout {
+            tab name="editor" {
+                pane split_direction="vertical" {
+                    pane name="files" size="25%"
+                    pane stacked=true {
+                        pane command="vim"
+                        pane edit="src/main.rs"
+                    }
+                }
+            }
+            tab name="logs" {
+                pane
+                floating_panes {
+                    pane name="tail" command="tail"
+                    pane x=0 y=0 width="50%" height=10
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let flat_layouts = layout.flatten(&viewport()).unwrap();
+    let tab_names: Vec<Option<String>> = flat_layouts.iter().map(|f| f.tab_name.clone()).collect();
+    assert_eq!(
+        tab_names,
+        vec![Some("editor".to_owned()), Some("logs".to_owned())]
+    );
+    let editor_panes: Vec<(Vec<usize>, Option<String>, (f32, f32, f32, f32), bool)> = flat_layouts
+        [0]
+    .panes
+    .iter()
+    .map(|p| {
+        (
+            p.path.clone(),
+            p.run_summary.clone(),
+            p.geom_percent,
+            p.stacked,
+        )
+    })
+    .collect();
+    assert_eq!(
+        editor_panes,
+        vec![
+            (vec![0, 0], None, (0.0, 0.0, 0.25, 1.0), false),
+            (
+                vec![0, 1, 0],
+                Some("vim".to_owned()),
+                (0.25, 0.0, 0.75, 0.025),
+                true
+            ),
+            (
+                vec![0, 1, 1],
+                Some("main.rs".to_owned()),
+                (0.25, 0.025, 0.75, 0.975),
+                true
+            ),
+        ]
+    );
+    let floating_panes: Vec<(Vec<usize>, Option<String>, (f32, f32, f32, f32))> = flat_layouts[1]
+        .panes
+        .iter()
+        .filter(|p| p.floating)
+        .map(|p| (p.path.clone(), p.name.clone(), p.geom_percent))
+        .collect();
+    assert_eq!(
+        floating_panes,
+        vec![
+            (vec![0], Some("tail".to_owned()), (0.25, 0.25, 0.5, 0.5)),
+            (vec![1], None, (0.0, 0.0, 0.5, 0.25)),
+        ]
+    );
+}
+
+#[test]
+fn flatten_floating_panes_placed_from_anchors() {
+    let kdl_layout = r#"
+        layout {
+            pane
+            floating_panes {
+                pane anchor="bottom_right" x=0 y=1 width=24 height=10
+                pane anchor="center" width=60 height=20
+                pane anchor="top_right" x=500 width=24 height=10
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let flat_layouts = layout.flatten(&viewport()).unwrap();
+    let floating_panes: Vec<(f32, f32, f32, f32)> = flat_layouts[0]
+        .panes
+        .iter()
+        .filter(|p| p.floating)
+        .map(|p| p.geom_percent)
+        .collect();
+    assert_eq!(
+        floating_panes,
+        vec![
+            (0.8, 0.725, 0.2, 0.25),
+            (0.25, 0.25, 0.5, 0.5),
+            (0.0, 0.0, 0.2, 0.25), // the offset is larger than the screen
+        ]
+    );
+}
+
+#[test]
+fn flatten_leaves_room_for_the_side_panel() {
+    let kdl_layout = r#"
+        layout {
+            pane
+            side_panel width="25%" {
+                pane command="htop"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let flat_layouts = layout.flatten(&viewport()).unwrap();
+    assert_eq!(flat_layouts[0].panes[0].geom_percent, (0.0, 0.0, 0.75, 1.0));
+}
+
+#[test]
+fn tiled_pane_layout_converts_to_a_flat_layout() {
+    let mut tiled_panes = TiledPaneLayout::default();
+    tiled_panes.children = vec![TiledPaneLayout::default(), TiledPaneLayout::default()];
+    tiled_panes.children[1].borderless = true;
+    let flat_layout = FlatLayout::try_from(&tiled_panes).unwrap();
+    let panes: Vec<(Vec<usize>, (f32, f32, f32, f32), bool)> = flat_layout
+        .panes
+        .iter()
+        .map(|p| (p.path.clone(), p.geom_percent, p.borderless))
+        .collect();
+    assert_eq!(
+        panes,
+        vec![
+            (vec![0], (0.0, 0.0, 1.0, 0.5), false),
+            (vec![1], (0.0, 0.5, 1.0, 0.5), true),
+        ]
+    );
+}
+
+#[test]
+fn flat_layout_serializes() {
+    let flat_layouts = flatten_built_in_layout("compact");
+    let serialized = serde_json::to_string(&flat_layouts).unwrap();
+    let deserialized: Vec<FlatLayout> = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, flat_layouts);
+}
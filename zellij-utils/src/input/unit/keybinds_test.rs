@@ -77,6 +77,112 @@ fn can_define_series_of_actions_for_same_keybinding() {
     );
 }
 
+#[test]
+fn can_define_swap_layout_keybindings_with_and_without_a_tag() {
+    let config_contents = r#"
+        keybinds {
+            tab {
+                bind "Space" { NextSwapLayout; }
+                bind "r" { NextSwapLayout "review"; }
+                bind "R" { PreviousSwapLayout "review"; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let actions_for_key = |key| {
+        config
+            .keybinds
+            .get_actions_for_key_in_mode(&InputMode::Tab, &key)
+            .cloned()
+    };
+    assert_eq!(
+        actions_for_key(Key::Char(' ')),
+        Some(vec![Action::NextSwapLayout(None)]),
+        "Untagged swap layout keybinding defined"
+    );
+    assert_eq!(
+        actions_for_key(Key::Char('r')),
+        Some(vec![Action::NextSwapLayout(Some("review".into()))]),
+        "Tagged next swap layout keybinding defined"
+    );
+    assert_eq!(
+        actions_for_key(Key::Char('R')),
+        Some(vec![Action::PreviousSwapLayout(Some("review".into()))]),
+        "Tagged previous swap layout keybinding defined"
+    );
+}
+
+#[test]
+fn can_define_rotate_tab_layout_keybindings() {
+    let config_contents = r#"
+        keybinds {
+            tab {
+                bind "o" { RotateTabLayout; }
+                bind "O" { RotateTabLayout "counterclockwise"; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let actions_for_key = |key| {
+        config
+            .keybinds
+            .get_actions_for_key_in_mode(&InputMode::Tab, &key)
+            .cloned()
+    };
+    assert_eq!(
+        actions_for_key(Key::Char('o')),
+        Some(vec![Action::RotateTabLayout { clockwise: true }]),
+    );
+    assert_eq!(
+        actions_for_key(Key::Char('O')),
+        Some(vec![Action::RotateTabLayout { clockwise: false }]),
+    );
+    let invalid_config = r#"
+        keybinds {
+            tab {
+                bind "o" { RotateTabLayout "sideways"; }
+            }
+        }
+    "#;
+    assert!(Config::from_kdl(invalid_config, None).is_err());
+}
+
+#[test]
+fn can_define_new_tab_keybindings_opening_a_tab_template() {
+    let config_contents = r#"
+        keybinds {
+            tab {
+                bind "d" { NewTab { template "dev"; name "backend"; }; }
+            }
+        }
+    "#;
+    let config = Config::from_kdl(config_contents, None).unwrap();
+    let actions = config
+        .keybinds
+        .get_actions_for_key_in_mode(&InputMode::Tab, &Key::Char('d'))
+        .cloned();
+    assert_eq!(
+        actions,
+        Some(vec![Action::NewTab(
+            None,
+            vec![],
+            None,
+            None,
+            Some("backend".into()),
+            Some("dev".into()),
+        )]),
+        "the template is looked up in the session layout when the tab opens"
+    );
+    let template_with_a_layout = r#"
+        keybinds {
+            tab {
+                bind "d" { NewTab { template "dev"; layout "compact"; }; }
+            }
+        }
+    "#;
+    assert!(Config::from_kdl(template_with_a_layout, None).is_err());
+}
+
 #[test]
 fn keybindings_bind_order_is_preserved() {
     let config_contents = r#"
@@ -0,0 +1,105 @@
+use super::*;
+use crate::input::config::ConfigError;
+use crate::input::layout::{Layout, TiledPaneLayout};
+use crate::pane_size::{PaneGeom, Size};
+
+fn tiled_layout(raw_layout: &str) -> TiledPaneLayout {
+    let layout = Layout::from_kdl(raw_layout, "layout_file_name".into(), None, None).unwrap();
+    layout.new_tab().0
+}
+
+fn space(cols: usize, rows: usize) -> PaneGeom {
+    PaneGeom::from(&Size { rows, cols })
+}
+
+#[test]
+fn errors_name_the_pane_that_does_not_fit() {
+    let layout = tiled_layout(
+        r#"
+        layout {
+            pane name="metrics" split_direction="vertical" {
+                pane size=30
+                pane size=30
+            }
+        }
+    "#,
+    );
+    let error = layout
+        .position_panes_in_space(&space(40, 20), None)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        LayoutApplyError::FixedSizeOverflow {
+            pane: LayoutPanePath::new(Some("metrics".into()), vec![0]),
+            needed: 60,
+            available: 40,
+            direction: SplitDirection::Vertical,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "The fixed size panes in pane 1 \"metrics\" take 60 columns but only 40 are available"
+    );
+}
+
+#[test]
+fn errors_point_at_unnamed_panes_by_their_place_in_the_layout() {
+    let layout = tiled_layout(
+        r#"
+        layout {
+            pane
+            pane {
+                pane split_direction="vertical" {
+                    pane
+                    pane
+                    pane
+                    pane
+                    pane
+                }
+            }
+        }
+    "#,
+    );
+    let error = layout
+        .position_panes_in_space(&space(4, 20), None)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        LayoutApplyError::NotEnoughRoom {
+            pane: LayoutPanePath::new(None, vec![1, 0]),
+            needed: 5,
+            available: 4,
+            direction: SplitDirection::Vertical,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Not enough room for the panes in pane 2.1: they need at least 5 columns but have 4"
+    );
+}
+
+#[test]
+fn layout_apply_errors_are_shown_as_they_are_as_config_errors() {
+    let error = LayoutApplyError::BelowMinimumSize {
+        pane: LayoutPanePath::new(None, vec![]),
+    };
+    assert_eq!(error.to_string(), "No room on screen for the layout");
+    assert_eq!(
+        ConfigError::from(error.clone()).to_string(),
+        error.to_string()
+    );
+}
+
+#[test]
+fn named_panes_are_located_by_their_place_in_the_layout_as_well() {
+    let pane = LayoutPanePath::new(Some("logs".into()), vec![1, 0]);
+    assert_eq!(pane.located(), "pane 2.1 \"logs\"");
+    assert_eq!(pane.to_string(), "pane \"logs\"");
+    let error = LayoutApplyError::BelowMinimumSize { pane };
+    assert_eq!(error.to_string(), "No room on screen for pane 2.1 \"logs\"");
+    assert_eq!(
+        LayoutPanePath::new(Some("root".into()), vec![]).located(),
+        "pane \"root\"",
+        "the layout itself has no place to be located by"
+    );
+}
@@ -0,0 +1,127 @@
+use super::*;
+use crate::input::command::PaneEncoding;
+use crate::input::layout::{
+    resolve_pane_name, ExtraPanesPolicy, FloatingPaneAnchor, FocusPolicy, Layout,
+    MissingSlotsPolicy, PaneNameValues, TabPin,
+};
+use std::str::FromStr;
+
+#[test]
+fn supported_capabilities_come_with_a_layout_that_parses() {
+    for capability in LAYOUT_CAPABILITIES {
+        match (capability.support, capability.example) {
+            (CapabilitySupport::Unsupported, _) => {},
+            (_, Some(example)) => {
+                if let Err(e) = Layout::from_kdl(example, "layout_file_name".into(), None, None) {
+                    panic!("example of {} failed to parse: {:?}", capability.id, e);
+                }
+            },
+            (_, None) => panic!("{} is advertised without an example", capability.id),
+        }
+    }
+}
+
+#[test]
+fn advertised_values_are_parsed() {
+    for value in layout_capability_values(SWAP_EXTRA_PANES) {
+        assert!(ExtraPanesPolicy::from_str(value).is_ok(), "{}", value);
+    }
+    for value in layout_capability_values(SWAP_MISSING_SLOTS) {
+        assert!(MissingSlotsPolicy::from_str(value).is_ok(), "{}", value);
+    }
+    for value in layout_capability_values(TAB_PIN) {
+        assert!(TabPin::from_str(value).is_ok(), "{}", value);
+    }
+    for value in layout_capability_values(DEFAULT_FOCUS) {
+        assert!(FocusPolicy::from_str(value).is_ok(), "{}", value);
+    }
+    for value in layout_capability_values(PANE_ENCODING) {
+        assert!(PaneEncoding::from_str(value).is_ok(), "{}", value);
+    }
+    for value in layout_capability_values(FLOATING_PANE_ANCHORS) {
+        assert!(FloatingPaneAnchor::from_str(value).is_ok(), "{}", value);
+    }
+    for placeholder in layout_capability_values(PANE_NAME_PLACEHOLDERS) {
+        let name = format!("{{{}}}", placeholder);
+        let resolved = resolve_pane_name(&name, &None, 1, &PaneNameValues::default());
+        assert_ne!(resolved, name, "{}", placeholder);
+    }
+    for constraint in layout_capability_values(SWAP_CONSTRAINTS) {
+        let kdl_layout = format!(
+            "layout {{ swap_tiled_layout {{ tab {}=2 {{ pane; }}; }}; }}",
+            constraint
+        );
+        assert!(
+            Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None).is_ok(),
+            "{}",
+            constraint
+        );
+    }
+    for condition in layout_capability_values(CONDITIONAL_PANES) {
+        let kdl_layout = format!("layout {{ pane {}=\"sh\"; }}", condition);
+        assert!(
+            Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None).is_ok(),
+            "{}",
+            condition
+        );
+    }
+}
+
+#[test]
+fn capability_ids_are_unique() {
+    for (i, capability) in LAYOUT_CAPABILITIES.iter().enumerate() {
+        assert!(
+            !LAYOUT_CAPABILITIES[i + 1..]
+                .iter()
+                .any(|other| other.id == capability.id),
+            "{} is listed twice",
+            capability.id
+        );
+    }
+}
+
+#[test]
+fn capabilities_json_includes_the_version() {
+    let json = layout_capabilities_json();
+    assert_eq!(json["version"], VERSION);
+    assert_eq!(json["capabilities"][STACKED_PANES], true);
+    assert_eq!(json["capabilities"][SIZE_OFFSETS], false);
+    assert_eq!(
+        json["capabilities"][SWAP_CONSTRAINTS],
+        serde_json::json!([
+            "exact_panes",
+            "min_panes",
+            "max_panes",
+            "min_groups",
+            "max_groups",
+            "session_tabs_max",
+            "session_tabs_min"
+        ])
+    );
+}
+
+#[test]
+fn capabilities_json_lists_the_deprecated_names() {
+    let json = layout_capabilities_json();
+    for deprecated in DEPRECATED_NAMES {
+        assert_eq!(json["deprecated"][deprecated.old], deprecated.new);
+    }
+    assert_eq!(json["deprecated"]["direction"], "split_direction");
+}
+
+#[test]
+fn deprecated_names_are_not_also_current_names() {
+    for deprecated in DEPRECATED_NAMES {
+        assert!(
+            !DEPRECATED_NAMES
+                .iter()
+                .any(|other| other.old == deprecated.new),
+            "{} is renamed to a deprecated name",
+            deprecated.old
+        );
+        assert_eq!(
+            deprecated_name(deprecated.kind, deprecated.old),
+            Some(deprecated)
+        );
+    }
+}
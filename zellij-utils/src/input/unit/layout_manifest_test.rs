@@ -0,0 +1,271 @@
+use super::*;
+use std::collections::HashMap;
+
+fn manifest(raw_layout: &str) -> LayoutManifest {
+    let layout = Layout::from_kdl(raw_layout, "layout_file_name".into(), None, None).unwrap();
+    layout.manifest(&PluginsConfig::default(), &EnvironmentVariables::default())
+}
+
+#[test]
+fn manifest_lists_what_each_tab_runs() {
+    let manifest = manifest(
+        r#"
+        layout {
+            cwd "/project"
+            tab name="code" {
+                pane name="editor" edit="src/main.rs"
+                pane name="server" command="cargo" cwd="server" {
+                    args "run" "--release"
+                }
+            }
+            tab name="tools" {
+                pane
+                pane name="explorer" {
+                    plugin location="file:/path/to/my/plugin.wasm"
+                }
+            }
+        }
+    "#,
+    );
+    assert_eq!(manifest.tabs.len(), 2);
+    assert_eq!(manifest.tabs[0].name.as_deref(), Some("code"));
+    assert_eq!(
+        manifest.tabs[0].panes[0].runs,
+        Some(PaneRunManifest::EditFile(
+            vec![PathBuf::from("/project/src/main.rs")],
+            None
+        ))
+    );
+    assert_eq!(
+        manifest.tabs[0].panes[1],
+        PaneManifest {
+            name: Some("server".into()),
+            runs: Some(PaneRunManifest::Command {
+                command: PathBuf::from("cargo"),
+                args: vec!["run".into(), "--release".into()],
+                waits_for: None,
+            }),
+            cwd: Some(PathBuf::from("/project/server")),
+            floating: None,
+            no_wrapper: false,
+            redacted: false,
+        }
+    );
+    assert_eq!(
+        manifest.tabs[1].panes[0].runs, None,
+        "a plain pane runs a shell"
+    );
+    assert_eq!(
+        manifest.tabs[1].panes[0].cwd,
+        Some(PathBuf::from("/project"))
+    );
+    assert_eq!(
+        manifest.tabs[1].panes[1].runs,
+        Some(PaneRunManifest::Plugin {
+            location: "/path/to/my/plugin.wasm".into(),
+            path: Some(PathBuf::from("/path/to/my/plugin.wasm")),
+        })
+    );
+}
+
+#[test]
+fn manifest_includes_floating_pane_geometry() {
+    let manifest = manifest(
+        r#"
+        layout {
+            pane
+            floating_panes {
+                pane name="logs" x="10%" y=2 width="50%" command="tail" {
+                    args "-f" "app.log"
+                }
+                pane
+            }
+        }
+    "#,
+    );
+    let panes = &manifest.tabs[0].panes;
+    assert_eq!(
+        panes.len(),
+        3,
+        "tiled panes are listed before floating ones"
+    );
+    assert_eq!(panes[0].floating, None);
+    assert_eq!(
+        panes[1].floating,
+        Some(FloatingGeometry {
+            x: Some("10%".into()),
+            y: Some("2".into()),
+            width: Some("50%".into()),
+            height: None,
+        })
+    );
+    assert_eq!(panes[2].floating, Some(FloatingGeometry::default()));
+}
+
+#[test]
+fn manifest_reports_unknown_plugin_aliases_and_env() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            pane {
+                plugin location="zellij:not-configured"
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let env = EnvironmentVariables::from_data(HashMap::from([(
+        "RUST_LOG".to_string(),
+        "debug".to_string(),
+    )]));
+    let manifest = layout.manifest(&PluginsConfig::default(), &env);
+    assert_eq!(
+        manifest.tabs[0].panes[0].runs,
+        Some(PaneRunManifest::Plugin {
+            location: "not-configured".into(),
+            path: None,
+        })
+    );
+    assert_eq!(
+        manifest.env.get("RUST_LOG").map(String::as_str),
+        Some("debug")
+    );
+    let printed = manifest.to_string();
+    assert!(printed.contains("plugin: not-configured (not found in the config)"));
+    assert!(printed.contains("RUST_LOG=\"debug\""));
+}
+
+#[test]
+fn manifest_lists_the_command_wrapper_apart_from_the_commands() {
+    let manifest = manifest(
+        r#"
+        layout {
+            command_wrapper "devcontainer" {
+                args "exec" "--"
+            }
+            pane command="cargo" {
+                args "build"
+            }
+            pane command="htop" no_wrapper=true
+        }
+    "#,
+    );
+    assert_eq!(
+        manifest.command_wrapper,
+        Some(CommandWrapper {
+            command: PathBuf::from("devcontainer"),
+            args: vec!["exec".into(), "--".into()],
+        })
+    );
+    assert_eq!(
+        manifest.tabs[0].panes[0].runs,
+        Some(PaneRunManifest::Command {
+            command: PathBuf::from("cargo"),
+            args: vec!["build".into()],
+            waits_for: None,
+        })
+    );
+    assert!(manifest.tabs[0].panes[1].no_wrapper);
+    let printed = manifest.to_string();
+    assert!(printed.contains("command wrapper: devcontainer\n  args: [\"exec\", \"--\"]"));
+    assert!(printed.contains("command: cargo\n    args: [\"build\"]"));
+    assert!(printed.contains("command: htop\n    not wrapped"));
+    assert!(!printed.contains("devcontainer exec"), "{}", printed);
+}
+
+#[test]
+fn manifest_leaves_out_what_panes_excluded_from_dump_run() {
+    let manifest = manifest(
+        r#"
+        layout {
+            pane name="deploy" command="deploy" exclude_from_dump=true {
+                args "--token" "abc"
+            }
+            pane command="htop"
+            floating_panes {
+                pane command="vault" exclude_from_dump=true
+            }
+        }
+    "#,
+    );
+    let panes = &manifest.tabs[0].panes;
+    assert!(panes[0].redacted);
+    assert_eq!(panes[0].runs, None);
+    assert_eq!(panes[0].name.as_deref(), Some("deploy"));
+    assert!(!panes[1].redacted);
+    assert!(panes[2].redacted);
+    assert_eq!(panes[2].runs, None);
+    let printed = manifest.to_string();
+    assert!(
+        printed.contains("pane \"deploy\"\n    redacted"),
+        "{}",
+        printed
+    );
+    assert!(!printed.contains("abc"), "{}", printed);
+    assert!(!printed.contains("vault"), "{}", printed);
+}
+
+#[test]
+fn manifest_for_review_lists_everything_the_layout_runs() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            pane name="deploy" command="deploy" exclude_from_dump=true
+            floating_panes {
+                pane command="vault" exclude_from_dump=true
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let manifest = layout.manifest_for_review(&PluginsConfig::default(), &Default::default());
+    let printed = manifest.to_string();
+    assert!(printed.contains("command: deploy"), "{}", printed);
+    assert!(printed.contains("command: vault"), "{}", printed);
+    assert!(manifest.runs_commands());
+}
+
+#[test]
+fn manifest_tells_whether_the_layout_runs_commands() {
+    let runs_commands = |raw_layout: &str| manifest(raw_layout).runs_commands();
+    assert!(!runs_commands(
+        r#"layout { pane; pane { plugin location="zellij:strider"; }; pane edit="README.md"; }"#
+    ));
+    assert!(runs_commands(r#"layout { pane; pane command="htop"; }"#));
+    assert!(runs_commands(
+        r#"layout { pane; floating_panes { pane command="htop"; }; }"#
+    ));
+    assert!(
+        runs_commands(r#"layout { command_wrapper "nix-shell"; pane edit="README.md"; }"#),
+        "the editor runs under the wrapper"
+    );
+}
+
+#[test]
+fn manifest_lists_sizes_fitted_to_content_as_fit() {
+    let manifest = manifest(
+        r#"
+        layout {
+            pane
+            floating_panes {
+                pane command="cal" height="fit" max_height=20 width=30
+            }
+        }
+    "#,
+    );
+    assert_eq!(
+        manifest.tabs[0].panes[1].floating,
+        Some(FloatingGeometry {
+            x: None,
+            y: None,
+            width: Some("30".into()),
+            height: Some("fit".into()),
+        })
+    );
+}
@@ -0,0 +1,113 @@
+use super::*;
+use std::path::Path;
+
+fn resurrection_fixture(name: &str) -> String {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let path = root.join("src/input/unit/fixtures/layouts").join(name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn error_message(error: ConfigError) -> String {
+    match error {
+        ConfigError::KdlError(kdl_error) => kdl_error.error_message,
+        e => format!("{}", e),
+    }
+}
+
+#[test]
+fn current_format_is_resurrected_without_migrating() {
+    let raw_layout = resurrection_fixture("resurrection-current.kdl");
+    let migrated_layout = migrate_resurrection_layout(&raw_layout).unwrap();
+    assert_eq!(migrated_layout.from_version, RESURRECTION_FORMAT_VERSION);
+    assert!(migrated_layout.applied_migrations.is_empty());
+    assert_eq!(migrated_layout.notice(), None);
+    assert!(!migrated_layout.raw_layout.contains("format_version"));
+    let (layout, notice) =
+        Layout::from_resurrection_kdl(&raw_layout, "resurrection-current.kdl".into(), None)
+            .unwrap();
+    assert_eq!(notice, None);
+    assert_eq!(layout.tabs().len(), 2);
+}
+
+#[test]
+fn older_format_is_migrated_with_a_notice() {
+    let raw_layout = resurrection_fixture("resurrection-v0.kdl");
+    // the old format does not parse as a current layout
+    assert!(Layout::from_kdl(&raw_layout, "resurrection-v0.kdl".into(), None, None).is_err());
+    let (layout, notice) =
+        Layout::from_resurrection_kdl(&raw_layout, "resurrection-v0.kdl".into(), None).unwrap();
+    let notice = notice.expect("a notice for the migrated layout");
+    assert!(!notice.contains('\n'), "{}", notice);
+    assert!(notice.contains("from format 0 to 1"), "{}", notice);
+    let (current_layout, _notice) = Layout::from_resurrection_kdl(
+        &resurrection_fixture("resurrection-current.kdl"),
+        "resurrection-current.kdl".into(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(layout, current_layout);
+}
+
+#[test]
+fn layouts_without_a_format_version_are_migrated_from_the_first_format() {
+    let raw_layout = r#"
+        layout {
+            pane direction="vertical" {
+                pane
+                pane
+            }
+        }
+    "#;
+    let migrated_layout = migrate_resurrection_layout(raw_layout).unwrap();
+    assert_eq!(migrated_layout.from_version, 0);
+    assert!(migrated_layout.raw_layout.contains("split_direction"));
+    assert!(migrated_layout.notice().is_some());
+}
+
+#[test]
+fn layouts_from_a_newer_format_are_refused() {
+    let raw_layout = format!(
+        "format_version {}\nlayout {{\n    pane\n}}\n",
+        RESURRECTION_FORMAT_VERSION + 1
+    );
+    let error = Layout::from_resurrection_kdl(&raw_layout, "newer.kdl".into(), None).unwrap_err();
+    let message = error_message(error);
+    assert!(message.contains("newer version of zellij"), "{}", message);
+    assert!(message.contains("upgrade zellij"), "{}", message);
+}
+
+#[test]
+fn invalid_format_versions_are_refused() {
+    for format_version in ["\"1\"", "-1", "1 2", "version=1"] {
+        let raw_layout = format!(
+            "format_version {}\nlayout {{\n    pane\n}}\n",
+            format_version
+        );
+        let message = error_message(migrate_resurrection_layout(&raw_layout).unwrap_err());
+        assert!(message.contains("whole number"), "{}", message);
+    }
+    let raw_layout = "format_version 1\nformat_version 1\nlayout {\n    pane\n}\n";
+    let message = error_message(migrate_resurrection_layout(raw_layout).unwrap_err());
+    assert!(message.contains("Only one format_version"), "{}", message);
+}
+
+#[test]
+fn there_is_a_migration_from_every_older_format() {
+    let from_versions: Vec<u64> = MIGRATIONS
+        .iter()
+        .map(|migration| migration.from_version)
+        .collect();
+    let older_versions: Vec<u64> =
+        (UNVERSIONED_FORMAT_VERSION..RESURRECTION_FORMAT_VERSION).collect();
+    assert_eq!(from_versions, older_versions);
+}
+
+#[test]
+fn versioned_layouts_are_resurrected_as_they_were_saved() {
+    let raw_layout =
+        "layout {\n    pane split_direction=\"vertical\" {\n        pane\n        pane\n    }\n}\n";
+    let saved_layout = versioned_resurrection_layout(raw_layout);
+    let migrated_layout = migrate_resurrection_layout(&saved_layout).unwrap();
+    assert_eq!(migrated_layout.raw_layout, raw_layout);
+    assert_eq!(migrated_layout.notice(), None);
+}
@@ -0,0 +1,175 @@
+use super::*;
+use crate::input::plugins::{PluginConfig, PluginTag, PluginType};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn layout(raw_layout: &str) -> Layout {
+    Layout::from_kdl(raw_layout, "layout_file_name".into(), None, None).unwrap()
+}
+
+fn screen(cols: usize, rows: usize) -> Option<Size> {
+    Some(Size { rows, cols })
+}
+
+#[test]
+fn preflight_passes_a_layout_that_fits() {
+    let layout = layout(
+        r#"
+        layout {
+            tab name="code" {
+                pane split_direction="vertical" {
+                    pane size=30
+                    pane
+                }
+            }
+            tab
+        }
+    "#,
+    );
+    assert_eq!(
+        layout.preflight(screen(80, 24), &PluginsConfig::default()),
+        vec![]
+    );
+}
+
+#[test]
+fn preflight_names_the_tab_without_room() {
+    let layout = layout(
+        r#"
+        layout {
+            tab name="code"
+            tab name="wide" {
+                pane split_direction="vertical" {
+                    pane size=30
+                    pane size=30
+                }
+            }
+            tab
+        }
+    "#,
+    );
+    let errors = layout.preflight(screen(40, 20), &PluginsConfig::default());
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].tab_index, 1);
+    assert_eq!(errors[0].tab_name.as_deref(), Some("wide"));
+    assert_eq!(
+        errors[0].to_string(),
+        "tab #2 \"wide\": The fixed size panes in pane 1 take 60 columns but only 40 are \
+         available (40 columns, 20 rows)"
+    );
+    assert_eq!(
+        layout.preflight(None, &PluginsConfig::default()),
+        vec![],
+        "geometry is only checked against a screen size"
+    );
+}
+
+#[test]
+fn preflight_leaves_room_for_the_side_panel() {
+    let layout = layout(
+        r#"
+        layout {
+            side_panel width=20 {
+                pane
+            }
+            pane split_direction="vertical" {
+                pane size=30
+                pane size=30
+            }
+        }
+    "#,
+    );
+    assert_eq!(
+        layout
+            .preflight(screen(70, 20), &PluginsConfig::default())
+            .len(),
+        1
+    );
+    assert_eq!(
+        layout.preflight(screen(90, 20), &PluginsConfig::default()),
+        vec![]
+    );
+}
+
+#[test]
+fn preflight_reports_plugins_that_cannot_be_found() {
+    let layout = layout(
+        r#"
+        layout {
+            tab {
+                pane {
+                    plugin location="zellij:tab-bar"
+                }
+                pane
+            }
+            tab {
+                pane
+                floating_panes {
+                    pane {
+                        plugin location="zellij:not-configured"
+                    }
+                }
+            }
+            tab {
+                pane {
+                    plugin location="file:/no/such/dir/plugin.wasm"
+                }
+            }
+        }
+    "#,
+    );
+    let plugins = PluginsConfig::from_data(HashMap::from([(
+        PluginTag::new("tab-bar"),
+        PluginConfig {
+            path: PathBuf::from("tab-bar"),
+            run: PluginType::Pane(None),
+            _allow_exec_host_cmd: false,
+            location: RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        },
+    )]));
+    let errors: Vec<String> = layout
+        .preflight(None, &plugins)
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "tab #2: plugin \"not-configured\" is not defined in the config".to_owned(),
+            "tab #3: plugin file /no/such/dir/plugin.wasm does not exist".to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn swap_layouts_that_do_not_fit_are_listed() {
+    let layout = layout(
+        r#"
+        layout {
+            pane
+            swap_tiled_layout name="wide" {
+                tab max_panes=2 {
+                    pane split_direction="vertical" {
+                        pane size=100
+                        pane
+                    }
+                }
+                tab {
+                    pane
+                    children
+                }
+            }
+        }
+    "#,
+    );
+    assert_eq!(
+        layout.swap_layouts_not_fitting(Size { cols: 80, rows: 24 }),
+        vec!["swap layout \"wide\" (max_panes 2) does not fit: The fixed size panes in pane 1 take 100 columns but only 80 are available".to_owned()]
+    );
+    assert!(layout
+        .swap_layouts_not_fitting(Size {
+            cols: 120,
+            rows: 40
+        })
+        .is_empty());
+}
@@ -0,0 +1,78 @@
+use super::*;
+use crate::input::layout::ValidationOptions;
+use crate::kdl::LayoutTemplates;
+use std::path::Path;
+
+fn layout_fixture(name: &str) -> String {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let path = root.join("src/input/unit/fixtures/layouts").join(name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn load_stats_of(raw_layout: &str) -> LayoutLoadStats {
+    let (_layout, _diagnostics, load_stats) = Layout::from_kdl_with_stats(
+        raw_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::strict(),
+        &LayoutTemplates::default(),
+    )
+    .unwrap();
+    load_stats
+}
+
+#[test]
+fn large_generated_layouts_have_their_load_stats_populated() {
+    let load_stats = load_stats_of(&layout_fixture("generated-monitoring.kdl"));
+    // 40 tabs of 4 rows of 6 metric panes, each with the tab bar and the status bar
+    assert_eq!(load_stats.pane_count, 40 * (4 * 6 + 2));
+    assert_eq!(load_stats.node_count, 2129);
+    assert!(
+        load_stats.parse_ms + load_stats.expand_ms + load_stats.geometry_ms > 0,
+        "{}",
+        load_stats
+    );
+}
+
+#[test]
+fn layouts_without_tabs_count_the_panes_of_their_new_tab_template() {
+    let load_stats = load_stats_of(
+        r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                pane
+            }
+            floating_panes {
+                pane
+            }
+        }
+    "#,
+    );
+    assert_eq!(load_stats.pane_count, 3);
+    assert_eq!(load_stats.node_count, 6);
+}
+
+#[test]
+fn load_stats_of_layouts_opened_together_add_up() {
+    let load_stats = LayoutLoadStats {
+        parse_ms: 10,
+        expand_ms: 20,
+        geometry_ms: 1,
+        node_count: 100,
+        pane_count: 12,
+    };
+    let combined = load_stats.combined_with(LayoutLoadStats {
+        parse_ms: 600,
+        node_count: 5,
+        pane_count: 1,
+        ..Default::default()
+    });
+    assert_eq!(
+        combined.to_string(),
+        "parse 610ms, expand 20ms, geometry 1ms (105 nodes, 13 panes)"
+    );
+    assert!(!load_stats.has_slow_stage());
+    assert!(combined.has_slow_stage());
+}
@@ -1,5 +1,11 @@
+use super::super::command::{PaneEncoding, ReadyCondition};
 use super::super::layout::*;
+use crate::kdl::LayoutTemplates;
+use crate::test_support::{
+    self, assert_run_instructions_match_positions, assert_tiles_exactly, viewport,
+};
 use insta::assert_snapshot;
+use std::str::FromStr;
 
 #[test]
 fn empty_layout() {
@@ -284,7 +290,14 @@ fn layout_with_tabs() {
     "#;
     let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
     let expected_layout = Layout {
-        tabs: vec![(None, TiledPaneLayout::default(), vec![])],
+        tabs: vec![(
+            None,
+            TiledPaneLayout::default(),
+            vec![],
+            None,
+            None,
+            (vec![], vec![]),
+        )],
         template: Some((TiledPaneLayout::default(), vec![])),
         ..Default::default()
     };
@@ -320,7 +333,10 @@ fn layout_with_nested_differing_tabs() {
                     ],
                     ..Default::default()
                 },
-                vec![], // floating panes
+                vec![],           // floating panes
+                None,             // pin
+                None,             // max_content_width
+                (vec![], vec![]), // swap layouts
             ),
             (
                 None,
@@ -329,7 +345,10 @@ fn layout_with_nested_differing_tabs() {
                     children: vec![TiledPaneLayout::default(), TiledPaneLayout::default()],
                     ..Default::default()
                 },
-                vec![], // floating panes
+                vec![],           // floating panes
+                None,             // pin
+                None,             // max_content_width
+                (vec![], vec![]), // swap layouts
             ),
         ],
         template: Some((TiledPaneLayout::default(), vec![])),
@@ -494,6 +513,441 @@ fn layout_with_command_panes_and_start_suspended() {
     assert_snapshot!(format!("{:#?}", layout));
 }
 
+#[test]
+fn layout_with_command_panes_and_strict_placeholders() {
+    let kdl_layout = r#"
+        layout {
+            pane name="api" command="just" {
+                args "dev" "--service" "{pane_name}"
+                strict_placeholders true
+            }
+            pane command="htop"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let run_instructions = tiled_panes.extract_run_instructions();
+    assert_eq!(
+        run_instructions,
+        vec![
+            Some(Run::Command(RunCommand {
+                command: PathBuf::from("just"),
+                args: vec!["dev".into(), "--service".into(), "{pane_name}".into()],
+                hold_on_close: true,
+                strict_placeholders: true,
+                ..Default::default()
+            })),
+            Some(Run::Command(RunCommand {
+                command: PathBuf::from("htop"),
+                hold_on_close: true,
+                ..Default::default()
+            })),
+        ],
+        "placeholders are only expanded when the panes are spawned"
+    );
+    assert_eq!(
+        tiled_panes.extract_pane_names(),
+        vec![Some("api".to_owned()), None]
+    );
+}
+
+#[test]
+fn strict_placeholders_from_pane_template() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="service" command="just" {
+                strict_placeholders true
+            }
+            service name="api" {
+                args "dev" "{pane_name}"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    match &tiled_panes.extract_run_instructions()[0] {
+        Some(Run::Command(run_command)) => assert!(run_command.strict_placeholders),
+        run => panic!("expected a command pane, got: {:?}", run),
+    }
+}
+
+#[test]
+fn strict_placeholders_without_command_is_an_error() {
+    let kdl_layout = r#"
+        layout {
+            pane strict_placeholders=true
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "strict_placeholders without a command");
+}
+
+#[test]
+fn layout_with_pane_depending_on_another_pane() {
+    let kdl_layout = r#"
+        layout {
+            pane name="db" command="postgres"
+            pane name="api" command="cargo" {
+                args "run"
+                depends_on "db"
+                ready_when "ready to accept connections"
+                ready_timeout 30
+            }
+            pane command="npm" depends_on="api" ready_regex="listening on :\\d+"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let dependencies: Vec<Option<PaneDependency>> = tiled_panes
+        .extract_run_instructions()
+        .into_iter()
+        .map(|run| match run {
+            Some(Run::Command(run_command)) => run_command.depends_on,
+            run => panic!("expected a command pane, got: {:?}", run),
+        })
+        .collect();
+    assert_eq!(
+        dependencies,
+        vec![
+            None,
+            Some(PaneDependency {
+                pane_name: "db".into(),
+                ready_when: Some(ReadyCondition::Contains(
+                    "ready to accept connections".into()
+                )),
+                timeout_secs: Some(30),
+            }),
+            Some(PaneDependency {
+                pane_name: "api".into(),
+                ready_when: Some(ReadyCondition::Matches("listening on :\\d+".into())),
+                timeout_secs: None,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn pane_dependency_from_pane_template() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="service" command="just" {
+                depends_on "db"
+            }
+            pane name="db" command="postgres"
+            service name="api"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    match &tiled_panes.extract_run_instructions()[1] {
+        Some(Run::Command(run_command)) => assert_eq!(
+            run_command
+                .depends_on
+                .as_ref()
+                .map(|d| d.pane_name.as_str()),
+            Some("db")
+        ),
+        run => panic!("expected a command pane, got: {:?}", run),
+    }
+}
+
+#[test]
+fn command_panes_can_have_an_on_failure_pane() {
+    let kdl_layout = r#"
+        layout {
+            pane command="tail" cwd="/projects/ci" {
+                args "-f" "build.log"
+                on_failure {
+                    pane command="notify-failure" {
+                        args "build"
+                    }
+                }
+            }
+            pane command="htop"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let failure_commands = tiled_panes.extract_failure_commands();
+    assert_eq!(
+        failure_commands,
+        vec![
+            Some(RunCommand {
+                command: PathBuf::from("notify-failure"),
+                args: vec!["build".into()],
+                cwd: Some(PathBuf::from("/projects/ci")),
+                hold_on_close: true,
+                ..Default::default()
+            }),
+            None,
+        ],
+        "the failure command runs in the cwd of the failed one"
+    );
+    let serialized = layout.to_kdl_string();
+    assert!(serialized.contains("on_failure"), "{}", serialized);
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn on_failure_pane_from_pane_template() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="watched" command="tail" {
+                on_failure {
+                    pane command="notify-failure"
+                }
+            }
+            watched
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    assert_eq!(
+        tiled_panes.extract_failure_commands()[0]
+            .as_ref()
+            .map(|failure_command| failure_command.command.clone()),
+        Some(PathBuf::from("notify-failure"))
+    );
+}
+
+#[test]
+fn invalid_on_failure_panes_are_an_error() {
+    let not_a_command_pane = r#"
+        layout {
+            pane {
+                on_failure {
+                    pane command="notify-failure"
+                }
+            }
+        }
+    "#;
+    let nested_on_failure = r#"
+        layout {
+            pane command="tail" {
+                on_failure {
+                    pane command="notify-failure" {
+                        on_failure {
+                            pane command="notify-again"
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+    let several_panes = r#"
+        layout {
+            pane command="tail" {
+                on_failure {
+                    pane command="notify-failure"
+                    pane command="notify-again"
+                }
+            }
+        }
+    "#;
+    let failure_pane_without_a_command = r#"
+        layout {
+            pane command="tail" {
+                on_failure {
+                    pane
+                }
+            }
+        }
+    "#;
+    for (kdl_layout, expected_error) in [
+        (
+            not_a_command_pane,
+            "can only be set if a command was specified",
+        ),
+        (nested_on_failure, "cannot be nested"),
+        (several_panes, "should hold a single pane"),
+        (failure_pane_without_a_command, "should run a command"),
+    ]
+    .iter()
+    {
+        match Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None) {
+            Err(ConfigError::KdlError(kdl_error)) => assert!(
+                kdl_error.error_message.contains(expected_error),
+                "unexpected error: {}",
+                kdl_error.error_message
+            ),
+            other => panic!("expected an error about on_failure, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn pane_dependency_cycle_is_an_error() {
+    let kdl_layout = r#"
+        layout {
+            pane name="db" command="postgres" depends_on="migrations"
+            pane name="api" command="cargo" depends_on="db"
+            floating_panes {
+                pane name="migrations" command="diesel" depends_on="api"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "dependency cycle");
+    let self_dependency = r#"
+        layout {
+            pane name="db" command="postgres" depends_on="db"
+        }
+    "#;
+    let layout = Layout::from_kdl_with_validation(
+        self_dependency,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::lenient(),
+        &LayoutTemplates::default(),
+    );
+    assert!(layout.is_err(), "cycles are an error even when lenient");
+}
+
+#[test]
+fn pane_dependency_graph_reports_the_cycle() {
+    let kdl_layout = r#"
+        layout {
+            pane name="db" command="postgres"
+            pane name="api" command="cargo" depends_on="db"
+            pane name="worker" command="cargo" depends_on="api"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (mut tiled_panes, floating_panes) = layout.new_tab();
+    let graph = PaneDependencyGraph::new(&tiled_panes, &floating_panes);
+    assert_eq!(graph.find_cycle(), None);
+    assert!(graph.unknown_dependencies().is_empty());
+    if let Some(Run::Command(run_command)) = tiled_panes.children[0].run.as_mut() {
+        run_command.depends_on = Some(PaneDependency {
+            pane_name: "worker".into(),
+            ready_when: None,
+            timeout_secs: None,
+        });
+    }
+    let graph = PaneDependencyGraph::new(&tiled_panes, &floating_panes);
+    assert_eq!(
+        graph.find_cycle(),
+        Some(vec![
+            "api".to_owned(),
+            "db".to_owned(),
+            "worker".to_owned(),
+            "api".to_owned()
+        ])
+    );
+}
+
+#[test]
+fn unknown_pane_dependency_is_a_warning() {
+    let kdl_layout = r#"
+        layout {
+            pane name="api" command="cargo" depends_on="db"
+        }
+    "#;
+    let (_layout, diagnostics) = Layout::from_kdl_with_validation(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::strict(),
+        &LayoutTemplates::default(),
+    )
+    .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "unknown-dependency");
+}
+
+#[test]
+fn layout_with_command_pane_encodings() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="legacy" command="ledger" encoding="latin1"
+            pane command="report" {
+                encoding "shift-jis"
+            }
+            legacy
+            legacy encoding="utf8"
+            floating_panes {
+                pane command="report" encoding="latin1"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.new_tab();
+    let encodings: Vec<Option<PaneEncoding>> = tiled_panes
+        .extract_run_instructions()
+        .into_iter()
+        .chain(floating_panes.into_iter().map(|pane| pane.run))
+        .map(|run| match run {
+            Some(Run::Command(run_command)) => run_command.encoding,
+            run => panic!("expected a command pane, got: {:?}", run),
+        })
+        .collect();
+    assert_eq!(
+        encodings,
+        vec![
+            Some(PaneEncoding::ShiftJis),
+            Some(PaneEncoding::Latin1),
+            Some(PaneEncoding::Utf8),
+            Some(PaneEncoding::Latin1),
+        ]
+    );
+}
+
+#[test]
+fn unknown_encoding_fails_parsing_with_span() {
+    let kdl_layout = r#"
+        layout {
+            pane command="report" encoding="ebcdic"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    match layout {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert_eq!(
+                kdl_error.error_message,
+                "encoding should be one of [\"utf8\", \"latin1\", \"shift-jis\"] found: ebcdic"
+            );
+            assert!(kdl_error.offset.is_some());
+        },
+        _ => panic!("expected a layout error, got: {:?}", layout),
+    }
+}
+
+#[test]
+fn encoding_without_command_is_an_error() {
+    let kdl_layout = r#"
+        layout {
+            pane encoding="latin1"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "encoding without a command");
+}
+
+#[test]
+fn invalid_pane_dependency_attributes_are_an_error() {
+    let layouts = [
+        r#"layout { pane name="db" command="postgres"; pane command="cargo" ready_when="up"; }"#,
+        r#"layout { pane name="db" command="postgres"; pane depends_on="db"; }"#,
+        r#"layout { pane name="db" command="postgres"; pane command="cargo" depends_on="db" ready_when="up" ready_regex="up"; }"#,
+        r#"layout { pane name="db" command="postgres"; pane command="cargo" depends_on="db" ready_regex="(up"; }"#,
+        r#"layout { pane name="db" command="postgres"; pane command="cargo" depends_on="db" ready_timeout=0; }"#,
+    ];
+    for kdl_layout in layouts {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_err(), "expected an error for: {}", kdl_layout);
+    }
+}
+
 #[test]
 fn layout_with_plugin_panes() {
     let kdl_layout = r#"
@@ -537,6 +991,75 @@ fn layout_with_plugin_panes() {
     assert_eq!(layout, expected_layout);
 }
 
+#[test]
+fn plugin_pane_template_consumed_without_overrides() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="tool" {
+                plugin location="file:/path/to/my/plugin.wasm" _allow_exec_host_cmd=true
+            }
+            tool
+            tool {
+                cwd "/tmp"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let expected_run = Some(Run::Plugin(RunPlugin {
+        location: RunPluginLocation::File(PathBuf::from("/path/to/my/plugin.wasm")),
+        _allow_exec_host_cmd: true,
+    }));
+    let tiled_panes = layout.new_tab().0;
+    assert_eq!(tiled_panes.children[0].run, expected_run);
+    // the cwd of the consumer leaves the absolute location of the plugin as it is
+    assert_eq!(tiled_panes.children[1].run, expected_run);
+}
+
+#[test]
+fn plugin_pane_template_consumed_with_overrides() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="tool" {
+                plugin location="file:/path/to/my/plugin.wasm" _allow_exec_host_cmd=true
+            }
+            tool {
+                plugin location="zellij:strider"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        layout.new_tab().0.children[0].run,
+        Some(Run::Plugin(RunPlugin {
+            location: RunPluginLocation::Zellij(PluginTag::new("strider")),
+            _allow_exec_host_cmd: true,
+        }))
+    );
+}
+
+#[test]
+fn cwd_is_joined_onto_relative_plugin_file_locations() {
+    let mut relative_file_plugin = Run::Plugin(RunPlugin {
+        location: RunPluginLocation::File(PathBuf::from("plugins/plugin.wasm")),
+        _allow_exec_host_cmd: false,
+    });
+    relative_file_plugin.add_cwd(&PathBuf::from("/tmp"));
+    assert_eq!(
+        relative_file_plugin,
+        Run::Plugin(RunPlugin {
+            location: RunPluginLocation::File(PathBuf::from("/tmp/plugins/plugin.wasm")),
+            _allow_exec_host_cmd: false,
+        })
+    );
+    let mut builtin_plugin = Run::Plugin(RunPlugin {
+        location: RunPluginLocation::Zellij(PluginTag::new("tab-bar")),
+        _allow_exec_host_cmd: false,
+    });
+    let expected_builtin_plugin = builtin_plugin.clone();
+    builtin_plugin.add_cwd(&PathBuf::from("/tmp"));
+    assert_eq!(builtin_plugin, expected_builtin_plugin);
+}
+
 #[test]
 fn layout_with_borderless_panes() {
     let kdl_layout = r#"
@@ -626,7 +1149,10 @@ fn layout_with_tab_names() {
                     children: vec![],
                     ..Default::default()
                 },
-                vec![], // floating panes
+                vec![],           // floating panes
+                None,             // pin
+                None,             // max_content_width
+                (vec![], vec![]), // swap layouts
             ),
             (
                 Some("my cool tab name 2".into()),
@@ -634,7 +1160,10 @@ fn layout_with_tab_names() {
                     children: vec![],
                     ..Default::default()
                 },
-                vec![], // floating panes
+                vec![],           // floating panes
+                None,             // pin
+                None,             // max_content_width
+                (vec![], vec![]), // swap layouts
             ),
         ],
         template: Some((TiledPaneLayout::default(), vec![])),
@@ -655,9 +1184,30 @@ fn layout_with_focused_tab() {
     let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
     let expected_layout = Layout {
         tabs: vec![
-            (None, TiledPaneLayout::default(), vec![]),
-            (None, TiledPaneLayout::default(), vec![]),
-            (None, TiledPaneLayout::default(), vec![]),
+            (
+                None,
+                TiledPaneLayout::default(),
+                vec![],
+                None,
+                None,
+                (vec![], vec![]),
+            ),
+            (
+                None,
+                TiledPaneLayout::default(),
+                vec![],
+                None,
+                None,
+                (vec![], vec![]),
+            ),
+            (
+                None,
+                TiledPaneLayout::default(),
+                vec![],
+                None,
+                None,
+                (vec![], vec![]),
+            ),
         ],
         template: Some((TiledPaneLayout::default(), vec![])),
         focused_tab_index: Some(1),
@@ -667,28 +1217,117 @@ fn layout_with_focused_tab() {
 }
 
 #[test]
-fn layout_with_tab_templates() {
+fn layout_with_pinned_tabs() {
     let kdl_layout = r#"
         layout {
-            tab_template name="one-above-one-below" {
+            tab name="scratch" pin="last"
+            tab pin="first"
+            tab
+            tab name="logs" pin="last" focus=true
+            tab pin="first"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let pins: Vec<Option<TabPin>> = layout.tabs().iter().map(|tab| tab.3).collect();
+    assert_eq!(
+        pins,
+        vec![
+            Some(TabPin::Last),
+            Some(TabPin::First),
+            None,
+            Some(TabPin::Last),
+            Some(TabPin::First),
+        ]
+    );
+    assert_eq!(layout.focused_tab_index(), Some(3));
+    assert_eq!(
+        layout.pinned_tab_position(3),
+        4,
+        "focused tab is found at its pinned position"
+    );
+    assert_eq!(layout.pinned_tab_position(0), 3);
+    assert_eq!(layout.pinned_tab_position(4), 1);
+}
+
+#[test]
+fn pinned_tab_with_template() {
+    let kdl_layout = r#"
+        layout {
+            tab_template name="with-bar" {
                 pane
                 children
-                pane
-            }
-            one-above-one-below name="my first tab" split_direction="Vertical" {
-                pane
-                pane
-            }
-            one-above-one-below name="my second tab" {
-                pane
-                pane
             }
-            one-above-one-below
+            with-bar name="scratch" pin="first"
         }
     "#;
     let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
-    let expected_layout = Layout {
-        tabs: vec![
+    assert_eq!(layout.tabs()[0].3, Some(TabPin::First));
+}
+
+#[test]
+fn invalid_tab_pin_is_an_error() {
+    let kdl_layout = r#"
+        layout {
+            tab pin="middle"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "invalid tab pin");
+}
+
+#[test]
+fn layout_with_max_content_width_tabs() {
+    let kdl_layout = r#"
+        layout {
+            tab_template name="with-bar" {
+                pane
+                children
+            }
+            tab name="write" max_content_width=100
+            tab
+            with-bar name="notes" max_content_width=80
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let max_content_widths: Vec<Option<usize>> = layout.tabs().iter().map(|tab| tab.4).collect();
+    assert_eq!(max_content_widths, vec![Some(100), None, Some(80)]);
+}
+
+#[test]
+fn invalid_max_content_width_is_an_error() {
+    for max_content_width in ["0", "\"wide\""] {
+        let kdl_layout = format!(
+            "layout {{ tab max_content_width={} {{ pane; }}; }}",
+            max_content_width
+        );
+        let layout = Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_err(), "{}", max_content_width);
+    }
+}
+
+#[test]
+fn layout_with_tab_templates() {
+    let kdl_layout = r#"
+        layout {
+            tab_template name="one-above-one-below" {
+                pane
+                children
+                pane
+            }
+            one-above-one-below name="my first tab" split_direction="Vertical" {
+                pane
+                pane
+            }
+            one-above-one-below name="my second tab" {
+                pane
+                pane
+            }
+            one-above-one-below
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let expected_layout = Layout {
+        tabs: vec![
             (
                 Some("my first tab".into()),
                 TiledPaneLayout {
@@ -704,7 +1343,10 @@ fn layout_with_tab_templates() {
                     ],
                     ..Default::default()
                 },
-                vec![], // floating panes
+                vec![],           // floating panes
+                None,             // pin
+                None,             // max_content_width
+                (vec![], vec![]), // swap layouts
             ),
             (
                 Some("my second tab".into()),
@@ -721,7 +1363,10 @@ fn layout_with_tab_templates() {
                     ],
                     ..Default::default()
                 },
-                vec![], // floating panes
+                vec![],           // floating panes
+                None,             // pin
+                None,             // max_content_width
+                (vec![], vec![]), // swap layouts
             ),
             (
                 None,
@@ -734,10 +1379,24 @@ fn layout_with_tab_templates() {
                     ],
                     ..Default::default()
                 },
-                vec![], // floating panes
+                vec![],           // floating panes
+                None,             // pin
+                None,             // max_content_width
+                (vec![], vec![]), // swap layouts
             ),
         ],
         template: Some((TiledPaneLayout::default(), vec![])),
+        tab_templates: BTreeMap::from([(
+            "one-above-one-below".to_string(),
+            (
+                TiledPaneLayout {
+                    children_split_direction: SplitDirection::Horizontal,
+                    children: vec![TiledPaneLayout::default(); 3],
+                    ..Default::default()
+                },
+                vec![],
+            ),
+        )]),
         ..Default::default()
     };
     assert_eq!(layout, expected_layout);
@@ -1269,6 +1928,65 @@ fn error_on_more_than_one_focused_tab() {
     assert_snapshot!(format!("{:?}", layout_error));
 }
 
+#[test]
+fn tabs_can_be_focused_by_name() {
+    let kdl_layout = r#"
+        layout {
+            focused_tab "logs"
+            tab name="code"
+            tab name="logs"
+            tab name="shell"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.focused_tab_index(), Some(1));
+    assert_eq!(layout.focused_tab_name.as_deref(), Some("logs"));
+    let serialized = layout.to_kdl_string();
+    assert!(
+        serialized.contains("focused_tab \"logs\""),
+        "{}",
+        serialized
+    );
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(reparsed.focused_tab_index(), Some(1));
+    assert_eq!(reparsed.focused_tab_name.as_deref(), Some("logs"));
+}
+
+#[test]
+fn tabs_focused_by_name_are_focused_by_position_once_renamed() {
+    let kdl_layout = r#"
+        layout {
+            focused_tab "logs"
+            tab name="code"
+            tab name="logs"
+        }
+    "#;
+    let mut layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    layout.tabs[1].0 = Some("server logs".into());
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(reparsed.focused_tab_index(), Some(1), "{}", serialized);
+    assert_eq!(reparsed.focused_tab_name, None);
+}
+
+#[test]
+fn error_on_focusing_tabs_by_a_name_that_does_not_name_a_single_tab() {
+    let invalid_layouts = [
+        // no tab with this name
+        r#"layout { focused_tab "logs"; tab name="code"; }"#,
+        r#"layout { focused_tab "logs"; pane; }"#,
+        // more than one tab with this name
+        r#"layout { focused_tab "logs"; tab name="logs"; tab name="logs"; }"#,
+        // two tabs claim focus
+        r#"layout { focused_tab "logs"; tab name="code" focus=true; tab name="logs"; }"#,
+        r#"layout { focused_tab "logs"; tab name="logs" focus=true; }"#,
+    ];
+    for kdl_layout in invalid_layouts {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_err(), "expected an error for: {}", kdl_layout);
+    }
+}
+
 #[test]
 fn args_override_args_in_template() {
     let kdl_layout = r#"
@@ -1630,6 +2348,137 @@ fn pane_template_with_command_propagated_to_its_consumer_edit() {
     assert_snapshot!(format!("{:#?}", layout));
 }
 
+#[test]
+fn edit_panes_can_open_several_files_as_editor_splits() {
+    let kdl_layout = r#"
+        layout {
+            cwd "/project"
+            pane {
+                edit "a.rs" "b.rs" "/tmp/c.rs" editor_splits="vertical"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        layout.template.unwrap().0.children[0].run,
+        Some(Run::EditFile(
+            vec![
+                PathBuf::from("/project/a.rs"),
+                PathBuf::from("/project/b.rs"),
+                PathBuf::from("/tmp/c.rs"),
+            ],
+            None,
+            Some(SplitDirection::Vertical)
+        ))
+    );
+    let kdl_layout = r#"
+        layout {
+            pane {
+                edit "a.rs" editor_splits="diagonal"
+            }
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_err());
+}
+
+#[test]
+fn environment_variables_are_expanded_in_cwds_commands_args_and_edited_files() {
+    std::env::set_var("ZELLIJ_LAYOUT_TEST_PROJECT", "/projects/zellij");
+    let kdl_layout = r#"
+        layout {
+            tab cwd="${ZELLIJ_LAYOUT_TEST_PROJECT}" {
+                pane command="$ZELLIJ_LAYOUT_TEST_PROJECT/run.sh" {
+                    args "--price=$$5" "$ZELLIJ_LAYOUT_TEST_PROJECT"
+                }
+                pane edit="$ZELLIJ_LAYOUT_TEST_PROJECT/Cargo.toml"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tab_layout = &layout.tabs[0].1;
+    assert_eq!(
+        tab_layout.children[0].run,
+        Some(Run::Command(RunCommand {
+            command: PathBuf::from("/projects/zellij/run.sh"),
+            args: vec!["--price=$5".into(), "/projects/zellij".into()],
+            cwd: Some(PathBuf::from("/projects/zellij")),
+            hold_on_close: true,
+            ..Default::default()
+        }))
+    );
+    assert_eq!(
+        tab_layout.children[1].run,
+        Some(Run::EditFile(
+            vec![PathBuf::from("/projects/zellij/Cargo.toml")],
+            None,
+            None
+        ))
+    );
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn cwds_starting_with_a_tilde_are_in_the_home_directory() {
+    let kdl_layout = r#"
+        layout {
+            pane cwd="~/projects"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let home_dir = directories_next::BaseDirs::new()
+        .unwrap()
+        .home_dir()
+        .to_path_buf();
+    assert_eq!(
+        layout.template.unwrap().0.children[0].run,
+        Some(Run::Cwd(home_dir.join("projects")))
+    );
+}
+
+#[test]
+fn unset_environment_variables_in_layouts_are_an_error_naming_them() {
+    let kdl_layout = r#"
+        layout {
+            pane cwd="/projects/$ZELLIJ_LAYOUT_TEST_UNSET"
+        }
+    "#;
+    let error = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap_err();
+    let error_message = included_layout_error_message(error);
+    assert!(
+        error_message.contains("ZELLIJ_LAYOUT_TEST_UNSET") && error_message.contains("cwd"),
+        "{}",
+        error_message
+    );
+}
+
+#[test]
+fn pane_template_edit_files_are_replaced_by_the_ones_of_its_consumer() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="review" {
+                edit "a.rs" "b.rs" editor_splits="horizontal"
+            }
+            review {
+                edit "c.rs"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        layout.template.unwrap().0.children[0].run,
+        Some(Run::EditFile(vec![PathBuf::from("c.rs")], None, None))
+    );
+}
+
 #[test]
 fn global_cwd_given_to_panes_without_cwd() {
     let kdl_layout = r#"
@@ -1843,9 +2692,6577 @@ fn can_load_swap_layouts_from_a_different_file() {
     let layout = Layout::from_kdl(
         kdl_layout,
         "layout_file_name".into(),
-        Some(("swap_layout_file_name".into(), kdl_swap_layout)),
+        Some(("swap_layout_file_name", kdl_swap_layout)),
         None,
     )
     .unwrap();
     assert_snapshot!(format!("{:#?}", layout));
 }
+
+#[test]
+fn validate_reports_no_diagnostics_for_a_valid_layout() {
+    let kdl_layout = r#"
+        layout {
+            pane size="30%"
+            pane size="70%" focus=true
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.validate(ValidationOptions::strict()), vec![]);
+}
+
+#[test]
+fn zero_sized_tiled_pane_is_an_error() {
+    let tiled_panes = TiledPaneLayout {
+        children: vec![
+            TiledPaneLayout {
+                split_size: Some(SplitSize::Fixed(0)),
+                ..Default::default()
+            },
+            TiledPaneLayout::default(),
+        ],
+        ..Default::default()
+    };
+    let diagnostics = check_zero_sizes(&tiled_panes, &[], &LayoutLocation::Tab(0));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "zero-size");
+    assert!(diagnostics[0].is_error());
+}
+
+#[test]
+fn zero_sized_floating_pane_is_an_error() {
+    let floating_panes = vec![FloatingPaneLayout {
+        width: Some(PercentOrFixed::Percent(0)),
+        ..Default::default()
+    }];
+    let diagnostics = check_zero_sizes(
+        &TiledPaneLayout::default(),
+        &floating_panes,
+        &LayoutLocation::Tab(0),
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "zero-size");
+}
+
+#[test]
+fn zero_sized_pane_fails_parsing_with_span() {
+    let kdl_layout = r#"
+        layout {
+            pane size="0"
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    match layout {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert_eq!(
+                kdl_error.error_message,
+                "Pane size should be greater than 0"
+            );
+            assert!(kdl_error.offset.is_some());
+        },
+        _ => panic!("expected a layout error, got: {:?}", layout),
+    }
+}
+
+#[test]
+fn sibling_percent_sizes_over_100_fail_parsing_at_the_pane_going_over() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size="70%"
+                pane size="60%"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    match layout {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert_eq!(
+                kdl_error.error_message,
+                "The sizes of sibling panes add up to 130%, which is more than 100%"
+            );
+            // pointing at the second pane, whether or not its span has its leading whitespace
+            let offset = kdl_error.offset.unwrap();
+            assert!(offset > kdl_layout.find("70%").unwrap());
+            assert!(offset <= kdl_layout.find("pane size=\"60%\"").unwrap());
+        },
+        _ => panic!("expected a layout error, got: {:?}", layout),
+    }
+    let in_a_tab = r#"
+        layout {
+            tab {
+                pane size="50%"
+                pane size="51%"
+            }
+        }
+    "#;
+    assert!(Layout::from_kdl(in_a_tab, "layout_file_name".into(), None, None).is_err());
+}
+
+#[test]
+fn sibling_percent_sizes_of_100_leave_no_room_for_panes_without_a_size() {
+    for kdl_layout in [
+        "layout { pane size=\"40%\"; pane size=\"60%\"; pane; }",
+        "layout { pane size=\"100%\"; pane size=\"2w\"; }",
+    ] {
+        match Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None) {
+            Err(ConfigError::KdlError(kdl_error)) => {
+                assert!(kdl_error.offset.is_some(), "{}", kdl_layout)
+            },
+            layout => panic!("expected a layout error, got: {:?}", layout),
+        }
+    }
+}
+
+#[test]
+fn panes_without_a_size_share_the_percent_their_siblings_leave() {
+    for kdl_layout in [
+        "layout { pane size=\"40%\"; pane size=\"60%\"; }",
+        "layout { pane size=\"60%\"; pane; pane; }",
+        "layout { pane size=\"60%\"; pane size=\"2w\"; pane; }",
+        "layout { pane size=\"100%\"; pane size=10; }",
+    ] {
+        assert!(
+            Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_ok(),
+            "{}",
+            kdl_layout
+        );
+    }
+    let implicit_sizes =
+        "layout { pane split_direction=\"vertical\" { pane size=\"60%\"; pane; pane; }; }";
+    assert_eq!(vertical_split_widths(implicit_sizes, 100), vec![60, 20, 20]);
+}
+
+#[test]
+fn sibling_percent_sizes_over_100_produce_a_warning() {
+    let tiled_panes = TiledPaneLayout {
+        children: vec![
+            TiledPaneLayout {
+                split_size: Some(SplitSize::Percent(60)),
+                ..Default::default()
+            },
+            TiledPaneLayout {
+                split_size: Some(SplitSize::Percent(60)),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let diagnostics = check_percent_sums(&tiled_panes, &LayoutLocation::Tab(0));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "percent-overflow");
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+}
+
+#[test]
+fn multiple_focused_panes_produce_a_warning() {
+    let tiled_panes = TiledPaneLayout {
+        children: vec![
+            TiledPaneLayout {
+                focus: Some(true),
+                ..Default::default()
+            },
+            TiledPaneLayout::default(),
+        ],
+        ..Default::default()
+    };
+    let floating_panes = vec![FloatingPaneLayout {
+        focus: Some(true),
+        ..Default::default()
+    }];
+    let diagnostics = check_multiple_focus(&tiled_panes, &floating_panes, &LayoutLocation::Tab(0));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "multiple-focus");
+}
+
+#[test]
+fn focused_tab_index_out_of_range_is_an_error() {
+    let layout = Layout {
+        tabs: vec![(
+            None,
+            TiledPaneLayout::default(),
+            vec![],
+            None,
+            None,
+            (vec![], vec![]),
+        )],
+        focused_tab_index: Some(1),
+        ..Default::default()
+    };
+    let diagnostics = check_focused_tab_index(&layout);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].is_error());
+}
+
+#[test]
+fn swap_constraints_shadowed_by_previous_ones_are_unreachable() {
+    let constraints = vec![
+        LayoutConstraint::MaxPanes(3),
+        LayoutConstraint::MaxPanes(5),
+        LayoutConstraint::MinPanes(4),
+        LayoutConstraint::MinPanes(8),
+        LayoutConstraint::NoConstraint,
+    ];
+    assert_eq!(
+        unreachable_swap_constraints(constraints.iter()),
+        vec![
+            LayoutConstraint::MinPanes(8),
+            LayoutConstraint::NoConstraint
+        ]
+    );
+    let constraints = vec![LayoutConstraint::MaxPanes(3), LayoutConstraint::MinPanes(6)];
+    assert_eq!(unreachable_swap_constraints(constraints.iter()), vec![]);
+    let constraints = vec![
+        LayoutConstraint::MinPanes(6),
+        LayoutConstraint::NoConstraint,
+    ];
+    assert_eq!(unreachable_swap_constraints(constraints.iter()), vec![]);
+    let constraints = vec![
+        LayoutConstraint::ExactPanes(1),
+        LayoutConstraint::ExactPanes(2),
+        LayoutConstraint::ExactPanes(4),
+        LayoutConstraint::MaxPanes(2),
+        LayoutConstraint::MaxPanes(3),
+        LayoutConstraint::MinPanes(4),
+    ];
+    assert_eq!(
+        unreachable_swap_constraints(constraints.iter()),
+        vec![LayoutConstraint::MaxPanes(2)]
+    );
+    let constraints = vec![
+        LayoutConstraint::PaneRange { min: 1, max: 3 },
+        LayoutConstraint::PaneRange { min: 5, max: 6 },
+        LayoutConstraint::MaxPanes(3),
+        LayoutConstraint::MaxPanes(4),
+        LayoutConstraint::MinPanes(5),
+    ];
+    assert_eq!(
+        unreachable_swap_constraints(constraints.iter()),
+        vec![LayoutConstraint::MaxPanes(3)]
+    );
+}
+
+#[test]
+fn swap_layouts_can_have_tags() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout name="review" {
+                tags "review" "wide"
+                tab { pane; pane; }
+            }
+            swap_tiled_layout name="untagged" {
+                tab { pane; pane; }
+            }
+            swap_floating_layout {
+                tags "review"
+                floating_panes { pane; }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_tags: Vec<&Vec<String>> = layout
+        .swap_tiled_layouts
+        .iter()
+        .map(|(_layouts, _name, tags)| tags)
+        .collect();
+    assert_eq!(
+        tiled_tags,
+        vec![
+            &vec!["review".to_owned(), "wide".to_owned()],
+            &Vec::<String>::new()
+        ]
+    );
+    assert_eq!(layout.swap_floating_layouts[0].2, vec!["review".to_owned()]);
+}
+
+#[test]
+fn swap_layout_tags_cannot_be_empty() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tags
+                tab { pane; pane; }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "error provided for empty tags");
+}
+
+fn selected_swap_entry_name(kdl_swap_entries: &str, counts: PaneCounts) -> Option<String> {
+    selected_swap_entry_name_in_session(kdl_swap_entries, counts, SessionContext::default())
+}
+
+fn selected_swap_entry_name_in_session(
+    kdl_swap_entries: &str,
+    counts: PaneCounts,
+    session_context: SessionContext,
+) -> Option<String> {
+    let kdl_layout = format!("layout {{ swap_tiled_layout {{ {} }}; }}", kdl_swap_entries);
+    let layout = Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    select_swap_layout_entry(
+        &layout.swap_tiled_layouts[0].0,
+        counts,
+        session_context,
+        |_| true,
+    )
+    .and_then(|entry| entry.children.first().and_then(|pane| pane.name.clone()))
+}
+
+#[test]
+fn swap_entries_can_be_selected_by_the_number_of_tabs_in_the_session() {
+    let entries = r#"
+        tab max_panes=2 { pane name="side-by-side"; }
+        tab max_panes=2 session_tabs_max=1 { pane name="zen"; }
+        tab session_tabs_min=3 { pane name="busy"; }
+        tab { pane name="grid"; }
+    "#;
+    let selected = |panes: usize, tab_count: usize| {
+        let counts = PaneCounts {
+            panes,
+            groups: panes,
+        };
+        let session_context = SessionContext {
+            tab_count,
+            focused_tab_index: Some(0),
+        };
+        selected_swap_entry_name_in_session(entries, counts, session_context)
+    };
+    assert_eq!(selected(2, 1), Some("zen".into()), "both constraints match");
+    assert_eq!(
+        selected(4, 1),
+        Some("grid".into()),
+        "too many panes for zen"
+    );
+    assert_eq!(selected(2, 2), Some("side-by-side".into()));
+    assert_eq!(selected(2, 3), Some("busy".into()));
+    assert_eq!(selected(4, 2), Some("grid".into()));
+}
+
+#[test]
+fn exact_pane_constraints_are_tried_before_min_and_max_ones() {
+    let mut constraints = vec![
+        LayoutConstraint::NoConstraint,
+        LayoutConstraint::MinPanes(2),
+        LayoutConstraint::MaxPanes(4),
+        LayoutConstraint::ExactPanes(4),
+        LayoutConstraint::ExactPanes(3),
+    ];
+    constraints.sort();
+    assert_eq!(
+        constraints,
+        vec![
+            LayoutConstraint::ExactPanes(3),
+            LayoutConstraint::ExactPanes(4),
+            LayoutConstraint::MaxPanes(4),
+            LayoutConstraint::MinPanes(2),
+            LayoutConstraint::NoConstraint,
+        ]
+    );
+}
+
+#[test]
+fn exact_pane_counts_win_over_min_and_max_ones() {
+    let entries = r#"
+        tab min_panes=2 { pane name="many"; }
+        tab max_panes=4 { pane name="few"; }
+        tab exact_panes=3 { pane name="three"; }
+        tab exact_panes=4 { pane name="four"; }
+    "#;
+    let selected = |panes: usize| {
+        let counts = PaneCounts {
+            panes,
+            groups: panes,
+        };
+        selected_swap_entry_name(entries, counts)
+    };
+    assert_eq!(selected(2), Some("few".into()), "no exact match");
+    assert_eq!(
+        selected(3),
+        Some("three".into()),
+        "all three constraints match"
+    );
+    assert_eq!(
+        selected(4),
+        Some("four".into()),
+        "all three constraints match"
+    );
+    assert_eq!(selected(5), Some("many".into()), "no exact match");
+}
+
+#[test]
+fn exact_pane_constraints_can_be_child_nodes() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab {
+                    exact_panes 3
+                    pane
+                    pane
+                    pane
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let constraints: Vec<&LayoutConstraint> = layout.swap_tiled_layouts[0].0.keys().collect();
+    assert_eq!(constraints, vec![&LayoutConstraint::ExactPanes(3)]);
+}
+
+#[test]
+fn swap_entries_can_have_a_range_of_pane_counts() {
+    let entries = r#"
+        tab exact_panes=5 { pane name="five"; }
+        tab min_panes=4 max_panes=6 { pane name="four-to-six"; }
+        tab { pane name="any"; }
+    "#;
+    let selected = |panes: usize| {
+        let counts = PaneCounts {
+            panes,
+            groups: panes,
+        };
+        selected_swap_entry_name(entries, counts)
+    };
+    assert_eq!(selected(3), Some("any".into()));
+    assert_eq!(selected(4), Some("four-to-six".into()));
+    assert_eq!(selected(5), Some("five".into()), "exact matches win");
+    assert_eq!(selected(6), Some("four-to-six".into()));
+    assert_eq!(selected(7), Some("any".into()));
+}
+
+#[test]
+fn pane_ranges_can_be_child_nodes() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab {
+                    min_panes 4
+                    max_panes 6
+                    pane
+                    pane { children; }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let constraints: Vec<&LayoutConstraint> = layout.swap_tiled_layouts[0].0.keys().collect();
+    assert_eq!(
+        constraints,
+        vec![&LayoutConstraint::PaneRange { min: 4, max: 6 }]
+    );
+    assert_eq!(
+        layout.swap_tiled_layouts[0].1,
+        Some("min_panes=4,max_panes=6 #1".into())
+    );
+}
+
+#[test]
+fn pane_ranges_cannot_end_before_they_start() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab min_panes=6 max_panes=4 { pane; }
+            }
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_err());
+}
+
+#[test]
+fn overlapping_pane_ranges_fail_parsing_with_both_spans() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab min_panes=2 max_panes=4 { pane; }
+                tab min_panes=4 max_panes=6 { pane; }
+            }
+        }
+    "#;
+    match Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None) {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert!(
+                kdl_error.error_message.contains("overlaps"),
+                "{}",
+                kdl_error.error_message
+            );
+            // node spans start where the whitespace before them does
+            let first_range = kdl_layout.find("tab min_panes=2").unwrap();
+            let second_range = kdl_layout.find("tab min_panes=4").unwrap();
+            let offset = kdl_error.offset.unwrap();
+            assert!(offset > first_range && offset <= second_range);
+            assert_eq!(kdl_error.secondary_labels.len(), 1);
+            assert!(kdl_error.secondary_labels[0].1 <= first_range);
+        },
+        layout => panic!("expected a layout error, got: {:?}", layout),
+    }
+}
+
+#[test]
+fn pane_ranges_only_overlap_in_the_same_swap_layout_and_session() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab min_panes=2 max_panes=4 { pane; }
+                tab min_panes=5 max_panes=6 { pane; }
+                tab min_panes=3 max_panes=5 session_tabs_min=2 { pane; }
+            }
+            swap_tiled_layout {
+                tab min_panes=3 max_panes=5 { pane; }
+            }
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_ok());
+}
+
+#[test]
+fn swap_entries_cannot_have_both_exact_and_max_pane_constraints() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab exact_panes=3 max_panes=4 { pane; pane; pane; }
+            }
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_err());
+}
+
+#[test]
+fn swap_entries_cannot_have_two_session_constraints() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab session_tabs_min=2 session_tabs_max=3 { pane; pane; }
+            }
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_err());
+}
+
+#[test]
+fn session_constraints_of_swap_entries_are_serialized() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab max_panes=2 session_tabs_max=1 { pane; pane; }
+                tab session_tabs_min=2 { pane; pane; children; }
+            }
+            swap_floating_layout {
+                floating_panes session_tabs_max=1 { pane; }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let constraints: Vec<String> = layout.swap_tiled_layouts[0]
+        .0
+        .keys()
+        .map(|constraint| constraint.to_string())
+        .collect();
+    assert_eq!(
+        constraints,
+        vec!["session_tabs_max 1, max_panes 2", "session_tabs_min 2"]
+    );
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn tabs_can_have_their_own_swap_layouts() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout name="everywhere" {
+                tab max_panes=2 { pane; pane; }
+            }
+            swap_floating_layout name="corners" {
+                floating_panes { pane; }
+            }
+            tab name="review" {
+                pane
+                swap_tiled_layout name="side-by-side" {
+                    tab max_panes=2 { pane split_direction="vertical" { pane; pane; }; }
+                }
+            }
+            tab name="logs" {
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let swap_layout_names_of_tab = |tab_index| {
+        let (swap_tiled_layouts, swap_floating_layouts) = layout.swap_layouts_of_tab(tab_index);
+        let swap_tiled_layout_names: Vec<String> = swap_tiled_layouts
+            .into_iter()
+            .filter_map(|(_entries, name, _tags)| name)
+            .collect();
+        let swap_floating_layout_names: Vec<String> = swap_floating_layouts
+            .into_iter()
+            .filter_map(|(_entries, name, _tags)| name)
+            .collect();
+        (swap_tiled_layout_names, swap_floating_layout_names)
+    };
+    assert_eq!(
+        swap_layout_names_of_tab(0),
+        (vec!["side-by-side".to_owned()], vec!["corners".to_owned()]),
+        "the swap layouts of the tab come first, the others are taken from the layout"
+    );
+    assert_eq!(
+        swap_layout_names_of_tab(1),
+        (vec!["everywhere".to_owned()], vec!["corners".to_owned()])
+    );
+    assert_eq!(layout.swap_tiled_layouts.len(), 1);
+    assert_eq!(
+        layout.tabs()[0].1.children,
+        vec![TiledPaneLayout::default()],
+        "swap layouts are not panes of the tab"
+    );
+}
+
+#[test]
+fn tab_swap_layouts_are_serialized_inside_their_tab() {
+    let kdl_layout = r#"
+        layout {
+            default_tab_template {
+                pane size=1 borderless=true { plugin location="zellij:tab-bar"; }
+                children
+            }
+            tab name="review" {
+                pane
+                swap_tiled_layout name="side-by-side" {
+                    tab max_panes=2 { pane split_direction="vertical" { pane; pane; }; }
+                }
+                swap_floating_layout {
+                    floating_panes { pane x=1; }
+                }
+            }
+            tab name="logs"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn tab_swap_layouts_cannot_refer_to_the_base_layout() {
+    let kdl_layout = r#"
+        layout {
+            tab {
+                pane name="editor"
+                pane
+                swap_tiled_layout {
+                    tab { pane ref="editor" size="+10%"; }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "pane refs in the swap layouts of a tab");
+}
+
+#[test]
+fn swap_entries_can_be_selected_by_the_number_of_stacked_groups() {
+    let group_entries = r#"
+        tab max_groups=2 { pane name="side-by-side"; }
+        tab min_panes=6 { pane name="grid"; }
+    "#;
+    let pane_entries = r#"
+        tab max_panes=2 { pane name="side-by-side"; }
+        tab min_panes=6 { pane name="grid"; }
+    "#;
+    // 8 panes, progressively collapsed into fewer stacks
+    let mut selected_with_groups = vec![];
+    let mut selected_with_panes = vec![];
+    for groups in [8, 5, 3, 2, 1] {
+        let counts = PaneCounts { panes: 8, groups };
+        selected_with_groups.push(selected_swap_entry_name(group_entries, counts));
+        selected_with_panes.push(selected_swap_entry_name(pane_entries, counts));
+    }
+    let grid = Some(String::from("grid"));
+    let side_by_side = Some(String::from("side-by-side"));
+    assert_eq!(
+        selected_with_groups,
+        vec![
+            grid.clone(),
+            grid.clone(),
+            grid.clone(),
+            side_by_side.clone(),
+            side_by_side
+        ]
+    );
+    assert_eq!(
+        selected_with_panes,
+        vec![grid.clone(), grid.clone(), grid.clone(), grid.clone(), grid],
+        "pane count constraints ignore stacking"
+    );
+}
+
+#[test]
+fn swap_entries_cannot_have_both_group_and_pane_constraints() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab min_panes=2 max_groups=3 { pane; pane; }
+            }
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_err());
+}
+
+fn swap_layout_names(kdl_layout: &str) -> (Vec<Option<String>>, Vec<Option<String>>) {
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    (
+        layout
+            .swap_tiled_layouts
+            .iter()
+            .map(|(_entries, name, _tags)| name.clone())
+            .collect(),
+        layout
+            .swap_floating_layouts
+            .iter()
+            .map(|(_entries, name, _tags)| name.clone())
+            .collect(),
+    )
+}
+
+#[test]
+fn named_swap_layouts_keep_their_names() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout name="wide-editor" {
+                tab max_panes=4 { pane; pane; }
+            }
+            swap_floating_layout name="corners" {
+                floating_panes { pane; }
+            }
+        }
+    "#;
+    assert_eq!(
+        swap_layout_names(kdl_layout),
+        (
+            vec![Some("wide-editor".to_owned())],
+            vec![Some("corners".to_owned())]
+        )
+    );
+}
+
+#[test]
+fn unnamed_swap_layouts_are_named_after_their_constraints_and_position() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab max_panes=4 { pane; pane; }
+            }
+            swap_tiled_layout {
+                tab max_panes=2 { pane; pane; }
+                tab min_panes=5 { pane; pane; }
+            }
+            swap_tiled_layout {
+                tab { pane; pane; }
+            }
+            swap_floating_layout {
+                floating_panes max_panes=1 { pane; }
+            }
+        }
+    "#;
+    assert_eq!(
+        swap_layout_names(kdl_layout),
+        (
+            vec![
+                Some("max_panes=4 #1".to_owned()),
+                Some("max_panes=2,min_panes=5 #2".to_owned()),
+                Some("#3".to_owned()),
+            ],
+            vec![Some("max_panes=1 #1".to_owned())]
+        )
+    );
+}
+
+#[test]
+fn only_the_unnamed_swap_layouts_of_a_mixed_set_are_named() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout name="wide-editor" {
+                tab max_panes=4 { pane; pane; }
+            }
+            swap_tiled_layout {
+                tab max_panes=4 { pane; pane; }
+            }
+            swap_tiled_layout name="tall" {
+                tab min_groups=3 { pane; pane; }
+            }
+            swap_tiled_layout {
+                tab min_groups=3 { pane; pane; }
+            }
+        }
+    "#;
+    assert_eq!(
+        swap_layout_names(kdl_layout).0,
+        vec![
+            Some("wide-editor".to_owned()),
+            Some("max_panes=4 #2".to_owned()),
+            Some("tall".to_owned()),
+            Some("min_groups=3 #4".to_owned()),
+        ],
+        "similar unnamed swap layouts are told apart by their position"
+    );
+}
+
+#[test]
+fn swap_layout_labels_have_the_position_among_all_swap_layouts() {
+    assert_eq!(swap_layout_label(0, 1, "BASE"), "1/1: BASE");
+    assert_eq!(swap_layout_label(2, 5, "wide-editor"), "3/5: wide-editor");
+    assert_eq!(
+        swap_layout_label(4, 5, "max_panes=4 #4"),
+        "5/5: max_panes=4 #4"
+    );
+}
+
+#[test]
+fn unbounded_swap_layout_without_children_produces_a_warning() {
+    let tiled_panes = TiledPaneLayout {
+        children: vec![TiledPaneLayout::default(), TiledPaneLayout::default()],
+        ..Default::default()
+    };
+    let location = LayoutLocation::SwapTiledLayout(0, LayoutConstraint::MinPanes(2));
+    let diagnostics =
+        check_missing_children_slot(&LayoutConstraint::MinPanes(2), &tiled_panes, &location);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "missing-children-slot");
+    let diagnostics =
+        check_missing_children_slot(&LayoutConstraint::MaxPanes(2), &tiled_panes, &location);
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn validate_attaches_spans_to_swap_layout_diagnostics() {
+    let kdl_layout = r#"
+        layout {
+            pane
+            swap_tiled_layout {
+                tab min_panes=2 {
+                    pane
+                    pane
+                }
+            }
+        }
+    "#;
+    let (_layout, diagnostics) = Layout::from_kdl_with_validation(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::strict(),
+        &LayoutTemplates::default(),
+    )
+    .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "missing-children-slot");
+    let span = diagnostics[0].span.expect("span not recorded");
+    assert!(kdl_layout[span.offset..]
+        .trim_start()
+        .starts_with("tab min_panes=2"));
+}
+
+#[test]
+fn lenient_validation_downgrades_errors_to_warnings() {
+    let layout = Layout {
+        tabs: vec![(
+            None,
+            TiledPaneLayout::default(),
+            vec![],
+            None,
+            None,
+            (vec![], vec![]),
+        )],
+        focused_tab_index: Some(3),
+        ..Default::default()
+    };
+    let diagnostics = layout.validate(ValidationOptions::lenient());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+}
+
+#[test]
+fn hide_title_is_parsed_on_tiled_and_floating_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane name="secret" hide_title=true command="deploy" {
+                args "--token" "abc"
+            }
+            pane
+            floating_panes {
+                pane hide_title=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.template.unwrap();
+    assert!(tiled_panes.children[0].hide_title);
+    assert_eq!(tiled_panes.children[0].name, Some("secret".into()));
+    assert!(!tiled_panes.children[1].hide_title);
+    assert!(floating_panes[0].hide_title);
+}
+
+#[test]
+fn hide_title_is_inherited_from_pane_templates() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="secret" hide_title=true
+            secret
+            secret hide_title=false
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.template.unwrap();
+    assert!(tiled_panes.children[0].hide_title);
+    assert!(!tiled_panes.children[1].hide_title);
+}
+
+#[test]
+fn read_only_is_parsed_on_tiled_and_floating_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane read_only=true command="tail" {
+                args "-f" "/var/log/syslog"
+            }
+            pane
+            floating_panes {
+                pane read_only=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.template.unwrap();
+    assert!(tiled_panes.children[0].read_only);
+    assert!(!tiled_panes.children[1].read_only);
+    assert!(floating_panes[0].read_only);
+}
+
+#[test]
+fn read_only_is_inherited_from_pane_templates() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="dashboard" read_only=true
+            dashboard
+            dashboard read_only=false
+            floating_panes {
+                dashboard
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.template.unwrap();
+    assert!(tiled_panes.children[0].read_only);
+    assert!(!tiled_panes.children[1].read_only);
+    assert!(floating_panes[0].read_only);
+}
+
+#[test]
+fn commands_of_panes_with_hidden_titles_are_redacted() {
+    let kdl_layout = r#"
+        layout {
+            pane hide_title=true command="deploy" {
+                args "--token" "abc"
+            }
+            pane command="htop"
+        }
+    "#;
+    let redacted = crate::kdl::redact_hidden_title_commands(kdl_layout).unwrap();
+    assert!(!redacted.contains("deploy"));
+    assert!(!redacted.contains("abc"));
+    assert!(redacted.contains("htop"));
+}
+
+#[test]
+fn panes_hiding_titles_through_their_template_are_redacted() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="secret" hide_title=true {
+                command "vault"
+            }
+            secret {
+                args "login" "--token" "abc"
+            }
+            secret hide_title=false {
+                args "status"
+            }
+            pane hide_title=true edit="/secrets/credentials.txt"
+            pane hide_title=true {
+                plugin location="file:/secrets/plugin.wasm"
+            }
+            pane command="htop"
+        }
+    "#;
+    let redacted = crate::kdl::redact_hidden_title_commands(kdl_layout).unwrap();
+    assert!(!redacted.contains("vault"));
+    assert!(!redacted.contains("abc"));
+    assert!(!redacted.contains("credentials"));
+    assert!(!redacted.contains("plugin.wasm"));
+    assert!(redacted.contains("status"));
+    assert!(redacted.contains("htop"));
+}
+
+#[test]
+fn commands_of_panes_excluded_from_dump_are_replaced_with_a_comment() {
+    let kdl_layout = r#"
+        layout {
+            pane name="deploy" size="30%" command="deploy" exclude_from_dump=true {
+                args "--token" "abc"
+                close_on_exit true
+            }
+            pane command="htop"
+        }
+    "#;
+    let redacted = crate::kdl::redact_panes_excluded_from_dump(kdl_layout).unwrap();
+    assert!(!redacted.contains("command=\"deploy\""), "{}", redacted);
+    assert!(!redacted.contains("abc"), "{}", redacted);
+    assert!(redacted.contains("// redacted"), "{}", redacted);
+    assert!(redacted.contains("htop"), "{}", redacted);
+    let layout = Layout::from_kdl(&redacted, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let deploy = &tiled_panes.children[0];
+    assert_eq!(deploy.name.as_deref(), Some("deploy"));
+    assert_eq!(deploy.split_size, Some(SplitSize::Percent(30)));
+    assert_eq!(deploy.run, None);
+    assert!(deploy.exclude_from_dump);
+}
+
+#[test]
+fn redacted_dumps_stay_redacted_when_dumped_again() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="secret" exclude_from_dump=true {
+                command "vault"
+            }
+            secret {
+                args "login" "--token" "abc"
+            }
+            floating_panes {
+                pane command="deploy" exclude_from_dump=true
+            }
+        }
+    "#;
+    let redacted = crate::kdl::redact_panes_excluded_from_dump(kdl_layout).unwrap();
+    assert!(!redacted.contains("vault"), "{}", redacted);
+    assert!(!redacted.contains("abc"), "{}", redacted);
+    assert!(!redacted.contains("deploy"), "{}", redacted);
+    let redacted_again = crate::kdl::redact_panes_excluded_from_dump(&redacted).unwrap();
+    assert_eq!(redacted, redacted_again);
+    assert!(Layout::from_kdl(&redacted_again, "layout_file_name".into(), None, None).is_ok());
+}
+
+#[test]
+fn exclude_from_dump_is_kept_on_the_layout() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="secret" exclude_from_dump=true
+            secret command="vault"
+            pane command="htop"
+            floating_panes {
+                pane command="deploy" exclude_from_dump=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.new_tab();
+    assert_eq!(tiled_panes.extract_exclude_from_dump(), vec![true, false]);
+    assert!(floating_panes[0].exclude_from_dump);
+}
+
+#[test]
+fn floating_panes_can_be_pinned() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="scratch" pinned=true x="70%" y=1
+            floating_panes {
+                scratch command="htop"
+                scratch pinned=false
+                pane { pinned true; }
+                pane
+            }
+            swap_floating_layout {
+                floating_panes max_panes=1 {
+                    pane pinned=true
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (_tiled_panes, floating_panes) = layout.new_tab();
+    let pinned: Vec<Option<bool>> = floating_panes.iter().map(|pane| pane.pinned).collect();
+    assert_eq!(pinned, vec![Some(true), Some(false), Some(true), None]);
+    let swap_entry = layout.swap_floating_layouts[0]
+        .0
+        .get(&LayoutConstraint::MaxPanes(1))
+        .unwrap();
+    assert_eq!(swap_entry[0].pinned, Some(true));
+}
+
+#[test]
+fn floating_panes_can_be_anchored() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="corner" anchor="bottom_right" x=2 y=1
+            floating_panes {
+                pane anchor="top_right" x=2
+                corner command="htop"
+                corner anchor="bottom_left"
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (_tiled_panes, floating_panes) = layout.new_tab();
+    let anchors: Vec<Option<FloatingPaneAnchor>> =
+        floating_panes.iter().map(|pane| pane.anchor).collect();
+    assert_eq!(
+        anchors,
+        vec![
+            Some(FloatingPaneAnchor::TopRight),
+            Some(FloatingPaneAnchor::BottomRight),
+            Some(FloatingPaneAnchor::BottomLeft),
+            None
+        ]
+    );
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(reparsed.new_tab().1, floating_panes, "{}", serialized);
+}
+
+#[test]
+fn unknown_anchors_are_an_error() {
+    let kdl_layout = r#"
+        layout {
+            floating_panes {
+                pane anchor="bottom"
+            }
+        }
+    "#;
+    let error = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap_err();
+    assert!(
+        format!("{:?}", error).contains("anchor should be one of"),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn anchored_positions_are_offsets_from_the_anchor_kept_inside_the_space() {
+    let offset = PercentOrFixed::Fixed(2);
+    assert_eq!(offset.to_anchored_position(100, 20, AnchorSide::Start), 2);
+    assert_eq!(offset.to_anchored_position(100, 20, AnchorSide::Middle), 42);
+    assert_eq!(offset.to_anchored_position(100, 20, AnchorSide::End), 78);
+    let too_far = PercentOrFixed::Fixed(500);
+    assert_eq!(too_far.to_anchored_position(100, 20, AnchorSide::Start), 80);
+    assert_eq!(
+        too_far.to_anchored_position(100, 20, AnchorSide::Middle),
+        80
+    );
+    assert_eq!(too_far.to_anchored_position(100, 20, AnchorSide::End), 0);
+    assert_eq!(
+        PercentOrFixed::Percent(10).to_anchored_position(100, 20, AnchorSide::End),
+        70
+    );
+    // a pane larger than the space is put at its start
+    assert_eq!(offset.to_anchored_position(10, 20, AnchorSide::End), 0);
+}
+
+#[test]
+fn floating_pane_coordinates_can_be_measured_from_the_far_edge() {
+    for (spelling, coordinate) in [
+        ("x=-10", PercentOrFixed::FixedFromFarEdge(10)),
+        ("x=\"-10\"", PercentOrFixed::FixedFromFarEdge(10)),
+        ("x=\"-25%\"", PercentOrFixed::PercentFromFarEdge(25)),
+        ("x=\"25%-\"", PercentOrFixed::PercentFromFarEdge(25)),
+        ("{ x -10; }", PercentOrFixed::FixedFromFarEdge(10)),
+    ] {
+        assert_eq!(
+            floating_pane_with(spelling).x,
+            Some(coordinate),
+            "{}",
+            spelling
+        );
+    }
+    let kdl_layout = r#"
+        layout {
+            floating_panes {
+                pane x=-2 y="-10%" width=20 height=5
+                pane x=0 y="-0"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(reparsed.new_tab().1, layout.new_tab().1, "{}", serialized);
+}
+
+#[test]
+fn coordinates_from_the_far_edge_keep_the_pane_inside_the_space() {
+    // the far side of the pane is 10 columns from the far edge
+    assert_eq!(
+        PercentOrFixed::FixedFromFarEdge(10).to_coordinate(100, 20),
+        70
+    );
+    assert_eq!(
+        PercentOrFixed::PercentFromFarEdge(10).to_coordinate(100, 20),
+        70
+    );
+    assert_eq!(
+        PercentOrFixed::FixedFromFarEdge(0).to_coordinate(100, 20),
+        80
+    );
+    // too far from the far edge, the pane is put at the start
+    assert_eq!(
+        PercentOrFixed::FixedFromFarEdge(500).to_coordinate(100, 20),
+        0
+    );
+    // the coordinates from the start are kept inside the space too
+    assert_eq!(PercentOrFixed::Fixed(500).to_coordinate(100, 20), 80);
+    // from an anchor, they are measured from its other side
+    assert_eq!(
+        PercentOrFixed::FixedFromFarEdge(10).to_anchored_position(100, 20, AnchorSide::End),
+        10
+    );
+}
+
+#[test]
+fn pinned_floating_panes_are_serialized() {
+    let kdl_layout = r#"
+        layout {
+            floating_panes {
+                pane pinned=true
+                pane pinned=false
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(reparsed.new_tab().1, layout.new_tab().1, "{}", serialized);
+    assert!(serialized.contains("pinned=true"), "{}", serialized);
+}
+
+fn three_pane_swap_entry_geometry(swap_entry_attributes: &str) -> String {
+    // positions a 3 pane swap layout entry in tabs with 2, 3 and 6 panes
+    let kdl_layout = format!(
+        r#"
+        layout {{
+            swap_tiled_layout name="three" {{
+                tab split_direction="vertical" {} {{
+                    pane
+                    pane focus=true
+                    pane
+                }}
+            }}
+        }}
+    "#,
+        swap_entry_attributes
+    );
+    let layout = Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let swap_entry = layout.swap_tiled_layouts[0]
+        .0
+        .get(&LayoutConstraint::NoConstraint)
+        .unwrap()
+        .clone();
+    let space = viewport(120, 40);
+    let mut geometry = String::new();
+    for tab_pane_count in [2, 3, 6] {
+        geometry.push_str(&format!("{} panes:\n", tab_pane_count));
+        let positions = swap_entry
+            .position_panes_in_space(&space, Some(tab_pane_count))
+            .unwrap();
+        for (_pane_layout, geom) in positions {
+            geometry.push_str(&format!(
+                "x: {}, y: {}, cols: {}, rows: {}, is_stacked: {}\n",
+                geom.x,
+                geom.y,
+                geom.cols.as_usize(),
+                geom.rows.as_usize(),
+                geom.is_stacked
+            ));
+        }
+    }
+    geometry
+}
+
+#[test]
+fn swap_entry_with_default_extra_panes_and_missing_slots_policies() {
+    assert_snapshot!(three_pane_swap_entry_geometry(""));
+}
+
+#[test]
+fn swap_entry_with_stack_last_extra_panes_policy() {
+    assert_snapshot!(three_pane_swap_entry_geometry(
+        r#"extra_panes="stack_last""#
+    ));
+}
+
+#[test]
+fn swap_entry_with_append_after_focused_extra_panes_policy() {
+    assert_snapshot!(three_pane_swap_entry_geometry(
+        r#"extra_panes="append_after_focused""#
+    ));
+}
+
+#[test]
+fn swap_entry_with_new_stack_extra_panes_policy() {
+    assert_snapshot!(three_pane_swap_entry_geometry(r#"extra_panes="new_stack""#));
+}
+
+#[test]
+fn swap_entry_with_collapse_missing_slots_policy() {
+    assert_snapshot!(three_pane_swap_entry_geometry(
+        r#"missing_slots="collapse""#
+    ));
+}
+
+#[test]
+fn swap_entry_with_keep_empty_missing_slots_policy() {
+    assert_snapshot!(three_pane_swap_entry_geometry(
+        r#"missing_slots="keep_empty""#
+    ));
+}
+
+#[test]
+fn invalid_swap_entry_policies_are_rejected() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab extra_panes="somewhere" {
+                    pane
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "unknown extra_panes policy");
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab missing_slots="fill" {
+                    pane
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "unknown missing_slots policy");
+}
+
+#[test]
+fn side_panel_is_parsed_alongside_tabs() {
+    let kdl_layout = r#"
+        layout {
+            side_panel width="25%" side="right" {
+                pane command="htop"
+            }
+            tab {
+                pane
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let side_panel = layout.side_panel.expect("side panel parsed");
+    assert_eq!(side_panel.width, PercentOrFixed::Percent(25));
+    assert!(matches!(side_panel.pane.run, Some(Run::Command(_))));
+    assert_eq!(layout.tabs.len(), 1, "side panel is not parsed as a tab");
+}
+
+#[test]
+fn side_panel_splits_space_with_tabs() {
+    let side_panel = SidePanelLayout {
+        width: PercentOrFixed::Percent(25),
+        pane: TiledPaneLayout::default(),
+    };
+    let (panel_geom, tab_area) = side_panel
+        .split_space(crate::pane_size::Size {
+            rows: 20,
+            cols: 100,
+        })
+        .unwrap();
+    assert_eq!(panel_geom.x, 75);
+    assert_eq!(panel_geom.cols.as_usize(), 25);
+    assert_eq!(panel_geom.rows.as_usize(), 20);
+    assert_eq!(tab_area, crate::pane_size::Size { rows: 20, cols: 75 });
+    assert!(
+        side_panel
+            .split_space(crate::pane_size::Size { rows: 20, cols: 1 })
+            .is_err(),
+        "no room left for the tabs"
+    );
+}
+
+#[test]
+fn invalid_side_panels_are_rejected() {
+    let missing_width = r#"
+        layout {
+            side_panel {
+                pane
+            }
+        }
+    "#;
+    let left_side = r#"
+        layout {
+            side_panel width="25%" side="left" {
+                pane
+            }
+        }
+    "#;
+    let two_panes = r#"
+        layout {
+            side_panel width="25%" {
+                pane
+                pane
+            }
+        }
+    "#;
+    let nested_panes = r#"
+        layout {
+            side_panel width="25%" {
+                pane {
+                    pane
+                }
+            }
+        }
+    "#;
+    for (kdl_layout, reason) in [
+        (missing_width, "side panel without a width"),
+        (left_side, "side panel docked to the left"),
+        (two_panes, "side panel with two panes"),
+        (nested_panes, "side panel with child panes"),
+    ] {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_err(), "{}", reason);
+    }
+}
+
+#[test]
+fn swap_tiled_layout_pane_refs_adjust_the_base_layout() {
+    let kdl_layout = r#"
+        layout {
+            pane name="editor" size="60%"
+            pane split_direction="vertical" {
+                pane size=10
+                pane
+            }
+            swap_tiled_layout {
+                tab max_panes=3 {
+                    pane ref="editor" size="+10%"
+                    pane ref="1/0" size="-2"
+                }
+                tab {
+                    pane ref="editor" size="30%"
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let base = layout.new_tab().0;
+    let (swap_tiled_layout, _name, _tags) = layout.swap_tiled_layouts.get(0).unwrap();
+
+    let mut expected = base.clone();
+    expected.children[0].split_size = Some(SplitSize::Percent(70));
+    expected.children[1].children[0].split_size = Some(SplitSize::Fixed(5));
+    assert_eq!(
+        swap_tiled_layout.get(&LayoutConstraint::MaxPanes(3)),
+        Some(&expected)
+    );
+
+    let mut expected = base.clone();
+    expected.children[0].split_size = Some(SplitSize::Percent(30));
+    assert_eq!(
+        swap_tiled_layout.get(&LayoutConstraint::NoConstraint),
+        Some(&expected)
+    );
+}
+
+#[test]
+fn swap_tiled_layout_pane_refs_in_a_swap_layout_file() {
+    let kdl_layout = r#"
+        layout {
+            pane name="editor" size="50%"
+            pane
+        }
+    "#;
+    let kdl_swap_layout = r#"
+        swap_tiled_layout name="wide-editor" {
+            tab {
+                pane ref="editor" size="+25%"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(
+        kdl_layout,
+        "layout_file_name".into(),
+        Some(("swap_layout_file_name", kdl_swap_layout)),
+        None,
+    )
+    .unwrap();
+    let (swap_tiled_layout, _name, _tags) = layout.swap_tiled_layouts.get(0).unwrap();
+    let editor_size = swap_tiled_layout
+        .get(&LayoutConstraint::NoConstraint)
+        .map(|swap_layout| swap_layout.children[0].split_size);
+    assert_eq!(editor_size, Some(Some(SplitSize::Percent(75))));
+}
+
+#[test]
+fn invalid_swap_tiled_layout_pane_refs_are_rejected() {
+    let swap_layout_with = |swap_entry: &str| {
+        format!(
+            r#"
+            layout {{
+                pane name="editor" size="60%"
+                pane name="logs" size=5
+                pane name="logs-too"
+                swap_tiled_layout {{
+                    tab {{
+                        {}
+                    }}
+                }}
+            }}
+        "#,
+            swap_entry
+        )
+    };
+    for (swap_entry, reason) in [
+        (r#"pane ref="nvim""#, "unknown pane name"),
+        (r#"pane ref="4""#, "unknown pane position"),
+        (r#"pane ref="editor" size="+50%""#, "size above 100%"),
+        (r#"pane ref="editor" size="-60%""#, "size below 1%"),
+        (
+            r#"pane ref="logs" size="+10%""#,
+            "percent change of a fixed size",
+        ),
+        (
+            r#"pane ref="logs-too" size="-1""#,
+            "relative change of a pane without size",
+        ),
+        (
+            r#"pane ref="editor"; pane"#,
+            "pane refs mixed with new panes",
+        ),
+        (
+            r#"pane ref="editor" borderless=true"#,
+            "other properties changed",
+        ),
+    ] {
+        let layout = Layout::from_kdl(
+            &swap_layout_with(swap_entry),
+            "layout_file_name".into(),
+            None,
+            None,
+        );
+        assert!(layout.is_err(), "{}", reason);
+    }
+}
+
+fn duplicate_property_layouts() -> Vec<(&'static str, &'static str)> {
+    vec![
+        (
+            r#"
+            layout {
+                pane size="50%" size="30%"
+                pane
+            }
+            "#,
+            "pane",
+        ),
+        (
+            r#"
+            layout {
+                tab name="first" name="second" {
+                    pane
+                }
+            }
+            "#,
+            "tab",
+        ),
+        (
+            r#"
+            layout {
+                pane
+                floating_panes {
+                    pane x=1 y=2 x=3
+                }
+            }
+            "#,
+            "floating pane",
+        ),
+        (
+            r#"
+            layout {
+                pane
+                swap_tiled_layout {
+                    tab max_panes=2 max_panes=3 {
+                        pane
+                    }
+                }
+            }
+            "#,
+            "swap layout entry",
+        ),
+    ]
+}
+
+#[test]
+fn duplicate_properties_fail_parsing_with_both_spans() {
+    for (kdl_layout, node) in duplicate_property_layouts() {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+        match layout {
+            Err(ConfigError::KdlError(kdl_error)) => {
+                assert!(
+                    kdl_error.error_message.starts_with("Duplicate property"),
+                    "duplicate property in {}: {}",
+                    node,
+                    kdl_error.error_message
+                );
+                assert!(kdl_error.offset.is_some());
+                assert_eq!(
+                    kdl_error.secondary_labels.len(),
+                    1,
+                    "first definition pointed at in {}",
+                    node
+                );
+            },
+            _ => panic!("expected a layout error for {}, got: {:?}", node, layout),
+        }
+    }
+}
+
+#[test]
+fn duplicate_properties_are_warnings_in_lenient_mode() {
+    for (kdl_layout, node) in duplicate_property_layouts() {
+        let (_layout, diagnostics) = Layout::from_kdl_with_validation(
+            kdl_layout,
+            "layout_file_name".into(),
+            None,
+            None,
+            ValidationOptions::lenient(),
+            &LayoutTemplates::default(),
+        )
+        .unwrap();
+        assert!(
+            diagnostics
+                .iter()
+                .any(|d| d.code == "duplicate-property" && d.span.is_some()),
+            "warning for duplicate property in {}",
+            node
+        );
+    }
+    let kdl_layout = r#"
+        layout {
+            pane size="50%" size="30%"
+            pane
+        }
+    "#;
+    let (layout, _diagnostics) = Layout::from_kdl_with_validation(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::lenient(),
+        &LayoutTemplates::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        layout.new_tab().0.children[0].split_size,
+        Some(SplitSize::Percent(30)),
+        "the last property is used"
+    );
+}
+
+#[test]
+fn stacked_children_cannot_be_split_vertically() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                children stacked=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "error provided for a vertical stack");
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="horizontal" {
+                pane
+                children stacked=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_ok(), "horizontal stacks are allowed");
+}
+
+#[test]
+fn stacked_children_cannot_contain_nested_splits() {
+    let kdl_layout = r#"
+        layout {
+            pane {
+                pane split_direction="vertical" {
+                    pane
+                    pane
+                }
+                children stacked=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "error provided for a nested split inside a stack"
+    );
+}
+
+#[test]
+fn stacked_panes_are_laid_out_horizontally_regardless_of_split_direction() {
+    let layout = TiledPaneLayout {
+        children_split_direction: SplitDirection::Vertical,
+        children_are_stacked: true,
+        children: vec![
+            TiledPaneLayout::default(),
+            TiledPaneLayout::default(),
+            TiledPaneLayout::default(),
+        ],
+        ..Default::default()
+    };
+    let space = viewport(120, 40);
+    let positions = layout.position_panes_in_space(&space, None).unwrap();
+    let geoms: Vec<(usize, usize, usize, usize)> = positions
+        .iter()
+        .map(|(_pane_layout, geom)| (geom.x, geom.y, geom.cols.as_usize(), geom.rows.as_usize()))
+        .collect();
+    assert_eq!(
+        geoms,
+        vec![(0, 0, 120, 1), (0, 1, 120, 1), (0, 2, 120, 38)],
+        "collapsed panes get one row each"
+    );
+}
+
+#[test]
+fn layout_sources_are_named_after_the_alias_or_the_full_path() {
+    let (_path, _layout, _swap_layouts, layout_sources) =
+        Layout::stringified_from_path_or_default(None, None).unwrap();
+    assert_eq!(layout_sources.name, "default");
+    let (_path, _layout, _swap_layouts, layout_sources) =
+        Layout::stringified_from_path_or_default(Some(&PathBuf::from("compact")), None).unwrap();
+    assert_eq!(layout_sources.name, "compact");
+
+    let layout_dir = tempfile::tempdir().unwrap();
+    let layout_path = layout_dir.path().join("work.kdl");
+    std::fs::write(&layout_path, "layout { pane; }").unwrap();
+    let (_path, _layout, _swap_layouts, layout_sources) =
+        Layout::stringified_from_path_or_default(Some(&layout_dir.path().join("work")), None)
+            .unwrap();
+    assert_eq!(
+        layout_sources.name,
+        std::fs::canonicalize(&layout_path)
+            .unwrap()
+            .display()
+            .to_string(),
+        "the extension is added when looking for the file"
+    );
+}
+
+#[test]
+fn layouts_read_from_stdin_are_named_stdin_and_have_no_swap_layouts() {
+    let (path, raw_layout, swap_layouts) =
+        Layout::stringified_from_reader("layout { pane; }".as_bytes(), STDIN_LAYOUT_NAME).unwrap();
+    assert_eq!(path, "stdin");
+    assert_eq!(raw_layout, "layout { pane; }");
+    assert!(swap_layouts.is_none());
+    let layout_sources = LayoutSources {
+        name: STDIN_LAYOUT_NAME.to_owned(),
+        main_layout: path,
+        ..Default::default()
+    };
+    assert_eq!(
+        layout_sources.main_layout_file(),
+        None,
+        "the includes of a layout read from stdin are not looked for next to a file"
+    );
+}
+
+#[test]
+fn empty_layouts_read_from_stdin_are_an_error() {
+    for raw_layout in ["", " \n\t\n"].iter() {
+        match Layout::stringified_from_reader(raw_layout.as_bytes(), STDIN_LAYOUT_NAME) {
+            Err(ConfigError::IoPath(e, path)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof);
+                assert_eq!(path, PathBuf::from("stdin"));
+            },
+            other => panic!("expected an error about the empty layout, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn only_layouts_read_from_a_file_have_a_main_layout_file() {
+    let layout_sources = |main_layout: &str, from_default_assets: bool| LayoutSources {
+        name: main_layout.to_owned(),
+        main_layout: main_layout.to_owned(),
+        from_default_assets,
+        ..Default::default()
+    };
+    assert_eq!(
+        layout_sources("/home/user/layouts/work.kdl", false).main_layout_file(),
+        Some(Path::new("/home/user/layouts/work.kdl"))
+    );
+    assert_eq!(
+        layout_sources("Default layout", true).main_layout_file(),
+        None
+    );
+    assert_eq!(
+        layout_sources("https://example.com/layouts/work.kdl", false).main_layout_file(),
+        None
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn swap_layouts_are_found_next_to_a_layout_link_and_its_target() {
+    use std::os::unix::fs::symlink;
+    let root = tempfile::tempdir().unwrap();
+    let dotfiles = root.path().join("dotfiles");
+    let layouts = root.path().join("layouts");
+    std::fs::create_dir(&dotfiles).unwrap();
+    std::fs::create_dir(&layouts).unwrap();
+    std::fs::write(dotfiles.join("work.kdl"), "layout { pane; }").unwrap();
+    std::fs::write(dotfiles.join("work.swap.kdl"), "// target").unwrap();
+    symlink(dotfiles.join("work.kdl"), layouts.join("work")).unwrap();
+
+    let (_path, _layout, swap_layouts) =
+        Layout::stringified_from_path(&layouts.join("work")).unwrap();
+    let (swap_layout_path, raw_swap_layout) = swap_layouts.unwrap();
+    assert_eq!(
+        PathBuf::from(swap_layout_path),
+        std::fs::canonicalize(dotfiles.join("work.swap.kdl")).unwrap(),
+        "the companion of a link without an extension is found next to its target"
+    );
+    assert_eq!(raw_swap_layout, "// target");
+
+    std::fs::write(layouts.join("work.swap.kdl"), "// link").unwrap();
+    let (_path, _layout, swap_layouts) =
+        Layout::stringified_from_path(&layouts.join("work")).unwrap();
+    let (swap_layout_path, raw_swap_layout) = swap_layouts.unwrap();
+    assert_eq!(
+        PathBuf::from(swap_layout_path),
+        layouts.join("work.swap.kdl")
+    );
+    assert_eq!(
+        raw_swap_layout, "// link",
+        "the companion next to the link comes first"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn swap_layouts_are_found_in_a_linked_layout_dir() {
+    use std::os::unix::fs::symlink;
+    let root = tempfile::tempdir().unwrap();
+    let dotfiles = root.path().join("dotfiles");
+    std::fs::create_dir(&dotfiles).unwrap();
+    std::fs::write(dotfiles.join("work.dev.kdl"), "layout { pane; }").unwrap();
+    std::fs::write(dotfiles.join("work.dev.swap.kdl"), "// swap").unwrap();
+    let layout_dir = root.path().join("layouts");
+    symlink(&dotfiles, &layout_dir).unwrap();
+
+    let (_path, _layout, swap_layouts, layout_sources) = Layout::stringified_from_path_or_default(
+        Some(&layout_dir.join("work.dev.kdl")),
+        Some(layout_dir.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        swap_layouts.map(|(path, _raw_swap_layout)| PathBuf::from(path)),
+        Some(layout_dir.join("work.dev.swap.kdl")),
+        "dots in the layout name are kept"
+    );
+    assert_eq!(
+        layout_sources.swap_layouts,
+        Some(layout_dir.join("work.dev.swap.kdl").display().to_string())
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn dangling_layout_links_are_reported_with_their_target() {
+    use std::os::unix::fs::symlink;
+    let root = tempfile::tempdir().unwrap();
+    let layout_link = root.path().join("broken.kdl");
+    symlink(root.path().join("missing.kdl"), &layout_link).unwrap();
+
+    let error = Layout::stringified_from_path(&layout_link).unwrap_err();
+    let message = error.to_string();
+    assert!(
+        message.contains("is a symlink to") && message.contains("missing.kdl"),
+        "unexpected error: {}",
+        message
+    );
+}
+
+fn layout_fixture(name: &str) -> PathBuf {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    root.join("src/input/unit/fixtures/layouts").join(name)
+}
+
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    // both 1 based, the source is expected to only have LF line endings
+    let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (
+        source[..offset].matches('\n').count() + 1,
+        source[line_start..offset].chars().count() + 1,
+    )
+}
+
+#[test]
+fn layouts_with_byte_order_mark_and_windows_line_endings_are_parsed() {
+    let (_path, bom_crlf_layout, _swap_layouts) =
+        Layout::stringified_from_path(&layout_fixture("bom-crlf.kdl")).unwrap();
+    assert!(bom_crlf_layout.starts_with('\u{feff}') && bom_crlf_layout.contains("\r\n"));
+    let expected_layout = Layout::from_kdl(
+        &bom_crlf_layout
+            .trim_start_matches('\u{feff}')
+            .replace("\r\n", "\n"),
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    for fixture in ["bom-crlf.kdl", "mixed-line-endings.kdl"] {
+        let (_path, raw_layout, _swap_layouts) =
+            Layout::stringified_from_path(&layout_fixture(fixture)).unwrap();
+        let (layout, diagnostics) = Layout::from_kdl_with_validation(
+            &raw_layout,
+            fixture.into(),
+            None,
+            None,
+            ValidationOptions::lenient(),
+            &LayoutTemplates::default(),
+        )
+        .unwrap();
+        assert_eq!(layout, expected_layout, "{} is parsed", fixture);
+        assert!(
+            diagnostics.iter().any(|d| d.code == "byte-order-mark"),
+            "byte order mark is noted in {}",
+            fixture
+        );
+    }
+}
+
+#[test]
+fn layout_errors_point_at_the_right_character_with_windows_line_endings() {
+    for fixture in [
+        "bom-crlf-with-error.kdl",
+        "mixed-line-endings-with-error.kdl",
+    ] {
+        let (_path, raw_layout, _swap_layouts) =
+            Layout::stringified_from_path(&layout_fixture(fixture)).unwrap();
+        let layout = Layout::from_kdl(&raw_layout, fixture.into(), None, None);
+        match layout {
+            Err(ConfigError::KdlError(kdl_error)) => {
+                // errors are reported with the normalized source
+                let (source, _had_byte_order_mark) = normalize_layout_source(&raw_layout);
+                let offset = kdl_error.offset.expect("error has an offset");
+                let len = kdl_error.len.expect("error has a length");
+                let span = &source[offset..offset + len];
+                let caret = offset + (span.len() - span.trim_start().len());
+                assert!(
+                    source[caret..].starts_with("bogus_property=true"),
+                    "error in {} points at: {:?}",
+                    fixture,
+                    span
+                );
+                assert_eq!(
+                    line_and_column(&source, caret),
+                    (11, 14),
+                    "line and column of the error in {}",
+                    fixture
+                );
+            },
+            _ => panic!("expected a layout error in {}, got: {:?}", fixture, layout),
+        }
+    }
+}
+
+#[test]
+fn command_pane_title_shows_the_command() {
+    let run = Run::Command(RunCommand {
+        command: PathBuf::from("htop"),
+        args: vec!["-d".into(), "10".into()],
+        ..Default::default()
+    });
+    assert_eq!(run.pane_title(&PaneTitleFormats::default()), "htop -d 10");
+    let formats = PaneTitleFormats {
+        command: Some("$ {command}".into()),
+        ..Default::default()
+    };
+    assert_eq!(run.pane_title(&formats), "$ htop -d 10");
+}
+
+#[test]
+fn edit_pane_title_shows_the_file_name_and_line() {
+    let run = Run::EditFile(
+        vec![PathBuf::from("/projects/zellij/src/main.rs")],
+        Some(42),
+        None,
+    );
+    assert_eq!(run.pane_title(&PaneTitleFormats::default()), "main.rs:42");
+    let run = Run::EditFile(
+        vec![PathBuf::from("/projects/zellij/src/main.rs")],
+        None,
+        None,
+    );
+    assert_eq!(run.pane_title(&PaneTitleFormats::default()), "main.rs");
+    let formats = PaneTitleFormats {
+        edit: Some("edit {path} (line {line})".into()),
+        ..Default::default()
+    };
+    let run = Run::EditFile(
+        vec![PathBuf::from("/projects/zellij/src/main.rs")],
+        Some(42),
+        None,
+    );
+    assert_eq!(
+        run.pane_title(&formats),
+        "edit /projects/zellij/src/main.rs (line 42)"
+    );
+}
+
+#[test]
+fn plugin_pane_title_shows_the_plugin_name() {
+    let run = Run::Plugin(RunPlugin {
+        _allow_exec_host_cmd: false,
+        location: RunPluginLocation::Zellij(PluginTag::new("status-bar")),
+    });
+    assert_eq!(run.pane_title(&PaneTitleFormats::default()), "status-bar");
+    let run = Run::Plugin(RunPlugin {
+        _allow_exec_host_cmd: false,
+        location: RunPluginLocation::File(PathBuf::from(
+            "/home/user/.config/zellij/plugins/my-plugin.wasm",
+        )),
+    });
+    assert_eq!(run.pane_title(&PaneTitleFormats::default()), "my-plugin");
+    let formats = PaneTitleFormats {
+        plugin: Some("{location}".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        run.pane_title(&formats),
+        "/home/user/.config/zellij/plugins/my-plugin.wasm"
+    );
+}
+
+#[test]
+fn cwd_pane_title_shows_the_last_directory() {
+    let run = Run::Cwd(PathBuf::from("/projects/zellij/"));
+    assert_eq!(run.pane_title(&PaneTitleFormats::default()), "zellij");
+    let run = Run::Cwd(PathBuf::from("/"));
+    assert_eq!(run.pane_title(&PaneTitleFormats::default()), "/");
+    let formats = PaneTitleFormats {
+        cwd: Some("{cwd} {unknown}".into()),
+        ..Default::default()
+    };
+    let run = Run::Cwd(PathBuf::from("/projects/zellij"));
+    assert_eq!(run.pane_title(&formats), "/projects/zellij {unknown}");
+}
+
+fn fitting_space() -> crate::pane_size::PaneGeom {
+    viewport(120, 40)
+}
+
+fn pane_geoms(layout: &TiledPaneLayout) -> Vec<crate::pane_size::PaneGeom> {
+    layout
+        .position_panes_in_space(&fitting_space(), None)
+        .unwrap()
+        .into_iter()
+        .map(|(_pane_layout, geom)| geom)
+        .collect()
+}
+
+fn cells(geoms: &[crate::pane_size::PaneGeom]) -> Vec<(usize, usize, usize, usize)> {
+    geoms
+        .iter()
+        .map(|g| (g.x, g.y, g.cols.as_usize(), g.rows.as_usize()))
+        .collect()
+}
+
+#[test]
+fn fitting_built_in_layouts_to_their_own_geometry_keeps_their_split_sizes() {
+    for layout_name in ["default", "strider", "compact", "disable-status-bar"] {
+        let (path_to_layout, raw_layout, _swap_layouts) =
+            Layout::stringified_from_default_assets(std::path::Path::new(layout_name)).unwrap();
+        let layout = Layout::from_kdl(&raw_layout, path_to_layout, None, None).unwrap();
+        let (tiled_panes, _floating_panes) = layout.new_tab();
+        let mut fitted_panes = tiled_panes.clone();
+        fitted_panes
+            .fit_split_sizes(&fitting_space(), &pane_geoms(&tiled_panes))
+            .unwrap();
+        assert_eq!(fitted_panes, tiled_panes, "{} layout changed", layout_name);
+    }
+}
+
+#[test]
+fn fitting_split_sizes_to_resized_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane size=1 borderless=true
+            pane split_direction="vertical" {
+                pane size="30%"
+                pane {
+                    pane
+                    pane size=5
+                }
+            }
+            pane size=2 borderless=true
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let mut geoms = pane_geoms(&tiled_panes);
+    // the left pane is widened by 7 columns
+    geoms[1].cols.increase_inner(7);
+    for geom in &mut geoms[2..4] {
+        geom.x += 7;
+        geom.cols.decrease_inner(7);
+    }
+    // and the bottom right pane made 3 rows taller
+    geoms[2].rows.decrease_inner(3);
+    geoms[3].y -= 3;
+    geoms[3].rows.increase_inner(3);
+
+    let mut fitted_panes = tiled_panes.clone();
+    fitted_panes
+        .fit_split_sizes(&fitting_space(), &geoms)
+        .unwrap();
+    assert_eq!(cells(&pane_geoms(&fitted_panes)), cells(&geoms));
+    assert_eq!(
+        fitted_panes.children[0].split_size,
+        Some(SplitSize::Fixed(1)),
+        "fixed sizes stay fixed"
+    );
+    assert_eq!(
+        fitted_panes.children[1].children[1].children[1].split_size,
+        Some(SplitSize::Fixed(5))
+    );
+
+    let mut refitted_panes = fitted_panes.clone();
+    refitted_panes
+        .fit_split_sizes(&fitting_space(), &geoms)
+        .unwrap();
+    assert_eq!(
+        refitted_panes, fitted_panes,
+        "fitting again changes nothing"
+    );
+}
+
+#[test]
+fn fitting_split_sizes_to_another_pane_count_is_an_error() {
+    let kdl_layout = r#"
+        layout {
+            pane
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (mut tiled_panes, _floating_panes) = layout.new_tab();
+    let geoms = pane_geoms(&tiled_panes);
+    assert!(tiled_panes
+        .fit_split_sizes(&fitting_space(), &geoms[..1])
+        .is_err());
+}
+
+const MIN_USABLE_STRIDER_WIDTH: usize = 16;
+
+fn strider_layout() -> Layout {
+    let (path_to_layout, raw_layout, swap_layouts) =
+        Layout::stringified_from_default_assets(std::path::Path::new("strider")).unwrap();
+    Layout::from_kdl(
+        &raw_layout,
+        path_to_layout,
+        swap_layouts
+            .as_ref()
+            .map(|(path, raw_swap_layout)| (path.as_str(), raw_swap_layout.as_str())),
+        None,
+    )
+    .unwrap()
+}
+
+fn strider_pane_geom(
+    tiled_panes: &TiledPaneLayout,
+    cols: usize,
+    max_panes: Option<usize>,
+) -> crate::pane_size::PaneGeom {
+    let space = viewport(cols, 40);
+    let strider = Some(Run::Plugin(RunPlugin {
+        _allow_exec_host_cmd: false,
+        location: RunPluginLocation::Zellij(PluginTag::new("strider")),
+    }));
+    tiled_panes
+        .position_panes_in_space(&space, max_panes)
+        .unwrap()
+        .into_iter()
+        .find(|(pane_layout, _geom)| pane_layout.run == strider)
+        .map(|(_pane_layout, geom)| geom)
+        .unwrap()
+}
+
+#[test]
+fn strider_pane_is_usable_or_stacked_at_any_width() {
+    let layout = strider_layout();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let mut layouts_to_check = vec![(tiled_panes, None)];
+    for (swap_tiled_layout, _swap_layout_name, _tags) in &layout.swap_tiled_layouts {
+        for (constraint, tiled_panes) in swap_tiled_layout {
+            let pane_count = match constraint {
+                LayoutConstraint::ExactPanes(exact_panes) => *exact_panes,
+                LayoutConstraint::PaneRange { max, .. } => *max,
+                LayoutConstraint::MaxPanes(max_panes) => *max_panes,
+                LayoutConstraint::MinPanes(min_panes) => min_panes + 2,
+                LayoutConstraint::MaxGroups(_)
+                | LayoutConstraint::MinGroups(_)
+                | LayoutConstraint::InSession(..)
+                | LayoutConstraint::NoConstraint => tiled_panes.pane_count(),
+            };
+            layouts_to_check.push((tiled_panes.clone(), Some(pane_count)));
+        }
+    }
+    for cols in [60, 100, 200] {
+        for (tiled_panes, max_panes) in &layouts_to_check {
+            let geom = strider_pane_geom(tiled_panes, cols, *max_panes);
+            assert!(
+                geom.is_stacked || geom.cols.as_usize() >= MIN_USABLE_STRIDER_WIDTH,
+                "strider pane is {} columns wide at {} columns with {:?} panes",
+                geom.cols.as_usize(),
+                cols,
+                max_panes
+            );
+        }
+        let (tiled_panes, max_panes) = &layouts_to_check[0];
+        assert_eq!(
+            strider_pane_geom(tiled_panes, cols, *max_panes).is_stacked,
+            cols < 80,
+            "strider pane is stacked only in narrow tabs"
+        );
+    }
+}
+
+#[test]
+fn strider_pane_width_and_stack_below_width_can_be_configured() {
+    let mut layout = strider_layout();
+    layout.configure_strider_panes(Some(SplitSize::Fixed(30)), Some(120));
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    assert!(strider_pane_geom(&tiled_panes, 100, None).is_stacked);
+    let geom = strider_pane_geom(&tiled_panes, 200, None);
+    assert!(!geom.is_stacked);
+    assert_eq!(geom.cols.as_usize(), 30);
+    for (swap_tiled_layout, _swap_layout_name, _tags) in &layout.swap_tiled_layouts {
+        for (constraint, tiled_panes) in swap_tiled_layout {
+            if let LayoutConstraint::MaxPanes(max_panes) = constraint {
+                let geom = strider_pane_geom(tiled_panes, 200, Some(*max_panes));
+                assert_eq!(geom.cols.as_usize(), 30, "swap layouts are configured too");
+            }
+        }
+    }
+}
+
+#[test]
+fn stack_below_width_must_be_a_positive_number_of_columns() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" stack_below_width=0 {
+                pane
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "error provided for a zero width");
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" stack_below_width="wide" {
+                pane
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "error provided for a width that is not a number"
+    );
+}
+
+#[test]
+fn layout_with_command_spawn_interval() {
+    let kdl_layout = r#"
+        layout {
+            command_spawn_interval_ms 500
+            pane command="htop"
+            pane command="tail"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.command_spawn_interval_ms, Some(500));
+    assert_eq!(
+        layout.template.unwrap().0.children.len(),
+        2,
+        "the interval is not parsed as a pane"
+    );
+    let kdl_layout = r#"
+        layout {
+            pane command="htop"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.command_spawn_interval_ms, None);
+}
+
+#[test]
+fn command_spawn_interval_must_be_a_positive_number_of_milliseconds() {
+    let kdl_layout = r#"
+        layout {
+            command_spawn_interval_ms -1
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(layout.is_err(), "error provided for a negative interval");
+    let kdl_layout = r#"
+        layout {
+            command_spawn_interval_ms "slow"
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "error provided for an interval that is not a number"
+    );
+}
+
+fn focused_pane_name(
+    kdl_layout: &str,
+    focus_policy: FocusPolicy,
+    max_panes: usize,
+) -> Option<String> {
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_layout, _floating_layouts) = layout.new_tab();
+    tiled_layout
+        .position_panes_in_space_with_focus(&fitting_space(), Some(max_panes), focus_policy)
+        .unwrap()
+        .into_iter()
+        .find(|(pane_layout, _geom)| pane_layout.focus == Some(true))
+        .and_then(|(pane_layout, _geom)| pane_layout.name)
+}
+
+const ASYMMETRIC_LAYOUT: &str = r#"
+    layout {
+        pane split_direction="vertical" {
+            pane name="wide" size="60%"
+            pane {
+                pane name="top"
+                pane name="logs"
+            }
+            pane name="narrow" size="10%"
+        }
+    }
+"#;
+
+#[test]
+fn default_focus_policies_focus_different_panes_of_an_asymmetric_layout() {
+    assert_eq!(
+        focused_pane_name(ASYMMETRIC_LAYOUT, FocusPolicy::First, 4).as_deref(),
+        Some("wide")
+    );
+    assert_eq!(
+        focused_pane_name(ASYMMETRIC_LAYOUT, FocusPolicy::Deepest, 4).as_deref(),
+        Some("logs")
+    );
+    assert_eq!(
+        focused_pane_name(ASYMMETRIC_LAYOUT, FocusPolicy::Largest, 4).as_deref(),
+        Some("wide")
+    );
+    assert_eq!(
+        focused_pane_name(ASYMMETRIC_LAYOUT, FocusPolicy::Last, 4).as_deref(),
+        Some("narrow")
+    );
+}
+
+#[test]
+fn largest_pane_is_picked_after_the_layout_is_split() {
+    let kdl_layout = r#"
+        layout {
+            pane name="small"
+            pane name="large" size="80%"
+        }
+    "#;
+    assert_eq!(
+        focused_pane_name(kdl_layout, FocusPolicy::Largest, 2).as_deref(),
+        Some("large")
+    );
+}
+
+#[test]
+fn explicitly_focused_panes_are_kept_whatever_the_focus_policy() {
+    let kdl_layout = r#"
+        layout {
+            pane name="first"
+            pane name="focused" focus=true
+            pane name="last"
+        }
+    "#;
+    for focus_policy in [
+        FocusPolicy::First,
+        FocusPolicy::Deepest,
+        FocusPolicy::Largest,
+        FocusPolicy::Last,
+    ] {
+        assert_eq!(
+            focused_pane_name(kdl_layout, focus_policy, 3).as_deref(),
+            Some("focused")
+        );
+    }
+}
+
+#[test]
+fn only_the_deepest_policy_focuses_extra_children() {
+    let kdl_layout = r#"
+        layout {
+            pane name="editor"
+            pane {
+                children
+            }
+        }
+    "#;
+    assert_eq!(
+        focused_pane_name(kdl_layout, FocusPolicy::First, 4).as_deref(),
+        Some("editor")
+    );
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let positions = layout
+        .new_tab()
+        .0
+        .position_panes_in_space_with_focus(&fitting_space(), Some(4), FocusPolicy::Deepest)
+        .unwrap();
+    assert_eq!(
+        positions
+            .iter()
+            .position(|(pane_layout, _geom)| pane_layout.focus == Some(true)),
+        Some(positions.len() - 1),
+        "the last extra child is focused by default, as it always was"
+    );
+}
+
+#[test]
+fn layout_with_default_focus() {
+    let kdl_layout = r#"
+        layout {
+            default_focus "largest"
+            pane
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.default_focus, Some(FocusPolicy::Largest));
+    assert_eq!(
+        layout.template.unwrap().0.children.len(),
+        2,
+        "the focus policy is not parsed as a pane"
+    );
+    let kdl_layout = r#"
+        layout {
+            default_focus "biggest"
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "error provided for an unknown focus policy"
+    );
+}
+
+#[test]
+fn layout_with_command_wrapper() {
+    let kdl_layout = r#"
+        layout {
+            command_wrapper "devcontainer" {
+                args "exec" "--"
+            }
+            pane command="cargo" {
+                args "build"
+            }
+            pane edit="src/main.rs" no_wrapper=true
+            floating_panes {
+                pane command="htop" no_wrapper=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        layout.command_wrapper,
+        Some(CommandWrapper {
+            command: PathBuf::from("devcontainer"),
+            args: vec!["exec".into(), "--".into()],
+        })
+    );
+    let (tiled_panes, floating_panes) = layout.new_tab();
+    assert_eq!(tiled_panes.extract_no_wrapper(), vec![false, true]);
+    assert!(floating_panes[0].no_wrapper);
+    assert_eq!(
+        tiled_panes.extract_run_instructions()[0],
+        Some(Run::Command(RunCommand {
+            command: PathBuf::from("cargo"),
+            args: vec!["build".into()],
+            ..Default::default()
+        })),
+        "the commands are kept as written, they are wrapped when spawned"
+    );
+}
+
+#[test]
+fn command_wrapper_must_name_a_program() {
+    let kdl_layout = r#"
+        layout {
+            command_wrapper {
+                args "exec" "--"
+            }
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "error provided for a wrapper without a program"
+    );
+}
+
+fn command_run(command: &str) -> Option<Run> {
+    Some(Run::Command(RunCommand {
+        command: PathBuf::from(command),
+        ..Default::default()
+    }))
+}
+
+fn plugin_run(location: &str) -> Option<Run> {
+    Some(Run::Plugin(RunPlugin {
+        location: RunPluginLocation::Zellij(PluginTag::new(location)),
+        _allow_exec_host_cmd: false,
+    }))
+}
+
+#[test]
+fn panes_running_the_same_kind_of_thing_fit_a_slot_best() {
+    let editor = Some(Run::EditFile(
+        vec![PathBuf::from("src/main.rs")],
+        None,
+        None,
+    ));
+    assert_eq!(
+        run_match(&command_run("htop"), None, &command_run("cargo"), None),
+        RunMatch::SameCategory
+    );
+    assert_eq!(
+        run_match(&command_run("htop"), None, &editor, None),
+        RunMatch::Compatible,
+        "an editor can take the slot of a command"
+    );
+    assert_eq!(
+        run_match(&editor, None, &None, None),
+        RunMatch::Compatible,
+        "a shell can take the slot of an editor"
+    );
+    assert_eq!(
+        run_match(
+            &plugin_run("tab-bar"),
+            None,
+            &plugin_run("status-bar"),
+            None
+        ),
+        RunMatch::SameCategory
+    );
+}
+
+#[test]
+fn pane_names_are_matched_before_falling_back_to_compatible_panes() {
+    let editor = Some(Run::EditFile(
+        vec![PathBuf::from("src/main.rs")],
+        None,
+        None,
+    ));
+    assert_eq!(
+        run_match(
+            &command_run("nvim"),
+            Some("editor"),
+            &editor,
+            Some("editor")
+        ),
+        RunMatch::SameName
+    );
+    assert_eq!(
+        run_match(&command_run("nvim"), Some("editor"), &editor, Some("logs")),
+        RunMatch::Compatible
+    );
+    assert_eq!(
+        run_match(&None, Some(""), &None, Some("")),
+        RunMatch::Compatible,
+        "panes without a name do not match by name"
+    );
+    assert!(RunMatch::SameCategory < RunMatch::SameName);
+    assert!(RunMatch::SameName < RunMatch::Compatible);
+}
+
+#[test]
+fn plugins_and_terminals_are_matched_last() {
+    assert_eq!(
+        run_match(&plugin_run("tab-bar"), None, &command_run("nvim"), None),
+        RunMatch::Incompatible
+    );
+    assert_eq!(
+        run_match(&None, Some("bar"), &plugin_run("tab-bar"), Some("bar")),
+        RunMatch::Incompatible,
+        "a plugin does not take the slot of a terminal because of its name"
+    );
+    assert!(RunMatch::Compatible < RunMatch::Incompatible);
+}
+
+#[test]
+fn layout_can_use_templates_defined_in_the_config() {
+    let config = r#"
+        layout_templates {
+            pane_template name="tab-bar" size=1 borderless=true {
+                plugin location="zellij:tab-bar"
+            }
+            pane_template name="status-bar" size=2 borderless=true {
+                plugin location="zellij:status-bar"
+            }
+            tab_template name="ui" {
+                tab-bar
+                children
+                status-bar
+            }
+        }
+    "#;
+    let config = crate::input::config::Config::from_kdl(config, None).unwrap();
+    let kdl_layout = r#"
+        layout {
+            ui name="first" {
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl_with_templates(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &config.layout_templates,
+    )
+    .unwrap();
+    let kdl_local_layout = r#"
+        layout {
+            pane_template name="tab-bar" size=1 borderless=true {
+                plugin location="zellij:tab-bar"
+            }
+            pane_template name="status-bar" size=2 borderless=true {
+                plugin location="zellij:status-bar"
+            }
+            tab_template name="ui" {
+                tab-bar
+                children
+                status-bar
+            }
+            ui name="first" {
+                pane
+            }
+        }
+    "#;
+    let local_layout =
+        Layout::from_kdl(kdl_local_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        layout, local_layout,
+        "templates from the config work like templates defined in the layout"
+    );
+    assert!(
+        Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_err(),
+        "config templates are only known to the layouts parsed with them"
+    );
+}
+
+#[test]
+fn layout_templates_replace_config_templates_with_the_same_name() {
+    let config = r#"
+        layout_templates {
+            pane_template name="editor" {
+                command "vim"
+            }
+            default_tab_template {
+                pane size=1 borderless=true {
+                    plugin location="zellij:tab-bar"
+                }
+                children
+            }
+        }
+    "#;
+    let config = crate::input::config::Config::from_kdl(config, None).unwrap();
+    let kdl_layout = r#"
+        layout {
+            pane_template name="editor" {
+                command "hx"
+            }
+            editor
+        }
+    "#;
+    let (layout, diagnostics) = Layout::from_kdl_with_validation(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::lenient(),
+        &config.layout_templates,
+    )
+    .unwrap();
+    let commands: Vec<std::path::PathBuf> = layout.tabs[0]
+        .1
+        .extract_run_instructions()
+        .into_iter()
+        .filter_map(|run| match run {
+            Some(Run::Command(run_command)) => Some(run_command.command),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        commands,
+        vec![std::path::PathBuf::from("hx")],
+        "the layout's template is used"
+    );
+    let shadowed: Vec<&LayoutDiagnostic> = diagnostics
+        .iter()
+        .filter(|d| d.code == "shadowed-template")
+        .collect();
+    assert_eq!(shadowed.len(), 1);
+    assert!(!shadowed[0].is_error());
+    let kdl_layout = r#"
+        layout {
+            default_tab_template {
+                children
+            }
+            tab {
+                pane
+            }
+        }
+    "#;
+    let (layout, diagnostics) = Layout::from_kdl_with_validation(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::strict(),
+        &config.layout_templates,
+    )
+    .unwrap();
+    assert_eq!(
+        layout.tabs[0].1.children.len(),
+        1,
+        "the layout's default_tab_template is used"
+    );
+    assert!(diagnostics.iter().all(|d| d.code == "shadowed-template"));
+    assert_eq!(diagnostics.len(), 1, "strict mode only warns about it");
+}
+
+#[test]
+fn config_templates_can_use_each_other() {
+    let config = r#"
+        layout_templates {
+            tab_template name="dev" {
+                half
+                children
+            }
+            pane_template name="half" size="50%" split_direction="vertical" {
+                shell
+                shell
+            }
+            pane_template name="shell" {
+                command "bash"
+            }
+        }
+    "#;
+    let config = crate::input::config::Config::from_kdl(config, None).unwrap();
+    let kdl_layout = r#"
+        layout {
+            dev {
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl_with_templates(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &config.layout_templates,
+    )
+    .unwrap();
+    let tab = &layout.tabs[0].1;
+    assert_eq!(tab.children[0].children.len(), 2);
+    assert!(
+        matches!(&tab.children[0].children[0].run, Some(Run::Command(c)) if c.command == std::path::PathBuf::from("bash"))
+    );
+    let config_with_an_unknown_node = r#"
+        layout_templates {
+            pane name="not-a-template"
+        }
+    "#;
+    assert!(crate::input::config::Config::from_kdl(config_with_an_unknown_node, None).is_err());
+    let config_with_a_cycle = r#"
+        layout_templates {
+            pane_template name="one" {
+                two
+            }
+            pane_template name="two" {
+                one
+            }
+        }
+    "#;
+    assert!(crate::input::config::Config::from_kdl(config_with_a_cycle, None).is_err());
+}
+
+#[test]
+fn test_support_layouts_tile_their_viewport() {
+    let space = viewport(120, 40);
+    for (name, layout) in test_support::all_layouts() {
+        let tiled_layouts: Vec<TiledPaneLayout> = if layout.has_tabs() {
+            layout.tabs().into_iter().map(|tab| tab.1).collect()
+        } else {
+            vec![layout.new_tab().0]
+        };
+        for tiled_panes in tiled_layouts {
+            let positions = tiled_panes
+                .position_panes_in_space(&space, None)
+                .unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_tiles_exactly(&positions, &space);
+        }
+    }
+}
+
+#[test]
+fn test_support_layouts_have_their_documented_panes() {
+    let pane_count = |tiled_panes: &TiledPaneLayout| tiled_panes.extract_run_instructions().len();
+    assert_eq!(pane_count(&test_support::single_pane().new_tab().0), 1);
+    assert_eq!(pane_count(&test_support::grid_2x2().new_tab().0), 4);
+    let stacked = test_support::stacked().new_tab().0;
+    assert!(stacked.children[0].children_are_stacked);
+    assert_eq!(pane_count(&stacked), 3);
+    let tabbed = test_support::tabbed_with_template();
+    let tab_names: Vec<Option<String>> = tabbed.tabs().into_iter().map(|tab| tab.0).collect();
+    assert_eq!(
+        tab_names,
+        vec![Some("editor".to_owned()), Some("shell".to_owned())]
+    );
+    assert_eq!(test_support::floating_heavy().new_tab().1.len(), 4);
+}
+
+#[test]
+#[should_panic(expected = "overlap")]
+fn assert_tiles_exactly_rejects_overlapping_panes() {
+    let space = viewport(10, 10);
+    let pane = (TiledPaneLayout::default(), viewport(10, 6));
+    let mut overlapping_pane = (TiledPaneLayout::default(), viewport(10, 5));
+    overlapping_pane.1.y = 5;
+    assert_tiles_exactly(&[pane, overlapping_pane], &space);
+}
+
+#[test]
+#[should_panic(expected = "no pane covers")]
+fn assert_tiles_exactly_rejects_gaps() {
+    let space = viewport(10, 10);
+    let pane = (TiledPaneLayout::default(), viewport(10, 9));
+    assert_tiles_exactly(&[pane], &space);
+}
+
+#[test]
+fn stack_with_more_panes_than_rows_is_laid_out_as_a_deep_stack() {
+    let stacked = test_support::stacked().new_tab().0;
+    let space = viewport(80, 2);
+    let positions = stacked.position_panes_in_space(&space, None).unwrap();
+    let geoms: Vec<(usize, usize)> = positions
+        .iter()
+        .map(|(_, geom)| (geom.y, geom.rows.as_usize()))
+        .collect();
+    assert_eq!(geoms, vec![(0, 1), (0, 1), (1, 1)]);
+    assert_eq!(
+        TiledPaneLayout::deep_stacks(&positions),
+        vec![vec![0, 1, 2]]
+    );
+    // once one of the panes sharing the top row is left out, the stack tiles its space
+    let shown_panes = vec![positions[1].clone(), positions[2].clone()];
+    assert_tiles_exactly(&shown_panes, &space);
+}
+
+#[test]
+fn stack_with_a_row_for_each_pane_is_not_a_deep_stack() {
+    let stacked = test_support::stacked().new_tab().0;
+    let space = viewport(80, 3);
+    let positions = stacked.position_panes_in_space(&space, None).unwrap();
+    assert_tiles_exactly(&positions, &space);
+    assert!(TiledPaneLayout::deep_stacks(&positions).is_empty());
+}
+
+#[test]
+fn stack_without_room_for_its_expanded_pane_is_an_error() {
+    let stacked = test_support::stacked().new_tab().0;
+    assert!(stacked
+        .position_panes_in_space(&viewport(80, 1), None)
+        .is_err());
+}
+
+fn parse_with_limits(kdl_layout: &str, limits: LayoutLimits) -> Result<Layout, ConfigError> {
+    Layout::from_kdl_with_validation(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::strict().with_limits(limits),
+        &LayoutTemplates::default(),
+    )
+    .map(|(layout, _diagnostics)| layout)
+}
+
+fn assert_limit_exceeded(result: Result<Layout, ConfigError>, limit_name: &str) {
+    match result {
+        Err(ConfigError::LayoutLimitExceeded(message)) => {
+            assert!(message.contains(limit_name), "{}", message)
+        },
+        Err(e) => panic!("expected {} to be exceeded, got: {}", limit_name, e),
+        Ok(_) => panic!("expected {} to be exceeded", limit_name),
+    }
+}
+
+#[test]
+fn layout_nesting_deeper_than_the_limit_is_refused_before_parsing() {
+    let depth = LayoutLimits::default().max_depth + 1;
+    let kdl_layout = format!(
+        "layout {{ {}pane{} }}",
+        "pane { ".repeat(depth),
+        " }".repeat(depth)
+    );
+    assert_limit_exceeded(
+        Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None),
+        "layout_max_depth",
+    );
+}
+
+#[test]
+fn braces_in_strings_and_comments_do_not_count_towards_the_depth_limit() {
+    let limits = LayoutLimits {
+        max_depth: 2,
+        ..Default::default()
+    };
+    let kdl_layout = r##"
+        // {{{
+        /* {{{ /* {{{ */ {{{ */
+        layout {
+            pane name="{{{" {
+                pane command="echo" {
+                    args r#"{{{"# "\"{{{"
+                }
+            }
+        }
+    "##;
+    assert!(parse_with_limits(kdl_layout, limits).is_err());
+    let limits = LayoutLimits {
+        max_depth: 3,
+        ..Default::default()
+    };
+    assert!(parse_with_limits(kdl_layout, limits).is_ok());
+}
+
+#[test]
+fn layout_with_more_nodes_than_the_limit_is_refused() {
+    let limits = LayoutLimits {
+        max_nodes: 10,
+        ..Default::default()
+    };
+    let kdl_layout = format!("layout {{ {} }}", "pane; ".repeat(10));
+    assert_limit_exceeded(parse_with_limits(&kdl_layout, limits), "layout_max_nodes");
+    let kdl_layout = format!("layout {{ {} }}", "pane; ".repeat(9));
+    assert!(parse_with_limits(&kdl_layout, limits).is_ok());
+}
+
+#[test]
+fn layout_file_larger_than_the_limit_is_refused() {
+    let limits = LayoutLimits {
+        max_file_size: 100,
+        ..Default::default()
+    };
+    let kdl_layout = format!("layout {{ pane name=\"{}\"; }}", "a".repeat(100));
+    assert_limit_exceeded(
+        parse_with_limits(&kdl_layout, limits),
+        "layout_max_file_size",
+    );
+}
+
+#[test]
+fn layout_limits_can_be_raised_in_the_config() {
+    let config = crate::input::config::Config::from_kdl(
+        "layout_max_depth 500\nlayout_max_nodes 100000\nlayout_max_file_size 1000",
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        config.options.layout_limits(),
+        LayoutLimits {
+            max_depth: 500,
+            max_nodes: 100_000,
+            max_file_size: 1000,
+        }
+    );
+    assert_eq!(
+        crate::input::options::Options::default().layout_limits(),
+        LayoutLimits::default()
+    );
+}
+
+#[test]
+fn layouts_starting_up_to_the_maximum_commands_are_opened() {
+    let kdl_layout = r#"
+        layout {
+            pane command="htop"
+            pane edit="README.md"
+            pane
+            floating_panes {
+                pane command="tail"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.command_count(), 3);
+    assert!(check_max_layout_commands(layout.command_count(), Some(3)).is_ok());
+    assert!(check_max_layout_commands(layout.command_count(), None).is_ok());
+    assert_eq!(
+        check_max_layout_commands(layout.command_count(), Some(2))
+            .unwrap_err()
+            .to_string(),
+        "The layout starts 3 commands, more than the maximum of 2 (max_layout_commands)"
+    );
+}
+
+#[test]
+fn the_commands_of_all_tabs_count_towards_the_maximum_but_not_the_template() {
+    let kdl_layout = r#"
+        layout {
+            tab {
+                pane command="htop"
+            }
+            tab {
+                pane command="tail"
+                pane command="top"
+            }
+            default_tab_template {
+                pane command="vim"
+                children
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        layout.command_count(),
+        5,
+        "each tab opens the command of the template once"
+    );
+    let kdl_layout_without_tabs = r#"
+        layout {
+            default_tab_template {
+                pane command="vim"
+                children
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(
+        kdl_layout_without_tabs,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(layout.command_count(), 1, "the template opens the only tab");
+}
+
+#[test]
+fn layouts_cannot_raise_the_maximum_commands_of_the_config() {
+    let layout_dir = tempfile::tempdir().unwrap();
+    let layout_path = layout_dir.path().join("many-commands.kdl");
+    std::fs::write(
+        &layout_path,
+        "layout {\n    pane command=\"htop\"\n}\nmax_layout_commands 100\n",
+    )
+    .unwrap();
+    let config = crate::input::config::Config::from_kdl("max_layout_commands 1", None).unwrap();
+    let (_layout, config, _layout_sources) =
+        Layout::from_path_or_default(Some(&layout_path), None, config, &Default::default())
+            .unwrap();
+    assert_eq!(config.options.max_layout_commands, Some(1));
+}
+
+#[test]
+fn deeply_nested_layouts_are_traversed_without_recursing() {
+    let depth = 2000;
+    let mut layout = TiledPaneLayout {
+        name: Some("innermost".into()),
+        ..Default::default()
+    };
+    for _ in 0..depth {
+        // every level takes a row, so that there is room for all of them
+        let row = TiledPaneLayout {
+            split_size: Some(SplitSize::Fixed(1)),
+            ..Default::default()
+        };
+        layout = TiledPaneLayout {
+            children: vec![row, layout],
+            ..Default::default()
+        };
+    }
+    assert_eq!(layout.deepest_depth(), depth + 1);
+    assert_eq!(layout.pane_count(), depth + 1);
+    let pane_names = layout.extract_pane_names();
+    assert_eq!(pane_names[depth].as_deref(), Some("innermost"));
+    assert_eq!(layout.extract_pane_paths()[depth], vec![1; depth]);
+    let space = viewport(10, depth * 2);
+    let positions = layout.position_panes_in_space(&space, None);
+    assert_eq!(positions.map(|p| p.len()), Ok(depth + 1));
+}
+
+fn parse_leniently(kdl_layout: &str) -> (Layout, Vec<LayoutDiagnostic>) {
+    Layout::from_kdl_with_validation(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        ValidationOptions::lenient(),
+        &LayoutTemplates::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn deprecated_names_are_translated_with_a_warning_in_lenient_mode() {
+    let deprecated_layout = r#"
+        layout {
+            template {
+                pane direction="vertical" {
+                    pane split_size="30%"
+                    children
+                }
+            }
+            pane {
+                direction "vertical"
+            }
+        }
+    "#;
+    let current_layout = r#"
+        layout {
+            default_tab_template {
+                pane split_direction="vertical" {
+                    pane size="30%"
+                    children
+                }
+            }
+            pane {
+                split_direction "vertical"
+            }
+        }
+    "#;
+    let (layout, diagnostics) = parse_leniently(deprecated_layout);
+    let (expected_layout, _diagnostics) = parse_leniently(current_layout);
+    assert_eq!(layout, expected_layout);
+    let deprecations: Vec<&LayoutDiagnostic> = diagnostics
+        .iter()
+        .filter(|d| d.code == "deprecated-name")
+        .collect();
+    assert_eq!(deprecations.len(), 4, "{:?}", diagnostics);
+    for (deprecation, (old, new)) in deprecations.iter().zip([
+        ("template", "default_tab_template"),
+        ("direction", "split_direction"),
+        ("split_size", "size"),
+        ("direction", "split_direction"),
+    ]) {
+        assert!(!deprecation.is_error());
+        assert!(
+            deprecation.message.contains(old) && deprecation.message.contains(new),
+            "{}",
+            deprecation.message
+        );
+        let span = deprecation.span.expect("deprecated names are pointed at");
+        assert!(
+            deprecated_layout[span.offset..span.offset + span.len].contains(old),
+            "{} pointed at",
+            old
+        );
+    }
+}
+
+#[test]
+fn deprecated_names_are_errors_naming_the_replacement_in_strict_mode() {
+    let kdl_layout = r#"
+        layout {
+            pane direction="vertical" {
+                pane
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    match layout {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert!(
+                kdl_error
+                    .error_message
+                    .contains("'direction' was renamed to 'split_direction'"),
+                "{}",
+                kdl_error.error_message
+            );
+            assert!(kdl_error.offset.is_some());
+        },
+        _ => panic!("expected a layout error, got: {:?}", layout),
+    }
+}
+
+#[test]
+fn deprecated_names_are_left_alone_in_templates_and_plugin_configuration() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="direction" {
+                pane
+                pane
+            }
+            direction
+            pane {
+                plugin location="zellij:tab-bar" {
+                    direction "vertical"
+                }
+            }
+        }
+    "#;
+    let (layout, diagnostics) = parse_leniently(kdl_layout);
+    assert!(
+        diagnostics.iter().all(|d| d.code != "deprecated-name"),
+        "{:?}",
+        diagnostics
+    );
+    assert_eq!(layout.template.unwrap().0.children.len(), 2);
+}
+
+#[test]
+fn formatting_a_layout_rewrites_its_deprecated_names() {
+    let kdl_layout = r#"
+        layout {
+            template {
+                pane direction="vertical" {
+                    pane split_size="30%"
+                    children
+                }
+            }
+        }
+    "#;
+    let formatted = crate::kdl::format_layout(kdl_layout).unwrap();
+    assert!(formatted.contains("default_tab_template"), "{}", formatted);
+    assert!(
+        formatted.contains("split_direction=\"vertical\""),
+        "{}",
+        formatted
+    );
+    assert!(formatted.contains("size=\"30%\""), "{}", formatted);
+    assert!(!formatted.contains("split_size"), "{}", formatted);
+    let (_layout, diagnostics) = parse_leniently(&formatted);
+    assert!(diagnostics.iter().all(|d| d.code != "deprecated-name"));
+}
+
+fn run_commands(positions: &[(TiledPaneLayout, PaneGeom)]) -> Vec<Option<String>> {
+    positions
+        .iter()
+        .map(|(pane_layout, _geom)| match &pane_layout.run {
+            Some(Run::Command(run_command)) => Some(run_command.command.display().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn authored_empty_containers_are_normalized_into_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane command="htop"
+                pane split_direction="vertical" command="vim" { }
+                pane split_direction="vertical" { }
+                pane command="tail"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_panes = layout.new_tab().0;
+    for empty_container in &tiled_panes.children[0].children[1..3] {
+        assert_eq!(
+            empty_container.children_split_direction,
+            SplitDirection::default()
+        );
+    }
+    let space = viewport(120, 40);
+    assert_run_instructions_match_positions(&tiled_panes, &space, None);
+    let positions = tiled_panes.position_panes_in_space(&space, None).unwrap();
+    assert_tiles_exactly(&positions, &space);
+    assert_eq!(
+        run_commands(&positions),
+        vec![
+            Some("htop".to_owned()),
+            Some("vim".to_owned()),
+            None,
+            Some("tail".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn containers_emptied_by_truncation_keep_their_commands_in_place() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane command="htop"
+                pane {
+                    pane command="git"
+                    pane command="tail"
+                }
+                pane split_direction="vertical" {
+                    pane command="top"
+                    pane command="less"
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_panes = layout.new_tab().0;
+    let space = viewport(120, 40);
+    for max_panes in 1..=5 {
+        assert_run_instructions_match_positions(&tiled_panes, &space, Some(max_panes));
+    }
+    let fitted_layout = tiled_panes.fitted_to_pane_count(&space, Some(3), FocusPolicy::default());
+    let truncated_container = &fitted_layout.children[0].children[2];
+    assert!(truncated_container.children.is_empty());
+    assert_eq!(
+        truncated_container.children_split_direction,
+        SplitDirection::default()
+    );
+    let positions = tiled_panes
+        .position_panes_in_space(&space, Some(3))
+        .unwrap();
+    assert_tiles_exactly(&positions, &space);
+    assert_eq!(
+        run_commands(&positions),
+        vec![Some("htop".to_owned()), None, None]
+    );
+}
+
+#[test]
+fn stacked_empty_containers_are_laid_out_as_single_panes() {
+    let mut tiled_panes = TiledPaneLayout {
+        children_split_direction: SplitDirection::Vertical,
+        children: vec![
+            TiledPaneLayout::default(),
+            TiledPaneLayout {
+                children_are_stacked: true,
+                ..Default::default()
+            },
+            TiledPaneLayout {
+                children_are_stacked: true,
+                children: vec![TiledPaneLayout::default(); 3],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let space = viewport(120, 40);
+    // the stack emptied by truncation
+    assert_run_instructions_match_positions(&tiled_panes, &space, Some(3));
+    let fitted_layout = tiled_panes.fitted_to_pane_count(&space, Some(3), FocusPolicy::default());
+    assert!(fitted_layout
+        .children
+        .iter()
+        .all(|child| child.children.is_empty() && !child.children_are_stacked));
+    // the stack that was authored empty
+    tiled_panes.normalize_empty_containers();
+    assert!(!tiled_panes.children[1].children_are_stacked);
+    assert!(tiled_panes.children[2].children_are_stacked);
+    assert_run_instructions_match_positions(&tiled_panes, &space, None);
+    let positions = tiled_panes.position_panes_in_space(&space, None).unwrap();
+    assert_eq!(positions.len(), 5);
+    assert!(!positions[1].1.is_stacked);
+    assert_tiles_exactly(&positions, &space);
+}
+
+#[test]
+fn run_instructions_are_indexed_like_the_positioned_panes() {
+    let kdl_layouts = [
+        r#"
+        layout {
+            pane split_direction="vertical" {
+                pane name="editor" command="vim"
+                pane {
+                    pane command="git"
+                    pane name="logs" command="tail"
+                }
+            }
+        }
+        "#,
+        r#"
+        layout {
+            pane split_direction="vertical" stack_below_width=100 {
+                pane name="left" command="htop"
+                pane {
+                    pane command="git"
+                    pane split_direction="vertical" {
+                        pane name="deep" command="top"
+                        pane command="less"
+                    }
+                }
+                pane command="tail"
+            }
+        }
+        "#,
+        r#"
+        layout {
+            pane stacked=true {
+                pane name="first" command="htop"
+                pane command="git"
+                pane command="top"
+                pane name="last" command="tail"
+            }
+            pane command="less"
+        }
+        "#,
+        r#"
+        layout {
+            pane split_direction="vertical" {
+                pane command="htop"
+                children
+                pane command="tail"
+            }
+        }
+        "#,
+    ];
+    let spaces = [viewport(120, 40), viewport(80, 40), viewport(120, 6)];
+    for kdl_layout in kdl_layouts.iter() {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+        let tiled_panes = layout.new_tab().0;
+        for space in spaces.iter() {
+            assert_run_instructions_match_positions(&tiled_panes, space, None);
+            for max_panes in 1..=7 {
+                assert_run_instructions_match_positions(&tiled_panes, space, Some(max_panes));
+            }
+        }
+    }
+}
+
+#[test]
+fn panes_collapsed_into_a_stack_keep_their_leaf_paths() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" stack_below_width=100 {
+                pane command="htop"
+                pane {
+                    pane command="git"
+                    pane name="logs" command="tail"
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_panes = layout.new_tab().0;
+    let expected_leaf_paths = vec![vec![0, 0], vec![0, 1, 0], vec![0, 1, 1]];
+    let run_instructions = tiled_panes.extract_run_instructions_indexed();
+    assert_eq!(
+        run_instructions
+            .iter()
+            .map(|run_instruction| run_instruction.leaf_path.clone())
+            .collect::<Vec<_>>(),
+        expected_leaf_paths
+    );
+    assert_eq!(run_instructions[2].pane_name, Some("logs".to_owned()));
+    for space in [viewport(120, 40), viewport(80, 40)].iter() {
+        let positions = tiled_panes
+            .position_panes_in_space_indexed(space, None, FocusPolicy::default())
+            .unwrap();
+        assert_eq!(
+            positions
+                .iter()
+                .map(|(_pane_layout, _pane_geom, leaf_path)| leaf_path.clone())
+                .collect::<Vec<_>>(),
+            expected_leaf_paths
+        );
+    }
+    assert_eq!(
+        tiled_panes.extract_run_instructions(),
+        run_instructions
+            .into_iter()
+            .map(|run_instruction| run_instruction.run)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn floating_pane_sizes_can_fit_their_content() {
+    let kdl_layout = r#"
+        layout {
+            floating_panes {
+                pane command="cal" height="fit" max_height=20 width=30
+                pane command="ls" width="fit" min_width=50 {
+                    height "fit"
+                    min_height 4
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let floating_panes = layout.template.unwrap().1;
+    assert_eq!(
+        floating_panes[0].height,
+        Some(PercentOrFixed::Fixed(FitToContent::INITIAL_ROWS))
+    );
+    assert_eq!(
+        floating_panes[0].height_fit,
+        Some(FitToContent {
+            min: None,
+            max: Some(20),
+        })
+    );
+    assert_eq!(floating_panes[0].width, Some(PercentOrFixed::Fixed(30)));
+    assert_eq!(floating_panes[0].width_fit, None);
+    assert_eq!(
+        floating_panes[1].width,
+        Some(PercentOrFixed::Fixed(50)),
+        "the initial size respects the bounds"
+    );
+    assert_eq!(
+        floating_panes[1].width_fit,
+        Some(FitToContent {
+            min: Some(50),
+            max: None,
+        })
+    );
+    assert_eq!(
+        floating_panes[1].height_fit,
+        Some(FitToContent {
+            min: Some(4),
+            max: None,
+        })
+    );
+}
+
+#[test]
+fn fit_bounds_are_only_allowed_with_a_consistent_fit() {
+    for kdl_layout in [
+        r#"layout { floating_panes { pane command="cal" height=10 min_height=5; }; }"#,
+        r#"layout { floating_panes { pane command="cal" min_width=20; }; }"#,
+        r#"layout { floating_panes { pane command="cal" height="fit" min_height=20 max_height=10; }; }"#,
+        r#"layout { floating_panes { pane command="cal" width="fit" max_width="50%"; }; }"#,
+    ] {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_err(), "{} should not parse", kdl_layout);
+    }
+}
+
+#[test]
+fn floating_pane_sizes_can_have_a_max() {
+    let pane = floating_pane_with("width=120 max_width=\"90%\" height=\"50%\" max_height=10");
+    assert_eq!(pane.width, Some(PercentOrFixed::Fixed(120)));
+    assert_eq!(pane.max_width, Some(PercentOrFixed::Percent(90)));
+    assert_eq!(pane.max_height, Some(PercentOrFixed::Fixed(10)));
+    assert_eq!(pane.resolve_size((80, 40), (40, 20)), (72, 10));
+    assert_eq!(pane.resolve_size((200, 10), (40, 20)), (120, 5));
+    // the max of a "fit" size is one of its bounds
+    let fit_pane = floating_pane_with("command=\"cal\" height=\"fit\" max_height=20");
+    assert_eq!(fit_pane.max_height, None);
+    assert_eq!(fit_pane.height_fit.and_then(|fit| fit.max), Some(20));
+}
+
+#[test]
+fn fixed_floating_pane_sizes_larger_than_the_screen_leave_a_margin() {
+    let pane = floating_pane_with("width=120 height=50");
+    assert_eq!(pane.resolve_size((80, 24), (40, 12)), (78, 22));
+    assert_eq!(pane.resolve_size((120, 50), (40, 12)), (120, 50));
+    // the declared sizes are kept for the layout to be dumped as it was written
+    assert_eq!(pane.width, Some(PercentOrFixed::Fixed(120)));
+    let pane_without_size = floating_pane_with("x=1");
+    assert_eq!(pane_without_size.resolve_size((80, 24), (40, 12)), (40, 12));
+}
+
+#[test]
+fn max_floating_pane_sizes_are_serialized() {
+    let kdl_layout = r#"
+        layout {
+            floating_panes {
+                pane width=120 max_width="90%" max_height=10
+                pane command="cal" height="fit" max_height=20
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(reparsed.new_tab().1, layout.new_tab().1, "{}", serialized);
+}
+
+#[test]
+fn fit_is_ignored_with_a_warning_for_panes_not_running_a_command() {
+    let (layout, diagnostics) = parse_leniently(
+        r#"
+        layout {
+            floating_panes {
+                pane height="fit"
+                pane width="fit" {
+                    plugin location="zellij:strider"
+                }
+                pane command="cal" height="fit"
+            }
+        }
+    "#,
+    );
+    let floating_panes = layout.template.unwrap().1;
+    assert!(floating_panes[..2]
+        .iter()
+        .all(|pane| pane.height_fit.is_none() && pane.width_fit.is_none()));
+    assert!(floating_panes[2].height_fit.is_some());
+    let ignored_fits = diagnostics
+        .iter()
+        .filter(|d| d.code == "fit-ignored")
+        .count();
+    assert_eq!(ignored_fits, 2, "{:?}", diagnostics);
+}
+
+#[test]
+fn concatenated_layouts_open_the_tabs_of_each_in_order() {
+    let editor = Layout::from_kdl(
+        r#"
+        layout {
+            tab name="code" {
+                pane command="vim"
+            }
+            tab name="shell" focus=true
+            swap_tiled_layout name="editor-swap" {
+                tab {
+                    pane
+                    pane
+                }
+            }
+        }
+    "#,
+        "editor.kdl".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let services = Layout::from_kdl(
+        r#"
+        layout {
+            default_tab_template {
+                pane size=1 borderless=true {
+                    plugin location="zellij:tab-bar"
+                }
+                children
+            }
+            tab name="shell" focus=true {
+                pane command="htop"
+            }
+            tab name="shell (2)"
+            swap_tiled_layout name="services-swap" {
+                tab {
+                    pane
+                }
+            }
+        }
+    "#,
+        "services.kdl".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let layout = editor.clone().concatenate(services.clone());
+    let tab_names: Vec<Option<String>> = layout
+        .tabs()
+        .into_iter()
+        .map(|(tab_name, ..)| tab_name)
+        .collect();
+    assert_eq!(
+        tab_names,
+        vec![
+            Some("code".to_owned()),
+            Some("shell".to_owned()),
+            Some("shell (3)".to_owned()),
+            Some("shell (2)".to_owned()),
+        ],
+        "a name clashing with a tab before it is disambiguated"
+    );
+    assert_eq!(layout.tabs[2].1, services.tabs[0].1);
+    assert_eq!(layout.focused_tab_index, Some(1), "the first focus wins");
+    assert_eq!(layout.template, services.template, "the last template wins");
+    let swap_layout_names: Vec<Option<String>> = layout
+        .swap_tiled_layouts
+        .iter()
+        .map(|(_swap_layouts, name, _tags)| name.clone())
+        .collect();
+    assert_eq!(
+        swap_layout_names,
+        vec![
+            Some("editor-swap".to_owned()),
+            Some("services-swap".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn concatenated_layouts_without_tabs_open_a_tab_each() {
+    let layout = test_support::grid_2x2().concatenate(test_support::single_pane());
+    assert_eq!(layout.tabs.len(), 2);
+    assert_eq!(layout.tabs[0].1, test_support::grid_2x2().new_tab().0);
+    assert_eq!(layout.tabs[1].1, test_support::single_pane().new_tab().0);
+    assert_eq!(layout.focused_tab_index, None);
+}
+
+#[test]
+fn suspended_layouts_wait_for_their_commands_to_be_started() {
+    let mut layout = Layout::from_kdl(
+        r#"
+        layout {
+            tab {
+                pane command="htop"
+                pane {
+                    pane command="cargo" {
+                        args "watch"
+                    }
+                    pane
+                }
+                floating_panes {
+                    pane command="tail"
+                }
+            }
+            swap_floating_layout {
+                floating_panes {
+                    pane command="top"
+                }
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    layout.suspend_commands();
+    let is_suspended = |run: &Option<Run>| match run {
+        Some(Run::Command(run_command)) => run_command.hold_on_start,
+        _ => true,
+    };
+    let (_tab_name, tiled_panes, floating_panes, ..) = &layout.tabs[0];
+    let run_instructions = tiled_panes.extract_run_instructions();
+    assert_eq!(run_instructions.len(), 3);
+    assert!(run_instructions.iter().all(is_suspended));
+    assert_eq!(run_instructions[2], None, "shells are left alone");
+    assert!(is_suspended(&floating_panes[0].run));
+    let (swap_floating_layouts, _name, _tags) = &layout.swap_floating_layouts[0];
+    assert!(swap_floating_layouts
+        .values()
+        .flatten()
+        .all(|floating_pane| is_suspended(&floating_pane.run)));
+}
+
+#[test]
+fn layout_naming_its_session() {
+    let kdl_layout = r#"
+        layout {
+            session_name "acme-api"
+            attach_to_existing true
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.session_name, Some("acme-api".to_owned()));
+    assert_eq!(layout.attach_to_existing, Some(true));
+    let layout_without_session =
+        Layout::from_kdl("layout { pane; }", "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        layout.new_tab(),
+        layout_without_session.new_tab(),
+        "not parsed as panes"
+    );
+}
+
+#[test]
+fn layout_session_is_created_or_attached_to_depending_on_whether_it_runs() {
+    let layout_attaching_to_existing = |attach_to_existing| Layout {
+        session_name: Some("acme-api".to_owned()),
+        attach_to_existing,
+        ..Default::default()
+    };
+    let not_running = |_: &str| false;
+    let running = |session_name: &str| session_name == "acme-api";
+    assert_eq!(
+        layout_attaching_to_existing(Some(true)).session_start(not_running),
+        Some(LayoutSessionStart::Create("acme-api".to_owned()))
+    );
+    assert_eq!(
+        layout_attaching_to_existing(Some(true)).session_start(running),
+        Some(LayoutSessionStart::Attach("acme-api".to_owned()))
+    );
+    for attach_to_existing in [Some(false), None] {
+        assert_eq!(
+            layout_attaching_to_existing(attach_to_existing).session_start(not_running),
+            Some(LayoutSessionStart::Create("acme-api".to_owned()))
+        );
+        assert_eq!(
+            layout_attaching_to_existing(attach_to_existing).session_start(running),
+            Some(LayoutSessionStart::AlreadyRunning("acme-api".to_owned())),
+            "refused when not attaching to existing sessions"
+        );
+    }
+    assert_eq!(Layout::default().session_start(running), None);
+}
+
+#[test]
+fn attach_to_existing_needs_a_session_name() {
+    let kdl_layout = r#"
+        layout {
+            attach_to_existing true
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "error provided for attach_to_existing without a session_name"
+    );
+}
+
+#[test]
+fn concatenated_layouts_take_the_session_of_the_last_layout_naming_one() {
+    let layout_naming_its_session = |session_name: &str, attach_to_existing| Layout {
+        session_name: Some(session_name.to_owned()),
+        attach_to_existing,
+        ..Default::default()
+    };
+    let layout = layout_naming_its_session("acme-api", Some(true))
+        .concatenate(layout_naming_its_session("acme-web", None))
+        .concatenate(test_support::single_pane());
+    assert_eq!(layout.session_name, Some("acme-web".to_owned()));
+    assert_eq!(layout.attach_to_existing, None);
+}
+
+#[test]
+fn swap_layouts_focusing_a_container_focus_its_first_pane() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab {
+                    pane command="vim"
+                    pane focus=true {
+                        pane command="htop"
+                        pane command="tail"
+                    }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (swap_tiled_layout, _name, _tags) = &layout.swap_tiled_layouts[0];
+    let swap_layout = swap_tiled_layout.values().next().unwrap();
+    assert_eq!(swap_layout.focused_node_count(), 1);
+    let container = &swap_layout.children[1];
+    assert_eq!(container.focus, None);
+    assert_eq!(container.children[0].focus, Some(true));
+    assert_eq!(container.children[1].focus, None);
+}
+
+#[test]
+fn swap_layouts_can_only_focus_one_pane() {
+    let kdl_layout = r#"
+        layout {
+            swap_tiled_layout {
+                tab {
+                    pane focus=true
+                    pane {
+                        pane
+                        pane focus=true
+                    }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+    assert!(
+        layout.is_err(),
+        "error provided for a swap layout focusing two panes"
+    );
+}
+
+#[test]
+fn tabs_can_only_focus_one_pane() {
+    let kdl_layout = r#"
+        layout {
+            tab name="first" {
+                pane focus=true
+                pane
+            }
+            tab name="second" {
+                pane focus=true
+                pane {
+                    pane
+                    pane { focus true; }
+                }
+                floating_panes {
+                    pane focus=true
+                }
+            }
+        }
+    "#;
+    match Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None) {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert_eq!(
+                kdl_error.error_message,
+                "Only one pane of a tab can be focused, found 3 panes with focus=true"
+            );
+            let second_tab = kdl_layout.find("tab name=\"second\"").unwrap();
+            let first_focus = second_tab + kdl_layout[second_tab..].find("focus=true").unwrap();
+            let second_focus = kdl_layout.find("focus true").unwrap();
+            let third_focus = kdl_layout.rfind("focus=true").unwrap();
+            let offset = kdl_error.offset.unwrap();
+            assert!(
+                offset > second_focus && offset < third_focus - 1,
+                "the error points at the second focus"
+            );
+            // entry spans start where the whitespace before them does
+            let points_at = |offset: usize, focus: usize| offset <= focus && focus - offset <= 1;
+            assert_eq!(kdl_error.secondary_labels.len(), 2);
+            assert!(points_at(kdl_error.secondary_labels[0].1, first_focus));
+            assert!(points_at(kdl_error.secondary_labels[1].1, third_focus));
+        },
+        layout => panic!("expected a layout error, got: {:?}", layout),
+    }
+}
+
+#[test]
+fn panes_focused_by_their_templates_count_towards_the_focus_of_their_tab() {
+    let focused_template = r#"
+        pane_template name="editor" focus=true {
+            command "vim"
+        }
+    "#;
+    let valid_layouts = [
+        // one focused pane in each tab
+        r#"layout { tab { editor; }; tab { editor; }; }"#,
+        // the pane using the template does not take its focus
+        r#"layout { editor; editor focus=false; }"#,
+        r#"layout { tab_template name="dev" { editor; children; }; dev { pane; }; }"#,
+    ];
+    for kdl_layout in valid_layouts {
+        let kdl_layout =
+            kdl_layout.replacen("layout {", &format!("layout {{ {}", focused_template), 1);
+        let layout = Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_ok(), "expected no error for: {}", kdl_layout);
+    }
+    let invalid_layouts = [
+        r#"layout { editor; editor; }"#,
+        r#"layout { tab { pane focus=true; editor; }; }"#,
+        r#"layout { tab_template name="dev" { editor; children; }; dev { pane focus=true; }; }"#,
+        r#"layout { default_tab_template { editor; children; }; tab { pane focus=true; }; }"#,
+        r#"layout { pane_template name="split" { editor; pane; }; split; pane focus=true; }"#,
+    ];
+    for kdl_layout in invalid_layouts {
+        let kdl_layout =
+            kdl_layout.replacen("layout {", &format!("layout {{ {}", focused_template), 1);
+        let layout = Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_err(), "expected an error for: {}", kdl_layout);
+    }
+}
+
+#[test]
+fn pane_positions_cache_positions_a_layout_once_per_space() {
+    let (grid, _floating_panes) = test_support::grid_2x2().template.unwrap();
+    let mut cache = PanePositionsCache::default();
+    let space = viewport(80, 24);
+    let positions = cache
+        .position_panes_in_space_with_focus(&grid, &space, Some(4), FocusPolicy::default())
+        .unwrap();
+    assert_eq!(
+        positions,
+        grid.position_panes_in_space_with_focus(&space, Some(4), FocusPolicy::default())
+            .unwrap()
+    );
+    cache
+        .position_panes_in_space_with_focus(&grid, &space, Some(4), FocusPolicy::default())
+        .unwrap();
+    assert_eq!(
+        cache.positions.len(),
+        1,
+        "positioned again in the same space"
+    );
+    let resized = cache
+        .position_panes_in_space_with_focus(
+            &grid,
+            &viewport(100, 30),
+            Some(4),
+            FocusPolicy::default(),
+        )
+        .unwrap();
+    assert_tiles_exactly(&resized, &viewport(100, 30));
+    assert_eq!(cache.positions.len(), 2);
+}
+
+#[test]
+fn pane_positions_cache_tells_apart_layouts_hashed_the_same() {
+    let (grid, _floating_panes) = test_support::grid_2x2().template.unwrap();
+    let mut running_htop = grid.clone();
+    running_htop.children[0].children[0].children[0].run = Some(Run::Command(RunCommand {
+        command: "htop".into(),
+        ..Default::default()
+    }));
+    let layout_hash = |layout: &TiledPaneLayout| {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        layout.hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(
+        layout_hash(&grid),
+        layout_hash(&running_htop),
+        "what a pane runs does not change where it is"
+    );
+    let mut cache = PanePositionsCache::default();
+    let space = viewport(80, 24);
+    cache
+        .position_panes_in_space_with_focus(&grid, &space, Some(4), FocusPolicy::default())
+        .unwrap();
+    let positions = cache
+        .position_panes_in_space_with_focus(&running_htop, &space, Some(4), FocusPolicy::default())
+        .unwrap();
+    assert!(
+        positions[0].0.run.is_some(),
+        "the panes of the layout positioned, not those of the cached one"
+    );
+}
+
+#[test]
+fn split_sizes_can_be_weights() {
+    assert_eq!(SplitSize::from_str("2w").unwrap(), SplitSize::Weight(2));
+    assert!(SplitSize::from_str("0w").is_err(), "a weight of 0");
+    assert!(
+        SplitSize::from_str("w").is_err(),
+        "a weight without a number"
+    );
+    let kdl_layout = r#"
+        layout {
+            pane size="2w"
+            pane size="1w"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.template.unwrap();
+    assert_eq!(
+        tiled_panes.children[0].split_size,
+        Some(SplitSize::Weight(2))
+    );
+    assert_eq!(
+        tiled_panes.children[1].split_size,
+        Some(SplitSize::Weight(1))
+    );
+}
+
+fn vertical_split_widths(kdl_layout: &str, cols: usize) -> Vec<usize> {
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.template.unwrap();
+    let space = viewport(cols, 20);
+    let positions = tiled_panes.position_panes_in_space(&space, None).unwrap();
+    assert_tiles_exactly(&positions, &space);
+    positions
+        .iter()
+        .map(|(_pane_layout, geom)| geom.cols.as_usize())
+        .collect()
+}
+
+#[test]
+fn weighted_panes_split_the_space_in_proportion_to_their_weights() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size="2w"
+                pane size="1w"
+                pane size="1w"
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![50, 25, 25]);
+}
+
+#[test]
+fn panes_without_a_size_have_a_weight_of_1() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size="2w"
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 90), vec![60, 30]);
+}
+
+#[test]
+fn weighted_panes_share_the_space_left_by_fixed_and_percent_sizes() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size=10
+                pane size="50%"
+                pane size="3w"
+                pane
+            }
+        }
+    "#;
+    // the percent is of the 90 columns the fixed pane leaves, the weights share the other half
+    // of them 3:1 (33.75 and 11.25 columns)
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![10, 45, 33, 12]);
+}
+
+#[test]
+fn weights_that_do_not_divide_the_space_evenly_still_fill_it() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size="1w"
+                pane size="1w"
+                pane size="1w"
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![33, 33, 34]);
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size="2w"
+                pane size="1w"
+                pane size="1w"
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 10), vec![5, 2, 3]);
+}
+
+#[test]
+fn weights_cannot_be_changed_by_a_relative_amount() {
+    assert!(SizeAdjustment::from_str("+10%")
+        .unwrap()
+        .apply(Some(SplitSize::Weight(2)))
+        .is_err());
+    assert_eq!(
+        SizeAdjustment::from_str("3w")
+            .unwrap()
+            .apply(Some(SplitSize::Weight(2))),
+        Ok(SplitSize::Weight(3))
+    );
+}
+
+#[test]
+fn a_pane_does_not_shrink_below_its_min_size() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane min_size=40
+                pane
+                pane
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![40, 20, 20, 20]);
+    assert_eq!(vertical_split_widths(kdl_layout, 200), vec![50, 50, 50, 50]);
+}
+
+#[test]
+fn a_pane_does_not_grow_past_its_max_size() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane max_size="25%"
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![25, 75]);
+    assert_eq!(vertical_split_widths(kdl_layout, 40), vec![10, 30]);
+}
+
+#[test]
+fn size_bounds_leave_fixed_size_siblings_alone() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size=10
+                pane min_size=60
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![10, 60, 30]);
+}
+
+#[test]
+fn the_children_of_a_bounded_pane_share_its_bounded_size() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane min_size=60 split_direction="vertical" {
+                    pane
+                    pane
+                }
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![30, 30, 40]);
+}
+
+#[test]
+fn size_bounds_are_ignored_in_stacks() {
+    let layout = TiledPaneLayout {
+        children_are_stacked: true,
+        children: vec![
+            TiledPaneLayout {
+                min_size: Some(SplitSize::Fixed(10)),
+                ..Default::default()
+            },
+            TiledPaneLayout::default(),
+            TiledPaneLayout::default(),
+        ],
+        ..Default::default()
+    };
+    let space = viewport(100, 20);
+    let positions = layout.position_panes_in_space(&space, None).unwrap();
+    let rows: Vec<usize> = positions
+        .iter()
+        .map(|(_pane_layout, geom)| geom.rows.as_usize())
+        .collect();
+    assert_eq!(rows, vec![1, 1, 18]);
+}
+
+#[test]
+fn size_bounds_that_cannot_be_satisfied_are_an_error() {
+    let positions_of = |kdl_layout: &str| {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+        let (tiled_panes, _floating_panes) = layout.template.unwrap();
+        tiled_panes
+            .position_panes_in_space(&viewport(100, 20), None)
+            .map(|positions| positions.len())
+    };
+    let min_sizes_too_large = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane min_size=70
+                pane min_size=40
+            }
+        }
+    "#;
+    assert!(positions_of(min_sizes_too_large).is_err());
+    let min_size_larger_than_the_screen = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane min_size=120
+                pane
+            }
+        }
+    "#;
+    assert!(positions_of(min_size_larger_than_the_screen).is_err());
+    let max_sizes_too_small = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane max_size=10
+                pane max_size="20%"
+            }
+        }
+    "#;
+    assert!(positions_of(max_sizes_too_small).is_err());
+}
+
+#[test]
+fn size_bounds_are_fixed_sizes_or_percents() {
+    let parse = |kdl_layout: &str| {
+        Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).map(|_| ())
+    };
+    assert!(parse("layout { pane min_size=30 max_size=\"50%\"; pane; }").is_ok());
+    assert!(parse("layout { pane min_size=\"2w\"; pane; }").is_err());
+    assert!(parse("layout { pane max_size=0; pane; }").is_err());
+    assert!(parse("layout { pane min_size=40 max_size=30; pane; }").is_err());
+}
+
+#[test]
+fn paths_under_the_old_root_are_rebased() {
+    let kdl_layout = r#"
+        layout {
+            pane cwd="/home/alice/work/api"
+            pane command="cargo" cwd="/home/alice/work"
+            pane edit="/home/alice/work/notes.md"
+            pane {
+                plugin location="file:/home/alice/plugins/tree.wasm"
+            }
+            pane cwd="/home/alice-old/work"
+            pane cwd="/home/al/work"
+            pane cwd="relative/home/alice"
+            floating_panes {
+                pane cwd="/home/alice"
+            }
+        }
+    "#;
+    let mut layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    layout.rebase_paths(Path::new("/home/alice"), Path::new("/Users/alice"));
+    let (tiled_panes, floating_panes) = layout.new_tab();
+    let runs: Vec<Option<Run>> = tiled_panes
+        .children
+        .iter()
+        .map(|pane| pane.run.clone())
+        .collect();
+    let cwd = |path: &str| Some(Run::Cwd(PathBuf::from(path)));
+    assert_eq!(runs[0], cwd("/Users/alice/work/api"));
+    match &runs[1] {
+        Some(Run::Command(run_command)) => {
+            assert_eq!(run_command.cwd, Some(PathBuf::from("/Users/alice/work")));
+            assert_eq!(run_command.command, PathBuf::from("cargo"));
+        },
+        other => panic!("expected a command, got {:?}", other),
+    }
+    assert_eq!(
+        runs[2],
+        Some(Run::EditFile(
+            vec![PathBuf::from("/Users/alice/work/notes.md")],
+            None,
+            None
+        ))
+    );
+    match &runs[3] {
+        Some(Run::Plugin(run_plugin)) => assert_eq!(
+            run_plugin.location,
+            RunPluginLocation::File(PathBuf::from("/Users/alice/plugins/tree.wasm"))
+        ),
+        other => panic!("expected a plugin, got {:?}", other),
+    }
+    assert_eq!(
+        runs[4],
+        cwd("/home/alice-old/work"),
+        "a partial match of the last component"
+    );
+    assert_eq!(runs[5], cwd("/home/al/work"), "not under the old root");
+    assert_eq!(runs[6], cwd("relative/home/alice"), "a relative path");
+    assert_eq!(
+        floating_panes[0].run,
+        cwd("/Users/alice"),
+        "the old root itself"
+    );
+}
+
+#[test]
+fn paths_under_the_old_root_by_symlink_are_not_rebased() {
+    let root = tempfile::tempdir().unwrap();
+    let work = root.path().join("work");
+    let linked_work = root.path().join("linked-work");
+    std::fs::create_dir_all(work.join("api")).unwrap();
+    std::os::unix::fs::symlink(&work, &linked_work).unwrap();
+    let new_root = Path::new("/srv/work");
+    let mut run = Run::Cwd(linked_work.join("api"));
+    run.rebase_paths(&work, new_root);
+    assert_eq!(
+        run,
+        Run::Cwd(linked_work.join("api")),
+        "reached through a link"
+    );
+    let mut run = Run::Cwd(linked_work.join("../work/api"));
+    run.rebase_paths(&work, new_root);
+    assert_eq!(
+        run,
+        Run::Cwd(linked_work.join("../work/api")),
+        "reached through .."
+    );
+    let mut run = Run::Cwd(work.join("api"));
+    run.rebase_paths(&work, new_root);
+    assert_eq!(run, Run::Cwd(new_root.join("api")));
+}
+
+#[test]
+fn rebasing_the_paths_of_a_layout_file_keeps_its_comments() {
+    let kdl_layout = r#"
+        layout {
+            // the api server
+            pane cwd="/home/alice/work/api" command="cargo" {
+                args "run"
+            }
+            pane {
+                cwd "/home/alice/work/web" // the frontend
+                edit "/home/alice-old/todo.md"
+            }
+            pane {
+                plugin location="file:/home/alice/plugins/tree.wasm"
+            }
+            pane cwd="/opt/tools"
+        }
+    "#;
+    let rebased = crate::kdl::rebase_layout_paths(
+        kdl_layout,
+        Path::new("/home/alice"),
+        Path::new("/Users/alice"),
+    )
+    .unwrap();
+    assert_eq!(
+        rebased,
+        kdl_layout.replace("/home/alice/", "/Users/alice/"),
+        "only the paths under the old root changed"
+    );
+    assert!(rebased.contains("// the api server"));
+    assert!(rebased.contains("/home/alice-old/todo.md"));
+}
+
+#[test]
+fn bundled_layouts_are_serialized_into_layout_files_parsing_into_the_same_layouts() {
+    for layout_name in ["default", "strider", "disable-status-bar", "compact"] {
+        let (layout_path, raw_layout, raw_swap_layouts) =
+            Layout::stringified_from_default_assets(&PathBuf::from(layout_name)).unwrap();
+        let layout = Layout::from_kdl(
+            &raw_layout,
+            layout_path,
+            raw_swap_layouts
+                .as_ref()
+                .map(|(swap_path, raw_swap)| (swap_path.as_str(), raw_swap.as_str())),
+            None,
+        )
+        .unwrap();
+        let serialized = layout.to_kdl_string();
+        let reparsed = Layout::from_str(
+            &serialized,
+            "serialized".into(),
+            None,
+            None,
+            &Default::default(),
+        )
+        .unwrap_or_else(|e| panic!("{} serialized into:\n{}\n{:?}", layout_name, serialized, e));
+        assert_eq!(
+            reparsed, layout,
+            "{} serialized into:\n{}",
+            layout_name, serialized
+        );
+    }
+    for (layout_name, layout) in test_support::all_layouts() {
+        if layout_name == "stacked" {
+            // its stack is set after parsing, layout files can only declare stacks in `children`
+            continue;
+        }
+        let serialized = layout.to_kdl_string();
+        let reparsed = Layout::from_str(
+            &serialized,
+            "serialized".into(),
+            None,
+            None,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            reparsed, layout,
+            "{} serialized into:\n{}",
+            layout_name, serialized
+        );
+    }
+}
+
+#[test]
+fn serialized_layouts_keep_what_their_panes_run_and_where() {
+    let kdl_layout = r#"
+        layout {
+            tab name="code" {
+                pane split_direction="vertical" {
+                    pane size="30%" command="cargo" cwd="/tmp/project" close_on_exit=true {
+                        args "watch" "-x" "test"
+                    }
+                    pane size="2w" edit="/tmp/project/README.md" borderless=true
+                    pane {
+                        plugin location="zellij:strider"
+                    }
+                }
+                floating_panes {
+                    pane x=0 y="10%" width=40 height="50%" command="htop" focus=true
+                }
+            }
+            tab name="shell" focus=true split_direction="vertical" {
+                pane cwd="/tmp" name="left"
+                pane {
+                    pane
+                    children stacked=true
+                }
+            }
+            swap_tiled_layout name="wide" {
+                tab min_panes=3 extra_panes="new_stack" {
+                    pane
+                    pane focus=true
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+    assert_eq!(reparsed.focused_tab_index, Some(1));
+}
+
+fn included_layout_error_message(error: crate::input::config::ConfigError) -> String {
+    match error {
+        crate::input::config::ConfigError::KdlError(kdl_error) => kdl_error.error_message,
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn layouts_use_the_templates_of_the_files_they_include() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(root.path().join("shared")).unwrap();
+    std::fs::create_dir_all(root.path().join("project")).unwrap();
+    std::fs::write(
+        root.path().join("shared/editors.kdl"),
+        "pane_template name=\"editor\" {\n    command \"hx\"\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.path().join("shared/templates.kdl"),
+        r#"
+            include "editors.kdl"
+            tab_template name="code" {
+                editor
+                children
+            }
+        "#,
+    )
+    .unwrap();
+    let layout_path = root.path().join("project/layout.kdl");
+    std::fs::write(
+        &layout_path,
+        r#"
+            layout {
+                include "../shared/templates.kdl"
+                code name="main" {
+                    pane command="htop"
+                }
+            }
+        "#,
+    )
+    .unwrap();
+    let (layout, _config, layout_sources) = Layout::from_path_or_default(
+        Some(&layout_path),
+        None,
+        Default::default(),
+        &Default::default(),
+    )
+    .unwrap();
+    let commands: Vec<std::path::PathBuf> = layout.tabs[0]
+        .1
+        .extract_run_instructions()
+        .into_iter()
+        .filter_map(|run| match run {
+            Some(Run::Command(run_command)) => Some(run_command.command),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        commands,
+        vec![
+            std::path::PathBuf::from("hx"),
+            std::path::PathBuf::from("htop")
+        ]
+    );
+    assert_eq!(
+        layout_sources.files.len(),
+        3,
+        "the included files are trusted (or not) along with the layout"
+    );
+}
+
+#[test]
+fn included_files_are_looked_up_in_the_layout_dir_after_the_including_one() {
+    let root = tempfile::tempdir().unwrap();
+    let layout_dir = root.path().join("layouts");
+    std::fs::create_dir_all(&layout_dir).unwrap();
+    std::fs::write(
+        layout_dir.join("templates.kdl"),
+        "pane_template name=\"editor\" {\n    command \"hx\"\n}\n",
+    )
+    .unwrap();
+    let layout_path = root.path().join("layout.kdl");
+    std::fs::write(
+        &layout_path,
+        "layout {\n    include \"templates.kdl\"\n    editor\n}\n",
+    )
+    .unwrap();
+    let (layout, _config, _layout_sources) = Layout::from_path_or_default(
+        Some(&layout_path),
+        Some(layout_dir),
+        Default::default(),
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(layout.command_count(), 1);
+    let error = Layout::from_path_or_default(
+        Some(&layout_path),
+        None,
+        Default::default(),
+        &Default::default(),
+    )
+    .unwrap_err();
+    assert!(included_layout_error_message(error).contains("Could not find the included file"));
+}
+
+#[test]
+fn include_cycles_are_refused_naming_both_files() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(
+        root.path().join("first.kdl"),
+        "include \"second.kdl\"\npane_template name=\"editor\" {\n    command \"hx\"\n}\n",
+    )
+    .unwrap();
+    std::fs::write(root.path().join("second.kdl"), "include \"first.kdl\"\n").unwrap();
+    let layout_path = root.path().join("layout.kdl");
+    std::fs::write(&layout_path, "layout {\n    include \"first.kdl\"\n}\n").unwrap();
+    let error = Layout::from_path_or_default(
+        Some(&layout_path),
+        None,
+        Default::default(),
+        &Default::default(),
+    )
+    .unwrap_err();
+    let error_message = included_layout_error_message(error);
+    assert!(error_message.contains("Include cycle"), "{}", error_message);
+    assert!(error_message.contains("first.kdl"), "{}", error_message);
+    assert!(error_message.contains("second.kdl"), "{}", error_message);
+}
+
+#[test]
+fn includes_cannot_nest_deeper_than_the_maximum() {
+    let root = tempfile::tempdir().unwrap();
+    let include_files = |count: usize| {
+        for i in 0..count {
+            let next_include = if i + 1 < count {
+                format!("include \"{}.kdl\"\n", i + 1)
+            } else {
+                String::new()
+            };
+            std::fs::write(root.path().join(format!("{}.kdl", i)), next_include).unwrap();
+        }
+    };
+    let layout_path = root.path().join("layout.kdl");
+    std::fs::write(&layout_path, "layout {\n    include \"0.kdl\"\n}\n").unwrap();
+    include_files(crate::kdl::MAX_INCLUDE_DEPTH);
+    assert!(Layout::from_path_or_default(
+        Some(&layout_path),
+        None,
+        Default::default(),
+        &Default::default()
+    )
+    .is_ok());
+    include_files(crate::kdl::MAX_INCLUDE_DEPTH + 1);
+    let error = Layout::from_path_or_default(
+        Some(&layout_path),
+        None,
+        Default::default(),
+        &Default::default(),
+    )
+    .unwrap_err();
+    assert!(included_layout_error_message(error).contains("levels deep"));
+}
+
+#[test]
+fn included_files_can_only_define_templates() {
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("panes.kdl"), "pane command=\"htop\"\n").unwrap();
+    let layout_path = root.path().join("layout.kdl");
+    std::fs::write(&layout_path, "layout {\n    include \"panes.kdl\"\n}\n").unwrap();
+    let error = Layout::from_path_or_default(
+        Some(&layout_path),
+        None,
+        Default::default(),
+        &Default::default(),
+    )
+    .unwrap_err();
+    assert!(included_layout_error_message(error).contains("only templates and includes"));
+}
+
+#[test]
+fn serialized_layouts_keep_all_the_files_of_edit_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane edit="/tmp/notes.md"
+            pane {
+                edit "/tmp/a.rs" "/tmp/b.rs" editor_splits="horizontal"
+            }
+            pane {
+                edit "/tmp/c.rs" "/tmp/d.rs"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn yaml_layouts_are_refused_with_a_way_to_convert_them() {
+    let yaml_layout =
+        "---\ntemplate:\n  direction: Horizontal\n  parts:\n    - direction: Vertical\n";
+    let root = tempfile::tempdir().unwrap();
+    let layout_path = root.path().join("old-layout.kdl");
+    std::fs::write(&layout_path, yaml_layout).unwrap();
+    match Layout::stringified_from_path(&layout_path) {
+        Err(crate::input::config::ConfigError::YamlLayout(path)) => {
+            assert_eq!(path, layout_path)
+        },
+        other => panic!("expected a YAML layout error, got {:?}", other),
+    }
+    let yaml_path = root.path().join("layout.yml");
+    std::fs::write(&yaml_path, "layout {\n    pane\n}\n").unwrap();
+    assert!(matches!(
+        Layout::stringified_from_path(&yaml_path),
+        Err(crate::input::config::ConfigError::YamlLayout(_))
+    ));
+    let error = Layout::from_str(
+        "tabs:\n  - direction: Vertical\n",
+        "pasted".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("zellij convert-layout pasted"));
+    assert!(!is_yaml_layout("// ---\nlayout {\n    pane\n}\n", None));
+}
+
+#[test]
+fn available_layouts_are_the_layout_dir_files_and_the_built_in_layouts_they_do_not_shadow() {
+    let layout_dir = tempfile::tempdir().unwrap();
+    for file_name in [
+        "default.kdl",
+        "work.kdl",
+        "work.swap.kdl",
+        "orphan.swap.kdl",
+        "notes.txt",
+    ] {
+        std::fs::write(layout_dir.path().join(file_name), "not read").unwrap();
+    }
+    std::fs::create_dir(layout_dir.path().join("directory.kdl")).unwrap();
+    let available_layouts = Layout::list_available(Some(&layout_dir.path().to_path_buf()));
+    let built_in = |name: &str, has_swap_layouts: bool| LayoutInfo {
+        name: name.into(),
+        source: LayoutInfoSource::BuiltIn,
+        has_swap_layouts,
+    };
+    assert_eq!(
+        available_layouts,
+        vec![
+            built_in("compact", true),
+            LayoutInfo {
+                name: "default".into(),
+                source: LayoutInfoSource::File(layout_dir.path().join("default.kdl")),
+                has_swap_layouts: false,
+            },
+            built_in("disable-status-bar", false),
+            built_in("strider", true),
+            LayoutInfo {
+                name: "work".into(),
+                source: LayoutInfoSource::File(layout_dir.path().join("work.kdl")),
+                has_swap_layouts: true,
+            },
+        ]
+    );
+    let built_in_layouts = Layout::list_available(None);
+    assert_eq!(built_in_layouts.len(), 4);
+    for layout_info in built_in_layouts {
+        let (_path_to_layout, _raw_layout, swap_layouts) =
+            Layout::stringified_from_default_assets(Path::new(&layout_info.name)).unwrap();
+        assert_eq!(swap_layouts.is_some(), layout_info.has_swap_layouts);
+    }
+}
+
+#[test]
+fn unknown_builtin_layouts_are_an_error_listing_the_builtin_ones() {
+    let error = Layout::stringified_from_default_assets(Path::new("no-such-layout")).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("the built-in layouts are: compact, default, disable-status-bar, strider"),
+        "{}",
+        error
+    );
+}
+
+fn tiled_pane_with(properties: &str) -> TiledPaneLayout {
+    let kdl_layout = format!("layout {{ pane {}; pane; }}", properties);
+    let layout = Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    layout.template.unwrap().0.children.remove(0)
+}
+
+fn floating_pane_with(properties: &str) -> FloatingPaneLayout {
+    let kdl_layout = format!("layout {{ floating_panes {{ pane {}; }}; }}", properties);
+    let layout = Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    layout.template.unwrap().1.remove(0)
+}
+
+// the ways `name` can be given `value` in a pane: as a bare or a quoted property or child
+fn size_spellings(name: &str, value: &str) -> Vec<String> {
+    let mut spellings = vec![
+        format!("{}=\"{}\"", name, value),
+        format!("{{ {} \"{}\"; }}", name, value),
+    ];
+    if !value.ends_with('%') {
+        spellings.push(format!("{}={}", name, value));
+        spellings.push(format!("{{ {} {}; }}", name, value));
+    }
+    spellings
+}
+
+#[test]
+fn every_spelling_of_a_size_parses_to_the_same_value() {
+    for fixed in [1, 7, 100, 250] {
+        for spelling in size_spellings("size", &fixed.to_string()) {
+            let pane = tiled_pane_with(&spelling);
+            assert_eq!(
+                pane.split_size,
+                Some(SplitSize::Fixed(fixed)),
+                "{}",
+                spelling
+            );
+        }
+        for spelling in size_spellings("min_size", &fixed.to_string()) {
+            let pane = tiled_pane_with(&spelling);
+            assert_eq!(pane.min_size, Some(SplitSize::Fixed(fixed)), "{}", spelling);
+        }
+        for name in ["x", "y", "width", "height"] {
+            for spelling in size_spellings(name, &fixed.to_string()) {
+                let pane = floating_pane_with(&spelling);
+                let value = match name {
+                    "x" => pane.x,
+                    "y" => pane.y,
+                    "width" => pane.width,
+                    _ => pane.height,
+                };
+                assert_eq!(value, Some(PercentOrFixed::Fixed(fixed)), "{}", spelling);
+            }
+        }
+    }
+    for percent in [1, 33, 100] {
+        let value = format!("{}%", percent);
+        for spelling in size_spellings("size", &value) {
+            let pane = tiled_pane_with(&spelling);
+            assert_eq!(
+                pane.split_size,
+                Some(SplitSize::Percent(percent)),
+                "{}",
+                spelling
+            );
+        }
+        for spelling in size_spellings("max_size", &value) {
+            let pane = tiled_pane_with(&spelling);
+            assert_eq!(
+                pane.max_size,
+                Some(SplitSize::Percent(percent)),
+                "{}",
+                spelling
+            );
+        }
+        for spelling in size_spellings("x", &value) {
+            let pane = floating_pane_with(&spelling);
+            assert_eq!(
+                pane.x,
+                Some(PercentOrFixed::Percent(percent)),
+                "{}",
+                spelling
+            );
+        }
+    }
+}
+
+#[test]
+fn fit_bounds_can_be_quoted_numbers() {
+    let pane = floating_pane_with("command=\"htop\" width=\"fit\" min_width=\"20\" max_width=40");
+    assert_eq!(
+        pane.width_fit,
+        Some(FitToContent {
+            min: Some(20),
+            max: Some(40),
+        })
+    );
+}
+
+#[test]
+fn sizes_that_are_not_positive_are_rejected_in_every_spelling() {
+    let invalid_panes = [
+        "pane size=0",
+        "pane size=\"0\"",
+        "pane size=-5",
+        "pane size=\"-5\"",
+        "pane { size -5; }",
+        "pane min_size=-1",
+        "floating_panes { pane width=0; }",
+        "floating_panes { pane width=\"0\"; }",
+        "floating_panes { pane width=-5; }",
+        "floating_panes { pane height=\"-10%\"; }",
+        "floating_panes { pane x=\"--1\"; }",
+        "floating_panes { pane { y 1.5; }; }",
+        "floating_panes { pane command=\"htop\" width=\"fit\" max_width=\"-1\"; }",
+    ];
+    for invalid_pane in invalid_panes {
+        let kdl_layout = format!("layout {{ {}; }}", invalid_pane);
+        assert!(
+            Layout::from_kdl(&kdl_layout, "layout_file_name".into(), None, None).is_err(),
+            "{}",
+            invalid_pane
+        );
+    }
+}
+
+#[test]
+fn percent_or_fixed_and_split_size_read_each_others_json() {
+    for json in [r#"{"Percent":30}"#, r#"{"percent":30}"#] {
+        assert_eq!(
+            serde_json::from_str::<PercentOrFixed>(json).unwrap(),
+            PercentOrFixed::Percent(30)
+        );
+        assert_eq!(
+            serde_json::from_str::<SplitSize>(json).unwrap(),
+            SplitSize::Percent(30)
+        );
+    }
+    for json in [r#"{"Fixed":4}"#, r#"{"fixed":4}"#] {
+        assert_eq!(
+            serde_json::from_str::<PercentOrFixed>(json).unwrap(),
+            PercentOrFixed::Fixed(4)
+        );
+        assert_eq!(
+            serde_json::from_str::<SplitSize>(json).unwrap(),
+            SplitSize::Fixed(4)
+        );
+    }
+    let split_size_json = serde_json::to_string(&SplitSize::Percent(30)).unwrap();
+    assert_eq!(
+        serde_json::from_str::<PercentOrFixed>(&split_size_json).unwrap(),
+        PercentOrFixed::Percent(30)
+    );
+    let percent_or_fixed_json = serde_json::to_string(&PercentOrFixed::Fixed(4)).unwrap();
+    assert_eq!(
+        serde_json::from_str::<SplitSize>(&percent_or_fixed_json).unwrap(),
+        SplitSize::Fixed(4)
+    );
+}
+
+#[test]
+fn percent_or_fixed_converts_to_and_from_split_size() {
+    for percent_or_fixed in [PercentOrFixed::Percent(25), PercentOrFixed::Fixed(12)] {
+        let split_size = SplitSize::try_from(percent_or_fixed.clone()).unwrap();
+        assert_eq!(PercentOrFixed::try_from(split_size), Ok(percent_or_fixed));
+    }
+    assert!(PercentOrFixed::try_from(SplitSize::Weight(2)).is_err());
+    assert!(SplitSize::try_from(PercentOrFixed::FixedFromFarEdge(10)).is_err());
+}
+
+fn tiled_layout_from_kdl(kdl_layout: &str) -> TiledPaneLayout {
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    layout.template.unwrap().0
+}
+
+#[test]
+fn rotating_a_layout_turns_its_splits_a_quarter_turn() {
+    let mut layout = tiled_layout_from_kdl(
+        r#"
+        layout {
+            pane split_direction="vertical" {
+                pane name="left" size="30%"
+                pane {
+                    pane name="top"
+                    pane name="bottom" size=5
+                }
+            }
+        }
+    "#,
+    );
+    let expected_layout = tiled_layout_from_kdl(
+        r#"
+        layout {
+            pane {
+                pane name="left" size="30%"
+                pane split_direction="vertical" {
+                    pane name="bottom" size=5
+                    pane name="top"
+                }
+            }
+        }
+    "#,
+    );
+    layout.rotate(true);
+    assert_eq!(layout.children, expected_layout.children);
+}
+
+#[test]
+fn rotating_a_layout_back_and_forth_leaves_it_as_it_was() {
+    let layout = tiled_layout_from_kdl(
+        r#"
+        layout {
+            pane split_direction="vertical" {
+                pane name="1"
+                pane name="2"
+            }
+            pane split_direction="vertical" {
+                pane name="3"
+                pane name="4"
+            }
+        }
+    "#,
+    );
+    let mut rotated_layout = layout.clone();
+    rotated_layout.rotate(true);
+    // 1 2 / 3 4 turns into 3 1 / 4 2
+    let top_right_pane = rotated_layout.pane_at_path(&[1, 0]).unwrap();
+    assert_eq!(top_right_pane.name.as_deref(), Some("1"));
+    rotated_layout.rotate(false);
+    assert_eq!(rotated_layout, layout);
+    for _ in 0..4 {
+        rotated_layout.rotate(false);
+    }
+    assert_eq!(rotated_layout, layout);
+}
+
+#[test]
+fn rotating_a_layout_leaves_its_stacks_as_they_are() {
+    let mut layout = tiled_layout_from_kdl(
+        r#"
+        layout {
+            pane split_direction="vertical" {
+                pane name="editor"
+                pane stacked=true {
+                    pane name="logs"
+                    pane name="tests"
+                }
+            }
+        }
+    "#,
+    );
+    let stack = layout.pane_at_path(&[0, 1]).unwrap().clone();
+    layout.rotate(false);
+    let rotated_container = &layout.children[0];
+    assert_eq!(
+        rotated_container.children_split_direction,
+        SplitDirection::Horizontal
+    );
+    assert_eq!(rotated_container.children[0], stack);
+    assert_eq!(
+        rotated_container.children[1].name.as_deref(),
+        Some("editor")
+    );
+}
+
+#[test]
+fn panes_failing_their_if_env_condition_are_dropped_and_leave_their_space_to_their_siblings() {
+    std::env::set_var("ZELLIJ_LAYOUT_TEST_IF_ENV_SET", "1");
+    std::env::remove_var("ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET");
+    let layout = tiled_layout_from_kdl(
+        r#"
+        layout {
+            pane split_direction="vertical" {
+                pane name="kept" if_env="ZELLIJ_LAYOUT_TEST_IF_ENV_SET"
+                pane name="dropped" if_env="ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET" focus=true
+                pane name="plain"
+            }
+        }
+    "#,
+    );
+    assert_eq!(
+        layout.extract_pane_names(),
+        vec![Some("kept".to_owned()), Some("plain".to_owned())]
+    );
+    // the layout now focuses no pane, so the focus policy chooses one
+    assert!(!layout.has_focused_node());
+    let positions = layout
+        .position_panes_in_space(&viewport(100, 20), None)
+        .unwrap();
+    let widths: Vec<usize> = positions
+        .iter()
+        .map(|(_pane, geom)| geom.cols.as_usize())
+        .collect();
+    assert_eq!(widths, vec![50, 50]);
+}
+
+#[test]
+fn tabs_failing_their_if_env_condition_are_dropped() {
+    std::env::remove_var("ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET");
+    let kdl_layout = r#"
+        layout {
+            tab name="docker" if_env="ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET"
+            tab name="editor"
+            tab name="logs" focus=true
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tab_names: Vec<Option<String>> = layout
+        .tabs
+        .iter()
+        .map(|(tab_name, ..)| tab_name.clone())
+        .collect();
+    assert_eq!(
+        tab_names,
+        vec![Some("editor".to_owned()), Some("logs".to_owned())]
+    );
+    assert_eq!(layout.focused_tab_index, Some(1));
+}
+
+#[test]
+fn dropping_the_focused_tab_leaves_the_layout_without_one() {
+    std::env::remove_var("ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET");
+    let kdl_layout = r#"
+        layout {
+            tab name="editor"
+            tab name="docker" if_env="ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET" focus=true
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(layout.tabs.len(), 1);
+    assert_eq!(layout.focused_tab_index, None);
+}
+
+#[test]
+fn command_conditions_are_checked_when_applied() {
+    let kdl_layout = r#"
+        layout {
+            pane command="docker" if_command_exists="docker"
+            pane command="podman" if_command_exists="podman"
+            floating_panes {
+                pane command="lazydocker" if_command_exists="lazydocker"
+            }
+        }
+    "#;
+    let mut layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    // the commands are only looked for once the layout is opened
+    assert_eq!(layout.new_tab().0.extract_pane_names().len(), 2);
+    let outcomes = layout.apply_command_conditions(|command| command == "podman");
+    let outcomes: Vec<String> = outcomes.iter().map(|o| o.to_string()).collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            "pane 1 is dropped (if_command_exists=\"docker\")".to_owned(),
+            "pane 2 is included (if_command_exists=\"podman\")".to_owned(),
+            "floating pane 1 is dropped (if_command_exists=\"lazydocker\")".to_owned(),
+        ]
+    );
+    let (tiled_panes, floating_panes) = layout.new_tab();
+    assert_eq!(tiled_panes.children.len(), 1);
+    assert!(floating_panes.is_empty());
+}
+
+#[test]
+fn conditions_of_pane_templates_are_kept_by_their_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="docker" command="docker" if_command_exists="docker"
+            docker name="containers"
+            docker name="images" if_command_exists="podman"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let conditions: Vec<Option<String>> = layout
+        .new_tab()
+        .0
+        .children
+        .iter()
+        .map(|pane| pane.conditions.if_command_exists.clone())
+        .collect();
+    assert_eq!(
+        conditions,
+        vec![Some("docker".to_owned()), Some("podman".to_owned())]
+    );
+}
+
+#[test]
+fn lenient_validation_reports_the_outcome_of_conditions() {
+    std::env::set_var("ZELLIJ_LAYOUT_TEST_IF_ENV_SET", "1");
+    std::env::remove_var("ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET");
+    let kdl_layout = r#"
+        layout {
+            pane name="docker" if_env="ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET"
+            pane if_env="ZELLIJ_LAYOUT_TEST_IF_ENV_SET"
+        }
+    "#;
+    let load = |validation_options| {
+        Layout::from_kdl_with_validation(
+            kdl_layout,
+            "layout_file_name".into(),
+            None,
+            None,
+            validation_options,
+            &LayoutTemplates::default(),
+        )
+        .unwrap()
+        .1
+    };
+    let diagnostics: Vec<String> = load(ValidationOptions::lenient())
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect();
+    assert_eq!(
+        diagnostics,
+        vec![
+            "info[pane-condition] (new tab template): pane \"docker\" is dropped (if_env=\"ZELLIJ_LAYOUT_TEST_IF_ENV_UNSET\")".to_owned(),
+            "info[pane-condition] (new tab template): pane 2 is included (if_env=\"ZELLIJ_LAYOUT_TEST_IF_ENV_SET\")".to_owned(),
+        ]
+    );
+    assert!(load(ValidationOptions::strict()).is_empty());
+}
+
+#[test]
+fn if_env_has_to_name_an_environment_variable() {
+    let kdl_layout = r#"
+        layout {
+            pane if_env="USE_DOCKER=1"
+        }
+    "#;
+    assert!(Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).is_err());
+}
+
+#[test]
+fn command_conditions_are_serialized() {
+    let kdl_layout = r#"
+        layout {
+            pane command="docker" if_command_exists="docker"
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn panes_without_a_size_share_what_their_fixed_size_siblings_leave_in_a_fixed_size_pane() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size=30 split_direction="vertical" {
+                    pane size=10
+                    pane
+                }
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![10, 20, 70]);
+}
+
+#[test]
+fn panes_without_a_size_are_sized_in_nested_fixed_size_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size=41 split_direction="vertical" {
+                    pane size=31 split_direction="vertical" {
+                        pane size=10
+                        pane size="2w"
+                        pane
+                    }
+                    pane
+                }
+                pane
+            }
+        }
+    "#;
+    assert_eq!(
+        vertical_split_widths(kdl_layout, 100),
+        vec![10, 14, 7, 10, 59]
+    );
+}
+
+#[test]
+fn no_room_for_the_panes_without_a_size_in_a_fixed_size_pane_is_an_error() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size=20 split_direction="vertical" {
+                    pane size="50%"
+                    pane
+                }
+                pane
+            }
+        }
+    "#;
+    let layout = tiled_layout_from_kdl(kdl_layout);
+    let error = layout
+        .position_panes_in_space(&viewport(100, 20), None)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        LayoutApplyError::NotEnoughRoom {
+            pane: LayoutPanePath::new(None, vec![0, 0]),
+            needed: 51,
+            available: 20,
+            direction: SplitDirection::Vertical,
+        }
+    );
+}
+
+#[test]
+fn cells_left_in_rounding_are_spread_across_equal_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                pane
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 80), vec![27, 27, 26]);
+    assert_eq!(vertical_split_widths(kdl_layout, 100), vec![34, 33, 33]);
+    assert_eq!(vertical_split_widths(kdl_layout, 101), vec![34, 34, 33]);
+    assert_eq!(vertical_split_widths(kdl_layout, 121), vec![41, 40, 40]);
+}
+
+#[test]
+fn cells_left_in_rounding_go_to_the_panes_that_lost_the_most_in_it() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size="25%"
+                pane size="50%"
+                pane size="25%"
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 80), vec![20, 40, 20]);
+    assert_eq!(vertical_split_widths(kdl_layout, 101), vec![25, 51, 25]);
+    assert_eq!(vertical_split_widths(kdl_layout, 102), vec![26, 51, 25]);
+    assert_eq!(vertical_split_widths(kdl_layout, 121), vec![30, 61, 30]);
+}
+
+#[test]
+fn cells_too_many_after_rounding_are_taken_from_all_the_panes() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane size=20 split_direction="vertical" {
+                    pane size="15%"
+                    pane size="15%"
+                }
+                pane
+            }
+        }
+    "#;
+    assert_eq!(vertical_split_widths(kdl_layout, 101), vec![10, 10, 81]);
+}
+
+#[test]
+fn fitting_split_sizes_to_panes_resized_where_rounding_spreads_cells() {
+    let kdl_layout = r#"
+        layout {
+            pane
+            pane
+            pane
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let space = viewport(20, 101);
+    let geoms_in_space = |layout: &TiledPaneLayout| -> Vec<crate::pane_size::PaneGeom> {
+        layout
+            .position_panes_in_space(&space, None)
+            .unwrap()
+            .into_iter()
+            .map(|(_pane_layout, geom)| geom)
+            .collect()
+    };
+    let mut geoms = geoms_in_space(&tiled_panes);
+    assert_eq!(
+        geoms.iter().map(|g| g.rows.as_usize()).collect::<Vec<_>>(),
+        vec![34, 34, 33]
+    );
+    // the top pane is made 4 rows shorter and the middle one 4 rows taller
+    geoms[0].rows.decrease_inner(4);
+    geoms[1].y -= 4;
+    geoms[1].rows.increase_inner(4);
+
+    let mut fitted_panes = tiled_panes.clone();
+    fitted_panes.fit_split_sizes(&space, &geoms).unwrap();
+    assert_eq!(cells(&geoms_in_space(&fitted_panes)), cells(&geoms));
+}
+
+#[test]
+fn fingerprints_do_not_change_with_comments_formatting_or_property_order() {
+    let fingerprint = |kdl_layout: &str| {
+        Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None)
+            .unwrap()
+            .fingerprint()
+    };
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane command="htop" name="monitor"
+                pane size="30%"
+            }
+        }
+    "#;
+    let reformatted_kdl_layout = r#"
+        // the monitor is on the left
+        layout {
+            pane    split_direction="vertical"  {
+                pane name="monitor" command="htop" /* on the left */
+                pane size="30%"
+            }
+        }
+    "#;
+    assert_eq!(fingerprint(kdl_layout), fingerprint(reformatted_kdl_layout));
+    assert_ne!(
+        Layout::fingerprint_of_source(kdl_layout),
+        Layout::fingerprint_of_source(reformatted_kdl_layout),
+        "the source fingerprint covers all of its text"
+    );
+    let changed_kdl_layout = kdl_layout.replace("30%", "40%");
+    assert_ne!(fingerprint(kdl_layout), fingerprint(&changed_kdl_layout));
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        Layout::from_str(
+            &layout.to_kdl_string(),
+            "serialized".into(),
+            None,
+            None,
+            &Default::default()
+        )
+        .unwrap()
+        .fingerprint(),
+        layout.fingerprint(),
+        "a layout written back has the same fingerprint"
+    );
+}
+
+fn rows_of_tab_template_fitted_to(max_panes: usize) -> Vec<usize> {
+    let kdl_layout = r#"
+        layout {
+            default_tab_template {
+                pane size=1 borderless=true
+                children
+                pane size=2 borderless=true
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    let space = viewport(100, 40);
+    let positions = tiled_panes
+        .position_panes_in_space(&space, Some(max_panes))
+        .unwrap();
+    assert_tiles_exactly(&positions, &space);
+    positions
+        .iter()
+        .map(|(_pane_layout, geom)| geom.rows.as_usize())
+        .collect()
+}
+
+#[test]
+fn extra_panes_share_the_space_of_a_children_block_with_siblings() {
+    let rows = rows_of_tab_template_fitted_to(5);
+    assert_eq!(rows.len(), 5);
+    assert_eq!((rows[0], rows[4]), (1, 2), "the siblings keep their sizes");
+    assert_eq!(rows[1..4].iter().sum::<usize>(), 37);
+    assert!(
+        rows[1..4].iter().max().unwrap() - rows[1..4].iter().min().unwrap() <= 1,
+        "{:?}",
+        rows
+    );
+
+    let rows = rows_of_tab_template_fitted_to(10);
+    assert_eq!(rows.len(), 10);
+    assert_eq!((rows[0], rows[9]), (1, 2), "the siblings keep their sizes");
+    assert_eq!(rows[1..9].iter().sum::<usize>(), 37);
+}
+
+#[test]
+fn a_children_block_with_siblings_gets_no_extra_pane_when_there_is_no_room_for_one() {
+    assert_eq!(rows_of_tab_template_fitted_to(1), vec![40]);
+}
+
+#[test]
+fn truncating_keeps_the_panes_running_commands_or_plugins_before_bare_ones() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane
+                pane command="htop"
+                pane
+                pane {
+                    plugin location="zellij:strider"
+                }
+                pane command="tail"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_panes = layout.new_tab().0;
+    let space = viewport(120, 40);
+    for max_panes in 1..=5 {
+        assert_run_instructions_match_positions(&tiled_panes, &space, Some(max_panes));
+    }
+    let fitted_layout = tiled_panes.fitted_to_pane_count(&space, Some(3), FocusPolicy::default());
+    let kept_runs: Vec<bool> = fitted_layout.children[0]
+        .children
+        .iter()
+        .map(|pane| pane.run.is_some())
+        .collect();
+    assert_eq!(kept_runs, vec![true, true, true]);
+    let positions = tiled_panes
+        .position_panes_in_space(&space, Some(3))
+        .unwrap();
+    assert_tiles_exactly(&positions, &space);
+    assert_eq!(
+        run_commands(&positions),
+        vec![Some("htop".to_owned()), None, Some("tail".to_owned())],
+        "the kept panes stay in the order they were in"
+    );
+    let positions = tiled_panes
+        .position_panes_in_space(&space, Some(4))
+        .unwrap();
+    assert_eq!(
+        run_commands(&positions),
+        vec![None, Some("htop".to_owned()), None, Some("tail".to_owned())],
+        "the first bare panes are kept when there is room for some"
+    );
+}
+
+#[test]
+fn truncating_keeps_the_focused_pane_first() {
+    let kdl_layout = r#"
+        layout {
+            pane split_direction="vertical" {
+                pane command="htop"
+                pane command="top"
+                pane {
+                    pane
+                    pane focus=true
+                }
+                pane command="tail"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_panes = layout.new_tab().0;
+    let space = viewport(120, 40);
+    let fitted_layout = tiled_panes.fitted_to_pane_count(&space, Some(2), FocusPolicy::default());
+    let kept_panes = &fitted_layout.children[0].children;
+    assert_eq!(kept_panes.len(), 2);
+    assert!(
+        kept_panes[0].run.is_some(),
+        "htop is kept next to the focus"
+    );
+    assert_eq!(
+        kept_panes[1].focus,
+        Some(true),
+        "the container cut down to a pane keeps the focus of the pane in it"
+    );
+    assert_run_instructions_match_positions(&tiled_panes, &space, Some(2));
+}
+
+fn command_env(run: &Option<Run>) -> BTreeMap<String, String> {
+    match run {
+        Some(Run::Command(run_command)) => run_command.env.clone(),
+        _ => panic!("expected a command, found: {:?}", run),
+    }
+}
+
+fn env_of(variables: &[(&str, &str)]) -> BTreeMap<String, String> {
+    variables
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn command_panes_can_have_environment_variables() {
+    let kdl_layout = r#"
+        layout {
+            pane command="cargo" {
+                args "watch"
+                env {
+                    RUST_LOG "debug"
+                    CARGO_TERM_COLOR "always"
+                }
+            }
+            floating_panes {
+                pane command="htop" {
+                    env {
+                        TERM "xterm-256color"
+                    }
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.new_tab();
+    assert_eq!(
+        command_env(&tiled_panes.children[0].run),
+        env_of(&[("RUST_LOG", "debug"), ("CARGO_TERM_COLOR", "always")])
+    );
+    assert_eq!(
+        command_env(&floating_panes[0].run),
+        env_of(&[("TERM", "xterm-256color")])
+    );
+}
+
+#[test]
+fn environment_variables_of_consumers_override_those_of_their_pane_template_one_by_one() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="cargo" command="cargo" {
+                env {
+                    RUST_LOG "info"
+                    CARGO_TERM_COLOR "always"
+                }
+            }
+            cargo {
+                env {
+                    RUST_LOG "debug"
+                    RUST_BACKTRACE "1"
+                }
+            }
+            cargo command="cargo" {
+                env {
+                    RUST_LOG "trace"
+                }
+            }
+            cargo
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_panes = layout.new_tab().0;
+    assert_eq!(
+        command_env(&tiled_panes.children[0].run),
+        env_of(&[
+            ("RUST_LOG", "debug"),
+            ("CARGO_TERM_COLOR", "always"),
+            ("RUST_BACKTRACE", "1")
+        ])
+    );
+    assert_eq!(
+        command_env(&tiled_panes.children[1].run),
+        env_of(&[("RUST_LOG", "trace"), ("CARGO_TERM_COLOR", "always")])
+    );
+    assert_eq!(
+        command_env(&tiled_panes.children[2].run),
+        env_of(&[("RUST_LOG", "info"), ("CARGO_TERM_COLOR", "always")])
+    );
+}
+
+#[test]
+fn invalid_environment_variables_are_errors() {
+    let invalid_layouts = [
+        // no command to run with them
+        r#"layout { pane { env { RUST_LOG "debug"; }; }; }"#,
+        r#"layout { pane_template name="shell"; shell { env { RUST_LOG "debug"; }; }; }"#,
+        r#"layout { pane command="cargo" { env; }; }"#,
+        r#"layout { pane command="cargo" { env { RUST_LOG; }; }; }"#,
+        r#"layout { pane command="cargo" { env { RUST_BACKTRACE 1; }; }; }"#,
+        r#"layout { pane command="cargo" { env { RUST_LOG "debug" "info"; }; }; }"#,
+        r#"layout { pane command="cargo" { env { RUST_LOG "debug"; RUST_LOG "info"; }; }; }"#,
+    ];
+    for kdl_layout in invalid_layouts {
+        let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None);
+        assert!(layout.is_err(), "expected an error for: {}", kdl_layout);
+    }
+}
+
+#[test]
+fn environment_variables_are_serialized() {
+    let kdl_layout = r#"
+        layout {
+            pane command="cargo" {
+                env {
+                    EMPTY ""
+                    GREETING "hello world"
+                    QUOTED "say \"hi\" and 'bye'"
+                    PRICE "$$5"
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        command_env(&reparsed.new_tab().0.children[0].run),
+        env_of(&[
+            ("EMPTY", ""),
+            ("GREETING", "hello world"),
+            ("QUOTED", "say \"hi\" and 'bye'"),
+            ("PRICE", "$5")
+        ]),
+        "{}",
+        serialized
+    );
+}
+
+fn pane_name_values() -> PaneNameValues {
+    PaneNameValues {
+        tab_name: "backend".into(),
+        home_dir: Some(PathBuf::from("/home/aram")),
+    }
+}
+
+#[test]
+fn placeholders_in_pane_names_are_resolved_from_what_the_panes_run() {
+    let kdl_layout = r#"
+        layout {
+            pane_template name="watcher" command="cargo" cwd="/home/aram/proj" {
+                args "watch"
+            }
+            pane split_direction="vertical" name="{tab} {index}" {
+                watcher name="{command} ({cwd})"
+                pane name="{index}: {args}{command} in {cwd}" cwd="/tmp"
+                pane name="{pane_name} {{index}" command="htop"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let tiled_panes = layout.new_tab().0;
+    let resolved = tiled_panes.with_resolved_pane_names(&pane_name_values());
+    let container = &resolved.children[0];
+    assert_eq!(container.name.as_deref(), Some("backend 1"));
+    assert_eq!(
+        container.children[0].name.as_deref(),
+        Some("cargo watch (~/proj)")
+    );
+    assert_eq!(container.children[1].name.as_deref(), Some("2:  in /tmp"));
+    assert_eq!(
+        container.children[2].name.as_deref(),
+        Some("{pane_name} {index}"),
+        "unknown placeholders are left as they are"
+    );
+}
+
+#[test]
+fn placeholders_in_floating_pane_names_are_resolved() {
+    let kdl_layout = r#"
+        layout {
+            floating_panes {
+                pane name="scratch"
+                pane name="{index}: {args}" command="tail" {
+                    args "-f" "log.txt"
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let floating_panes = layout.new_tab().1;
+    let names: Vec<Option<String>> = floating_panes
+        .iter()
+        .enumerate()
+        .map(|(index, floating_pane)| {
+            floating_pane
+                .with_resolved_name(index + 1, &pane_name_values())
+                .name
+        })
+        .collect();
+    assert_eq!(
+        names,
+        vec![Some("scratch".to_owned()), Some("2: -f log.txt".to_owned())]
+    );
+}
+
+#[test]
+fn pane_names_with_placeholders_are_serialized_as_they_are_written() {
+    let kdl_layout = r#"
+        layout {
+            pane name="{command} ({cwd})" command="cargo" cwd="/tmp"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let serialized = layout.to_kdl_string();
+    let reparsed = Layout::from_kdl(&serialized, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        reparsed.new_tab().0.children[0].name.as_deref(),
+        Some("{command} ({cwd})"),
+        "{}",
+        serialized
+    );
+}
+
+fn run_cwd(run: &Option<Run>) -> Option<PathBuf> {
+    match run {
+        Some(Run::Command(run_command)) => run_command.cwd.clone(),
+        Some(Run::Cwd(cwd)) => Some(cwd.clone()),
+        Some(Run::EditFile(paths, _line_number, _editor_splits)) => paths.first().cloned(),
+        _ => None,
+    }
+}
+
+#[test]
+fn global_cwd_and_tab_cwd_prepended_to_floating_panes() {
+    let kdl_layout = r#"
+        layout {
+            cwd "/tmp"
+            tab cwd="./foo" {
+                pane
+                floating_panes {
+                    pane // should have /tmp/foo
+                    pane command="tail" cwd="./bar" // should have /tmp/foo/bar
+                    pane cwd="/opt" // should have /opt
+                    pane edit="README.md" // should edit /tmp/foo/README.md
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let cwds: Vec<Option<PathBuf>> = layout.tabs[0]
+        .2
+        .iter()
+        .map(|floating_pane| run_cwd(&floating_pane.run))
+        .collect();
+    assert_eq!(
+        cwds,
+        vec![
+            Some(PathBuf::from("/tmp/foo")),
+            Some(PathBuf::from("/tmp/foo/bar")),
+            Some(PathBuf::from("/opt")),
+            Some(PathBuf::from("/tmp/foo/README.md")),
+        ]
+    );
+}
+
+#[test]
+fn tab_cwd_prepended_to_floating_panes_of_tab_templates_and_pane_templates() {
+    let kdl_layout = r#"
+        layout {
+            cwd "/tmp"
+            pane_template name="logs" command="tail" cwd="./logs"
+            tab_template name="my_tab_template" {
+                children
+                floating_panes {
+                    pane cwd="./bar" // should have /tmp/foo/bar
+                }
+            }
+            my_tab_template cwd="./foo" {
+                pane
+                floating_panes {
+                    logs // should have /tmp/foo/logs
+                }
+            }
+            tab {
+                pane
+                floating_panes {
+                    logs // should have /tmp/logs
+                }
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let cwds = |floating_panes: &[FloatingPaneLayout]| -> Vec<Option<PathBuf>> {
+        floating_panes
+            .iter()
+            .map(|floating_pane| run_cwd(&floating_pane.run))
+            .collect()
+    };
+    assert_eq!(
+        cwds(&layout.tabs[0].2),
+        vec![
+            Some(PathBuf::from("/tmp/foo/bar")),
+            Some(PathBuf::from("/tmp/foo/logs")),
+        ]
+    );
+    assert_eq!(
+        cwds(&layout.tabs[1].2),
+        vec![Some(PathBuf::from("/tmp/logs"))]
+    );
+}
+
+#[test]
+fn global_cwd_prepended_to_floating_panes_of_layouts_without_tabs() {
+    let kdl_layout = r#"
+        layout {
+            cwd "/tmp"
+            pane
+            floating_panes {
+                pane cwd="./foo"
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    assert_eq!(
+        run_cwd(&layout.new_tab().1[0].run),
+        Some(PathBuf::from("/tmp/foo"))
+    );
+}
+
+#[test]
+fn new_tabs_can_be_opened_from_the_tab_templates_of_the_layout() {
+    let kdl_layout = r#"
+        layout {
+            cwd "/tmp"
+            tab_template name="dev" {
+                pane command="cargo" {
+                    args "watch"
+                }
+                children
+                floating_panes {
+                    pane command="htop"
+                }
+            }
+            dev name="backend"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    let (tiled_panes, floating_panes) = layout.new_tab_with_template(Some("dev")).unwrap();
+    assert_eq!(
+        tiled_panes.children.len(),
+        2,
+        "the children slot gets a pane"
+    );
+    assert_eq!(
+        tiled_panes.children[0].run,
+        Some(Run::Command(RunCommand {
+            command: PathBuf::from("cargo"),
+            args: vec!["watch".into()],
+            cwd: Some(PathBuf::from("/tmp")),
+            hold_on_close: true,
+            ..Default::default()
+        }))
+    );
+    assert_eq!(
+        tiled_panes.children[1].run,
+        Some(Run::Cwd(PathBuf::from("/tmp")))
+    );
+    assert_eq!(floating_panes.len(), 1);
+    assert_eq!(run_cwd(&floating_panes[0].run), Some(PathBuf::from("/tmp")));
+    assert_eq!(
+        layout.new_tab_with_template(None).unwrap(),
+        layout.new_tab()
+    );
+}
+
+#[test]
+fn new_tabs_cannot_be_opened_from_unknown_tab_templates() {
+    let kdl_layout = r#"
+        layout {
+            tab_template name="dev" {
+                children
+            }
+            pane_template name="editor" {
+                pane
+            }
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None).unwrap();
+    for template_name in ["docs", "editor", "default_tab_template"] {
+        let error = layout
+            .new_tab_with_template(Some(template_name))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "There is no tab_template named \"{}\" in the layout",
+                template_name
+            )
+        );
+    }
+}
+
+#[test]
+fn concatenated_layouts_keep_the_tab_templates_of_both() {
+    let first = Layout::from_kdl(
+        r#"
+        layout {
+            tab_template name="dev" {
+                pane
+                children
+            }
+            tab_template name="logs" {
+                children
+            }
+        }
+    "#,
+        "first".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let second = Layout::from_kdl(
+        r#"
+        layout {
+            tab_template name="logs" {
+                pane
+                pane
+                children
+            }
+        }
+    "#,
+        "second".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let layout = first.clone().concatenate(second.clone());
+    assert_eq!(
+        layout.new_tab_with_template(Some("dev")).unwrap(),
+        first.new_tab_with_template(Some("dev")).unwrap()
+    );
+    assert_eq!(
+        layout.new_tab_with_template(Some("logs")).unwrap(),
+        second.new_tab_with_template(Some("logs")).unwrap(),
+        "the template of the last layout wins"
+    );
+}
+
+#[test]
+fn auto_split_direction_splits_each_pane_along_its_longer_side() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            pane split_direction="auto" {
+                pane
+                pane split_direction="auto" {
+                    pane
+                    pane
+                }
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let positions = layout
+        .new_tab()
+        .0
+        .position_panes_in_space(&viewport(100, 80), None)
+        .unwrap();
+    let geoms: Vec<(usize, usize, usize, usize)> = positions
+        .iter()
+        .map(|(_pane_layout, geom)| (geom.x, geom.y, geom.cols.as_usize(), geom.rows.as_usize()))
+        .collect();
+    assert_eq!(
+        geoms,
+        vec![(0, 0, 50, 80), (50, 0, 50, 40), (50, 40, 50, 40)],
+        "the wide space is split vertically, the tall pane in it horizontally"
+    );
+    let positions_again = layout
+        .new_tab()
+        .0
+        .position_panes_in_space(&viewport(100, 80), None)
+        .unwrap();
+    assert_eq!(positions_again, positions);
+}
+
+#[test]
+fn panes_positioned_from_an_auto_split_record_the_direction_they_were_split_in() {
+    let layout = TiledPaneLayout {
+        children_split_direction: SplitDirection::Auto,
+        children: vec![
+            TiledPaneLayout::default(),
+            TiledPaneLayout {
+                children_split_direction: SplitDirection::Auto,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let positions = layout
+        .position_panes_in_space(&viewport(100, 30), None)
+        .unwrap();
+    let directions: Vec<SplitDirection> = positions
+        .iter()
+        .map(|(pane_layout, _geom)| pane_layout.children_split_direction)
+        .collect();
+    assert_eq!(
+        directions,
+        vec![SplitDirection::Horizontal, SplitDirection::Vertical],
+        "the direction is resolved in the space of the pane"
+    );
+}
+
+#[test]
+fn auto_split_direction_splits_square_spaces_horizontally() {
+    assert_eq!(
+        SplitDirection::Auto.resolve(&viewport(40, 40)),
+        SplitDirection::Horizontal
+    );
+    assert_eq!(
+        SplitDirection::Auto.resolve(&viewport(41, 40)),
+        SplitDirection::Vertical
+    );
+    assert_eq!(
+        SplitDirection::Vertical.resolve(&viewport(10, 40)),
+        SplitDirection::Vertical,
+        "only the automatic direction is resolved"
+    );
+}
+
+#[test]
+fn panes_added_to_an_auto_split_children_block_split_along_its_longer_side() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            pane size=1 borderless=true
+            pane split_direction="auto" {
+                children
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let positions = layout
+        .new_tab()
+        .0
+        .position_panes_in_space(&viewport(40, 101), Some(3))
+        .unwrap();
+    let geoms: Vec<(usize, usize, usize, usize)> = positions
+        .iter()
+        .map(|(_pane_layout, geom)| (geom.x, geom.y, geom.cols.as_usize(), geom.rows.as_usize()))
+        .collect();
+    assert_eq!(
+        geoms,
+        vec![(0, 0, 40, 1), (0, 1, 40, 50), (0, 51, 40, 50)],
+        "the tall container is split horizontally"
+    );
+}
+
+#[test]
+fn auto_split_direction_is_serialized() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            pane split_direction="auto" {
+                pane
+                pane
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let serialized = layout.to_kdl_string();
+    assert!(
+        serialized.contains("split_direction=\"auto\""),
+        "{}",
+        serialized
+    );
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn editors_cannot_split_automatically() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            pane {
+                edit "a.rs" "b.rs" editor_splits="auto"
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    );
+    assert!(
+        layout.is_err(),
+        "error provided for automatic editor splits"
+    );
+}
+
+#[test]
+fn stacks_expand_the_pane_marked_as_expanded() {
+    let layout = TiledPaneLayout {
+        children_are_stacked: true,
+        children: vec![
+            TiledPaneLayout::default(),
+            TiledPaneLayout {
+                is_expanded_in_stack: true,
+                ..Default::default()
+            },
+            TiledPaneLayout::default(),
+        ],
+        ..Default::default()
+    };
+    let positions = layout
+        .position_panes_in_space(&viewport(120, 40), None)
+        .unwrap();
+    let geoms: Vec<(usize, usize, usize, usize)> = positions
+        .iter()
+        .map(|(_pane_layout, geom)| (geom.x, geom.y, geom.cols.as_usize(), geom.rows.as_usize()))
+        .collect();
+    assert_eq!(
+        geoms,
+        vec![(0, 0, 120, 1), (0, 1, 120, 38), (0, 39, 120, 1)],
+        "the panes around the expanded one get a row each"
+    );
+}
+
+#[test]
+fn panes_of_stacks_can_be_marked_as_expanded_in_layouts() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            pane_template name="logs" expanded=true command="tail"
+            tab {
+                pane {
+                    pane name="first"
+                    logs name="logs"
+                    pane name="last"
+                    children stacked=true
+                }
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let positions = layout.tabs[0]
+        .1
+        .position_panes_in_space(&viewport(120, 40), None)
+        .unwrap();
+    let expanded_panes: Vec<Option<String>> = positions
+        .iter()
+        .filter(|(_pane_layout, geom)| geom.rows.as_usize() > 1)
+        .map(|(pane_layout, _geom)| pane_layout.name.clone())
+        .collect();
+    assert_eq!(
+        expanded_panes,
+        vec![Some("logs".to_owned())],
+        "the pane template marks its panes as expanded"
+    );
+    let serialized = layout.to_kdl_string();
+    assert!(serialized.contains("expanded=true"), "{}", serialized);
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn error_on_expanding_more_than_one_pane_of_a_stack() {
+    let kdl_layout = r#"
+        layout {
+            pane {
+                pane expanded=true
+                pane
+                pane expanded=true
+                children stacked=true
+            }
+        }
+    "#;
+    match Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None) {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert_eq!(
+                kdl_error.error_message,
+                "Only one pane of a stack can be expanded, found 2 panes with expanded=true"
+            );
+            let first_expanded = kdl_layout.find("pane expanded=true").unwrap();
+            assert!(
+                kdl_error.offset.unwrap() > first_expanded,
+                "the error points at the second expanded pane"
+            );
+        },
+        layout => panic!("expected a layout error, got: {:?}", layout),
+    }
+}
+
+#[test]
+fn floating_stacks_share_their_geometry_with_their_panes() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            floating_panes {
+                pane name="alone"
+                stack x=2 y=3 width=80 height=20 {
+                    pane name="first"
+                    pane name="second" expanded=true
+                    pane name="third"
+                }
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let (_tiled_panes, floating_panes) = layout.new_tab();
+    assert_eq!(floating_panes.len(), 4);
+    assert_eq!(floating_panes[0].stack, None);
+    for stacked_pane in &floating_panes[1..] {
+        assert_eq!(stacked_pane.stack, Some(0));
+        assert_eq!(stacked_pane.x, Some(PercentOrFixed::Fixed(2)));
+        assert_eq!(stacked_pane.y, Some(PercentOrFixed::Fixed(3)));
+        assert_eq!(stacked_pane.width, Some(PercentOrFixed::Fixed(80)));
+        assert_eq!(stacked_pane.height, Some(PercentOrFixed::Fixed(20)));
+    }
+    assert!(floating_panes[2].is_expanded_in_stack);
+    let stack_geom = PaneGeom {
+        x: 2,
+        y: 3,
+        cols: Dimension::fixed(80),
+        rows: Dimension::fixed(20),
+        is_stacked: false,
+    };
+    let geoms: Vec<(usize, usize)> = (1..4)
+        .map(|i| floating_stack_geom(&floating_panes, i, stack_geom))
+        .map(|geom| (geom.y, geom.rows.as_usize()))
+        .collect();
+    assert_eq!(
+        geoms,
+        vec![(3, 1), (4, 18), (22, 1)],
+        "the expanded pane takes what its collapsed siblings leave"
+    );
+    assert_eq!(
+        floating_stack_geom(&floating_panes, 0, stack_geom),
+        stack_geom,
+        "panes outside of a stack are left where they are"
+    );
+}
+
+#[test]
+fn floating_stacks_expand_their_last_pane_by_default() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            floating_panes {
+                stack x=0 y=0 width=40 height=10 {
+                    pane
+                    pane
+                }
+            }
+            floating_panes {
+                stack x=50 y=0 width=40 height=10 {
+                    pane
+                }
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let (_tiled_panes, floating_panes) = layout.new_tab();
+    let stacks: Vec<Option<usize>> = floating_panes.iter().map(|pane| pane.stack).collect();
+    assert_eq!(
+        stacks,
+        vec![Some(0), Some(0), Some(1)],
+        "the stacks of different floating_panes blocks are told apart"
+    );
+    let stack_geom = PaneGeom {
+        x: 0,
+        y: 0,
+        cols: Dimension::fixed(40),
+        rows: Dimension::fixed(10),
+        is_stacked: false,
+    };
+    assert_eq!(
+        floating_stack_geom(&floating_panes, 1, stack_geom)
+            .rows
+            .as_usize(),
+        9
+    );
+    assert_eq!(
+        floating_stack_geom(&floating_panes, 2, stack_geom),
+        stack_geom,
+        "a stack of one pane gives it the whole stack"
+    );
+}
+
+#[test]
+fn floating_stacks_are_serialized_as_stack_nodes() {
+    let layout = Layout::from_kdl(
+        r#"
+        layout {
+            floating_panes {
+                stack x="10%" y=2 width="50%" height=20 {
+                    pane command="htop"
+                    pane expanded=true
+                }
+                pane x=1 y=1
+            }
+        }
+    "#,
+        "layout_file_name".into(),
+        None,
+        None,
+    )
+    .unwrap();
+    let serialized = layout.to_kdl_string();
+    assert!(serialized.contains("stack x="), "{}", serialized);
+    let reparsed = Layout::from_str(
+        &serialized,
+        "serialized".into(),
+        None,
+        None,
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reparsed, layout, "serialized into:\n{}", serialized);
+}
+
+#[test]
+fn error_on_invalid_floating_stacks() {
+    let invalid_layouts = [
+        (
+            r#"layout { floating_panes { stack x=1 y=1 { pane x=5; }; }; }"#,
+            "The panes of a floating stack take the position and size of the stack",
+        ),
+        (
+            r#"layout { floating_panes { stack x=1 y=1 { pane expanded=true; pane expanded=true; }; }; }"#,
+            "Only one pane of a stack can be expanded, found 2 panes with expanded=true",
+        ),
+        (
+            r#"layout { floating_panes { stack x=1 y=1; }; }"#,
+            "A floating stack needs at least one pane",
+        ),
+        (
+            r#"layout { floating_panes { stack x=1 y=1 { stack { pane; }; }; }; }"#,
+            "A floating stack can only contain pane nodes, found: stack",
+        ),
+        (
+            r#"layout { floating_panes { stack anchor="center" { pane; }; }; }"#,
+            "A floating stack can only have an x, y, width and height",
+        ),
+        (
+            r#"layout { floating_panes { pane expanded=true; }; }"#,
+            "Only the panes of a stack can be expanded",
+        ),
+    ];
+    for (kdl_layout, expected_error) in invalid_layouts {
+        match Layout::from_kdl(kdl_layout, "layout_file_name".into(), None, None) {
+            Err(ConfigError::KdlError(kdl_error)) => {
+                assert_eq!(kdl_error.error_message, expected_error, "{}", kdl_layout)
+            },
+            layout => panic!(
+                "expected a layout error for {}, got: {:?}",
+                kdl_layout, layout
+            ),
+        }
+    }
+}
+
+#[test]
+fn layouts_read_from_a_file_are_reloaded_from_it() {
+    let layout_dir = tempfile::tempdir().unwrap();
+    let layout_path = layout_dir.path().join("work.kdl");
+    let swap_layout_path = layout_dir.path().join("work.swap.kdl");
+    std::fs::write(&layout_path, "layout { pane; }").unwrap();
+    std::fs::write(
+        &swap_layout_path,
+        "swap_tiled_layout name=\"wide\" { tab max_panes=3 { pane; pane; }; }",
+    )
+    .unwrap();
+    let (_layout, _config, layout_sources) = Layout::from_path_or_default(
+        Some(&layout_path),
+        None,
+        Config::default(),
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        layout_sources.watched_files(),
+        Some(vec![layout_path.clone(), swap_layout_path.clone()])
+    );
+
+    std::fs::write(&layout_path, "layout { pane; pane command=\"htop\"; }").unwrap();
+    let layout = Layout::reload(
+        &layout_path,
+        None,
+        ValidationOptions::strict(),
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(layout.new_tab().0.pane_count(), 2);
+    assert_eq!(
+        layout.swap_tiled_layouts[0].1,
+        Some("wide".to_owned()),
+        "the swap layouts are read again along with the layout"
+    );
+
+    std::fs::write(&layout_path, "layout { pane size=; }").unwrap();
+    assert!(Layout::reload(
+        &layout_path,
+        None,
+        ValidationOptions::strict(),
+        &Default::default()
+    )
+    .is_err());
+}
+
+#[test]
+fn builtin_layouts_are_not_watched() {
+    let (_layout, _config, layout_sources) =
+        Layout::from_path_or_default(None, None, Config::default(), &Default::default()).unwrap();
+    assert_eq!(layout_sources.watched_files(), None);
+}
@@ -0,0 +1,125 @@
+use super::*;
+use crate::input::layout::Layout;
+use std::os::unix::fs::symlink;
+
+fn layout_file_at(path: &Path) -> LayoutFile {
+    let (_layout_path, _raw_layout, _raw_swap_layouts, layout_sources) =
+        Layout::stringified_from_path_or_default(Some(&path.to_path_buf()), None).unwrap();
+    layout_sources.files[0].clone()
+}
+
+#[test]
+fn content_hash_covers_the_layout_and_its_swap_layouts() {
+    let layout = "layout { pane command=\"htop\"; }";
+    assert_eq!(
+        layout_content_hash(layout, None),
+        layout_content_hash(layout, None)
+    );
+    assert_ne!(
+        layout_content_hash(layout, None),
+        layout_content_hash("layout { pane command=\"top\"; }", None)
+    );
+    assert_ne!(
+        layout_content_hash(layout, None),
+        layout_content_hash(layout, Some("layout { swap_tiled_layout; }"))
+    );
+    assert_ne!(
+        layout_content_hash("layout { pane; }", Some("")),
+        layout_content_hash("layout { pane", Some("; }")),
+        "text moved between the two files"
+    );
+}
+
+#[test]
+fn trusted_layouts_are_remembered_in_the_data_dir() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let mut trusted_layouts = TrustedLayouts::load(data_dir.path());
+    assert_eq!(trusted_layouts, TrustedLayouts::default());
+    trusted_layouts.remember(data_dir.path(), "abc").unwrap();
+    trusted_layouts.remember(data_dir.path(), "def").unwrap();
+    trusted_layouts.remember(data_dir.path(), "abc").unwrap();
+    let reloaded = TrustedLayouts::load(data_dir.path());
+    assert!(reloaded.contains("abc") && reloaded.contains("def"));
+    assert!(!reloaded.contains("ghi"));
+    let trusted_layouts_file =
+        fs::read_to_string(data_dir.path().join(TRUSTED_LAYOUTS_FILE_NAME)).unwrap();
+    assert_eq!(trusted_layouts_file.lines().count(), 2, "no duplicates");
+}
+
+#[test]
+fn layouts_outside_the_layout_dir_are_untrusted_until_trusted() {
+    let root = tempfile::tempdir().unwrap();
+    let layout_dir = root.path().join("layouts");
+    let downloads = root.path().join("downloads");
+    fs::create_dir_all(&layout_dir).unwrap();
+    fs::create_dir_all(&downloads).unwrap();
+    fs::write(
+        layout_dir.join("work.kdl"),
+        "layout { pane command=\"htop\"; }",
+    )
+    .unwrap();
+    fs::write(
+        downloads.join("work.kdl"),
+        "layout { pane command=\"curl\"; }",
+    )
+    .unwrap();
+    let data_dir = root.path().join("data");
+    let mut trusted_layouts = TrustedLayouts::load(&data_dir);
+
+    let in_layout_dir = layout_file_at(&layout_dir.join("work.kdl"));
+    assert!(in_layout_dir.is_trusted(Some(&layout_dir), &trusted_layouts));
+    let downloaded = layout_file_at(&downloads.join("work.kdl"));
+    assert!(!downloaded.is_trusted(Some(&layout_dir), &trusted_layouts));
+    let escaping = layout_file_at(&layout_dir.join("../downloads/work.kdl"));
+    assert!(
+        !escaping.is_trusted(Some(&layout_dir), &trusted_layouts),
+        "a path leaving the layout dir"
+    );
+
+    trusted_layouts
+        .remember(&data_dir, &downloaded.content_hash)
+        .unwrap();
+    assert!(downloaded.is_trusted(Some(&layout_dir), &TrustedLayouts::load(&data_dir)));
+    fs::write(
+        downloads.join("work.kdl"),
+        "layout { pane command=\"rm\"; }",
+    )
+    .unwrap();
+    let changed = layout_file_at(&downloads.join("work.kdl"));
+    assert!(
+        !changed.is_trusted(Some(&layout_dir), &TrustedLayouts::load(&data_dir)),
+        "a trusted layout that changed"
+    );
+}
+
+#[test]
+fn layouts_linked_from_the_layout_dir_and_built_in_layouts_are_trusted() {
+    let root = tempfile::tempdir().unwrap();
+    let dotfiles = root.path().join("dotfiles");
+    fs::create_dir_all(&dotfiles).unwrap();
+    fs::write(
+        dotfiles.join("work.kdl"),
+        "layout { pane command=\"htop\"; }",
+    )
+    .unwrap();
+    let layout_dir = root.path().join("layouts");
+    symlink(&dotfiles, &layout_dir).unwrap();
+    let trusted_layouts = TrustedLayouts::default();
+
+    let linked = layout_file_at(&layout_dir.join("work.kdl"));
+    assert!(linked.is_trusted(Some(&layout_dir), &trusted_layouts));
+    let (_layout_path, _raw_layout, _raw_swap_layouts, layout_sources) =
+        Layout::stringified_from_path_or_default(Some(&PathBuf::from("strider")), None).unwrap();
+    assert!(layout_sources
+        .untrusted_files(Some(&layout_dir), &trusted_layouts)
+        .is_empty());
+}
+
+#[test]
+fn layouts_are_trusted_by_the_fingerprint_of_their_source() {
+    let layout = "layout { pane command=\"htop\"; }";
+    assert_eq!(
+        layout_content_hash(layout, None),
+        Layout::fingerprint_of_source(layout)
+    );
+}
@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn http_and_https_layout_paths_are_urls() {
+    assert_eq!(
+        layout_url(Path::new("https://example.com/layouts/dev.kdl")).map(|url| url.to_string()),
+        Some("https://example.com/layouts/dev.kdl".to_owned())
+    );
+    assert!(layout_url(Path::new("http://localhost:8080/dev")).is_some());
+}
+
+#[test]
+fn other_layout_paths_are_not_urls() {
+    assert!(layout_url(Path::new("dev")).is_none());
+    assert!(layout_url(Path::new("layouts/dev.kdl")).is_none());
+    assert!(layout_url(Path::new("/home/user/layouts/dev.kdl")).is_none());
+    assert!(layout_url(Path::new("file:///home/user/layouts/dev.kdl")).is_none());
+    assert!(layout_url(Path::new("ftp://example.com/dev.kdl")).is_none());
+}
+
+#[test]
+fn swap_layouts_are_fetched_next_to_the_layout() {
+    let swap_layout_url_of = |url: &str| {
+        swap_layout_url(&Url::parse(url).unwrap())
+            .map(|swap_layout_url| swap_layout_url.to_string())
+    };
+    assert_eq!(
+        swap_layout_url_of("https://example.com/layouts/dev.kdl"),
+        Some("https://example.com/layouts/dev.swap.kdl".to_owned())
+    );
+    assert_eq!(
+        swap_layout_url_of("https://example.com/layouts/dev"),
+        Some("https://example.com/layouts/dev.swap.kdl".to_owned())
+    );
+    assert_eq!(
+        swap_layout_url_of("https://example.com/raw/dev.kdl?ref=main"),
+        Some("https://example.com/raw/dev.swap.kdl?ref=main".to_owned())
+    );
+    assert_eq!(swap_layout_url_of("https://example.com/layouts/"), None);
+}
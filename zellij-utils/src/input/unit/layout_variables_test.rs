@@ -0,0 +1,176 @@
+use super::*;
+use crate::input::command::RunCommand;
+use crate::input::layout::{Layout, Run};
+use std::path::PathBuf;
+
+fn variables(variables: &[(&str, &str)]) -> LayoutVariables {
+    variables
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn layout_variables_are_given_as_name_equals_value() {
+    assert_eq!(
+        parse_layout_variable("project=/home/me/foo"),
+        Ok(("project".into(), "/home/me/foo".into()))
+    );
+    assert_eq!(
+        parse_layout_variable("filter=a=b"),
+        Ok(("filter".into(), "a=b".into())),
+        "the value can have an equal sign"
+    );
+    assert_eq!(
+        parse_layout_variable("empty="),
+        Ok(("empty".into(), "".into()))
+    );
+    assert!(parse_layout_variable("project").is_err());
+    assert!(parse_layout_variable("=value").is_err());
+    assert!(parse_layout_variable("my project=foo").is_err());
+}
+
+#[test]
+fn layout_variables_are_substituted_in_the_panes_of_the_layout() {
+    let kdl_layout = r#"
+        layout {
+            vars {
+                project
+                branch "main"
+            }
+            pane name="{{project}} ({{branch}})" cwd="{{project}}" command="git" {
+                args "checkout" "{{branch}}"
+            }
+            pane edit="{{project}}/README.md"
+        }
+    "#;
+    let layout = Layout::from_str(
+        kdl_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &variables(&[("project", "/home/me/foo")]),
+    )
+    .unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    assert_eq!(
+        tiled_panes.extract_pane_names(),
+        vec![Some("/home/me/foo (main)".to_owned()), None]
+    );
+    let run_instructions = tiled_panes.extract_run_instructions();
+    assert_eq!(
+        run_instructions[0],
+        Some(Run::Command(RunCommand {
+            command: PathBuf::from("git"),
+            args: vec!["checkout".into(), "main".into()],
+            cwd: Some(PathBuf::from("/home/me/foo")),
+            hold_on_close: true,
+            ..Default::default()
+        }))
+    );
+    match &run_instructions[1] {
+        Some(Run::EditFile(paths, ..)) => {
+            assert_eq!(paths, &vec![PathBuf::from("/home/me/foo/README.md")])
+        },
+        other => panic!("expected a pane editing a file, found {:?}", other),
+    }
+}
+
+#[test]
+fn given_layout_variables_take_precedence_over_their_defaults() {
+    let raw_layout = r#"layout { vars { branch "main"; }; pane name="{{branch}}"; }"#;
+    assert_eq!(
+        resolve_layout_variables(raw_layout, "layout_file_name", &variables(&[])).unwrap(),
+        variables(&[("branch", "main")])
+    );
+    assert_eq!(
+        resolve_layout_variables(
+            raw_layout,
+            "layout_file_name",
+            &variables(&[("branch", "dev"), ("other", "value")])
+        )
+        .unwrap(),
+        variables(&[("branch", "dev"), ("other", "value")]),
+        "those given without being declared are substituted as well"
+    );
+}
+
+#[test]
+fn required_layout_variables_without_a_value_are_all_listed() {
+    let raw_layout = r#"
+        layout {
+            vars {
+                project
+                branch "main"
+                ticket
+            }
+            pane cwd="{{project}}"
+        }
+    "#;
+    let error = Layout::from_str(
+        raw_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &variables(&[]),
+    )
+    .unwrap_err();
+    match error {
+        ConfigError::KdlError(kdl_error) => {
+            assert_eq!(
+                kdl_error.error_message,
+                "No value for the layout variables: project, ticket (give them with --layout-var NAME=VALUE)"
+            );
+            // pointing at the vars block, whether or not its span has its leading whitespace
+            let offset = kdl_error.offset.unwrap();
+            assert!(offset > raw_layout.find("layout").unwrap());
+            assert!(offset <= raw_layout.find("vars").unwrap());
+            assert!(kdl_error.src.is_some());
+        },
+        e => panic!("expected a KdlError, found {:?}", e),
+    }
+}
+
+#[test]
+fn layout_variable_defaults_must_be_strings() {
+    let raw_layout = "layout {\n    vars {\n        width 10\n    }\n}\n";
+    match resolve_layout_variables(raw_layout, "layout_file_name", &variables(&[])) {
+        Err(ConfigError::KdlError(kdl_error)) => {
+            assert_eq!(
+                kdl_error.error_message,
+                "The default of the layout variable 'width' must be a string"
+            );
+            let offset = kdl_error.offset.unwrap();
+            assert!(offset > raw_layout.find("vars").unwrap());
+            assert!(offset <= raw_layout.find("width").unwrap());
+        },
+        other => panic!("expected a KdlError, found {:?}", other),
+    }
+}
+
+#[test]
+fn only_the_layout_variables_are_substituted() {
+    let raw_layout = r#"pane name="{{project}} {{other}} {{pane_name} {{{project}}""#;
+    assert_eq!(
+        substitute_layout_variables(raw_layout, &variables(&[("project", "foo")])),
+        r#"pane name="foo {{other}} {{pane_name} {foo""#
+    );
+}
+
+#[test]
+fn layout_variable_values_stay_in_their_string() {
+    let raw_layout = r#"layout { pane name="{{title}}"; }"#;
+    let layout = Layout::from_str(
+        raw_layout,
+        "layout_file_name".into(),
+        None,
+        None,
+        &variables(&[("title", "say \"hi\" \\ bye")]),
+    )
+    .unwrap();
+    let (tiled_panes, _floating_panes) = layout.new_tab();
+    assert_eq!(
+        tiled_panes.extract_pane_names(),
+        vec![Some("say \"hi\" \\ bye".to_owned())]
+    );
+}
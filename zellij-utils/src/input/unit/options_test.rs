@@ -0,0 +1,60 @@
+use super::*;
+
+#[test]
+fn glob_matches_literal_text() {
+    assert!(glob_matches("work", "work"));
+    assert!(!glob_matches("work", "work-1"));
+    assert!(!glob_matches("work-1", "work"));
+    assert!(glob_matches("", ""));
+    assert!(!glob_matches("", "work"));
+}
+
+#[test]
+fn glob_star_matches_any_number_of_characters() {
+    assert!(glob_matches("*", ""));
+    assert!(glob_matches("*", "anything"));
+    assert!(glob_matches("work-*", "work-"));
+    assert!(glob_matches("work-*", "work-api"));
+    assert!(!glob_matches("work-*", "scratch-api"));
+    assert!(glob_matches("*-api", "work-api"));
+    assert!(glob_matches("w*-*-api", "work-backend-api"));
+    assert!(glob_matches("*a*a*", "banana"));
+    assert!(!glob_matches("*a*a*a*a", "banana"));
+    assert!(glob_matches("**", "work"));
+}
+
+#[test]
+fn glob_question_mark_matches_exactly_one_character() {
+    assert!(glob_matches("work-?", "work-1"));
+    assert!(!glob_matches("work-?", "work-"));
+    assert!(!glob_matches("work-?", "work-12"));
+    assert!(glob_matches("?ork-*", "fork-it"));
+    assert!(glob_matches("*?", "é"));
+}
+
+#[test]
+fn default_layout_for_session_picks_the_first_matching_pattern() {
+    let options = Options {
+        default_layouts: Some(vec![
+            ("work-*".into(), PathBuf::from("work")),
+            ("work-api".into(), PathBuf::from("api")),
+            ("scratch-?".into(), PathBuf::from("compact")),
+        ]),
+        default_layout: Some(PathBuf::from("strider")),
+        ..Default::default()
+    };
+    assert_eq!(
+        options.default_layout_for_session("work-api"),
+        Some(&PathBuf::from("work"))
+    );
+    assert_eq!(
+        options.default_layout_for_session("scratch-1"),
+        Some(&PathBuf::from("compact"))
+    );
+    assert_eq!(
+        options.default_layout_for_session("scratch-12"),
+        None,
+        "unmatched sessions are left to default_layout"
+    );
+    assert_eq!(Options::default().default_layout_for_session("work"), None);
+}
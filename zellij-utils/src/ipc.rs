@@ -4,7 +4,12 @@ use crate::{
     data::{ClientId, InputMode, Style},
     errors::{get_current_ctx, prelude::*, ErrorContext},
     input::keybinds::Keybinds,
-    input::{actions::Action, layout::Layout, options::Options, plugins::PluginsConfig},
+    input::{
+        actions::Action,
+        layout::{Layout, LayoutSources},
+        options::Options,
+        plugins::PluginsConfig,
+    },
     pane_size::{Size, SizeInPixels},
 };
 use interprocess::local_socket::LocalSocketStream;
@@ -86,6 +91,7 @@ pub enum ClientToServerMsg {
         Box<CliArgs>,
         Box<Options>,
         Box<Layout>,
+        Box<LayoutSources>,
         Option<PluginsConfig>,
     ),
     AttachClient(ClientAttributes, Options),
@@ -94,6 +100,7 @@ pub enum ClientToServerMsg {
     KillSession,
     ConnStatus,
     ListClients,
+    SessionInfo,
 }
 
 // Types of messages sent from the server to the client
@@ -109,6 +116,15 @@ pub enum ServerToClientMsg {
     SwitchToMode(InputMode),
     Connected,
     ActiveClients(Vec<ClientId>),
+    Log(Vec<String>),
+    LogError(Vec<String>),
+    SessionInfo(SessionInfo),
+}
+
+/// What a running session reports about itself when sessions are listed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub layout: String, // the name or path of the layout the session was started with
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -0,0 +1,202 @@
+use crate::input::{
+    config::ConfigError,
+    layout::{normalize_layout_source, Layout, LayoutFile, ValidationOptions},
+    layout_trust::layout_content_hash,
+};
+use crate::{kdl_children_nodes, kdl_first_entry_as_string, kdl_name};
+use kdl::{KdlDocument, KdlNode};
+
+use super::{kdl_layout_error, KdlLayoutParser, LayoutTemplates};
+
+use std::path::{Path, PathBuf};
+
+/// How many levels of files including each other a layout can go through
+pub const MAX_INCLUDE_DEPTH: usize = 10;
+
+impl Layout {
+    /// The templates defined in the files the layout includes with `include "path/to/file.kdl"`
+    /// (and in the files those include), available over `layout_templates` and replacing the ones
+    /// with the same names. Relative paths are looked up in the directory of the including file,
+    /// then in the layout dir. Also returns the included files, so that they are trusted (or not)
+    /// along with the layout.
+    ///
+    /// `layout_path` is `None` for the layouts built into zellij, whose includes can only come
+    /// from the layout dir.
+    pub fn resolve_includes(
+        raw_layout: &str,
+        file_name: &str,
+        layout_path: Option<&Path>,
+        layout_dir: Option<&Path>,
+        validation_options: ValidationOptions,
+        layout_templates: &LayoutTemplates,
+    ) -> Result<(LayoutTemplates, Vec<LayoutFile>), ConfigError> {
+        let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+        let kdl_layout: KdlDocument = match raw_layout.parse() {
+            Ok(kdl_layout) => kdl_layout,
+            // reported when the layout itself is parsed
+            Err(_) => return Ok((layout_templates.clone(), vec![])),
+        };
+        let include_nodes: Vec<&KdlNode> = kdl_layout
+            .nodes()
+            .iter()
+            .filter(|n| kdl_name!(n) == "layout")
+            .filter_map(|layout_node| kdl_children_nodes!(layout_node))
+            .flatten()
+            .filter(|n| kdl_name!(n) == "include")
+            .collect();
+        let mut includes = Includes {
+            layout_dir,
+            validation_options,
+            layout_templates: layout_templates.clone(),
+            including_paths: layout_path
+                .map(|layout_path| vec![Layout::canonical_layout_path(layout_path)])
+                .unwrap_or_default(),
+            included_files: vec![],
+        };
+        includes.include(
+            &include_nodes,
+            file_name,
+            &raw_layout,
+            layout_path.and_then(|p| p.parent()),
+            0,
+        )?;
+        Ok((includes.layout_templates, includes.included_files))
+    }
+}
+
+struct Includes<'a> {
+    layout_dir: Option<&'a Path>,
+    validation_options: ValidationOptions,
+    layout_templates: LayoutTemplates,
+    // the files currently being included, to find cycles
+    including_paths: Vec<PathBuf>,
+    included_files: Vec<LayoutFile>,
+}
+
+impl<'a> Includes<'a> {
+    fn include(
+        &mut self,
+        include_nodes: &[&KdlNode],
+        including_file_name: &str,
+        raw_including_file: &str,
+        including_dir: Option<&Path>,
+        depth: usize,
+    ) -> Result<(), ConfigError> {
+        // errors about an include node point at it in the file it is in
+        let in_including_file = |e: ConfigError| match e {
+            ConfigError::KdlError(kdl_error) if kdl_error.src.is_none() => ConfigError::KdlError(
+                kdl_error.add_src(including_file_name.into(), raw_including_file.into()),
+            ),
+            e => e,
+        };
+        for include_node in include_nodes {
+            let included_path = self
+                .included_path(include_node, including_dir)
+                .map_err(in_including_file)?;
+            let included_file_name = included_path.display().to_string();
+            let canonical_included_path = Layout::canonical_layout_path(&included_path);
+            if self.including_paths.contains(&canonical_included_path) {
+                return Err(in_including_file(ConfigError::new_layout_kdl_error(
+                    format!(
+                        "Include cycle: {} includes {}, which is already including it",
+                        including_file_name, included_file_name
+                    ),
+                    include_node.span().offset(),
+                    include_node.span().len(),
+                )));
+            }
+            if depth >= MAX_INCLUDE_DEPTH {
+                return Err(in_including_file(ConfigError::new_layout_kdl_error(
+                    format!(
+                        "Cannot include {}, layouts can only include files {} levels deep",
+                        included_file_name, MAX_INCLUDE_DEPTH
+                    ),
+                    include_node.span().offset(),
+                    include_node.span().len(),
+                )));
+            }
+            let raw_included_file = std::fs::read_to_string(&included_path)
+                .map_err(|e| ConfigError::IoPath(e, included_path.clone()))?;
+            self.validation_options
+                .limits
+                .check_source(&raw_included_file)?;
+            let content_hash = layout_content_hash(&raw_included_file, None);
+            let (raw_included_file, _had_byte_order_mark) =
+                normalize_layout_source(&raw_included_file);
+            let kdl_included_file: KdlDocument = raw_included_file
+                .parse()
+                .map_err(|e| kdl_layout_error(e, included_file_name.clone(), &raw_included_file))?;
+            // the files it includes come first, so that its own templates can use theirs
+            let nested_include_nodes: Vec<&KdlNode> = kdl_included_file
+                .nodes()
+                .iter()
+                .filter(|n| kdl_name!(n) == "include")
+                .collect();
+            self.including_paths.push(canonical_included_path);
+            self.include(
+                &nested_include_nodes,
+                &included_file_name,
+                &raw_included_file,
+                included_path.parent(),
+                depth + 1,
+            )?;
+            self.including_paths.pop();
+            self.layout_templates =
+                KdlLayoutParser::new(&raw_included_file, None, self.validation_options)
+                    .with_templates(&self.layout_templates)
+                    .parse_included_templates()
+                    .map_err(|e| match e {
+                        ConfigError::KdlError(kdl_error) => ConfigError::KdlError(
+                            kdl_error
+                                .add_src(included_file_name.clone(), raw_included_file.clone()),
+                        ),
+                        ConfigError::KdlDeserializationError(kdl_error) => kdl_layout_error(
+                            kdl_error,
+                            included_file_name.clone(),
+                            &raw_included_file,
+                        ),
+                        e => e,
+                    })?;
+            self.included_files.push(LayoutFile {
+                path: included_file_name,
+                from_default_assets: false,
+                content_hash,
+            });
+        }
+        Ok(())
+    }
+    fn included_path(
+        &self,
+        include_node: &KdlNode,
+        including_dir: Option<&Path>,
+    ) -> Result<PathBuf, ConfigError> {
+        let path = kdl_first_entry_as_string!(include_node)
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                ConfigError::new_layout_kdl_error(
+                    "include needs the path of the file to include, eg. include \"templates.kdl\""
+                        .into(),
+                    include_node.span().offset(),
+                    include_node.span().len(),
+                )
+            })?;
+        if path.is_absolute() {
+            return Ok(path);
+        }
+        including_dir
+            .map(|dir| dir.join(&path))
+            .into_iter()
+            .chain(self.layout_dir.map(|dir| dir.join(&path)))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| {
+                ConfigError::new_layout_kdl_error(
+                    format!(
+                        "Could not find the included file {} next to the layout or in the layout dir",
+                        path.display()
+                    ),
+                    include_node.span().offset(),
+                    include_node.span().len(),
+                )
+            })
+    }
+}
@@ -1,22 +1,35 @@
 use crate::input::{
-    command::RunCommand,
+    command::{
+        expand_env_vars, expand_home, CommandWrapper, PaneDependency, PaneEncoding, ReadyCondition,
+        RunCommand,
+    },
     config::ConfigError,
     layout::{
-        FloatingPaneLayout, Layout, LayoutConstraint, PercentOrFixed, Run, RunPlugin,
-        RunPluginLocation, SplitDirection, SplitSize, SwapFloatingLayout, SwapTiledLayout,
-        TiledPaneLayout,
+        synthesized_swap_layout_name, ExtraPanesPolicy, FitToContent, FloatingPaneAnchor,
+        FloatingPaneLayout, FocusPolicy, Layout, LayoutConstraint, LayoutDiagnostic,
+        LayoutLocation, LayoutSpan, LayoutSpans, MissingSlotsPolicy, PaneRef, PercentOrFixed, Run,
+        RunPlugin, RunPluginLocation, SessionConstraint, SidePanelLayout, SizeAdjustment,
+        SplitDirection, SplitSize, SwapFloatingLayout, SwapTiledLayout, TabPin, TiledPaneLayout,
+        ValidationOptions,
+    },
+    layout_capabilities::{
+        deprecated_name, layout_capability_values, DeprecatedName, DeprecatedNameKind,
+        DEFAULT_FOCUS, FLOATING_PANE_ANCHORS, PANE_ENCODING, SWAP_CONSTRAINTS, TAB_PIN,
     },
+    layout_stats::{elapsed_ms, LayoutLoadStats},
 };
 
 use kdl::*;
 
+use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::str::FromStr;
+use std::time::Instant;
 
 use crate::{
-    kdl_child_with_name, kdl_children_nodes, kdl_get_bool_property_or_child_value,
-    kdl_get_bool_property_or_child_value_with_error, kdl_get_child,
-    kdl_get_int_property_or_child_value, kdl_get_property_or_child,
+    kdl_child_with_name, kdl_children_nodes, kdl_first_entry_as_string,
+    kdl_get_bool_property_or_child_value, kdl_get_bool_property_or_child_value_with_error,
+    kdl_get_child, kdl_get_int_property_or_child_value, kdl_get_property_or_child,
     kdl_get_string_property_or_child_value, kdl_get_string_property_or_child_value_with_error,
     kdl_name, kdl_parsing_error, kdl_property_names, kdl_property_or_child_value_node,
     kdl_string_arguments,
@@ -34,24 +47,221 @@ pub enum PaneOrFloatingPane {
     Either(TiledPaneLayout),
 }
 
+/// Pane and tab templates defined outside of a layout (in the `layout_templates` block of the
+/// config or in the files the layout includes), the layouts parsed with them can use them as if
+/// they defined them
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LayoutTemplates {
+    tab_templates: HashMap<String, (TiledPaneLayout, Vec<FloatingPaneLayout>, KdlNode)>,
+    pane_templates: HashMap<String, (PaneOrFloatingPane, KdlNode)>,
+    default_tab_template: Option<(TiledPaneLayout, Vec<FloatingPaneLayout>, KdlNode)>,
+}
+
 pub struct KdlLayoutParser<'a> {
     global_cwd: Option<PathBuf>,
     raw_layout: &'a str,
     tab_templates: HashMap<String, (TiledPaneLayout, Vec<FloatingPaneLayout>, KdlNode)>,
     pane_templates: HashMap<String, (PaneOrFloatingPane, KdlNode)>,
     default_tab_template: Option<(TiledPaneLayout, Vec<FloatingPaneLayout>, KdlNode)>,
+    // the templates that came from outside of the layout and were not redefined in it
+    shared_template_names: HashSet<String>,
+    shares_default_tab_template: bool,
+    spans: LayoutSpans,
+    parsing_swap_layouts: bool,
+    // the swap layouts of a tab have no spans, those are indexed by the swap layouts of the layout
+    parsing_tab_swap_layouts: bool,
+    // swap tiled layout entries made of pane refs, resolved once the base layout is parsed
+    unresolved_pane_refs: Vec<(usize, LayoutConstraint, Vec<(PaneRef, KdlNode)>)>,
+    validation_options: ValidationOptions,
+    warnings: RefCell<Vec<LayoutDiagnostic>>,
+    load_stats: LayoutLoadStats,
 }
 
 impl<'a> KdlLayoutParser<'a> {
-    pub fn new(raw_layout: &'a str, global_cwd: Option<PathBuf>) -> Self {
+    pub fn new(
+        raw_layout: &'a str,
+        global_cwd: Option<PathBuf>,
+        validation_options: ValidationOptions,
+    ) -> Self {
         KdlLayoutParser {
             raw_layout,
             tab_templates: HashMap::new(),
             pane_templates: HashMap::new(),
             default_tab_template: None,
+            shared_template_names: HashSet::new(),
+            shares_default_tab_template: false,
             global_cwd,
+            spans: LayoutSpans::default(),
+            parsing_swap_layouts: false,
+            parsing_tab_swap_layouts: false,
+            unresolved_pane_refs: vec![],
+            validation_options,
+            warnings: RefCell::new(vec![]),
+            load_stats: LayoutLoadStats::default(),
+        }
+    }
+    /// Makes the templates available to the layout, templates it defines with the same names
+    /// replace them
+    pub fn with_templates(mut self, layout_templates: &LayoutTemplates) -> Self {
+        self.tab_templates = layout_templates.tab_templates.clone();
+        self.pane_templates = layout_templates.pane_templates.clone();
+        self.default_tab_template = layout_templates.default_tab_template.clone();
+        self.shared_template_names = layout_templates
+            .tab_templates
+            .keys()
+            .chain(layout_templates.pane_templates.keys())
+            .cloned()
+            .collect();
+        self.shares_default_tab_template = layout_templates.default_tab_template.is_some();
+        self
+    }
+    /// Parses the templates of a `layout_templates` block, along with the ones this parser was
+    /// given
+    pub fn parse_templates(
+        &mut self,
+        layout_templates_node: &KdlNode,
+    ) -> Result<LayoutTemplates, ConfigError> {
+        if let Some(children_document) = layout_templates_node.children() {
+            let children = children_document.nodes();
+            for child in children {
+                let child_name = kdl_name!(child);
+                if child_name != "pane_template"
+                    && child_name != "tab_template"
+                    && child_name != "default_tab_template"
+                {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "Unknown layout_templates node: '{}' (only templates can be defined here)",
+                            child_name
+                        ),
+                        child
+                    ));
+                }
+            }
+            self.populate_pane_templates(children, children_document)?;
+            self.populate_tab_templates(children)?;
+        }
+        Ok(self.layout_templates())
+    }
+    /// Parses the templates of a layout file included by another one (see
+    /// [`Layout::resolve_includes`]), along with the ones this parser was given. The `include`
+    /// nodes of the file are resolved by the caller.
+    pub fn parse_included_templates(&mut self) -> Result<LayoutTemplates, ConfigError> {
+        let mut kdl_fragment: KdlDocument = self.raw_layout.parse()?;
+        self.validation_options
+            .limits
+            .check_node_count(kdl_node_count(&kdl_fragment))?;
+        self.translate_deprecated_names(&mut kdl_fragment)?;
+        let children = kdl_fragment.nodes();
+        for child in children {
+            let child_name = kdl_name!(child);
+            if child_name != "pane_template"
+                && child_name != "tab_template"
+                && child_name != "default_tab_template"
+                && child_name != "include"
+            {
+                return Err(kdl_parsing_error!(
+                    format!(
+                        "Unknown node in an included layout file: '{}' (only templates and includes can be defined here)",
+                        child_name
+                    ),
+                    child
+                ));
+            }
+        }
+        self.populate_pane_templates(children, &kdl_fragment)?;
+        self.populate_tab_templates(children)?;
+        Ok(self.layout_templates())
+    }
+    fn layout_templates(&self) -> LayoutTemplates {
+        LayoutTemplates {
+            tab_templates: self.tab_templates.clone(),
+            pane_templates: self.pane_templates.clone(),
+            default_tab_template: self.default_tab_template.clone(),
         }
     }
+    // a template defined in the layout replaces the shared template with the same name
+    fn shadow_shared_template(&mut self, template_name: &str, kdl_node: &KdlNode) {
+        let is_shared = if template_name == "default_tab_template" {
+            std::mem::replace(&mut self.shares_default_tab_template, false)
+        } else {
+            self.shared_template_names.remove(template_name)
+        };
+        if is_shared {
+            self.tab_templates.remove(template_name);
+            self.pane_templates.remove(template_name);
+            let mut warning = LayoutDiagnostic::warning(
+                "shadowed-template",
+                format!(
+                    "The layout's \"{}\" replaces the one defined in the config or in an included file",
+                    template_name
+                ),
+                None,
+            );
+            warning.span = Some(LayoutSpan {
+                offset: kdl_node.span().offset(),
+                len: kdl_node.span().len(),
+                in_swap_layout_file: self.parsing_swap_layouts,
+            });
+            self.warnings.borrow_mut().push(warning);
+        }
+    }
+    // old spellings are renamed before parsing, in strict mode they are refused instead
+    fn translate_deprecated_names(
+        &self,
+        kdl_document: &mut KdlDocument,
+    ) -> Result<(), ConfigError> {
+        for (deprecated, offset, len) in migrate_deprecated_names(kdl_document) {
+            if self.validation_options.strict {
+                return Err(ConfigError::new_layout_kdl_error(
+                    format!(
+                        "'{}' was renamed to '{}', please use '{}' instead",
+                        deprecated.old, deprecated.new, deprecated.new
+                    ),
+                    offset,
+                    len,
+                ));
+            }
+            let mut warning = LayoutDiagnostic::warning(
+                "deprecated-name",
+                format!(
+                    "'{}' is deprecated and was read as '{}', its new spelling",
+                    deprecated.old, deprecated.new
+                ),
+                None,
+            );
+            warning.span = Some(LayoutSpan {
+                offset,
+                len,
+                in_swap_layout_file: self.parsing_swap_layouts,
+            });
+            self.warnings.borrow_mut().push(warning);
+        }
+        Ok(())
+    }
+    pub fn spans(&self) -> &LayoutSpans {
+        &self.spans
+    }
+    /// How long parsing and expanding the layout (and its swap layouts) took, the geometry is
+    /// left to the caller
+    pub fn load_stats(&self) -> LayoutLoadStats {
+        self.load_stats
+    }
+    /// Problems found while parsing that are only errors in strict mode
+    pub fn warnings(&self) -> Vec<LayoutDiagnostic> {
+        self.warnings.borrow().clone()
+    }
+    fn record_span(&mut self, location: LayoutLocation, kdl_node: &KdlNode) {
+        if self.parsing_tab_swap_layouts {
+            return;
+        }
+        let span = LayoutSpan {
+            offset: kdl_node.span().offset(),
+            len: kdl_node.span().len(),
+            in_swap_layout_file: self.parsing_swap_layouts,
+        };
+        self.spans.insert(location, span);
+    }
     fn is_a_reserved_word(&self, word: &str) -> bool {
         word == "pane"
             || word == "layout"
@@ -66,20 +276,49 @@ impl<'a> KdlLayoutParser<'a> {
             || word == "args"
             || word == "close_on_exit"
             || word == "start_suspended"
+            || word == "strict_placeholders"
+            || word == "depends_on"
+            || word == "ready_when"
+            || word == "ready_regex"
+            || word == "ready_timeout"
+            || word == "encoding"
+            || word == "env"
+            || word == "on_failure"
             || word == "borderless"
             || word == "focus"
+            || word == "expanded"
             || word == "name"
             || word == "size"
+            || word == "min_size"
+            || word == "max_size"
             || word == "cwd"
             || word == "split_direction"
+            || word == "stack_below_width"
             || word == "swap_tiled_layout"
             || word == "swap_floating_layout"
+            || word == "side_panel"
+            || word == "command_spawn_interval_ms"
+            || word == "default_focus"
+            || word == "command_wrapper"
+            || word == "session_name"
+            || word == "attach_to_existing"
+            || word == "focused_tab"
+            || word == "stack"
+            || word == "include"
+            || word == "vars"
     }
     fn is_a_valid_pane_property(&self, property_name: &str) -> bool {
         property_name == "borderless"
+            || property_name == "hide_title"
+            || property_name == "read_only"
+            || property_name == "no_wrapper"
+            || property_name == "exclude_from_dump"
             || property_name == "focus"
+            || property_name == "expanded"
             || property_name == "name"
             || property_name == "size"
+            || property_name == "min_size"
+            || property_name == "max_size"
             || property_name == "plugin"
             || property_name == "command"
             || property_name == "edit"
@@ -87,12 +326,30 @@ impl<'a> KdlLayoutParser<'a> {
             || property_name == "args"
             || property_name == "close_on_exit"
             || property_name == "start_suspended"
+            || property_name == "strict_placeholders"
+            || property_name == "depends_on"
+            || property_name == "ready_when"
+            || property_name == "ready_regex"
+            || property_name == "ready_timeout"
+            || property_name == "encoding"
+            || property_name == "env"
+            || property_name == "if_env"
+            || property_name == "if_command_exists"
+            || property_name == "on_failure"
             || property_name == "split_direction"
+            || property_name == "stack_below_width"
             || property_name == "pane"
             || property_name == "children"
     }
     fn is_a_valid_floating_pane_property(&self, property_name: &str) -> bool {
         property_name == "borderless"
+            || property_name == "hide_title"
+            || property_name == "pinned"
+            || property_name == "anchor"
+            || property_name == "expanded"
+            || property_name == "read_only"
+            || property_name == "no_wrapper"
+            || property_name == "exclude_from_dump"
             || property_name == "focus"
             || property_name == "name"
             || property_name == "plugin"
@@ -102,10 +359,23 @@ impl<'a> KdlLayoutParser<'a> {
             || property_name == "args"
             || property_name == "close_on_exit"
             || property_name == "start_suspended"
+            || property_name == "strict_placeholders"
+            || property_name == "depends_on"
+            || property_name == "ready_when"
+            || property_name == "ready_regex"
+            || property_name == "ready_timeout"
+            || property_name == "encoding"
+            || property_name == "env"
+            || property_name == "if_env"
+            || property_name == "if_command_exists"
             || property_name == "x"
             || property_name == "y"
             || property_name == "width"
             || property_name == "height"
+            || property_name == "min_width"
+            || property_name == "max_width"
+            || property_name == "min_height"
+            || property_name == "max_height"
     }
     fn is_a_valid_tab_property(&self, property_name: &str) -> bool {
         property_name == "focus"
@@ -114,8 +384,19 @@ impl<'a> KdlLayoutParser<'a> {
             || property_name == "cwd"
             || property_name == "floating_panes"
             || property_name == "children"
+            || property_name == "exact_panes"
             || property_name == "max_panes"
             || property_name == "min_panes"
+            || property_name == "max_groups"
+            || property_name == "min_groups"
+            || property_name == "session_tabs_max"
+            || property_name == "session_tabs_min"
+            || property_name == "extra_panes"
+            || property_name == "missing_slots"
+            || property_name == "pin"
+            || property_name == "max_content_width"
+            || property_name == "if_env"
+            || property_name == "if_command_exists"
     }
     fn assert_legal_node_name(&self, name: &str, kdl_node: &KdlNode) -> Result<(), ConfigError> {
         if name.contains(char::is_whitespace) {
@@ -166,34 +447,28 @@ impl<'a> KdlLayoutParser<'a> {
             Ok(())
         }
     }
-    fn parse_split_size(&self, kdl_node: &KdlNode) -> Result<Option<SplitSize>, ConfigError> {
-        if let Some(size) = kdl_get_string_property_or_child_value!(kdl_node, "size") {
-            match SplitSize::from_str(size) {
-                Ok(size) => Ok(Some(size)),
-                Err(_e) => Err(kdl_parsing_error!(
-                    format!(
-                        "size should be a fixed number (eg. 1) or a quoted percent (eg. \"50%\")"
-                    ),
-                    kdl_node
-                )),
-            }
-        } else if let Some(size) = kdl_get_int_property_or_child_value!(kdl_node, "size") {
-            if size == 0 {
-                return Err(kdl_parsing_error!(
-                    format!("size should be greater than 0"),
-                    kdl_node
-                ));
-            }
-            Ok(Some(SplitSize::Fixed(size as usize)))
-        } else if let Some(node) = kdl_property_or_child_value_node!(kdl_node, "size") {
+    /// How a size-like value (eg. `size`, `x` or `min_width`) is spelled, a bare integer
+    /// (`x 10`) is spelled like a quoted one (`x "10"`) so that both parse the same way
+    fn size_like_value(
+        &self,
+        kdl_node: &KdlNode,
+        value_name: &str,
+        expected: &str,
+    ) -> Result<Option<String>, ConfigError> {
+        if let Some(value) = kdl_get_string_property_or_child_value!(kdl_node, value_name) {
+            Ok(Some(value.to_owned()))
+        } else if let Some(value) = kdl_get_int_property_or_child_value!(kdl_node, value_name) {
+            Ok(Some(value.to_string()))
+        } else if let Some(node) = kdl_property_or_child_value_node!(kdl_node, value_name) {
             Err(kdl_parsing_error!(
-                format!("size should be a fixed number (eg. 1) or a quoted percent (eg. \"50%\")"),
+                format!("{} should be {}", value_name, expected),
                 node
             ))
-        } else if let Some(node) = kdl_child_with_name!(kdl_node, "size") {
+        } else if let Some(node) = kdl_child_with_name!(kdl_node, value_name) {
             Err(kdl_parsing_error!(
                 format!(
-                    "size cannot be bare, it should have a value (eg. 'size 1', or 'size \"50%\"')"
+                    "{} cannot be bare, it should have a value that is {}",
+                    value_name, expected
                 ),
                 node
             ))
@@ -201,59 +476,216 @@ impl<'a> KdlLayoutParser<'a> {
             Ok(None)
         }
     }
+    fn parse_split_size(&self, kdl_node: &KdlNode) -> Result<Option<SplitSize>, ConfigError> {
+        let expected =
+            "a fixed number (eg. 1), a quoted percent (eg. \"50%\") or a quoted weight (eg. \"2w\")";
+        let size = match self.size_like_value(kdl_node, "size", expected)? {
+            Some(size) => size,
+            None => return Ok(None),
+        };
+        match SplitSize::from_str(&size) {
+            Ok(SplitSize::Fixed(0)) => Err(kdl_parsing_error!(
+                format!("size should be greater than 0"),
+                kdl_node
+            )),
+            Ok(size) => Ok(Some(size)),
+            Err(_e) => Err(kdl_parsing_error!(
+                format!("size should be {}", expected),
+                kdl_node
+            )),
+        }
+    }
+    fn parse_size_bound(
+        &self,
+        kdl_node: &KdlNode,
+        bound_name: &str,
+    ) -> Result<Option<SplitSize>, ConfigError> {
+        let expected = "a fixed number (eg. 30) or a quoted percent (eg. \"25%\")";
+        let bound = match self.size_like_value(kdl_node, bound_name, expected)? {
+            Some(bound) => bound,
+            None => return Ok(None),
+        };
+        match SplitSize::from_str(&bound) {
+            Ok(SplitSize::Fixed(0)) | Ok(SplitSize::Percent(0)) => Err(kdl_parsing_error!(
+                format!("{} should be greater than 0", bound_name),
+                kdl_node
+            )),
+            Ok(bound @ SplitSize::Fixed(_)) | Ok(bound @ SplitSize::Percent(_)) => Ok(Some(bound)),
+            Ok(SplitSize::Weight(_)) | Err(_) => Err(kdl_parsing_error!(
+                format!("{} should be {}", bound_name, expected),
+                kdl_node
+            )),
+        }
+    }
+    /// The `min_size` and `max_size` of a pane
+    fn parse_size_bounds(
+        &self,
+        kdl_node: &KdlNode,
+    ) -> Result<(Option<SplitSize>, Option<SplitSize>), ConfigError> {
+        let min_size = self.parse_size_bound(kdl_node, "min_size")?;
+        let max_size = self.parse_size_bound(kdl_node, "max_size")?;
+        match (min_size, max_size) {
+            (Some(SplitSize::Fixed(min)), Some(SplitSize::Fixed(max)))
+            | (Some(SplitSize::Percent(min)), Some(SplitSize::Percent(max)))
+                if min > max =>
+            {
+                Err(kdl_parsing_error!(
+                    format!("min_size cannot be larger than max_size"),
+                    kdl_node
+                ))
+            },
+            bounds => Ok(bounds),
+        }
+    }
     fn parse_percent_or_fixed(
         &self,
         kdl_node: &KdlNode,
         value_name: &str,
-        can_be_zero: bool,
     ) -> Result<Option<PercentOrFixed>, ConfigError> {
-        if let Some(size) = kdl_get_string_property_or_child_value!(kdl_node, value_name) {
-            match PercentOrFixed::from_str(size) {
-                Ok(size) => {
-                    if !can_be_zero && size.is_zero() {
-                        Err(kdl_parsing_error!(
-                            format!("{} should be greater than 0", value_name),
-                            kdl_node
-                        ))
-                    } else {
-                        Ok(Some(size))
-                    }
-                },
-                Err(_e) => Err(kdl_parsing_error!(
-                    format!(
-                        "{} should be a fixed number (eg. 1) or a quoted percent (eg. \"50%\")",
-                        value_name
-                    ),
-                    kdl_node
-                )),
-            }
-        } else if let Some(size) = kdl_get_int_property_or_child_value!(kdl_node, value_name) {
-            if size == 0 && !can_be_zero {
-                return Err(kdl_parsing_error!(
-                    format!("{} should be greater than 0", value_name),
-                    kdl_node
-                ));
+        let expected = "a fixed number (eg. 1) or a quoted percent (eg. \"50%\")";
+        let value = match self.size_like_value(kdl_node, value_name, expected)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        match PercentOrFixed::from_str(&value) {
+            Ok(value) if value.is_zero() => Err(kdl_parsing_error!(
+                format!("{} should be greater than 0", value_name),
+                kdl_node
+            )),
+            Ok(value) if !value.is_from_far_edge() => Ok(Some(value)),
+            _ => Err(kdl_parsing_error!(
+                format!("{} should be {}", value_name, expected),
+                kdl_node
+            )),
+        }
+    }
+    // the x or y of a floating pane (`value_name`), which can also be measured from the far edge
+    fn parse_floating_pane_coordinate(
+        &self,
+        kdl_node: &KdlNode,
+        value_name: &str,
+    ) -> Result<Option<PercentOrFixed>, ConfigError> {
+        let expected = "a fixed number (eg. 1) or a quoted percent (eg. \"50%\"), or either of them from the far edge (eg. -10 or \"-10%\")";
+        let value = match self.size_like_value(kdl_node, value_name, expected)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        match PercentOrFixed::from_str(&value) {
+            Ok(value) => Ok(Some(value)),
+            Err(_e) => Err(kdl_parsing_error!(
+                format!("{} should be {}", value_name, expected),
+                kdl_node
+            )),
+        }
+    }
+    // the size of a floating pane (`value_name` is "height" or "width"), along with how to fit it
+    // to the output of its command when it is "fit"
+    fn parse_floating_pane_size(
+        &self,
+        kdl_node: &KdlNode,
+        value_name: &str,
+    ) -> Result<(Option<PercentOrFixed>, Option<FitToContent>), ConfigError> {
+        let min_name = format!("min_{}", value_name);
+        let max_name = format!("max_{}", value_name);
+        let min = self.parse_fit_bound(kdl_node, &min_name)?;
+        if self.is_fit_size(kdl_node, value_name) {
+            let max = self.parse_fit_bound(kdl_node, &max_name)?;
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(kdl_parsing_error!(
+                        format!("{} cannot be larger than {}", min_name, max_name),
+                        kdl_node
+                    ));
+                }
             }
-            Ok(Some(PercentOrFixed::Fixed(size as usize)))
-        } else if let Some(node) = kdl_property_or_child_value_node!(kdl_node, "size") {
-            Err(kdl_parsing_error!(
-                format!(
-                    "{} should be a fixed number (eg. 1) or a quoted percent (eg. \"50%\")",
-                    value_name
-                ),
-                node
-            ))
-        } else if let Some(node) = kdl_child_with_name!(kdl_node, "size") {
-            Err(kdl_parsing_error!(
+            let fit = FitToContent { min, max };
+            let initial_size = if value_name == "height" {
+                FitToContent::INITIAL_ROWS
+            } else {
+                FitToContent::INITIAL_COLS
+            };
+            let initial_size = PercentOrFixed::Fixed(fit.bound(initial_size, usize::MAX));
+            return Ok((Some(initial_size), Some(fit)));
+        }
+        if min.is_some() {
+            return Err(kdl_parsing_error!(
                 format!(
-                    "{} cannot be bare, it should have a value (eg. 'size 1', or 'size \"50%\"')",
-                    value_name
+                    "{} only applies to a {} of \"fit\" (eg. '{}=\"fit\"')",
+                    min_name, value_name, value_name
                 ),
-                node
-            ))
-        } else {
-            Ok(None)
+                kdl_node
+            ));
+        }
+        Ok((self.parse_percent_or_fixed(kdl_node, value_name)?, None))
+    }
+    // the max_height or max_width of a floating pane (`value_name` is "height" or "width"), those
+    // of a "fit" size are bounds of the fit instead (see `parse_floating_pane_size`)
+    fn parse_floating_pane_max_size(
+        &self,
+        kdl_node: &KdlNode,
+        value_name: &str,
+    ) -> Result<Option<PercentOrFixed>, ConfigError> {
+        if self.is_fit_size(kdl_node, value_name) {
+            return Ok(None);
         }
+        self.parse_percent_or_fixed(kdl_node, &format!("max_{}", value_name))
+    }
+    fn is_fit_size(&self, kdl_node: &KdlNode, value_name: &str) -> bool {
+        kdl_get_string_property_or_child_value!(kdl_node, value_name) == Some("fit")
+    }
+    fn parse_fit_bound(
+        &self,
+        kdl_node: &KdlNode,
+        bound_name: &str,
+    ) -> Result<Option<usize>, ConfigError> {
+        self.parse_cell_count(kdl_node, bound_name, "a fixed number (eg. 20)")
+    }
+    /// A number of columns or rows that is greater than 0
+    fn parse_cell_count(
+        &self,
+        kdl_node: &KdlNode,
+        value_name: &str,
+        expected: &str,
+    ) -> Result<Option<usize>, ConfigError> {
+        let count = match self.size_like_value(kdl_node, value_name, expected)? {
+            Some(count) => count,
+            None => return Ok(None),
+        };
+        match usize::from_str(&count) {
+            Ok(count) if count > 0 => Ok(Some(count)),
+            Ok(_) => Err(kdl_parsing_error!(
+                format!("{} should be greater than 0", value_name),
+                kdl_node
+            )),
+            Err(_e) => Err(kdl_parsing_error!(
+                format!("{} should be {}", value_name, expected),
+                kdl_node
+            )),
+        }
+    }
+    // only the output of a command is known once it exits, other panes keep their initial size
+    fn ignore_fit_unless_command_pane(
+        &self,
+        floating_pane: &mut FloatingPaneLayout,
+        kdl_node: &KdlNode,
+    ) {
+        let has_fit = floating_pane.height_fit.is_some() || floating_pane.width_fit.is_some();
+        if !has_fit || matches!(floating_pane.run, Some(Run::Command(_))) {
+            return;
+        }
+        floating_pane.height_fit = None;
+        floating_pane.width_fit = None;
+        let mut warning = LayoutDiagnostic::warning(
+            "fit-ignored",
+            "\"fit\" only resizes floating panes running a command once it exits, this pane keeps its initial size".into(),
+            None,
+        );
+        warning.span = Some(LayoutSpan {
+            offset: kdl_node.span().offset(),
+            len: kdl_node.span().len(),
+            in_swap_layout_file: self.parsing_swap_layouts,
+        });
+        self.warnings.borrow_mut().push(warning);
     }
     fn parse_plugin_block(&self, plugin_block: &KdlNode) -> Result<Option<Run>, ConfigError> {
         let _allow_exec_host_cmd =
@@ -293,16 +725,198 @@ impl<'a> KdlLayoutParser<'a> {
                 if kdl_args.entries().is_empty() {
                     return Err(kdl_parsing_error!(format!("args cannot be empty and should contain one or more command arguments (eg. args \"-h\" \"-v\")"), kdl_args));
                 }
-                Ok(Some(
-                    kdl_string_arguments!(kdl_args)
-                        .iter()
-                        .map(|s| String::from(*s))
-                        .collect(),
-                ))
+                let args = kdl_string_arguments!(kdl_args);
+                kdl_args
+                    .entries()
+                    .iter()
+                    .zip(args)
+                    .map(|(entry, arg)| {
+                        self.expand_env_vars(arg, "args", entry.span().offset(), entry.span().len())
+                    })
+                    .collect::<Result<Vec<String>, ConfigError>>()
+                    .map(Some)
             },
             None => Ok(None),
         }
     }
+    // each child of the env block is a variable with its value (eg. `env { RUST_LOG "debug"; }`),
+    // environment variables in the values are expanded as they are in the args
+    fn parse_env(
+        &self,
+        pane_node: &KdlNode,
+    ) -> Result<Option<BTreeMap<String, String>>, ConfigError> {
+        let kdl_env = match kdl_get_child!(pane_node, "env") {
+            Some(kdl_env) => kdl_env,
+            None => return Ok(None),
+        };
+        let variables = kdl_children_nodes!(kdl_env).unwrap_or(&[]);
+        if variables.is_empty() {
+            return Err(kdl_parsing_error!(format!("env cannot be empty and should contain one or more variables (eg. env {{ RUST_LOG \"debug\"; }})"), kdl_env));
+        }
+        let mut env = BTreeMap::new();
+        for variable in variables {
+            let name = kdl_name!(variable);
+            let string_value = match variable.entries() {
+                [entry] if entry.name().is_none() => {
+                    entry.value().as_string().map(|value| (entry, value))
+                },
+                _ => None,
+            };
+            let (entry, value) = match string_value {
+                Some(string_value) => string_value,
+                None => {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "The environment variable {} should have a single string value (eg. {} \"value\")",
+                            name, name
+                        ),
+                        variable
+                    ));
+                },
+            };
+            let value =
+                self.expand_env_vars(value, "env", entry.span().offset(), entry.span().len())?;
+            if env.insert(name.to_owned(), value).is_some() {
+                return Err(kdl_parsing_error!(
+                    format!("The environment variable {} is set more than once", name),
+                    variable
+                ));
+            }
+        }
+        Ok(Some(env))
+    }
+    // `$VAR` and `${VAR}` are taken from the environment zellij was started in when the layout is
+    // loaded, `$$` is a literal dollar
+    fn expand_env_vars(
+        &self,
+        value: &str,
+        node_name: &str,
+        offset: usize,
+        len: usize,
+    ) -> Result<String, ConfigError> {
+        expand_env_vars(value).map_err(|variable| {
+            ConfigError::new_kdl_error(
+                format!(
+                    "The environment variable {} used in {} is not set (a literal dollar is written $$)",
+                    variable, node_name
+                ),
+                offset,
+                len,
+            )
+        })
+    }
+    // environment variables are expanded as in `expand_env_vars` and a leading `~` is the home
+    // directory
+    fn expand_path(
+        &self,
+        path: &str,
+        node_name: &str,
+        offset: usize,
+        len: usize,
+    ) -> Result<PathBuf, ConfigError> {
+        let expanded = self.expand_env_vars(path, node_name, offset, len)?;
+        // a `~` coming from the value of a variable is taken as is, as in shells
+        if path.starts_with('~') {
+            Ok(expand_home(&expanded))
+        } else {
+            Ok(PathBuf::from(expanded))
+        }
+    }
+    // the offset and length of the value of a property or child node, for errors about it
+    fn value_span(&self, kdl_node: &KdlNode, name: &str) -> (usize, usize) {
+        let span = kdl_property_or_child_value_node!(kdl_node, name)
+            .map(|entry| entry.span())
+            .unwrap_or_else(|| kdl_node.span());
+        (span.offset(), span.len())
+    }
+    // either `edit="file"` or `edit "file" "other_file" editor_splits="vertical"`, opening the
+    // other files as splits of the same editor
+    fn parse_edit(
+        &self,
+        pane_node: &KdlNode,
+    ) -> Result<Option<(Vec<PathBuf>, Option<SplitDirection>)>, ConfigError> {
+        let kdl_edit = match kdl_get_child!(pane_node, "edit") {
+            Some(kdl_edit) if pane_node.get("edit").is_none() => kdl_edit,
+            _ => {
+                return match kdl_get_string_property_or_child_value_with_error!(pane_node, "edit") {
+                    Some(file_to_edit) => {
+                        let (offset, len) = self.value_span(pane_node, "edit");
+                        Ok(Some((
+                            vec![self.expand_path(file_to_edit, "edit", offset, len)?],
+                            None,
+                        )))
+                    },
+                    None => Ok(None),
+                }
+            },
+        };
+        let mut files_to_edit = vec![];
+        for entry in kdl_edit.entries().iter().filter(|e| e.name().is_none()) {
+            match entry.value().as_string() {
+                Some(file_to_edit) => files_to_edit.push(self.expand_path(
+                    file_to_edit,
+                    "edit",
+                    entry.span().offset(),
+                    entry.span().len(),
+                )?),
+                None => {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "edit should be a string, found {} - not a string",
+                            entry.value()
+                        ),
+                        entry
+                    ))
+                },
+            }
+        }
+        if files_to_edit.is_empty() {
+            return Err(kdl_parsing_error!(
+                "edit should contain one or more files (eg. edit \"a.rs\" \"b.rs\")".into(),
+                kdl_edit
+            ));
+        }
+        let editor_splits = match kdl_edit.get("editor_splits") {
+            Some(entry) => match entry
+                .value()
+                .as_string()
+                .and_then(|direction| SplitDirection::from_str(direction).ok())
+                // the editor splits its own window, which the layout knows nothing about
+                .filter(|direction| *direction != SplitDirection::Auto)
+            {
+                Some(editor_splits) => Some(editor_splits),
+                None => {
+                    let error_message = format!(
+                        "editor_splits should be either \"horizontal\" or \"vertical\" found: {}",
+                        entry.value()
+                    );
+                    return Err(kdl_parsing_error!(error_message, entry));
+                },
+            },
+            None => None,
+        };
+        Ok(Some((files_to_edit, editor_splits)))
+    }
+    fn parse_swap_layout_tags(
+        &self,
+        swap_layout_node: &KdlNode,
+    ) -> Result<Vec<String>, ConfigError> {
+        match kdl_get_child!(swap_layout_node, "tags") {
+            Some(kdl_tags) => {
+                if kdl_tags.entries().is_empty() {
+                    return Err(kdl_parsing_error!(
+                        format!("tags cannot be empty and should contain one or more tags (eg. tags \"review\" \"wide\")"),
+                        kdl_tags
+                    ));
+                }
+                Ok(kdl_string_arguments!(kdl_tags)
+                    .iter()
+                    .map(|s| String::from(*s))
+                    .collect())
+            },
+            None => Ok(vec![]),
+        }
+    }
     fn cwd_prefix(&self, tab_cwd: Option<&PathBuf>) -> Result<Option<PathBuf>, ConfigError> {
         Ok(match (&self.global_cwd, tab_cwd) {
             (Some(global_cwd), Some(tab_cwd)) => Some(global_cwd.join(tab_cwd)),
@@ -311,38 +925,145 @@ impl<'a> KdlLayoutParser<'a> {
             (None, None) => None,
         })
     }
+    // the floating panes are parsed without a cwd prefix, which is added once the tab they are in
+    // is known
+    fn add_cwd_to_floating_panes(
+        &self,
+        floating_panes: &mut [FloatingPaneLayout],
+        tab_cwd: Option<&PathBuf>,
+    ) -> Result<(), ConfigError> {
+        if let Some(cwd_prefix) = self.cwd_prefix(tab_cwd)? {
+            for floating_pane in floating_panes.iter_mut() {
+                floating_pane.add_cwd_to_layout(&cwd_prefix);
+            }
+        }
+        Ok(())
+    }
     fn parse_cwd(&self, kdl_node: &KdlNode) -> Result<Option<PathBuf>, ConfigError> {
-        Ok(
-            kdl_get_string_property_or_child_value_with_error!(kdl_node, "cwd")
-                .map(|cwd| PathBuf::from(cwd)),
-        )
+        match kdl_get_string_property_or_child_value_with_error!(kdl_node, "cwd") {
+            Some(cwd) => {
+                let (offset, len) = self.value_span(kdl_node, "cwd");
+                self.expand_path(cwd, "cwd", offset, len).map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+    fn parse_pane_dependency(
+        &self,
+        kdl_node: &KdlNode,
+    ) -> Result<Option<PaneDependency>, ConfigError> {
+        let pane_name = kdl_get_string_property_or_child_value_with_error!(kdl_node, "depends_on");
+        let ready_when = kdl_get_string_property_or_child_value_with_error!(kdl_node, "ready_when");
+        let ready_regex =
+            kdl_get_string_property_or_child_value_with_error!(kdl_node, "ready_regex");
+        let ready_timeout = kdl_get_int_property_or_child_value!(kdl_node, "ready_timeout");
+        let pane_name = match pane_name {
+            Some(pane_name) => pane_name.to_owned(),
+            None if ready_when.is_some() || ready_regex.is_some() || ready_timeout.is_some() => {
+                return Err(kdl_parsing_error!(
+                    "ready_when, ready_regex and ready_timeout can only be set if depends_on was specified".into(),
+                    kdl_node
+                ));
+            },
+            None => return Ok(None),
+        };
+        let ready_when = match (ready_when, ready_regex) {
+            (Some(_), Some(_)) => {
+                return Err(kdl_parsing_error!(
+                    "cannot have both ready_when and ready_regex for the same pane".into(),
+                    kdl_node
+                ));
+            },
+            (Some(text), None) => Some(ReadyCondition::Contains(text.to_owned())),
+            (None, Some(regex)) => {
+                if let Err(e) = regex::Regex::new(regex) {
+                    return Err(kdl_parsing_error!(
+                        format!("Invalid ready_regex: {}", e),
+                        kdl_node
+                    ));
+                }
+                Some(ReadyCondition::Matches(regex.to_owned()))
+            },
+            (None, None) => None,
+        };
+        let timeout_secs = match ready_timeout {
+            Some(timeout) if timeout > 0 => Some(timeout as u64),
+            Some(_) => {
+                return Err(kdl_parsing_error!(
+                    "ready_timeout must be a positive number of seconds".into(),
+                    kdl_node
+                ));
+            },
+            None => None,
+        };
+        Ok(Some(PaneDependency {
+            pane_name,
+            ready_when,
+            timeout_secs,
+        }))
+    }
+    fn parse_encoding(&self, kdl_node: &KdlNode) -> Result<Option<PaneEncoding>, ConfigError> {
+        match kdl_get_string_property_or_child_value_with_error!(kdl_node, "encoding") {
+            Some(encoding) => match PaneEncoding::from_str(encoding) {
+                Ok(encoding) => Ok(Some(encoding)),
+                Err(_e) => {
+                    let error_message = format!(
+                        "encoding should be one of {:?} found: {}",
+                        layout_capability_values(PANE_ENCODING),
+                        encoding
+                    );
+                    match kdl_property_or_child_value_node!(kdl_node, "encoding") {
+                        Some(entry) => Err(kdl_parsing_error!(error_message, entry)),
+                        None => Err(kdl_parsing_error!(error_message, kdl_node)),
+                    }
+                },
+            },
+            None => Ok(None),
+        }
     }
     fn parse_pane_command(
         &self,
         pane_node: &KdlNode,
         is_template: bool,
     ) -> Result<Option<Run>, ConfigError> {
-        let command = kdl_get_string_property_or_child_value_with_error!(pane_node, "command")
-            .map(|c| PathBuf::from(c));
-        let edit = kdl_get_string_property_or_child_value_with_error!(pane_node, "edit")
-            .map(|c| PathBuf::from(c));
+        let command = match kdl_get_string_property_or_child_value_with_error!(pane_node, "command")
+        {
+            Some(command) => {
+                let (offset, len) = self.value_span(pane_node, "command");
+                Some(PathBuf::from(
+                    self.expand_env_vars(command, "command", offset, len)?,
+                ))
+            },
+            None => None,
+        };
+        let edit = self.parse_edit(pane_node)?;
         let cwd = self.parse_cwd(pane_node)?;
         let args = self.parse_args(pane_node)?;
         let close_on_exit =
             kdl_get_bool_property_or_child_value_with_error!(pane_node, "close_on_exit");
         let start_suspended =
             kdl_get_bool_property_or_child_value_with_error!(pane_node, "start_suspended");
+        let strict_placeholders =
+            kdl_get_bool_property_or_child_value_with_error!(pane_node, "strict_placeholders");
+        let depends_on = self.parse_pane_dependency(pane_node)?;
+        let encoding = self.parse_encoding(pane_node)?;
+        let env = self.parse_env(pane_node)?;
         if !is_template {
             self.assert_no_bare_attributes_in_pane_node(
                 &command,
                 &args,
                 &close_on_exit,
                 &start_suspended,
+                &strict_placeholders,
+                &depends_on,
+                &encoding,
+                &env,
                 pane_node,
             )?;
         }
         let hold_on_close = close_on_exit.map(|c| !c).unwrap_or(true);
         let hold_on_start = start_suspended.map(|c| c).unwrap_or(false);
+        let strict_placeholders = strict_placeholders.unwrap_or(false);
         match (command, edit, cwd) {
             (None, None, Some(cwd)) => Ok(Some(Run::Cwd(cwd))),
             (Some(command), None, cwd) => Ok(Some(Run::Command(RunCommand {
@@ -351,9 +1072,18 @@ impl<'a> KdlLayoutParser<'a> {
                 cwd,
                 hold_on_close,
                 hold_on_start,
+                strict_placeholders,
+                depends_on,
+                encoding,
+                env: env.unwrap_or_default(),
             }))),
-            (None, Some(edit), Some(cwd)) => Ok(Some(Run::EditFile(cwd.join(edit), None))),
-            (None, Some(edit), None) => Ok(Some(Run::EditFile(edit, None))),
+            (None, Some((files_to_edit, editor_splits)), cwd) => {
+                let mut run = Run::EditFile(files_to_edit, None, editor_splits);
+                if let Some(cwd) = cwd {
+                    run.add_cwd(&cwd);
+                }
+                Ok(Some(run))
+            },
             (Some(_command), Some(_edit), _) => Err(ConfigError::new_layout_kdl_error(
                 "cannot have both a command and an edit instruction for the same pane".into(),
                 pane_node.span().offset(),
@@ -362,6 +1092,49 @@ impl<'a> KdlLayoutParser<'a> {
             _ => Ok(None),
         }
     }
+    /// The pane declared in the `on_failure` block of a command pane, eg.
+    /// `on_failure { pane command="notify-failure"; }`
+    fn parse_on_failure(
+        &self,
+        pane_node: &KdlNode,
+        run: &Option<Run>,
+    ) -> Result<Option<Box<TiledPaneLayout>>, ConfigError> {
+        let on_failure_node = match kdl_get_child!(pane_node, "on_failure") {
+            Some(on_failure_node) => on_failure_node,
+            None => return Ok(None),
+        };
+        if !matches!(run, Some(Run::Command(_))) {
+            return Err(kdl_parsing_error!(
+                "on_failure can only be set if a command was specified".into(),
+                on_failure_node
+            ));
+        }
+        let failure_pane_node = match kdl_children_nodes!(on_failure_node) {
+            Some([failure_pane_node]) if kdl_name!(failure_pane_node) == "pane" => {
+                failure_pane_node
+            },
+            _ => {
+                return Err(kdl_parsing_error!(
+                    "on_failure should hold a single pane, eg. on_failure { pane command=\"notify-failure\"; }".into(),
+                    on_failure_node
+                ));
+            },
+        };
+        if let Some(nested_on_failure_node) = kdl_get_child!(failure_pane_node, "on_failure") {
+            return Err(kdl_parsing_error!(
+                "on_failure cannot be nested in another on_failure".into(),
+                nested_on_failure_node
+            ));
+        }
+        let failure_pane = self.parse_pane_node(failure_pane_node)?;
+        if !matches!(failure_pane.run, Some(Run::Command(_))) || !failure_pane.children.is_empty() {
+            return Err(kdl_parsing_error!(
+                "the pane of on_failure should run a command".into(),
+                failure_pane_node
+            ));
+        }
+        Ok(Some(Box::new(failure_pane)))
+    }
     fn parse_command_plugin_or_edit_block(
         &self,
         kdl_node: &KdlNode,
@@ -411,28 +1184,55 @@ impl<'a> KdlLayoutParser<'a> {
     fn parse_pane_node(&self, kdl_node: &KdlNode) -> Result<TiledPaneLayout, ConfigError> {
         self.assert_valid_pane_properties(kdl_node)?;
         let borderless = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "borderless");
+        let hide_title = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "hide_title");
+        let read_only = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "read_only");
+        let no_wrapper = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "no_wrapper");
+        let exclude_from_dump =
+            kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_dump");
         let focus = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "focus");
+        let expanded = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded");
         let name = kdl_get_string_property_or_child_value_with_error!(kdl_node, "name")
             .map(|name| name.to_string());
         let split_size = self.parse_split_size(kdl_node)?;
+        let (min_size, max_size) = self.parse_size_bounds(kdl_node)?;
         let run = self.parse_command_plugin_or_edit_block(kdl_node)?;
+        let on_failure = self.parse_on_failure(kdl_node, &run)?;
         let children_split_direction = self.parse_split_direction(kdl_node)?;
+        let stack_below_width = self.parse_stack_below_width(kdl_node)?;
+        let conditions = self.parse_conditions(kdl_node)?;
         let (external_children_index, children_are_stacked, children) =
             match kdl_children_nodes!(kdl_node) {
                 Some(children) => self.parse_child_pane_nodes_for_pane(&children)?,
                 None => (None, false, vec![]),
             };
+        self.assert_valid_stacked_children(
+            kdl_node,
+            children_split_direction,
+            children_are_stacked,
+            &children,
+        )?;
+        self.assert_percent_sizes_fit(kdl_node, children_are_stacked, &children)?;
         self.assert_no_mixed_children_and_properties(kdl_node)?;
         Ok(TiledPaneLayout {
             borderless: borderless.unwrap_or_default(),
+            hide_title: hide_title.unwrap_or_default(),
+            read_only: read_only.unwrap_or_default(),
+            no_wrapper: no_wrapper.unwrap_or_default(),
+            exclude_from_dump: exclude_from_dump.unwrap_or_default(),
             focus,
+            is_expanded_in_stack: expanded.unwrap_or_default(),
             name,
             split_size,
+            min_size,
+            max_size,
             run,
             children_split_direction,
             external_children_index,
             children,
             children_are_stacked,
+            stack_below_width,
+            on_failure,
+            conditions,
             ..Default::default()
         })
     }
@@ -441,25 +1241,48 @@ impl<'a> KdlLayoutParser<'a> {
         kdl_node: &KdlNode,
     ) -> Result<FloatingPaneLayout, ConfigError> {
         self.assert_valid_floating_pane_properties(kdl_node)?;
-        let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
-        let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
-        let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
-        let y = self.parse_percent_or_fixed(kdl_node, "y", true)?;
+        let (height, height_fit) = self.parse_floating_pane_size(kdl_node, "height")?;
+        let (width, width_fit) = self.parse_floating_pane_size(kdl_node, "width")?;
+        let max_height = self.parse_floating_pane_max_size(kdl_node, "height")?;
+        let max_width = self.parse_floating_pane_max_size(kdl_node, "width")?;
+        let x = self.parse_floating_pane_coordinate(kdl_node, "x")?;
+        let y = self.parse_floating_pane_coordinate(kdl_node, "y")?;
         let run = self.parse_command_plugin_or_edit_block(kdl_node)?;
         let focus = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "focus");
+        let pinned = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "pinned");
+        let anchor = self.parse_anchor(kdl_node)?;
+        let hide_title = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "hide_title");
+        let read_only = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "read_only");
+        let no_wrapper = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "no_wrapper");
+        let exclude_from_dump =
+            kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_dump");
         let name = kdl_get_string_property_or_child_value_with_error!(kdl_node, "name")
             .map(|name| name.to_string());
+        let conditions = self.parse_conditions(kdl_node)?;
         self.assert_no_mixed_children_and_properties(kdl_node)?;
-        Ok(FloatingPaneLayout {
+        let mut floating_pane = FloatingPaneLayout {
             name,
             height,
             width,
+            max_height,
+            max_width,
             x,
             y,
             run,
             focus,
+            pinned,
+            anchor,
+            hide_title: hide_title.unwrap_or_default(),
+            read_only: read_only.unwrap_or_default(),
+            no_wrapper: no_wrapper.unwrap_or_default(),
+            exclude_from_dump: exclude_from_dump.unwrap_or_default(),
+            height_fit,
+            width_fit,
+            conditions,
             ..Default::default()
-        })
+        };
+        self.ignore_fit_unless_command_pane(&mut floating_pane, kdl_node);
+        Ok(floating_pane)
     }
     fn insert_children_to_pane_template(
         &self,
@@ -473,6 +1296,13 @@ impl<'a> KdlLayoutParser<'a> {
                 Some(children) => self.parse_child_pane_nodes_for_pane(&children)?,
                 None => (None, false, vec![]),
             };
+        self.assert_valid_stacked_children(
+            kdl_node,
+            children_split_direction,
+            children_are_stacked,
+            &pane_parts,
+        )?;
+        self.assert_percent_sizes_fit(kdl_node, children_are_stacked, &pane_parts)?;
         if pane_parts.len() > 0 {
             let child_panes_layout = TiledPaneLayout {
                 children_split_direction,
@@ -531,12 +1361,23 @@ impl<'a> KdlLayoutParser<'a> {
         should_mark_external_children_index: bool,
         pane_template_kdl_node: &KdlNode,
     ) -> Result<TiledPaneLayout, ConfigError> {
+        self.assert_no_duplicate_properties(kdl_node)?;
         match pane_template {
             PaneOrFloatingPane::Pane(mut pane_template)
             | PaneOrFloatingPane::Either(mut pane_template) => {
                 let borderless =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "borderless");
+                let hide_title =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "hide_title");
+                let read_only =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "read_only");
+                let no_wrapper =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "no_wrapper");
+                let exclude_from_dump =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_dump");
                 let focus = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "focus");
+                let expanded =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded");
                 let name = kdl_get_string_property_or_child_value_with_error!(kdl_node, "name")
                     .map(|name| name.to_string());
                 let args = self.parse_args(kdl_node)?;
@@ -544,7 +1385,15 @@ impl<'a> KdlLayoutParser<'a> {
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "close_on_exit");
                 let start_suspended =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "start_suspended");
+                let strict_placeholders = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "strict_placeholders"
+                );
+                let depends_on = self.parse_pane_dependency(kdl_node)?;
+                let encoding = self.parse_encoding(kdl_node)?;
+                let env = self.parse_env(kdl_node)?;
                 let split_size = self.parse_split_size(kdl_node)?;
+                let (min_size, max_size) = self.parse_size_bounds(kdl_node)?;
                 let run = self.parse_command_plugin_or_edit_block_for_template(kdl_node)?;
 
                 // TODO: change should_insert_children to should_keep_pane_external_children_index
@@ -561,6 +1410,10 @@ impl<'a> KdlLayoutParser<'a> {
                     &args,
                     &close_on_exit,
                     &start_suspended,
+                    &strict_placeholders,
+                    &depends_on,
+                    &encoding,
+                    &env,
                     kdl_node,
                 )?;
                 self.insert_children_to_pane_template(
@@ -569,25 +1422,56 @@ impl<'a> KdlLayoutParser<'a> {
                     pane_template_kdl_node,
                 )?;
                 pane_template.run = Run::merge(&pane_template.run, &run);
+                if let Some(on_failure) = self.parse_on_failure(kdl_node, &pane_template.run)? {
+                    pane_template.on_failure = Some(on_failure);
+                }
                 if let Some(pane_template_run_command) = pane_template.run.as_mut() {
                     // we need to do this because panes consuming a pane_template
                     // can have bare args without a command
                     pane_template_run_command.add_args(args);
                     pane_template_run_command.add_close_on_exit(close_on_exit);
                     pane_template_run_command.add_start_suspended(start_suspended);
+                    pane_template_run_command.add_strict_placeholders(strict_placeholders);
+                    pane_template_run_command.add_depends_on(depends_on);
+                    pane_template_run_command.add_encoding(encoding);
+                    pane_template_run_command.add_env(env);
                 };
                 if let Some(borderless) = borderless {
                     pane_template.borderless = borderless;
                 }
+                if let Some(hide_title) = hide_title {
+                    pane_template.hide_title = hide_title;
+                }
+                if let Some(read_only) = read_only {
+                    pane_template.read_only = read_only;
+                }
+                if let Some(no_wrapper) = no_wrapper {
+                    pane_template.no_wrapper = no_wrapper;
+                }
+                if let Some(exclude_from_dump) = exclude_from_dump {
+                    pane_template.exclude_from_dump = exclude_from_dump;
+                }
+                pane_template
+                    .conditions
+                    .merge(self.parse_conditions(kdl_node)?);
                 if let Some(focus) = focus {
                     pane_template.focus = Some(focus);
                 }
+                if let Some(expanded) = expanded {
+                    pane_template.is_expanded_in_stack = expanded;
+                }
                 if let Some(name) = name {
                     pane_template.name = Some(name);
                 }
                 if let Some(split_size) = split_size {
                     pane_template.split_size = Some(split_size);
                 }
+                if let Some(min_size) = min_size {
+                    pane_template.min_size = Some(min_size);
+                }
+                if let Some(max_size) = max_size {
+                    pane_template.max_size = Some(max_size);
+                }
                 if let Some(index_of_children) = pane_template.external_children_index {
                     pane_template
                         .children
@@ -620,6 +1504,7 @@ impl<'a> KdlLayoutParser<'a> {
         pane_template: PaneOrFloatingPane,
         pane_template_kdl_node: &KdlNode,
     ) -> Result<FloatingPaneLayout, ConfigError> {
+        self.assert_no_duplicate_properties(kdl_node)?;
         match pane_template {
             PaneOrFloatingPane::Pane(_) => {
                 let pane_template_name = kdl_get_string_property_or_child_value_with_error!(
@@ -635,6 +1520,16 @@ impl<'a> KdlLayoutParser<'a> {
             },
             PaneOrFloatingPane::FloatingPane(mut pane_template) => {
                 let focus = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "focus");
+                let pinned = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "pinned");
+                let anchor = self.parse_anchor(kdl_node)?;
+                let hide_title =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "hide_title");
+                let read_only =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "read_only");
+                let no_wrapper =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "no_wrapper");
+                let exclude_from_dump =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_dump");
                 let name = kdl_get_string_property_or_child_value_with_error!(kdl_node, "name")
                     .map(|name| name.to_string());
                 let args = self.parse_args(kdl_node)?;
@@ -642,6 +1537,13 @@ impl<'a> KdlLayoutParser<'a> {
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "close_on_exit");
                 let start_suspended =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "start_suspended");
+                let strict_placeholders = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "strict_placeholders"
+                );
+                let depends_on = self.parse_pane_dependency(kdl_node)?;
+                let encoding = self.parse_encoding(kdl_node)?;
+                let env = self.parse_env(kdl_node)?;
                 let run = self.parse_command_plugin_or_edit_block_for_template(kdl_node)?;
                 self.assert_no_bare_attributes_in_pane_node_with_template(
                     &run,
@@ -649,6 +1551,10 @@ impl<'a> KdlLayoutParser<'a> {
                     &args,
                     &close_on_exit,
                     &start_suspended,
+                    &strict_placeholders,
+                    &depends_on,
+                    &encoding,
+                    &env,
                     kdl_node,
                 )?;
                 pane_template.run = Run::merge(&pane_template.run, &run);
@@ -658,23 +1564,58 @@ impl<'a> KdlLayoutParser<'a> {
                     pane_template_run_command.add_args(args);
                     pane_template_run_command.add_close_on_exit(close_on_exit);
                     pane_template_run_command.add_start_suspended(start_suspended);
+                    pane_template_run_command.add_strict_placeholders(strict_placeholders);
+                    pane_template_run_command.add_depends_on(depends_on);
+                    pane_template_run_command.add_encoding(encoding);
+                    pane_template_run_command.add_env(env);
                 };
                 if let Some(focus) = focus {
                     pane_template.focus = Some(focus);
                 }
+                if let Some(pinned) = pinned {
+                    pane_template.pinned = Some(pinned);
+                }
+                if let Some(anchor) = anchor {
+                    pane_template.anchor = Some(anchor);
+                }
+                if let Some(hide_title) = hide_title {
+                    pane_template.hide_title = hide_title;
+                }
+                if let Some(read_only) = read_only {
+                    pane_template.read_only = read_only;
+                }
+                if let Some(no_wrapper) = no_wrapper {
+                    pane_template.no_wrapper = no_wrapper;
+                }
+                if let Some(exclude_from_dump) = exclude_from_dump {
+                    pane_template.exclude_from_dump = exclude_from_dump;
+                }
+                pane_template
+                    .conditions
+                    .merge(self.parse_conditions(kdl_node)?);
                 if let Some(name) = name {
                     pane_template.name = Some(name);
                 }
-                let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
-                let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
-                let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
-                let y = self.parse_percent_or_fixed(kdl_node, "y", true)?;
+                let (height, height_fit) = self.parse_floating_pane_size(kdl_node, "height")?;
+                let (width, width_fit) = self.parse_floating_pane_size(kdl_node, "width")?;
+                let max_height = self.parse_floating_pane_max_size(kdl_node, "height")?;
+                let max_width = self.parse_floating_pane_max_size(kdl_node, "width")?;
+                let x = self.parse_floating_pane_coordinate(kdl_node, "x")?;
+                let y = self.parse_floating_pane_coordinate(kdl_node, "y")?;
                 // let mut floating_pane = FloatingPaneLayout::from(&pane_template);
                 if let Some(height) = height {
                     pane_template.height = Some(height);
+                    pane_template.height_fit = height_fit;
                 }
                 if let Some(width) = width {
                     pane_template.width = Some(width);
+                    pane_template.width_fit = width_fit;
+                }
+                if let Some(max_height) = max_height {
+                    pane_template.max_height = Some(max_height);
+                }
+                if let Some(max_width) = max_width {
+                    pane_template.max_width = Some(max_width);
                 }
                 if let Some(y) = y {
                     pane_template.y = Some(y);
@@ -682,10 +1623,21 @@ impl<'a> KdlLayoutParser<'a> {
                 if let Some(x) = x {
                     pane_template.x = Some(x);
                 }
+                self.ignore_fit_unless_command_pane(&mut pane_template, kdl_node);
                 Ok(pane_template)
             },
             PaneOrFloatingPane::Either(mut pane_template) => {
                 let focus = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "focus");
+                let pinned = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "pinned");
+                let anchor = self.parse_anchor(kdl_node)?;
+                let hide_title =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "hide_title");
+                let read_only =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "read_only");
+                let no_wrapper =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "no_wrapper");
+                let exclude_from_dump =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_dump");
                 let name = kdl_get_string_property_or_child_value_with_error!(kdl_node, "name")
                     .map(|name| name.to_string());
                 let args = self.parse_args(kdl_node)?;
@@ -693,6 +1645,13 @@ impl<'a> KdlLayoutParser<'a> {
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "close_on_exit");
                 let start_suspended =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "start_suspended");
+                let strict_placeholders = kdl_get_bool_property_or_child_value_with_error!(
+                    kdl_node,
+                    "strict_placeholders"
+                );
+                let depends_on = self.parse_pane_dependency(kdl_node)?;
+                let encoding = self.parse_encoding(kdl_node)?;
+                let env = self.parse_env(kdl_node)?;
                 let run = self.parse_command_plugin_or_edit_block_for_template(kdl_node)?;
                 self.assert_no_bare_attributes_in_pane_node_with_template(
                     &run,
@@ -700,6 +1659,10 @@ impl<'a> KdlLayoutParser<'a> {
                     &args,
                     &close_on_exit,
                     &start_suspended,
+                    &strict_placeholders,
+                    &depends_on,
+                    &encoding,
+                    &env,
                     kdl_node,
                 )?;
                 pane_template.run = Run::merge(&pane_template.run, &run);
@@ -709,23 +1672,58 @@ impl<'a> KdlLayoutParser<'a> {
                     pane_template_run_command.add_args(args);
                     pane_template_run_command.add_close_on_exit(close_on_exit);
                     pane_template_run_command.add_start_suspended(start_suspended);
+                    pane_template_run_command.add_strict_placeholders(strict_placeholders);
+                    pane_template_run_command.add_depends_on(depends_on);
+                    pane_template_run_command.add_encoding(encoding);
+                    pane_template_run_command.add_env(env);
                 };
                 if let Some(focus) = focus {
                     pane_template.focus = Some(focus);
                 }
+                if let Some(hide_title) = hide_title {
+                    pane_template.hide_title = hide_title;
+                }
+                if let Some(read_only) = read_only {
+                    pane_template.read_only = read_only;
+                }
+                if let Some(no_wrapper) = no_wrapper {
+                    pane_template.no_wrapper = no_wrapper;
+                }
+                if let Some(exclude_from_dump) = exclude_from_dump {
+                    pane_template.exclude_from_dump = exclude_from_dump;
+                }
+                pane_template
+                    .conditions
+                    .merge(self.parse_conditions(kdl_node)?);
                 if let Some(name) = name {
                     pane_template.name = Some(name);
                 }
-                let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
-                let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
-                let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
-                let y = self.parse_percent_or_fixed(kdl_node, "y", true)?;
+                let (height, height_fit) = self.parse_floating_pane_size(kdl_node, "height")?;
+                let (width, width_fit) = self.parse_floating_pane_size(kdl_node, "width")?;
+                let max_height = self.parse_floating_pane_max_size(kdl_node, "height")?;
+                let max_width = self.parse_floating_pane_max_size(kdl_node, "width")?;
+                let x = self.parse_floating_pane_coordinate(kdl_node, "x")?;
+                let y = self.parse_floating_pane_coordinate(kdl_node, "y")?;
                 let mut floating_pane = FloatingPaneLayout::from(&pane_template);
+                if let Some(pinned) = pinned {
+                    floating_pane.pinned = Some(pinned);
+                }
+                if let Some(anchor) = anchor {
+                    floating_pane.anchor = Some(anchor);
+                }
                 if let Some(height) = height {
                     floating_pane.height = Some(height);
+                    floating_pane.height_fit = height_fit;
                 }
                 if let Some(width) = width {
                     floating_pane.width = Some(width);
+                    floating_pane.width_fit = width_fit;
+                }
+                if let Some(max_height) = max_height {
+                    floating_pane.max_height = Some(max_height);
+                }
+                if let Some(max_width) = max_width {
+                    floating_pane.max_width = Some(max_width);
                 }
                 if let Some(y) = y {
                     floating_pane.y = Some(y);
@@ -733,48 +1731,262 @@ impl<'a> KdlLayoutParser<'a> {
                 if let Some(x) = x {
                     floating_pane.x = Some(x);
                 }
-                Ok(floating_pane)
+                self.ignore_fit_unless_command_pane(&mut floating_pane, kdl_node);
+                Ok(floating_pane)
+            },
+        }
+    }
+    fn parse_split_direction(&self, kdl_node: &KdlNode) -> Result<SplitDirection, ConfigError> {
+        match kdl_get_string_property_or_child_value_with_error!(kdl_node, "split_direction") {
+            Some(direction) => match SplitDirection::from_str(direction) {
+                Ok(split_direction) => Ok(split_direction),
+                Err(_e) => Err(kdl_parsing_error!(
+                    format!(
+                        "split_direction should be either \"horizontal\", \"vertical\" or \"auto\" found: {}",
+                        direction
+                    ),
+                    kdl_node
+                )),
+            },
+            None => Ok(SplitDirection::default()),
+        }
+    }
+    fn parse_command_spawn_interval(
+        &self,
+        layout_node: &KdlNode,
+    ) -> Result<Option<u64>, ConfigError> {
+        match kdl_get_int_property_or_child_value!(layout_node, "command_spawn_interval_ms") {
+            Some(interval) if interval >= 0 => Ok(Some(interval as u64)),
+            Some(_) => Err(kdl_parsing_error!(
+                format!("command_spawn_interval_ms cannot be negative"),
+                layout_node
+            )),
+            None => {
+                match kdl_property_or_child_value_node!(layout_node, "command_spawn_interval_ms") {
+                    Some(node) => Err(kdl_parsing_error!(
+                        format!(
+                        "command_spawn_interval_ms should be a number of milliseconds (eg. 500)"
+                    ),
+                        node
+                    )),
+                    None => Ok(None),
+                }
             },
         }
     }
-    fn parse_split_direction(&self, kdl_node: &KdlNode) -> Result<SplitDirection, ConfigError> {
-        match kdl_get_string_property_or_child_value_with_error!(kdl_node, "split_direction") {
-            Some(direction) => match SplitDirection::from_str(direction) {
-                Ok(split_direction) => Ok(split_direction),
+    fn parse_command_wrapper(
+        &self,
+        layout_node: &KdlNode,
+    ) -> Result<Option<CommandWrapper>, ConfigError> {
+        let wrapper_node = match kdl_get_child!(layout_node, "command_wrapper") {
+            Some(wrapper_node) => wrapper_node,
+            None => return Ok(None),
+        };
+        let command = kdl_first_entry_as_string!(wrapper_node).ok_or_else(|| {
+            kdl_parsing_error!(
+                format!("command_wrapper should be given the program to run the commands under (eg. command_wrapper \"devcontainer\")"),
+                wrapper_node
+            )
+        })?;
+        let command = self.expand_env_vars(
+            command,
+            "command_wrapper",
+            wrapper_node.span().offset(),
+            wrapper_node.span().len(),
+        )?;
+        Ok(Some(CommandWrapper {
+            command: PathBuf::from(command),
+            args: self.parse_args(wrapper_node)?.unwrap_or_default(),
+        }))
+    }
+    fn parse_session_name(
+        &self,
+        layout_node: &KdlNode,
+    ) -> Result<(Option<String>, Option<bool>), ConfigError> {
+        let session_name =
+            kdl_get_string_property_or_child_value_with_error!(layout_node, "session_name");
+        let attach_to_existing =
+            kdl_get_bool_property_or_child_value_with_error!(layout_node, "attach_to_existing");
+        match session_name {
+            Some(session_name) if session_name.trim().is_empty() => Err(kdl_parsing_error!(
+                format!("session_name cannot be empty"),
+                layout_node
+            )),
+            None if attach_to_existing.is_some() => Err(kdl_parsing_error!(
+                format!("attach_to_existing needs the session_name of the session to attach to"),
+                layout_node
+            )),
+            session_name => Ok((session_name.map(|s| s.to_owned()), attach_to_existing)),
+        }
+    }
+    // the tab named by `focused_tab`, which cannot be used along with `focus=true` on a tab
+    fn parse_focused_tab(
+        &self,
+        layout_node: &KdlNode,
+        tab_names: &[Option<String>],
+        has_focused_tab: bool,
+    ) -> Result<Option<(usize, String)>, ConfigError> {
+        let focused_tab =
+            match kdl_get_string_property_or_child_value_with_error!(layout_node, "focused_tab") {
+                Some(focused_tab) => focused_tab,
+                None => return Ok(None),
+            };
+        let (offset, len) = self.value_span(layout_node, "focused_tab");
+        let error = |message: String| ConfigError::new_layout_kdl_error(message, offset, len);
+        if has_focused_tab {
+            return Err(error(
+                "Only one tab can be focused, either with focused_tab or with focus=true".into(),
+            ));
+        }
+        let mut named_tabs = tab_names
+            .iter()
+            .enumerate()
+            .filter(|(_index, tab_name)| tab_name.as_deref() == Some(focused_tab));
+        match (named_tabs.next(), named_tabs.next()) {
+            (Some((index, _tab_name)), None) => Ok(Some((index, focused_tab.to_owned()))),
+            (Some(_), Some(_)) => Err(error(format!(
+                "More than one tab is named \"{}\", focused_tab cannot tell which to focus",
+                focused_tab
+            ))),
+            (None, _) => Err(error(format!(
+                "There is no tab named \"{}\" to focus",
+                focused_tab
+            ))),
+        }
+    }
+    fn parse_default_focus(
+        &self,
+        layout_node: &KdlNode,
+    ) -> Result<Option<FocusPolicy>, ConfigError> {
+        match kdl_get_string_property_or_child_value_with_error!(layout_node, "default_focus") {
+            Some(policy) => match FocusPolicy::from_str(policy) {
+                Ok(policy) => Ok(Some(policy)),
                 Err(_e) => Err(kdl_parsing_error!(
                     format!(
-                        "split_direction should be either \"horizontal\" or \"vertical\" found: {}",
-                        direction
+                        "default_focus should be one of {:?} found: {}",
+                        layout_capability_values(DEFAULT_FOCUS),
+                        policy
+                    ),
+                    layout_node
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+    fn parse_anchor(&self, kdl_node: &KdlNode) -> Result<Option<FloatingPaneAnchor>, ConfigError> {
+        match kdl_get_string_property_or_child_value_with_error!(kdl_node, "anchor") {
+            Some(anchor) => match FloatingPaneAnchor::from_str(anchor) {
+                Ok(anchor) => Ok(Some(anchor)),
+                Err(_e) => Err(kdl_parsing_error!(
+                    format!(
+                        "anchor should be one of {:?} found: {}",
+                        layout_capability_values(FLOATING_PANE_ANCHORS),
+                        anchor
                     ),
                     kdl_node
                 )),
             },
-            None => Ok(SplitDirection::default()),
+            None => Ok(None),
+        }
+    }
+    fn parse_stack_below_width(&self, kdl_node: &KdlNode) -> Result<Option<usize>, ConfigError> {
+        self.parse_cell_count(
+            kdl_node,
+            "stack_below_width",
+            "a number of columns (eg. 80)",
+        )
+    }
+    fn parse_max_content_width(&self, kdl_node: &KdlNode) -> Result<Option<usize>, ConfigError> {
+        self.parse_cell_count(
+            kdl_node,
+            "max_content_width",
+            "a number of columns (eg. 100)",
+        )
+    }
+    fn parse_tab_pin(&self, kdl_node: &KdlNode) -> Result<Option<TabPin>, ConfigError> {
+        match kdl_get_string_property_or_child_value_with_error!(kdl_node, "pin") {
+            Some(pin) => match TabPin::from_str(pin) {
+                Ok(pin) => Ok(Some(pin)),
+                Err(_e) => Err(kdl_parsing_error!(
+                    format!(
+                        "pin should be one of {:?} found: {}",
+                        layout_capability_values(TAB_PIN),
+                        pin
+                    ),
+                    kdl_node
+                )),
+            },
+            None => Ok(None),
         }
     }
+    fn parse_conditions(&self, kdl_node: &KdlNode) -> Result<PaneConditions, ConfigError> {
+        let if_env = kdl_get_string_property_or_child_value_with_error!(kdl_node, "if_env");
+        if let Some(variable) = if_env {
+            if variable.is_empty() || variable.contains(|c| c == '=' || c == '\0') {
+                return Err(kdl_parsing_error!(
+                    format!(
+                        "if_env should be the name of an environment variable, found: {:?}",
+                        variable
+                    ),
+                    kdl_node
+                ));
+            }
+        }
+        let if_command_exists =
+            kdl_get_string_property_or_child_value_with_error!(kdl_node, "if_command_exists");
+        if if_command_exists == Some("") {
+            return Err(kdl_parsing_error!(
+                "if_command_exists should be a command, found an empty string".into(),
+                kdl_node
+            ));
+        }
+        Ok(PaneConditions {
+            if_env: if_env.map(|variable| variable.to_owned()),
+            if_command_exists: if_command_exists.map(|command| command.to_owned()),
+        })
+    }
     fn has_only_neutral_pane_template_properties(
         &self,
         kdl_node: &KdlNode,
     ) -> Result<bool, ConfigError> {
         // pane properties
         let borderless = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "borderless");
+        let expanded = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded");
         let split_size = self.parse_split_size(kdl_node)?;
+        let (min_size, max_size) = self.parse_size_bounds(kdl_node)?;
         let split_direction =
             kdl_get_string_property_or_child_value_with_error!(kdl_node, "split_direction");
+        let stack_below_width = self.parse_stack_below_width(kdl_node)?;
         let has_children_nodes = self.has_child_nodes(kdl_node);
+        let has_on_failure = kdl_get_child!(kdl_node, "on_failure").is_some();
 
         // floating pane properties
-        let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
-        let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
-        let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
-        let y = self.parse_percent_or_fixed(kdl_node, "y", true)?;
+        let (height, _height_fit) = self.parse_floating_pane_size(kdl_node, "height")?;
+        let (width, _width_fit) = self.parse_floating_pane_size(kdl_node, "width")?;
+        let max_height = self.parse_floating_pane_max_size(kdl_node, "height")?;
+        let max_width = self.parse_floating_pane_max_size(kdl_node, "width")?;
+        let x = self.parse_floating_pane_coordinate(kdl_node, "x")?;
+        let y = self.parse_floating_pane_coordinate(kdl_node, "y")?;
+        let pinned = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "pinned");
+        let anchor = kdl_get_string_property_or_child_value_with_error!(kdl_node, "anchor");
 
         let has_pane_properties = borderless.is_some()
+            || expanded.is_some()
             || split_size.is_some()
+            || min_size.is_some()
+            || max_size.is_some()
             || split_direction.is_some()
-            || has_children_nodes;
-        let has_floating_pane_properties =
-            height.is_some() || width.is_some() || x.is_some() || y.is_some();
+            || stack_below_width.is_some()
+            || has_children_nodes
+            || has_on_failure;
+        let has_floating_pane_properties = height.is_some()
+            || width.is_some()
+            || max_height.is_some()
+            || max_width.is_some()
+            || x.is_some()
+            || y.is_some()
+            || pinned.is_some()
+            || anchor.is_some();
         if has_pane_properties || has_floating_pane_properties {
             Ok(false)
         } else {
@@ -789,23 +2001,42 @@ impl<'a> KdlLayoutParser<'a> {
 
         // pane properties
         let borderless = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "borderless");
+        let expanded = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded");
         let split_size = self.parse_split_size(kdl_node)?;
+        let (min_size, max_size) = self.parse_size_bounds(kdl_node)?;
         let split_direction =
             kdl_get_string_property_or_child_value_with_error!(kdl_node, "split_direction");
+        let stack_below_width = self.parse_stack_below_width(kdl_node)?;
         let has_children_nodes = self.has_child_nodes(kdl_node);
+        let has_on_failure = kdl_get_child!(kdl_node, "on_failure").is_some();
 
         // floating pane properties
-        let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
-        let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
-        let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
-        let y = self.parse_percent_or_fixed(kdl_node, "y", true)?;
+        let (height, _height_fit) = self.parse_floating_pane_size(kdl_node, "height")?;
+        let (width, _width_fit) = self.parse_floating_pane_size(kdl_node, "width")?;
+        let max_height = self.parse_floating_pane_max_size(kdl_node, "height")?;
+        let max_width = self.parse_floating_pane_max_size(kdl_node, "width")?;
+        let x = self.parse_floating_pane_coordinate(kdl_node, "x")?;
+        let y = self.parse_floating_pane_coordinate(kdl_node, "y")?;
+        let pinned = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "pinned");
+        let anchor = kdl_get_string_property_or_child_value_with_error!(kdl_node, "anchor");
 
         let has_pane_properties = borderless.is_some()
+            || expanded.is_some()
             || split_size.is_some()
+            || min_size.is_some()
+            || max_size.is_some()
             || split_direction.is_some()
-            || has_children_nodes;
-        let has_floating_pane_properties =
-            height.is_some() || width.is_some() || x.is_some() || y.is_some();
+            || stack_below_width.is_some()
+            || has_children_nodes
+            || has_on_failure;
+        let has_floating_pane_properties = height.is_some()
+            || width.is_some()
+            || max_height.is_some()
+            || max_width.is_some()
+            || x.is_some()
+            || y.is_some()
+            || pinned.is_some()
+            || anchor.is_some();
 
         if has_pane_properties && has_floating_pane_properties {
             let mut pane_properties = vec![];
@@ -815,9 +2046,21 @@ impl<'a> KdlLayoutParser<'a> {
             if split_size.is_some() {
                 pane_properties.push("split_size");
             }
+            if min_size.is_some() {
+                pane_properties.push("min_size");
+            }
+            if max_size.is_some() {
+                pane_properties.push("max_size");
+            }
             if split_direction.is_some() {
                 pane_properties.push("split_direction");
             }
+            if stack_below_width.is_some() {
+                pane_properties.push("stack_below_width");
+            }
+            if has_on_failure {
+                pane_properties.push("on_failure");
+            }
             if has_children_nodes {
                 pane_properties.push("child nodes");
             }
@@ -828,12 +2071,24 @@ impl<'a> KdlLayoutParser<'a> {
             if width.is_some() {
                 floating_pane_properties.push("width");
             }
+            if max_height.is_some() {
+                floating_pane_properties.push("max_height");
+            }
+            if max_width.is_some() {
+                floating_pane_properties.push("max_width");
+            }
             if x.is_some() {
                 floating_pane_properties.push("x");
             }
             if y.is_some() {
                 floating_pane_properties.push("y");
             }
+            if pinned.is_some() {
+                floating_pane_properties.push("pinned");
+            }
+            if anchor.is_some() {
+                floating_pane_properties.push("anchor");
+            }
             Err(ConfigError::new_layout_kdl_error(
                 format!(
                     "A pane_template cannot have both pane ({}) and floating pane ({}) properties",
@@ -859,8 +2114,21 @@ impl<'a> KdlLayoutParser<'a> {
             ))?;
         self.assert_legal_node_name(&template_name, kdl_node)?;
         self.assert_legal_template_name(&template_name, kdl_node)?;
+        self.shadow_shared_template(&template_name, kdl_node);
         let focus = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "focus");
+        let pinned = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "pinned");
+        let anchor = self.parse_anchor(kdl_node)?;
+        let hide_title = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "hide_title")
+            .unwrap_or_default();
+        let read_only = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "read_only")
+            .unwrap_or_default();
+        let no_wrapper = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "no_wrapper")
+            .unwrap_or_default();
+        let exclude_from_dump =
+            kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_dump")
+                .unwrap_or_default();
         let run = self.parse_command_plugin_or_edit_block(kdl_node)?;
+        let conditions = self.parse_conditions(kdl_node)?;
 
         let is_floating = self.differentiate_pane_and_floating_pane_template(&kdl_node)?;
         let can_be_either_floating_or_tiled =
@@ -872,7 +2140,12 @@ impl<'a> KdlLayoutParser<'a> {
                 (
                     PaneOrFloatingPane::Either(TiledPaneLayout {
                         focus,
+                        hide_title,
+                        read_only,
+                        no_wrapper,
+                        exclude_from_dump,
                         run,
+                        conditions,
                         ..Default::default()
                     }),
                     kdl_node.clone(),
@@ -881,20 +2154,33 @@ impl<'a> KdlLayoutParser<'a> {
         } else if is_floating {
             self.assert_valid_floating_pane_properties(kdl_node)?;
             // floating pane properties
-            let height = self.parse_percent_or_fixed(kdl_node, "height", false)?;
-            let width = self.parse_percent_or_fixed(kdl_node, "width", false)?;
-            let x = self.parse_percent_or_fixed(kdl_node, "x", true)?;
-            let y = self.parse_percent_or_fixed(kdl_node, "y", true)?;
+            let (height, height_fit) = self.parse_floating_pane_size(kdl_node, "height")?;
+            let (width, width_fit) = self.parse_floating_pane_size(kdl_node, "width")?;
+            let max_height = self.parse_floating_pane_max_size(kdl_node, "height")?;
+            let max_width = self.parse_floating_pane_max_size(kdl_node, "width")?;
+            let x = self.parse_floating_pane_coordinate(kdl_node, "x")?;
+            let y = self.parse_floating_pane_coordinate(kdl_node, "y")?;
             self.pane_templates.insert(
                 template_name,
                 (
                     PaneOrFloatingPane::FloatingPane(FloatingPaneLayout {
                         focus,
+                        pinned,
+                        anchor,
+                        hide_title,
+                        read_only,
+                        no_wrapper,
+                        exclude_from_dump,
                         run,
                         height,
                         width,
+                        max_height,
+                        max_width,
                         x,
                         y,
+                        height_fit,
+                        width_fit,
+                        conditions,
                         ..Default::default()
                     }),
                     kdl_node.clone(),
@@ -905,26 +2191,47 @@ impl<'a> KdlLayoutParser<'a> {
             // pane properties
             let borderless =
                 kdl_get_bool_property_or_child_value_with_error!(kdl_node, "borderless");
+            let expanded = kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded");
             let split_size = self.parse_split_size(kdl_node)?;
+            let (min_size, max_size) = self.parse_size_bounds(kdl_node)?;
             let children_split_direction = self.parse_split_direction(kdl_node)?;
+            let stack_below_width = self.parse_stack_below_width(kdl_node)?;
+            let on_failure = self.parse_on_failure(kdl_node, &run)?;
             let (external_children_index, children_are_stacked, pane_parts) =
                 match kdl_children_nodes!(kdl_node) {
                     Some(children) => self.parse_child_pane_nodes_for_pane(&children)?,
                     None => (None, false, vec![]),
                 };
+            self.assert_valid_stacked_children(
+                kdl_node,
+                children_split_direction,
+                children_are_stacked,
+                &pane_parts,
+            )?;
+            self.assert_percent_sizes_fit(kdl_node, children_are_stacked, &pane_parts)?;
             self.assert_no_mixed_children_and_properties(kdl_node)?;
             self.pane_templates.insert(
                 template_name,
                 (
                     PaneOrFloatingPane::Pane(TiledPaneLayout {
                         borderless: borderless.unwrap_or_default(),
+                        hide_title,
+                        read_only,
+                        no_wrapper,
+                        exclude_from_dump,
                         focus,
+                        is_expanded_in_stack: expanded.unwrap_or_default(),
                         split_size,
+                        min_size,
+                        max_size,
                         run,
                         children_split_direction,
                         external_children_index,
                         children: pane_parts,
                         children_are_stacked,
+                        stack_below_width,
+                        on_failure,
+                        conditions,
                         ..Default::default()
                     }),
                     kdl_node.clone(),
@@ -943,16 +2250,21 @@ impl<'a> KdlLayoutParser<'a> {
             Option<String>,
             TiledPaneLayout,
             Vec<FloatingPaneLayout>,
+            Option<TabPin>,
+            Option<usize>,
         ),
         ConfigError,
     > {
-        // (is_focused, Option<tab_name>, PaneLayout, Vec<FloatingPaneLayout>)
+        // (is_focused, Option<tab_name>, PaneLayout, Vec<FloatingPaneLayout>, Option<TabPin>,
+        // Option<max_content_width>)
         self.assert_valid_tab_properties(kdl_node)?;
         let tab_name =
             kdl_get_string_property_or_child_value!(kdl_node, "name").map(|s| s.to_string());
-        let tab_cwd =
-            kdl_get_string_property_or_child_value!(kdl_node, "cwd").map(|c| PathBuf::from(c));
+        let tab_cwd = self.parse_cwd(kdl_node)?;
         let is_focused = kdl_get_bool_property_or_child_value!(kdl_node, "focus").unwrap_or(false);
+        let pin = self.parse_tab_pin(kdl_node)?;
+        let max_content_width = self.parse_max_content_width(kdl_node)?;
+        let conditions = self.parse_conditions(kdl_node)?;
         let children_split_direction = self.parse_split_direction(kdl_node)?;
         let mut child_floating_panes = vec![];
         let children = match kdl_children_nodes!(kdl_node) {
@@ -966,15 +2278,45 @@ impl<'a> KdlLayoutParser<'a> {
             },
             None => vec![],
         };
+        self.assert_percent_sizes_fit(kdl_node, false, &children)?;
         let mut pane_layout = TiledPaneLayout {
             children_split_direction,
             children,
+            conditions,
             ..Default::default()
         };
         if let Some(cwd_prefix) = &self.cwd_prefix(tab_cwd.as_ref())? {
             pane_layout.add_cwd_to_layout(&cwd_prefix);
         }
-        Ok((is_focused, tab_name, pane_layout, child_floating_panes))
+        self.add_cwd_to_floating_panes(&mut child_floating_panes, tab_cwd.as_ref())?;
+        Ok((
+            is_focused,
+            tab_name,
+            pane_layout,
+            child_floating_panes,
+            pin,
+            max_content_width,
+        ))
+    }
+    /// The `swap_tiled_layout` and `swap_floating_layout` nodes of a tab, cycled through in it
+    /// instead of those of the layout
+    fn parse_tab_swap_layouts(
+        &mut self,
+        kdl_node: &KdlNode,
+    ) -> Result<(Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>), ConfigError> {
+        let mut swap_tiled_layouts = vec![];
+        let mut swap_floating_layouts = vec![];
+        if let Some(children) = kdl_children_nodes!(kdl_node) {
+            self.parsing_tab_swap_layouts = true;
+            let parsed = self
+                .populate_swap_tiled_layouts(children, &mut swap_tiled_layouts)
+                .and_then(|_| {
+                    self.populate_swap_floating_layouts(children, &mut swap_floating_layouts)
+                });
+            self.parsing_tab_swap_layouts = false;
+            parsed?;
+        }
+        Ok((swap_tiled_layouts, swap_floating_layouts))
     }
     fn parse_child_pane_nodes_for_tab(
         &self,
@@ -997,6 +2339,18 @@ impl<'a> KdlLayoutParser<'a> {
                 )?);
             } else if kdl_name!(child) == "floating_panes" {
                 self.populate_floating_pane_children(child, child_floating_panes)?;
+            } else if should_mark_external_children_index
+                && layout_capability_values(SWAP_CONSTRAINTS).contains(&kdl_name!(child))
+            {
+                // the constraints of a swap layout entry can also be its child nodes, they are
+                // parsed along with the entry
+                continue;
+            } else if !should_mark_external_children_index
+                && (kdl_name!(child) == "swap_tiled_layout"
+                    || kdl_name!(child) == "swap_floating_layout")
+            {
+                // the swap layouts of a tab are parsed along with the tab
+                continue;
             } else if self.is_a_valid_tab_property(kdl_name!(child)) {
                 return Err(ConfigError::new_layout_kdl_error(
                     format!("Tab property '{}' must be placed on the tab title line and not in the child braces", kdl_name!(child)),
@@ -1108,6 +2462,10 @@ impl<'a> KdlLayoutParser<'a> {
         args: &Option<Vec<String>>,
         close_on_exit: &Option<bool>,
         start_suspended: &Option<bool>,
+        strict_placeholders: &Option<bool>,
+        depends_on: &Option<PaneDependency>,
+        encoding: &Option<PaneEncoding>,
+        env: &Option<BTreeMap<String, String>>,
         pane_node: &KdlNode,
     ) -> Result<(), ConfigError> {
         if let (None, None, true) = (pane_run, pane_template_run, args.is_some()) {
@@ -1128,6 +2486,30 @@ impl<'a> KdlLayoutParser<'a> {
                 pane_node
             ));
         }
+        if let (None, None, true) = (pane_run, pane_template_run, strict_placeholders.is_some()) {
+            return Err(kdl_parsing_error!(
+                format!("strict_placeholders can only be specified if a command was specified either in the pane_template or in the pane"),
+                pane_node
+            ));
+        }
+        if let (None, None, true) = (pane_run, pane_template_run, depends_on.is_some()) {
+            return Err(kdl_parsing_error!(
+                format!("depends_on can only be specified if a command was specified either in the pane_template or in the pane"),
+                pane_node
+            ));
+        }
+        if let (None, None, true) = (pane_run, pane_template_run, encoding.is_some()) {
+            return Err(kdl_parsing_error!(
+                format!("encoding can only be specified if a command was specified either in the pane_template or in the pane"),
+                pane_node
+            ));
+        }
+        if let (None, None, true) = (pane_run, pane_template_run, env.is_some()) {
+            return Err(kdl_parsing_error!(
+                format!("env can only be specified if a command was specified either in the pane_template or in the pane"),
+                pane_node
+            ));
+        }
         Ok(())
     }
     fn assert_no_bare_attributes_in_pane_node(
@@ -1136,6 +2518,10 @@ impl<'a> KdlLayoutParser<'a> {
         args: &Option<Vec<String>>,
         close_on_exit: &Option<bool>,
         start_suspended: &Option<bool>,
+        strict_placeholders: &Option<bool>,
+        depends_on: &Option<PaneDependency>,
+        encoding: &Option<PaneEncoding>,
+        env: &Option<BTreeMap<String, String>>,
         pane_node: &KdlNode,
     ) -> Result<(), ConfigError> {
         if command.is_none() {
@@ -1153,6 +2539,34 @@ impl<'a> KdlLayoutParser<'a> {
                     pane_node.span().len(),
                 ));
             }
+            if strict_placeholders.is_some() {
+                return Err(ConfigError::new_layout_kdl_error(
+                    "strict_placeholders can only be set if a command was specified".into(),
+                    pane_node.span().offset(),
+                    pane_node.span().len(),
+                ));
+            }
+            if depends_on.is_some() {
+                return Err(ConfigError::new_layout_kdl_error(
+                    "depends_on can only be set if a command was specified".into(),
+                    pane_node.span().offset(),
+                    pane_node.span().len(),
+                ));
+            }
+            if encoding.is_some() {
+                return Err(ConfigError::new_layout_kdl_error(
+                    "encoding can only be set if a command was specified".into(),
+                    pane_node.span().offset(),
+                    pane_node.span().len(),
+                ));
+            }
+            if env.is_some() {
+                return Err(ConfigError::new_layout_kdl_error(
+                    "env can only be set if a command was specified".into(),
+                    pane_node.span().offset(),
+                    pane_node.span().len(),
+                ));
+            }
             if args.is_some() {
                 return Err(ConfigError::new_layout_kdl_error(
                     "args can only be set if a command was specified".into(),
@@ -1174,7 +2588,47 @@ impl<'a> KdlLayoutParser<'a> {
         }
         Ok(())
     }
+    fn assert_no_duplicate_properties(&self, kdl_node: &KdlNode) -> Result<(), ConfigError> {
+        // kdl uses the last of several properties with the same name, in lenient mode we do the
+        // same and only warn about it
+        let mut seen_properties: HashMap<&str, &KdlEntry> = HashMap::new();
+        for entry in kdl_node.entries() {
+            let name = match entry.name() {
+                Some(name) => name.value(),
+                None => continue,
+            };
+            if let Some(previous_entry) = seen_properties.insert(name, entry) {
+                let message = format!(
+                    "Duplicate property '{}', the last one ({}={}) would be used",
+                    name,
+                    name,
+                    entry.value()
+                );
+                if self.validation_options.strict {
+                    return Err(ConfigError::new_layout_kdl_error(
+                        message,
+                        entry.span().offset(),
+                        entry.span().len(),
+                    )
+                    .with_secondary_label(
+                        format!("'{}' first defined here", name),
+                        previous_entry.span().offset(),
+                        previous_entry.span().len(),
+                    ));
+                }
+                let mut warning = LayoutDiagnostic::warning("duplicate-property", message, None);
+                warning.span = Some(LayoutSpan {
+                    offset: entry.span().offset(),
+                    len: entry.span().len(),
+                    in_swap_layout_file: self.parsing_swap_layouts,
+                });
+                self.warnings.borrow_mut().push(warning);
+            }
+        }
+        Ok(())
+    }
     fn assert_valid_pane_properties(&self, pane_node: &KdlNode) -> Result<(), ConfigError> {
+        self.assert_no_duplicate_properties(pane_node)?;
         for entry in pane_node.entries() {
             match entry
                 .name()
@@ -1205,6 +2659,7 @@ impl<'a> KdlLayoutParser<'a> {
         &self,
         pane_node: &KdlNode,
     ) -> Result<(), ConfigError> {
+        self.assert_no_duplicate_properties(pane_node)?;
         for entry in pane_node.entries() {
             match entry
                 .name()
@@ -1235,6 +2690,7 @@ impl<'a> KdlLayoutParser<'a> {
         &self,
         pane_node: &KdlNode,
     ) -> Result<(), ConfigError> {
+        self.assert_no_duplicate_properties(pane_node)?;
         for entry in pane_node.entries() {
             match entry
                 .name()
@@ -1264,6 +2720,7 @@ impl<'a> KdlLayoutParser<'a> {
         Ok(())
     }
     fn assert_valid_tab_properties(&self, pane_node: &KdlNode) -> Result<(), ConfigError> {
+        self.assert_no_duplicate_properties(pane_node)?;
         let all_property_names = kdl_property_names!(pane_node);
         for name in all_property_names {
             if !self.is_a_valid_tab_property(name) {
@@ -1276,6 +2733,120 @@ impl<'a> KdlLayoutParser<'a> {
         }
         Ok(())
     }
+    fn assert_valid_stacked_children(
+        &self,
+        kdl_node: &KdlNode,
+        children_split_direction: SplitDirection,
+        children_are_stacked: bool,
+        children: &[TiledPaneLayout],
+    ) -> Result<(), ConfigError> {
+        // stacks are a column of collapsed panes with one expanded pane, so they can only be
+        // split horizontally and (until nested stacks are supported) can only hold simple panes
+        if !children_are_stacked {
+            return Ok(());
+        }
+        if children_split_direction == SplitDirection::Vertical {
+            let split_direction_node =
+                kdl_property_or_child_value_node!(kdl_node, "split_direction");
+            return Err(ConfigError::new_layout_kdl_error(
+                "Panes with stacked children cannot be split vertically, stacks are always split horizontally".into(),
+                split_direction_node
+                    .map(|n| n.span().offset())
+                    .unwrap_or_else(|| kdl_node.span().offset()),
+                split_direction_node
+                    .map(|n| n.span().len())
+                    .unwrap_or_else(|| kdl_node.span().len()),
+            ));
+        }
+        if children.iter().any(|child| !child.children.is_empty()) {
+            return Err(ConfigError::new_layout_kdl_error(
+                "Panes with stacked children cannot also contain panes with their own child panes, nested splits inside a stack are not supported".into(),
+                kdl_node.span().offset(),
+                kdl_node.span().len(),
+            ));
+        }
+        let expanded_count = children
+            .iter()
+            .filter(|child| child.is_expanded_in_stack)
+            .count();
+        if expanded_count > 1 {
+            // pointing at the second pane marked as expanded, when it is marked as such itself
+            let offending_node = kdl_children_nodes!(kdl_node)
+                .and_then(|kdl_children| {
+                    kdl_children
+                        .iter()
+                        .filter(|child| {
+                            kdl_get_bool_property_or_child_value!(child, "expanded") == Some(true)
+                        })
+                        .nth(1)
+                })
+                .unwrap_or(kdl_node);
+            return Err(ConfigError::new_layout_kdl_error(
+                format!(
+                    "Only one pane of a stack can be expanded, found {} panes with expanded=true",
+                    expanded_count
+                ),
+                offending_node.span().offset(),
+                offending_node.span().len(),
+            ));
+        }
+        Ok(())
+    }
+    // percent sizes are shares of the space the fixed size siblings leave, what they leave in turn
+    // is shared by the siblings without a size (or with a weight)
+    fn assert_percent_sizes_fit(
+        &self,
+        kdl_node: &KdlNode,
+        children_are_stacked: bool,
+        children: &[TiledPaneLayout],
+    ) -> Result<(), ConfigError> {
+        if children_are_stacked {
+            // the panes of a stack are not sized by their split sizes
+            return Ok(());
+        }
+        let percent_sum: usize = children
+            .iter()
+            .filter_map(|child| match child.split_size {
+                Some(SplitSize::Percent(percent)) => Some(percent),
+                _ => None,
+            })
+            .sum();
+        let has_flexible_children = children
+            .iter()
+            .any(|child| matches!(child.split_size, None | Some(SplitSize::Weight(_))));
+        if percent_sum > 100 {
+            // pointing at the pane that brings the sum over 100%, when its size is its own
+            let mut declared_percent_sum = 0;
+            let offending_node = kdl_children_nodes!(kdl_node)
+                .and_then(|kdl_children| {
+                    kdl_children.iter().find(|child| {
+                        declared_percent_sum +=
+                            kdl_get_string_property_or_child_value!(child, "size")
+                                .and_then(|size| size.strip_suffix('%'))
+                                .and_then(|percent| percent.parse::<usize>().ok())
+                                .unwrap_or(0);
+                        declared_percent_sum > 100
+                    })
+                })
+                .unwrap_or(kdl_node);
+            Err(ConfigError::new_layout_kdl_error(
+                format!(
+                    "The sizes of sibling panes add up to {}%, which is more than 100%",
+                    percent_sum
+                ),
+                offending_node.span().offset(),
+                offending_node.span().len(),
+            ))
+        } else if percent_sum == 100 && has_flexible_children {
+            Err(ConfigError::new_layout_kdl_error(
+                "The sizes of sibling panes add up to 100%, which leaves no room for the panes without a size".into(),
+                kdl_node.span().offset(),
+                kdl_node.span().len(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
     fn assert_no_mixed_children_and_properties(
         &self,
         kdl_node: &KdlNode,
@@ -1347,15 +2918,20 @@ impl<'a> KdlLayoutParser<'a> {
             Option<String>,
             TiledPaneLayout,
             Vec<FloatingPaneLayout>,
+            Option<TabPin>,
+            Option<usize>,
         ),
         ConfigError,
     > {
-        // (is_focused, Option<tab_name>, PaneLayout, Vec<FloatingPaneLayout>)
+        // (is_focused, Option<tab_name>, PaneLayout, Vec<FloatingPaneLayout>, Option<TabPin>,
+        // Option<max_content_width>)
+        self.assert_no_duplicate_properties(kdl_node)?;
         let tab_name =
             kdl_get_string_property_or_child_value!(kdl_node, "name").map(|s| s.to_string());
-        let tab_cwd =
-            kdl_get_string_property_or_child_value!(kdl_node, "cwd").map(|c| PathBuf::from(c));
+        let tab_cwd = self.parse_cwd(kdl_node)?;
         let is_focused = kdl_get_bool_property_or_child_value!(kdl_node, "focus").unwrap_or(false);
+        let pin = self.parse_tab_pin(kdl_node)?;
+        let max_content_width = self.parse_max_content_width(kdl_node)?;
         let children_split_direction = self.parse_split_direction(kdl_node)?;
         match kdl_children_nodes!(kdl_node) {
             Some(children) => {
@@ -1364,6 +2940,7 @@ impl<'a> KdlLayoutParser<'a> {
                     should_mark_external_children_index,
                     &mut tab_template_floating_panes,
                 )?;
+                self.assert_percent_sizes_fit(kdl_node, false, &child_panes)?;
                 let child_panes_layout = TiledPaneLayout {
                     children_split_direction,
                     children: child_panes,
@@ -1387,12 +2964,19 @@ impl<'a> KdlLayoutParser<'a> {
         if let Some(cwd_prefix) = self.cwd_prefix(tab_cwd.as_ref())? {
             tab_layout.add_cwd_to_layout(&cwd_prefix);
         }
+        // the floating panes of the template as well as those of the tab
+        self.add_cwd_to_floating_panes(&mut tab_template_floating_panes, tab_cwd.as_ref())?;
         tab_layout.external_children_index = None;
+        tab_layout
+            .conditions
+            .merge(self.parse_conditions(kdl_node)?);
         Ok((
             is_focused,
             tab_name,
             tab_layout,
             tab_template_floating_panes,
+            pin,
+            max_content_width,
         ))
     }
     fn populate_one_tab_template(&mut self, kdl_node: &KdlNode) -> Result<(), ConfigError> {
@@ -1405,6 +2989,7 @@ impl<'a> KdlLayoutParser<'a> {
             ))?;
         self.assert_legal_node_name(&template_name, kdl_node)?;
         self.assert_legal_template_name(&template_name, kdl_node)?;
+        self.shadow_shared_template(&template_name, kdl_node);
         if self.tab_templates.contains_key(&template_name) {
             return Err(ConfigError::new_layout_kdl_error(
                 format!(
@@ -1430,6 +3015,7 @@ impl<'a> KdlLayoutParser<'a> {
         Ok(())
     }
     fn populate_default_tab_template(&mut self, kdl_node: &KdlNode) -> Result<(), ConfigError> {
+        self.shadow_shared_template("default_tab_template", kdl_node);
         let (tab_template, tab_template_floating_panes) = self.parse_tab_template_node(kdl_node)?;
         self.default_tab_template =
             Some((tab_template, tab_template_floating_panes, kdl_node.clone()));
@@ -1489,6 +3075,7 @@ impl<'a> KdlLayoutParser<'a> {
                 }
             }
         }
+        self.assert_percent_sizes_fit(kdl_node, false, &tab_children)?;
         Ok((
             TiledPaneLayout {
                 children_split_direction,
@@ -1586,14 +3173,97 @@ impl<'a> KdlLayoutParser<'a> {
     fn populate_global_cwd(&mut self, layout_node: &KdlNode) -> Result<(), ConfigError> {
         // we only populate global cwd from the layout file if another wasn't explicitly passed to us
         if self.global_cwd.is_none() {
-            if let Some(global_cwd) =
-                kdl_get_string_property_or_child_value_with_error!(layout_node, "cwd")
-            {
-                self.global_cwd = Some(PathBuf::from(global_cwd));
-            }
+            self.global_cwd = self.parse_cwd(layout_node)?;
         }
         Ok(())
     }
+    fn parse_side_panel(
+        &self,
+        layout_children: &[KdlNode],
+    ) -> Result<Option<SidePanelLayout>, ConfigError> {
+        let mut side_panel_nodes = layout_children
+            .iter()
+            .filter(|c| kdl_name!(c) == "side_panel");
+        let side_panel_node = match side_panel_nodes.next() {
+            Some(side_panel_node) => side_panel_node,
+            None => return Ok(None),
+        };
+        if let Some(extra_side_panel_node) = side_panel_nodes.next() {
+            return Err(kdl_parsing_error!(
+                "Only one side_panel per layout allowed".into(),
+                extra_side_panel_node
+            ));
+        }
+        self.assert_no_duplicate_properties(side_panel_node)?;
+        for name in kdl_property_names!(side_panel_node) {
+            if name != "width" && name != "side" {
+                return Err(kdl_parsing_error!(
+                    format!("Invalid side_panel property '{}'", name),
+                    side_panel_node
+                ));
+            }
+        }
+        if let Some(side) =
+            kdl_get_string_property_or_child_value_with_error!(side_panel_node, "side")
+        {
+            if side != "right" {
+                return Err(kdl_parsing_error!(
+                    format!(
+                        "Invalid side_panel side '{}', the side panel can only be docked to the \"right\"",
+                        side
+                    ),
+                    side_panel_node
+                ));
+            }
+        }
+        let width = self
+            .parse_percent_or_fixed(side_panel_node, "width")?
+            .ok_or_else(|| {
+                kdl_parsing_error!(
+                    "side_panel must have a width (eg. width=\"25%\")".into(),
+                    side_panel_node
+                )
+            })?;
+        let mut panes = vec![];
+        for child in kdl_children_nodes!(side_panel_node).unwrap_or(&[]) {
+            match kdl_name!(child) {
+                "pane" => panes.push(child),
+                "width" | "side" => {},
+                other => {
+                    return Err(kdl_parsing_error!(
+                        format!("side_panel can only contain a pane node, found: {}", other),
+                        child
+                    ))
+                },
+            }
+        }
+        let pane_node = match panes.as_slice() {
+            [pane_node] => pane_node,
+            _ => {
+                return Err(kdl_parsing_error!(
+                    "side_panel must contain exactly one pane".into(),
+                    side_panel_node
+                ))
+            },
+        };
+        let mut pane = self.parse_pane_node(pane_node)?;
+        if !pane.children.is_empty() {
+            return Err(kdl_parsing_error!(
+                "The side_panel pane cannot have child panes".into(),
+                pane_node
+            ));
+        }
+        if let Some(Run::Plugin(_)) = pane.run {
+            return Err(kdl_parsing_error!(
+                "The side_panel pane cannot be a plugin".into(),
+                pane_node
+            ));
+        }
+        if let Some(global_cwd) = &self.global_cwd {
+            pane.add_cwd_to_layout(&global_cwd);
+        }
+        Ok(Some(SidePanelLayout { width, pane }))
+    }
     fn populate_pane_templates(
         &mut self,
         layout_children: &[KdlNode],
@@ -1653,13 +3323,37 @@ impl<'a> KdlLayoutParser<'a> {
             if child_name == "swap_tiled_layout" {
                 let swap_layout_name =
                     kdl_get_string_property_or_child_value!(child, "name").map(|n| String::from(n));
+                let swap_layout_tags = self.parse_swap_layout_tags(child)?;
                 if let Some(swap_tiled_layout_group) = kdl_children_nodes!(child) {
                     let mut swap_tiled_layout = BTreeMap::new();
+                    let mut pane_ranges = vec![];
                     for layout in swap_tiled_layout_group {
                         let layout_node_name = kdl_name!(layout);
                         if layout_node_name == "tab" {
                             let layout_constraint = self.parse_constraint(layout)?;
+                            self.assert_no_overlapping_pane_range(
+                                layout,
+                                &layout_constraint,
+                                &mut pane_ranges,
+                            )?;
+                            self.record_span(
+                                LayoutLocation::SwapTiledLayout(
+                                    swap_tiled_layouts.len(),
+                                    layout_constraint.clone(),
+                                ),
+                                layout,
+                            );
 
+                            if let Some(swap_layout) = self
+                                .populate_one_swap_tiled_layout_from_pane_refs(
+                                    layout,
+                                    swap_tiled_layouts.len(),
+                                    &layout_constraint,
+                                )?
+                            {
+                                swap_tiled_layout.insert(layout_constraint, swap_layout);
+                                continue;
+                            }
                             match &self.default_tab_template {
                                 Some((
                                     default_tab_template,
@@ -1667,17 +3361,28 @@ impl<'a> KdlLayoutParser<'a> {
                                     default_tab_template_kdl_node,
                                 )) => {
                                     let default_tab_template = default_tab_template.clone();
-                                    let layout = self
+                                    let mut swap_layout = self
                                         .populate_one_swap_tiled_layout_with_template(
                                             layout,
                                             default_tab_template,
                                             default_tab_template_kdl_node.clone(),
                                         )?;
-                                    swap_tiled_layout.insert(layout_constraint, layout);
+                                    self.populate_swap_tiled_layout_policies(
+                                        layout,
+                                        &mut swap_layout,
+                                    )?;
+                                    self.assert_one_focused_pane(layout, &swap_layout)?;
+                                    swap_tiled_layout.insert(layout_constraint, swap_layout);
                                 },
                                 None => {
-                                    let layout = self.populate_one_swap_tiled_layout(layout)?;
-                                    swap_tiled_layout.insert(layout_constraint, layout);
+                                    let mut swap_layout =
+                                        self.populate_one_swap_tiled_layout(layout)?;
+                                    self.populate_swap_tiled_layout_policies(
+                                        layout,
+                                        &mut swap_layout,
+                                    )?;
+                                    self.assert_one_focused_pane(layout, &swap_layout)?;
+                                    swap_tiled_layout.insert(layout_constraint, swap_layout);
                                 },
                             }
                         } else if let Some((
@@ -1687,18 +3392,177 @@ impl<'a> KdlLayoutParser<'a> {
                         )) = self.tab_templates.get(layout_node_name).cloned()
                         {
                             let layout_constraint = self.parse_constraint(layout)?;
-                            let layout = self.populate_one_swap_tiled_layout_with_template(
+                            self.assert_no_overlapping_pane_range(
                                 layout,
-                                tab_template,
-                                tab_template_kdl_node,
+                                &layout_constraint,
+                                &mut pane_ranges,
                             )?;
-                            swap_tiled_layout.insert(layout_constraint, layout);
+                            self.record_span(
+                                LayoutLocation::SwapTiledLayout(
+                                    swap_tiled_layouts.len(),
+                                    layout_constraint.clone(),
+                                ),
+                                layout,
+                            );
+                            if let Some(swap_layout) = self
+                                .populate_one_swap_tiled_layout_from_pane_refs(
+                                    layout,
+                                    swap_tiled_layouts.len(),
+                                    &layout_constraint,
+                                )?
+                            {
+                                swap_tiled_layout.insert(layout_constraint, swap_layout);
+                                continue;
+                            }
+                            let mut swap_layout = self
+                                .populate_one_swap_tiled_layout_with_template(
+                                    layout,
+                                    tab_template,
+                                    tab_template_kdl_node,
+                                )?;
+                            self.populate_swap_tiled_layout_policies(layout, &mut swap_layout)?;
+                            self.assert_one_focused_pane(layout, &swap_layout)?;
+                            swap_tiled_layout.insert(layout_constraint, swap_layout);
                         }
                     }
-                    swap_tiled_layouts.push((swap_tiled_layout, swap_layout_name));
+                    let swap_layout_name = swap_layout_name.or_else(|| {
+                        Some(synthesized_swap_layout_name(
+                            &swap_tiled_layout,
+                            swap_tiled_layouts.len(),
+                        ))
+                    });
+                    swap_tiled_layouts.push((
+                        swap_tiled_layout,
+                        swap_layout_name,
+                        swap_layout_tags,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+    // the panes claiming the focus of each tab, those the tab declares and those of the templates
+    // it is made from, so that the error can point at all of them
+    fn assert_one_focused_pane_per_tab(&self, layout_node: &KdlNode) -> Result<(), ConfigError> {
+        let layout_children = match kdl_children_nodes!(layout_node) {
+            Some(layout_children) => layout_children,
+            None => return Ok(()),
+        };
+        let default_tab_template = self
+            .default_tab_template
+            .as_ref()
+            .map(|(_, _, kdl_node)| (kdl_node, self.shares_default_tab_template));
+        let mut layout_panes = vec![];
+        let mut has_tabs = false;
+        for child in layout_children {
+            let child_name = kdl_name!(child);
+            let tab_template = if child_name == "tab" {
+                Some(default_tab_template)
+            } else {
+                self.tab_templates.get(child_name).map(|(_, _, kdl_node)| {
+                    Some((kdl_node, self.shared_template_names.contains(child_name)))
+                })
+            };
+            if let Some(tab_template) = tab_template {
+                has_tabs = true;
+                let tab_panes = kdl_children_nodes!(child).unwrap_or(&[]);
+                let focus_spans = self.tab_focus_spans(tab_panes, tab_template, child);
+                assert_one_focus_span(&focus_spans)?;
+            } else if child_name == "pane"
+                || child_name == "floating_panes"
+                || self.pane_templates.contains_key(child_name)
+            {
+                layout_panes.push(child.clone());
+            }
+        }
+        if !has_tabs {
+            // the layout node itself is the (only) tab
+            let focus_spans =
+                self.tab_focus_spans(&layout_panes, default_tab_template, layout_node);
+            assert_one_focus_span(&focus_spans)?;
+        }
+        Ok(())
+    }
+    fn tab_focus_spans(
+        &self,
+        tab_panes: &[KdlNode],
+        tab_template: Option<(&KdlNode, bool)>,
+        tab_node: &KdlNode,
+    ) -> Vec<(usize, usize)> {
+        let mut focus_spans = vec![];
+        if let Some((tab_template_node, is_shared)) = tab_template {
+            let template_user_span = if is_shared {
+                Some((tab_node.span().offset(), tab_node.span().len()))
+            } else {
+                None
+            };
+            let tab_template_panes = kdl_children_nodes!(tab_template_node).unwrap_or(&[]);
+            self.collect_focus_spans(tab_template_panes, template_user_span, &mut focus_spans);
+        }
+        self.collect_focus_spans(tab_panes, None, &mut focus_spans);
+        focus_spans.sort();
+        focus_spans
+    }
+    // the spans of the `focus=true` of these panes and of the panes in them. The panes of the
+    // shared templates are defined outside of the layout, so the node using the template is
+    // pointed at instead
+    fn collect_focus_spans(
+        &self,
+        kdl_nodes: &[KdlNode],
+        template_user_span: Option<(usize, usize)>,
+        focus_spans: &mut Vec<(usize, usize)>,
+    ) {
+        let focus_of =
+            |kdl_node: &KdlNode| kdl_get_bool_property_or_child_value!(kdl_node, "focus");
+        for kdl_node in kdl_nodes {
+            let node_name = kdl_name!(kdl_node);
+            if node_name == "swap_tiled_layout" || node_name == "swap_floating_layout" {
+                // the panes of swap layouts are focused when the swap layout is applied
+                continue;
+            }
+            let pane_template = self.pane_templates.get(node_name);
+            let is_pane = node_name == "pane" || pane_template.is_some();
+            if is_pane && focus_of(kdl_node) == Some(true) {
+                focus_spans
+                    .push(template_user_span.unwrap_or_else(|| self.value_span(kdl_node, "focus")));
+            }
+            if let Some((_pane_template, pane_template_node)) = pane_template {
+                let template_user_span = template_user_span.or_else(|| {
+                    if self.shared_template_names.contains(node_name) {
+                        Some((kdl_node.span().offset(), kdl_node.span().len()))
+                    } else {
+                        None
+                    }
+                });
+                // the focus of the node using the template replaces the one of the template
+                if focus_of(kdl_node).is_none() && focus_of(pane_template_node) == Some(true) {
+                    focus_spans.push(
+                        template_user_span
+                            .unwrap_or_else(|| self.value_span(pane_template_node, "focus")),
+                    );
                 }
+                if let Some(template_children) = kdl_children_nodes!(pane_template_node) {
+                    self.collect_focus_spans(template_children, template_user_span, focus_spans);
+                }
+            }
+            if let Some(children) = kdl_children_nodes!(kdl_node) {
+                self.collect_focus_spans(children, template_user_span, focus_spans);
             }
         }
+    }
+    // the pane a swap layout focuses is the one that gets the focus when it is applied, so there
+    // can only be one
+    fn assert_one_focused_pane(
+        &self,
+        layout_node: &KdlNode,
+        swap_layout: &TiledPaneLayout,
+    ) -> Result<(), ConfigError> {
+        if swap_layout.focused_node_count() > 1 {
+            return Err(kdl_parsing_error!(
+                format!("Only one pane of a swap layout can be focused"),
+                layout_node
+            ));
+        }
         Ok(())
     }
     fn populate_swap_floating_layouts(
@@ -1711,12 +3575,26 @@ impl<'a> KdlLayoutParser<'a> {
             if child_name == "swap_floating_layout" {
                 let swap_layout_name =
                     kdl_get_string_property_or_child_value!(child, "name").map(|n| String::from(n));
+                let swap_layout_tags = self.parse_swap_layout_tags(child)?;
                 if let Some(swap_floating_layout_group) = kdl_children_nodes!(child) {
                     let mut swap_floating_layout = BTreeMap::new();
+                    let mut pane_ranges = vec![];
                     for layout in swap_floating_layout_group {
                         let layout_node_name = kdl_name!(layout);
                         if layout_node_name == "floating_panes" {
                             let layout_constraint = self.parse_constraint(layout)?;
+                            self.assert_no_overlapping_pane_range(
+                                layout,
+                                &layout_constraint,
+                                &mut pane_ranges,
+                            )?;
+                            self.record_span(
+                                LayoutLocation::SwapFloatingLayout(
+                                    swap_floating_layouts.len(),
+                                    layout_constraint.clone(),
+                                ),
+                                layout,
+                            );
                             let layout = self.populate_one_swap_floating_layout(layout)?;
                             swap_floating_layout.insert(layout_constraint, layout);
                         } else if let Some((
@@ -1726,6 +3604,18 @@ impl<'a> KdlLayoutParser<'a> {
                         )) = self.tab_templates.get(layout_node_name).cloned()
                         {
                             let layout_constraint = self.parse_constraint(layout)?;
+                            self.assert_no_overlapping_pane_range(
+                                layout,
+                                &layout_constraint,
+                                &mut pane_ranges,
+                            )?;
+                            self.record_span(
+                                LayoutLocation::SwapFloatingLayout(
+                                    swap_floating_layouts.len(),
+                                    layout_constraint.clone(),
+                                ),
+                                layout,
+                            );
                             let layout = self.populate_one_swap_floating_layout_with_template(
                                 layout,
                                 tab_template,
@@ -1735,42 +3625,275 @@ impl<'a> KdlLayoutParser<'a> {
                             swap_floating_layout.insert(layout_constraint, layout);
                         }
                     }
-                    swap_floating_layouts.push((swap_floating_layout, swap_layout_name));
+                    let swap_layout_name = swap_layout_name.or_else(|| {
+                        Some(synthesized_swap_layout_name(
+                            &swap_floating_layout,
+                            swap_floating_layouts.len(),
+                        ))
+                    });
+                    swap_floating_layouts.push((
+                        swap_floating_layout,
+                        swap_layout_name,
+                        swap_layout_tags,
+                    ));
                 }
             }
         }
         Ok(())
     }
-    fn parse_constraint(&mut self, layout_node: &KdlNode) -> Result<LayoutConstraint, ConfigError> {
-        if let Some(max_panes) = kdl_get_string_property_or_child_value!(layout_node, "max_panes") {
-            return Err(kdl_parsing_error!(
+    // which swap layout entry a pane count picks would otherwise depend on the order of the
+    // entries rather than on their constraints
+    fn assert_no_overlapping_pane_range<'k>(
+        &self,
+        layout_node: &'k KdlNode,
+        layout_constraint: &LayoutConstraint,
+        pane_ranges: &mut Vec<(LayoutConstraint, &'k KdlNode)>,
+    ) -> Result<(), ConfigError> {
+        let (min, max) = match layout_constraint.pane_constraint() {
+            LayoutConstraint::PaneRange { min, max } => (*min, *max),
+            _ => return Ok(()),
+        };
+        let overlapping = pane_ranges.iter().find(|(other_constraint, _other_node)| {
+            match other_constraint.pane_constraint() {
+                LayoutConstraint::PaneRange {
+                    min: other_min,
+                    max: other_max,
+                } => {
+                    other_constraint.session_constraint() == layout_constraint.session_constraint()
+                        && min <= *other_max
+                        && *other_min <= max
+                },
+                _ => false,
+            }
+        });
+        if let Some((other_constraint, other_node)) = overlapping {
+            return Err(ConfigError::new_layout_kdl_error(
                 format!(
-                    "max_panes should be a fixed number (eg. 1) and not a quoted string (\"{}\")",
-                    max_panes
+                    "The pane range of this swap layout ({}) overlaps with the one of another ({})",
+                    layout_constraint, other_constraint
                 ),
-                layout_node
+                layout_node.span().offset(),
+                layout_node.span().len(),
+            )
+            .with_secondary_label(
+                format!("overlapping pane range ({})", other_constraint),
+                other_node.span().offset(),
+                other_node.span().len(),
             ));
-        };
-        if let Some(min_panes) = kdl_get_string_property_or_child_value!(layout_node, "min_panes") {
+        }
+        pane_ranges.push((layout_constraint.clone(), layout_node));
+        Ok(())
+    }
+    fn parse_constraint(&mut self, layout_node: &KdlNode) -> Result<LayoutConstraint, ConfigError> {
+        for constraint in layout_capability_values(SWAP_CONSTRAINTS) {
+            if let Some(value) = kdl_get_string_property_or_child_value!(layout_node, *constraint) {
+                return Err(kdl_parsing_error!(
+                    format!(
+                        "{} should be a fixed number (eg. 1) and not a quoted string (\"{}\")",
+                        constraint, value
+                    ),
+                    layout_node
+                ));
+            }
+        }
+        let (session_constraints, pane_constraints): (Vec<(&str, usize)>, Vec<(&str, usize)>) =
+            layout_capability_values(SWAP_CONSTRAINTS)
+                .iter()
+                .filter_map(|constraint| {
+                    kdl_get_int_property_or_child_value!(layout_node, *constraint)
+                        .map(|count| (*constraint, count as usize))
+                })
+                .partition(|(constraint, _count)| constraint.starts_with("session_"));
+        // min_panes and max_panes together are a range, in the order of SWAP_CONSTRAINTS
+        let is_pane_range = matches!(
+            pane_constraints.as_slice(),
+            [("min_panes", _), ("max_panes", _)]
+        );
+        if (pane_constraints.len() > 1 && !is_pane_range) || session_constraints.len() > 1 {
             return Err(kdl_parsing_error!(
-                format!(
-                    "min_panes should be a fixed number (eg. 1) and not a quoted string (\"{}\")",
-                    min_panes
-                ),
+                format!("cannot have more than one constraint on the panes (eg. exact_panes + max_panes), except min_panes with max_panes, or on the tabs of the session (eg. session_tabs_max + session_tabs_min)"),
                 layout_node
             ));
+        }
+        let pane_constraint = match pane_constraints.first().copied() {
+            Some(("min_panes", min)) if is_pane_range => {
+                let max = pane_constraints[1].1;
+                if min > max {
+                    return Err(kdl_parsing_error!(
+                        format!("min_panes cannot be larger than max_panes"),
+                        layout_node
+                    ));
+                }
+                LayoutConstraint::PaneRange { min, max }
+            },
+            Some(("exact_panes", exact_panes)) => LayoutConstraint::ExactPanes(exact_panes),
+            Some(("max_panes", max_panes)) => LayoutConstraint::MaxPanes(max_panes),
+            Some(("min_panes", min_panes)) => LayoutConstraint::MinPanes(min_panes),
+            Some(("max_groups", max_groups)) => LayoutConstraint::MaxGroups(max_groups),
+            Some(("min_groups", min_groups)) => LayoutConstraint::MinGroups(min_groups),
+            Some((constraint, _count)) => {
+                return Err(kdl_parsing_error!(
+                    format!("unsupported constraint: {}", constraint),
+                    layout_node
+                ));
+            },
+            None => LayoutConstraint::NoConstraint,
+        };
+        let session_constraint = match session_constraints.first().copied() {
+            Some(("session_tabs_max", max_tabs)) => SessionConstraint::MaxTabs(max_tabs),
+            Some(("session_tabs_min", min_tabs)) => SessionConstraint::MinTabs(min_tabs),
+            Some((constraint, _count)) => {
+                return Err(kdl_parsing_error!(
+                    format!("unsupported constraint: {}", constraint),
+                    layout_node
+                ));
+            },
+            None => return Ok(pane_constraint),
         };
-        let max_panes = kdl_get_int_property_or_child_value!(layout_node, "max_panes");
-        let min_panes = kdl_get_int_property_or_child_value!(layout_node, "min_panes");
-        match (min_panes, max_panes) {
-            (Some(_min_panes), Some(_max_panes)) => Err(kdl_parsing_error!(
-                format!("cannot have more than one constraint (eg. max_panes + min_panes)'"),
+        Ok(LayoutConstraint::InSession(
+            session_constraint,
+            Box::new(pane_constraint),
+        ))
+    }
+    fn populate_swap_tiled_layout_policies(
+        &self,
+        layout_node: &KdlNode,
+        swap_layout: &mut TiledPaneLayout,
+    ) -> Result<(), ConfigError> {
+        if let Some(extra_panes) =
+            kdl_get_string_property_or_child_value_with_error!(layout_node, "extra_panes")
+        {
+            match ExtraPanesPolicy::from_str(extra_panes) {
+                Ok(extra_panes) => swap_layout.extra_panes = Some(extra_panes),
+                Err(e) => return Err(kdl_parsing_error!(e.to_string(), layout_node)),
+            }
+        }
+        if let Some(missing_slots) =
+            kdl_get_string_property_or_child_value_with_error!(layout_node, "missing_slots")
+        {
+            match MissingSlotsPolicy::from_str(missing_slots) {
+                Ok(missing_slots) => swap_layout.missing_slots = missing_slots,
+                Err(e) => return Err(kdl_parsing_error!(e.to_string(), layout_node)),
+            }
+        }
+        Ok(())
+    }
+    fn populate_one_swap_tiled_layout_from_pane_refs(
+        &mut self,
+        layout_node: &KdlNode,
+        swap_layout_index: usize,
+        layout_constraint: &LayoutConstraint,
+    ) -> Result<Option<TiledPaneLayout>, ConfigError> {
+        // returns a placeholder to be replaced once the base layout is known
+        match self.parse_swap_pane_refs(layout_node)? {
+            Some(_pane_refs) if self.parsing_tab_swap_layouts => Err(kdl_parsing_error!(
+                "The swap layouts of a tab cannot refer to the base layout, their panes should be defined in full".into(),
                 layout_node
             )),
-            (Some(min_panes), None) => Ok(LayoutConstraint::MinPanes(min_panes as usize)),
-            (None, Some(max_panes)) => Ok(LayoutConstraint::MaxPanes(max_panes as usize)),
-            _ => Ok(LayoutConstraint::NoConstraint),
+            Some(pane_refs) => {
+                let mut swap_layout = TiledPaneLayout::default();
+                self.populate_swap_tiled_layout_policies(layout_node, &mut swap_layout)?;
+                self.unresolved_pane_refs.push((
+                    swap_layout_index,
+                    layout_constraint.clone(),
+                    pane_refs,
+                ));
+                Ok(Some(swap_layout))
+            },
+            None => Ok(None),
+        }
+    }
+    fn parse_swap_pane_refs(
+        &self,
+        layout_node: &KdlNode,
+    ) -> Result<Option<Vec<(PaneRef, KdlNode)>>, ConfigError> {
+        // a swap tiled layout entry is either made only of pane refs to the base layout (and then
+        // it is the base layout with those panes changed) or fully defined without any refs
+        let children = kdl_children_nodes!(layout_node).unwrap_or(&[]);
+        let has_pane_refs = children
+            .iter()
+            .any(|child| kdl_get_string_property_or_child_value!(child, "ref").is_some());
+        if !has_pane_refs {
+            return Ok(None);
+        }
+        self.assert_valid_tab_properties(layout_node)?;
+        let mut pane_refs = vec![];
+        for child in children {
+            let target = match kdl_get_string_property_or_child_value!(child, "ref") {
+                Some(target) if kdl_name!(child) == "pane" => target,
+                _ => {
+                    return Err(kdl_parsing_error!(
+                        "Swap layout entries referring to the base layout can only contain panes with a ref (eg. pane ref=\"editor\" size=\"+10%\")".into(),
+                        child
+                    ))
+                },
+            };
+            for name in kdl_property_names!(child) {
+                if name != "ref" && name != "size" {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "Invalid property '{}' for a pane with a ref, only its size can be changed",
+                            name
+                        ),
+                        child
+                    ));
+                }
+            }
+            let has_child_panes = kdl_children_nodes!(child)
+                .map(|nodes| {
+                    nodes
+                        .iter()
+                        .any(|n| kdl_name!(n) != "ref" && kdl_name!(n) != "size")
+                })
+                .unwrap_or(false);
+            if has_child_panes {
+                return Err(kdl_parsing_error!(
+                    "Panes with a ref cannot have children, they are taken from the base layout"
+                        .into(),
+                    child
+                ));
+            }
+            let size = if let Some(size) = kdl_get_string_property_or_child_value!(child, "size") {
+                Some(SizeAdjustment::from_str(size).map_err(|_e| {
+                    kdl_parsing_error!(
+                        format!("size of a pane with a ref should be a fixed number, a quoted percent or a quoted change (eg. \"+10%\" or \"-2\")"),
+                        child
+                    )
+                })?)
+            } else {
+                self.parse_split_size(child)?.map(SizeAdjustment::Set)
+            };
+            pane_refs.push((
+                PaneRef {
+                    target: target.into(),
+                    size,
+                },
+                child.clone(),
+            ));
+        }
+        Ok(Some(pane_refs))
+    }
+    fn resolve_swap_pane_refs(&mut self, layout: &mut Layout) -> Result<(), ConfigError> {
+        // the base layout is the one new tabs are created with
+        let base = layout.new_tab().0;
+        for (index, layout_constraint, pane_refs) in self.unresolved_pane_refs.drain(..) {
+            let swap_layout = match layout.swap_tiled_layouts.get_mut(index).and_then(
+                |(swap_tiled_layout, _name, _tags)| swap_tiled_layout.get_mut(&layout_constraint),
+            ) {
+                Some(swap_layout) => swap_layout,
+                None => continue,
+            };
+            let mut resolved = base.clone();
+            for (pane_ref, pane_ref_node) in &pane_refs {
+                resolved
+                    .apply_pane_ref(pane_ref)
+                    .map_err(|e| kdl_parsing_error!(e, pane_ref_node))?;
+            }
+            resolved.extra_panes = swap_layout.extra_panes;
+            resolved.missing_slots = swap_layout.missing_slots;
+            *swap_layout = resolved;
         }
+        Ok(())
     }
     fn populate_one_swap_tiled_layout(
         &self,
@@ -1790,6 +3913,7 @@ impl<'a> KdlLayoutParser<'a> {
             },
             None => vec![],
         };
+        self.assert_percent_sizes_fit(layout_node, false, &children)?;
         let pane_layout = TiledPaneLayout {
             children_split_direction,
             children,
@@ -1820,6 +3944,7 @@ impl<'a> KdlLayoutParser<'a> {
         let mut floating_panes = vec![];
         self.assert_valid_tab_properties(layout_node)?;
         self.populate_floating_pane_children(layout_node, &mut floating_panes)?;
+        self.add_cwd_to_floating_panes(&mut floating_panes, None)?;
         Ok(floating_panes)
     }
     fn populate_one_swap_floating_layout_with_template(
@@ -1839,9 +3964,39 @@ impl<'a> KdlLayoutParser<'a> {
         )?;
         Ok(layout.3)
     }
+    // the tab templates as the tabs they open when they are given no children, with the global
+    // cwd of the layout
+    fn new_tab_templates(
+        &self,
+    ) -> Result<BTreeMap<String, (TiledPaneLayout, Vec<FloatingPaneLayout>)>, ConfigError> {
+        self.tab_templates
+            .iter()
+            .map(|(template_name, (template, floating_panes, _kdl_node))| {
+                let mut template = template.clone();
+                if let Some(children_index) = template.external_children_index.take() {
+                    template
+                        .children
+                        .insert(children_index, TiledPaneLayout::default());
+                }
+                if let Some(global_cwd) = &self.global_cwd {
+                    template.add_cwd_to_layout(global_cwd);
+                }
+                let mut floating_panes = floating_panes.clone();
+                self.add_cwd_to_floating_panes(&mut floating_panes, None)?;
+                Ok((template_name.clone(), (template, floating_panes)))
+            })
+            .collect()
+    }
     fn layout_with_tabs(
         &self,
-        tabs: Vec<(Option<String>, TiledPaneLayout, Vec<FloatingPaneLayout>)>,
+        tabs: Vec<(
+            Option<String>,
+            TiledPaneLayout,
+            Vec<FloatingPaneLayout>,
+            Option<TabPin>,
+            Option<usize>,
+            (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+        )>,
         focused_tab_index: Option<usize>,
         swap_tiled_layouts: Vec<SwapTiledLayout>,
         swap_floating_layouts: Vec<SwapFloatingLayout>,
@@ -1876,7 +4031,14 @@ impl<'a> KdlLayoutParser<'a> {
             // to explicitly place it in the first tab
             vec![]
         } else {
-            vec![(None, main_tab_layout.clone(), floating_panes.clone())]
+            vec![(
+                None,
+                main_tab_layout.clone(),
+                floating_panes.clone(),
+                None,
+                None,
+                (vec![], vec![]),
+            )]
         };
         let template = default_template.unwrap_or_else(|| main_tab_layout.clone());
         // create a layout with one tab that has these child panes
@@ -1912,6 +4074,9 @@ impl<'a> KdlLayoutParser<'a> {
             Option<String>,
             TiledPaneLayout,
             Vec<FloatingPaneLayout>,
+            Option<TabPin>,
+            Option<usize>,
+            (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
         )>,
         child_panes: &mut Vec<TiledPaneLayout>,
         child_floating_panes: &mut Vec<FloatingPaneLayout>,
@@ -1931,7 +4096,13 @@ impl<'a> KdlLayoutParser<'a> {
             }
             child_panes.push(pane_node);
         } else if child_name == "floating_panes" {
-            self.populate_floating_pane_children(child, child_floating_panes)?;
+            // appended to the floating panes of the other blocks, so that their stacks are told
+            // apart
+            let mut floating_panes = child_floating_panes.clone();
+            self.populate_floating_pane_children(child, &mut floating_panes)?;
+            let mut floating_panes = floating_panes.split_off(child_floating_panes.len());
+            self.add_cwd_to_floating_panes(&mut floating_panes, None)?;
+            child_floating_panes.append(&mut floating_panes);
         } else if child_name == "tab" {
             if !child_panes.is_empty() || !child_floating_panes.is_empty() {
                 return Err(ConfigError::new_layout_kdl_error(
@@ -1940,26 +4111,36 @@ impl<'a> KdlLayoutParser<'a> {
                     child.span().len(),
                 ));
             }
-            match &self.default_tab_template {
-                Some((
-                    default_tab_template,
-                    default_tab_template_floating_panes,
-                    default_tab_template_kdl_node,
-                )) => {
-                    let default_tab_template = default_tab_template.clone();
-                    let should_mark_external_children_index = false;
-                    child_tabs.push(self.parse_tab_node_with_template(
-                        child,
+            self.record_span(LayoutLocation::Tab(child_tabs.len()), child);
+            let swap_layouts = self.parse_tab_swap_layouts(child)?;
+            let (is_focused, tab_name, tab_layout, floating_panes, pin, max_content_width) =
+                match &self.default_tab_template {
+                    Some((
                         default_tab_template,
-                        default_tab_template_floating_panes.clone(),
-                        should_mark_external_children_index,
+                        default_tab_template_floating_panes,
                         default_tab_template_kdl_node,
-                    )?);
-                },
-                None => {
-                    child_tabs.push(self.parse_tab_node(child)?);
-                },
-            }
+                    )) => {
+                        let default_tab_template = default_tab_template.clone();
+                        let should_mark_external_children_index = false;
+                        self.parse_tab_node_with_template(
+                            child,
+                            default_tab_template,
+                            default_tab_template_floating_panes.clone(),
+                            should_mark_external_children_index,
+                            default_tab_template_kdl_node,
+                        )?
+                    },
+                    None => self.parse_tab_node(child)?,
+                };
+            child_tabs.push((
+                is_focused,
+                tab_name,
+                tab_layout,
+                floating_panes,
+                pin,
+                max_content_width,
+                swap_layouts,
+            ));
         } else if let Some((tab_template, tab_template_floating_panes, tab_template_kdl_node)) =
             self.tab_templates.get(child_name).cloned()
         {
@@ -1970,14 +4151,26 @@ impl<'a> KdlLayoutParser<'a> {
                     child.span().len(),
                 ));
             }
+            self.record_span(LayoutLocation::Tab(child_tabs.len()), child);
+            let swap_layouts = self.parse_tab_swap_layouts(child)?;
             let should_mark_external_children_index = false;
-            child_tabs.push(self.parse_tab_node_with_template(
+            let (is_focused, tab_name, tab_layout, floating_panes, pin, max_content_width) = self
+                .parse_tab_node_with_template(
                 child,
                 tab_template,
                 tab_template_floating_panes,
                 should_mark_external_children_index,
                 &tab_template_kdl_node,
-            )?);
+            )?;
+            child_tabs.push((
+                is_focused,
+                tab_name,
+                tab_layout,
+                floating_panes,
+                pin,
+                max_content_width,
+                swap_layouts,
+            ));
         } else if let Some((pane_template, pane_template_kdl_node)) =
             self.pane_templates.get(child_name).cloned()
         {
@@ -2015,33 +4208,157 @@ impl<'a> KdlLayoutParser<'a> {
     ) -> Result<(), ConfigError> {
         if let Some(children) = kdl_children_nodes!(child) {
             for child in children {
-                if kdl_name!(child) == "pane" {
-                    let mut pane_node = self.parse_floating_pane_node(child)?;
-                    if let Some(global_cwd) = &self.global_cwd {
-                        pane_node.add_cwd_to_layout(&global_cwd);
-                    }
-                    child_floating_panes.push(pane_node);
-                } else if let Some((pane_template, pane_template_kdl_node)) =
-                    self.pane_templates.get(kdl_name!(child)).cloned()
-                {
-                    let pane_node = self.parse_floating_pane_node_with_template(
-                        child,
-                        pane_template,
-                        &pane_template_kdl_node,
-                    )?;
-                    child_floating_panes.push(pane_node);
-                } else {
+                if kdl_name!(child) == "stack" {
+                    self.populate_floating_stack(child, child_floating_panes)?;
+                    continue;
+                }
+                match self.parse_floating_pane_or_template_node(child)? {
+                    Some(pane_node) => {
+                        if pane_node.is_expanded_in_stack {
+                            return Err(ConfigError::new_layout_kdl_error(
+                                "Only the panes of a stack can be expanded".into(),
+                                child.span().offset(),
+                                child.span().len(),
+                            ));
+                        }
+                        child_floating_panes.push(pane_node);
+                    },
+                    None => {
+                        return Err(ConfigError::new_layout_kdl_error(
+                            format!(
+                                "floating_panes can only contain pane and stack nodes, found: {}",
+                                kdl_name!(child)
+                            ),
+                            child.span().offset(),
+                            child.span().len(),
+                        ));
+                    },
+                }
+            }
+        };
+        Ok(())
+    }
+    // a floating pane, from a `pane` node or one using a pane template, None for other nodes
+    fn parse_floating_pane_or_template_node(
+        &self,
+        kdl_node: &KdlNode,
+    ) -> Result<Option<FloatingPaneLayout>, ConfigError> {
+        let mut pane_node = if kdl_name!(kdl_node) == "pane" {
+            self.parse_floating_pane_node(kdl_node)?
+        } else if let Some((pane_template, pane_template_kdl_node)) =
+            self.pane_templates.get(kdl_name!(kdl_node)).cloned()
+        {
+            self.parse_floating_pane_node_with_template(
+                kdl_node,
+                pane_template,
+                &pane_template_kdl_node,
+            )?
+        } else {
+            return Ok(None);
+        };
+        if let Some(expanded) =
+            kdl_get_bool_property_or_child_value_with_error!(kdl_node, "expanded")
+        {
+            pane_node.is_expanded_in_stack = expanded;
+        }
+        Ok(Some(pane_node))
+    }
+    // the panes of a `stack` in `floating_panes` share its position and size, one below the other
+    // with all but the expanded one collapsed to their title bar (see `floating_stack_geom`)
+    fn populate_floating_stack(
+        &self,
+        stack_node: &KdlNode,
+        child_floating_panes: &mut Vec<FloatingPaneLayout>,
+    ) -> Result<(), ConfigError> {
+        self.assert_no_duplicate_properties(stack_node)?;
+        for entry in stack_node.entries() {
+            let property_name = entry.name().map(|name| name.value());
+            if !matches!(property_name, Some("x" | "y" | "width" | "height")) {
+                return Err(ConfigError::new_layout_kdl_error(
+                    "A floating stack can only have an x, y, width and height".into(),
+                    entry.span().offset(),
+                    entry.span().len(),
+                ));
+            }
+        }
+        let (height, height_fit) = self.parse_floating_pane_size(stack_node, "height")?;
+        let (width, width_fit) = self.parse_floating_pane_size(stack_node, "width")?;
+        if height_fit.is_some() || width_fit.is_some() {
+            return Err(kdl_parsing_error!(
+                "A floating stack cannot fit to the content of its panes".into(),
+                stack_node
+            ));
+        }
+        let x = self.parse_floating_pane_coordinate(stack_node, "x")?;
+        let y = self.parse_floating_pane_coordinate(stack_node, "y")?;
+        // the stacks of the floating panes of a tab (those of its template included) are told
+        // apart by their index among them
+        let stack = child_floating_panes
+            .iter()
+            .filter_map(|pane| pane.stack)
+            .max()
+            .map_or(0, |last_stack| last_stack + 1);
+        let mut stacked_panes = vec![];
+        let mut expanded_nodes = vec![];
+        for child in kdl_children_nodes!(stack_node).unwrap_or(&[]) {
+            if matches!(kdl_name!(child), "x" | "y" | "width" | "height") {
+                // the geometry of the stack written as child nodes
+                continue;
+            }
+            let mut pane_node = match self.parse_floating_pane_or_template_node(child)? {
+                Some(pane_node) => pane_node,
+                None => {
                     return Err(ConfigError::new_layout_kdl_error(
                         format!(
-                            "floating_panes can only contain pane nodes, found: {}",
+                            "A floating stack can only contain pane nodes, found: {}",
                             kdl_name!(child)
                         ),
                         child.span().offset(),
                         child.span().len(),
                     ));
-                }
+                },
+            };
+            let has_geometry = pane_node.x.is_some()
+                || pane_node.y.is_some()
+                || pane_node.width.is_some()
+                || pane_node.height.is_some()
+                || pane_node.max_width.is_some()
+                || pane_node.max_height.is_some()
+                || pane_node.anchor.is_some();
+            if has_geometry {
+                return Err(ConfigError::new_layout_kdl_error(
+                    "The panes of a floating stack take the position and size of the stack".into(),
+                    child.span().offset(),
+                    child.span().len(),
+                ));
             }
-        };
+            if pane_node.is_expanded_in_stack {
+                expanded_nodes.push(child);
+            }
+            pane_node.x = x.clone();
+            pane_node.y = y.clone();
+            pane_node.width = width.clone();
+            pane_node.height = height.clone();
+            pane_node.stack = Some(stack);
+            stacked_panes.push(pane_node);
+        }
+        if stacked_panes.is_empty() {
+            return Err(kdl_parsing_error!(
+                "A floating stack needs at least one pane".into(),
+                stack_node
+            ));
+        }
+        if let Some(second_expanded_node) = expanded_nodes.get(1) {
+            return Err(ConfigError::new_layout_kdl_error(
+                format!(
+                    "Only one pane of a stack can be expanded, found {} panes with expanded=true",
+                    expanded_nodes.len()
+                ),
+                second_expanded_node.span().offset(),
+                second_expanded_node.span().len(),
+            ));
+        }
+        child_floating_panes.append(&mut stacked_panes);
         Ok(())
     }
     pub fn parse_external_swap_layouts(
@@ -2049,9 +4366,22 @@ impl<'a> KdlLayoutParser<'a> {
         raw_swap_layouts: &str,
         mut existing_layout: Layout,
     ) -> Result<Layout, ConfigError> {
-        let kdl_swap_layout: KdlDocument = raw_swap_layouts.parse()?;
-        let mut swap_tiled_layouts = vec![];
-        let mut swap_floating_layouts = vec![];
+        let parse_start = Instant::now();
+        let mut kdl_swap_layout: KdlDocument = raw_swap_layouts.parse()?;
+        self.load_stats.parse_ms += elapsed_ms(parse_start);
+        let node_count = kdl_node_count(&kdl_swap_layout);
+        self.load_stats.node_count += node_count;
+        self.validation_options
+            .limits
+            .check_node_count(node_count)?;
+        let expand_start = Instant::now();
+        // we start from the existing swap layouts so that the recorded spans have the right index
+        let mut swap_tiled_layouts: Vec<SwapTiledLayout> =
+            existing_layout.swap_tiled_layouts.drain(..).collect();
+        let mut swap_floating_layouts: Vec<SwapFloatingLayout> =
+            existing_layout.swap_floating_layouts.drain(..).collect();
+        self.parsing_swap_layouts = true;
+        self.translate_deprecated_names(&mut kdl_swap_layout)?;
 
         for node in kdl_swap_layout.nodes() {
             let node_name = kdl_name!(node);
@@ -2084,16 +4414,25 @@ impl<'a> KdlLayoutParser<'a> {
         self.populate_swap_tiled_layouts(kdl_swap_layout.nodes(), &mut swap_tiled_layouts)?;
         self.populate_swap_floating_layouts(kdl_swap_layout.nodes(), &mut swap_floating_layouts)?;
 
-        existing_layout
-            .swap_tiled_layouts
-            .append(&mut swap_tiled_layouts);
-        existing_layout
-            .swap_floating_layouts
-            .append(&mut swap_floating_layouts);
+        self.parsing_swap_layouts = false;
+        existing_layout.swap_tiled_layouts = swap_tiled_layouts;
+        self.resolve_swap_pane_refs(&mut existing_layout)?;
+        existing_layout.swap_floating_layouts = swap_floating_layouts;
+        existing_layout.normalize_empty_containers();
+        self.load_stats.expand_ms += elapsed_ms(expand_start);
         Ok(existing_layout)
     }
     pub fn parse(&mut self) -> Result<Layout, ConfigError> {
-        let kdl_layout: KdlDocument = self.raw_layout.parse()?;
+        let parse_start = Instant::now();
+        let mut kdl_layout: KdlDocument = self.raw_layout.parse()?;
+        self.load_stats.parse_ms += elapsed_ms(parse_start);
+        let node_count = kdl_node_count(&kdl_layout);
+        self.load_stats.node_count += node_count;
+        self.validation_options
+            .limits
+            .check_node_count(node_count)?;
+        let expand_start = Instant::now();
+        self.translate_deprecated_names(&mut kdl_layout)?;
         let layout_node = kdl_layout
             .nodes()
             .iter()
@@ -2121,12 +4460,14 @@ impl<'a> KdlLayoutParser<'a> {
         let mut child_floating_panes = vec![];
         let mut swap_tiled_layouts = vec![];
         let mut swap_floating_layouts = vec![];
+        let mut side_panel = None;
         if let Some(children) = kdl_children_nodes!(layout_node) {
             self.populate_global_cwd(layout_node)?;
             self.populate_pane_templates(children, &kdl_layout)?;
             self.populate_tab_templates(children)?;
             self.populate_swap_tiled_layouts(children, &mut swap_tiled_layouts)?;
             self.populate_swap_floating_layouts(children, &mut swap_floating_layouts)?;
+            side_panel = self.parse_side_panel(children)?;
             for child in children {
                 self.populate_layout_child(
                     child,
@@ -2136,10 +4477,26 @@ impl<'a> KdlLayoutParser<'a> {
                 )?;
             }
         }
-        if !child_tabs.is_empty() {
+        self.assert_percent_sizes_fit(layout_node, false, &child_panes)?;
+        self.assert_one_focused_pane_per_tab(layout_node)?;
+        self.record_span(LayoutLocation::NewTabTemplate, layout_node);
+        if child_tabs.is_empty() {
+            // the layout node itself is the (only) tab
+            self.record_span(LayoutLocation::Tab(0), layout_node);
+        }
+        let tab_names: Vec<Option<String>> = child_tabs
+            .iter()
+            .map(|(_is_focused, tab_name, ..)| tab_name.clone())
+            .collect();
+        let focused_tab = self.parse_focused_tab(
+            layout_node,
+            &tab_names,
+            child_tabs.iter().any(|(is_focused, ..)| *is_focused),
+        )?;
+        let mut layout = if !child_tabs.is_empty() {
             let has_more_than_one_focused_tab = child_tabs
                 .iter()
-                .filter(|(is_focused, _, _, _)| *is_focused)
+                .filter(|(is_focused, ..)| *is_focused)
                 .count()
                 > 1;
             if has_more_than_one_focused_tab {
@@ -2149,18 +4506,40 @@ impl<'a> KdlLayoutParser<'a> {
                     kdl_layout.span().len(),
                 ));
             }
-            let focused_tab_index = child_tabs
-                .iter()
-                .position(|(is_focused, _, _, _)| *is_focused);
-            let child_tabs: Vec<(Option<String>, TiledPaneLayout, Vec<FloatingPaneLayout>)> =
-                child_tabs
-                    .drain(..)
-                    .map(
-                        |(_is_focused, tab_name, pane_layout, floating_panes_layout)| {
-                            (tab_name, pane_layout, floating_panes_layout)
-                        },
-                    )
-                    .collect();
+            let focused_tab_index = match &focused_tab {
+                Some((index, _name)) => Some(*index),
+                None => child_tabs.iter().position(|(is_focused, ..)| *is_focused),
+            };
+            let child_tabs: Vec<(
+                Option<String>,
+                TiledPaneLayout,
+                Vec<FloatingPaneLayout>,
+                Option<TabPin>,
+                Option<usize>,
+                (Vec<SwapTiledLayout>, Vec<SwapFloatingLayout>),
+            )> = child_tabs
+                .drain(..)
+                .map(
+                    |(
+                        _is_focused,
+                        tab_name,
+                        pane_layout,
+                        floating_panes_layout,
+                        pin,
+                        max_content_width,
+                        swap_layouts,
+                    )| {
+                        (
+                            tab_name,
+                            pane_layout,
+                            floating_panes_layout,
+                            pin,
+                            max_content_width,
+                            swap_layouts,
+                        )
+                    },
+                )
+                .collect();
             self.layout_with_tabs(
                 child_tabs,
                 focused_tab_index,
@@ -2180,6 +4559,142 @@ impl<'a> KdlLayoutParser<'a> {
                 swap_tiled_layouts,
                 swap_floating_layouts,
             )
+        }?;
+        layout.side_panel = side_panel;
+        layout.focused_tab_name = focused_tab.map(|(_index, tab_name)| tab_name);
+        layout.tab_templates = self.new_tab_templates()?;
+        layout.command_spawn_interval_ms = self.parse_command_spawn_interval(layout_node)?;
+        layout.default_focus = self.parse_default_focus(layout_node)?;
+        layout.command_wrapper = self.parse_command_wrapper(layout_node)?;
+        let (session_name, attach_to_existing) = self.parse_session_name(layout_node)?;
+        layout.session_name = session_name;
+        layout.attach_to_existing = attach_to_existing;
+        self.resolve_swap_pane_refs(&mut layout)?;
+        layout.normalize_empty_containers();
+        self.load_stats.expand_ms += elapsed_ms(expand_start);
+        Ok(layout)
+    }
+}
+
+// only one pane of a tab can get the focus, the error points at the second pane claiming it and
+// labels the others
+fn assert_one_focus_span(focus_spans: &[(usize, usize)]) -> Result<(), ConfigError> {
+    match focus_spans {
+        [first, second, others @ ..] => {
+            let mut error = ConfigError::new_layout_kdl_error(
+                format!(
+                    "Only one pane of a tab can be focused, found {} panes with focus=true",
+                    focus_spans.len()
+                ),
+                second.0,
+                second.1,
+            )
+            .with_secondary_label(
+                "first pane with focus=true".into(),
+                first.0,
+                first.1,
+            );
+            for other in others {
+                error = error.with_secondary_label(
+                    "another pane with focus=true".into(),
+                    other.0,
+                    other.1,
+                );
+            }
+            Err(error)
+        },
+        _ => Ok(()),
+    }
+}
+
+/// How many nodes the document has, at any depth
+fn kdl_node_count(kdl_document: &KdlDocument) -> usize {
+    let mut node_count = 0;
+    let mut to_count = vec![kdl_document];
+    while let Some(document) = to_count.pop() {
+        node_count += document.nodes().len();
+        to_count.extend(document.nodes().iter().filter_map(|node| node.children()));
+    }
+    node_count
+}
+
+/// Renames the deprecated spellings (see [`DEPRECATED_NAMES`]) in the document to their
+/// replacements, returning what was renamed along with the offset and length of where it was
+///
+/// [`DEPRECATED_NAMES`]: crate::input::layout_capabilities::DEPRECATED_NAMES
+pub fn migrate_deprecated_names(
+    kdl_document: &mut KdlDocument,
+) -> Vec<(&'static DeprecatedName, usize, usize)> {
+    let mut migrated = vec![];
+    // a user template can have the name of a deprecated attribute, it is used as a node
+    let mut template_names = HashSet::new();
+    let mut to_visit = vec![&*kdl_document];
+    while let Some(document) = to_visit.pop() {
+        for node in document.nodes() {
+            let is_template =
+                kdl_name!(node) == "pane_template" || kdl_name!(node) == "tab_template";
+            if let Some(name) = kdl_get_string_property_or_child_value!(node, "name") {
+                if is_template {
+                    template_names.insert(name.to_owned());
+                }
+            }
+            to_visit.extend(node.children());
+        }
+    }
+    for node in kdl_document.nodes_mut() {
+        if kdl_name!(node) != "layout" {
+            continue;
         }
+        if let Some(children) = node.children_mut() {
+            for child in children.nodes_mut() {
+                if let Some(deprecated) =
+                    deprecated_name(DeprecatedNameKind::LayoutNode, kdl_name!(child))
+                {
+                    migrated.push((deprecated, child.span().offset(), child.span().len()));
+                    child.set_name(deprecated.new);
+                }
+            }
+        }
+    }
+    let mut to_migrate: Vec<&mut KdlNode> = kdl_document.nodes_mut().iter_mut().collect();
+    while let Some(node) = to_migrate.pop() {
+        if kdl_name!(node) == "plugin" {
+            // plugins are configured with their own attributes
+            continue;
+        }
+        for entry in node.entries_mut().iter_mut() {
+            let deprecated = entry
+                .name()
+                .and_then(|name| deprecated_name(DeprecatedNameKind::Attribute, name.value()));
+            if let Some(deprecated) = deprecated {
+                migrated.push((deprecated, entry.span().offset(), entry.span().len()));
+                *entry = renamed_kdl_property(entry, deprecated.new);
+            }
+        }
+        if let Some(children) = node.children_mut() {
+            for child in children.nodes_mut().iter_mut() {
+                let deprecated = deprecated_name(DeprecatedNameKind::Attribute, kdl_name!(child))
+                    .filter(|_| !template_names.contains(kdl_name!(child)));
+                if let Some(deprecated) = deprecated {
+                    migrated.push((deprecated, child.span().offset(), child.span().len()));
+                    child.set_name(deprecated.new);
+                }
+                to_migrate.push(child);
+            }
+        }
+    }
+    migrated.sort_by_key(|(_deprecated, offset, _len)| *offset);
+    migrated
+}
+
+fn renamed_kdl_property(entry: &KdlEntry, new_name: &str) -> KdlEntry {
+    let mut renamed = KdlEntry::new_prop(new_name, entry.value().clone());
+    if let Some(leading) = entry.leading() {
+        renamed.set_leading(leading);
+    }
+    if let Some(value_repr) = entry.value_repr() {
+        renamed.set_value_repr(value_repr);
     }
+    renamed.set_span(*entry.span());
+    renamed
 }
@@ -0,0 +1,756 @@
+//! Writes layouts back into the text of a layout file, see [`Layout::to_kdl_string`]
+use crate::input::command::{PaneEncoding, ReadyCondition, RunCommand};
+use crate::input::layout::{
+    ExtraPanesPolicy, FitToContent, FloatingPaneAnchor, FloatingPaneLayout, FocusPolicy, Layout,
+    LayoutConstraint, MissingSlotsPolicy, PaneConditions, PercentOrFixed, Run, SessionConstraint,
+    SplitDirection, SplitSize, SwapFloatingLayout, SwapTiledLayout, TabPin, TiledPaneLayout,
+};
+use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
+use std::collections::BTreeMap;
+use std::path::Path;
+use url::Url;
+
+impl Layout {
+    /// The layout as the text of a layout file, which parses back into this layout.
+    ///
+    /// Tabs are written inside the `default_tab_template` they were created from when they can
+    /// be told apart from it, otherwise they are written out whole and new tabs open with a single
+    /// pane. What cannot be written in a layout file is left out: the cwd containers inherit
+    /// (their panes keep it), the line a file is edited at and stacks that were not declared
+    /// with a `children stacked=true` block.
+    pub fn to_kdl_string(&self) -> String {
+        let mut layout_node = KdlNode::new("layout");
+        let layout_children = layout_node.ensure_children().nodes_mut();
+        if let Some(command_spawn_interval_ms) = self.command_spawn_interval_ms {
+            layout_children.push(kdl_value_node(
+                "command_spawn_interval_ms",
+                command_spawn_interval_ms as i64,
+            ));
+        }
+        if let Some(default_focus) = self.default_focus {
+            layout_children.push(kdl_value_node(
+                "default_focus",
+                focus_policy_name(default_focus),
+            ));
+        }
+        if let Some(command_wrapper) = &self.command_wrapper {
+            let mut command_wrapper_node =
+                kdl_value_node("command_wrapper", path_string(&command_wrapper.command));
+            push_args(&mut command_wrapper_node, &command_wrapper.args);
+            layout_children.push(command_wrapper_node);
+        }
+        // the tab focused by name is focused by name again, unless it was renamed since
+        let focused_tab_name = self.focused_tab_name.as_ref().filter(|focused_tab_name| {
+            self.focused_tab_index
+                .and_then(|index| self.tabs.get(index))
+                .and_then(|(tab_name, ..)| tab_name.as_ref())
+                == Some(*focused_tab_name)
+        });
+        if let Some(focused_tab_name) = focused_tab_name {
+            layout_children.push(kdl_value_node("focused_tab", focused_tab_name.as_str()));
+        }
+        if let Some(session_name) = &self.session_name {
+            layout_children.push(kdl_value_node("session_name", session_name.as_str()));
+        }
+        if let Some(attach_to_existing) = self.attach_to_existing {
+            layout_children.push(kdl_value_node("attach_to_existing", attach_to_existing));
+        }
+        if let Some(side_panel) = &self.side_panel {
+            let mut side_panel_node = KdlNode::new("side_panel");
+            push_prop(
+                &mut side_panel_node,
+                "width",
+                percent_or_fixed(&side_panel.width),
+            );
+            side_panel_node
+                .ensure_children()
+                .nodes_mut()
+                .push(side_panel.pane.to_kdl());
+            layout_children.push(side_panel_node);
+        }
+        let (template, template_floating_panes) = self.template.clone().unwrap_or_default();
+        let slot = if self.tabs.is_empty() {
+            // the layout is its own (only) tab, and swap layouts are not created from a template
+            for pane in &template.children {
+                layout_children.push(pane.to_kdl());
+            }
+            if !template_floating_panes.is_empty() {
+                layout_children.push(floating_panes_node(&template_floating_panes));
+            }
+            vec![]
+        } else {
+            let tab_layouts = self
+                .tabs
+                .iter()
+                .map(|(_name, tiled_panes, ..)| tiled_panes)
+                .chain(self.swap_tiled_layout_entries());
+            let slot = tab_template_slot(&template, tab_layouts).unwrap_or_default();
+            if !slot.is_empty() {
+                layout_children.push(default_tab_template_node(&template, &slot));
+            }
+            for (i, (name, tiled_panes, floating_panes, pin, max_content_width, swap_layouts)) in
+                self.tabs.iter().enumerate()
+            {
+                let mut tab_node = tab_node(&tab_contents(tiled_panes, &template, &slot));
+                if let Some(name) = name {
+                    push_prop(&mut tab_node, "name", name.as_str());
+                }
+                if self.focused_tab_index == Some(i) && focused_tab_name.is_none() {
+                    push_prop(&mut tab_node, "focus", true);
+                }
+                if let Some(pin) = pin {
+                    push_prop(&mut tab_node, "pin", tab_pin_name(*pin));
+                }
+                if let Some(max_content_width) = max_content_width {
+                    push_prop(
+                        &mut tab_node,
+                        "max_content_width",
+                        *max_content_width as i64,
+                    );
+                }
+                push_conditions(&mut tab_node, &tiled_panes.conditions);
+                if !floating_panes.is_empty() {
+                    tab_node
+                        .ensure_children()
+                        .nodes_mut()
+                        .push(floating_panes_node(floating_panes));
+                }
+                let (swap_tiled_layouts, swap_floating_layouts) = swap_layouts;
+                let mut tab_swap_layout_nodes =
+                    swap_layout_nodes(swap_tiled_layouts, swap_floating_layouts, &template, &slot);
+                if !tab_swap_layout_nodes.is_empty() {
+                    tab_node
+                        .ensure_children()
+                        .nodes_mut()
+                        .append(&mut tab_swap_layout_nodes);
+                }
+                layout_children.push(tab_node);
+            }
+            slot
+        };
+        layout_children.append(&mut swap_layout_nodes(
+            &self.swap_tiled_layouts,
+            &self.swap_floating_layouts,
+            &template,
+            &slot,
+        ));
+        if layout_children.is_empty() {
+            *layout_node.children_mut() = None;
+        }
+        let mut document = KdlDocument::new();
+        document.nodes_mut().push(layout_node);
+        document.fmt();
+        document.to_string()
+    }
+    // the entries of the swap tiled layouts of the layout and of its tabs
+    fn swap_tiled_layout_entries(&self) -> impl Iterator<Item = &TiledPaneLayout> + Clone {
+        let tab_swap_tiled_layouts = self
+            .tabs
+            .iter()
+            .flat_map(|(.., (swap_tiled_layouts, _))| swap_tiled_layouts);
+        self.swap_tiled_layouts
+            .iter()
+            .chain(tab_swap_tiled_layouts)
+            .flat_map(|(swap_layouts, _name, _tags)| swap_layouts.values())
+    }
+}
+
+impl TiledPaneLayout {
+    /// The pane as a `pane` node of a layout file
+    pub fn to_kdl(&self) -> KdlNode {
+        let mut pane_node = KdlNode::new("pane");
+        if let Some(name) = &self.name {
+            push_prop(&mut pane_node, "name", name.as_str());
+        }
+        if let Some(split_size) = self.split_size {
+            push_prop(&mut pane_node, "size", split_size_value(split_size));
+        }
+        if let Some(min_size) = self.min_size {
+            push_prop(&mut pane_node, "min_size", split_size_value(min_size));
+        }
+        if let Some(max_size) = self.max_size {
+            push_prop(&mut pane_node, "max_size", split_size_value(max_size));
+        }
+        if let Some(split_direction) = split_direction_value(self.children_split_direction) {
+            push_prop(&mut pane_node, "split_direction", split_direction);
+        }
+        if let Some(stack_below_width) = self.stack_below_width {
+            push_prop(
+                &mut pane_node,
+                "stack_below_width",
+                stack_below_width as i64,
+            );
+        }
+        if let Some(focus) = self.focus {
+            push_prop(&mut pane_node, "focus", focus);
+        }
+        push_flag(&mut pane_node, "expanded", self.is_expanded_in_stack);
+        push_flag(&mut pane_node, "hide_title", self.hide_title);
+        push_flag(&mut pane_node, "read_only", self.read_only);
+        push_flag(&mut pane_node, "no_wrapper", self.no_wrapper);
+        push_flag(&mut pane_node, "exclude_from_dump", self.exclude_from_dump);
+        push_conditions(&mut pane_node, &self.conditions);
+        let has_children = !self.children.is_empty() || self.external_children_index.is_some();
+        if has_children {
+            // panes with children can neither run anything nor be borderless
+            let mut child_nodes: Vec<KdlNode> =
+                self.children.iter().map(|child| child.to_kdl()).collect();
+            if self.external_children_index.is_some() || self.children_are_stacked {
+                let mut children_node = KdlNode::new("children");
+                if self.children_are_stacked {
+                    push_prop(&mut children_node, "stacked", true);
+                }
+                let children_index = self
+                    .external_children_index
+                    .unwrap_or(child_nodes.len())
+                    .min(child_nodes.len());
+                child_nodes.insert(children_index, children_node);
+            }
+            pane_node.ensure_children().nodes_mut().extend(child_nodes);
+        } else {
+            push_flag(&mut pane_node, "borderless", self.borderless);
+            push_run(&mut pane_node, &self.run);
+            if let Some(on_failure) = &self.on_failure {
+                let mut on_failure_node = KdlNode::new("on_failure");
+                on_failure_node
+                    .ensure_children()
+                    .nodes_mut()
+                    .push(on_failure.to_kdl());
+                pane_node
+                    .ensure_children()
+                    .nodes_mut()
+                    .push(on_failure_node);
+            }
+        }
+        pane_node
+    }
+}
+
+impl FloatingPaneLayout {
+    /// The pane as a `pane` node of the `floating_panes` of a layout file
+    pub fn to_kdl(&self) -> KdlNode {
+        let mut pane_node = KdlNode::new("pane");
+        if let Some(name) = &self.name {
+            push_prop(&mut pane_node, "name", name.as_str());
+        }
+        if let Some(x) = &self.x {
+            push_prop(&mut pane_node, "x", percent_or_fixed(x));
+        }
+        if let Some(y) = &self.y {
+            push_prop(&mut pane_node, "y", percent_or_fixed(y));
+        }
+        push_floating_pane_size(&mut pane_node, "width", &self.width, &self.width_fit);
+        push_floating_pane_size(&mut pane_node, "height", &self.height, &self.height_fit);
+        // the max of a "fit" size is written as a bound of the fit
+        if let (Some(max_width), None) = (&self.max_width, &self.width_fit) {
+            push_prop(&mut pane_node, "max_width", percent_or_fixed(max_width));
+        }
+        if let (Some(max_height), None) = (&self.max_height, &self.height_fit) {
+            push_prop(&mut pane_node, "max_height", percent_or_fixed(max_height));
+        }
+        if let Some(focus) = self.focus {
+            push_prop(&mut pane_node, "focus", focus);
+        }
+        if let Some(pinned) = self.pinned {
+            push_prop(&mut pane_node, "pinned", pinned);
+        }
+        if let Some(anchor) = self.anchor {
+            push_prop(&mut pane_node, "anchor", anchor_name(anchor));
+        }
+        push_flag(&mut pane_node, "expanded", self.is_expanded_in_stack);
+        push_flag(&mut pane_node, "hide_title", self.hide_title);
+        push_flag(&mut pane_node, "read_only", self.read_only);
+        push_flag(&mut pane_node, "no_wrapper", self.no_wrapper);
+        push_flag(&mut pane_node, "exclude_from_dump", self.exclude_from_dump);
+        push_conditions(&mut pane_node, &self.conditions);
+        push_run(&mut pane_node, &self.run);
+        pane_node
+    }
+}
+
+// the position of the pane of the `default_tab_template` that the `children` of the tabs went
+// into, the first of the plain panes of the template that all the tabs and swap layouts were
+// created from (when the template is a plain pane itself, this is the root and no template is
+// needed)
+fn tab_template_slot<'a>(
+    template: &TiledPaneLayout,
+    tab_layouts: impl Iterator<Item = &'a TiledPaneLayout> + Clone,
+) -> Option<Vec<usize>> {
+    plain_pane_positions(template).into_iter().find(|slot| {
+        tab_layouts
+            .clone()
+            .all(|tab_layout| without_tab_template(tab_layout, template, slot).is_some())
+    })
+}
+
+fn plain_pane_positions(layout: &TiledPaneLayout) -> Vec<Vec<usize>> {
+    let mut positions = vec![];
+    let mut to_visit = vec![(layout, vec![])];
+    while let Some((pane, position)) = to_visit.pop() {
+        if *pane == TiledPaneLayout::default() {
+            positions.push(position.clone());
+        }
+        // reversed so that the panes are popped (and the positions found) in order
+        for (i, child) in pane.children.iter().enumerate().rev() {
+            let mut child_position = position.clone();
+            child_position.push(i);
+            to_visit.push((child, child_position));
+        }
+    }
+    positions
+}
+
+// what the tab had in the `children` of `template`, if it was created from it
+fn without_tab_template(
+    tab_layout: &TiledPaneLayout,
+    template: &TiledPaneLayout,
+    slot: &[usize],
+) -> Option<TiledPaneLayout> {
+    let mut tab_layout = tab_layout.clone();
+    // the policies of swap layouts are written on their tab node, not in the template
+    tab_layout.extra_panes = None;
+    tab_layout.missing_slots = MissingSlotsPolicy::default();
+    let mut pane = &mut tab_layout;
+    for i in slot {
+        pane = pane.children.get_mut(*i)?;
+    }
+    let tab_contents = std::mem::take(pane);
+    let is_plain_container = tab_contents
+        == TiledPaneLayout {
+            children_split_direction: tab_contents.children_split_direction,
+            children: tab_contents.children.clone(),
+            ..Default::default()
+        };
+    if is_plain_container && tab_layout == *template {
+        Some(tab_contents)
+    } else {
+        None
+    }
+}
+
+fn tab_contents(
+    tab_layout: &TiledPaneLayout,
+    template: &TiledPaneLayout,
+    slot: &[usize],
+) -> TiledPaneLayout {
+    without_tab_template(tab_layout, template, slot).unwrap_or_else(|| TiledPaneLayout {
+        children_split_direction: tab_layout.children_split_direction,
+        children: tab_layout.children.clone(),
+        ..Default::default()
+    })
+}
+
+fn default_tab_template_node(template: &TiledPaneLayout, slot: &[usize]) -> KdlNode {
+    // the template as it was written, with a `children` block where the tabs go
+    let mut template = template.clone();
+    if let Some((children_index, parent_position)) = slot.split_last() {
+        let mut parent = &mut template;
+        for i in parent_position {
+            parent = &mut parent.children[*i];
+        }
+        parent.children.remove(*children_index);
+        parent.external_children_index = Some(*children_index);
+    }
+    tab_node_named("default_tab_template", &template)
+}
+
+// the split_direction property of a pane or tab, None for the default (horizontal) one
+fn split_direction_value(split_direction: SplitDirection) -> Option<&'static str> {
+    match split_direction {
+        SplitDirection::Horizontal => None,
+        SplitDirection::Vertical => Some("vertical"),
+        SplitDirection::Auto => Some("auto"),
+    }
+}
+
+fn tab_node(tab_layout: &TiledPaneLayout) -> KdlNode {
+    tab_node_named("tab", tab_layout)
+}
+
+fn tab_node_named(node_name: &str, tab_layout: &TiledPaneLayout) -> KdlNode {
+    let mut tab_node = KdlNode::new(node_name);
+    if let Some(split_direction) = split_direction_value(tab_layout.children_split_direction) {
+        push_prop(&mut tab_node, "split_direction", split_direction);
+    }
+    let mut child_nodes: Vec<KdlNode> = tab_layout
+        .children
+        .iter()
+        .map(|child| child.to_kdl())
+        .collect();
+    if let Some(children_index) = tab_layout.external_children_index {
+        child_nodes.insert(
+            children_index.min(child_nodes.len()),
+            KdlNode::new("children"),
+        );
+    }
+    if !child_nodes.is_empty() {
+        tab_node.ensure_children().nodes_mut().extend(child_nodes);
+    }
+    tab_node
+}
+
+fn floating_panes_node(floating_panes: &[FloatingPaneLayout]) -> KdlNode {
+    let mut floating_panes_node = KdlNode::new("floating_panes");
+    let mut nodes: Vec<KdlNode> = vec![];
+    // the position among `nodes` of the node of each stack, placed where its first pane was
+    let mut stack_positions: BTreeMap<usize, usize> = BTreeMap::new();
+    for pane in floating_panes {
+        let stack = match pane.stack {
+            Some(stack) => stack,
+            None => {
+                nodes.push(pane.to_kdl());
+                continue;
+            },
+        };
+        let position = *stack_positions.entry(stack).or_insert_with(|| {
+            // the geometry of the stack is written on its node rather than on each of its panes
+            let mut stack_node = KdlNode::new("stack");
+            if let Some(x) = &pane.x {
+                push_prop(&mut stack_node, "x", percent_or_fixed(x));
+            }
+            if let Some(y) = &pane.y {
+                push_prop(&mut stack_node, "y", percent_or_fixed(y));
+            }
+            push_floating_pane_size(&mut stack_node, "width", &pane.width, &None);
+            push_floating_pane_size(&mut stack_node, "height", &pane.height, &None);
+            nodes.push(stack_node);
+            nodes.len() - 1
+        });
+        let stacked_pane = FloatingPaneLayout {
+            x: None,
+            y: None,
+            width: None,
+            height: None,
+            stack: None,
+            ..pane.clone()
+        };
+        nodes[position]
+            .ensure_children()
+            .nodes_mut()
+            .push(stacked_pane.to_kdl());
+    }
+    if !nodes.is_empty() {
+        floating_panes_node
+            .ensure_children()
+            .nodes_mut()
+            .extend(nodes);
+    }
+    floating_panes_node
+}
+
+/// The `swap_tiled_layout` and `swap_floating_layout` nodes of a layout or of one of its tabs
+fn swap_layout_nodes(
+    swap_tiled_layouts: &[SwapTiledLayout],
+    swap_floating_layouts: &[SwapFloatingLayout],
+    template: &TiledPaneLayout,
+    slot: &[usize],
+) -> Vec<KdlNode> {
+    let mut swap_layout_nodes = vec![];
+    for (swap_layouts, name, tags) in swap_tiled_layouts {
+        let mut swap_layout_node = swap_layout_node("swap_tiled_layout", name, tags);
+        for (constraint, tiled_panes) in swap_layouts {
+            let mut tab_node = tab_node(&tab_contents(tiled_panes, template, slot));
+            push_constraint(&mut tab_node, constraint);
+            if let Some(extra_panes) = tiled_panes.extra_panes {
+                push_prop(&mut tab_node, "extra_panes", extra_panes_name(extra_panes));
+            }
+            if tiled_panes.missing_slots != MissingSlotsPolicy::default() {
+                push_prop(
+                    &mut tab_node,
+                    "missing_slots",
+                    missing_slots_name(tiled_panes.missing_slots),
+                );
+            }
+            swap_layout_node
+                .ensure_children()
+                .nodes_mut()
+                .push(tab_node);
+        }
+        swap_layout_nodes.push(swap_layout_node);
+    }
+    for (swap_layouts, name, tags) in swap_floating_layouts {
+        let mut swap_layout_node = swap_layout_node("swap_floating_layout", name, tags);
+        for (constraint, floating_panes) in swap_layouts {
+            let mut floating_panes_node = floating_panes_node(floating_panes);
+            push_constraint(&mut floating_panes_node, constraint);
+            swap_layout_node
+                .ensure_children()
+                .nodes_mut()
+                .push(floating_panes_node);
+        }
+        swap_layout_nodes.push(swap_layout_node);
+    }
+    swap_layout_nodes
+}
+
+fn swap_layout_node(node_name: &str, name: &Option<String>, tags: &[String]) -> KdlNode {
+    let mut swap_layout_node = KdlNode::new(node_name);
+    if let Some(name) = name {
+        push_prop(&mut swap_layout_node, "name", name.as_str());
+    }
+    // layouts without entries are kept, so that swap layouts are cycled through in the same order
+    let swap_layout_children = swap_layout_node.ensure_children().nodes_mut();
+    if !tags.is_empty() {
+        let mut tags_node = KdlNode::new("tags");
+        for tag in tags {
+            tags_node.push(KdlEntry::new(tag.as_str()));
+        }
+        swap_layout_children.push(tags_node);
+    }
+    swap_layout_node
+}
+
+fn push_run(pane_node: &mut KdlNode, run: &Option<Run>) {
+    match run {
+        Some(Run::Command(run_command)) => push_run_command(pane_node, run_command),
+        Some(Run::EditFile(paths, _line_number, None)) if paths.len() == 1 => {
+            push_prop(pane_node, "edit", path_string(&paths[0]));
+        },
+        Some(Run::EditFile(paths, _line_number, editor_splits)) => {
+            let mut edit_node = KdlNode::new("edit");
+            for path in paths {
+                edit_node.push(KdlEntry::new(path_string(path)));
+            }
+            match editor_splits {
+                Some(SplitDirection::Vertical) => {
+                    push_prop(&mut edit_node, "editor_splits", "vertical")
+                },
+                Some(SplitDirection::Horizontal) => {
+                    push_prop(&mut edit_node, "editor_splits", "horizontal")
+                },
+                Some(SplitDirection::Auto) | None => {},
+            }
+            pane_node.ensure_children().nodes_mut().push(edit_node);
+        },
+        Some(Run::Cwd(cwd)) => push_prop(pane_node, "cwd", path_string(cwd)),
+        Some(Run::Plugin(run_plugin)) => {
+            let mut plugin_node = KdlNode::new("plugin");
+            push_prop(
+                &mut plugin_node,
+                "location",
+                Url::from(&run_plugin.location).to_string(),
+            );
+            push_flag(
+                &mut plugin_node,
+                "_allow_exec_host_cmd",
+                run_plugin._allow_exec_host_cmd,
+            );
+            pane_node.ensure_children().nodes_mut().push(plugin_node);
+        },
+        None => {},
+    }
+}
+
+fn push_run_command(pane_node: &mut KdlNode, run_command: &RunCommand) {
+    push_prop(pane_node, "command", path_string(&run_command.command));
+    if let Some(cwd) = &run_command.cwd {
+        push_prop(pane_node, "cwd", path_string(cwd));
+    }
+    push_flag(pane_node, "close_on_exit", !run_command.hold_on_close);
+    push_flag(pane_node, "start_suspended", run_command.hold_on_start);
+    push_flag(
+        pane_node,
+        "strict_placeholders",
+        run_command.strict_placeholders,
+    );
+    if let Some(depends_on) = &run_command.depends_on {
+        push_prop(pane_node, "depends_on", depends_on.pane_name.as_str());
+        match &depends_on.ready_when {
+            Some(ReadyCondition::Contains(text)) => {
+                push_prop(pane_node, "ready_when", text.as_str())
+            },
+            Some(ReadyCondition::Matches(regex)) => {
+                push_prop(pane_node, "ready_regex", regex.as_str())
+            },
+            None => {},
+        }
+        if let Some(timeout_secs) = depends_on.timeout_secs {
+            push_prop(pane_node, "ready_timeout", timeout_secs as i64);
+        }
+    }
+    if let Some(encoding) = run_command.encoding {
+        push_prop(pane_node, "encoding", pane_encoding_name(encoding));
+    }
+    push_args(pane_node, &run_command.args);
+    push_env(pane_node, &run_command.env);
+}
+
+fn push_env(node: &mut KdlNode, env: &BTreeMap<String, String>) {
+    if env.is_empty() {
+        return;
+    }
+    let mut env_node = KdlNode::new("env");
+    for (name, value) in env {
+        let mut variable_node = KdlNode::new(name.as_str());
+        variable_node.push(KdlEntry::new(escape_env_vars(value)));
+        env_node.ensure_children().nodes_mut().push(variable_node);
+    }
+    node.ensure_children().nodes_mut().push(env_node);
+}
+
+fn push_args(node: &mut KdlNode, args: &[String]) {
+    if args.is_empty() {
+        return;
+    }
+    let mut args_node = KdlNode::new("args");
+    for arg in args {
+        args_node.push(KdlEntry::new(escape_env_vars(arg)));
+    }
+    node.ensure_children().nodes_mut().push(args_node);
+}
+
+fn push_floating_pane_size(
+    pane_node: &mut KdlNode,
+    size_name: &str,
+    size: &Option<PercentOrFixed>,
+    fit: &Option<FitToContent>,
+) {
+    match (fit, size) {
+        (Some(fit), _) => {
+            push_prop(pane_node, size_name, "fit");
+            if let Some(min) = fit.min {
+                push_prop(pane_node, &format!("min_{}", size_name), min as i64);
+            }
+            if let Some(max) = fit.max {
+                push_prop(pane_node, &format!("max_{}", size_name), max as i64);
+            }
+        },
+        (None, Some(size)) => push_prop(pane_node, size_name, percent_or_fixed(size)),
+        (None, None) => {},
+    }
+}
+
+fn push_constraint(node: &mut KdlNode, constraint: &LayoutConstraint) {
+    match constraint {
+        LayoutConstraint::InSession(session_constraint, pane_constraint) => {
+            match session_constraint {
+                SessionConstraint::MaxTabs(max_tabs) => {
+                    push_prop(node, "session_tabs_max", *max_tabs as i64)
+                },
+                SessionConstraint::MinTabs(min_tabs) => {
+                    push_prop(node, "session_tabs_min", *min_tabs as i64)
+                },
+            }
+            push_constraint(node, pane_constraint);
+        },
+        LayoutConstraint::ExactPanes(exact_panes) => {
+            push_prop(node, "exact_panes", *exact_panes as i64)
+        },
+        LayoutConstraint::PaneRange { min, max } => {
+            push_prop(node, "min_panes", *min as i64);
+            push_prop(node, "max_panes", *max as i64);
+        },
+        LayoutConstraint::MaxPanes(max_panes) => push_prop(node, "max_panes", *max_panes as i64),
+        LayoutConstraint::MinPanes(min_panes) => push_prop(node, "min_panes", *min_panes as i64),
+        LayoutConstraint::MaxGroups(max_groups) => {
+            push_prop(node, "max_groups", *max_groups as i64)
+        },
+        LayoutConstraint::MinGroups(min_groups) => {
+            push_prop(node, "min_groups", *min_groups as i64)
+        },
+        LayoutConstraint::NoConstraint => {},
+    }
+}
+
+fn push_prop(node: &mut KdlNode, name: &str, value: impl Into<KdlValue>) {
+    node.push(KdlEntry::new_prop(name, value));
+}
+
+// boolean properties are only written when they are set, since they default to false
+fn push_flag(node: &mut KdlNode, name: &str, value: bool) {
+    if value {
+        push_prop(node, name, true);
+    }
+}
+
+fn push_conditions(node: &mut KdlNode, conditions: &PaneConditions) {
+    if let Some(variable) = &conditions.if_env {
+        push_prop(node, "if_env", variable.as_str());
+    }
+    if let Some(command) = &conditions.if_command_exists {
+        push_prop(node, "if_command_exists", command.as_str());
+    }
+}
+
+fn kdl_value_node(name: &str, value: impl Into<KdlValue>) -> KdlNode {
+    let mut node = KdlNode::new(name);
+    node.push(KdlEntry::new(value));
+    node
+}
+
+fn path_string(path: &Path) -> String {
+    escape_env_vars(&path.to_string_lossy())
+}
+
+// the environment variables in commands, args, cwds and edited paths are expanded when the layout
+// is parsed, so the dollars left are literal ones
+fn escape_env_vars(text: &str) -> String {
+    text.replace('$', "$$")
+}
+
+// fixed sizes are written as numbers and the others as strings, as in hand written layouts
+fn split_size_value(split_size: SplitSize) -> KdlValue {
+    match split_size {
+        SplitSize::Fixed(fixed) => KdlValue::Base10(fixed as i64),
+        SplitSize::Percent(percent) => KdlValue::String(format!("{}%", percent)),
+        SplitSize::Weight(weight) => KdlValue::String(format!("{}w", weight)),
+    }
+}
+
+fn percent_or_fixed(percent_or_fixed: &PercentOrFixed) -> KdlValue {
+    match percent_or_fixed {
+        PercentOrFixed::Fixed(fixed) => KdlValue::Base10(*fixed as i64),
+        PercentOrFixed::Percent(percent) => KdlValue::String(format!("{}%", percent)),
+        PercentOrFixed::FixedFromFarEdge(fixed) => KdlValue::String(format!("-{}", fixed)),
+        PercentOrFixed::PercentFromFarEdge(percent) => KdlValue::String(format!("-{}%", percent)),
+    }
+}
+
+fn focus_policy_name(focus_policy: FocusPolicy) -> &'static str {
+    match focus_policy {
+        FocusPolicy::First => "first",
+        FocusPolicy::Deepest => "deepest",
+        FocusPolicy::Largest => "largest",
+        FocusPolicy::Last => "last",
+    }
+}
+
+fn tab_pin_name(tab_pin: TabPin) -> &'static str {
+    match tab_pin {
+        TabPin::First => "first",
+        TabPin::Last => "last",
+    }
+}
+
+fn anchor_name(anchor: FloatingPaneAnchor) -> &'static str {
+    match anchor {
+        FloatingPaneAnchor::TopLeft => "top_left",
+        FloatingPaneAnchor::TopRight => "top_right",
+        FloatingPaneAnchor::BottomLeft => "bottom_left",
+        FloatingPaneAnchor::BottomRight => "bottom_right",
+        FloatingPaneAnchor::Center => "center",
+    }
+}
+
+fn extra_panes_name(extra_panes: ExtraPanesPolicy) -> &'static str {
+    match extra_panes {
+        ExtraPanesPolicy::StackLast => "stack_last",
+        ExtraPanesPolicy::AppendAfterFocused => "append_after_focused",
+        ExtraPanesPolicy::NewStack => "new_stack",
+    }
+}
+
+fn missing_slots_name(missing_slots: MissingSlotsPolicy) -> &'static str {
+    match missing_slots {
+        MissingSlotsPolicy::Collapse => "collapse",
+        MissingSlotsPolicy::KeepEmpty => "keep_empty",
+    }
+}
+
+fn pane_encoding_name(encoding: PaneEncoding) -> &'static str {
+    match encoding {
+        PaneEncoding::Utf8 => "utf8",
+        PaneEncoding::Latin1 => "latin1",
+        PaneEncoding::ShiftJis => "shift-jis",
+    }
+}
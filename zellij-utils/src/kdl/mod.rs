@@ -1,14 +1,25 @@
+mod kdl_layout_includes;
 mod kdl_layout_parser;
+mod kdl_layout_serializer;
 use crate::data::{Direction, InputMode, Key, Palette, PaletteColor, Resize};
 use crate::envs::EnvironmentVariables;
 use crate::input::config::{Config, ConfigError, KdlError};
 use crate::input::keybinds::Keybinds;
-use crate::input::layout::{Layout, RunPlugin, RunPluginLocation};
-use crate::input::options::{Clipboard, OnForceClose, Options};
+use crate::input::layout::{
+    normalize_layout_source, rebased_path, FocusPolicy, Layout, LayoutDiagnostic, RunPlugin,
+    RunPluginLocation, SplitSize, ValidationOptions,
+};
+use crate::input::layout_stats::LayoutLoadStats;
+use crate::input::layout_variables::LayoutVariables;
+use crate::input::options::{
+    Clipboard, ConfirmLayoutCommands, OnForceClose, OnLayoutFailure, Options, PaneTitleFormats,
+};
 use crate::input::plugins::{PluginConfig, PluginTag, PluginType, PluginsConfig};
 use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
+pub use kdl_layout_includes::MAX_INCLUDE_DEPTH;
 use kdl_layout_parser::KdlLayoutParser;
-use std::collections::HashMap;
+pub use kdl_layout_parser::{migrate_deprecated_names, LayoutTemplates};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Read;
 use strum::IntoEnumIterator;
@@ -16,9 +27,9 @@ use url::Url;
 
 use miette::NamedSource;
 
-use kdl::{KdlDocument, KdlEntry, KdlNode};
+use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use crate::input::actions::{Action, SearchDirection, SearchOption};
@@ -49,6 +60,8 @@ macro_rules! parse_kdl_action_arguments {
                 "HalfPageScrollUp" => Ok(Action::HalfPageScrollUp),
                 "HalfPageScrollDown" => Ok(Action::HalfPageScrollDown),
                 "ToggleFocusFullscreen" => Ok(Action::ToggleFocusFullscreen),
+                "TogglePaneReadOnly" => Ok(Action::TogglePaneReadOnly),
+                "FlushCommandQueue" => Ok(Action::FlushCommandQueue),
                 "TogglePaneFrames" => Ok(Action::TogglePaneFrames),
                 "ToggleActiveSyncTab" => Ok(Action::ToggleActiveSyncTab),
                 "TogglePaneEmbedOrFloating" => Ok(Action::TogglePaneEmbedOrFloating),
@@ -66,8 +79,6 @@ macro_rules! parse_kdl_action_arguments {
                 "Confirm" => Ok(Action::Confirm),
                 "Deny" => Ok(Action::Deny),
                 "ToggleMouseMode" => Ok(Action::ToggleMouseMode),
-                "PreviousSwapLayout" => Ok(Action::PreviousSwapLayout),
-                "NextSwapLayout" => Ok(Action::NextSwapLayout),
                 _ => Err(ConfigError::new_kdl_error(
                     format!("Unsupported action: {:?}", $action_name),
                     $action_node.span().offset(),
@@ -456,6 +467,28 @@ impl Action {
             },
             "MovePaneBackwards" => Ok(Action::MovePaneBackwards),
             "DumpScreen" => Ok(Action::DumpScreen(string, false)),
+            "PreviousSwapLayout" => {
+                let tag = Some(string).filter(|tag| !tag.is_empty());
+                Ok(Action::PreviousSwapLayout(tag))
+            },
+            "NextSwapLayout" => {
+                let tag = Some(string).filter(|tag| !tag.is_empty());
+                Ok(Action::NextSwapLayout(tag))
+            },
+            "RotateTabLayout" => match string.to_ascii_lowercase().as_str() {
+                "" | "clockwise" => Ok(Action::RotateTabLayout { clockwise: true }),
+                "counterclockwise" | "counter-clockwise" => {
+                    Ok(Action::RotateTabLayout { clockwise: false })
+                },
+                _ => Err(ConfigError::new_kdl_error(
+                    format!(
+                        "Invalid rotation: '{}', expected clockwise or counterclockwise",
+                        string
+                    ),
+                    action_node.span().offset(),
+                    action_node.span().len(),
+                )),
+            },
             "NewPane" => {
                 if string.is_empty() {
                     return Ok(Action::NewPane(None, None));
@@ -658,6 +691,12 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
             "ToggleFocusFullscreen" => {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
+            "TogglePaneReadOnly" => {
+                parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
+            },
+            "FlushCommandQueue" => {
+                parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
+            },
             "TogglePaneFrames" => {
                 parse_kdl_action_arguments!(action_name, action_arguments, kdl_action)
             },
@@ -753,7 +792,39 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
             "NewTab" => {
                 let command_metadata = action_children.iter().next();
                 if command_metadata.is_none() {
-                    return Ok(Action::NewTab(None, vec![], None, None, None));
+                    return Ok(Action::NewTab(None, vec![], None, None, None, None));
+                }
+
+                let name = command_metadata
+                    .and_then(|c_m| kdl_child_string_value_for_entry(c_m, "name"))
+                    .map(|name_string| name_string.to_string());
+                let template = command_metadata
+                    .and_then(|c_m| kdl_child_string_value_for_entry(c_m, "template"))
+                    .map(|template_name| template_name.to_string());
+                if let Some(template) = template {
+                    // the template is one of the session layout, which is only known to the server
+                    let has_layout_or_cwd = command_metadata
+                        .map(|c_m| {
+                            kdl_child_string_value_for_entry(c_m, "layout").is_some()
+                                || kdl_child_string_value_for_entry(c_m, "cwd").is_some()
+                        })
+                        .unwrap_or(false);
+                    if has_layout_or_cwd {
+                        return Err(ConfigError::new_kdl_error(
+                            "A new tab opened from a template cannot have a layout or a cwd"
+                                .to_string(),
+                            kdl_action.span().offset(),
+                            kdl_action.span().len(),
+                        ));
+                    }
+                    return Ok(Action::NewTab(
+                        None,
+                        vec![],
+                        None,
+                        None,
+                        name,
+                        Some(template),
+                    ));
                 }
 
                 let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
@@ -766,11 +837,8 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                     .and_then(|c_m| kdl_child_string_value_for_entry(c_m, "cwd"))
                     .map(|cwd_string| PathBuf::from(cwd_string))
                     .map(|cwd| current_dir.join(cwd));
-                let name = command_metadata
-                    .and_then(|c_m| kdl_child_string_value_for_entry(c_m, "name"))
-                    .map(|name_string| name_string.to_string());
 
-                let (path_to_raw_layout, raw_layout, swap_layouts) =
+                let (path_to_raw_layout, raw_layout, swap_layouts, _layout_sources) =
                     Layout::stringified_from_path_or_default(layout.as_ref(), None).map_err(
                         |e| {
                             ConfigError::new_kdl_error(
@@ -786,6 +854,7 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                     path_to_raw_layout,
                     swap_layouts.as_ref().map(|(f, p)| (f.as_str(), p.as_str())),
                     cwd,
+                    &LayoutVariables::new(),
                 )
                 .map_err(|e| {
                     ConfigError::new_kdl_error(
@@ -803,7 +872,9 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                         kdl_action.span().len(),
                     ));
                 } else if !tabs.is_empty() {
-                    let (tab_name, layout, floating_panes_layout) = tabs.drain(..).next().unwrap();
+                    // pins only apply to the tabs of the session layout
+                    let (tab_name, layout, floating_panes_layout, ..) =
+                        tabs.drain(..).next().unwrap();
                     let name = tab_name.or(name);
 
                     Ok(Action::NewTab(
@@ -812,6 +883,7 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                         None,
                         None,
                         name,
+                        None,
                     ))
                 } else {
                     let (layout, floating_panes_layout) = layout.new_tab();
@@ -822,6 +894,7 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                         None,
                         None,
                         name,
+                        None,
                     ))
                 }
             },
@@ -837,6 +910,18 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 action_arguments,
                 kdl_action
             ),
+            "RerunPane" => {
+                let arguments = action_arguments.iter().copied();
+                let target_pane = kdl_arguments_that_are_strings(arguments)?
+                    .into_iter()
+                    .next();
+                let force = action_children
+                    .iter()
+                    .next()
+                    .and_then(|c_m| kdl_child_bool_value_for_entry(c_m, "force"))
+                    .unwrap_or(false);
+                Ok(Action::RerunPane(target_pane, force))
+            },
             "Run" => {
                 let arguments = action_arguments.iter().copied();
                 let mut args = kdl_arguments_that_are_strings(arguments)?;
@@ -872,8 +957,21 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
                 };
                 Ok(Action::Run(run_command_action))
             },
-            "PreviousSwapLayout" => Ok(Action::PreviousSwapLayout),
-            "NextSwapLayout" => Ok(Action::NextSwapLayout),
+            "PreviousSwapLayout" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "NextSwapLayout" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
+            "RotateTabLayout" => parse_kdl_action_char_or_string_arguments!(
+                action_name,
+                action_arguments,
+                kdl_action
+            ),
             _ => Err(ConfigError::new_kdl_error(
                 format!("Unsupported action: {}", action_name).into(),
                 kdl_action.span().offset(),
@@ -1302,6 +1400,44 @@ impl Options {
             kdl_property_first_arg_as_bool_or_error!(kdl_options, "pane_frames").map(|(v, _)| v);
         let auto_layout =
             kdl_property_first_arg_as_bool_or_error!(kdl_options, "auto_layout").map(|(v, _)| v);
+        let respect_fixed_sizes =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "respect_fixed_sizes")
+                .map(|(v, _)| v);
+        let layout_hot_reload =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "layout_hot_reload")
+                .map(|(v, _)| v);
+        let pane_title_formats = match kdl_options.get("pane_title_formats") {
+            Some(kdl_pane_title_formats) => {
+                let mut pane_title_formats = PaneTitleFormats::default();
+                for kdl_format in kdl_children_nodes_or_error!(
+                    kdl_pane_title_formats,
+                    "empty pane_title_formats block"
+                ) {
+                    let format = kdl_first_entry_as_string!(kdl_format)
+                        .map(|f| f.to_owned())
+                        .ok_or(ConfigError::new_kdl_error(
+                            format!("Missing format for: {}", kdl_name!(kdl_format)),
+                            kdl_format.span().offset(),
+                            kdl_format.span().len(),
+                        ))?;
+                    match kdl_name!(kdl_format) {
+                        "command" => pane_title_formats.command = Some(format),
+                        "edit" => pane_title_formats.edit = Some(format),
+                        "plugin" => pane_title_formats.plugin = Some(format),
+                        "cwd" => pane_title_formats.cwd = Some(format),
+                        name => {
+                            return Err(ConfigError::new_kdl_error(
+                                format!("Unknown pane title format: {}, expected one of: command, edit, plugin, cwd", name),
+                                kdl_format.span().offset(),
+                                kdl_format.span().len(),
+                            ))
+                        },
+                    }
+                }
+                Some(pane_title_formats)
+            },
+            None => None,
+        };
         let theme = kdl_property_first_arg_as_string_or_error!(kdl_options, "theme")
             .map(|(theme, _entry)| theme.to_string());
         let default_mode =
@@ -1314,6 +1450,26 @@ impl Options {
         let default_layout =
             kdl_property_first_arg_as_string_or_error!(kdl_options, "default_layout")
                 .map(|(string, _entry)| PathBuf::from(string));
+        let default_layouts = match kdl_options.get("default_layouts") {
+            Some(kdl_default_layouts) => {
+                let mut default_layouts = vec![];
+                for kdl_default_layout in
+                    kdl_children_nodes_or_error!(kdl_default_layouts, "empty default_layouts block")
+                {
+                    let pattern = kdl_name!(kdl_default_layout);
+                    let layout = kdl_first_entry_as_string!(kdl_default_layout).ok_or(
+                        ConfigError::new_kdl_error(
+                            format!("Missing layout for session name pattern: {:?}", pattern),
+                            kdl_default_layout.span().offset(),
+                            kdl_default_layout.span().len(),
+                        ),
+                    )?;
+                    default_layouts.push((pattern.to_owned(), PathBuf::from(layout)));
+                }
+                Some(default_layouts)
+            },
+            None => None,
+        };
         let layout_dir = kdl_property_first_arg_as_string_or_error!(kdl_options, "layout_dir")
             .map(|(string, _entry)| PathBuf::from(string));
         let theme_dir = kdl_property_first_arg_as_string_or_error!(kdl_options, "theme_dir")
@@ -1347,12 +1503,109 @@ impl Options {
         let attach_to_session =
             kdl_property_first_arg_as_bool_or_error!(kdl_options, "attach_to_session")
                 .map(|(v, _)| v);
+        let strider_pane_width = match kdl_options
+            .get("strider_pane_width")
+            .and_then(|property| property.entries().iter().next())
+        {
+            Some(entry) => {
+                let width = match (entry.value().as_i64(), entry.value().as_string()) {
+                    (Some(fixed_width), _) if fixed_width > 0 => {
+                        Some(SplitSize::Fixed(fixed_width as usize))
+                    },
+                    (_, Some(width)) => SplitSize::from_str(width).ok(),
+                    _ => None,
+                };
+                Some(width.ok_or_else(|| kdl_parsing_error!(
+                    format!(
+                        "strider_pane_width should be a fixed number (eg. 30) or a quoted percent (eg. \"20%\"), found: {}",
+                        entry.value()
+                    ),
+                    entry
+                ))?)
+            },
+            None => None,
+        };
+        let strider_stack_below_width =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "strider_stack_below_width")
+                .map(|(width, _entry)| width as usize);
+        let command_spawn_interval_ms =
+            match kdl_property_first_arg_as_i64_or_error!(kdl_options, "command_spawn_interval_ms")
+            {
+                Some((interval, _entry)) if interval >= 0 => Some(interval as u64),
+                Some((_interval, entry)) => {
+                    return Err(kdl_parsing_error!(
+                        format!("command_spawn_interval_ms cannot be negative"),
+                        entry
+                    ));
+                },
+                None => None,
+            };
+        let default_focus =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "default_focus") {
+                Some((string, entry)) => Some(FocusPolicy::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for default_focus: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let layout_max_depth =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "layout_max_depth")
+                .map(|(depth, _entry)| depth as usize);
+        let layout_max_nodes =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "layout_max_nodes")
+                .map(|(nodes, _entry)| nodes as usize);
+        let layout_max_file_size =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "layout_max_file_size")
+                .map(|(size, _entry)| size as usize);
+        let max_layout_commands =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "max_layout_commands")
+                .map(|(max_commands, _entry)| max_commands as usize);
+        let on_layout_failure =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "on_layout_failure") {
+                Some((string, entry)) => Some(OnLayoutFailure::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for on_layout_failure: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let confirm_layout_commands = match kdl_property_first_arg_as_string_or_error!(
+            kdl_options,
+            "confirm_layout_commands"
+        ) {
+            Some((string, entry)) => {
+                Some(ConfirmLayoutCommands::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for confirm_layout_commands: '{}'", string),
+                        entry
+                    )
+                })?)
+            },
+            None => None,
+        };
+        let resize_relayout_interval_ms = match kdl_property_first_arg_as_i64_or_error!(
+            kdl_options,
+            "resize_relayout_interval_ms"
+        ) {
+            Some((interval, _entry)) if interval >= 0 => Some(interval as u64),
+            Some((_interval, entry)) => {
+                return Err(kdl_parsing_error!(
+                    format!("resize_relayout_interval_ms cannot be negative"),
+                    entry
+                ));
+            },
+            None => None,
+        };
         Ok(Options {
             simplified_ui,
             theme,
             default_mode,
             default_shell,
             default_layout,
+            default_layouts,
             layout_dir,
             theme_dir,
             mouse_mode,
@@ -1367,6 +1620,20 @@ impl Options {
             session_name,
             attach_to_session,
             auto_layout,
+            respect_fixed_sizes,
+            layout_hot_reload,
+            pane_title_formats,
+            strider_pane_width,
+            strider_stack_below_width,
+            command_spawn_interval_ms,
+            default_focus,
+            on_layout_failure,
+            layout_max_depth,
+            layout_max_nodes,
+            layout_max_file_size,
+            max_layout_commands,
+            confirm_layout_commands,
+            resize_relayout_interval_ms,
         })
     }
 }
@@ -1403,17 +1670,91 @@ impl Layout {
         raw_swap_layouts: Option<(&str, &str)>, // raw_swap_layouts swap_layouts_file_name
         cwd: Option<PathBuf>,
     ) -> Result<Self, ConfigError> {
-        let mut kdl_layout_parser = KdlLayoutParser::new(raw_layout, cwd);
-        let layout = kdl_layout_parser.parse().map_err(|e| match e {
-            ConfigError::KdlError(kdl_error) => {
-                ConfigError::KdlError(kdl_error.add_src(file_name, String::from(raw_layout)))
+        Layout::from_kdl_with_templates(
+            raw_layout,
+            file_name,
+            raw_swap_layouts,
+            cwd,
+            &LayoutTemplates::default(),
+        )
+    }
+    /// Parses the layout with the templates defined in the config available to it
+    pub fn from_kdl_with_templates(
+        raw_layout: &str,
+        file_name: String,
+        raw_swap_layouts: Option<(&str, &str)>, // raw_swap_layouts swap_layouts_file_name
+        cwd: Option<PathBuf>,
+        layout_templates: &LayoutTemplates,
+    ) -> Result<Self, ConfigError> {
+        let (layout, warnings) = Layout::from_kdl_with_validation(
+            raw_layout,
+            file_name,
+            raw_swap_layouts,
+            cwd,
+            ValidationOptions::strict(),
+            layout_templates,
+        )?;
+        for warning in warnings {
+            log::warn!("{}", warning);
+        }
+        Ok(layout)
+    }
+    /// Parses the layout and validates it with the given options, returning the first error as a
+    /// `ConfigError` and the rest of the diagnostics alongside the layout
+    pub fn from_kdl_with_validation(
+        raw_layout: &str,
+        file_name: String,
+        raw_swap_layouts: Option<(&str, &str)>, // raw_swap_layouts swap_layouts_file_name
+        cwd: Option<PathBuf>,
+        validation_options: ValidationOptions,
+        layout_templates: &LayoutTemplates,
+    ) -> Result<(Self, Vec<LayoutDiagnostic>), ConfigError> {
+        Layout::from_kdl_with_stats(
+            raw_layout,
+            file_name,
+            raw_swap_layouts,
+            cwd,
+            validation_options,
+            layout_templates,
+        )
+        .map(|(layout, diagnostics, _load_stats)| (layout, diagnostics))
+    }
+    /// Like [`Layout::from_kdl_with_validation`], also returning how long each stage of loading
+    /// the layout took
+    pub fn from_kdl_with_stats(
+        raw_layout: &str,
+        file_name: String,
+        raw_swap_layouts: Option<(&str, &str)>, // raw_swap_layouts swap_layouts_file_name
+        cwd: Option<PathBuf>,
+        validation_options: ValidationOptions,
+        layout_templates: &LayoutTemplates,
+    ) -> Result<(Self, Vec<LayoutDiagnostic>, LayoutLoadStats), ConfigError> {
+        validation_options.limits.check_source(raw_layout)?;
+        if let Some((_raw_swap_layout_filename, raw_swap_layout)) = raw_swap_layouts {
+            validation_options.limits.check_source(raw_swap_layout)?;
+        }
+        // errors point at the normalized source, so that is also what they are reported with
+        let (raw_layout, had_byte_order_mark) = normalize_layout_source(raw_layout);
+        let raw_layout = raw_layout.as_str();
+        let raw_swap_layout = raw_swap_layouts
+            .map(|(_filename, raw_swap_layout)| normalize_layout_source(raw_swap_layout).0);
+        let raw_swap_layouts = raw_swap_layouts.zip(raw_swap_layout.as_deref()).map(
+            |((raw_swap_layout_filename, _), raw_swap_layout)| {
+                (raw_swap_layout_filename, raw_swap_layout)
             },
+        );
+        let mut kdl_layout_parser = KdlLayoutParser::new(raw_layout, cwd, validation_options)
+            .with_templates(layout_templates);
+        let layout = kdl_layout_parser.parse().map_err(|e| match e {
+            ConfigError::KdlError(kdl_error) => ConfigError::KdlError(
+                kdl_error.add_src(file_name.clone(), String::from(raw_layout)),
+            ),
             ConfigError::KdlDeserializationError(kdl_error) => {
-                kdl_layout_error(kdl_error, file_name, raw_layout)
+                kdl_layout_error(kdl_error, file_name.clone(), raw_layout)
             },
             e => e,
         })?;
-        match raw_swap_layouts {
+        let mut layout = match raw_swap_layouts {
             Some((raw_swap_layout_filename, raw_swap_layout)) => {
                 // here we use the same parser to parse the swap layout so that we can reuse assets
                 // (eg. pane and tab templates)
@@ -1432,13 +1773,286 @@ impl Layout {
                             raw_swap_layout,
                         ),
                         e => e,
-                    })
+                    })?
             },
-            None => Ok(layout),
+            None => layout,
+        };
+        // the panes are dropped before anything looks at the layout, so that it is as if they
+        // were never in it
+        let condition_outcomes = layout.apply_env_conditions();
+        let mut diagnostics = kdl_layout_parser.warnings();
+        if had_byte_order_mark {
+            diagnostics.insert(
+                0,
+                LayoutDiagnostic::warning(
+                    "byte-order-mark",
+                    "The layout starts with a UTF-8 byte order mark (BOM), it was ignored".into(),
+                    None,
+                ),
+            );
+        }
+        if !validation_options.strict {
+            diagnostics.extend(condition_outcomes.iter().map(|outcome| {
+                outcome
+                    .to_diagnostic()
+                    .with_spans(kdl_layout_parser.spans())
+            }));
+        }
+        diagnostics.extend(
+            layout
+                .validate(validation_options)
+                .into_iter()
+                .map(|d| d.with_spans(kdl_layout_parser.spans())),
+        );
+        if let Some(error) = diagnostics.iter().find(|d| d.is_error()) {
+            return Err(layout_diagnostic_error(
+                error,
+                file_name,
+                raw_layout,
+                raw_swap_layouts,
+            ));
+        }
+        let mut load_stats = kdl_layout_parser.load_stats();
+        let (geometry_ms, pane_count) = layout.time_geometry();
+        load_stats.geometry_ms = geometry_ms;
+        load_stats.pane_count = pane_count;
+        Ok((layout, diagnostics, load_stats))
+    }
+}
+
+/// Formats a layout, rewriting the deprecated spellings it uses (see
+/// [`DEPRECATED_NAMES`](crate::input::layout_capabilities::DEPRECATED_NAMES)) on the way
+pub fn format_layout(raw_layout: &str) -> Result<String, ConfigError> {
+    let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+    let mut kdl_layout: KdlDocument = raw_layout.parse()?;
+    migrate_deprecated_names(&mut kdl_layout);
+    kdl_layout.fmt();
+    Ok(kdl_layout.to_string())
+}
+
+/// Replaces what panes marked with `hide_title` run (their command and args, the file they edit or
+/// the plugin they load) with a placeholder, so that layouts can be shared without leaking the
+/// secrets these panes were hiding
+pub fn redact_hidden_title_commands(raw_layout: &str) -> Result<String, ConfigError> {
+    let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+    let mut kdl_layout: KdlDocument = raw_layout.parse()?;
+    // panes can also get hide_title from the pane_template they are created from
+    let mut templates_hiding_titles = HashSet::new();
+    collect_templates_hiding_titles(kdl_layout.nodes(), &mut templates_hiding_titles);
+    redact_hidden_title_nodes(kdl_layout.nodes_mut(), &templates_hiding_titles);
+    Ok(kdl_layout.to_string())
+}
+
+fn collect_templates_hiding_titles(
+    nodes: &[KdlNode],
+    templates_hiding_titles: &mut HashSet<String>,
+) {
+    for node in nodes {
+        if node.name().value() == "pane_template"
+            && kdl_get_bool_property_or_child_value!(node, "hide_title").unwrap_or(false)
+        {
+            if let Some(template_name) = kdl_get_string_property_or_child_value!(node, "name") {
+                templates_hiding_titles.insert(template_name.to_owned());
+            }
+        }
+        if let Some(children) = node.children() {
+            collect_templates_hiding_titles(children.nodes(), templates_hiding_titles);
+        }
+    }
+}
+
+fn redact_kdl_entry(entry: &mut KdlEntry) {
+    entry.set_value("<redacted>");
+    // otherwise the original text of the value is what gets printed
+    entry.set_value_repr("\"<redacted>\"");
+}
+
+fn redact_hidden_title_nodes(nodes: &mut [KdlNode], templates_hiding_titles: &HashSet<String>) {
+    let redacted_properties = ["command", "args", "edit", "plugin"];
+    for node in nodes.iter_mut() {
+        let hides_title = kdl_get_bool_property_or_child_value!(node, "hide_title")
+            .unwrap_or_else(|| templates_hiding_titles.contains(node.name().value()));
+        if hides_title {
+            for entry in node.entries_mut().iter_mut() {
+                let is_redacted = entry
+                    .name()
+                    .map(|n| redacted_properties.contains(&n.value()))
+                    .unwrap_or(false);
+                if is_redacted {
+                    redact_kdl_entry(entry);
+                }
+            }
+            if let Some(children) = node.children_mut() {
+                for child in children.nodes_mut().iter_mut() {
+                    if redacted_properties.contains(&child.name().value()) {
+                        for entry in child.entries_mut().iter_mut() {
+                            redact_kdl_entry(entry);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(children) = node.children_mut() {
+            redact_hidden_title_nodes(children.nodes_mut(), templates_hiding_titles);
         }
     }
 }
 
+/// Removes the commands (and their args and options) of panes marked with `exclude_from_dump`,
+/// leaving a `// redacted` comment in their place. The panes keep their name, size and the
+/// attribute itself, so dumping the result again keeps them redacted
+pub fn redact_panes_excluded_from_dump(raw_layout: &str) -> Result<String, ConfigError> {
+    let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+    let mut kdl_layout: KdlDocument = raw_layout.parse()?;
+    let mut excluded_templates = HashSet::new();
+    collect_templates_excluded_from_dump(kdl_layout.nodes(), &mut excluded_templates);
+    redact_nodes_excluded_from_dump(kdl_layout.nodes_mut(), &excluded_templates);
+    Ok(kdl_layout.to_string())
+}
+
+fn collect_templates_excluded_from_dump(
+    nodes: &[KdlNode],
+    excluded_templates: &mut HashSet<String>,
+) {
+    for node in nodes {
+        if node.name().value() == "pane_template"
+            && kdl_get_bool_property_or_child_value!(node, "exclude_from_dump").unwrap_or(false)
+        {
+            if let Some(template_name) = kdl_get_string_property_or_child_value!(node, "name") {
+                excluded_templates.insert(template_name.to_owned());
+            }
+        }
+        if let Some(children) = node.children() {
+            collect_templates_excluded_from_dump(children.nodes(), excluded_templates);
+        }
+    }
+}
+
+fn redact_nodes_excluded_from_dump(nodes: &mut [KdlNode], excluded_templates: &HashSet<String>) {
+    // what is left must still parse, so the options that only make sense with a command go too
+    let redacted_attributes = [
+        "command",
+        "args",
+        "close_on_exit",
+        "start_suspended",
+        "strict_placeholders",
+        "depends_on",
+        "ready_when",
+        "ready_regex",
+        "ready_timeout",
+        "encoding",
+        "env",
+        "on_failure",
+    ];
+    for node in nodes.iter_mut() {
+        let is_excluded = kdl_get_bool_property_or_child_value!(node, "exclude_from_dump")
+            .unwrap_or_else(|| excluded_templates.contains(node.name().value()));
+        if is_excluded {
+            let entry_count = node.entries().len();
+            node.entries_mut().retain(|entry| {
+                entry
+                    .name()
+                    .map(|n| !redacted_attributes.contains(&n.value()))
+                    .unwrap_or(true)
+            });
+            let mut redacted_anything = node.entries().len() != entry_count;
+            if let Some(children) = node.children_mut() {
+                let child_count = children.nodes().len();
+                children
+                    .nodes_mut()
+                    .retain(|child| !redacted_attributes.contains(&child.name().value()));
+                redacted_anything |= children.nodes().len() != child_count;
+            }
+            if redacted_anything {
+                let leading = node.leading().unwrap_or("").to_owned();
+                let indentation = leading.rsplit('\n').next().unwrap_or("").to_owned();
+                node.set_leading(format!("{}// redacted\n{}", leading, indentation));
+            }
+        }
+        if let Some(children) = node.children_mut() {
+            redact_nodes_excluded_from_dump(children.nodes_mut(), excluded_templates);
+        }
+    }
+}
+
+/// Moves the paths of a layout (the `cwd` of its panes, tabs and templates, the files its panes
+/// `edit` and the `file:` locations of their plugins) from under `old_root` to under `new_root`,
+/// see [`rebased_path`]. Everything else, comments included, is kept as it was written
+pub fn rebase_layout_paths(
+    raw_layout: &str,
+    old_root: &Path,
+    new_root: &Path,
+) -> Result<String, ConfigError> {
+    let (raw_layout, _had_byte_order_mark) = normalize_layout_source(raw_layout);
+    let mut kdl_layout: KdlDocument = raw_layout.parse()?;
+    rebase_path_nodes(kdl_layout.nodes_mut(), old_root, new_root);
+    Ok(kdl_layout.to_string())
+}
+
+fn rebase_path_nodes(nodes: &mut [KdlNode], old_root: &Path, new_root: &Path) {
+    for node in nodes.iter_mut() {
+        let node_name = node.name().value().to_owned();
+        for entry in node.entries_mut().iter_mut() {
+            // properties (`pane cwd="..."`) or the arguments of child nodes (`cwd "..."`)
+            let attribute = entry
+                .name()
+                .map(|name| name.value().to_owned())
+                .unwrap_or_else(|| node_name.clone());
+            rebase_path_entry(entry, &attribute, old_root, new_root);
+        }
+        if let Some(children) = node.children_mut() {
+            rebase_path_nodes(children.nodes_mut(), old_root, new_root);
+        }
+    }
+}
+
+fn rebase_path_entry(entry: &mut KdlEntry, attribute: &str, old_root: &Path, new_root: &Path) {
+    let value = match entry.value().as_string() {
+        Some(value) => value,
+        None => return,
+    };
+    let rebased = match attribute {
+        "cwd" | "edit" => {
+            rebased_path(Path::new(value), old_root, new_root).map(|p| p.display().to_string())
+        },
+        "plugin" | "location" => value
+            .strip_prefix("file:")
+            .and_then(|path| rebased_path(Path::new(path), old_root, new_root))
+            .map(|p| format!("file:{}", p.display())),
+        _ => None,
+    };
+    if let Some(rebased) = rebased {
+        let value_repr = KdlValue::String(rebased.clone()).to_string();
+        entry.set_value(rebased);
+        // otherwise the original text of the value is what gets printed
+        entry.set_value_repr(value_repr);
+    }
+}
+
+fn layout_diagnostic_error(
+    diagnostic: &LayoutDiagnostic,
+    file_name: String,
+    raw_layout: &str,
+    raw_swap_layouts: Option<(&str, &str)>,
+) -> ConfigError {
+    let src = match (diagnostic.span, raw_swap_layouts) {
+        (Some(span), Some((raw_swap_layout_filename, raw_swap_layout)))
+            if span.in_swap_layout_file =>
+        {
+            NamedSource::new(raw_swap_layout_filename, String::from(raw_swap_layout))
+        },
+        _ => NamedSource::new(file_name, String::from(raw_layout)),
+    };
+    ConfigError::KdlError(KdlError {
+        error_message: diagnostic.message.clone(),
+        src: Some(src),
+        offset: diagnostic.span.map(|s| s.offset),
+        len: diagnostic.span.map(|s| s.len),
+        help_message: None,
+        secondary_labels: vec![],
+    })
+}
+
 fn kdl_layout_error(kdl_error: kdl::KdlError, file_name: String, raw_layout: &str) -> ConfigError {
     let error_message = match kdl_error.kind {
         kdl::KdlErrorKind::Context("valid node terminator") => {
@@ -1456,6 +2070,7 @@ fn kdl_layout_error(kdl_error: kdl::KdlError, file_name: String, raw_layout: &st
         offset: Some(kdl_error.span.offset()),
         len: Some(kdl_error.span.len()),
         help_message: None,
+        secondary_labels: vec![],
     };
     ConfigError::KdlError(kdl_error)
 }
@@ -1630,6 +2245,7 @@ impl Keybinds {
 impl Config {
     pub fn from_kdl(kdl_config: &str, base_config: Option<Config>) -> Result<Config, ConfigError> {
         let mut config = base_config.unwrap_or_else(|| Config::default());
+        let raw_config = kdl_config;
         let kdl_config: KdlDocument = kdl_config.parse()?;
 
         let config_options = Options::from_kdl(&kdl_config)?;
@@ -1656,6 +2272,13 @@ impl Config {
             let config_env = EnvironmentVariables::from_kdl(&env_config)?;
             config.env = config.env.merge(config_env);
         }
+        if let Some(kdl_layout_templates) = kdl_config.get("layout_templates") {
+            // templates of the base config are available to these ones and replaced by them
+            config.layout_templates =
+                KdlLayoutParser::new(raw_config, None, ValidationOptions::strict())
+                    .with_templates(&config.layout_templates)
+                    .parse_templates(kdl_layout_templates)?;
+        }
         Ok(config)
     }
 }
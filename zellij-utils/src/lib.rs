@@ -9,6 +9,8 @@ pub mod pane_size;
 pub mod position;
 pub mod setup;
 pub mod shared;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 
 // The following modules can't be used when targeting wasm
 #[cfg(not(target_family = "wasm"))]
@@ -20,6 +22,6 @@ pub mod logging; // Requires log4rs
 
 #[cfg(not(target_family = "wasm"))]
 pub use ::{
-    anyhow, async_std, clap, interprocess, lazy_static, libc, nix, regex, serde, signal_hook,
-    tempfile, termwiz, vte,
+    anyhow, async_std, clap, interprocess, lazy_static, libc, nix, regex, serde, serde_json,
+    signal_hook, tempfile, termwiz, vte,
 };
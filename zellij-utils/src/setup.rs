@@ -9,14 +9,27 @@ use crate::{
     },
     errors::prelude::*,
     input::{
+        command::command_exists,
         config::{Config, ConfigError},
-        layout::Layout,
+        layout::{
+            DiagnosticSeverity, Layout, LayoutDiagnostic, LayoutSources, ValidationOptions,
+            BUILTIN_LAYOUTS,
+        },
+        layout_capabilities::layout_capabilities_json,
+        layout_stats::LayoutLoadStats,
         options::Options,
+        plugins::PluginsConfig,
     },
+    kdl::{
+        format_layout, rebase_layout_paths, redact_hidden_title_commands,
+        redact_panes_excluded_from_dump, LayoutTemplates,
+    },
+    pane_size::Size,
 };
 use clap::{Args, IntoApp};
 use clap_complete::Shell;
 use directories_next::BaseDirs;
+use miette::Report;
 use serde::{Deserialize, Serialize};
 use std::{
     convert::TryFrom, fmt::Write as FmtWrite, io::Write, path::Path, path::PathBuf, process,
@@ -24,6 +37,13 @@ use std::{
 
 const CONFIG_LOCATION: &str = ".config/zellij";
 const CONFIG_NAME: &str = "config.kdl";
+
+/// The terminal sizes (columns, rows) `zellij setup --check-layout` fits the layout into
+pub const CHECK_LAYOUT_SIZES: &[(usize, usize)] = &[(80, 24), (120, 40), (200, 60)];
+/// What `zellij setup --check-layout` exits with when the layout cannot be loaded
+pub const CHECK_LAYOUT_LOAD_FAILED: i32 = 1;
+/// What `zellij setup --check-layout` exits with when the layout has errors
+pub const CHECK_LAYOUT_HAS_ERRORS: i32 = 2;
 static ARROW_SEPARATOR: &str = "";
 
 #[cfg(not(test))]
@@ -180,27 +200,82 @@ pub fn dump_default_config() -> std::io::Result<()> {
     dump_asset(DEFAULT_CONFIG)
 }
 
-pub fn dump_specified_layout(layout: &str) -> std::io::Result<()> {
-    match layout {
-        "strider" => dump_asset(STRIDER_LAYOUT),
-        "default" => dump_asset(DEFAULT_LAYOUT),
-        "compact" => dump_asset(COMPACT_BAR_LAYOUT),
-        "disable-status" => dump_asset(NO_STATUS_LAYOUT),
-        not_found => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("Layout: {} not found", not_found),
-        )),
+pub fn dump_specified_layout(
+    layout: &str,
+    include_hidden_titles: bool,
+    include_redacted: bool,
+) -> std::io::Result<()> {
+    // disable-status is what the disable-status-bar layout used to be dumped with
+    let builtin_layout_name = if layout == "disable-status" {
+        "disable-status-bar"
+    } else {
+        layout
+    };
+    let raw_layout = match BUILTIN_LAYOUTS.get(builtin_layout_name) {
+        Some(builtin_layout) => builtin_layout.layout.to_vec(),
+        None if Path::new(layout).is_file() => std::fs::read(layout)?,
+        None => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Layout: {} not found", layout),
+            ))
+        },
+    };
+    if include_hidden_titles && include_redacted {
+        return dump_asset(&raw_layout);
+    }
+    let mut redacted_layout = String::from_utf8(raw_layout)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    if !include_redacted {
+        redacted_layout = redact_panes_excluded_from_dump(&redacted_layout)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    }
+    // panes with hide_title usually run commands with secrets in them
+    if !include_hidden_titles {
+        redacted_layout = redact_hidden_title_commands(&redacted_layout)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    }
+    dump_asset(redacted_layout.as_bytes())
+}
+
+// the error, with the part of the layout it is about when it has one
+fn config_error_report(e: ConfigError) -> String {
+    match e {
+        ConfigError::KdlError(kdl_error) => {
+            let report: Report = kdl_error.into();
+            format!("{:?}", report)
+        },
+        e => e.to_string(),
     }
 }
 
+pub fn format_specified_layout(layout_path: &Path) -> std::io::Result<()> {
+    let raw_layout = std::fs::read_to_string(layout_path)?;
+    let formatted_layout = format_layout(&raw_layout)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    dump_asset(formatted_layout.as_bytes())
+}
+
+pub fn rebase_specified_layout(
+    layout_path: &Path,
+    old_root: &Path,
+    new_root: &Path,
+) -> std::io::Result<()> {
+    let raw_layout = std::fs::read_to_string(layout_path)?;
+    let rebased_layout = rebase_layout_paths(&raw_layout, old_root, new_root)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    std::fs::write(layout_path, rebased_layout)
+}
+
 pub fn dump_specified_swap_layout(swap_layout: &str) -> std::io::Result<()> {
-    match swap_layout {
-        "strider" => dump_asset(STRIDER_SWAP_LAYOUT),
-        "default" => dump_asset(DEFAULT_SWAP_LAYOUT),
-        "compact" => dump_asset(COMPACT_BAR_SWAP_LAYOUT),
-        not_found => Err(std::io::Error::new(
+    match BUILTIN_LAYOUTS
+        .get(swap_layout)
+        .and_then(|builtin_layout| builtin_layout.swap_layout)
+    {
+        Some((_description, raw_swap_layout)) => dump_asset(raw_swap_layout),
+        None => Err(std::io::Error::new(
             std::io::ErrorKind::Other,
-            format!("Swap Layout not found for: {}", not_found),
+            format!("Swap Layout not found for: {}", swap_layout),
         )),
     }
 }
@@ -256,14 +331,65 @@ pub struct Setup {
     #[clap(long, value_parser)]
     pub check: bool,
 
+    /// With --check, also print how long each stage of loading the layout took
+    #[clap(long, value_parser, requires("check"))]
+    pub timings: bool,
+
+    /// With --check, also print the fingerprint of the layout, which changes whenever what it
+    /// opens does, and that of its source, which changes with any change to its text
+    #[clap(long, value_parser, requires("check"))]
+    pub fingerprint: bool,
+
+    /// Check the specified layout file and its swap layouts without starting a session, fitting
+    /// it into a few common terminal sizes. Exits with 1 when it cannot be loaded and 2 when it
+    /// has errors
+    #[clap(long, value_name = "LAYOUT", value_parser)]
+    pub check_layout: Option<PathBuf>,
+
     /// Dump the specified layout file to stdout
     #[clap(long, value_parser)]
     pub dump_layout: Option<String>,
 
+    /// Do not redact the commands of panes with `hide_title` when dumping a layout
+    #[clap(long, value_parser)]
+    pub include_hidden_titles: bool,
+
+    /// Do not redact the commands of panes with `exclude_from_dump` when dumping a layout
+    #[clap(long, value_parser)]
+    pub include_redacted: bool,
+
     /// Dump the specified swap layout file to stdout
     #[clap(long, value_parser)]
     pub dump_swap_layout: Option<String>,
 
+    /// Print the specified layout file formatted, with its deprecated spellings rewritten
+    #[clap(long, value_name = "LAYOUT", value_parser)]
+    pub fmt_layout: Option<PathBuf>,
+
+    /// Move the paths under OLD in the layout file given with --in-place to under NEW
+    #[clap(
+        long,
+        value_names = &["OLD", "NEW"],
+        number_of_values = 2,
+        value_parser,
+        requires("in_place")
+    )]
+    pub rebase_layout: Option<Vec<PathBuf>>,
+
+    /// The layout file --rebase-layout rewrites, keeping its comments and formatting
+    #[clap(
+        short,
+        long,
+        value_name = "LAYOUT",
+        value_parser,
+        requires("rebase_layout")
+    )]
+    pub in_place: Option<PathBuf>,
+
+    /// Print the layout format features this version supports as JSON
+    #[clap(long, value_parser)]
+    pub layout_capabilities: bool,
+
     /// Dump the builtin plugins to DIR or "DATA DIR" if unspecified
     #[clap(
         long,
@@ -293,7 +419,9 @@ impl Setup {
     /// 2. layout options
     ///    (`layout.yaml` / `zellij --layout`)
     /// 3. config options (`config.yaml`)
-    pub fn from_cli_args(cli_args: &CliArgs) -> Result<(Config, Layout, Options), ConfigError> {
+    pub fn from_cli_args(
+        cli_args: &CliArgs,
+    ) -> Result<(Config, Layout, Options, LayoutSources), ConfigError> {
         // note that this can potentially exit the process
         Setup::handle_setup_commands(cli_args);
         let config = Config::try_from(cli_args)?;
@@ -303,7 +431,7 @@ impl Setup {
             } else {
                 None
             };
-        let (layout, mut config) =
+        let (layout, mut config, layout_sources) =
             Setup::parse_layout_and_override_config(cli_config_options.as_ref(), config, cli_args)?;
         let config_options = match cli_config_options {
             Some(cli_config_options) => config.options.merge(cli_config_options),
@@ -333,7 +461,12 @@ impl Setup {
 
         if let Some(Command::Setup(ref setup)) = &cli_args.command {
             setup
-                .from_cli_with_options(cli_args, &config_options)
+                .from_cli_with_options(
+                    cli_args,
+                    &config_options,
+                    &config.layout_templates,
+                    &config.plugins,
+                )
                 .map_or_else(
                     |e| {
                         eprintln!("{:?}", e);
@@ -342,7 +475,24 @@ impl Setup {
                     |_| {},
                 );
         };
-        Ok((config, layout, config_options))
+        Ok((config, layout, config_options, layout_sources))
+    }
+
+    /// Sets up a new session called `session_name` with the layout its name matches in the
+    /// `default_layouts` option, returns `None` if it matches none of them or if a layout was
+    /// explicitly chosen on the command line
+    pub fn from_cli_args_for_session(
+        cli_args: &CliArgs,
+        config_options: &Options,
+        session_name: &str,
+    ) -> Option<Result<(Config, Layout, Options, LayoutSources), ConfigError>> {
+        if !cli_args.layout.is_empty() {
+            return None;
+        }
+        let layout = config_options.default_layout_for_session(session_name)?;
+        let mut cli_args = cli_args.clone();
+        cli_args.layout = vec![layout.clone()];
+        Some(Setup::from_cli_args(&cli_args))
     }
 
     /// General setup helpers
@@ -367,7 +517,7 @@ impl Setup {
         }
 
         if let Some(layout) = &self.dump_layout {
-            dump_specified_layout(layout)?;
+            dump_specified_layout(layout, self.include_hidden_titles, self.include_redacted)?;
             std::process::exit(0);
         }
 
@@ -376,16 +526,51 @@ impl Setup {
             std::process::exit(0);
         }
 
+        if let Some(layout_path) = &self.fmt_layout {
+            format_specified_layout(layout_path)?;
+            std::process::exit(0);
+        }
+
+        if let (Some(roots), Some(layout_path)) = (&self.rebase_layout, &self.in_place) {
+            rebase_specified_layout(layout_path, &roots[0], &roots[1])?;
+            std::process::exit(0);
+        }
+
+        if self.layout_capabilities {
+            println!("{:#}", layout_capabilities_json());
+            std::process::exit(0);
+        }
+
         Ok(())
     }
 
     /// Checks the merged configuration
-    pub fn from_cli_with_options(&self, opts: &CliArgs, config_options: &Options) -> Result<()> {
+    pub fn from_cli_with_options(
+        &self,
+        opts: &CliArgs,
+        config_options: &Options,
+        layout_templates: &LayoutTemplates,
+        plugins: &PluginsConfig,
+    ) -> Result<()> {
         if self.check {
-            Setup::check_defaults_config(opts, config_options)?;
+            Setup::check_defaults_config(
+                opts,
+                config_options,
+                layout_templates,
+                plugins,
+                self.timings,
+                self.fingerprint,
+            )?;
             std::process::exit(0);
         }
 
+        if let Some(layout_path) = &self.check_layout {
+            let (message, exit_code) =
+                Setup::check_layout(layout_path, opts, config_options, layout_templates, plugins);
+            print!("{}", message);
+            std::process::exit(exit_code);
+        }
+
         if let Some(maybe_path) = &self.dump_plugins {
             let data_dir = &opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
             let dir = match maybe_path {
@@ -401,7 +586,14 @@ impl Setup {
         Ok(())
     }
 
-    pub fn check_defaults_config(opts: &CliArgs, config_options: &Options) -> std::io::Result<()> {
+    pub fn check_defaults_config(
+        opts: &CliArgs,
+        config_options: &Options,
+        layout_templates: &LayoutTemplates,
+        plugins: &PluginsConfig,
+        timings: bool,
+        fingerprint: bool,
+    ) -> std::io::Result<()> {
         let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
         let config_dir = opts.config_dir.clone().or_else(find_default_config_dir);
         let plugin_dir = data_dir.join("plugins");
@@ -470,11 +662,99 @@ impl Setup {
             )
             .unwrap();
         }
-        if let Some(layout_dir) = layout_dir {
+        if let Some(layout_dir) = &layout_dir {
             writeln!(&mut message, "[LAYOUT DIR]: {:?}", layout_dir).unwrap();
         } else {
             message.push_str("[LAYOUT DIR]: Not Found\n");
         }
+        // each of the layouts opened together is checked on its own
+        let layout_paths: Vec<Option<&PathBuf>> = if opts.layout.is_empty() {
+            vec![None]
+        } else {
+            opts.layout.iter().map(Some).collect()
+        };
+        for layout_path in layout_paths {
+            match Layout::stringified_from_path_or_default(layout_path, layout_dir.clone()) {
+                Ok((layout_path, raw_layout, raw_swap_layouts, layout_sources)) => {
+                    writeln!(&mut message, "[LAYOUT]: {}", layout_sources).unwrap();
+                    match Setup::load_layout_to_check(
+                        layout_path,
+                        &raw_layout,
+                        raw_swap_layouts,
+                        &layout_sources,
+                        layout_dir.as_deref(),
+                        opts,
+                        config_options,
+                        layout_templates,
+                    ) {
+                        Ok((mut layout, mut diagnostics, load_stats)) => {
+                            // as loaded, like everywhere else the fingerprint is taken
+                            let layout_fingerprint = layout.fingerprint();
+                            // a session started from here would look for the commands on this
+                            // machine too
+                            let condition_outcomes = layout.apply_command_conditions(|command| {
+                                command_exists(Path::new(command), None)
+                            });
+                            diagnostics.extend(
+                                condition_outcomes
+                                    .iter()
+                                    .map(|outcome| outcome.to_diagnostic()),
+                            );
+                            // the screen size is only known once the session starts
+                            let preflight_errors = layout.preflight(None, plugins);
+                            let is_well_defined = diagnostics
+                                .iter()
+                                .all(|diagnostic| diagnostic.severity == DiagnosticSeverity::Info);
+                            if is_well_defined && preflight_errors.is_empty() {
+                                message.push_str("[LAYOUT]: Well defined.\n");
+                            }
+                            if timings {
+                                writeln!(&mut message, "[LAYOUT TIMINGS]: {}", load_stats).unwrap();
+                            }
+                            if fingerprint {
+                                writeln!(
+                                    &mut message,
+                                    "[LAYOUT FINGERPRINT]: {}",
+                                    layout_fingerprint
+                                )
+                                .unwrap();
+                                writeln!(
+                                    &mut message,
+                                    "[LAYOUT SOURCE FINGERPRINT]: {}",
+                                    Layout::fingerprint_of_source(&raw_layout)
+                                )
+                                .unwrap();
+                            }
+                            for diagnostic in diagnostics {
+                                writeln!(&mut message, " {}", diagnostic).unwrap();
+                            }
+                            for preflight_error in preflight_errors {
+                                writeln!(&mut message, "[LAYOUT ERROR]: {}", preflight_error)
+                                    .unwrap();
+                            }
+                        },
+                        Err(e) => writeln!(&mut message, "[LAYOUT ERROR]: {}", e).unwrap(),
+                    }
+                },
+                Err(e) => writeln!(&mut message, "[LAYOUT ERROR]: {}", e).unwrap(),
+            }
+        }
+        for (pattern, layout) in config_options.default_layouts.iter().flatten() {
+            match Layout::stringified_from_path_or_default(Some(layout), layout_dir.clone()) {
+                Ok((_layout_path, _raw_layout, _raw_swap_layouts, layout_sources)) => writeln!(
+                    &mut message,
+                    "[DEFAULT LAYOUTS]: {:?} -> {}",
+                    pattern, layout_sources
+                )
+                .unwrap(),
+                Err(e) => writeln!(
+                    &mut message,
+                    "[DEFAULT LAYOUTS ERROR]: {:?} -> {:?}: {}",
+                    pattern, layout, e
+                )
+                .unwrap(),
+            }
+        }
         writeln!(&mut message, "[SYSTEM DATA DIR]: {:?}", system_data_dir).unwrap();
 
         writeln!(&mut message, "[ARROW SEPARATOR]: {}", ARROW_SEPARATOR).unwrap();
@@ -564,11 +844,149 @@ impl Setup {
             _ => {},
         }
     }
+    /// Loads the layout as a session would (with the variables given to it on the command line
+    /// and the files it includes), but leniently so that all of its diagnostics are returned
+    /// rather than the first error
+    #[allow(clippy::too_many_arguments)]
+    fn load_layout_to_check(
+        layout_path: String,
+        raw_layout: &str,
+        raw_swap_layouts: Option<(String, String)>,
+        layout_sources: &LayoutSources,
+        layout_dir: Option<&Path>,
+        opts: &CliArgs,
+        config_options: &Options,
+        layout_templates: &LayoutTemplates,
+    ) -> Result<(Layout, Vec<LayoutDiagnostic>, LayoutLoadStats), ConfigError> {
+        let validation_options =
+            ValidationOptions::lenient().with_limits(config_options.layout_limits());
+        let (raw_layout, raw_swap_layouts) = Layout::with_variables(
+            raw_layout,
+            &layout_path,
+            raw_swap_layouts,
+            &opts.layout_variables(),
+        )?;
+        let (layout_templates, _included_files) = Layout::resolve_includes(
+            &raw_layout,
+            &layout_path,
+            layout_sources.main_layout_file(),
+            layout_dir,
+            validation_options,
+            layout_templates,
+        )?;
+        Layout::from_kdl_with_stats(
+            &raw_layout,
+            layout_path,
+            raw_swap_layouts
+                .as_ref()
+                .map(|(p, r)| (p.as_str(), r.as_str())),
+            None,
+            validation_options,
+            &layout_templates,
+        )
+    }
+
+    /// Checks the layout end to end without starting a session: loads it along with its swap
+    /// layouts, then fits its tabs into each of [`CHECK_LAYOUT_SIZES`]. Returns what was found
+    /// and the exit code: 0 when the layout is fine (warnings aside),
+    /// [`CHECK_LAYOUT_LOAD_FAILED`] when it cannot be loaded and [`CHECK_LAYOUT_HAS_ERRORS`]
+    /// when it has errors
+    pub fn check_layout(
+        layout_path: &Path,
+        opts: &CliArgs,
+        config_options: &Options,
+        layout_templates: &LayoutTemplates,
+        plugins: &PluginsConfig,
+    ) -> (String, i32) {
+        let mut message = String::new();
+        let config_dir = opts.config_dir.clone().or_else(find_default_config_dir);
+        let layout_dir = config_options
+            .layout_dir
+            .clone()
+            .or_else(|| get_layout_dir(config_dir));
+        let loaded_layout = Layout::stringified_from_path_or_default(
+            Some(&layout_path.to_path_buf()),
+            layout_dir.clone(),
+        )
+        .and_then(
+            |(path_to_raw_layout, raw_layout, raw_swap_layouts, layout_sources)| {
+                writeln!(&mut message, "[LAYOUT]: {}", layout_sources).unwrap();
+                if let Some(swap_layouts) = &layout_sources.swap_layouts {
+                    writeln!(&mut message, "[SWAP LAYOUT]: {}", swap_layouts).unwrap();
+                }
+                Setup::load_layout_to_check(
+                    path_to_raw_layout,
+                    &raw_layout,
+                    raw_swap_layouts,
+                    &layout_sources,
+                    layout_dir.as_deref(),
+                    opts,
+                    config_options,
+                    layout_templates,
+                )
+            },
+        );
+        let (layout, diagnostics, _load_stats) = match loaded_layout {
+            Ok(loaded_layout) => loaded_layout,
+            Err(e) => {
+                writeln!(&mut message, "[LAYOUT ERROR]: {}", config_error_report(e)).unwrap();
+                return (message, CHECK_LAYOUT_LOAD_FAILED);
+            },
+        };
+        let mut has_errors = false;
+        let mut is_well_defined = true;
+        for diagnostic in diagnostics {
+            has_errors |= diagnostic.severity == DiagnosticSeverity::Error;
+            is_well_defined &= diagnostic.severity == DiagnosticSeverity::Info;
+            writeln!(&mut message, " {}", diagnostic).unwrap();
+        }
+        // the errors that do not depend on the size of the terminal are only reported once
+        let errors_at_any_size = layout.preflight(None, plugins);
+        for error in &errors_at_any_size {
+            writeln!(&mut message, "[LAYOUT ERROR]: {}", error).unwrap();
+        }
+        has_errors |= !errors_at_any_size.is_empty();
+        for (cols, rows) in CHECK_LAYOUT_SIZES {
+            let screen_size = Size {
+                cols: *cols,
+                rows: *rows,
+            };
+            for error in layout.preflight(Some(screen_size), plugins) {
+                if !errors_at_any_size.contains(&error) {
+                    has_errors = true;
+                    writeln!(
+                        &mut message,
+                        "[LAYOUT ERROR]: at {}x{}, {}",
+                        cols, rows, error
+                    )
+                    .unwrap();
+                }
+            }
+            // a swap layout that does not fit is skipped when swapping, so this is no error
+            for swap_layout_warning in layout.swap_layouts_not_fitting(screen_size) {
+                is_well_defined = false;
+                writeln!(
+                    &mut message,
+                    "[LAYOUT WARNING]: at {}x{}, {}",
+                    cols, rows, swap_layout_warning
+                )
+                .unwrap();
+            }
+        }
+        if has_errors {
+            return (message, CHECK_LAYOUT_HAS_ERRORS);
+        }
+        if is_well_defined {
+            message.push_str("[LAYOUT]: Well defined.\n");
+        }
+        (message, 0)
+    }
+
     fn parse_layout_and_override_config(
         cli_config_options: Option<&Options>,
         config: Config,
         cli_args: &CliArgs,
-    ) -> Result<(Layout, Config), ConfigError> {
+    ) -> Result<(Layout, Config, LayoutSources), ConfigError> {
         // find the layout folder relative to which we'll look for our layout
         let layout_dir = cli_config_options
             .as_ref()
@@ -580,18 +998,24 @@ impl Setup {
         // the chosen layout can either be a path relative to the layout_dir or a name of one
         // of our assets, this distinction is made when parsing the layout - TODO: ideally, this
         // logic should not be split up and all the decisions should happen here
-        let chosen_layout = cli_args
-            .layout
-            .clone()
-            .or_else(|| {
-                cli_config_options
-                    .as_ref()
-                    .and_then(|cli_options| cli_options.default_layout.clone())
-            })
-            .or_else(|| config.options.default_layout.clone());
+        let chosen_layouts = if cli_args.layout.is_empty() {
+            cli_config_options
+                .as_ref()
+                .and_then(|cli_options| cli_options.default_layout.clone())
+                .or_else(|| config.options.default_layout.clone())
+                .into_iter()
+                .collect()
+        } else {
+            cli_args.layout.clone()
+        };
         // we merge-override the config here because the layout might contain configuration
         // that needs to take precedence
-        Layout::from_path_or_default(chosen_layout.as_ref(), layout_dir.clone(), config)
+        Layout::from_paths_or_default(
+            &chosen_layouts,
+            layout_dir.clone(),
+            config,
+            &cli_args.layout_variables(),
+        )
     }
     fn handle_setup_commands(cli_args: &CliArgs) {
         if let Some(Command::Setup(ref setup)) = &cli_args.command {
@@ -608,16 +1032,28 @@ impl Setup {
 
 #[cfg(test)]
 mod setup_test {
-    use super::Setup;
+    use super::{Setup, CHECK_LAYOUT_HAS_ERRORS, CHECK_LAYOUT_LOAD_FAILED};
     use crate::cli::{CliArgs, Command};
     use crate::input::options::{CliOptions, Options};
+    use crate::input::plugins::PluginsConfig;
+    use crate::kdl::LayoutTemplates;
     use insta::assert_snapshot;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+
+    fn check_layout(layout_path: &Path) -> (String, i32) {
+        Setup::check_layout(
+            layout_path,
+            &CliArgs::default(),
+            &Options::default(),
+            &LayoutTemplates::default(),
+            &PluginsConfig::default(),
+        )
+    }
 
     #[test]
     fn default_config_with_no_cli_arguments() {
         let cli_args = CliArgs::default();
-        let (config, layout, options) = Setup::from_cli_args(&cli_args).unwrap();
+        let (config, layout, options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", config));
         assert_snapshot!(format!("{:#?}", layout));
         assert_snapshot!(format!("{:#?}", options));
@@ -632,27 +1068,27 @@ mod setup_test {
             },
             ..Default::default()
         }));
-        let (_config, _layout, options) = Setup::from_cli_args(&cli_args).unwrap();
+        let (_config, _layout, options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", options));
     }
     #[test]
     fn layout_options_override_config_options() {
         let mut cli_args = CliArgs::default();
-        cli_args.layout = Some(PathBuf::from(format!(
+        cli_args.layout = vec![PathBuf::from(format!(
             "{}/src/test-fixtures/layout-with-options.kdl",
             env!("CARGO_MANIFEST_DIR")
-        )));
-        let (_config, layout, options) = Setup::from_cli_args(&cli_args).unwrap();
+        ))];
+        let (_config, layout, options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", options));
         assert_snapshot!(format!("{:#?}", layout));
     }
     #[test]
     fn cli_arguments_override_layout_options() {
         let mut cli_args = CliArgs::default();
-        cli_args.layout = Some(PathBuf::from(format!(
+        cli_args.layout = vec![PathBuf::from(format!(
             "{}/src/test-fixtures/layout-with-options.kdl",
             env!("CARGO_MANIFEST_DIR")
-        )));
+        ))];
         cli_args.command = Some(Command::Options(CliOptions {
             options: Options {
                 pane_frames: Some(true),
@@ -660,22 +1096,67 @@ mod setup_test {
             },
             ..Default::default()
         }));
-        let (_config, layout, options) = Setup::from_cli_args(&cli_args).unwrap();
+        let (_config, layout, options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", options));
         assert_snapshot!(format!("{:#?}", layout));
     }
     #[test]
+    fn multiple_layouts_open_their_tabs_in_order_and_compose_their_options() {
+        let mut cli_args = CliArgs::default();
+        cli_args.layout = vec![
+            PathBuf::from(format!(
+                "{}/src/test-fixtures/layout-with-options.kdl",
+                env!("CARGO_MANIFEST_DIR")
+            )),
+            PathBuf::from(format!(
+                "{}/src/test-fixtures/layout-with-tabs-and-options.kdl",
+                env!("CARGO_MANIFEST_DIR")
+            )),
+        ];
+        let (_config, layout, options, layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
+        assert_eq!(
+            options.pane_frames,
+            Some(true),
+            "later layouts apply over earlier ones"
+        );
+        assert_eq!(options.simplified_ui, Some(true));
+        let tab_names: Vec<Option<String>> = layout
+            .tabs()
+            .into_iter()
+            .map(|(tab_name, ..)| tab_name)
+            .collect();
+        assert_eq!(tab_names, vec![None, Some("services".to_owned())]);
+        assert!(layout_sources.name.contains(" + "));
+    }
+    #[test]
+    fn errors_in_one_of_multiple_layouts_name_it() {
+        let mut cli_args = CliArgs::default();
+        cli_args.layout = vec![
+            PathBuf::from(format!(
+                "{}/src/test-fixtures/layout-with-options.kdl",
+                env!("CARGO_MANIFEST_DIR")
+            )),
+            PathBuf::from("/no/such/dir/services.kdl"),
+        ];
+        let error = Setup::from_cli_args(&cli_args).unwrap_err();
+        assert!(
+            error.to_string().contains("/no/such/dir/services.kdl"),
+            "{}",
+            error
+        );
+    }
+    #[test]
     fn layout_env_vars_override_config_env_vars() {
         let mut cli_args = CliArgs::default();
         cli_args.config = Some(PathBuf::from(format!(
             "{}/src/test-fixtures/config-with-env-vars.kdl",
             env!("CARGO_MANIFEST_DIR")
         )));
-        cli_args.layout = Some(PathBuf::from(format!(
+        cli_args.layout = vec![PathBuf::from(format!(
             "{}/src/test-fixtures/layout-with-env-vars.kdl",
             env!("CARGO_MANIFEST_DIR")
-        )));
-        let (config, _layout, _options) = Setup::from_cli_args(&cli_args).unwrap();
+        ))];
+        let (config, _layout, _options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", config));
     }
     #[test]
@@ -685,11 +1166,11 @@ mod setup_test {
             "{}/src/test-fixtures/config-with-ui-config.kdl",
             env!("CARGO_MANIFEST_DIR")
         )));
-        cli_args.layout = Some(PathBuf::from(format!(
+        cli_args.layout = vec![PathBuf::from(format!(
             "{}/src/test-fixtures/layout-with-ui-config.kdl",
             env!("CARGO_MANIFEST_DIR")
-        )));
-        let (config, _layout, _options) = Setup::from_cli_args(&cli_args).unwrap();
+        ))];
+        let (config, _layout, _options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", config));
     }
     #[test]
@@ -699,11 +1180,11 @@ mod setup_test {
             "{}/src/test-fixtures/config-with-plugins-config.kdl",
             env!("CARGO_MANIFEST_DIR")
         )));
-        cli_args.layout = Some(PathBuf::from(format!(
+        cli_args.layout = vec![PathBuf::from(format!(
             "{}/src/test-fixtures/layout-with-plugins-config.kdl",
             env!("CARGO_MANIFEST_DIR")
-        )));
-        let (config, _layout, _options) = Setup::from_cli_args(&cli_args).unwrap();
+        ))];
+        let (config, _layout, _options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", config));
     }
     #[test]
@@ -713,11 +1194,11 @@ mod setup_test {
             "{}/src/test-fixtures/config-with-themes-config.kdl",
             env!("CARGO_MANIFEST_DIR")
         )));
-        cli_args.layout = Some(PathBuf::from(format!(
+        cli_args.layout = vec![PathBuf::from(format!(
             "{}/src/test-fixtures/layout-with-themes-config.kdl",
             env!("CARGO_MANIFEST_DIR")
-        )));
-        let (config, _layout, _options) = Setup::from_cli_args(&cli_args).unwrap();
+        ))];
+        let (config, _layout, _options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", config));
     }
     #[test]
@@ -727,11 +1208,127 @@ mod setup_test {
             "{}/src/test-fixtures/config-with-keybindings-config.kdl",
             env!("CARGO_MANIFEST_DIR")
         )));
-        cli_args.layout = Some(PathBuf::from(format!(
+        cli_args.layout = vec![PathBuf::from(format!(
             "{}/src/test-fixtures/layout-with-keybindings-config.kdl",
             env!("CARGO_MANIFEST_DIR")
-        )));
-        let (config, _layout, _options) = Setup::from_cli_args(&cli_args).unwrap();
+        ))];
+        let (config, _layout, _options, _layout_sources) = Setup::from_cli_args(&cli_args).unwrap();
         assert_snapshot!(format!("{:#?}", config));
     }
+    #[test]
+    fn session_name_picks_default_layout() {
+        let cli_args = CliArgs::default();
+        let config_options = Options {
+            default_layouts: Some(vec![
+                ("work-*".into(), PathBuf::from("compact")),
+                ("*".into(), PathBuf::from("strider")),
+            ]),
+            ..Default::default()
+        };
+        let (_config, _layout, _options, layout_sources) =
+            Setup::from_cli_args_for_session(&cli_args, &config_options, "work-api")
+                .expect("session name matches a default layout")
+                .unwrap();
+        assert_eq!(layout_sources.name, "compact");
+        let (_config, _layout, _options, layout_sources) =
+            Setup::from_cli_args_for_session(&cli_args, &config_options, "scratch")
+                .expect("session name matches a default layout")
+                .unwrap();
+        assert_eq!(layout_sources.name, "strider");
+    }
+    #[test]
+    fn explicit_layout_overrides_default_layout_for_session() {
+        let mut cli_args = CliArgs::default();
+        cli_args.layout = vec![PathBuf::from("default")];
+        let config_options = Options {
+            default_layouts: Some(vec![("*".into(), PathBuf::from("compact"))]),
+            ..Default::default()
+        };
+        assert!(
+            Setup::from_cli_args_for_session(&cli_args, &config_options, "work-api").is_none(),
+            "explicit layout is kept"
+        );
+        assert!(
+            Setup::from_cli_args_for_session(&CliArgs::default(), &Options::default(), "work-api")
+                .is_none(),
+            "no default_layouts, layout is kept"
+        );
+    }
+    #[test]
+    fn check_layout_passes_a_layout_that_fits_every_size() {
+        let layout_dir = tempfile::tempdir().unwrap();
+        let layout_path = layout_dir.path().join("dev.kdl");
+        std::fs::write(&layout_path, "layout {\n    pane\n    pane size=10\n}\n").unwrap();
+        let (message, exit_code) = check_layout(&layout_path);
+        assert_eq!(exit_code, 0, "{}", message);
+        assert!(message.contains("[LAYOUT]: Well defined."), "{}", message);
+    }
+    #[test]
+    fn check_layout_reports_the_sizes_a_layout_does_not_fit_in() {
+        let layout_dir = tempfile::tempdir().unwrap();
+        let layout_path = layout_dir.path().join("dev.kdl");
+        std::fs::write(
+            &layout_path,
+            "layout {\n    pane name=\"logs\" size=30\n    pane\n}\n",
+        )
+        .unwrap();
+        let (message, exit_code) = check_layout(&layout_path);
+        assert_eq!(exit_code, CHECK_LAYOUT_HAS_ERRORS, "{}", message);
+        assert!(
+            message.contains("[LAYOUT ERROR]: at 80x24, tab #1: The fixed size panes in the layout take 30 rows but only 24 are available"),
+            "{}",
+            message
+        );
+        assert!(!message.contains("at 120x40"), "{}", message);
+        assert!(!message.contains("Well defined"), "{}", message);
+    }
+    #[test]
+    fn check_layout_fails_on_a_layout_that_cannot_be_loaded() {
+        let layout_dir = tempfile::tempdir().unwrap();
+        let layout_path = layout_dir.path().join("dev.kdl");
+        std::fs::write(&layout_path, "layout {\n    pane {\n}\n").unwrap();
+        let (message, exit_code) = check_layout(&layout_path);
+        assert_eq!(exit_code, CHECK_LAYOUT_LOAD_FAILED, "{}", message);
+        assert!(message.contains("[LAYOUT ERROR]"), "{}", message);
+        let (message, exit_code) = check_layout(&layout_dir.path().join("missing.kdl"));
+        assert_eq!(exit_code, CHECK_LAYOUT_LOAD_FAILED, "{}", message);
+    }
+    #[test]
+    fn check_layout_warns_about_the_swap_layouts_that_are_never_used() {
+        let layout_dir = tempfile::tempdir().unwrap();
+        let layout_path = layout_dir.path().join("dev.kdl");
+        std::fs::write(&layout_path, "layout {\n    pane\n}\n").unwrap();
+        std::fs::write(
+            layout_dir.path().join("dev.swap.kdl"),
+            r#"
+            swap_tiled_layout name="wide" {
+                tab min_panes=4 {
+                    pane split_direction="vertical" {
+                        pane
+                        pane size=150
+                        children
+                    }
+                }
+                tab min_panes=8 {
+                    pane
+                    children
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let (message, exit_code) = check_layout(&layout_path);
+        assert_eq!(exit_code, 0, "{}", message);
+        assert!(message.contains("[SWAP LAYOUT]: "), "{}", message);
+        assert!(message.contains("will never be used"), "{}", message);
+        assert!(
+            message.contains(
+                "[LAYOUT WARNING]: at 80x24, swap layout \"wide\" (min_panes 4) does not fit"
+            ),
+            "{}",
+            message
+        );
+        assert!(!message.contains("at 200x60"), "{}", message);
+        assert!(!message.contains("Well defined"), "{}", message);
+    }
 }
@@ -33,6 +33,14 @@ pub fn ansi_len(s: &str) -> usize {
     from_utf8(&strip(s).unwrap()).unwrap().width()
 }
 
+/// The text of terminal output without its escape sequences, invalid UTF-8 is replaced
+pub fn strip_ansi(bytes: &[u8]) -> String {
+    match strip(bytes) {
+        Ok(stripped) => String::from_utf8_lossy(&stripped).into_owned(),
+        Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
 pub fn adjust_to_size(s: &str, rows: usize, columns: usize) -> String {
     s.lines()
         .map(|l| {
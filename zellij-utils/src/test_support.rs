@@ -0,0 +1,233 @@
+//! Canned layouts and geometry helpers for testing code that consumes zellij layouts.
+//!
+//! Available to zellij's own tests and, with the `test-support` feature, to other crates.
+//!
+//! Stability: the fixtures are only added to. An existing fixture keeps its panes and the way they
+//! split the space, so tests asserting on their geometry keep passing across releases; a fixture
+//! that has to change is added under a new name instead. The assertion helpers may get more
+//! thorough (ie. fail on layouts they previously accepted) but will not get more lenient.
+use crate::{
+    input::layout::{FocusPolicy, Layout, TiledPaneLayout},
+    pane_size::PaneGeom,
+};
+
+/// A single tiled pane
+pub const SINGLE_PANE: &str = r#"
+layout {
+    pane
+}
+"#;
+
+/// Four tiled panes, two columns of two panes each
+pub const GRID_2X2: &str = r#"
+layout {
+    pane split_direction="vertical" {
+        pane {
+            pane
+            pane
+        }
+        pane {
+            pane
+            pane
+        }
+    }
+}
+"#;
+
+/// Three panes under a pane whose children are stacked once parsed by [`stacked`]
+pub const STACKED: &str = r#"
+layout {
+    pane {
+        pane
+        pane
+        pane
+    }
+}
+"#;
+
+/// Two named tabs sharing a default tab template with a tab bar and a status bar
+pub const TABBED_WITH_TEMPLATE: &str = r#"
+layout {
+    default_tab_template {
+        pane size=1 borderless=true {
+            plugin location="zellij:tab-bar"
+        }
+        children
+        pane size=2 borderless=true {
+            plugin location="zellij:status-bar"
+        }
+    }
+    tab name="editor" focus=true {
+        pane
+    }
+    tab name="shell" {
+        pane split_direction="vertical" {
+            pane
+            pane
+        }
+    }
+}
+"#;
+
+/// One tiled pane under four floating panes, positioned in all the ways a floating pane can be
+pub const FLOATING_HEAVY: &str = r#"
+layout {
+    pane
+    floating_panes {
+        pane
+        pane x=0 y=0 width="50%" height="50%"
+        pane x="50%" y=0 width="50%" height="50%"
+        pane command="htop" {
+            x 10
+            y "50%"
+            width 40
+            height "40%"
+        }
+    }
+}
+"#;
+
+fn parse(raw_layout: &str, name: &str) -> Layout {
+    Layout::from_kdl(raw_layout, name.into(), None, None)
+        .unwrap_or_else(|e| panic!("test support layout {} failed to parse: {:?}", name, e))
+}
+
+/// [`SINGLE_PANE`], parsed
+pub fn single_pane() -> Layout {
+    parse(SINGLE_PANE, "single_pane")
+}
+
+/// [`GRID_2X2`], parsed
+pub fn grid_2x2() -> Layout {
+    parse(GRID_2X2, "grid_2x2")
+}
+
+/// [`STACKED`], parsed and with the children of its pane stacked. Stacks can only be written in
+/// swap layouts (`children stacked=true`), so they are set here instead
+pub fn stacked() -> Layout {
+    let mut layout = parse(STACKED, "stacked");
+    if let Some((tiled_panes, _floating_panes)) = layout.template.as_mut() {
+        if let Some(stack) = tiled_panes.children.first_mut() {
+            stack.children_are_stacked = true;
+        }
+    }
+    layout
+}
+
+/// [`TABBED_WITH_TEMPLATE`], parsed
+pub fn tabbed_with_template() -> Layout {
+    parse(TABBED_WITH_TEMPLATE, "tabbed_with_template")
+}
+
+/// [`FLOATING_HEAVY`], parsed
+pub fn floating_heavy() -> Layout {
+    parse(FLOATING_HEAVY, "floating_heavy")
+}
+
+/// All the canned layouts, by name
+pub fn all_layouts() -> Vec<(&'static str, Layout)> {
+    vec![
+        ("single_pane", single_pane()),
+        ("grid_2x2", grid_2x2()),
+        ("stacked", stacked()),
+        ("tabbed_with_template", tabbed_with_template()),
+        ("floating_heavy", floating_heavy()),
+    ]
+}
+
+/// A space of `cols` by `rows` at the top left of the screen, to lay panes out in
+pub fn viewport(cols: usize, rows: usize) -> PaneGeom {
+    let mut space = PaneGeom::default();
+    space.cols.set_inner(cols);
+    space.rows.set_inner(rows);
+    space
+}
+
+/// Asserts that the positioned panes (as returned by
+/// [`TiledPaneLayout::position_panes_in_space`]) cover every cell of `viewport` exactly once
+pub fn assert_tiles_exactly(panes: &[(TiledPaneLayout, PaneGeom)], viewport: &PaneGeom) {
+    let (cols, rows) = (viewport.cols.as_usize(), viewport.rows.as_usize());
+    let mut covered_by: Vec<Option<usize>> = vec![None; cols * rows];
+    for (index, (_pane_layout, geom)) in panes.iter().enumerate() {
+        let (pane_cols, pane_rows) = (geom.cols.as_usize(), geom.rows.as_usize());
+        assert!(
+            pane_cols > 0 && pane_rows > 0,
+            "pane #{} ({:?}) has no size",
+            index,
+            geom
+        );
+        assert!(
+            geom.x >= viewport.x
+                && geom.y >= viewport.y
+                && geom.x + pane_cols <= viewport.x + cols
+                && geom.y + pane_rows <= viewport.y + rows,
+            "pane #{} ({:?}) is not inside the viewport ({:?})",
+            index,
+            geom,
+            viewport
+        );
+        for y in geom.y - viewport.y..geom.y - viewport.y + pane_rows {
+            for x in geom.x - viewport.x..geom.x - viewport.x + pane_cols {
+                if let Some(other) = covered_by[y * cols + x].replace(index) {
+                    panic!(
+                        "panes #{} and #{} overlap at ({}, {})",
+                        other,
+                        index,
+                        x + viewport.x,
+                        y + viewport.y
+                    );
+                }
+            }
+        }
+    }
+    if let Some(cell) = covered_by.iter().position(|pane| pane.is_none()) {
+        panic!(
+            "no pane covers ({}, {}) of the viewport ({:?})",
+            cell % cols + viewport.x,
+            cell / cols + viewport.y,
+            viewport
+        );
+    }
+}
+
+/// Asserts that `layout`, fitted to `max_panes` the way
+/// [`TiledPaneLayout::position_panes_in_space`] does, has one run instruction for each pane it is
+/// positioned as. Commands are matched with their panes by this order, so a mismatch runs them in
+/// the wrong panes
+pub fn assert_run_instructions_match_positions(
+    layout: &TiledPaneLayout,
+    viewport: &PaneGeom,
+    max_panes: Option<usize>,
+) {
+    let fitted_layout = layout.fitted_to_pane_count(viewport, max_panes, FocusPolicy::default());
+    let positions = layout
+        .position_panes_in_space_indexed(viewport, max_panes, FocusPolicy::default())
+        .unwrap_or_else(|e| panic!("layout could not be positioned: {}", e));
+    let run_instructions = fitted_layout.extract_run_instructions_indexed();
+    assert_eq!(
+        positions.len(),
+        run_instructions.len(),
+        "{} panes positioned but {} run instructions",
+        positions.len(),
+        run_instructions.len()
+    );
+    for (index, ((pane_layout, _geom, leaf_path), run_instruction)) in
+        positions.iter().zip(run_instructions).enumerate()
+    {
+        assert_eq!(
+            pane_layout.run, run_instruction.run,
+            "pane #{} is positioned with another run instruction than the one extracted for it",
+            index
+        );
+        assert_eq!(
+            leaf_path, &run_instruction.leaf_path,
+            "pane #{} is positioned at another leaf path than the one extracted for it",
+            index
+        );
+        assert_eq!(
+            pane_layout.name, run_instruction.pane_name,
+            "pane #{} is positioned with another name than the one extracted for it",
+            index
+        );
+    }
+}